@@ -3,12 +3,14 @@ mod edit_distance;
 use gimli::{
     constants,
     write::{
-        Address, AttributeValue, DwarfUnit, EndianVec, Expression, Range, RangeList, Sections,
-        UnitEntryId,
+        Address, AttributeValue, DwarfUnit, EndianVec, Expression, LineProgram, LineString, Range,
+        RangeList, Sections, UnitEntryId,
     },
+    LineEncoding,
 };
 use object::{write, Architecture, BinaryFormat, SectionKind};
 use std::fs;
+use std::path::{Path, PathBuf};
 
 use binaryninja::logger::Logger;
 use binaryninja::{
@@ -193,6 +195,7 @@ fn export_type(
                 AttributeValue::Data1(t.width() as u8),
             );
 
+            let is_signed = t.is_signed().contents;
             for enum_field in t.get_enumeration().unwrap().members() {
                 let enum_field_die_uid = dwarf.unit.add(enum_die_uid, constants::DW_TAG_enumerator);
                 dwarf.unit.get_mut(enum_field_die_uid).set(
@@ -201,7 +204,11 @@ fn export_type(
                 );
                 dwarf.unit.get_mut(enum_field_die_uid).set(
                     gimli::DW_AT_const_value,
-                    AttributeValue::Data4(enum_field.value as u32),
+                    if is_signed {
+                        AttributeValue::Sdata(enum_field.value as i64)
+                    } else {
+                        AttributeValue::Udata(enum_field.value)
+                    },
                 );
             }
 
@@ -377,7 +384,30 @@ fn export_types(
     defined_types: &mut Vec<(Ref<Type>, UnitEntryId)>,
 ) {
     for t in &bv.types() {
-        export_type(t.name.to_string(), &t.ty, bv, defined_types, dwarf);
+        let name = t.name.to_string();
+        match t.ty.type_class() {
+            TypeClass::StructureTypeClass
+            | TypeClass::EnumerationTypeClass
+            | TypeClass::NamedTypeReferenceClass => {
+                export_type(name, &t.ty, bv, defined_types, dwarf);
+            }
+            _ => {
+                // Any other named type is an alias of the type it was defined as
+                let typedef_die_uid = dwarf.unit.add(dwarf.unit.root(), constants::DW_TAG_typedef);
+                dwarf.unit.get_mut(typedef_die_uid).set(
+                    gimli::DW_AT_name,
+                    AttributeValue::String(name.as_bytes().to_vec()),
+                );
+                if let Some(target_die_uid) =
+                    export_type(format!("{}", t.ty), &t.ty, bv, defined_types, dwarf)
+                {
+                    dwarf
+                        .unit
+                        .get_mut(typedef_die_uid)
+                        .set(gimli::DW_AT_type, AttributeValue::UnitRef(target_die_uid));
+                }
+            }
+        }
     }
 }
 
@@ -574,6 +604,83 @@ fn export_data_vars(
     }
 }
 
+/// Add a line table mapping every commented address of `bv` to a line of a listing of the
+/// comments, so debuggers show the comment of the instruction they stop at as its source line.
+///
+/// Returns the lines of the listing, to be written to `listing_path`.
+fn export_line_info(bv: &BinaryView, dwarf: &mut DwarfUnit, listing_path: &Path) -> Vec<String> {
+    let line_string = |s: &str| LineString::String(s.as_bytes().to_vec());
+    let file_name = listing_path
+        .file_name()
+        .map_or("comments".into(), |name| name.to_string_lossy());
+    let directory = match listing_path.parent().map(|dir| dir.to_string_lossy()) {
+        Some(dir) if !dir.is_empty() => dir,
+        _ => ".".into(),
+    };
+    let mut program = LineProgram::new(
+        dwarf.unit.encoding(),
+        LineEncoding::default(),
+        line_string(&directory),
+        line_string(&file_name),
+        None,
+    );
+    let file_id = program.add_file(line_string(&file_name), program.default_directory(), None);
+
+    let mut functions: Vec<_> = bv.functions().iter().map(|f| f.to_owned()).collect();
+    functions.sort_by_key(|function| function.start());
+    let mut listing = vec![];
+    for function in &functions {
+        let start = function.start();
+        let mut comments = vec![];
+        let function_comment = function.comment();
+        if !function_comment.is_empty() {
+            comments.push((start, function_comment.to_string()));
+        }
+        for comment in &function.comments() {
+            // Rows of a sequence can't go below its start
+            if comment.addr >= start {
+                comments.push((comment.addr, comment.comment.to_string()));
+            }
+        }
+        if comments.is_empty() {
+            continue;
+        }
+        comments.sort_by_key(|(addr, _)| *addr);
+
+        program.begin_sequence(Some(Address::Constant(start)));
+        for (addr, comment) in &comments {
+            listing.push(format!("{:#x}: {}", addr, comment.replace('\n', " ")));
+            let row = program.row();
+            row.address_offset = addr - start;
+            row.file = file_id;
+            row.line = listing.len() as u64;
+            program.generate_row();
+        }
+        let end = function
+            .address_ranges()
+            .iter()
+            .map(|range| range.end)
+            .max()
+            .unwrap_or(start);
+        let last_comment = comments.last().map_or(start, |(addr, _)| *addr);
+        program.end_sequence(end.max(last_comment + 1) - start);
+    }
+
+    if !listing.is_empty() {
+        let root = dwarf.unit.root();
+        dwarf.unit.get_mut(root).set(
+            gimli::DW_AT_name,
+            AttributeValue::String(file_name.as_bytes().to_vec()),
+        );
+        dwarf.unit.get_mut(root).set(
+            gimli::DW_AT_comp_dir,
+            AttributeValue::String(directory.as_bytes().to_vec()),
+        );
+        dwarf.unit.line_program = program;
+    }
+    listing
+}
+
 fn present_form(bv_arch: &str) -> Vec<FormResponses> {
     // TODO : Verify inputs (like save location) so that we can fail early
     // TODO : Add Language field
@@ -631,16 +738,8 @@ fn present_form(bv_arch: &str) -> Vec<FormResponses> {
         .get_form_input("Export as DWARF")
 }
 
-fn write_dwarf<T: gimli::Endianity>(
-    responses: Vec<FormResponses>,
-    endian: T,
-    dwarf: &mut DwarfUnit,
-) {
-    if responses.len() < 2 {
-        return;
-    }
-
-    let arch = match responses[1] {
+fn selected_architecture(response: &FormResponses) -> Architecture {
+    match response {
         Index(0) => Architecture::Unknown,
         Index(1) => Architecture::Aarch64,
         Index(2) => Architecture::Aarch64_Ilp32,
@@ -665,8 +764,15 @@ fn write_dwarf<T: gimli::Endianity>(
         Index(21) => Architecture::Wasm32,
         Index(22) => Architecture::Xtensa,
         _ => Architecture::Unknown,
-    };
+    }
+}
 
+/// Write `dwarf` into the debug sections of an object file for `arch`.
+fn write_object<T: gimli::Endianity>(
+    dwarf: &mut DwarfUnit,
+    arch: Architecture,
+    endian: T,
+) -> Result<Vec<u8>, String> {
     // let format = match responses[2] {
     //     Index(0) => BinaryFormat::Coff,
     //     Index(1) => BinaryFormat::Elf,
@@ -690,7 +796,7 @@ fn write_dwarf<T: gimli::Endianity>(
 
     // Finally, write the DWARF data to the sections.
     let mut sections = Sections::new(EndianVec::new(endian));
-    dwarf.write(&mut sections).unwrap();
+    dwarf.write(&mut sections).map_err(|e| e.to_string())?;
 
     sections
         .for_each(|input_id, input_data| {
@@ -714,54 +820,81 @@ fn write_dwarf<T: gimli::Endianity>(
         })
         .unwrap();
 
-    if let interaction::FormResponses::String(filename) = &responses[0] {
-        if let Ok(out_data) = out_object.write() {
-            if let Err(err) = fs::write(filename, out_data) {
-                error!("Failed to write DWARF file: {}", err);
-            } else {
-                info!("Successfully saved as DWARF to `{}`", filename);
-            }
-        } else {
-            error!("Failed to write DWARF with requested settings");
-        }
-    }
+    out_object.write().map_err(|e| e.to_string())
 }
 
-fn export_dwarf(bv: &BinaryView) {
-    let arch_name = if let Some(arch) = bv.default_arch() {
-        arch.name()
-    } else {
-        BnString::new("Unknown")
-    };
-    let responses = present_form(arch_name.as_str());
-
+/// The DWARF for the analysis of `bv`, with a line table into the comment listing that will be
+/// written to `listing_path`, and the lines of that listing.
+fn build_dwarf(bv: &BinaryView, listing_path: &Path) -> (DwarfUnit, Vec<String>) {
     let encoding = gimli::Encoding {
         format: gimli::Format::Dwarf32,
-        version: 4,
+        version: 5,
         address_size: bv.address_size() as u8,
     };
 
     // Create a container for a single compilation unit.
-    // TODO : Add attributes to the compilation unit DIE?
     let mut dwarf = DwarfUnit::new(encoding);
-    dwarf.unit.get_mut(dwarf.unit.root()).set(
+    let root = dwarf.unit.root();
+    dwarf.unit.get_mut(root).set(
         gimli::DW_AT_producer,
         AttributeValue::String("Binary Ninja DWARF Export Plugin".as_bytes().to_vec()),
     );
+    // Addresses in the unit are absolute
+    dwarf.unit.get_mut(root).set(
+        gimli::DW_AT_low_pc,
+        AttributeValue::Address(Address::Constant(0)),
+    );
 
     // Everything has types, so we need to track what is already defined globally as to not duplicate type entries
     let mut defined_types: Vec<(Ref<Type>, UnitEntryId)> = vec![];
     export_types(bv, &mut dwarf, &mut defined_types);
     export_functions(bv, &mut dwarf, &mut defined_types);
     export_data_vars(bv, &mut dwarf, &mut defined_types);
+    let listing = export_line_info(bv, &mut dwarf, listing_path);
     // TODO: Export all symbols instead of just data vars?
     // TODO: Sections? Segments?
 
-    if bv.default_endianness() == binaryninja::Endianness::LittleEndian {
-        write_dwarf(responses, gimli::LittleEndian, &mut dwarf);
+    (dwarf, listing)
+}
+
+fn export_dwarf(bv: &BinaryView) {
+    let arch_name = if let Some(arch) = bv.default_arch() {
+        arch.name()
     } else {
-        write_dwarf(responses, gimli::BigEndian, &mut dwarf);
+        BnString::new("Unknown")
     };
+    let responses = present_form(arch_name.as_str());
+    let [FormResponses::String(filename), arch_response, ..] = responses.as_slice() else {
+        return;
+    };
+
+    let path = PathBuf::from(filename);
+    let listing_path = path.with_extension("comments");
+    let (mut dwarf, listing) = build_dwarf(bv, &listing_path);
+    let arch = selected_architecture(arch_response);
+    let out_data = if bv.default_endianness() == binaryninja::Endianness::LittleEndian {
+        write_object(&mut dwarf, arch, gimli::LittleEndian)
+    } else {
+        write_object(&mut dwarf, arch, gimli::BigEndian)
+    };
+    let out_data = match out_data {
+        Ok(out_data) => out_data,
+        Err(err) => {
+            error!("Failed to write DWARF with requested settings: {}", err);
+            return;
+        }
+    };
+
+    if let Err(err) = fs::write(&path, out_data) {
+        error!("Failed to write DWARF file: {}", err);
+        return;
+    }
+    if !listing.is_empty() {
+        if let Err(err) = fs::write(&listing_path, listing.join("\n") + "\n") {
+            error!("Failed to write comment listing: {}", err);
+        }
+    }
+    info!("Successfully saved as DWARF to `{}`", filename);
 }
 
 struct MyCommand;
@@ -789,3 +922,108 @@ pub extern "C" fn CorePluginInit() -> bool {
 
     true
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use binaryninja::debuginfo::DebugInfoParser;
+    use binaryninja::file_metadata::FileMetadata;
+    use binaryninja::headless::Session;
+    use binaryninja::platform::Platform;
+    use binaryninja::types::{EnumerationBuilder, MemberScope, StructureBuilder};
+    use object::{Object, ObjectSection};
+    use std::num::NonZeroUsize;
+    use std::sync::OnceLock;
+
+    static INIT: OnceLock<Session> = OnceLock::new();
+
+    fn get_session<'a>() -> &'a Session {
+        INIT.get_or_init(|| Session::new().expect("Failed to initialize session"))
+    }
+
+    /// The addresses and lines of the rows of the line tables in the object file `data`.
+    fn line_rows(data: &[u8]) -> Vec<(u64, u64)> {
+        let file = object::File::parse(data).expect("Failed to parse exported object");
+        let dwarf = gimli::Dwarf::load(|id| -> gimli::Result<_> {
+            let data = file
+                .section_by_name(id.name())
+                .and_then(|section| section.data().ok())
+                .unwrap_or(&[]);
+            Ok(gimli::EndianSlice::new(data, gimli::LittleEndian))
+        })
+        .unwrap();
+        let mut rows = vec![];
+        let mut units = dwarf.units();
+        while let Some(header) = units.next().unwrap() {
+            let unit = dwarf.unit(header).unwrap();
+            let Some(program) = unit.line_program else {
+                continue;
+            };
+            let mut program_rows = program.rows();
+            while let Some((_, row)) = program_rows.next_row().unwrap() {
+                if let (false, Some(line)) = (row.end_sequence(), row.line()) {
+                    rows.push((row.address(), line.get()));
+                }
+            }
+        }
+        rows
+    }
+
+    #[test]
+    fn round_trip() {
+        let _session = get_session();
+        // push rbp; mov rbp, rsp; pop rbp; ret
+        let mut data = vec![0x55, 0x48, 0x89, 0xe5, 0x5d, 0xc3];
+        data.resize(0x100, 0);
+        let view = BinaryView::from_data(&FileMetadata::new(), &data).unwrap();
+        let platform = Platform::by_name("linux-x86_64").unwrap();
+        let function = view.add_auto_function(&platform, 0).unwrap();
+        view.update_analysis_and_wait();
+        function.set_comment("entry");
+        function.set_comment_at(1, "sets up the frame");
+
+        let mut point = StructureBuilder::new();
+        let int = Type::int(4, true);
+        point.append(&int, "x", MemberAccess::PublicAccess, MemberScope::NoScope);
+        point.append(&int, "y", MemberAccess::PublicAccess, MemberScope::NoScope);
+        let point = Type::structure(&point.finalize());
+        view.define_user_type("point", &point);
+        let color = EnumerationBuilder::new()
+            .insert("red", 0)
+            .insert("green", 1)
+            .finalize();
+        let color = Type::enumeration(&color, NonZeroUsize::new(4).unwrap(), false);
+        view.define_user_type("color", &color);
+        view.define_user_type("handle_t", &Type::int(8, false));
+        view.define_user_data_var(0x80, &point);
+
+        let listing_path = std::env::temp_dir().join("dwarf_export_round_trip.comments");
+        let (mut dwarf, listing) = build_dwarf(&view, &listing_path);
+        assert_eq!(listing, ["0x0: entry", "0x1: sets up the frame"]);
+        let out_data = write_object(&mut dwarf, Architecture::X86_64, gimli::LittleEndian)
+            .expect("Failed to write DWARF");
+        assert_eq!(line_rows(&out_data), [(0, 1), (1, 2)]);
+
+        // Import what was exported
+        let out_path = std::env::temp_dir().join("dwarf_export_round_trip.debug");
+        fs::write(&out_path, &out_data).unwrap();
+        let debug_view = binaryninja::load(&out_path).expect("Failed to load exported DWARF");
+        let parser = DebugInfoParser::from_name("DWARF").unwrap();
+        let debug_info = parser
+            .parse_debug_info(&view, &debug_view, None)
+            .expect("Failed to import exported DWARF");
+        let type_names: Vec<_> = debug_info
+            .types_by_name("DWARF")
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        for name in ["point", "color", "handle_t"] {
+            assert!(type_names.iter().any(|n| n == name), "missing type {name}");
+        }
+        let data_variables = debug_info.data_variables_by_name("DWARF");
+        assert!(data_variables.iter().any(|var| var.address == 0x80));
+        let functions = debug_info.functions_by_name("DWARF");
+        assert!(functions.iter().any(|func| func.address() == 0));
+        let _ = fs::remove_file(out_path);
+    }
+}
@@ -0,0 +1,14 @@
+int other(int x);
+
+int leaf(int a, int b)
+{
+	volatile int counter = a;
+	for (int i = 0; i < b; i++)
+		counter += i;
+	return counter;
+}
+
+int main(int argc, char** argv)
+{
+	return leaf(argc, 3) + other(argc);
+}
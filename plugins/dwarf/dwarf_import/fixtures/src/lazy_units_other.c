@@ -0,0 +1,8 @@
+__thread int calls;
+
+int other(int x)
+{
+	volatile int scratch = x * 2;
+	calls++;
+	return scratch + 1;
+}
@@ -0,0 +1,18 @@
+namespace net {
+namespace tcp {
+int connect(int port)
+{
+	return port + 1;
+}
+}
+
+int send(int n)
+{
+	return n * 2;
+}
+}
+
+int main(int argc, char** argv)
+{
+	return net::tcp::connect(argc) + net::send(argc);
+}
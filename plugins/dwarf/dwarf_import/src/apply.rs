@@ -0,0 +1,164 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// A debug info parser must not change the view it parses, so the tables the parser fills besides
+// the debug info are kept here until the debug info is applied. The module activity stores them
+// on the view when its analysis completes, lazy imports of units right after applying the units.
+// Functions are placed into the components of their source paths at the same time.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use binaryninja::{
+    binary_view::{BinaryView, BinaryViewExt},
+    frame_info::FrameInfoTable,
+    import_diagnostics::ImportDiagnostics,
+    inlined_calls::InlinedCallTable,
+    settings::{QueryOptions, Settings},
+    source_lines::SourceLineTable,
+    workflow::{Activity, AnalysisContext, Workflow},
+};
+use dwarfreader::index::UnitIndex;
+use log::error;
+
+use crate::PARSER_NAME;
+
+const ACTIVITY_NAME: &str = "analysis.plugins.dwarfImport.storeTables";
+const ACTIVITY_CONFIG: &str = r#"{
+    "name": "analysis.plugins.dwarfImport.storeTables",
+    "title": "Store DWARF Tables",
+    "description": "This analysis step stores the diagnostics, inlined calls, source lines and frame information of the last DWARF import on the view, tags the inlined calls and places the imported functions into components.",
+    "eligibility": {
+        "auto": {},
+        "runOnce": false
+    }
+}"#;
+
+/// The tables of a run of the parser.
+pub(crate) struct ParsedTables {
+    pub(crate) diagnostics: ImportDiagnostics,
+    pub(crate) inlined_calls: InlinedCallTable,
+    pub(crate) source_lines: SourceLineTable,
+    /// Not set when only some units were imported, their frame info was stored when indexing.
+    pub(crate) frame_info: Option<FrameInfoTable>,
+    /// Add to the tables of the earlier runs instead of replacing them.
+    pub(crate) merge: bool,
+    /// The units to import lazily, set when the parser only indexed them.
+    pub(crate) index: Option<UnitIndex>,
+}
+
+impl ParsedTables {
+    /// The tables of a run that failed to read any DWARF.
+    pub(crate) fn failed(diagnostics: ImportDiagnostics) -> Self {
+        Self {
+            diagnostics,
+            inlined_calls: InlinedCallTable::new(PARSER_NAME),
+            source_lines: SourceLineTable::new(PARSER_NAME),
+            frame_info: None,
+            merge: false,
+            index: None,
+        }
+    }
+}
+
+// By session id of the view parsed
+static PARSED_TABLES: Mutex<BTreeMap<usize, ParsedTables>> = Mutex::new(BTreeMap::new());
+
+/// Keep the tables of a run of the parser on `view` until its debug info is applied.
+pub(crate) fn keep(view: &BinaryView, tables: ParsedTables) {
+    PARSED_TABLES
+        .lock()
+        .unwrap()
+        .insert(view.file().session_id(), tables);
+}
+
+/// The unit index of the last run of the parser on `view`, dropping the other tables.
+pub(crate) fn take_index(view: &BinaryView) -> Option<UnitIndex> {
+    PARSED_TABLES
+        .lock()
+        .unwrap()
+        .remove(&view.file().session_id())?
+        .index
+}
+
+/// Store the tables of the last run of the parser on `view` on it and tag its inlined calls, then
+/// start the lazy import of its units if it only indexed them.
+pub(crate) fn store(view: &BinaryView) {
+    let Some(tables) = PARSED_TABLES
+        .lock()
+        .unwrap()
+        .remove(&view.file().session_id())
+    else {
+        return;
+    };
+
+    if let Some(frame_info) = &tables.frame_info {
+        frame_info.store(view);
+    }
+    // Functions only get the components of their source paths once they exist in the view
+    if Settings::new().get_bool_with_opts(
+        "analysis.debugInfo.componentsFromSourcePaths",
+        &mut QueryOptions::new_with_view(view),
+    ) {
+        view.debug_info().apply_components(view, PARSER_NAME);
+    }
+    if !tables.merge {
+        tables.diagnostics.store(view);
+        tables.inlined_calls.store(view);
+        tables.source_lines.store(view);
+        tables.inlined_calls.annotate(view);
+        if let Some(index) = tables.index {
+            crate::lazy::start(view, index);
+        }
+        return;
+    }
+
+    let mut diagnostics = ImportDiagnostics::for_importer(view, PARSER_NAME)
+        .unwrap_or_else(|| ImportDiagnostics::new(PARSER_NAME));
+    diagnostics.merge(&tables.diagnostics);
+    diagnostics.store(view);
+
+    let mut inlined_calls = InlinedCallTable::for_importer(view, PARSER_NAME)
+        .unwrap_or_else(|| InlinedCallTable::new(PARSER_NAME));
+    for call in tables.inlined_calls.calls() {
+        inlined_calls.add(call.clone());
+    }
+    inlined_calls.store(view);
+
+    let mut source_lines = SourceLineTable::for_importer(view, PARSER_NAME)
+        .unwrap_or_else(|| SourceLineTable::new(PARSER_NAME));
+    for (range, location) in tables.source_lines.iter() {
+        source_lines.add(range, &location.file, location.line, location.column);
+    }
+    source_lines.store(view);
+
+    tables.inlined_calls.annotate(view);
+}
+
+/// Store the tables of each import once the analysis it starts completes.
+pub(crate) fn register_activity() -> bool {
+    let workflow = Workflow::instance("core.module.metaAnalysis").clone("core.module.metaAnalysis");
+    let activity =
+        Activity::new_with_action(ACTIVITY_CONFIG, |ctx: &AnalysisContext| store(&ctx.view()));
+    if workflow.register_activity(&activity).is_err() {
+        error!("Failed to register the DWARF table activity");
+        return false;
+    }
+    workflow.insert("core.module.notifyCompletion", [ACTIVITY_NAME]);
+    if workflow.register().is_err() {
+        error!("Failed to register the DWARF table activity");
+        return false;
+    }
+    true
+}
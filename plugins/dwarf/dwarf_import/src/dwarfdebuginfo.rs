@@ -13,27 +13,35 @@
 // limitations under the License.
 
 use crate::{
-    helpers::{get_uid, resolve_specification, DieReference},
+    helpers::{dwarf_register_name, get_uid, resolve_specification, DieReference},
+    merge::{structurally_equal, ExistingTypes},
     ReaderType,
 };
 
 use binaryninja::{
+    architecture::{ArchitectureExt, CoreArchitecture, Register as _},
     binary_view::{BinaryView, BinaryViewBase, BinaryViewExt},
     debuginfo::{DebugFunctionInfo, DebugInfo},
+    frame_info::{Cfa, FrameInfoTable, FrameRow},
+    import_diagnostics::ImportDiagnostics,
+    inlined_calls::{InlinedCall, InlinedCallTable},
     platform::Platform,
     rc::*,
+    source_lines::SourceLineTable,
     symbol::SymbolType,
     template_simplifier::simplify_str_to_fqn,
     types::{FunctionParameter, Type},
     variable::NamedVariableWithType,
 };
 
-use gimli::{DebuggingInformationEntry, Dwarf, Unit};
+use dwarfreader::location::{LocationRange, VariableLocation};
+use dwarfreader::supplementary::DieResolver;
+use gimli::{DebuggingInformationEntry, Dwarf, Register, Unit};
 
 use binaryninja::confidence::Conf;
 use binaryninja::variable::{Variable, VariableSourceType};
 use indexmap::{map::Values, IndexMap};
-use log::{debug, error, warn};
+use log::{debug, error};
 use std::{cmp::Ordering, collections::HashMap, hash::Hash};
 
 pub(crate) type TypeUID = usize;
@@ -41,7 +49,6 @@ pub(crate) type TypeUID = usize;
 /////////////////////////
 // FunctionInfoBuilder
 
-// TODO : Function local variables
 #[derive(PartialEq, Eq, Hash)]
 pub(crate) struct FunctionInfoBuilder {
     pub(crate) full_name: Option<String>,
@@ -51,8 +58,9 @@ pub(crate) struct FunctionInfoBuilder {
     pub(crate) parameters: Vec<Option<(String, TypeUID)>>,
     pub(crate) platform: Option<Ref<Platform>>,
     pub(crate) variable_arguments: bool,
-    pub(crate) stack_variables: Vec<NamedVariableWithType>,
-    pub(crate) use_cfa: bool, //TODO actually store more info about the frame base
+    pub(crate) local_variables: Vec<NamedVariableWithType>,
+    pub(crate) frame_base: Option<VariableLocation>,
+    pub(crate) components: Vec<String>,
 }
 
 impl FunctionInfoBuilder {
@@ -111,9 +119,9 @@ impl DebugType {
 }
 
 pub(crate) struct DebugInfoBuilderContext<R: ReaderType> {
-    units: Vec<Unit<R>>,
-    sup_units: Vec<Unit<R>>,
+    resolver: DieResolver<R>,
     names: HashMap<TypeUID, String>,
+    namespaces: HashMap<TypeUID, Vec<String>>,
     default_address_size: usize,
     pub(crate) total_die_count: usize,
     pub(crate) total_unit_size_bytes: usize,
@@ -121,46 +129,34 @@ pub(crate) struct DebugInfoBuilderContext<R: ReaderType> {
 
 impl<R: ReaderType> DebugInfoBuilderContext<R> {
     pub(crate) fn new(view: &BinaryView, dwarf: &Dwarf<R>) -> Option<Self> {
-        let mut units = vec![];
-        let mut iter = dwarf.units();
-        while let Ok(Some(header)) = iter.next() {
-            if let Ok(unit) = dwarf.unit(header) {
-                units.push(unit);
-            } else {
-                error!("Unable to read DWARF information. File may be malformed or corrupted. Not applying debug info.");
+        let resolver = match DieResolver::new(dwarf) {
+            Ok(resolver) => resolver,
+            Err(e) => {
+                error!("Unable to read DWARF information. File may be malformed or corrupted. Not applying debug info: {}", e);
                 return None;
             }
-        }
-
-        let mut sup_units = vec![];
-        if let Some(sup_dwarf) = dwarf.sup() {
-            let mut sup_iter = sup_dwarf.units();
-            while let Ok(Some(header)) = sup_iter.next() {
-                if let Ok(unit) = sup_dwarf.unit(header) {
-                    sup_units.push(unit);
-                } else {
-                    error!("Unable to read supplementary DWARF information. File may be malformed or corrupted. Not applying debug info.");
-                    return None;
-                }
-            }
-        }
+        };
 
         Some(Self {
-            units,
-            sup_units,
+            resolver,
             names: HashMap::new(),
+            namespaces: HashMap::new(),
             default_address_size: view.address_size(),
             total_die_count: 0,
             total_unit_size_bytes: 0,
         })
     }
 
+    pub(crate) fn resolver(&self) -> &DieResolver<R> {
+        &self.resolver
+    }
+
     pub(crate) fn units(&self) -> &[Unit<R>] {
-        &self.units
+        self.resolver.units()
     }
 
     pub(crate) fn sup_units(&self) -> &[Unit<R>] {
-        &self.sup_units
+        self.resolver.sup_units()
     }
 
     pub(crate) fn default_address_size(&self) -> usize {
@@ -190,6 +186,33 @@ impl<R: ReaderType> DebugInfoBuilderContext<R> {
             DieReference::Err => None,
         }
     }
+
+    /// Record the namespaces and modules enclosing the function declared by `die_uid`, outermost
+    /// first.
+    pub(crate) fn set_namespaces(&mut self, die_uid: TypeUID, namespaces: Vec<String>) {
+        self.namespaces.insert(die_uid, namespaces);
+    }
+
+    /// The namespaces and modules enclosing the declaration of the function `entry`, following
+    /// its specification, as out-of-line definitions are not nested in their namespace.
+    pub(crate) fn get_namespaces(
+        &self,
+        dwarf: &Dwarf<R>,
+        unit: &Unit<R>,
+        entry: &DebuggingInformationEntry<R>,
+    ) -> &[String] {
+        match resolve_specification(dwarf, unit, entry, self) {
+            DieReference::UnitAndOffset((dwarf, entry_unit, entry_offset)) => self
+                .namespaces
+                .get(&get_uid(
+                    dwarf,
+                    entry_unit,
+                    &entry_unit.entry(entry_offset).unwrap(),
+                ))
+                .map_or(&[], |namespaces| namespaces.as_slice()),
+            DieReference::Err => &[],
+        }
+    }
 }
 
 // DWARF info is stored and displayed in a tree, but is really a graph
@@ -201,7 +224,13 @@ pub(crate) struct DebugInfoBuilder {
     full_function_name_indices: HashMap<String, usize>,
     types: IndexMap<TypeUID, DebugType>,
     data_variables: HashMap<u64, (Option<String>, TypeUID)>,
-    range_data_offsets: iset::IntervalMap<u64, i64>,
+    arch: Option<CoreArchitecture>,
+    components_from_source_paths: bool,
+    components_from_namespaces: bool,
+    diagnostics: ImportDiagnostics,
+    inlined_calls: InlinedCallTable,
+    source_lines: SourceLineTable,
+    frame_info: FrameInfoTable,
 }
 
 impl DebugInfoBuilder {
@@ -212,12 +241,73 @@ impl DebugInfoBuilder {
             full_function_name_indices: HashMap::new(),
             types: IndexMap::new(),
             data_variables: HashMap::new(),
-            range_data_offsets: iset::IntervalMap::new(),
+            arch: None,
+            components_from_source_paths: false,
+            components_from_namespaces: false,
+            diagnostics: ImportDiagnostics::new(crate::PARSER_NAME),
+            inlined_calls: InlinedCallTable::new(crate::PARSER_NAME),
+            source_lines: SourceLineTable::new(crate::PARSER_NAME),
+            frame_info: FrameInfoTable::new(crate::PARSER_NAME),
         }
     }
 
-    pub(crate) fn set_range_data_offsets(&mut self, offsets: iset::IntervalMap<u64, i64>) {
-        self.range_data_offsets = offsets
+    pub(crate) fn diagnostics(&self) -> &ImportDiagnostics {
+        &self.diagnostics
+    }
+
+    pub(crate) fn inlined_calls(&self) -> &InlinedCallTable {
+        &self.inlined_calls
+    }
+
+    pub(crate) fn add_inlined_call(&mut self, call: InlinedCall) {
+        self.inlined_calls.add(call);
+    }
+
+    pub(crate) fn source_lines(&self) -> &SourceLineTable {
+        &self.source_lines
+    }
+
+    pub(crate) fn source_lines_mut(&mut self) -> &mut SourceLineTable {
+        &mut self.source_lines
+    }
+
+    pub(crate) fn frame_info(&self) -> &FrameInfoTable {
+        &self.frame_info
+    }
+
+    pub(crate) fn set_frame_info(&mut self, frame_info: FrameInfoTable) {
+        self.frame_info = frame_info
+    }
+
+    /// Set the architecture the registers of variable locations are resolved with.
+    pub(crate) fn set_arch(&mut self, arch: Option<CoreArchitecture>) {
+        self.arch = arch
+    }
+
+    pub(crate) fn components_from_source_paths(&self) -> bool {
+        self.components_from_source_paths
+    }
+
+    pub(crate) fn set_components_from_source_paths(&mut self, enabled: bool) {
+        self.components_from_source_paths = enabled
+    }
+
+    pub(crate) fn components_from_namespaces(&self) -> bool {
+        self.components_from_namespaces
+    }
+
+    pub(crate) fn set_components_from_namespaces(&mut self, enabled: bool) {
+        self.components_from_namespaces = enabled
+    }
+
+    /// Place the function at `idx` into the component path `components`, keeping the path of the
+    /// unit that first defined it.
+    pub(crate) fn set_function_components(&mut self, idx: usize, components: &[String]) {
+        if let Some(function) = self.functions.get_mut(idx) {
+            if function.components.is_empty() {
+                function.components = components.to_vec();
+            }
+        }
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -229,7 +319,7 @@ impl DebugInfoBuilder {
         address: Option<u64>,
         parameters: &Vec<Option<(String, TypeUID)>>,
         variable_arguments: bool,
-        use_cfa: bool,
+        frame_base: Option<VariableLocation>,
     ) -> Option<usize> {
         // Returns the index of the function
         // Raw names should be the primary key, but if they don't exist, use the full name
@@ -298,8 +388,9 @@ impl DebugInfoBuilder {
             parameters: parameters.clone(),
             platform: None,
             variable_arguments,
-            stack_variables: vec![],
-            use_cfa,
+            local_variables: vec![],
+            frame_base,
+            components: vec![],
         };
 
         if let Some(n) = &function.full_name {
@@ -316,10 +407,6 @@ impl DebugInfoBuilder {
         Some(self.functions.len() - 1)
     }
 
-    pub(crate) fn functions(&self) -> &[FunctionInfoBuilder] {
-        &self.functions
-    }
-
     #[allow(dead_code)]
     pub(crate) fn types(&self) -> Values<'_, TypeUID, DebugType> {
         self.types.values()
@@ -339,12 +426,12 @@ impl DebugInfoBuilder {
             },
         ) {
             if existing_type != t && commit {
-                warn!("DWARF info contains duplicate type definition. Overwriting type `{}` (named `{:?}`) with `{}` (named `{:?}`)",
+                self.diagnostics.warn(format!("DWARF info contains duplicate type definition. Overwriting type `{}` (named `{:?}`) with `{}` (named `{:?}`)",
                     existing_type,
                     existing_name,
                     t,
                     name
-                );
+                ));
             }
         }
     }
@@ -361,29 +448,17 @@ impl DebugInfoBuilder {
         self.types.contains_key(&type_uid)
     }
 
-    pub(crate) fn add_stack_variable(
+    /// Add a local variable to the function at `fn_idx`, stored at each of `locations` over the
+    /// ranges they hold for. Locations without a range hold for `lexical_block`, or for the whole
+    /// function if the variable isn't in one.
+    pub(crate) fn add_local_variable(
         &mut self,
         fn_idx: Option<usize>,
-        offset: i64,
+        locations: &[LocationRange],
         name: Option<String>,
         type_uid: Option<TypeUID>,
         lexical_block: Option<&iset::IntervalSet<u64>>,
     ) {
-        let name = match name {
-            Some(x) => {
-                if x.len() == 1 && x.chars().next() == Some('\x00') {
-                    // Anonymous variable, generate name
-                    format!("debug_var_{}", offset)
-                } else {
-                    x
-                }
-            }
-            None => {
-                // Anonymous variable, generate name
-                format!("debug_var_{}", offset)
-            }
-        };
-
         let Some(function_index) = fn_idx else {
             // If we somehow lost track of what subprogram we're in or we're not actually in a subprogram
             error!(
@@ -397,69 +472,121 @@ impl DebugInfoBuilder {
             Some(uid) => Conf::new(self.get_type(uid).unwrap().ty.clone(), 128),
             None => Conf::new(Type::void(), 0),
         };
-        let function = &mut self.functions[function_index];
-
-        // TODO: If we can't find a known offset can we try to guess somehow?
+        let function = &self.functions[function_index];
+        let frame_base = function.frame_base;
 
         let Some(func_addr) = function.address else {
             // If we somehow are processing a function's variables before the function is created
             error!("Trying to add a local variable without a known function start. Please report this issue.");
             return;
         };
-
-        let Some(adjustment_at_variable_lifetime_start) = lexical_block
-            .and_then(|block_ranges| {
-                block_ranges
-                    .unsorted_iter()
-                    .find_map(|x| self.range_data_offsets.values_overlap(x.start).next())
-            })
-            .or_else(|| {
-                // Try using the offset at the adjustment 4 bytes after the function start, in case the function starts with a stack adjustment
-                // TODO: This is a decent heuristic but not perfect, since further adjustments could still be made
-                self.range_data_offsets.values_overlap(func_addr + 4).next()
-            })
-            .or_else(|| {
-                // If all else fails, use the function start address
-                self.range_data_offsets.values_overlap(func_addr).next()
-            })
-        else {
-            // Unknown why, but this is happening with MachO + external dSYM
-            debug!("Refusing to add a local variable ({}@{}) to function at {} without a known CIE offset.", name, offset, func_addr);
-            return;
-        };
-
-        // TODO: handle non-sp frame bases
-        // TODO: if not in a lexical block these can be wrong, see https://github.com/Vector35/binaryninja-api/issues/5882#issuecomment-2406065057
-        let adjusted_offset = if function.use_cfa {
-            // Apply CFA offset to variable storage offset if DW_AT_frame_base is frame base is CFA
-            offset + adjustment_at_variable_lifetime_start
-        } else {
-            // If it's using SP, we know the SP offset is <SP offset> + (<entry SP CFA offset> - <SP CFA offset>)
-            let Some(adjustment_at_entry) =
-                self.range_data_offsets.values_overlap(func_addr).next()
-            else {
-                // Unknown why, but this is happening with MachO + external dSYM
-                debug!("Refusing to add a local variable ({}@{}) to function at {} without a known CIE offset for function start.", name, offset, func_addr);
-                return;
+        let scope_start = lexical_block
+            .and_then(|block_ranges| block_ranges.unsorted_iter().map(|x| x.start).min())
+            .unwrap_or(func_addr);
+
+        // Anonymous variables are named after where they are stored
+        let name = name.filter(|x| !(x.len() == 1 && x.starts_with('\x00')));
+        let mut variables = vec![];
+        for location in locations {
+            let live = location.range.is_some();
+            let address = location.range.as_ref().map_or(scope_start, |x| x.start);
+            let resolved = match frame_base {
+                Some(frame_base) => location.location.with_frame_base(frame_base),
+                None => Some(location.location),
+            };
+            let var = match resolved {
+                Some(VariableLocation::Register(register)) => self.register_variable(register),
+                Some(VariableLocation::RegisterOffset { register, offset }) => self
+                    .cfa_relative_offset(address, live, register, offset)
+                    .and_then(|offset| self.stack_variable(func_addr, offset)),
+                Some(VariableLocation::CfaOffset(offset)) => self.stack_variable(func_addr, offset),
+                _ => None,
+            };
+            let Some(var) = var else {
+                debug!(
+                    "Unable to map location {:?} of local variable {:?} in function at {:#x} to a variable",
+                    location.location, name, func_addr
+                );
+                continue;
             };
 
-            offset + (adjustment_at_entry - adjustment_at_variable_lifetime_start)
-        };
+            if var.ty == VariableSourceType::StackVariableSourceType && var.storage > 0 {
+                // If we somehow end up with a positive sp offset
+                error!("Trying to add a local variable {:?} in function at {:#x} at positive storage offset {}. Please report this issue.", name, func_addr, var.storage);
+                self.diagnostics.skip(
+                    "local variable",
+                    Some(func_addr),
+                    format!(
+                        "`{}` has a positive storage offset {}",
+                        name.as_deref().unwrap_or("<anonymous>"),
+                        var.storage
+                    ),
+                );
+                continue;
+            }
+            if !variables.contains(&var) {
+                variables.push(var);
+            }
+        }
 
-        if adjusted_offset > 0 {
-            // If we somehow end up with a positive sp offset
-            error!("Trying to add a local variable \"{}\" in function at {:#x} at positive storage offset {}. Please report this issue.", name, func_addr, adjusted_offset);
-            return;
+        let function = &mut self.functions[function_index];
+        for var in variables {
+            if function.local_variables.iter().any(|x| x.variable == var) {
+                continue;
+            }
+            let name = name
+                .clone()
+                .unwrap_or_else(|| format!("debug_var_{}", var.storage));
+            function
+                .local_variables
+                .push(NamedVariableWithType::new(var, ty.clone(), name, false));
         }
+    }
 
-        let var = Variable::new(
+    /// The variable for DWARF register `register`.
+    fn register_variable(&self, register: Register) -> Option<Variable> {
+        let arch = self.arch.as_ref()?;
+        let name = dwarf_register_name(Some(arch), register);
+        let register = arch.register_by_name(name.as_str())?;
+        Some(Variable::new(
+            VariableSourceType::RegisterVariableSourceType,
+            0,
+            register.id().0 as i64,
+        ))
+    }
+
+    /// The stack variable at `offset` from the canonical frame address of the function at
+    /// `func_addr`.
+    fn stack_variable(&self, func_addr: u64, offset: i64) -> Option<Variable> {
+        // Stack offsets count from the stack pointer at the entry of the function, which the CFA
+        // is defined relative to there
+        let Cfa::RegisterOffset {
+            offset: entry_offset,
+            ..
+        } = &self.frame_info.row_at(func_addr)?.cfa
+        else {
+            return None;
+        };
+        Some(Variable::new(
             VariableSourceType::StackVariableSourceType,
             0,
-            adjusted_offset,
-        );
-        function
-            .stack_variables
-            .push(NamedVariableWithType::new(var, ty, name, false));
+            offset.wrapping_add(*entry_offset),
+        ))
+    }
+
+    /// The offset from the canonical frame address of the value of DWARF register `register` plus
+    /// `offset`, at `addr` if the location is `live` only from there, else for the whole scope
+    /// starting at `addr`.
+    fn cfa_relative_offset(
+        &self,
+        addr: u64,
+        live: bool,
+        register: Register,
+        offset: i64,
+    ) -> Option<i64> {
+        let name = dwarf_register_name(self.arch.as_ref(), register);
+        let cfa_offset = register_cfa_offset(self.frame_info.rows(), addr, live, &name)?;
+        Some(offset.wrapping_sub(cfa_offset))
     }
 
     pub(crate) fn add_data_variable(
@@ -475,17 +602,32 @@ impl DebugInfoBuilder {
             let new_type = self.get_type(type_uid).unwrap().ty.as_ref();
 
             if existing_type_uid != type_uid || existing_type != new_type {
-                warn!("DWARF info contains duplicate data variable definition. Overwriting data variable at 0x{:08x} (`{}`) with `{}`",
+                let message = format!("DWARF info contains duplicate data variable definition. Overwriting data variable at 0x{:08x} (`{}`) with `{}`",
                     address,
                     existing_type,
                     new_type
                 );
+                self.diagnostics.warn(message);
             }
         }
     }
 
-    fn commit_types(&self, debug_info: &mut DebugInfo) {
+    /// Views have no thread-local storage to put the variable at, so it is only reported.
+    pub(crate) fn skip_thread_local_variable(&mut self, name: Option<&str>, offset: u64) {
+        self.diagnostics.skip(
+            "thread-local variable",
+            None,
+            format!(
+                "`{}` at TLS offset {:#x} has no address in the view",
+                name.unwrap_or("<anonymous>"),
+                offset
+            ),
+        );
+    }
+
+    fn commit_types(&mut self, debug_info: &mut DebugInfo) {
         let mut type_uids_by_name: HashMap<String, TypeUID> = HashMap::new();
+        let mut committed = 0;
 
         for (debug_type_uid, debug_type) in self.types.iter() {
             if !debug_type.commit {
@@ -502,7 +644,7 @@ impl DebugInfoBuilder {
                 };
 
                 let mut skip_adding_type = false;
-                if stored_debug_type.ty != debug_type.ty {
+                if !structurally_equal(&stored_debug_type.ty, &debug_type.ty) {
                     // We already stored a type with this name and it's a different type, deconflict the name and try again
                     let mut i = 1;
                     loop {
@@ -513,7 +655,7 @@ impl DebugInfoBuilder {
                                 break;
                             }
                             if let Some(stored_debug_type) = self.types.get(stored_uid) {
-                                if stored_debug_type.ty == debug_type.ty {
+                                if structurally_equal(&stored_debug_type.ty, &debug_type.ty) {
                                     // We already have a type with this name but it's the same type so we're ok
                                     skip_adding_type = true;
                                     break;
@@ -535,13 +677,19 @@ impl DebugInfoBuilder {
             };
 
             // TODO : Components
-            debug_info.add_type(&debug_type_name, &debug_type.ty, &[]);
+            if debug_info.add_type(&debug_type_name, &debug_type.ty, &[]) {
+                committed += 1;
+            } else {
+                self.diagnostics
+                    .skip("type", None, format!("unable to add `{}`", debug_type_name));
+            }
             type_uids_by_name.insert(debug_type_name, *debug_type_uid);
         }
+        self.diagnostics.add_count("types", committed);
     }
 
     // TODO : Consume data?
-    fn commit_data_variables(&self, debug_info: &mut DebugInfo) {
+    fn commit_data_variables(&mut self, debug_info: &mut DebugInfo) {
         for (&address, (name, type_uid)) in &self.data_variables {
             assert!(debug_info.add_data_variable(
                 address,
@@ -550,6 +698,8 @@ impl DebugInfoBuilder {
                 &[] // TODO : Components
             ));
         }
+        self.diagnostics
+            .add_count("data variables", self.data_variables.len() as u64);
     }
 
     fn get_function_type(&self, function: &FunctionInfoBuilder) -> Ref<Type> {
@@ -577,29 +727,47 @@ impl DebugInfoBuilder {
         Type::function(&return_type, parameters, function.variable_arguments)
     }
 
-    fn commit_functions(&self, debug_info: &mut DebugInfo) {
-        for function in self.functions() {
+    fn commit_functions(&mut self, debug_info: &mut DebugInfo) {
+        let mut committed = 0;
+        for function in &self.functions {
             // let calling_convention: Option<Ref<CallingConvention<CoreArchitecture>>> = None;
 
-            debug_info.add_function(DebugFunctionInfo::new(
+            let added = debug_info.add_function(DebugFunctionInfo::new(
                 function.full_name.clone(),
                 function.full_name.clone(), // TODO : This should eventually be changed, but the "full_name" should probably be the unsimplified version, and the "short_name" should be the simplified version...currently the symbols view shows the full version, so changing it here too makes it look bad in the UI
                 function.raw_name.clone(),
                 Some(self.get_function_type(function)),
                 function.address,
                 function.platform.clone(),
-                vec![],                           // TODO : Components
-                function.stack_variables.clone(), // TODO: local non-stack variables
+                function.components.clone(),
+                function.local_variables.clone(),
             ));
+            if added {
+                committed += 1;
+            } else {
+                let name = function.full_name.as_deref().unwrap_or("<anonymous>");
+                self.diagnostics.skip(
+                    "function",
+                    function.address,
+                    format!("unable to add `{}`", name),
+                );
+            }
         }
+        self.diagnostics.add_count("functions", committed);
     }
 
-    pub(crate) fn post_process(&mut self, bv: &BinaryView, _debug_info: &mut DebugInfo) -> &Self {
+    pub(crate) fn post_process(
+        &mut self,
+        bv: &BinaryView,
+        debug_info: &mut DebugInfo,
+    ) -> &mut Self {
         //   When originally resolving names, we need to check:
         //     If there's already a name from binja that's "more correct" than what we found (has more namespaces)
         //     If there's no name for the DIE, but there's a linkage name that's resolved in binja to a usable name
         // This is no longer true, because DWARF doesn't provide platform information for functions, so we at least need to post-process thumb functions
 
+        // Wraps around when the view is loaded below the base the DWARF assumes
+        let diff = bv.start().wrapping_sub(bv.original_image_base());
         for func in &mut self.functions {
             // If the function's raw name already exists in the binary...
             if let Some(raw_name) = &func.raw_name {
@@ -629,29 +797,101 @@ impl DebugInfoBuilder {
             }
 
             if let Some(address) = func.address.as_mut() {
-                let (diff, overflowed) = bv.start().overflowing_sub(bv.original_image_base());
-                if !overflowed {
-                    *address = (*address).overflowing_add(diff).0; // rebase the address
-                    let existing_functions = bv.functions_at(*address);
-                    match existing_functions.len().cmp(&1) {
-                        Ordering::Greater => {
-                            warn!("Multiple existing functions at address {address:08x}. One or more functions at this address may have the wrong platform information. Please report this binary.");
-                        }
-                        Ordering::Equal => {
-                            func.platform = Some(existing_functions.get(0).platform())
-                        }
-                        Ordering::Less => {}
+                *address = (*address).wrapping_add(diff); // rebase the address
+                let existing_functions = bv.functions_at(*address);
+                match existing_functions.len().cmp(&1) {
+                    Ordering::Greater => {
+                        self.diagnostics.warn(format!("Multiple existing functions at address {address:08x}. One or more functions at this address may have the wrong platform information. Please report this binary."));
                     }
+                    Ordering::Equal => func.platform = Some(existing_functions.get(0).platform()),
+                    Ordering::Less => {}
                 }
             }
         }
 
+        self.inlined_calls.rebase(diff);
+        self.source_lines.rebase(diff);
+        self.frame_info.rebase(diff);
+        self.diagnostics
+            .add_count("inlined calls", self.inlined_calls.calls().len() as u64);
+        self.diagnostics
+            .add_count("source line ranges", self.source_lines.len() as u64);
+        self.diagnostics
+            .add_count("frame rows", self.frame_info.len() as u64);
+
+        self.merge_existing_types(bv, debug_info);
         self
     }
 
-    pub(crate) fn commit_info(&self, debug_info: &mut DebugInfo) {
+    /// Stop types the view already has from being committed, so they aren't added a second time
+    /// under a deconflicted name. Other DWARF types refer to them by name and use the existing ones.
+    fn merge_existing_types(&mut self, bv: &BinaryView, debug_info: &DebugInfo) {
+        let existing = ExistingTypes::new(bv, debug_info);
+        let mut merged = 0;
+        for debug_type in self.types.values_mut() {
+            if debug_type.commit && existing.contains_equivalent(&debug_type.name, &debug_type.ty) {
+                debug_type.commit = false;
+                merged += 1;
+            }
+        }
+        self.diagnostics.add_count("merged types", merged);
+    }
+
+    pub(crate) fn commit_info(&mut self, debug_info: &mut DebugInfo) {
         self.commit_types(debug_info);
         self.commit_data_variables(debug_info);
         self.commit_functions(debug_info);
     }
 }
+
+/// The offset of the canonical frame address from register `register` in the frame `rows`, at
+/// `addr` if `live`, else for the scope starting at `addr`.
+pub(crate) fn register_cfa_offset(
+    rows: &[FrameRow],
+    addr: u64,
+    live: bool,
+    register: &str,
+) -> Option<i64> {
+    let first = rows.partition_point(|row| row.range.end <= addr);
+    let start_row = rows.get(first).filter(|row| row.range.contains(&addr))?;
+    let Cfa::RegisterOffset {
+        register: start_register,
+        ..
+    } = &start_row.cfa
+    else {
+        return None;
+    };
+
+    // A frame pointer only holds the CFA after the prologue sets it up, which can be after the
+    // start of the scope of a variable, and the stack pointer moves down until the prologue is
+    // done. The rows of a function are contiguous, follow them until the CFA is defined by
+    // another register or, for the scope of a location without a range, the stack pointer moves
+    // up again.
+    let mut end = addr;
+    let mut found = None;
+    for row in &rows[first..] {
+        if row.range.start > end {
+            break;
+        }
+        end = row.range.end;
+        let Cfa::RegisterOffset {
+            register: cfa_register,
+            offset: cfa_offset,
+        } = &row.cfa
+        else {
+            break;
+        };
+        if cfa_register == register {
+            if found.is_some_and(|found| *cfa_offset < found) {
+                break;
+            }
+            found = Some(*cfa_offset);
+            if live {
+                break;
+            }
+        } else if found.is_some() || cfa_register != start_register {
+            break;
+        }
+    }
+    found
+}
@@ -20,7 +20,8 @@ use crate::{helpers::*, ReaderType};
 
 use binaryninja::template_simplifier::simplify_str_to_str;
 use cpp_demangle::DemangleOptions;
-use gimli::{constants, AttributeValue, DebuggingInformationEntry, Dwarf, Operation, Unit};
+use dwarfreader::location::evaluate_location;
+use gimli::{constants, AttributeValue, DebuggingInformationEntry, Dwarf, Unit};
 use log::{debug, error};
 use regex::Regex;
 
@@ -136,18 +137,16 @@ pub(crate) fn parse_function_entry<R: ReaderType>(
         return None;
     }
 
-    let use_cfa;
-    if let Ok(Some(AttributeValue::Exprloc(mut expression))) =
-        entry.attr_value(constants::DW_AT_frame_base)
-    {
-        use_cfa = match Operation::parse(&mut expression.0, unit.encoding()) {
-            Ok(Operation::Register { register: _ }) => false, // TODO: handle register-relative encodings later
-            Ok(Operation::CallFrameCFA) => true,
-            _ => false,
-        };
-    } else {
-        use_cfa = false;
-    }
+    // Frame bases given as a location list, which move between registers, aren't supported
+    let frame_base = match entry.attr_value(constants::DW_AT_frame_base) {
+        Ok(Some(AttributeValue::Exprloc(expression))) => {
+            evaluate_location(expression, unit.encoding()).unwrap_or_else(|e| {
+                debug!("Failed to evaluate frame base of {:?}: {}", full_name, e);
+                None
+            })
+        }
+        _ => None,
+    };
 
     debug_info_builder.insert_function(
         full_name,
@@ -156,7 +155,7 @@ pub(crate) fn parse_function_entry<R: ReaderType>(
         address,
         &parameters,
         variable_arguments,
-        use_cfa,
+        frame_base,
     )
 }
 
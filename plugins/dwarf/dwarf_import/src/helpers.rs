@@ -12,28 +12,29 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
 use std::ffi::OsStr;
 use std::path::{Path, PathBuf};
-use std::{collections::HashMap, ops::Deref, str::FromStr, sync::mpsc};
+use std::sync::Mutex;
 
 use crate::{DebugInfoBuilderContext, ReaderType};
-use binaryninja::binary_view::BinaryViewBase;
-use binaryninja::file_metadata::FileMetadata;
-use binaryninja::Endianness;
+use binaryninja::progress::ProgressScope;
 use binaryninja::{
+    architecture::{ArchitectureExt, CoreArchitecture, Register as _},
     binary_view::{BinaryView, BinaryViewExt},
-    download_provider::{DownloadInstanceInputOutputCallbacks, DownloadProvider},
     rc::Ref,
     settings::Settings,
 };
-use gimli::Dwarf;
 use gimli::{
-    constants, Attribute, AttributeValue,
-    AttributeValue::{DebugInfoRef, DebugInfoRefSup, UnitRef},
-    DebuggingInformationEntry, Operation, Unit, UnitOffset, UnitSectionOffset,
+    constants, Attribute, AttributeValue, AttributeValue::DebugInfoRefSup,
+    DebuggingInformationEntry, Dwarf, DwarfFileType, EvaluationResult, Expression, Location,
+    Operation, Piece, Register, Unit, UnitOffset, UnitSectionOffset,
 };
 
+use binaryninja::debuginfo::find_external_debug_file;
+use binaryninja::debuginfo::locator::{self, DebugFileLocator};
 use binaryninja::settings::QueryOptions;
+use dwarfreader::debuginfod::DebuginfodClient;
 use log::warn;
 
 pub(crate) fn get_uid<R: ReaderType>(
@@ -41,8 +42,11 @@ pub(crate) fn get_uid<R: ReaderType>(
     unit: &Unit<R>,
     entry: &DebuggingInformationEntry<R>,
 ) -> usize {
-    // We set a large gap between supplementary and main entries
-    let adj = dwarf.sup().map_or(0, |_| 0x1000000000000000);
+    // We set a large gap between supplementary, main and split entries
+    let adj = match dwarf.file_type {
+        DwarfFileType::Dwo => 0x2000000000000000,
+        DwarfFileType::Main => dwarf.sup().map_or(0, |_| 0x1000000000000000),
+    };
     let entry_offset = match entry.offset().to_unit_section_offset(unit) {
         UnitSectionOffset::DebugInfoOffset(o) => o.0,
         UnitSectionOffset::DebugTypesOffset(o) => o.0,
@@ -65,62 +69,21 @@ pub(crate) fn get_attr_die<'a, R: ReaderType>(
     debug_info_builder_context: &'a DebugInfoBuilderContext<R>,
     attr: constants::DwAt,
 ) -> Option<DieReference<'a, R>> {
-    match entry.attr_value(attr) {
-        Ok(Some(UnitRef(offset))) => Some(DieReference::UnitAndOffset((dwarf, unit, offset))),
-        Ok(Some(DebugInfoRef(offset))) => {
-            if dwarf.sup().is_some() {
-                for source_unit in debug_info_builder_context.units() {
-                    if let Some(new_offset) = offset.to_unit_offset(&source_unit.header) {
-                        return Some(DieReference::UnitAndOffset((
-                            dwarf,
-                            source_unit,
-                            new_offset,
-                        )));
-                    }
-                }
-            } else {
-                // This could either have no supplementary file because it is one or because it just doesn't have one
-                // operate on supplementary file if dwarf is a supplementary file, else self
-
-                // It's possible this is a reference in the supplementary file to itself
-                for source_unit in debug_info_builder_context.sup_units() {
-                    if let Some(new_offset) = offset.to_unit_offset(&source_unit.header) {
-                        return Some(DieReference::UnitAndOffset((
-                            dwarf,
-                            source_unit,
-                            new_offset,
-                        )));
-                    }
-                }
-
-                // ... or it just doesn't have a supplementary file
-                for source_unit in debug_info_builder_context.units() {
-                    if let Some(new_offset) = offset.to_unit_offset(&source_unit.header) {
-                        return Some(DieReference::UnitAndOffset((
-                            dwarf,
-                            source_unit,
-                            new_offset,
-                        )));
-                    }
-                }
-            }
-
-            None
-        }
-        Ok(Some(DebugInfoRefSup(offset))) => {
-            for source_unit in debug_info_builder_context.sup_units() {
-                if let Some(new_offset) = offset.to_unit_offset(&source_unit.header) {
-                    return Some(DieReference::UnitAndOffset((
-                        dwarf.sup().unwrap(),
-                        source_unit,
-                        new_offset,
-                    )));
-                }
+    let value = entry.attr_value(attr).ok()??;
+    let is_sup_ref = matches!(value, DebugInfoRefSup(_));
+    match debug_info_builder_context
+        .resolver()
+        .resolve(dwarf, unit, value)
+    {
+        Some(die) => Some(DieReference::UnitAndOffset((
+            die.dwarf, die.unit, die.offset,
+        ))),
+        None => {
+            if is_sup_ref {
+                warn!("Failed to fetch DIE. Supplementary debug information may be incomplete.");
             }
-            warn!("Failed to fetch DIE. Supplementary debug information may be incomplete.");
             None
         }
-        _ => None,
     }
 }
 
@@ -353,186 +316,69 @@ pub(crate) fn get_expr_value<R: ReaderType>(unit: &Unit<R>, attr: Attribute<R>)
     }
 }
 
-pub(crate) fn get_build_id(view: &BinaryView) -> Result<String, String> {
-    let mut build_id: Option<String> = None;
-
-    if let Some(raw_view) = view.raw_view() {
-        if let Some(build_id_section) = raw_view.section_by_name(".note.gnu.build-id") {
-            // Name size - 4 bytes
-            // Desc size - 4 bytes
-            // Type - 4 bytes
-            // Name - n bytes
-            // Desc - n bytes
-            let build_id_bytes =
-                raw_view.read_vec(build_id_section.start(), build_id_section.len());
-            if build_id_bytes.len() < 12 {
-                return Err("Build id section must be at least 12 bytes".to_string());
-            }
-
-            let name_len: u32;
-            let desc_len: u32;
-            let note_type: u32;
-            match raw_view.default_endianness() {
-                Endianness::LittleEndian => {
-                    name_len = u32::from_le_bytes(build_id_bytes[0..4].try_into().unwrap());
-                    desc_len = u32::from_le_bytes(build_id_bytes[4..8].try_into().unwrap());
-                    note_type = u32::from_le_bytes(build_id_bytes[8..12].try_into().unwrap());
-                }
-                Endianness::BigEndian => {
-                    name_len = u32::from_be_bytes(build_id_bytes[0..4].try_into().unwrap());
-                    desc_len = u32::from_be_bytes(build_id_bytes[4..8].try_into().unwrap());
-                    note_type = u32::from_be_bytes(build_id_bytes[8..12].try_into().unwrap());
-                }
-            };
+pub(crate) enum StaticLocation {
+    Address(u64),
+    ThreadLocal(u64),
+    Unsupported,
+}
 
-            if note_type != 3 {
-                return Err(format!("Build id section has wrong type: {}", note_type));
+// Evaluates the location expression of a variable with static storage; these are usually a lone
+// DW_OP_addr, but optimized binaries also emit address arithmetic and TLS forms
+pub(crate) fn get_static_location<R: ReaderType>(
+    dwarf: &Dwarf<R>,
+    unit: &Unit<R>,
+    expression: Expression<R>,
+) -> gimli::Result<StaticLocation> {
+    let mut evaluation = expression.evaluation(unit.encoding());
+    let mut result = evaluation.evaluate()?;
+    loop {
+        result = match result {
+            EvaluationResult::Complete => break,
+            EvaluationResult::RequiresRelocatedAddress(address) => {
+                evaluation.resume_with_relocated_address(address)?
             }
-
-            let expected_len = (12 + name_len + desc_len) as usize;
-
-            if build_id_bytes.len() < expected_len {
-                return Err(format!(
-                    "Build id section not expected length: expected {}, got {}",
-                    expected_len,
-                    build_id_bytes.len()
-                ));
+            EvaluationResult::RequiresIndexedAddress { index, .. } => {
+                let address = dwarf.address(unit, index)?;
+                evaluation.resume_with_indexed_address(address)?
             }
-
-            let desc: &[u8] = &build_id_bytes[(12 + name_len as usize)..expected_len];
-            build_id = Some(desc.iter().map(|b| format!("{:02x}", b)).collect());
-        }
+            EvaluationResult::RequiresTls(offset) => {
+                return Ok(StaticLocation::ThreadLocal(offset))
+            }
+            // Anything else depends on runtime state (registers, memory, frame base)
+            _ => return Ok(StaticLocation::Unsupported),
+        };
     }
 
-    if let Some(x) = build_id {
-        Ok(x)
-    } else {
-        Err("Failed to get build id".to_string())
+    match evaluation.result().as_slice() {
+        [Piece {
+            location: Location::Address { address },
+            ..
+        }] => Ok(StaticLocation::Address(*address)),
+        _ => Ok(StaticLocation::Unsupported),
     }
 }
 
-pub(crate) fn download_debug_info(
-    build_id: &str,
-    view: &BinaryView,
-) -> Result<Ref<BinaryView>, String> {
-    let mut settings_query_opts = QueryOptions::new_with_view(view);
-    let settings = Settings::new();
-    let debug_server_urls =
-        settings.get_string_list_with_opts("network.debuginfodServers", &mut settings_query_opts);
-
-    for debug_server_url in debug_server_urls.iter() {
-        let artifact_url = format!("{}/buildid/{}/debuginfo", debug_server_url, build_id);
-
-        // Download from remote
-        let (tx, rx) = mpsc::channel();
-        let write = move |data: &[u8]| -> usize {
-            if tx.send(Vec::from(data)).is_ok() {
-                data.len()
-            } else {
-                0
-            }
-        };
-
-        let dp = DownloadProvider::try_default().map_err(|_| "No default download provider")?;
-        let mut inst = dp
-            .create_instance()
-            .map_err(|_| "Couldn't create download instance")?;
-        let result = inst
-            .perform_custom_request(
-                "GET",
-                artifact_url,
-                HashMap::<String, String>::new(),
-                DownloadInstanceInputOutputCallbacks {
-                    read: None,
-                    write: Some(Box::new(write)),
-                    progress: None,
-                },
-            )
-            .map_err(|e| e.to_string())?;
-        if result.status_code != 200 {
-            continue;
-        }
-
-        let mut expected_length = None;
-        for (k, v) in result.headers.iter() {
-            if k.to_lowercase() == "content-length" {
-                expected_length = Some(usize::from_str(v).map_err(|e| e.to_string())?);
-            }
-        }
-
-        let mut data = vec![];
-        while let Ok(packet) = rx.try_recv() {
-            data.extend(packet.into_iter());
-        }
-
-        if let Some(length) = expected_length {
-            if data.len() != length {
-                return Err(format!(
-                    "Bad length: expected {} got {}",
-                    length,
-                    data.len()
-                ));
-            }
-        }
-
-        let options = "{\"analysis.debugInfo.internal\": false}";
-        let bv = BinaryView::from_data(FileMetadata::new().deref(), &data)
-            .map_err(|_| "Unable to create binary view from downloaded data".to_string())?;
-
-        return binaryninja::load_view(bv.deref(), false, Some(options))
-            .ok_or("Unable to load binary view from downloaded data".to_string());
-    }
-    Err("Could not find a server with debug info for this file".to_string())
+/// Directories searched for debug files, empty if searching them is disabled.
+pub(crate) fn debug_directories(view: &BinaryView) -> Vec<PathBuf> {
+    DebugFileLocator::from_settings(view)
+        .debug_directories()
+        .to_vec()
 }
 
 pub(crate) fn find_local_debug_file_for_build_id(
     build_id: &str,
     view: &BinaryView,
-) -> Option<String> {
-    let mut settings_query_opts = QueryOptions::new_with_view(view);
-    let settings = Settings::new();
-    let debug_dirs_enabled = settings.get_bool_with_opts(
-        "analysis.debugInfo.enableDebugDirectories",
-        &mut settings_query_opts,
-    );
-
-    if !debug_dirs_enabled {
-        return None;
-    }
-
-    let debug_info_paths = settings.get_string_list_with_opts(
-        "analysis.debugInfo.debugDirectories",
-        &mut settings_query_opts,
-    );
-
-    if debug_info_paths.is_empty() {
-        return None;
-    }
-
-    for debug_info_path in debug_info_paths.into_iter() {
-        let path = PathBuf::from(debug_info_path);
-        let elf_path = path.join(&build_id[..2]).join(&build_id[2..]).join("elf");
-
-        let debug_ext_path = path
-            .join(&build_id[..2])
-            .join(format!("{}.debug", &build_id[2..]));
-
-        let final_path = if debug_ext_path.exists() {
-            debug_ext_path
-        } else if elf_path.exists() {
-            elf_path
-        } else {
-            // No paths exist in this dir, try the next one
-            continue;
-        };
-        return final_path.to_str().and_then(|x| Some(x.to_string()));
-    }
-    None
+) -> Option<PathBuf> {
+    DebugFileLocator::from_settings(view).find_for_build_id(build_id)
 }
 
+/// Load the debug file for `build_id` from the debug directories or, if enabled, debuginfod.
+///
+/// `progress` is used for the download, when one is needed.
 pub(crate) fn load_debug_info_for_build_id(
     build_id: &str,
     view: &BinaryView,
+    progress: ProgressScope,
 ) -> (Option<Ref<BinaryView>>, bool) {
     let mut settings_query_opts = QueryOptions::new_with_view(view);
     let settings = Settings::new();
@@ -546,7 +392,17 @@ pub(crate) fn load_debug_info_for_build_id(
             false,
         );
     } else if settings.get_bool_with_opts("network.enableDebuginfod", &mut settings_query_opts) {
-        return (download_debug_info(build_id, view).ok(), true);
+        let client = DebuginfodClient::from_settings(view);
+        return match client.fetch(build_id, progress) {
+            Ok(debug_view) => (Some(debug_view), true),
+            Err(e) => {
+                warn!(
+                    "Unable to fetch debug info for build id {}: {}",
+                    build_id, e
+                );
+                (None, false)
+            }
+        };
     }
     (None, false)
 }
@@ -589,7 +445,31 @@ pub(crate) fn load_sibling_debug_file(view: &BinaryView) -> (Option<Ref<BinaryVi
     let Some(debug_file) = find_sibling_debug_file(view) else {
         return (None, false);
     };
+    (load_debug_file(view, debug_file), false)
+}
+
+// By session id of the view. Lazy imports run the parser once per batch of units, which would
+// otherwise search the debug directories (and Spotlight, for dSYMs) every time.
+static EXTERNAL_DEBUG_FILES: Mutex<BTreeMap<usize, Option<PathBuf>>> = Mutex::new(BTreeMap::new());
+
+/// The separate debug file of `view` in the debug directories, searched for once per view.
+pub(crate) fn external_debug_file(view: &BinaryView) -> Option<PathBuf> {
+    if !locator::may_have_debug_file(view) {
+        return None;
+    }
+    EXTERNAL_DEBUG_FILES
+        .lock()
+        .unwrap()
+        .entry(view.file().session_id())
+        .or_insert_with(|| find_external_debug_file(view))
+        .clone()
+}
 
+/// Open the separate debug file of `view` at `debug_file`, for the platform of `view`.
+pub(crate) fn load_debug_file(
+    view: &BinaryView,
+    debug_file: impl AsRef<Path>,
+) -> Option<Ref<BinaryView>> {
     let load_settings = match view.default_platform() {
         Some(plat) => format!(
             "{{\"analysis.debugInfo.internal\": false, \"loader.platform\": \"{}\"}}",
@@ -598,8 +478,79 @@ pub(crate) fn load_sibling_debug_file(view: &BinaryView) -> (Option<Ref<BinaryVi
         None => "{\"analysis.debugInfo.internal\": false}".to_string(),
     };
 
-    (
-        binaryninja::load_with_options(debug_file, false, Some(load_settings)),
+    binaryninja::load_with_options(debug_file, false, Some(load_settings))
+}
+
+/// Path of the `X.dwp` split DWARF package next to a file named X, if there is one.
+pub(crate) fn find_dwp_file(view: &BinaryView) -> Option<PathBuf> {
+    let mut settings_query_opts = QueryOptions::new_with_view(view);
+    let settings = Settings::new();
+    let load_dwp =
+        settings.get_bool_with_opts("analysis.debugInfo.loadDwpFiles", &mut settings_query_opts);
+
+    if !load_dwp {
+        return None;
+    }
+
+    let dwp_file = PathBuf::from(format!("{}.dwp", view.file().filename()));
+    dwp_file.is_file().then_some(dwp_file)
+}
+
+pub(crate) fn load_dwp_file(view: &BinaryView) -> Option<Ref<BinaryView>> {
+    let dwp_file = find_dwp_file(view)?;
+    let dwp_view = binaryninja::load_with_options(
+        dwp_file,
         false,
-    )
+        Some("{\"analysis.debugInfo.internal\": false}"),
+    )?;
+    // Figure out if it's the given view or the raw view that has the package sections in it
+    match dwp_view.section_by_name(".debug_cu_index") {
+        Some(_) => Some(dwp_view),
+        None => dwp_view.raw_view(),
+    }
+}
+
+/// Names architectures give registers besides their DWARF name, by DWARF register number.
+fn register_aliases(arch: &str) -> &'static [(u16, &'static str)] {
+    match arch {
+        "aarch64" => &[(29, "fp"), (30, "lr"), (31, "sp")],
+        "armv7" | "armv7eb" | "thumb2" | "thumb2eb" => &[(13, "sp"), (14, "lr"), (15, "pc")],
+        _ if arch.starts_with("mips") => &[(29, "sp"), (30, "fp"), (31, "ra")],
+        _ => &[],
+    }
+}
+
+/// The name `arch` gives DWARF register number `register`. The DWARF name of the register if the
+/// architecture has no register by that name, `dwarf_reg<n>` if the register is unknown.
+pub(crate) fn dwarf_register_name(arch: Option<&CoreArchitecture>, register: Register) -> String {
+    let arch_name = arch.map(|arch| arch.name().to_string()).unwrap_or_default();
+    let arch_name = arch_name.as_str();
+    let dwarf_name = match arch_name {
+        "x86_64" => gimli::X86_64::register_name(register),
+        "x86" | "x86_16" => gimli::X86::register_name(register),
+        "aarch64" => gimli::AArch64::register_name(register),
+        "armv7" | "armv7eb" | "thumb2" | "thumb2eb" => gimli::Arm::register_name(register),
+        _ if arch_name.starts_with("rv") => gimli::RiscV::register_name(register),
+        _ if arch_name.starts_with("mips") => gimli::MIPS::register_name(register),
+        _ if arch_name.starts_with("ppc") => gimli::PowerPc64::register_name(register),
+        _ => None,
+    }
+    .map(str::to_lowercase);
+
+    if let Some(arch) = arch {
+        let aliases = register_aliases(arch_name)
+            .iter()
+            .filter(|(number, _)| *number == register.0)
+            .map(|(_, alias)| alias.to_string());
+        for candidate in dwarf_name.iter().cloned().chain(aliases) {
+            // Some architectures prefix register names with `$`, not always as DWARF does
+            let bare = candidate.trim_start_matches('$');
+            for name in [candidate.as_str(), bare, &format!("${}", bare)] {
+                if let Some(register) = arch.register_by_name(name) {
+                    return register.name().to_string();
+                }
+            }
+        }
+    }
+    dwarf_name.unwrap_or_else(|| format!("dwarf_reg{}", register.0))
 }
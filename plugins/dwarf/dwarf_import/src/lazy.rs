@@ -0,0 +1,289 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Lazy imports only index the compilation units up front. The units are imported later, when
+// analysis reaches a function in one of them or the user asks for a name one of them defines. An
+// import of some units runs the parser again on a worker thread, which takes the units to parse
+// from here.
+//
+// The units imported so far are stored in the view metadata, which is removed once every unit is
+// imported. A view reopened with the metadata still there has its units indexed again when
+// analysis reaches its first function, and the import continues with the units left.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Mutex;
+
+use binaryninja::{
+    binary_view::{BinaryView, BinaryViewBase, BinaryViewExt},
+    command::Command,
+    debuginfo::{DebugInfo, DebugInfoParser},
+    function::Function,
+    interaction::get_text_line_input,
+    rc::Ref,
+    worker_thread::execute_on_worker_thread,
+    workflow::{Activity, AnalysisContext, Workflow},
+};
+use dwarfreader::index::UnitIndex;
+use gimli::DebugInfoOffset;
+use log::{error, info, warn};
+
+use crate::{UnitSelection, PARSER_NAME};
+
+pub(crate) const LAZY_IMPORT_SETTING: &str = "analysis.debugInfo.lazyDwarfImport";
+
+/// View metadata key of the offsets of the units imported so far.
+const IMPORTED_UNITS_METADATA_KEY: &str = "dwarf_lazy_import_units";
+
+const ACTIVITY_NAME: &str = "analysis.plugins.dwarfImport.lazyImport";
+const ACTIVITY_CONFIG: &str = r#"{
+    "name": "analysis.plugins.dwarfImport.lazyImport",
+    "title": "Import DWARF Units Lazily",
+    "description": "This analysis step imports the DWARF compilation unit of each function analysis reaches, while importing DWARF lazily.",
+    "eligibility": {
+        "auto": {},
+        "runOnce": false
+    }
+}"#;
+
+/// The units of a view still to be imported.
+struct LazyUnits {
+    index: UnitIndex,
+    /// The units imported or requested so far.
+    imported: HashSet<DebugInfoOffset<usize>>,
+    requested: Vec<DebugInfoOffset<usize>>,
+    /// Set from queueing an import until it finishes, so only one parse of the view runs at a time.
+    queued: bool,
+}
+
+enum LazyImport {
+    /// The units of a reopened view are being indexed again.
+    Indexing,
+    Ready(LazyUnits),
+}
+
+// By session id of the view. Everything here can be restored from the view, it doesn't keep the
+// view alive and is removed once every unit is imported.
+static LAZY_IMPORTS: Mutex<BTreeMap<usize, LazyImport>> = Mutex::new(BTreeMap::new());
+
+thread_local! {
+    /// The units the parser is run for on this thread, while a lazy import runs it.
+    static SELECTION: RefCell<Option<UnitSelection>> = const { RefCell::new(None) };
+}
+
+/// The units to parse, if the parser is run by a lazy import on this thread.
+pub(crate) fn take_selection() -> Option<UnitSelection> {
+    SELECTION.with(|selection| selection.borrow_mut().take())
+}
+
+/// Run the parser on `view` for `selection`.
+fn run_parser(view: &BinaryView, selection: UnitSelection) -> Option<Ref<DebugInfo>> {
+    let parser = DebugInfoParser::from_name(PARSER_NAME).ok()?;
+    SELECTION.with(|current| *current.borrow_mut() = Some(selection));
+    let debug_info = parser.parse_debug_info(view, view, None);
+    SELECTION.with(|current| current.borrow_mut().take());
+    debug_info
+}
+
+/// Start a lazy import of the units of `index` into `view`, importing those of the functions
+/// `view` already has. Continues the import the view metadata records, if there is one.
+pub(crate) fn start(view: &BinaryView, index: UnitIndex) {
+    let imported: HashSet<_> = view
+        .query_metadata(IMPORTED_UNITS_METADATA_KEY)
+        .and_then(|units| Vec::<u64>::try_from(&*units).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|unit| DebugInfoOffset(unit as usize))
+        .collect();
+    if imported.is_empty() {
+        info!(
+            "Indexed {} DWARF compilation units, importing them as they are used",
+            index.len()
+        );
+        view.store_metadata(IMPORTED_UNITS_METADATA_KEY, &Vec::<u64>::new(), true);
+    }
+    if imported.len() >= index.len() {
+        view.remove_metadata(IMPORTED_UNITS_METADATA_KEY);
+        LAZY_IMPORTS
+            .lock()
+            .unwrap()
+            .remove(&view.file().session_id());
+        return;
+    }
+
+    let mut imports = LAZY_IMPORTS.lock().unwrap();
+    let mut units = LazyUnits {
+        index,
+        imported,
+        requested: vec![],
+        queued: false,
+    };
+    for func in view.functions().iter() {
+        let offsets = units.index.units_at(dwarf_address(view, func.start()));
+        request_units(view, &mut units, &offsets);
+    }
+    imports.insert(view.file().session_id(), LazyImport::Ready(units));
+}
+
+/// The address in the DWARF of `addr`, for views loaded at a different base than it assumes.
+fn dwarf_address(view: &BinaryView, addr: u64) -> u64 {
+    addr.wrapping_sub(view.start().wrapping_sub(view.original_image_base()))
+}
+
+/// Import the units of the function at `func` that weren't imported yet, indexing the units of a
+/// reopened view first.
+fn function_analyzed(view: &BinaryView, func: &Function) {
+    let session = view.file().session_id();
+    let mut imports = LAZY_IMPORTS.lock().unwrap();
+    match imports.get_mut(&session) {
+        Some(LazyImport::Ready(units)) => {
+            let offsets = units.index.units_at(dwarf_address(view, func.start()));
+            request_units(view, units, &offsets);
+        }
+        Some(LazyImport::Indexing) => {}
+        None => {
+            if view.query_metadata(IMPORTED_UNITS_METADATA_KEY).is_none() {
+                return;
+            }
+            imports.insert(session, LazyImport::Indexing);
+            let view = view.to_owned();
+            execute_on_worker_thread("Indexing DWARF compilation units", move || {
+                index_units(&view)
+            });
+        }
+    }
+}
+
+/// Index the units of a reopened view and continue its import.
+fn index_units(view: &Ref<BinaryView>) {
+    let _ = run_parser(view, UnitSelection::Index);
+    match crate::apply::take_index(view) {
+        Some(index) => start(view, index),
+        None => {
+            warn!("Failed to index DWARF compilation units, not importing the units left");
+            LAZY_IMPORTS
+                .lock()
+                .unwrap()
+                .remove(&view.file().session_id());
+        }
+    }
+}
+
+/// Request the import of the units at `offsets` that weren't imported yet, on a worker thread.
+fn request_units(view: &BinaryView, units: &mut LazyUnits, offsets: &[DebugInfoOffset<usize>]) {
+    for &unit in offsets {
+        if units.imported.insert(unit) {
+            units.requested.push(unit);
+        }
+    }
+    // Units requested while an import is queued or running are imported once it is done
+    if !units.queued && !units.requested.is_empty() {
+        units.queued = true;
+        let view = view.to_owned();
+        execute_on_worker_thread("Importing DWARF compilation units", move || {
+            import_requested_units(&view)
+        });
+    }
+}
+
+fn import_requested_units(view: &Ref<BinaryView>) {
+    let session = view.file().session_id();
+    loop {
+        let requested = {
+            let mut imports = LAZY_IMPORTS.lock().unwrap();
+            let Some(LazyImport::Ready(units)) = imports.get_mut(&session) else {
+                return;
+            };
+            if units.requested.is_empty() {
+                units.queued = false;
+                return;
+            }
+            std::mem::take(&mut units.requested)
+        };
+
+        match run_parser(view, UnitSelection::Only(requested.clone())) {
+            Some(debug_info) => {
+                view.apply_debug_info(&debug_info);
+                crate::apply::store(view);
+            }
+            None => warn!("Failed to import DWARF compilation units"),
+        }
+
+        let mut stored: Vec<u64> = view
+            .query_metadata(IMPORTED_UNITS_METADATA_KEY)
+            .and_then(|units| Vec::<u64>::try_from(&*units).ok())
+            .unwrap_or_default();
+        stored.extend(requested.iter().map(|unit| unit.0 as u64));
+
+        let mut imports = LAZY_IMPORTS.lock().unwrap();
+        let done = match imports.get(&session) {
+            Some(LazyImport::Ready(units)) => stored.len() >= units.index.len(),
+            _ => true,
+        };
+        if done {
+            imports.remove(&session);
+            view.remove_metadata(IMPORTED_UNITS_METADATA_KEY);
+            return;
+        }
+        view.store_metadata(IMPORTED_UNITS_METADATA_KEY, &stored, true);
+    }
+}
+
+/// Import the units of each function analysis reaches while importing lazily.
+pub(crate) fn register_activity() -> bool {
+    let workflow =
+        Workflow::instance("core.function.metaAnalysis").clone("core.function.metaAnalysis");
+    let activity = Activity::new_with_action(ACTIVITY_CONFIG, |ctx: &AnalysisContext| {
+        function_analyzed(&ctx.view(), &ctx.function())
+    });
+    if workflow.register_activity(&activity).is_err() {
+        error!("Failed to register the lazy DWARF import activity");
+        return false;
+    }
+    workflow.insert("core.function.runFunctionRecognizers", [ACTIVITY_NAME]);
+    if workflow.register().is_err() {
+        error!("Failed to register the lazy DWARF import activity");
+        return false;
+    }
+    true
+}
+
+/// Imports the units defining a name, for types and for functions analysis hasn't found.
+pub(crate) struct ImportNameCommand;
+
+impl Command for ImportNameCommand {
+    fn action(&self, view: &BinaryView) {
+        let Some(name) = get_text_line_input("Function or type name", "Import DWARF Debug Info")
+        else {
+            return;
+        };
+        let mut imports = LAZY_IMPORTS.lock().unwrap();
+        let Some(LazyImport::Ready(units)) = imports.get_mut(&view.file().session_id()) else {
+            return;
+        };
+        let offsets = units.index.units_named(name.trim());
+        if offsets.is_empty() {
+            warn!("No DWARF compilation unit defines `{}`", name.trim());
+        }
+        request_units(view, units, &offsets);
+    }
+
+    fn valid(&self, view: &BinaryView) -> bool {
+        let imports = LAZY_IMPORTS.lock().unwrap();
+        matches!(
+            imports.get(&view.file().session_id()),
+            Some(LazyImport::Ready(units)) if units.index.has_names()
+        )
+    }
+}
@@ -12,80 +12,89 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod apply;
 mod die_handlers;
 mod dwarfdebuginfo;
 mod functions;
 mod helpers;
+mod lazy;
+mod lines;
+mod merge;
 mod types;
 
 use std::collections::HashMap;
 
+use crate::apply::ParsedTables;
 use crate::dwarfdebuginfo::{DebugInfoBuilder, DebugInfoBuilderContext};
 use crate::functions::parse_function_entry;
-use crate::helpers::{get_attr_die, get_name, get_uid, DieReference};
+use crate::helpers::{get_attr_as_u64, get_attr_die, get_name, get_uid, DieReference};
 use crate::types::parse_variable;
 
 use binaryninja::binary_view::BinaryViewBase;
 use binaryninja::{
     binary_view::{BinaryView, BinaryViewExt},
-    debuginfo::{CustomDebugInfoParser, DebugInfo, DebugInfoParser},
-    settings::Settings,
+    command::register_command,
+    debuginfo::{
+        locator, source_path_components, CustomDebugInfoParser, DebugInfo, DebugInfoParser,
+    },
+    frame_info::{Cfa, FrameInfoTable, FrameRow, SavedRegister},
+    import_diagnostics::ImportDiagnostics,
+    inlined_calls::InlinedCall,
+    rc::Ref,
+    settings::{QueryOptions, Settings},
     template_simplifier::simplify_str_to_str,
 };
-use dwarfreader::{
-    create_section_reader, get_endian, is_dwo_dwarf, is_non_dwo_dwarf, is_raw_dwo_dwarf,
+use dwarfreader::debuginfod;
+use dwarfreader::dwp::SplitUnit;
+use dwarfreader::index::UnitIndex;
+use dwarfreader::supplementary::{
+    find_supplementary_file, matches_supplementary_file, supplementary_link,
 };
+use dwarfreader::{is_dwo_dwarf, is_non_dwo_dwarf, DwarfReaderContext, SectionReader};
 
 use functions::parse_lexical_block;
 use gimli::{
-    constants, CfaRule, DebuggingInformationEntry, Dwarf, DwarfFileType, Reader, Section,
-    SectionId, Unit, UnwindContext, UnwindSection,
+    constants, CfaRule, DebugInfoOffset, DebuggingInformationEntry, Dwarf, Reader, RegisterRule,
+    Section, SectionId, Unit, UnitSectionOffset, UnwindContext, UnwindSection,
 };
 
 use binaryninja::logger::Logger;
-use helpers::{get_build_id, load_debug_info_for_build_id};
-use log::{debug, error, warn};
+use binaryninja::progress::ProgressScope;
+use helpers::{
+    debug_directories, dwarf_register_name, load_debug_info_for_build_id, load_dwp_file,
+};
+use log::{error, warn};
+
+/// The name the parser is registered under, which is also the importer name of its diagnostics
+const PARSER_NAME: &str = "DWARF";
 
 trait ReaderType: Reader<Offset = usize> {}
 impl<T: Reader<Offset = usize>> ReaderType for T {}
 
-pub(crate) fn split_progress<'b, F: Fn(usize, usize) -> Result<(), ()> + 'b>(
-    original_fn: F,
-    subpart: usize,
-    subpart_weights: &[f64],
-) -> Box<dyn Fn(usize, usize) -> Result<(), ()> + 'b> {
-    // Normalize weights
-    let weight_sum: f64 = subpart_weights.iter().sum();
-    if weight_sum < 0.0001 {
-        return Box::new(|_, _| Ok(()));
-    }
+/// The compilation units [`parse_dwarf`] parses.
+enum UnitSelection {
+    /// Every unit, including those of the supplementary file and the `.dwp` package.
+    All,
+    /// No unit, only an index of the units for a lazy import.
+    Index,
+    /// The units of the main file at these offsets, and their split units.
+    Only(Vec<DebugInfoOffset<usize>>),
+}
 
-    // Keep a running count of weights for the start
-    let mut subpart_starts = vec![];
-    let mut start = 0f64;
-    for w in subpart_weights {
-        subpart_starts.push(start);
-        start += *w;
+impl UnitSelection {
+    fn includes(&self, offset: UnitSectionOffset) -> bool {
+        match self {
+            UnitSelection::All | UnitSelection::Index => true,
+            UnitSelection::Only(units) => offset
+                .as_debug_info_offset()
+                .is_some_and(|offset| units.contains(&offset)),
+        }
     }
-
-    let subpart_start = subpart_starts[subpart] / weight_sum;
-    let weight = subpart_weights[subpart] / weight_sum;
-
-    Box::new(move |cur: usize, max: usize| {
-        // Just use a large number for easy divisibility
-        let steps = 1000000f64;
-        let subpart_size = steps * weight;
-        let subpart_progress = ((cur as f64) / (max as f64)) * subpart_size;
-
-        original_fn(
-            (subpart_start * steps + subpart_progress) as usize,
-            steps as usize,
-        )
-    })
 }
 
 fn calculate_total_unit_bytes<R: ReaderType>(
     dwarf: &Dwarf<R>,
+    split_units: &[SplitUnit<R>],
     debug_info_builder_context: &mut DebugInfoBuilderContext<R>,
 ) {
     let mut iter = dwarf.units();
@@ -93,142 +102,227 @@ fn calculate_total_unit_bytes<R: ReaderType>(
     while let Ok(Some(header)) = iter.next() {
         total_size += header.length_including_self();
     }
+    for split_unit in split_units {
+        total_size += split_unit.unit.header.length_including_self();
+    }
     debug_info_builder_context.total_unit_size_bytes = total_size;
 }
 
 fn recover_names<R: ReaderType>(
     dwarf: &Dwarf<R>,
+    split_units: &[SplitUnit<R>],
+    selection: &UnitSelection,
     debug_info_builder_context: &mut DebugInfoBuilderContext<R>,
-    progress: &dyn Fn(usize, usize) -> Result<(), ()>,
+    progress: &ProgressScope,
 ) -> bool {
     let mut res = true;
+    // The types of the selected units may refer to any unit of the supplementary file
     if let Some(sup_dwarf) = dwarf.sup() {
-        res = recover_names_internal(sup_dwarf, debug_info_builder_context, progress);
+        res = recover_names_internal(
+            sup_dwarf,
+            &UnitSelection::All,
+            debug_info_builder_context,
+            progress,
+        );
     }
 
     if res {
-        res = recover_names_internal(dwarf, debug_info_builder_context, progress);
+        res = recover_names_internal(dwarf, selection, debug_info_builder_context, progress);
+    }
+
+    let mut current_byte_offset: usize = 0;
+    for split_unit in split_units {
+        if !res {
+            break;
+        }
+        res = recover_unit_names(
+            &split_unit.dwarf,
+            &split_unit.unit,
+            debug_info_builder_context,
+            progress,
+            &mut current_byte_offset,
+        );
     }
     res
 }
 
 fn recover_names_internal<R: ReaderType>(
     dwarf: &Dwarf<R>,
+    selection: &UnitSelection,
     debug_info_builder_context: &mut DebugInfoBuilderContext<R>,
-    progress: &dyn Fn(usize, usize) -> Result<(), ()>,
+    progress: &ProgressScope,
 ) -> bool {
     let mut iter = dwarf.units();
     let mut current_byte_offset: usize = 0;
     while let Ok(Some(header)) = iter.next() {
-        let unit_offset = header.offset().as_debug_info_offset().unwrap().0;
+        if !selection.includes(header.offset()) {
+            continue;
+        }
         let unit = dwarf.unit(header).unwrap();
-        let mut namespace_qualifiers: Vec<(isize, String)> = vec![];
-        let mut entries = unit.entries();
-        let mut depth = 0;
-
-        // The first entry in the unit is the header for the unit
-        if let Ok(Some((delta_depth, _))) = entries.next_dfs() {
-            depth += delta_depth;
-            debug_info_builder_context.total_die_count += 1;
+        if !recover_unit_names(
+            dwarf,
+            &unit,
+            debug_info_builder_context,
+            progress,
+            &mut current_byte_offset,
+        ) {
+            return false;
         }
+    }
+
+    true
+}
 
-        while let Ok(Some((delta_depth, entry))) = entries.next_dfs() {
-            debug_info_builder_context.total_die_count += 1;
+fn recover_unit_names<R: ReaderType>(
+    dwarf: &Dwarf<R>,
+    unit: &Unit<R>,
+    debug_info_builder_context: &mut DebugInfoBuilderContext<R>,
+    progress: &ProgressScope,
+    current_byte_offset: &mut usize,
+) -> bool {
+    let unit_offset = unit.header.offset().as_debug_info_offset().unwrap().0;
+    let mut namespace_qualifiers: Vec<(isize, String)> = vec![];
+    // The namespaces and modules alone, used to place functions into components
+    let mut component_qualifiers: Vec<(isize, String)> = vec![];
+    let mut entries = unit.entries();
+    let mut depth = 0;
 
-            if (*progress)(
-                current_byte_offset,
+    // The first entry in the unit is the header for the unit
+    if let Ok(Some((delta_depth, _))) = entries.next_dfs() {
+        depth += delta_depth;
+        debug_info_builder_context.total_die_count += 1;
+    }
+
+    while let Ok(Some((delta_depth, entry))) = entries.next_dfs() {
+        debug_info_builder_context.total_die_count += 1;
+
+        if progress
+            .report(
+                *current_byte_offset,
                 debug_info_builder_context.total_unit_size_bytes,
             )
             .is_err()
-            {
-                return false; // Parsing canceled
-            };
-            current_byte_offset = unit_offset + entry.offset().0;
-
-            depth += delta_depth;
-            if depth < 0 {
-                error!("DWARF information is seriously malformed. Aborting parsing.");
-                return false;
-            }
+        {
+            return false; // Parsing canceled
+        };
+        *current_byte_offset = unit_offset + entry.offset().0;
+
+        depth += delta_depth;
+        if depth < 0 {
+            error!("DWARF information is seriously malformed. Aborting parsing.");
+            return false;
+        }
+
+        // TODO : Better module/component support
+        namespace_qualifiers.retain(|&(entry_depth, _)| entry_depth < depth);
+        component_qualifiers.retain(|&(entry_depth, _)| entry_depth < depth);
 
-            // TODO : Better module/component support
-            namespace_qualifiers.retain(|&(entry_depth, _)| entry_depth < depth);
-
-            match entry.tag() {
-                constants::DW_TAG_namespace => {
-                    fn resolve_namespace_name<R: ReaderType>(
-                        dwarf: &Dwarf<R>,
-                        unit: &Unit<R>,
-                        entry: &DebuggingInformationEntry<R>,
-                        debug_info_builder_context: &DebugInfoBuilderContext<R>,
-                        namespace_qualifiers: &mut Vec<(isize, String)>,
-                        depth: isize,
+        match entry.tag() {
+            constants::DW_TAG_namespace => {
+                fn resolve_namespace_name<R: ReaderType>(
+                    dwarf: &Dwarf<R>,
+                    unit: &Unit<R>,
+                    entry: &DebuggingInformationEntry<R>,
+                    debug_info_builder_context: &DebugInfoBuilderContext<R>,
+                    namespace_qualifiers: &mut Vec<(isize, String)>,
+                    depth: isize,
+                ) {
+                    if let Some(namespace_qualifier) =
+                        get_name(dwarf, unit, entry, debug_info_builder_context)
+                    {
+                        namespace_qualifiers.push((depth, namespace_qualifier));
+                    } else if let Some(die_reference) = get_attr_die(
+                        dwarf,
+                        unit,
+                        entry,
+                        debug_info_builder_context,
+                        constants::DW_AT_extension,
                     ) {
-                        if let Some(namespace_qualifier) =
-                            get_name(dwarf, unit, entry, debug_info_builder_context)
-                        {
-                            namespace_qualifiers.push((depth, namespace_qualifier));
-                        } else if let Some(die_reference) = get_attr_die(
-                            dwarf,
-                            unit,
-                            entry,
-                            debug_info_builder_context,
-                            constants::DW_AT_extension,
-                        ) {
-                            match die_reference {
-                                DieReference::UnitAndOffset((dwarf, entry_unit, entry_offset)) => {
-                                    resolve_namespace_name(
-                                        dwarf,
-                                        entry_unit,
-                                        &entry_unit.entry(entry_offset).unwrap(),
-                                        debug_info_builder_context,
-                                        namespace_qualifiers,
-                                        depth,
-                                    )
-                                }
-                                DieReference::Err => {
-                                    warn!(
-                                        "Failed to fetch DIE when resolving namespace. Debug information may be incomplete."
-                                    );
-                                }
+                        match die_reference {
+                            DieReference::UnitAndOffset((dwarf, entry_unit, entry_offset)) => {
+                                resolve_namespace_name(
+                                    dwarf,
+                                    entry_unit,
+                                    &entry_unit.entry(entry_offset).unwrap(),
+                                    debug_info_builder_context,
+                                    namespace_qualifiers,
+                                    depth,
+                                )
+                            }
+                            DieReference::Err => {
+                                warn!(
+                                    "Failed to fetch DIE when resolving namespace. Debug information may be incomplete."
+                                );
                             }
-                        } else {
-                            namespace_qualifiers.push((depth, "anonymous_namespace".to_string()));
                         }
+                    } else {
+                        namespace_qualifiers.push((depth, "anonymous_namespace".to_string()));
                     }
+                }
 
-                    resolve_namespace_name(
-                        dwarf,
-                        &unit,
-                        entry,
-                        debug_info_builder_context,
-                        &mut namespace_qualifiers,
+                resolve_namespace_name(
+                    dwarf,
+                    unit,
+                    entry,
+                    debug_info_builder_context,
+                    &mut namespace_qualifiers,
+                    depth,
+                );
+                if let Some(qualifier) = namespace_qualifiers.last() {
+                    if qualifier.0 == depth {
+                        component_qualifiers.push(qualifier.clone());
+                    }
+                }
+            }
+            constants::DW_TAG_module => {
+                let name = get_name(dwarf, unit, entry, debug_info_builder_context);
+                component_qualifiers.push((
+                    depth,
+                    name.clone()
+                        .unwrap_or_else(|| "anonymous_module".to_string()),
+                ));
+                if let Some(name) = name {
+                    debug_info_builder_context.set_name(get_uid(dwarf, unit, entry), name);
+                }
+            }
+            constants::DW_TAG_class_type
+            | constants::DW_TAG_structure_type
+            | constants::DW_TAG_union_type => {
+                if let Some(name) = get_name(dwarf, unit, entry, debug_info_builder_context) {
+                    namespace_qualifiers.push((depth, name))
+                } else {
+                    namespace_qualifiers.push((
                         depth,
-                    );
+                        match entry.tag() {
+                            constants::DW_TAG_class_type => "anonymous_class".to_string(),
+                            constants::DW_TAG_structure_type => "anonymous_structure".to_string(),
+                            constants::DW_TAG_union_type => "anonymous_union".to_string(),
+                            _ => unreachable!(),
+                        },
+                    ))
                 }
-                constants::DW_TAG_class_type
-                | constants::DW_TAG_structure_type
-                | constants::DW_TAG_union_type => {
-                    if let Some(name) = get_name(dwarf, &unit, entry, debug_info_builder_context) {
-                        namespace_qualifiers.push((depth, name))
-                    } else {
-                        namespace_qualifiers.push((
-                            depth,
-                            match entry.tag() {
-                                constants::DW_TAG_class_type => "anonymous_class".to_string(),
-                                constants::DW_TAG_structure_type => {
-                                    "anonymous_structure".to_string()
-                                }
-                                constants::DW_TAG_union_type => "anonymous_union".to_string(),
-                                _ => unreachable!(),
-                            },
-                        ))
-                    }
+                debug_info_builder_context.set_name(
+                    get_uid(dwarf, unit, entry),
+                    simplify_str_to_str(
+                        namespace_qualifiers
+                            .iter()
+                            .map(|(_, namespace)| namespace.to_owned())
+                            .collect::<Vec<String>>()
+                            .join("::"),
+                    )
+                    .to_string(),
+                );
+            }
+            constants::DW_TAG_typedef
+            | constants::DW_TAG_subprogram
+            | constants::DW_TAG_enumeration_type => {
+                if let Some(name) = get_name(dwarf, unit, entry, debug_info_builder_context) {
                     debug_info_builder_context.set_name(
-                        get_uid(dwarf, &unit, entry),
+                        get_uid(dwarf, unit, entry),
                         simplify_str_to_str(
                             namespace_qualifiers
                                 .iter()
+                                .chain(vec![&(-1, name)].into_iter())
                                 .map(|(_, namespace)| namespace.to_owned())
                                 .collect::<Vec<String>>()
                                 .join("::"),
@@ -236,31 +330,23 @@ fn recover_names_internal<R: ReaderType>(
                         .to_string(),
                     );
                 }
-                constants::DW_TAG_typedef
-                | constants::DW_TAG_subprogram
-                | constants::DW_TAG_enumeration_type => {
-                    if let Some(name) = get_name(dwarf, &unit, entry, debug_info_builder_context) {
-                        debug_info_builder_context.set_name(
-                            get_uid(dwarf, &unit, entry),
-                            simplify_str_to_str(
-                                namespace_qualifiers
-                                    .iter()
-                                    .chain(vec![&(-1, name)].into_iter())
-                                    .map(|(_, namespace)| namespace.to_owned())
-                                    .collect::<Vec<String>>()
-                                    .join("::"),
-                            )
-                            .to_string(),
-                        );
-                    }
-                }
-                _ => {
-                    if let Some(name) = get_name(dwarf, &unit, entry, debug_info_builder_context) {
-                        debug_info_builder_context.set_name(get_uid(dwarf, &unit, entry), name);
-                    }
+            }
+            _ => {
+                if let Some(name) = get_name(dwarf, unit, entry, debug_info_builder_context) {
+                    debug_info_builder_context.set_name(get_uid(dwarf, unit, entry), name);
                 }
             }
         }
+
+        if entry.tag() == constants::DW_TAG_subprogram && !component_qualifiers.is_empty() {
+            debug_info_builder_context.set_namespaces(
+                get_uid(dwarf, unit, entry),
+                component_qualifiers
+                    .iter()
+                    .map(|(_, namespace)| namespace.to_owned())
+                    .collect(),
+            );
+        }
     }
 
     true
@@ -271,30 +357,52 @@ fn parse_unit<R: ReaderType>(
     unit: &Unit<R>,
     debug_info_builder_context: &DebugInfoBuilderContext<R>,
     debug_info_builder: &mut DebugInfoBuilder,
-    progress: &dyn Fn(usize, usize) -> Result<(), ()>,
+    progress: &ProgressScope,
     current_die_number: &mut usize,
 ) {
     let mut entries = unit.entries();
 
+    lines::parse_line_program(dwarf, unit, debug_info_builder);
+
+    // Every function of the unit goes into the directories of its source file
+    let components = match (
+        debug_info_builder.components_from_source_paths(),
+        &unit.name,
+    ) {
+        (true, Some(name)) => {
+            let name = name.to_string_lossy().unwrap_or_default();
+            let comp_dir = unit
+                .comp_dir
+                .as_ref()
+                .and_then(|dir| dir.to_string_lossy().ok());
+            source_path_components(&name, comp_dir.as_deref())
+        }
+        _ => vec![],
+    };
+
     let mut current_depth: isize = 0;
     let mut functions_by_depth: Vec<(Option<usize>, isize)> = vec![];
     let mut lexical_blocks_by_depth: Vec<(iset::IntervalSet<u64>, isize)> = vec![];
+    let mut inlined_calls_by_depth: Vec<isize> = vec![];
 
     // Really all we care about as we iterate the entries in a given unit is how they modify state (our perception of the file)
     // There's a lot of junk we don't care about in DWARF info, so we choose a couple DIEs and mutate state (add functions (which adds the types it uses) and keep track of what namespace we're in)
     while let Ok(Some((depth_delta, entry))) = entries.next_dfs() {
         *current_die_number += 1;
-        if (*progress)(
-            *current_die_number,
-            debug_info_builder_context.total_die_count,
-        )
-        .is_err()
+        if progress
+            .report(
+                *current_die_number,
+                debug_info_builder_context.total_die_count,
+            )
+            .is_err()
         {
             return; // Parsing canceled
         }
 
         current_depth = current_depth.saturating_add(depth_delta);
 
+        inlined_calls_by_depth.retain(|&depth| depth < current_depth);
+
         loop {
             if let Some((_fn_idx, depth)) = functions_by_depth.last() {
                 if current_depth <= *depth {
@@ -326,6 +434,17 @@ fn parse_unit<R: ReaderType>(
                     debug_info_builder_context,
                     debug_info_builder,
                 );
+                if let Some(idx) = fn_idx {
+                    let mut function_components = components.clone();
+                    if debug_info_builder.components_from_namespaces() {
+                        function_components.extend_from_slice(
+                            debug_info_builder_context.get_namespaces(dwarf, unit, entry),
+                        );
+                    }
+                    if !function_components.is_empty() {
+                        debug_info_builder.set_function_components(idx, &function_components);
+                    }
+                }
                 functions_by_depth.push((fn_idx, current_depth));
             }
             constants::DW_TAG_lexical_block => {
@@ -333,6 +452,24 @@ fn parse_unit<R: ReaderType>(
                     lexical_blocks_by_depth.push((block_ranges, current_depth));
                 }
             }
+            constants::DW_TAG_inlined_subroutine => {
+                if let Some(ranges) = parse_lexical_block(dwarf, unit, entry) {
+                    let callee = debug_info_builder_context
+                        .get_name(dwarf, unit, entry)
+                        .unwrap_or_else(|| "<unknown>".to_string());
+                    let call_line = match entry.attr(constants::DW_AT_call_line) {
+                        Ok(Some(attr)) => get_attr_as_u64(&attr),
+                        _ => None,
+                    };
+                    debug_info_builder.add_inlined_call(InlinedCall {
+                        callee,
+                        ranges: ranges.unsorted_iter().collect(),
+                        depth: inlined_calls_by_depth.len(),
+                        call_line,
+                    });
+                }
+                inlined_calls_by_depth.push(current_depth);
+            }
             constants::DW_TAG_variable => {
                 let current_fn_idx = functions_by_depth.last().and_then(|x| x.0);
                 let current_lexical_block = lexical_blocks_by_depth.last().and_then(|x| Some(&x.0));
@@ -365,10 +502,12 @@ fn parse_unit<R: ReaderType>(
     }
 }
 
+/// The CFA offsets of the rows of `unwind_section`, and all of its rows with their saved
+/// registers.
 fn parse_unwind_section<R: Reader, U: UnwindSection<R>>(
     view: &BinaryView,
     unwind_section: U,
-) -> gimli::Result<iset::IntervalMap<u64, i64>>
+) -> gimli::Result<FrameInfoTable>
 where
     <U as UnwindSection<R>>::Offset: std::hash::Hash,
 {
@@ -407,14 +546,17 @@ where
         bases = bases.set_got(section.start());
     }
 
+    let arch = view.default_arch();
+    let register_name = |register| dwarf_register_name(arch.as_ref(), register);
+
     let mut cies = HashMap::new();
-    let mut cfa_offsets = iset::IntervalMap::new();
+    let mut frame_info = FrameInfoTable::new(PARSER_NAME);
 
     let mut entries = unwind_section.entries(&bases);
     let mut unwind_context = UnwindContext::new();
     loop {
         match entries.next()? {
-            None => return Ok(cfa_offsets),
+            None => return Ok(frame_info),
             Some(gimli::CieOrFde::Cie(_cie)) => {
                 // TODO: do we want to do anything with standalone CIEs?
             }
@@ -433,7 +575,7 @@ where
 
                 if fde.len() == 0 {
                     // This FDE is a terminator
-                    return Ok(cfa_offsets);
+                    return Ok(frame_info);
                 }
 
                 if fde.initial_address().overflowing_add(fde.len()).1 {
@@ -448,27 +590,30 @@ where
                     let mut fde_table = fde.rows(&unwind_section, &bases, &mut unwind_context)?;
 
                     while let Some(row) = fde_table.next_row()? {
-                        match row.cfa() {
-                            CfaRule::RegisterAndOffset {
-                                register: _,
-                                offset,
-                            } => {
-                                // TODO: we should store offsets by register
-                                if row.start_address() < row.end_address() {
-                                    cfa_offsets
-                                        .insert(row.start_address()..row.end_address(), *offset);
-                                } else {
-                                    debug!(
-                                        "Invalid FDE table row addresses: {:#x}..{:#x}",
-                                        row.start_address(),
-                                        row.end_address()
-                                    );
+                        let saved_registers = row
+                            .registers()
+                            .filter_map(|(register, rule)| match rule {
+                                RegisterRule::Offset(offset) => Some(SavedRegister {
+                                    register: register_name(*register),
+                                    cfa_offset: *offset,
+                                }),
+                                _ => None,
+                            })
+                            .collect();
+                        let cfa = match row.cfa() {
+                            CfaRule::RegisterAndOffset { register, offset } => {
+                                Cfa::RegisterOffset {
+                                    register: register_name(*register),
+                                    offset: *offset,
                                 }
                             }
-                            CfaRule::Expression(_) => {
-                                debug!("Unhandled CFA expression when determining offset");
-                            }
+                            CfaRule::Expression(_) => Cfa::Expression,
                         };
+                        frame_info.add(FrameRow {
+                            range: row.start_address()..row.end_address(),
+                            cfa,
+                            saved_registers,
+                        });
                     }
                 }
             }
@@ -476,35 +621,71 @@ where
     }
 }
 
-fn get_supplementary_build_id(bv: &BinaryView) -> Option<String> {
-    let raw_view = bv.raw_view()?;
-    if let Some(section) = raw_view.section_by_name(".gnu_debugaltlink") {
-        let start = section.start();
-        let len = section.len();
+/// Load the supplementary file `debug_view` refers to, looking next to it and in the debug
+/// directories by path, then by build id.
+fn load_supplementary_file(
+    bv: &BinaryView,
+    debug_view: &BinaryView,
+    progress: &ProgressScope,
+) -> Option<Ref<BinaryView>> {
+    let link = supplementary_link(debug_view)?;
+    let sup_view = match find_supplementary_file(debug_view, &link, &debug_directories(bv)) {
+        Some(path) => binaryninja::load_with_options(
+            path,
+            false,
+            Some("{\"analysis.debugInfo.internal\": false}"),
+        ),
+        None => link
+            .build_id()
+            .and_then(|build_id| load_debug_info_for_build_id(&build_id, bv, progress.clone()).0),
+    };
+    let Some(sup_view) = sup_view else {
+        warn!(
+            "Unable to find supplementary debug file `{}`. Debug information may be incomplete.",
+            link.filename
+        );
+        return None;
+    };
+    if !matches_supplementary_file(&link, &sup_view) {
+        warn!(
+            "Supplementary debug file `{}` does not match the checksum in .debug_sup, ignoring it",
+            sup_view.file().filename()
+        );
+        return None;
+    }
+    sup_view.raw_view()
+}
 
-        if len < 20 {
-            // Not large enough to hold a build id
-            return None;
+/// Resolve the skeleton units of `dwarf` to their split units in the `.dwp` package `dwp_bv`.
+fn load_split_units(
+    dwp_bv: &BinaryView,
+    dwarf: &Dwarf<SectionReader>,
+    skeletons: &[&Unit<SectionReader>],
+) -> Vec<SplitUnit<SectionReader>> {
+    let dwp = match DwarfReaderContext::new(dwp_bv).load_dwp() {
+        Ok(dwp) => dwp,
+        Err(e) => {
+            error!("Failed to load .dwp file: {}", e);
+            return vec![];
         }
-
-        raw_view
-            .read_vec(start, len)
-            .splitn(2, |x| *x == 0)
-            .last()
-            .map(|a| a.iter().map(|b| format!("{:02x}", b)).collect())
-    } else {
-        None
-    }
+    };
+    dwp.split_units(dwarf, skeletons.iter().copied())
+        .filter_map(|split_unit| {
+            split_unit
+                .map_err(|e| warn!("Failed to read split unit from .dwp file: {}", e))
+                .ok()
+        })
+        .collect()
 }
 
 fn parse_dwarf(
-    _bv: &BinaryView,
+    bv: &BinaryView,
     debug_bv: &BinaryView,
     supplementary_bv: Option<&BinaryView>,
-    progress: Box<dyn Fn(usize, usize) -> Result<(), ()>>,
-) -> Result<DebugInfoBuilder, ()> {
-    // TODO: warn if no supplementary file and .gnu_debugaltlink section present
-
+    dwp_bv: Option<&BinaryView>,
+    selection: &UnitSelection,
+    progress: ProgressScope,
+) -> Result<(DebugInfoBuilder, Option<UnitIndex>), ()> {
     // Determine if this is a DWO
     // TODO : Make this more robust...some DWOs follow non-DWO conventions
 
@@ -516,14 +697,9 @@ fn parse_dwarf(
         raw_view
     };
 
-    let dwo_file = is_dwo_dwarf(view) || is_raw_dwo_dwarf(view);
-
     // gimli setup
-    let endian = get_endian(view);
-    let mut section_reader =
-        |section_id: SectionId| -> _ { create_section_reader(section_id, view, endian, dwo_file) };
-
-    let mut dwarf = match Dwarf::load(&mut section_reader) {
+    let reader = DwarfReaderContext::new(view);
+    let mut dwarf = match reader.load_dwarf() {
         Ok(x) => x,
         Err(e) => {
             error!("Failed to load DWARF info: {}", e);
@@ -531,46 +707,33 @@ fn parse_dwarf(
         }
     };
 
-    if dwo_file {
-        dwarf.file_type = DwarfFileType::Dwo;
-    } else {
-        dwarf.file_type = DwarfFileType::Main;
-    }
-
     if let Some(sup_bv) = supplementary_bv {
-        let sup_endian = get_endian(sup_bv);
-        let sup_dwo_file = is_dwo_dwarf(sup_bv) || is_raw_dwo_dwarf(sup_bv);
-        let sup_section_reader = |section_id: SectionId| -> _ {
-            create_section_reader(section_id, sup_bv, sup_endian, sup_dwo_file)
-        };
-        if let Err(e) = dwarf.load_sup(sup_section_reader) {
+        if let Err(e) = DwarfReaderContext::new(sup_bv).load_sup(&mut dwarf) {
             error!("Failed to load supplementary file: {}", e);
         }
     }
 
-    let range_data_offsets;
-    if view.section_by_name(".eh_frame").is_some() || view.section_by_name("__eh_frame").is_some() {
-        let eh_frame_endian = get_endian(view);
-        let eh_frame_section_reader = |section_id: SectionId| -> _ {
-            create_section_reader(section_id, view, eh_frame_endian, dwo_file)
-        };
-        let mut eh_frame = gimli::EhFrame::load(eh_frame_section_reader).unwrap();
+    // Frame information doesn't come from units, a lazy import stored all of it when indexing
+    let frame_info;
+    if let UnitSelection::Only(_) = selection {
+        let mut stored = FrameInfoTable::for_importer(bv, PARSER_NAME)
+            .unwrap_or_else(|| FrameInfoTable::new(PARSER_NAME));
+        // The stored table is rebased to the view, units are parsed at the addresses of the DWARF
+        stored.rebase(bv.original_image_base().wrapping_sub(bv.start()));
+        frame_info = stored;
+    } else if reader.has_section(SectionId::EhFrame) {
+        let mut eh_frame = gimli::EhFrame::load(|section_id| reader.section(section_id)).unwrap();
         eh_frame.set_address_size(view.address_size() as u8);
-        range_data_offsets = parse_unwind_section(view, eh_frame)
+        frame_info = parse_unwind_section(view, eh_frame)
             .map_err(|e| error!("Error parsing .eh_frame: {}", e))?;
-    } else if view.section_by_name(".debug_frame").is_some()
-        || view.section_by_name("__debug_frame").is_some()
-    {
-        let debug_frame_endian = get_endian(view);
-        let debug_frame_section_reader = |section_id: SectionId| -> _ {
-            create_section_reader(section_id, view, debug_frame_endian, dwo_file)
-        };
-        let mut debug_frame = gimli::DebugFrame::load(debug_frame_section_reader).unwrap();
+    } else if reader.has_section(SectionId::DebugFrame) {
+        let mut debug_frame =
+            gimli::DebugFrame::load(|section_id| reader.section(section_id)).unwrap();
         debug_frame.set_address_size(view.address_size() as u8);
-        range_data_offsets = parse_unwind_section(view, debug_frame)
+        frame_info = parse_unwind_section(view, debug_frame)
             .map_err(|e| error!("Error parsing .debug_frame: {}", e))?;
     } else {
-        range_data_offsets = Default::default();
+        frame_info = FrameInfoTable::new(PARSER_NAME);
     }
 
     // Create debug info builder and recover name mapping first
@@ -578,68 +741,125 @@ fn parse_dwarf(
     //   it is not possible to correctly track namespaces while you're parsing "in order" without backtracking,
     //   so we just do it up front
     let mut debug_info_builder = DebugInfoBuilder::new();
-    debug_info_builder.set_range_data_offsets(range_data_offsets);
+    debug_info_builder.set_frame_info(frame_info);
+    debug_info_builder.set_arch(view.default_arch());
+    debug_info_builder.set_components_from_source_paths(Settings::new().get_bool_with_opts(
+        "analysis.debugInfo.componentsFromSourcePaths",
+        &mut QueryOptions::new_with_view(bv),
+    ));
+    debug_info_builder.set_components_from_namespaces(Settings::new().get_bool_with_opts(
+        "analysis.debugInfo.componentsFromNamespaces",
+        &mut QueryOptions::new_with_view(bv),
+    ));
+
+    if let UnitSelection::Index = selection {
+        match reader.load_unit_index(&dwarf) {
+            Ok(index) => return Ok((debug_info_builder, Some(index))),
+            Err(e) => warn!("Failed to index DWARF units, importing all of them: {}", e),
+        }
+    }
 
     if let Some(mut debug_info_builder_context) = DebugInfoBuilderContext::new(view, &dwarf) {
-        calculate_total_unit_bytes(&dwarf, &mut debug_info_builder_context);
-
-        let progress_weights = [0.5, 0.5];
-        let name_progress = split_progress(&progress, 0, &progress_weights);
-        let parse_progress = split_progress(&progress, 1, &progress_weights);
-
-        if !recover_names(&dwarf, &mut debug_info_builder_context, &name_progress)
-            || debug_info_builder_context.total_die_count == 0
+        let split_units = match dwp_bv {
+            Some(dwp_bv) => {
+                let skeletons: Vec<_> = debug_info_builder_context
+                    .units()
+                    .iter()
+                    .filter(|unit| selection.includes(unit.header.offset()))
+                    .collect();
+                load_split_units(dwp_bv, &dwarf, &skeletons)
+            }
+            None => vec![],
+        };
+        calculate_total_unit_bytes(&dwarf, &split_units, &mut debug_info_builder_context);
+
+        let parts = progress.subscopes(&[1, 1]);
+        let (name_progress, parse_progress) = (&parts[0], &parts[1]);
+
+        if !recover_names(
+            &dwarf,
+            &split_units,
+            selection,
+            &mut debug_info_builder_context,
+            name_progress,
+        ) || debug_info_builder_context.total_die_count == 0
         {
-            return Ok(debug_info_builder);
+            return Ok((debug_info_builder, None));
         }
 
-        // Parse all the compilation units
+        // Parse the selected compilation units, which pull in the types they use from others
         let mut current_die_number = 0;
 
-        for unit in debug_info_builder_context.sup_units() {
+        let sup_units = match selection {
+            UnitSelection::Only(_) => &[],
+            _ => debug_info_builder_context.sup_units(),
+        };
+        for unit in sup_units {
             parse_unit(
                 dwarf.sup().unwrap(),
                 unit,
                 &debug_info_builder_context,
                 &mut debug_info_builder,
-                &parse_progress,
+                parse_progress,
                 &mut current_die_number,
             );
         }
 
         for unit in debug_info_builder_context.units() {
+            if !selection.includes(unit.header.offset()) {
+                continue;
+            }
             parse_unit(
                 &dwarf,
                 unit,
                 &debug_info_builder_context,
                 &mut debug_info_builder,
-                &parse_progress,
+                parse_progress,
+                &mut current_die_number,
+            );
+        }
+
+        for split_unit in &split_units {
+            parse_unit(
+                &split_unit.dwarf,
+                &split_unit.unit,
+                &debug_info_builder_context,
+                &mut debug_info_builder,
+                parse_progress,
                 &mut current_die_number,
             );
         }
     }
 
-    Ok(debug_info_builder)
+    Ok((debug_info_builder, None))
+}
+
+/// The tables `builder` filled besides the debug info, stored once the debug info is applied.
+fn parsed_tables(
+    builder: &DebugInfoBuilder,
+    selection: &UnitSelection,
+    index: Option<UnitIndex>,
+) -> ParsedTables {
+    let lazy_units = matches!(selection, UnitSelection::Only(_));
+    ParsedTables {
+        diagnostics: builder.diagnostics().clone(),
+        inlined_calls: builder.inlined_calls().clone(),
+        source_lines: builder.source_lines().clone(),
+        frame_info: (!lazy_units).then(|| builder.frame_info().clone()),
+        merge: lazy_units,
+        index,
+    }
 }
 
 struct DWARFParser;
 
 impl CustomDebugInfoParser for DWARFParser {
     fn is_valid(&self, view: &BinaryView) -> bool {
-        if dwarfreader::is_valid(view) || dwarfreader::can_use_debuginfod(view) {
-            return true;
-        }
-        if dwarfreader::has_build_id_section(view) {
-            if let Ok(build_id) = get_build_id(view) {
-                if helpers::find_local_debug_file_for_build_id(&build_id, view).is_some() {
-                    return true;
-                }
-            }
-        }
-        if helpers::find_sibling_debug_file(view).is_some() {
-            return true;
-        }
-        false
+        // Only cheap checks here, `parse_info` searches the debug directories
+        dwarfreader::is_valid(view)
+            || dwarfreader::can_use_debuginfod(view)
+            || helpers::find_sibling_debug_file(view).is_some()
+            || locator::may_have_debug_file(view)
     }
 
     fn parse_info(
@@ -647,13 +867,21 @@ impl CustomDebugInfoParser for DWARFParser {
         debug_info: &mut DebugInfo,
         bv: &BinaryView,
         debug_file: &BinaryView,
-        progress: Box<dyn Fn(usize, usize) -> Result<(), ()>>,
+        progress: ProgressScope,
     ) -> bool {
+        // Fetching debug files from debuginfod can take a while, so give it part of the progress
+        let parts = progress.subscopes(&[1, 4]);
+        let (fetch_progress, parse_progress) = (&parts[0], &parts[1]);
+
         let (external_file, close_external) = if !dwarfreader::is_valid(bv) {
             if let (Some(debug_view), x) = helpers::load_sibling_debug_file(bv) {
                 (Some(debug_view), x)
-            } else if let Ok(build_id) = get_build_id(bv) {
-                load_debug_info_for_build_id(&build_id, bv)
+            } else if let Some(debug_view) =
+                helpers::external_debug_file(bv).and_then(|path| helpers::load_debug_file(bv, path))
+            {
+                (Some(debug_view), false)
+            } else if let Ok(build_id) = debuginfod::build_id(bv) {
+                load_debug_info_for_build_id(&build_id, bv, fetch_progress.clone())
             } else {
                 (None, false)
             }
@@ -661,34 +889,86 @@ impl CustomDebugInfoParser for DWARFParser {
             (None, false)
         };
 
-        let sup_bv = get_supplementary_build_id(external_file.as_deref().unwrap_or(debug_file))
-            .and_then(|build_id| {
-                load_debug_info_for_build_id(&build_id, bv)
-                    .0
-                    .map(|x| x.raw_view().unwrap())
-            });
+        let sup_bv = load_supplementary_file(
+            bv,
+            external_file.as_deref().unwrap_or(debug_file),
+            fetch_progress,
+        );
+        if fetch_progress.finish().is_err() {
+            return false;
+        }
+        let dwp_bv = load_dwp_file(bv);
+
+        let selection = match lazy::take_selection() {
+            Some(selection) => selection,
+            None if Settings::new().get_bool_with_opts(
+                lazy::LAZY_IMPORT_SETTING,
+                &mut QueryOptions::new_with_view(bv),
+            ) =>
+            {
+                UnitSelection::Index
+            }
+            None => UnitSelection::All,
+        };
 
         let result = match parse_dwarf(
             bv,
             external_file.as_deref().unwrap_or(debug_file),
             sup_bv.as_deref(),
-            progress,
+            dwp_bv.as_deref(),
+            &selection,
+            parse_progress.clone(),
         ) {
-            Ok(mut builder) => {
+            Ok((mut builder, index)) => {
                 builder.post_process(bv, debug_info).commit_info(debug_info);
+                apply::keep(bv, parsed_tables(&builder, &selection, index));
                 true
             }
-            Err(_) => false,
+            Err(_) => {
+                let mut diagnostics = ImportDiagnostics::new(PARSER_NAME);
+                diagnostics.warn("Unable to read DWARF information, nothing was imported");
+                apply::keep(bv, ParsedTables::failed(diagnostics));
+                false
+            }
         };
 
         if let (Some(ext), true) = (external_file, close_external) {
             ext.file().close();
         }
+        if let Some(dwp_bv) = dwp_bv {
+            dwp_bv.file().close();
+        }
 
         result
     }
 }
 
+/// The settings of the components functions are placed into.
+fn register_component_settings(settings: &Settings) {
+    // Also registered by the PDB import, whichever plugin loads first registers it
+    settings.register_setting_json(
+        "analysis.debugInfo.componentsFromSourcePaths",
+        r#"{
+            "title" : "Create Components from Source Paths",
+            "type" : "boolean",
+            "default" : false,
+            "description" : "Place functions into components following the directories of the source files they were compiled from.",
+            "ignore" : []
+        }"#,
+    );
+
+    settings.register_setting_json(
+        "analysis.debugInfo.componentsFromNamespaces",
+        r#"{
+            "title" : "Create Components from Namespaces",
+            "type" : "boolean",
+            "default" : false,
+            "description" : "Place functions into components following the namespaces and modules they are declared in. Combined with source path components, the namespaces are nested under the source file.",
+            "ignore" : []
+        }"#,
+    );
+}
+
 #[no_mangle]
 pub extern "C" fn CorePluginInit() -> bool {
     Logger::new("DWARF").init();
@@ -719,7 +999,18 @@ pub extern "C" fn CorePluginInit() -> bool {
     );
 
     settings.register_setting_json(
-        "analysis.debugInfo.enableDebugDirectories",
+        debuginfod::CACHE_DIRECTORY_SETTING,
+        r#"{
+            "title" : "Debuginfod Cache Directory",
+            "type" : "string",
+            "default" : "",
+            "description" : "Directory debug info fetched from Debuginfod servers is cached in. Defaults to the debuginfod folder of the user directory.",
+            "ignore" : []
+        }"#,
+    );
+
+    settings.register_setting_json(
+        locator::ENABLE_DEBUG_DIRECTORIES_SETTING,
         r#"{
             "title" : "Enable Debug File Directories",
             "type" : "boolean",
@@ -730,13 +1021,36 @@ pub extern "C" fn CorePluginInit() -> bool {
     );
 
     settings.register_setting_json(
-        "analysis.debugInfo.debugDirectories",
+        locator::DEBUG_DIRECTORIES_SETTING,
         r#"{
             "title" : "Debug File Directories",
             "type" : "array",
             "sorted" : true,
             "default" : [],
-            "description" : "Paths to folder containing DWARF debug info stored by build id.",
+            "description" : "Paths to folders containing DWARF debug info stored by build id or by the path of the binary, searched after /usr/lib/debug.",
+            "ignore" : []
+        }"#,
+    );
+
+    settings.register_setting_json(
+        locator::DSYM_DIRECTORIES_SETTING,
+        r#"{
+            "title" : "dSYM Directories",
+            "type" : "array",
+            "sorted" : true,
+            "default" : [],
+            "description" : "Paths to folders containing .dSYM bundles, matched to Mach-O files by UUID.",
+            "ignore" : []
+        }"#,
+    );
+
+    settings.register_setting_json(
+        locator::SPOTLIGHT_SETTING,
+        r#"{
+            "title" : "Search Spotlight for dSYM Bundles",
+            "type" : "boolean",
+            "default" : true,
+            "description" : "On macOS, ask Spotlight for the .dSYM bundle matching the UUID of a Mach-O file.",
             "ignore" : []
         }"#,
     );
@@ -752,6 +1066,242 @@ pub extern "C" fn CorePluginInit() -> bool {
         }"#,
     );
 
-    DebugInfoParser::register("DWARF", DWARFParser {});
+    settings.register_setting_json(
+        "analysis.debugInfo.loadDwpFiles",
+        r#"{
+            "title" : "Enable Loading of Split DWARF Packages",
+            "type" : "boolean",
+            "default" : true,
+            "description" : "Enable automatic loading of the X.dwp split DWARF package next to a file named X.",
+            "ignore" : []
+        }"#,
+    );
+
+    register_component_settings(&settings);
+
+    settings.register_setting_json(
+        lazy::LAZY_IMPORT_SETTING,
+        r#"{
+            "title" : "Import DWARF Lazily",
+            "type" : "boolean",
+            "default" : false,
+            "description" : "Only index the DWARF compilation units when loading a file, and import each unit when analysis adds a function in it. Speeds up loading files with a lot of debug info. Types are imported with the functions using them, or from the unit defining a name with the 'Import DWARF Debug Info for Name' command.",
+            "ignore" : []
+        }"#,
+    );
+
+    DebugInfoParser::register(PARSER_NAME, DWARFParser {});
+    if !apply::register_activity() || !lazy::register_activity() {
+        return false;
+    }
+    register_command(
+        "Import DWARF Debug Info for Name",
+        "Import the DWARF compilation units defining a function or type, while importing lazily",
+        lazy::ImportNameCommand,
+    );
     true
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dwarfdebuginfo::register_cfa_offset;
+    use binaryninja::headless::Session;
+    use binaryninja::variable::VariableSourceType;
+    use std::path::PathBuf;
+
+    fn no_progress() -> ProgressScope {
+        ProgressScope::new(|_, _| true)
+    }
+
+    #[test]
+    fn imports_one_unit_lazily() {
+        let session = Session::new().expect("Failed to initialize session");
+        let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+        // Keep the core from importing the debug info itself
+        let view = session
+            .load_with_options(
+                out_dir.join("lazy_units"),
+                true,
+                Some(r#"{"analysis.debugInfo.internal": false}"#),
+            )
+            .expect("Failed to load view");
+        let leaf = view.symbol_by_raw_name("leaf").unwrap().address();
+        let other = view.symbol_by_raw_name("other").unwrap().address();
+
+        let (builder, index) = parse_dwarf(
+            &view,
+            &view,
+            None,
+            None,
+            &UnitSelection::Index,
+            no_progress(),
+        )
+        .unwrap();
+        let index = index.expect("Units were not indexed");
+        assert_eq!(index.len(), 2);
+        // Indexing stores the frame info the units are parsed with
+        apply::keep(&view, parsed_tables(&builder, &UnitSelection::Index, None));
+        apply::store(&view);
+
+        let units = index.units_at(leaf);
+        assert_eq!(units.len(), 1);
+        let selection = UnitSelection::Only(units);
+        let (mut builder, _) =
+            parse_dwarf(&view, &view, None, None, &selection, no_progress()).unwrap();
+        let mut debug_info = view.debug_info();
+        builder
+            .post_process(&view, &mut debug_info)
+            .commit_info(&mut debug_info);
+        view.apply_debug_info(&debug_info);
+        view.update_analysis_and_wait();
+
+        let functions = debug_info.functions();
+        assert!(functions.iter().all(|func| func.address() != other));
+        let leaf_info = functions
+            .iter()
+            .find(|func| func.address() == leaf)
+            .expect("The function of the unit was not imported");
+        let counter = leaf_info
+            .local_variables()
+            .iter()
+            .find(|var| var.name == "counter")
+            .expect("The local of the function was not imported");
+        assert_eq!(
+            counter.variable.ty,
+            VariableSourceType::StackVariableSourceType
+        );
+        // `DW_OP_fbreg -24` from the CFA, which is 8 bytes above the stack pointer at entry
+        assert_eq!(counter.variable.storage, -16);
+        let leaf_function = view
+            .function_at(&view.default_platform().unwrap(), leaf)
+            .unwrap();
+        assert!(leaf_function
+            .stack_layout()
+            .iter()
+            .any(|var| var.name == "counter" && var.variable == counter.variable));
+    }
+
+    #[test]
+    fn places_functions_into_namespace_components() {
+        let session = Session::new().expect("Failed to initialize session");
+        register_component_settings(&Settings::new());
+        let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+        let view = session
+            .load_with_options(
+                out_dir.join("namespaces"),
+                true,
+                Some(
+                    r#"{
+                        "analysis.debugInfo.internal": false,
+                        "analysis.debugInfo.componentsFromNamespaces": true
+                    }"#,
+                ),
+            )
+            .expect("Failed to load view");
+        let connect = view
+            .symbol_by_raw_name("_ZN3net3tcp7connectEi")
+            .unwrap()
+            .address();
+        let send = view.symbol_by_raw_name("_ZN3net4sendEi").unwrap().address();
+        let main = view.symbol_by_raw_name("main").unwrap().address();
+
+        let (mut builder, _) =
+            parse_dwarf(&view, &view, None, None, &UnitSelection::All, no_progress()).unwrap();
+        let mut debug_info = view.debug_info();
+        builder
+            .post_process(&view, &mut debug_info)
+            .commit_info(&mut debug_info);
+        let components = |address: u64| -> Vec<String> {
+            let functions = debug_info.functions_by_name(PARSER_NAME);
+            let function = functions.iter().find(|f| f.address() == address).unwrap();
+            function.components().to_vec()
+        };
+        assert_eq!(components(connect), ["net", "tcp"]);
+        assert_eq!(components(send), ["net"]);
+        assert!(components(main).is_empty());
+
+        view.apply_debug_info(&debug_info);
+        view.update_analysis_and_wait();
+        assert_eq!(debug_info.apply_components(&view, PARSER_NAME), 2);
+        let tcp = view.create_component_path(&["net", "tcp"]).unwrap();
+        let connect_function = view
+            .function_at(&view.default_platform().unwrap(), connect)
+            .unwrap();
+        assert!(tcp.contains_function(&connect_function));
+        assert_eq!(tcp.parent().unwrap().functions().len(), 1);
+    }
+
+    #[test]
+    fn reports_thread_local_variables() {
+        let session = Session::new().expect("Failed to initialize session");
+        let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+        let view = session
+            .load_with_options(
+                out_dir.join("lazy_units"),
+                true,
+                Some(r#"{"analysis.debugInfo.internal": false}"#),
+            )
+            .expect("Failed to load view");
+
+        let (builder, _) =
+            parse_dwarf(&view, &view, None, None, &UnitSelection::All, no_progress()).unwrap();
+        assert!(builder
+            .diagnostics()
+            .skipped()
+            .iter()
+            .any(|item| item.kind == "thread-local variable" && item.reason.contains("`calls`")));
+    }
+
+    fn row(range: std::ops::Range<u64>, register: &str, offset: i64) -> FrameRow {
+        FrameRow {
+            range,
+            cfa: Cfa::RegisterOffset {
+                register: register.to_string(),
+                offset,
+            },
+            saved_registers: vec![],
+        }
+    }
+
+    #[test]
+    fn finds_the_cfa_offset_of_a_register() {
+        // push rbx; sub rsp, 0x20; ...; add rsp, 0x20; pop rbx; ret, then the next function
+        let stack_pointer_frame = [
+            row(0x1000..0x1001, "rsp", 8),
+            row(0x1001..0x1005, "rsp", 16),
+            row(0x1005..0x1030, "rsp", 48),
+            row(0x1030..0x1031, "rsp", 16),
+            row(0x1031..0x1032, "rsp", 8),
+            row(0x1032..0x1040, "rsp", 8),
+        ];
+        // Without a range the stack pointer is taken after the prologue
+        assert_eq!(
+            register_cfa_offset(&stack_pointer_frame, 0x1000, false, "rsp"),
+            Some(48)
+        );
+        // With one where the location starts
+        assert_eq!(
+            register_cfa_offset(&stack_pointer_frame, 0x1001, true, "rsp"),
+            Some(16)
+        );
+        assert_eq!(
+            register_cfa_offset(&stack_pointer_frame, 0x1000, false, "rbp"),
+            None
+        );
+
+        // push rbp; mov rbp, rsp; ...; leave; ret
+        let frame_pointer_frame = [
+            row(0x2000..0x2001, "rsp", 8),
+            row(0x2001..0x2004, "rsp", 16),
+            row(0x2004..0x2020, "rbp", 16),
+            row(0x2020..0x2021, "rsp", 8),
+        ];
+        for live in [false, true] {
+            assert_eq!(
+                register_cfa_offset(&frame_pointer_frame, 0x2000, live, "rbp"),
+                Some(16)
+            );
+        }
+    }
+}
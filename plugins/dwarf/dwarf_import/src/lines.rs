@@ -0,0 +1,130 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::dwarfdebuginfo::DebugInfoBuilder;
+use crate::ReaderType;
+
+use binaryninja::source_lines::SourceLineTable;
+use gimli::{AttributeValue, ColumnType, Dwarf, LineProgramHeader, LineRow, Unit};
+use log::warn;
+use std::collections::HashMap;
+
+// The row of a line program waiting for the row after it, which ends its address range
+struct PendingRow {
+    address: u64,
+    path: Option<String>,
+    line: u64,
+    column: Option<u64>,
+}
+
+// Linkers point the sequences of discarded functions at one of these
+fn is_tombstone(address: u64) -> bool {
+    [u64::MAX, u64::MAX - 1, u32::MAX as u64, u32::MAX as u64 - 1].contains(&address)
+}
+
+fn attr_to_string<R: ReaderType>(
+    dwarf: &Dwarf<R>,
+    unit: &Unit<R>,
+    attr: AttributeValue<R>,
+) -> Option<String> {
+    let attr = dwarf.attr_string(unit, attr).ok()?;
+    Some(attr.to_string_lossy().ok()?.into_owned())
+}
+
+fn file_path<R: ReaderType>(
+    dwarf: &Dwarf<R>,
+    unit: &Unit<R>,
+    header: &LineProgramHeader<R>,
+    row: &LineRow,
+) -> Option<String> {
+    let file = row.file(header)?;
+    let name = attr_to_string(dwarf, unit, file.path_name())?;
+    let directory = file
+        .directory(header)
+        .and_then(|dir| attr_to_string(dwarf, unit, dir));
+    let absolute = name.starts_with(['/', '\\']) || name.get(1..2) == Some(":");
+    match directory {
+        Some(dir) if !absolute && !dir.is_empty() => {
+            let separator = if dir.ends_with(['/', '\\']) { "" } else { "/" };
+            Some(format!("{dir}{separator}{name}"))
+        }
+        _ => Some(name),
+    }
+}
+
+/// Add the rows of the line program of `unit` to the line table of `debug_info_builder`.
+pub(crate) fn parse_line_program<R: ReaderType>(
+    dwarf: &Dwarf<R>,
+    unit: &Unit<R>,
+    debug_info_builder: &mut DebugInfoBuilder,
+) {
+    let Some(program) = unit.line_program.clone() else {
+        return;
+    };
+    let table: &mut SourceLineTable = debug_info_builder.source_lines_mut();
+    let mut paths: HashMap<u64, Option<String>> = HashMap::new();
+    let mut pending: Option<PendingRow> = None;
+    let mut sequence_start = true;
+    let mut discarded_sequence = false;
+
+    let mut rows = program.rows();
+    loop {
+        let (header, row) = match rows.next_row() {
+            Ok(Some(next)) => next,
+            Ok(None) => break,
+            Err(e) => {
+                warn!("Failed to read line program, source lines may be incomplete: {e}");
+                break;
+            }
+        };
+        if sequence_start {
+            discarded_sequence = is_tombstone(row.address());
+            sequence_start = false;
+        }
+
+        if let Some(previous) = pending.take() {
+            if let (false, Some(path)) = (discarded_sequence, &previous.path) {
+                table.add(
+                    previous.address..row.address(),
+                    path,
+                    previous.line,
+                    previous.column,
+                );
+            }
+        }
+
+        if row.end_sequence() {
+            sequence_start = true;
+            continue;
+        }
+        // Line zero marks code that isn't from any line, such as compiler generated code
+        let Some(line) = row.line() else {
+            continue;
+        };
+        let column = match row.column() {
+            ColumnType::LeftEdge => None,
+            ColumnType::Column(column) => Some(column.get()),
+        };
+        let path = paths
+            .entry(row.file_index())
+            .or_insert_with(|| file_path(dwarf, unit, header, row))
+            .clone();
+        pending = Some(PendingRow {
+            address: row.address(),
+            path,
+            line: line.get(),
+            column,
+        });
+    }
+}
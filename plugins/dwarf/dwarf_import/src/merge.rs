@@ -0,0 +1,171 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use binaryninja::{
+    binary_view::{BinaryView, BinaryViewExt},
+    debuginfo::DebugInfo,
+    rc::Array,
+    type_library::TypeLibrary,
+    types::{StructureType, Type, TypeClass},
+};
+
+/// Types a view already has under a name, from its analysis, its type libraries and the debug info
+/// of other parsers. DWARF types equal to one of these are not imported again, which would only
+/// add the same type a second time with a numeric suffix.
+pub(crate) struct ExistingTypes<'a> {
+    view: &'a BinaryView,
+    debug_info: &'a DebugInfo,
+    libraries: Array<TypeLibrary>,
+}
+
+impl<'a> ExistingTypes<'a> {
+    pub(crate) fn new(view: &'a BinaryView, debug_info: &'a DebugInfo) -> Self {
+        Self {
+            view,
+            debug_info,
+            libraries: view.type_libraries(),
+        }
+    }
+
+    /// Whether a type named `name` that is structurally equal to `ty` already exists.
+    pub(crate) fn contains_equivalent(&self, name: &str, ty: &Type) -> bool {
+        if self
+            .view
+            .type_by_name(name)
+            .is_some_and(|existing| structurally_equal(&existing, ty))
+        {
+            return true;
+        }
+        if self.libraries.iter().any(|library| {
+            library
+                .get_named_type(name.into())
+                .is_some_and(|existing| structurally_equal(&existing, ty))
+        }) {
+            return true;
+        }
+        // The name of a debug type is the parser it came from
+        self.debug_info
+            .get_types_by_name(name)
+            .iter()
+            .filter(|existing| existing.name != crate::PARSER_NAME)
+            .any(|existing| structurally_equal(&existing.ty.contents, ty))
+    }
+}
+
+/// Referenced type names as written by different producers only differ in whitespace, such as
+/// `basic_string<char,std::char_traits<char>>` and `basic_string<char, std::char_traits<char> >`.
+fn same_type_name(a: &str, b: &str) -> bool {
+    a.chars()
+        .filter(|c| !c.is_whitespace())
+        .eq(b.chars().filter(|c| !c.is_whitespace()))
+}
+
+fn same_optional_type(a: Option<&Type>, b: Option<&Type>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => structurally_equal(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+/// Whether two types have the same layout and members, ignoring confidence, `const` and
+/// `volatile`. Types referenced by name are equal if their names are.
+pub(crate) fn structurally_equal(a: &Type, b: &Type) -> bool {
+    if a == b {
+        return true;
+    }
+    if a.type_class() != b.type_class() {
+        return false;
+    }
+
+    match a.type_class() {
+        TypeClass::NamedTypeReferenceClass => {
+            match (a.get_named_type_reference(), b.get_named_type_reference()) {
+                (Some(a), Some(b)) => same_type_name(&a.name().to_string(), &b.name().to_string()),
+                _ => false,
+            }
+        }
+        TypeClass::VoidTypeClass | TypeClass::VarArgsTypeClass => true,
+        TypeClass::BoolTypeClass | TypeClass::FloatTypeClass | TypeClass::WideCharTypeClass => {
+            a.width() == b.width()
+        }
+        TypeClass::IntegerTypeClass => {
+            a.width() == b.width() && a.is_signed().contents == b.is_signed().contents
+        }
+        TypeClass::PointerTypeClass => {
+            a.width() == b.width()
+                && same_optional_type(
+                    a.target().as_ref().map(|t| t.contents.as_ref()),
+                    b.target().as_ref().map(|t| t.contents.as_ref()),
+                )
+        }
+        TypeClass::ArrayTypeClass => {
+            a.count() == b.count()
+                && same_optional_type(
+                    a.element_type().as_ref().map(|t| t.contents.as_ref()),
+                    b.element_type().as_ref().map(|t| t.contents.as_ref()),
+                )
+        }
+        TypeClass::StructureTypeClass => {
+            let (Some(a_structure), Some(b_structure)) = (a.get_structure(), b.get_structure())
+            else {
+                return false;
+            };
+            // Classes and structures only differ in the default access of their members
+            let is_union = |ty: StructureType| ty == StructureType::UnionStructureType;
+            if a.width() != b.width()
+                || is_union(a_structure.structure_type()) != is_union(b_structure.structure_type())
+            {
+                return false;
+            }
+            let (a_members, b_members) = (a_structure.members(), b_structure.members());
+            a_members.len() == b_members.len()
+                && a_members.iter().zip(&b_members).all(|(a, b)| {
+                    a.offset == b.offset
+                        && a.name == b.name
+                        && structurally_equal(&a.ty.contents, &b.ty.contents)
+                })
+        }
+        TypeClass::EnumerationTypeClass => {
+            let (Some(a_enumeration), Some(b_enumeration)) =
+                (a.get_enumeration(), b.get_enumeration())
+            else {
+                return false;
+            };
+            let (a_members, b_members) = (a_enumeration.members(), b_enumeration.members());
+            a.width() == b.width()
+                && a_members.len() == b_members.len()
+                && a_members
+                    .iter()
+                    .zip(&b_members)
+                    .all(|(a, b)| a.name == b.name && a.value == b.value)
+        }
+        TypeClass::FunctionTypeClass => {
+            let (Some(a_params), Some(b_params)) = (a.parameters(), b.parameters()) else {
+                return false;
+            };
+            a.has_variable_arguments().contents == b.has_variable_arguments().contents
+                && same_optional_type(
+                    a.return_value().as_ref().map(|t| t.contents.as_ref()),
+                    b.return_value().as_ref().map(|t| t.contents.as_ref()),
+                )
+                && a_params.len() == b_params.len()
+                && a_params
+                    .iter()
+                    .zip(&b_params)
+                    .all(|(a, b)| structurally_equal(&a.ty.contents, &b.ty.contents))
+        }
+        _ => false,
+    }
+}
@@ -23,7 +23,8 @@ use binaryninja::{
     },
 };
 
-use gimli::{constants, AttributeValue, DebuggingInformationEntry, Dwarf, Operation, Unit};
+use dwarfreader::location::{variable_locations, VariableLocation};
+use gimli::{constants, AttributeValue, DebuggingInformationEntry, Dwarf, Unit};
 
 use log::{debug, error, warn};
 
@@ -49,44 +50,52 @@ pub(crate) fn parse_variable<R: ReaderType>(
         return;
     };
 
-    let AttributeValue::Exprloc(mut expression) = attr.value() else {
-        return;
-    };
-
-    match Operation::parse(&mut expression.0, unit.encoding()) {
-        Ok(Operation::FrameOffset { offset }) => {
-            debug_info_builder.add_stack_variable(
+    // Variables of functions are in registers or their frame, maybe in several over their lifetime
+    if function_index.is_some() {
+        let locations = match variable_locations(dwarf, unit, attr.value()) {
+            Ok(locations) => locations,
+            Err(e) => {
+                error!("Error parsing location of variable {:?}: {}", full_name, e);
+                return;
+            }
+        };
+        let is_static = locations
+            .iter()
+            .all(|x| matches!(x.location, VariableLocation::Address(_)));
+        if !is_static {
+            debug_info_builder.add_local_variable(
                 function_index,
-                offset,
+                &locations,
                 full_name,
                 type_uid,
                 lexical_block,
             );
+            return;
         }
-        //Ok(Operation::RegisterOffset { register: _, offset: _, base_type: _ }) => {
-        //    //TODO: look up register by index (binja register indexes don't match processor indexes?)
-        //    //TODO: calculate absolute stack offset
-        //    //TODO: add by absolute offset
-        //},
-        Ok(Operation::Address { address }) => {
+    }
+
+    let AttributeValue::Exprloc(expression) = attr.value() else {
+        return;
+    };
+
+    match get_static_location(dwarf, unit, expression) {
+        Ok(StaticLocation::Address(address)) => {
             if let Some(uid) = type_uid {
                 debug_info_builder.add_data_variable(address, full_name, uid)
             }
         }
-        Ok(Operation::AddressIndex { index }) => {
-            if let Some(uid) = type_uid {
-                if let Ok(address) = dwarf.address(unit, index) {
-                    debug_info_builder.add_data_variable(address, full_name, uid)
-                } else {
-                    warn!("Invalid index into IAT: {}", index.0);
-                }
-            }
+        Ok(StaticLocation::ThreadLocal(offset)) => {
+            debug!(
+                "Skipping thread-local variable {:?} at TLS offset {:#x}",
+                full_name, offset
+            );
+            debug_info_builder.skip_thread_local_variable(full_name.as_deref(), offset);
         }
-        Ok(op) => {
-            debug!("Unhandled operation type for variable: {:?}", op);
+        Ok(StaticLocation::Unsupported) => {
+            debug!("Unhandled location expression for variable {:?}", full_name);
         }
-        Err(e) => error!(
-            "Error parsing operation type for variable {:?}: {}",
+        Err(e) => warn!(
+            "Failed to evaluate location of variable {:?}: {}",
             full_name, e
         ),
     }
@@ -18,7 +18,7 @@ use binaryninja::{
     disassembly::{DisassemblyTextLine, InstructionTextToken, InstructionTextTokenKind},
     flowgraph::{BranchType, EdgeStyle, FlowGraph, FlowGraphNode, FlowGraphOption},
 };
-use dwarfreader::is_valid;
+use dwarfreader::{is_valid, DwarfReaderContext};
 
 use binaryninja::disassembly::StringType;
 use gimli::{
@@ -29,7 +29,6 @@ use gimli::{
     EntriesTreeNode,
     Reader,
     ReaderOffset,
-    SectionId,
     Unit,
     UnitSectionOffset,
 };
@@ -285,11 +284,9 @@ fn dump_dwarf(bv: &BinaryView) {
     graph_root.set_lines(["Graph Root".into()]);
     graph.append(&graph_root);
 
-    let endian = dwarfreader::get_endian(bv);
-    let section_reader = |section_id: SectionId| -> _ {
-        dwarfreader::create_section_reader(section_id, bv, endian, false)
-    };
-    let dwarf = Dwarf::load(&section_reader).unwrap();
+    let dwarf = DwarfReaderContext::new_with_dwo(bv, false)
+        .load_dwarf()
+        .unwrap();
 
     let mut iter = dwarf.units();
     while let Some(header) = iter.next().unwrap() {
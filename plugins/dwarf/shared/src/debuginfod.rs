@@ -0,0 +1,461 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fetching separate debug files from debuginfod servers.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use binaryninja::{
+    binary_view::{BinaryView, BinaryViewBase, BinaryViewExt},
+    download_provider::{DownloadInstanceInputOutputCallbacks, DownloadProvider},
+    progress::ProgressCallback,
+    rc::Ref,
+    settings::{QueryOptions, Settings},
+    Endianness,
+};
+
+use crate::Error;
+
+/// Setting holding the directory fetched debug files are cached in.
+pub const CACHE_DIRECTORY_SETTING: &str = "network.debuginfodCacheDirectory";
+
+/// Note type of a GNU build id note (`NT_GNU_BUILD_ID`).
+const NT_GNU_BUILD_ID: u32 = 3;
+
+/// The build id of `view`, as a lowercase hex string, read from its `.note.gnu.build-id` section.
+pub fn build_id(view: &BinaryView) -> Result<String, Error> {
+    let raw_view = view
+        .raw_view()
+        .ok_or(Error::MissingSection(".note.gnu.build-id"))?;
+    let section = raw_view
+        .section_by_name(".note.gnu.build-id")
+        .ok_or(Error::MissingSection(".note.gnu.build-id"))?;
+    let note = raw_view.read_vec(section.start(), section.len());
+    parse_build_id_note(&note, raw_view.default_endianness())
+}
+
+/// Parse the descriptor of a GNU build id note.
+pub fn parse_build_id_note(note: &[u8], endianness: Endianness) -> Result<String, Error> {
+    // Name size - 4 bytes
+    // Desc size - 4 bytes
+    // Type - 4 bytes
+    // Name - n bytes
+    // Desc - n bytes
+    let read_u32 = |offset: usize| {
+        let bytes: [u8; 4] = note[offset..offset + 4].try_into().unwrap();
+        match endianness {
+            Endianness::LittleEndian => u32::from_le_bytes(bytes),
+            Endianness::BigEndian => u32::from_be_bytes(bytes),
+        }
+    };
+
+    if note.len() < 12 {
+        return Err(Error::InvalidBuildId(
+            "build id note must be at least 12 bytes".to_string(),
+        ));
+    }
+    let name_len = read_u32(0) as usize;
+    let desc_len = read_u32(4) as usize;
+    let note_type = read_u32(8);
+    if note_type != NT_GNU_BUILD_ID {
+        return Err(Error::InvalidBuildId(format!(
+            "build id note has wrong type: {}",
+            note_type
+        )));
+    }
+
+    // The descriptor starts after the name, padded to 4 bytes
+    let desc_start = 12 + name_len.next_multiple_of(4);
+    let Some(desc) = note.get(desc_start..desc_start + desc_len) else {
+        return Err(Error::InvalidBuildId(format!(
+            "build id note not expected length: expected {}, got {}",
+            desc_start + desc_len,
+            note.len()
+        )));
+    };
+    if desc.is_empty() {
+        return Err(Error::InvalidBuildId("build id is empty".to_string()));
+    }
+    Ok(desc.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Default cache directory, used when [`CACHE_DIRECTORY_SETTING`] is empty.
+pub fn default_cache_directory() -> PathBuf {
+    binaryninja::user_directory().join("debuginfod")
+}
+
+/// Client for a list of debuginfod servers, with a local cache in front of them.
+///
+/// Files are identified by the build id in their `.note.gnu.build-id` section. Each server is
+/// asked for `/buildid/<build id>/debuginfo` in turn, and the first file found is stored in the
+/// cache directory, laid out the same way as the cache of the reference debuginfod client, so
+/// that later fetches do not need the network.
+///
+/// ```no_run
+/// use dwarfreader::debuginfod::DebuginfodClient;
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// let client = DebuginfodClient::from_settings(&view);
+/// let debug_view = client
+///     .fetch_for_view(&view, |done, total| {
+///         println!("{}/{}", done, total);
+///         true
+///     })
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebuginfodClient {
+    servers: Vec<String>,
+    cache_directory: PathBuf,
+}
+
+impl DebuginfodClient {
+    pub fn new(servers: Vec<String>, cache_directory: impl Into<PathBuf>) -> Self {
+        Self {
+            servers,
+            cache_directory: cache_directory.into(),
+        }
+    }
+
+    /// Client for the servers and cache directory set for `view`.
+    pub fn from_settings(view: &BinaryView) -> Self {
+        let mut query_options = QueryOptions::new_with_view(view);
+        let settings = Settings::new();
+        let servers = settings
+            .get_string_list_with_opts("network.debuginfodServers", &mut query_options)
+            .into_iter()
+            .map(|url| url.trim_end_matches('/').to_string())
+            .filter(|url| !url.is_empty())
+            .collect();
+        let cache_directory = settings
+            .get_string_with_opts(CACHE_DIRECTORY_SETTING, &mut query_options)
+            .to_string();
+        let cache_directory = match cache_directory.is_empty() {
+            true => default_cache_directory(),
+            false => PathBuf::from(cache_directory),
+        };
+        Self::new(servers, cache_directory)
+    }
+
+    pub fn servers(&self) -> &[String] {
+        &self.servers
+    }
+
+    pub fn cache_directory(&self) -> &Path {
+        &self.cache_directory
+    }
+
+    /// Where the debug file for `build_id` is cached, whether or not it has been fetched.
+    pub fn cache_path(&self, build_id: &str) -> PathBuf {
+        self.cache_directory.join(build_id).join("debuginfo")
+    }
+
+    /// Path of the debug file for `build_id`, downloading it into the cache if it is not there yet.
+    ///
+    /// `progress` is called with the number of bytes downloaded so far and the total size of the
+    /// file (0 if the server does not say), returning `false` cancels the download.
+    pub fn fetch_path<P: ProgressCallback + 'static>(
+        &self,
+        build_id: &str,
+        progress: P,
+    ) -> Result<PathBuf, Error> {
+        validate_build_id(build_id)?;
+        let cache_path = self.cache_path(build_id);
+        if cache_path.is_file() {
+            return Ok(cache_path);
+        }
+
+        let data = self.download(build_id, progress)?;
+        let cache_dir = cache_path.parent().unwrap();
+        fs::create_dir_all(cache_dir)?;
+        // Write to a temporary file first so that a partial file never ends up in the cache
+        static NEXT_TEMP_FILE: AtomicUsize = AtomicUsize::new(0);
+        let temp_file = NEXT_TEMP_FILE.fetch_add(1, Ordering::Relaxed);
+        let temp_path = cache_dir.join(format!(
+            ".debuginfo.{}.{}.tmp",
+            std::process::id(),
+            temp_file
+        ));
+        fs::write(&temp_path, &data)?;
+        fs::rename(&temp_path, &cache_path)?;
+        Ok(cache_path)
+    }
+
+    /// Fetch the debug file for `build_id` and open it, see [`DebuginfodClient::fetch_path`].
+    pub fn fetch<P: ProgressCallback + 'static>(
+        &self,
+        build_id: &str,
+        progress: P,
+    ) -> Result<Ref<BinaryView>, Error> {
+        let path = self.fetch_path(build_id, progress)?;
+        binaryninja::load_with_options(
+            &path,
+            false,
+            Some("{\"analysis.debugInfo.internal\": false}"),
+        )
+        .ok_or(Error::LoadFailed(path))
+    }
+
+    /// Fetch and open the debug file for the build id of `view`.
+    pub fn fetch_for_view<P: ProgressCallback + 'static>(
+        &self,
+        view: &BinaryView,
+        progress: P,
+    ) -> Result<Ref<BinaryView>, Error> {
+        self.fetch(&build_id(view)?, progress)
+    }
+
+    /// Fetch the debug file for `build_id` on a background thread.
+    ///
+    /// The returned [`DebuginfodFetch`] is a [`Future`] resolving to the opened debug file, and
+    /// can also be waited on from synchronous code.
+    pub fn fetch_async<P: ProgressCallback + Send + 'static>(
+        &self,
+        build_id: &str,
+        progress: P,
+    ) -> DebuginfodFetch {
+        let client = self.clone();
+        let build_id = build_id.to_string();
+        DebuginfodFetch::spawn(move || client.fetch(&build_id, progress))
+    }
+
+    fn download<P: ProgressCallback + 'static>(
+        &self,
+        build_id: &str,
+        progress: P,
+    ) -> Result<Vec<u8>, Error> {
+        let provider = DownloadProvider::try_default()
+            .map_err(|_| Error::Download("no default download provider".to_string()))?;
+        // Shared between the requests to each server
+        let progress = Rc::new(RefCell::new(progress));
+
+        for server in &self.servers {
+            let url = format!("{}/buildid/{}/debuginfo", server, build_id);
+            let data = Rc::new(RefCell::new(Vec::new()));
+            let cancelled = Rc::new(RefCell::new(false));
+
+            let write_data = data.clone();
+            let write = move |bytes: &[u8]| -> usize {
+                write_data.borrow_mut().extend_from_slice(bytes);
+                bytes.len()
+            };
+            let request_progress = progress.clone();
+            let request_cancelled = cancelled.clone();
+            let on_progress = move |done: u64, total: u64| -> bool {
+                let mut progress = request_progress.borrow_mut();
+                // Through the raw callback so that `NoProgressCallback` is not called directly
+                let keep_going = unsafe {
+                    P::cb_progress_callback(progress.into_raw(), done as usize, total as usize)
+                };
+                *request_cancelled.borrow_mut() = !keep_going;
+                keep_going
+            };
+
+            let mut instance = provider
+                .create_instance()
+                .map_err(|_| Error::Download("couldn't create download instance".to_string()))?;
+            let response = instance.perform_custom_request(
+                "GET",
+                url.as_str(),
+                HashMap::<String, String>::new(),
+                DownloadInstanceInputOutputCallbacks {
+                    read: None,
+                    write: Some(Box::new(write)),
+                    progress: Some(Box::new(on_progress)),
+                },
+            );
+            if *cancelled.borrow() {
+                return Err(Error::Cancelled);
+            }
+            let response = match response {
+                Ok(response) => response,
+                // Try the next server if this one can't be reached
+                Err(_) => continue,
+            };
+            if response.status_code != 200 {
+                continue;
+            }
+
+            let data = data.take();
+            let expected_length = response
+                .headers
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+                .and_then(|(_, v)| v.trim().parse::<usize>().ok());
+            if let Some(expected) = expected_length {
+                if data.len() != expected {
+                    return Err(Error::Download(format!(
+                        "bad length from {}: expected {} got {}",
+                        url,
+                        expected,
+                        data.len()
+                    )));
+                }
+            }
+            return Ok(data);
+        }
+
+        Err(Error::DebugInfoNotFound(build_id.to_string()))
+    }
+}
+
+/// A debug file being fetched on a background thread, see [`DebuginfodClient::fetch_async`].
+pub struct DebuginfodFetch {
+    shared: Arc<FetchShared>,
+}
+
+#[derive(Default)]
+struct FetchShared {
+    state: Mutex<FetchState>,
+    finished: Condvar,
+}
+
+#[derive(Default)]
+struct FetchState {
+    result: Option<Result<Ref<BinaryView>, Error>>,
+    waker: Option<Waker>,
+    done: bool,
+}
+
+impl DebuginfodFetch {
+    fn spawn<F>(fetch: F) -> Self
+    where
+        F: FnOnce() -> Result<Ref<BinaryView>, Error> + Send + 'static,
+    {
+        let shared = Arc::new(FetchShared::default());
+        let thread_shared = shared.clone();
+        thread::spawn(move || {
+            let result = fetch();
+            let mut state = thread_shared.state.lock().unwrap();
+            state.result = Some(result);
+            state.done = true;
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+            thread_shared.finished.notify_all();
+        });
+        Self { shared }
+    }
+
+    /// Whether the fetch has finished, successfully or not.
+    pub fn is_finished(&self) -> bool {
+        self.shared.state.lock().unwrap().done
+    }
+
+    /// Block until the fetch has finished.
+    pub fn wait(self) -> Result<Ref<BinaryView>, Error> {
+        let state = self.shared.state.lock().unwrap();
+        let mut state = self
+            .shared
+            .finished
+            .wait_while(state, |state| !state.done)
+            .unwrap();
+        state
+            .result
+            .take()
+            .expect("DebuginfodFetch waited on after completion")
+    }
+}
+
+impl Future for DebuginfodFetch {
+    type Output = Result<Ref<BinaryView>, Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut state = self.shared.state.lock().unwrap();
+        if !state.done {
+            state.waker = Some(cx.waker().clone());
+            return Poll::Pending;
+        }
+        let result = state.result.take();
+        Poll::Ready(result.expect("DebuginfodFetch polled after completion"))
+    }
+}
+
+/// Build ids end up in URLs and paths, so only accept hex strings.
+fn validate_build_id(build_id: &str) -> Result<(), Error> {
+    if build_id.len() < 2 || !build_id.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(Error::InvalidBuildId(format!(
+            "not a hex string: {:?}",
+            build_id
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn build_id_note(endianness: Endianness, name: &[u8], desc: &[u8]) -> Vec<u8> {
+        let u32_bytes = |value: u32| match endianness {
+            Endianness::LittleEndian => value.to_le_bytes(),
+            Endianness::BigEndian => value.to_be_bytes(),
+        };
+        let mut note = Vec::new();
+        note.extend(u32_bytes(name.len() as u32));
+        note.extend(u32_bytes(desc.len() as u32));
+        note.extend(u32_bytes(NT_GNU_BUILD_ID));
+        note.extend(name);
+        note.resize(12 + name.len().next_multiple_of(4), 0);
+        note.extend(desc);
+        note
+    }
+
+    #[test]
+    fn parses_build_id_notes() {
+        let desc = [0xde, 0xad, 0xbe, 0xef, 0x01];
+        for endianness in [Endianness::LittleEndian, Endianness::BigEndian] {
+            let note = build_id_note(endianness, b"GNU\0", &desc);
+            assert_eq!(
+                parse_build_id_note(&note, endianness).unwrap(),
+                "deadbeef01"
+            );
+        }
+        // Names that need padding
+        let note = build_id_note(Endianness::LittleEndian, b"GNU", &desc);
+        assert_eq!(
+            parse_build_id_note(&note, Endianness::LittleEndian).unwrap(),
+            "deadbeef01"
+        );
+
+        let truncated = &build_id_note(Endianness::LittleEndian, b"GNU\0", &desc)[..18];
+        assert!(parse_build_id_note(truncated, Endianness::LittleEndian).is_err());
+        assert!(parse_build_id_note(&[0; 8], Endianness::LittleEndian).is_err());
+        let mut wrong_type = build_id_note(Endianness::LittleEndian, b"GNU\0", &desc);
+        wrong_type[8] = 1;
+        assert!(parse_build_id_note(&wrong_type, Endianness::LittleEndian).is_err());
+    }
+
+    #[test]
+    fn caches_by_build_id() {
+        let client = DebuginfodClient::new(vec![], "/cache");
+        assert_eq!(
+            client.cache_path("0123abcd"),
+            Path::new("/cache/0123abcd/debuginfo")
+        );
+        assert!(validate_build_id("0123abcd").is_ok());
+        assert!(validate_build_id("../etc").is_err());
+        assert!(validate_build_id("").is_err());
+    }
+}
@@ -0,0 +1,228 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Split DWARF packages (`.dwp` files).
+
+use gimli::{
+    DebugInfoOffset, DebugTypeSignature, Dwarf, DwarfFileType, DwarfPackage, DwoId, Reader,
+    Section, Unit,
+};
+
+use crate::Error;
+
+/// A split compilation unit along with the sections it was read from.
+pub struct SplitUnit<R: Reader> {
+    pub dwarf: Dwarf<R>,
+    pub unit: Unit<R>,
+}
+
+/// An indexed `.dwp` package.
+///
+/// With split DWARF, the main file only holds a skeleton unit for each compilation unit, the rest
+/// of the debug info is moved into `.dwo` files. `dwp` combines those into a single package,
+/// indexed by `.debug_cu_index` and `.debug_tu_index`.
+///
+/// Load one from a view with [`crate::DwarfReaderContext::load_dwp`].
+pub struct DwpArchive<R: Reader> {
+    package: DwarfPackage<R>,
+}
+
+impl<R: Reader> DwpArchive<R> {
+    pub fn new(package: DwarfPackage<R>) -> Self {
+        Self { package }
+    }
+
+    pub fn package(&self) -> &DwarfPackage<R> {
+        &self.package
+    }
+
+    /// Number of compilation units in `.debug_cu_index`.
+    pub fn cu_count(&self) -> u32 {
+        self.package.cu_index.unit_count()
+    }
+
+    /// Number of type units in `.debug_tu_index`.
+    pub fn tu_count(&self) -> u32 {
+        self.package.tu_index.unit_count()
+    }
+
+    /// The sections of the compilation unit with id `dwo_id`, for a skeleton unit in `parent`.
+    pub fn find_cu(&self, dwo_id: DwoId, parent: &Dwarf<R>) -> Result<Option<Dwarf<R>>, Error> {
+        Ok(self.package.find_cu(dwo_id, parent)?)
+    }
+
+    /// The sections of the type unit with the given type signature.
+    pub fn find_tu(
+        &self,
+        signature: DebugTypeSignature,
+        parent: &Dwarf<R>,
+    ) -> Result<Option<Dwarf<R>>, Error> {
+        Ok(self.package.find_tu(signature, parent)?)
+    }
+
+    /// Resolve a skeleton unit of `parent` to its split unit in the package.
+    ///
+    /// Returns `None` for units that are not skeleton units, or whose split unit is not in the
+    /// package. The split unit is read from the whole `.debug_info.dwo` section, so that offsets
+    /// of DIEs are unique across all units of the package.
+    pub fn split_unit(
+        &self,
+        parent: &Dwarf<R>,
+        skeleton: &Unit<R>,
+    ) -> Result<Option<SplitUnit<R>>, Error> {
+        let Some(dwo_id) = skeleton.dwo_id else {
+            return Ok(None);
+        };
+        if parent.file_type == DwarfFileType::Dwo {
+            return Ok(None);
+        }
+        let Some(mut dwarf) = self.find_cu(dwo_id, parent)? else {
+            return Ok(None);
+        };
+
+        let package_info = self.package.debug_info.clone();
+        let offset = dwarf.debug_info.reader().offset_from(package_info.reader());
+        dwarf.debug_info = package_info;
+        let header = dwarf
+            .debug_info
+            .header_from_offset(DebugInfoOffset(offset))?;
+        let mut unit = dwarf.unit(header)?;
+        unit.copy_relocated_attributes(skeleton);
+        Ok(Some(SplitUnit { dwarf, unit }))
+    }
+
+    /// Resolve all skeleton units in `skeletons` that have a split unit in the package.
+    pub fn split_units<'a>(
+        &'a self,
+        parent: &'a Dwarf<R>,
+        skeletons: impl IntoIterator<Item = &'a Unit<R>> + 'a,
+    ) -> impl Iterator<Item = Result<SplitUnit<R>, Error>> + 'a {
+        skeletons
+            .into_iter()
+            .filter_map(move |skeleton| self.split_unit(parent, skeleton).transpose())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gimli::{AttributeValue, EndianSlice, LittleEndian, SectionId};
+
+    type R = EndianSlice<'static, LittleEndian>;
+
+    fn slice(data: Vec<u8>) -> R {
+        EndianSlice::new(Vec::leak(data), LittleEndian)
+    }
+
+    fn u32s(values: &[u32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    /// A DWARF 5 unit with a single DIE using abbrev code 1.
+    fn unit(unit_type: u8, dwo_id: u64, die: &[u8]) -> Vec<u8> {
+        let mut data = u32s(&[(2 + 1 + 1 + 4 + 8 + 1 + die.len()) as u32]);
+        data.extend_from_slice(&[5, 0, unit_type, 8, 0, 0, 0, 0]);
+        data.extend_from_slice(&dwo_id.to_le_bytes());
+        data.push(1);
+        data.extend_from_slice(die);
+        data
+    }
+
+    /// A package with the split units 1 and 2, and the offset of unit 2.
+    fn package() -> (DwpArchive<R>, usize) {
+        // DW_TAG_compile_unit with a DW_AT_name string
+        let abbrev = vec![1, 0x11, 0, 0x03, 0x08, 0, 0, 0];
+        let first = unit(0x05, 1, b"first.c\0");
+        let second = unit(0x05, 2, b"second.c\0");
+        let second_offset = first.len();
+
+        // Header, then the hash table with each id in the slot of the same number
+        let mut cu_index = u32s(&[5, 2, 2, 4]);
+        for id in [0u64, 1, 2, 0] {
+            cu_index.extend_from_slice(&id.to_le_bytes());
+        }
+        cu_index.extend(u32s(&[0, 1, 2, 0]));
+        // DW_SECT_INFO and DW_SECT_ABBREV, then the offsets and sizes of each row
+        cu_index.extend(u32s(&[1, 3]));
+        cu_index.extend(u32s(&[0, 0, second_offset as u32, 0]));
+        let abbrev_len = abbrev.len() as u32;
+        cu_index.extend(u32s(&[
+            first.len() as u32,
+            abbrev_len,
+            second.len() as u32,
+            abbrev_len,
+        ]));
+
+        let info = [first, second].concat();
+        let package = DwarfPackage::load(
+            |id| -> gimli::Result<R> {
+                Ok(match id {
+                    SectionId::DebugCuIndex => slice(cu_index.clone()),
+                    SectionId::DebugInfo => slice(info.clone()),
+                    SectionId::DebugAbbrev => slice(abbrev.clone()),
+                    _ => slice(vec![]),
+                })
+            },
+            slice(vec![]),
+        )
+        .unwrap();
+        (DwpArchive::new(package), second_offset)
+    }
+
+    /// A main file with a skeleton unit for each of `dwo_ids`.
+    fn skeletons(dwo_ids: &[u64]) -> (Dwarf<R>, Vec<Unit<R>>) {
+        let info: Vec<u8> = dwo_ids.iter().flat_map(|id| unit(0x04, *id, &[])).collect();
+        let dwarf = Dwarf {
+            // DW_TAG_skeleton_unit without attributes
+            debug_abbrev: slice(vec![1, 0x4a, 0, 0, 0, 0]).into(),
+            debug_info: slice(info).into(),
+            ..Default::default()
+        };
+        let mut units = vec![];
+        let mut headers = dwarf.units();
+        while let Some(header) = headers.next().unwrap() {
+            units.push(dwarf.unit(header).unwrap());
+        }
+        (dwarf, units)
+    }
+
+    #[test]
+    fn resolves_skeleton_units() {
+        let (dwp, second_offset) = package();
+        assert_eq!((dwp.cu_count(), dwp.tu_count()), (2, 0));
+
+        let (dwarf, units) = skeletons(&[2, 3, 1]);
+        let split: Vec<SplitUnit<R>> = dwp
+            .split_units(&dwarf, &units)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let names: Vec<String> = split
+            .iter()
+            .map(|split| {
+                let mut entries = split.unit.entries();
+                let (_, entry) = entries.next_dfs().unwrap().unwrap();
+                match entry.attr_value(gimli::DW_AT_name).unwrap() {
+                    Some(AttributeValue::String(name)) => name.to_string_lossy().into_owned(),
+                    value => panic!("unexpected name {:?}", value),
+                }
+            })
+            .collect();
+        assert_eq!(names, ["second.c", "first.c"]);
+        assert_eq!(
+            split[0].unit.header.offset().as_debug_info_offset(),
+            Some(DebugInfoOffset(second_offset)),
+            "split units keep their offset in the package"
+        );
+    }
+}
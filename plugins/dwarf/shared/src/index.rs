@@ -0,0 +1,487 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An index of the compilation units of a file.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use gimli::{
+    constants, DebugInfoOffset, DebugPubNames, DebugPubTypes, DebugStr, DebugStrOffset, DwForm,
+    DwIdx, Dwarf, Format, Reader, ReaderOffset, UnitType,
+};
+
+use crate::Error;
+
+/// A name of a `.debug_names` index and the compilation units defining it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexedName<O> {
+    pub name: String,
+    pub units: Vec<DebugInfoOffset<O>>,
+}
+
+type Abbreviations = HashMap<u64, Vec<(DwIdx, DwForm)>>;
+
+fn parse_abbreviations<R: Reader>(mut table: R) -> Result<Abbreviations, Error> {
+    let mut abbreviations = HashMap::new();
+    loop {
+        let code = table.read_uleb128()?;
+        if code == 0 {
+            break;
+        }
+        let _tag = table.read_uleb128()?;
+        let mut attributes = vec![];
+        loop {
+            let (index, form) = (table.read_uleb128_u16()?, table.read_uleb128_u16()?);
+            if index == 0 && form == 0 {
+                break;
+            }
+            attributes.push((DwIdx(index), DwForm(form)));
+        }
+        abbreviations.insert(code, attributes);
+    }
+    Ok(abbreviations)
+}
+
+fn read_index_value<R: Reader>(entry: &mut R, form: DwForm, format: Format) -> Result<u64, Error> {
+    Ok(match form {
+        constants::DW_FORM_flag_present => 1,
+        constants::DW_FORM_flag | constants::DW_FORM_data1 | constants::DW_FORM_ref1 => {
+            entry.read_u8()?.into()
+        }
+        constants::DW_FORM_data2 | constants::DW_FORM_ref2 => entry.read_u16()?.into(),
+        constants::DW_FORM_data4 | constants::DW_FORM_ref4 => entry.read_u32()?.into(),
+        constants::DW_FORM_data8 | constants::DW_FORM_ref8 | constants::DW_FORM_ref_sig8 => {
+            entry.read_u64()?
+        }
+        constants::DW_FORM_udata | constants::DW_FORM_ref_udata => entry.read_uleb128()?,
+        constants::DW_FORM_sdata => entry.read_sleb128()? as u64,
+        constants::DW_FORM_sec_offset | constants::DW_FORM_strp => {
+            entry.read_offset(format)?.into_u64()
+        }
+        constants::DW_FORM_data16 => {
+            entry.skip(R::Offset::from_u8(16))?;
+            0
+        }
+        _ => return Err(gimli::Error::UnknownForm(form).into()),
+    })
+}
+
+/// The names of the `.debug_names` section `section`, with their strings read from `debug_str`.
+///
+/// Names only defined in type units are left out, as are the type units of names also defined in
+/// compilation units.
+pub fn parse_debug_names<R: Reader>(
+    mut section: R,
+    debug_str: &DebugStr<R>,
+) -> Result<Vec<IndexedName<R::Offset>>, Error> {
+    let mut names = vec![];
+    // Linkers concatenate the index of every object file, unless they merge them
+    while !section.is_empty() {
+        let (length, format) = section.read_initial_length()?;
+        let mut index = section.split(length)?;
+        let version = index.read_u16()?;
+        if version != 5 {
+            return Err(Error::UnsupportedVersion(".debug_names", version));
+        }
+        let _padding = index.read_u16()?;
+        let comp_unit_count = index.read_u32()?;
+        let local_type_unit_count = index.read_u32()?;
+        let foreign_type_unit_count = index.read_u32()?;
+        let bucket_count = index.read_u32()?;
+        let name_count = index.read_u32()? as u64;
+        let abbreviation_table_size = index.read_u32()?;
+        let augmentation_string_size = index.read_u32()?;
+        index.skip(R::Offset::from_u32(augmentation_string_size))?;
+
+        let mut units = vec![];
+        for _ in 0..comp_unit_count {
+            units.push(DebugInfoOffset(index.read_offset(format)?));
+        }
+        // Only listing the names, the type units and the hash table aren't needed
+        let word_size = format.word_size() as u64;
+        let hashes = if bucket_count > 0 { name_count * 4 } else { 0 };
+        let skipped = local_type_unit_count as u64 * word_size
+            + foreign_type_unit_count as u64 * 8
+            + bucket_count as u64 * 4
+            + hashes;
+        index.skip(R::Offset::from_u64(skipped)?)?;
+        let mut string_offsets = index.split(R::Offset::from_u64(name_count * word_size)?)?;
+        let mut entry_offsets = index.split(R::Offset::from_u64(name_count * word_size)?)?;
+        let abbreviations =
+            parse_abbreviations(index.split(R::Offset::from_u32(abbreviation_table_size))?)?;
+        let entry_pool = index;
+
+        for _ in 0..name_count {
+            let string = debug_str.get_str(DebugStrOffset(string_offsets.read_offset(format)?))?;
+            let mut entry = entry_pool.clone();
+            entry.skip(entry_offsets.read_offset(format)?)?;
+
+            let mut name_units = vec![];
+            loop {
+                let code = entry.read_uleb128()?;
+                if code == 0 {
+                    break;
+                }
+                let attributes = abbreviations
+                    .get(&code)
+                    .ok_or(gimli::Error::UnknownAbbreviation(code))?;
+                let (mut unit, mut type_unit) = (None, false);
+                for &(index, form) in attributes {
+                    let value = read_index_value(&mut entry, form, format)?;
+                    match index {
+                        constants::DW_IDX_compile_unit => unit = Some(value),
+                        constants::DW_IDX_type_unit => type_unit = true,
+                        _ => {}
+                    }
+                }
+                // The unit is left out of indexes of a single compilation unit
+                let unit = match unit {
+                    _ if type_unit => continue,
+                    Some(unit) => units.get(unit as usize),
+                    None if units.len() == 1 => units.first(),
+                    None => None,
+                };
+                if let Some(&unit) = unit.filter(|unit| !name_units.contains(*unit)) {
+                    name_units.push(unit);
+                }
+            }
+            if !name_units.is_empty() {
+                names.push(IndexedName {
+                    name: string.to_string_lossy()?.into_owned(),
+                    units: name_units,
+                });
+            }
+        }
+    }
+    Ok(names)
+}
+
+/// The compilation units of a file, by the names they define and the addresses they have code
+/// at, for importing them one at a time.
+///
+/// Building the DIE trees of every unit of a large file takes a long time. The index only reads
+/// the root DIE of each unit, for the addresses the unit has code at, and the name tables
+/// compilers emit for debuggers: `.debug_names`, or `.debug_pubnames` and `.debug_pubtypes` before
+/// DWARF 5. The units defining a function or type can then be found without reading the rest.
+///
+/// Type units aren't indexed, they are found through the references of the units using them.
+#[derive(Clone, Debug, Default)]
+pub struct UnitIndex {
+    units: Vec<DebugInfoOffset<usize>>,
+    /// Positions in `units` of the units defining each name.
+    names: HashMap<String, Vec<usize>>,
+    /// Address ranges of the units by position in `units`, sorted by start address.
+    ranges: Vec<(Range<u64>, usize)>,
+    longest_range: u64,
+}
+
+impl UnitIndex {
+    /// Index the compilation units of `dwarf` with the names of its `.debug_names` section
+    /// `debug_names` or, if it has none, of its `.debug_pubnames` and `.debug_pubtypes` sections.
+    ///
+    /// See [`crate::DwarfReaderContext::load_unit_index`] for loading these from a view.
+    pub fn new<R: Reader<Offset = usize>>(
+        dwarf: &Dwarf<R>,
+        debug_names: R,
+        debug_pubnames: &DebugPubNames<R>,
+        debug_pubtypes: &DebugPubTypes<R>,
+    ) -> Result<Self, Error> {
+        let mut index = Self::default();
+        let mut positions = HashMap::new();
+        let mut headers = dwarf.units();
+        while let Some(header) = headers.next()? {
+            if let UnitType::Type { .. } | UnitType::SplitType { .. } = header.type_() {
+                continue;
+            }
+            let Some(offset) = header.offset().as_debug_info_offset() else {
+                continue;
+            };
+            let position = index.units.len();
+            index.units.push(offset);
+            positions.insert(offset, position);
+
+            let unit = dwarf.unit(header)?;
+            let mut ranges = dwarf.unit_ranges(&unit)?;
+            while let Some(range) = ranges.next()? {
+                if range.begin < range.end {
+                    index.ranges.push((range.begin..range.end, position));
+                    index.longest_range = index.longest_range.max(range.end - range.begin);
+                }
+            }
+        }
+        index.ranges.sort_by_key(|(range, _)| range.start);
+
+        let mut add_name = |name: String, unit: DebugInfoOffset<usize>| {
+            let Some(&position) = positions.get(&unit) else {
+                return;
+            };
+            let units = index.names.entry(name).or_default();
+            if !units.contains(&position) {
+                units.push(position);
+            }
+        };
+        let names = parse_debug_names(debug_names, &dwarf.debug_str)?;
+        if !names.is_empty() {
+            for name in names {
+                for unit in name.units {
+                    add_name(name.name.clone(), unit);
+                }
+            }
+        } else {
+            let mut pubnames = debug_pubnames.items();
+            while let Some(item) = pubnames.next()? {
+                let name = item.name().to_string_lossy()?.into_owned();
+                add_name(name, item.unit_header_offset());
+            }
+            let mut pubtypes = debug_pubtypes.items();
+            while let Some(item) = pubtypes.next()? {
+                let name = item.name().to_string_lossy()?.into_owned();
+                add_name(name, item.unit_header_offset());
+            }
+        }
+        Ok(index)
+    }
+
+    /// The offsets of the indexed compilation units, in the order they are in the file.
+    pub fn units(&self) -> &[DebugInfoOffset<usize>] {
+        &self.units
+    }
+
+    pub fn len(&self) -> usize {
+        self.units.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.units.is_empty()
+    }
+
+    /// Whether the file had name tables, without them units are only found by address.
+    pub fn has_names(&self) -> bool {
+        !self.names.is_empty()
+    }
+
+    /// The compilation units defining `name`.
+    ///
+    /// `.debug_names` only holds the last component of qualified names, which qualified names
+    /// missing from the index are looked up by.
+    pub fn units_named(&self, name: &str) -> Vec<DebugInfoOffset<usize>> {
+        let positions = self.names.get(name).or_else(|| {
+            let (_, last) = name.rsplit_once("::")?;
+            self.names.get(last)
+        });
+        positions
+            .into_iter()
+            .flatten()
+            .map(|&position| self.units[position])
+            .collect()
+    }
+
+    /// The compilation units with code at `addr`.
+    pub fn units_at(&self, addr: u64) -> Vec<DebugInfoOffset<usize>> {
+        let end = self
+            .ranges
+            .partition_point(|(range, _)| range.start <= addr);
+        let first = addr.saturating_sub(self.longest_range);
+        let mut units = vec![];
+        for (range, position) in self.ranges[..end].iter().rev() {
+            if range.start < first {
+                break;
+            }
+            let unit = self.units[*position];
+            if range.contains(&addr) && !units.contains(&unit) {
+                units.push(unit);
+            }
+        }
+        units
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gimli::write::{self, Address, AttributeValue, EndianVec, Sections};
+    use gimli::{Encoding, EndianSlice, LittleEndian, SectionId};
+
+    type R = EndianSlice<'static, LittleEndian>;
+
+    fn slice(data: Vec<u8>) -> R {
+        EndianSlice::new(Vec::leak(data), LittleEndian)
+    }
+
+    fn u32s(values: &[u32]) -> Vec<u8> {
+        values.iter().flat_map(|v| v.to_le_bytes()).collect()
+    }
+
+    /// A `.debug_str` with `strings`, and the offset of each.
+    fn strings(strings: &[&str]) -> (Vec<u8>, Vec<u32>) {
+        let mut data = vec![];
+        let mut offsets = vec![];
+        for string in strings {
+            offsets.push(data.len() as u32);
+            data.extend_from_slice(string.as_bytes());
+            data.push(0);
+        }
+        (data, offsets)
+    }
+
+    /// A `.debug_names` index of `units`, with names at `string_offsets` defined by the units at
+    /// the positions of `name_units`. Type unit entries are added for the names of `type_names`.
+    fn debug_names(
+        units: &[u32],
+        string_offsets: &[u32],
+        name_units: &[&[u8]],
+        type_names: &[usize],
+    ) -> Vec<u8> {
+        // 1: DW_TAG_subprogram with DW_IDX_compile_unit as data1 and DW_IDX_die_offset as ref4
+        // 2: DW_TAG_structure_type with DW_IDX_type_unit as data1
+        let abbreviations = vec![1, 0x2e, 1, 0x0b, 3, 0x13, 0, 0, 2, 0x13, 2, 0x0b, 0, 0, 0];
+        let mut entries = vec![];
+        let mut entry_offsets = vec![];
+        for (i, units) in name_units.iter().enumerate() {
+            entry_offsets.push(entries.len() as u32);
+            for &unit in *units {
+                entries.extend_from_slice(&[1, unit, 0x10, 0, 0, 0]);
+            }
+            if type_names.contains(&i) {
+                entries.extend_from_slice(&[2, 0]);
+            }
+            entries.push(0);
+        }
+
+        let mut body = vec![5, 0, 0, 0];
+        body.extend(u32s(&[units.len() as u32, 1, 0, 0]));
+        body.extend(u32s(&[
+            string_offsets.len() as u32,
+            abbreviations.len() as u32,
+            0,
+        ]));
+        body.extend(u32s(units));
+        // The local type unit
+        body.extend(u32s(&[0]));
+        body.extend(u32s(string_offsets));
+        body.extend(u32s(&entry_offsets));
+        body.extend(abbreviations);
+        body.extend(entries);
+        [u32s(&[body.len() as u32]), body].concat()
+    }
+
+    #[test]
+    fn parses_debug_names() {
+        let (debug_str, offsets) = strings(&["main", "helper", "point"]);
+        let debug_str = DebugStr::from(slice(debug_str));
+        let section = debug_names(&[0, 0x40], &offsets, &[&[0], &[0, 1], &[]], &[2]);
+        // A second index, as in the output of linkers not merging them
+        let single = debug_names(&[0x80], &offsets[..1], &[&[0]], &[]);
+        let section = slice([section, single].concat());
+
+        let names = parse_debug_names(section, &debug_str).unwrap();
+        assert_eq!(
+            names,
+            [
+                IndexedName {
+                    name: "main".to_string(),
+                    units: vec![DebugInfoOffset(0)],
+                },
+                IndexedName {
+                    name: "helper".to_string(),
+                    units: vec![DebugInfoOffset(0), DebugInfoOffset(0x40)],
+                },
+                IndexedName {
+                    name: "main".to_string(),
+                    units: vec![DebugInfoOffset(0x80)],
+                },
+            ]
+        );
+
+        // DWARF 4 had no `.debug_names`
+        let mut version_4 = debug_names(&[0], &offsets[..1], &[&[0]], &[]);
+        version_4[4] = 4;
+        assert!(parse_debug_names(slice(version_4), &debug_str).is_err());
+    }
+
+    /// A file with a compilation unit named after, and with code at, each of `units`.
+    fn dwarf(units: &[(&str, Range<u64>)]) -> (Dwarf<R>, Vec<DebugInfoOffset<usize>>) {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 4,
+            address_size: 8,
+        };
+        let mut dwarf = write::Dwarf::new();
+        for (name, range) in units {
+            let unit_id = dwarf
+                .units
+                .add(write::Unit::new(encoding, write::LineProgram::none()));
+            let unit = dwarf.units.get_mut(unit_id);
+            let root = unit.get_mut(unit.root());
+            root.set(
+                constants::DW_AT_name,
+                AttributeValue::String(name.as_bytes().to_vec()),
+            );
+            root.set(
+                constants::DW_AT_low_pc,
+                AttributeValue::Address(Address::Constant(range.start)),
+            );
+            root.set(
+                constants::DW_AT_high_pc,
+                AttributeValue::Udata(range.end - range.start),
+            );
+        }
+        let mut sections = Sections::new(EndianVec::new(LittleEndian));
+        dwarf.write(&mut sections).unwrap();
+
+        let mut data = HashMap::new();
+        sections
+            .for_each(|id, section| -> gimli::Result<()> {
+                data.insert(id, section.slice().to_vec());
+                Ok(())
+            })
+            .unwrap();
+        let dwarf = Dwarf::load(|id: SectionId| -> gimli::Result<R> {
+            Ok(slice(data.get(&id).cloned().unwrap_or_default()))
+        })
+        .unwrap();
+        let mut offsets = vec![];
+        let mut headers = dwarf.units();
+        while let Some(header) = headers.next().unwrap() {
+            offsets.push(header.offset().as_debug_info_offset().unwrap());
+        }
+        (dwarf, offsets)
+    }
+
+    #[test]
+    fn indexes_units() {
+        let (mut dwarf, offsets) = dwarf(&[("a.c", 0x1000..0x1100), ("b.c", 0x1100..0x1400)]);
+        let (debug_str, string_offsets) = strings(&["main", "helper"]);
+        dwarf.debug_str = DebugStr::from(slice(debug_str));
+        let units: Vec<u32> = offsets.iter().map(|offset| offset.0 as u32).collect();
+        let debug_names = slice(debug_names(&units, &string_offsets, &[&[0], &[1]], &[]));
+        let no_pubnames = DebugPubNames::from(slice(vec![]));
+        let no_pubtypes = DebugPubTypes::from(slice(vec![]));
+
+        let index = UnitIndex::new(&dwarf, debug_names, &no_pubnames, &no_pubtypes).unwrap();
+        assert_eq!(index.units(), offsets);
+        assert!(index.has_names());
+        assert_eq!(index.units_named("main"), [offsets[0]]);
+        assert_eq!(index.units_named("ns::helper"), [offsets[1]]);
+        assert!(index.units_named("missing").is_empty());
+        assert_eq!(index.units_at(0x10ff), [offsets[0]]);
+        assert_eq!(index.units_at(0x1100), [offsets[1]]);
+        assert!(index.units_at(0x1400).is_empty());
+
+        let index = UnitIndex::new(&dwarf, slice(vec![]), &no_pubnames, &no_pubtypes).unwrap();
+        assert!(!index.has_names());
+        assert_eq!(index.units_at(0x1000), [offsets[0]]);
+    }
+}
@@ -12,15 +12,33 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use gimli::{EndianRcSlice, Endianity, RunTimeEndian, SectionId};
+pub mod debuginfod;
+pub mod dwp;
+pub mod index;
+pub mod location;
+mod paged_reader;
+pub mod supplementary;
+
+pub use paged_reader::{PagedReader, MAX_CACHED_PAGES, PAGE_SIZE};
+
+use gimli::{
+    DebugPubNames, DebugPubTypes, Dwarf, DwarfFileType, DwarfPackage, EndianRcSlice, Endianity,
+    RunTimeEndian, Section as _, SectionId,
+};
+
+use crate::index::UnitIndex;
 
 use binaryninja::{
     binary_view::{BinaryView, BinaryViewBase, BinaryViewExt},
+    rc::Ref,
+    section::{Section, SectionCompression},
     settings::Settings,
     Endianness,
 };
 
 use binaryninja::settings::QueryOptions;
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::rc::Rc;
 //////////////////////
 // Dwarf Validation
@@ -30,15 +48,38 @@ pub enum Error {
     #[error("unknown section compression method {0:#x}")]
     UnknownCompressionMethod(u32),
 
+    #[error("missing required section {0}")]
+    MissingSection(&'static str),
+
+    #[error("unsupported {0} version {1}")]
+    UnsupportedVersion(&'static str, u16),
+
     #[error("{0}")]
     GimliError(#[from] gimli::Error),
 
     #[error("{0}")]
     IoError(#[from] std::io::Error),
+
+    #[error("invalid build id: {0}")]
+    InvalidBuildId(String),
+
+    #[error("download failed: {0}")]
+    Download(String),
+
+    #[error("no debuginfod server has debug info for build id {0}")]
+    DebugInfoNotFound(String),
+
+    #[error("cancelled")]
+    Cancelled,
+
+    #[error("unable to load {}", .0.display())]
+    LoadFailed(std::path::PathBuf),
 }
 
 pub fn is_non_dwo_dwarf(view: &BinaryView) -> bool {
-    view.section_by_name(".debug_info").is_some() || view.section_by_name("__debug_info").is_some()
+    view.section_by_name(".debug_info").is_some()
+        || view.section_by_name("__debug_info").is_some()
+        || view.section_by_name(".zdebug_info").is_some()
 }
 
 pub fn is_dwo_dwarf(view: &BinaryView) -> bool {
@@ -49,6 +90,7 @@ pub fn is_raw_non_dwo_dwarf(view: &BinaryView) -> bool {
     if let Some(raw_view) = view.raw_view() {
         raw_view.section_by_name(".debug_info").is_some()
             || view.section_by_name("__debug_info").is_some()
+            || raw_view.section_by_name(".zdebug_info").is_some()
     } else {
         false
     }
@@ -62,6 +104,14 @@ pub fn is_raw_dwo_dwarf(view: &BinaryView) -> bool {
     }
 }
 
+/// Whether `view` or its raw view is a split DWARF package (`.dwp`).
+pub fn is_dwp(view: &BinaryView) -> bool {
+    [Some(view.to_owned()), view.raw_view()]
+        .into_iter()
+        .flatten()
+        .any(|view| view.section_by_name(".debug_cu_index").is_some())
+}
+
 pub fn can_use_debuginfod(view: &BinaryView) -> bool {
     let mut query_options = QueryOptions::new_with_view(view);
     has_build_id_section(view)
@@ -95,94 +145,229 @@ pub fn create_section_reader<'a, Endian: 'a + Endianity>(
     endian: Endian,
     dwo_file: bool,
 ) -> Result<EndianRcSlice<Endian>, Error> {
-    let section_name = if dwo_file && section_id.dwo_name().is_some() {
-        section_id.dwo_name().unwrap()
-    } else {
-        section_id.name()
-    };
+    match find_section(view, section_id, dwo_file) {
+        Some(section) => read_section(view, &section, endian),
+        None => Ok(EndianRcSlice::new(Rc::from([]), endian)),
+    }
+}
+
+/// Sections larger than this are read from the view on demand rather than all at once.
+pub const PAGED_SECTION_THRESHOLD: usize = 0x400_0000;
+
+/// Like [`create_section_reader`], but large sections are read a page at a time as they are
+/// parsed instead of being copied into memory up front.
+///
+/// Compressed sections are always decompressed into memory.
+pub fn create_paged_section_reader<Endian: Endianity>(
+    section_id: SectionId,
+    view: &BinaryView,
+    endian: Endian,
+    dwo_file: bool,
+) -> Result<PagedReader<Endian>, Error> {
+    match find_section(view, section_id, dwo_file) {
+        Some(section) => read_paged_section(view, &section, endian),
+        None => Ok(PagedReader::from_data(Rc::from([]), endian)),
+    }
+}
+
+fn read_paged_section<Endian: Endianity>(
+    view: &BinaryView,
+    section: &Section,
+    endian: Endian,
+) -> Result<PagedReader<Endian>, Error> {
+    if let Some(data) = read_compressed_section(view, section)? {
+        return Ok(PagedReader::from_data(data.into(), endian));
+    }
+    if section.len() <= PAGED_SECTION_THRESHOLD {
+        let data = view.read_vec(section.start(), section.len());
+        return Ok(PagedReader::from_data(data.into(), endian));
+    }
+    Ok(PagedReader::new(
+        view,
+        section.start(),
+        section.len(),
+        endian,
+    ))
+}
+
+/// The reader type sections of a [`DwarfReaderContext`] are read with.
+pub type SectionReader = PagedReader<RunTimeEndian>;
+
+/// Reads DWARF sections out of a [`BinaryView`], caching each section the first time it is requested.
+///
+/// Sections larger than [`PAGED_SECTION_THRESHOLD`] are paged in from the view as they are read.
+///
+/// Other plugins (CFI, line table, ...) can use this instead of wiring up their own section loaders.
+pub struct DwarfReaderContext<'a> {
+    view: &'a BinaryView,
+    endian: RunTimeEndian,
+    dwo_file: bool,
+    sections: RefCell<HashMap<SectionId, SectionReader>>,
+}
+
+impl<'a> DwarfReaderContext<'a> {
+    /// Create a reader for `view`, detecting whether it holds split (DWO) debug info.
+    pub fn new(view: &'a BinaryView) -> Self {
+        let dwo_file = is_dwo_dwarf(view) || is_raw_dwo_dwarf(view);
+        Self::new_with_dwo(view, dwo_file)
+    }
 
-    if let Some(section) = view.section_by_name(section_name) {
-        // TODO : This is kinda broke....should add rust wrappers for some of this
-        if let Some(symbol) = view
-            .symbols()
-            .iter()
-            .find(|symbol| symbol.full_name().as_str() == "__elf_section_headers")
-        {
-            if let Some(data_var) = view
-                .data_variables()
-                .iter()
-                .find(|var| var.address == symbol.address())
-            {
-                // TODO : This should eventually be wrapped by some DataView sorta thingy thing, like how python does it
-                let data_type = &data_var.ty.contents;
-                let data = view.read_vec(data_var.address, data_type.width() as usize);
-                let element_type = data_type.element_type().unwrap().contents;
-
-                if let Some(current_section_header) = data
-                    .chunks(element_type.width() as usize)
-                    .find(|section_header| {
-                        if view.address_size() == 4 {
-                            endian.read_u32(&section_header[16..20]) as u64 == section.start()
-                        } else {
-                            endian.read_u64(&section_header[24..32]) == section.start()
-                        }
-                    })
-                {
-                    let section_flags = if view.address_size() == 4 {
-                        endian.read_u32(&current_section_header[8..12]) as u64
-                    } else {
-                        endian.read_u64(&current_section_header[8..16])
-                    };
-                    // If the section has the compressed bit set
-                    if (section_flags & 2048) != 0 {
-                        // Get section, trim header, decompress, return
-                        let compressed_header_size = view.address_size() * 3;
-
-                        let offset = section.start() + compressed_header_size as u64;
-                        let len = section.len() - compressed_header_size;
-
-                        let ch_type_vec = view.read_vec(section.start(), 4);
-                        let ch_type = endian.read_u32(&ch_type_vec);
-
-                        if let Ok(buffer) = view.read_buffer(offset, len) {
-                            match ch_type {
-                                1 => {
-                                    return Ok(EndianRcSlice::new(
-                                        buffer.zlib_decompress().get_data().into(),
-                                        endian,
-                                    ));
-                                }
-                                2 => {
-                                    return Ok(EndianRcSlice::new(
-                                        zstd::decode_all(buffer.get_data())?.as_slice().into(),
-                                        endian,
-                                    ));
-                                }
-                                x => {
-                                    return Err(Error::UnknownCompressionMethod(x));
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+    /// Create a reader for `view`, using the `.dwo` section names when `dwo_file` is set.
+    pub fn new_with_dwo(view: &'a BinaryView, dwo_file: bool) -> Self {
+        Self {
+            view,
+            endian: get_endian(view),
+            dwo_file,
+            sections: RefCell::new(HashMap::new()),
         }
-        let offset = section.start();
-        let len = section.len();
-        if len == 0 {
-            Ok(EndianRcSlice::new(Rc::from([]), endian))
-        } else {
-            Ok(EndianRcSlice::new(
-                Rc::from(view.read_vec(offset, len).as_slice()),
-                endian,
-            ))
+    }
+
+    pub fn view(&self) -> &'a BinaryView {
+        self.view
+    }
+
+    pub fn endian(&self) -> RunTimeEndian {
+        self.endian
+    }
+
+    pub fn is_dwo(&self) -> bool {
+        self.dwo_file
+    }
+
+    pub fn has_section(&self, section_id: SectionId) -> bool {
+        find_section(self.view, section_id, self.dwo_file).is_some()
+    }
+
+    /// Length of the section as stored in the view, before any decompression.
+    pub fn section_len(&self, section_id: SectionId) -> Option<u64> {
+        find_section(self.view, section_id, self.dwo_file).map(|section| section.len() as u64)
+    }
+
+    /// Read the contents of a section, returning an empty slice if it is not present.
+    ///
+    /// This has the signature gimli expects of a section loader, so it can be passed to
+    /// [`gimli::Dwarf::load`] and friends as `|id| reader.section(id)`.
+    pub fn section(&self, section_id: SectionId) -> Result<SectionReader, Error> {
+        if let Some(data) = self.sections.borrow().get(&section_id) {
+            return Ok(data.clone());
+        }
+        let data = create_paged_section_reader(section_id, self.view, self.endian, self.dwo_file)?;
+        self.sections.borrow_mut().insert(section_id, data.clone());
+        Ok(data)
+    }
+
+    /// Like [`DwarfReaderContext::section`], but fails if the section is not present.
+    pub fn required_section(&self, section_id: SectionId) -> Result<SectionReader, Error> {
+        if !self.has_section(section_id) {
+            return Err(Error::MissingSection(section_name(
+                section_id,
+                self.dwo_file,
+            )));
+        }
+        self.section(section_id)
+    }
+
+    /// Read the `.debug_names` section, returning an empty slice if it is not present.
+    ///
+    /// gimli has no [`SectionId`] for it, so it isn't loaded along with the other sections.
+    pub fn debug_names(&self) -> Result<SectionReader, Error> {
+        match find_section_by_name(self.view, ".debug_names") {
+            Some(section) => read_paged_section(self.view, &section, self.endian),
+            None => Ok(PagedReader::from_data(Rc::from([]), self.endian)),
         }
-    } else if let Some(section) = view.section_by_name("__".to_string() + &section_name[1..]) {
+    }
+
+    /// Index the compilation units of `dwarf`, loaded from this context's view, with the name
+    /// tables of the view.
+    pub fn load_unit_index(&self, dwarf: &Dwarf<SectionReader>) -> Result<UnitIndex, Error> {
+        let debug_pubnames = DebugPubNames::load(|section_id| self.section(section_id))?;
+        let debug_pubtypes = DebugPubTypes::load(|section_id| self.section(section_id))?;
+        UnitIndex::new(dwarf, self.debug_names()?, &debug_pubnames, &debug_pubtypes)
+    }
+
+    /// Load the sections of this context's view as the supplementary file of `dwarf`.
+    ///
+    /// See [`supplementary`] for finding the supplementary file of a view.
+    pub fn load_sup(&self, dwarf: &mut Dwarf<SectionReader>) -> Result<(), Error> {
+        dwarf.load_sup(|section_id| self.section(section_id))
+    }
+
+    /// Load the `.dwp` package index and sections of this context's view.
+    ///
+    /// Fails if the view has no `.debug_cu_index` section.
+    pub fn load_dwp(&self) -> Result<dwp::DwpArchive<SectionReader>, Error> {
+        self.required_section(SectionId::DebugCuIndex)?;
+        let empty = PagedReader::from_data(Rc::from([]), self.endian);
+        let package = DwarfPackage::load(|section_id| self.section(section_id), empty)?;
+        Ok(dwp::DwpArchive::new(package))
+    }
+
+    /// Load all DWARF sections, marking the result as a DWO file if appropriate.
+    pub fn load_dwarf(&self) -> Result<Dwarf<SectionReader>, Error> {
+        let mut dwarf = Dwarf::load(|section_id| self.section(section_id))?;
+        dwarf.file_type = if self.dwo_file {
+            DwarfFileType::Dwo
+        } else {
+            DwarfFileType::Main
+        };
+        Ok(dwarf)
+    }
+}
+
+fn section_name(section_id: SectionId, dwo_file: bool) -> &'static str {
+    match section_id.dwo_name() {
+        Some(dwo_name) if dwo_file => dwo_name,
+        _ => section_id.name(),
+    }
+}
+
+// Mach-O sections use `__debug_info` rather than `.debug_info`, and legacy compressed ELF
+// sections `.zdebug_info`
+fn find_section(view: &BinaryView, section_id: SectionId, dwo_file: bool) -> Option<Ref<Section>> {
+    find_section_by_name(view, section_name(section_id, dwo_file))
+}
+
+fn find_section_by_name(view: &BinaryView, section_name: &str) -> Option<Ref<Section>> {
+    view.section_by_name(section_name)
+        .or_else(|| view.section_by_name("__".to_string() + &section_name[1..]))
+        .or_else(|| view.section_by_name(".z".to_string() + &section_name[1..]))
+}
+
+fn read_section<Endian: Endianity>(
+    view: &BinaryView,
+    section: &Section,
+    endian: Endian,
+) -> Result<EndianRcSlice<Endian>, Error> {
+    if let Some(data) = read_compressed_section(view, section)? {
+        return Ok(EndianRcSlice::new(data.into(), endian));
+    }
+    let offset = section.start();
+    let len = section.len();
+    if len == 0 {
+        Ok(EndianRcSlice::new(Rc::from([]), endian))
+    } else {
         Ok(EndianRcSlice::new(
-            Rc::from(view.read_vec(section.start(), section.len()).as_slice()),
+            Rc::from(view.read_vec(offset, len).as_slice()),
             endian,
         ))
-    } else {
-        Ok(EndianRcSlice::new(Rc::from([]), endian))
+    }
+}
+
+/// Decompressed contents of the section, `None` if it is not compressed.
+fn read_compressed_section(view: &BinaryView, section: &Section) -> Result<Option<Vec<u8>>, Error> {
+    let Some(header) = section.compression(view) else {
+        return Ok(None);
+    };
+    let offset = section.start() + header.header_size as u64;
+    let len = section.len().saturating_sub(header.header_size);
+    let Ok(buffer) = view.read_buffer(offset, len) else {
+        return Ok(None);
+    };
+    match header.compression {
+        SectionCompression::Zlib | SectionCompression::ZlibGnu => {
+            Ok(Some(buffer.zlib_decompress().get_data().to_vec()))
+        }
+        SectionCompression::Zstd => Ok(Some(zstd::decode_all(buffer.get_data())?)),
+        SectionCompression::Unknown(x) => Err(Error::UnknownCompressionMethod(x)),
     }
 }
@@ -0,0 +1,306 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Locations of variables (`DW_AT_location` and `DW_AT_frame_base`).
+
+use std::ops::Range;
+
+use gimli::{AttributeValue, Dwarf, Encoding, Expression, Operation, Reader, Register, Unit};
+
+use crate::Error;
+
+/// Where a variable is stored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VariableLocation {
+    /// In a register.
+    Register(Register),
+    /// In memory, at the value of a register plus an offset.
+    RegisterOffset { register: Register, offset: i64 },
+    /// In memory, at the frame base of the function (`DW_AT_frame_base`) plus an offset.
+    FrameOffset(i64),
+    /// In memory, at the canonical frame address plus an offset.
+    CfaOffset(i64),
+    /// In memory, at a fixed address.
+    Address(u64),
+}
+
+impl VariableLocation {
+    /// Replace the frame base of a [`VariableLocation::FrameOffset`] by `frame_base`, the
+    /// location the `DW_AT_frame_base` of the function evaluates to.
+    ///
+    /// A frame base in a register is the value of that register, not memory it points to.
+    pub fn with_frame_base(self, frame_base: VariableLocation) -> Option<VariableLocation> {
+        let VariableLocation::FrameOffset(offset) = self else {
+            return Some(self);
+        };
+        match frame_base {
+            VariableLocation::Register(register) => {
+                Some(VariableLocation::RegisterOffset { register, offset })
+            }
+            VariableLocation::RegisterOffset {
+                register,
+                offset: base,
+            } => Some(VariableLocation::RegisterOffset {
+                register,
+                offset: base.wrapping_add(offset),
+            }),
+            VariableLocation::CfaOffset(base) => {
+                Some(VariableLocation::CfaOffset(base.wrapping_add(offset)))
+            }
+            VariableLocation::FrameOffset(_) | VariableLocation::Address(_) => None,
+        }
+    }
+}
+
+/// A location of a variable and the addresses it holds at, `None` for all of its scope.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocationRange {
+    pub range: Option<Range<u64>>,
+    pub location: VariableLocation,
+}
+
+/// A value on the stack of the evaluation.
+#[derive(Clone, Copy)]
+enum Value {
+    Constant(u64),
+    Register(Register, i64),
+    FrameBase(i64),
+    Cfa(i64),
+}
+
+impl Value {
+    fn add(self, delta: i64) -> Value {
+        match self {
+            Value::Constant(value) => Value::Constant(value.wrapping_add(delta as u64)),
+            Value::Register(register, offset) => {
+                Value::Register(register, offset.wrapping_add(delta))
+            }
+            Value::FrameBase(offset) => Value::FrameBase(offset.wrapping_add(delta)),
+            Value::Cfa(offset) => Value::Cfa(offset.wrapping_add(delta)),
+        }
+    }
+}
+
+/// The location `expression` describes, `None` if it isn't a location the evaluation supports,
+/// such as a computed value (`DW_OP_stack_value`) or a variable split over several pieces.
+///
+/// The expression is evaluated symbolically, without the registers and memory of the running
+/// program, which is enough for the expressions compilers emit for variables: they place a
+/// variable in a register, at an offset from a register or from the frame base of its function,
+/// or at a fixed address.
+pub fn evaluate_location<R: Reader>(
+    expression: Expression<R>,
+    encoding: Encoding,
+) -> Result<Option<VariableLocation>, Error> {
+    let mut operations = expression.operations(encoding);
+    let mut stack: Vec<Value> = vec![];
+    let mut register = None;
+    let mut pieces = 0;
+    while let Some(operation) = operations.next()? {
+        // Only the piece ending a register or memory location may follow it
+        if pieces > 0 || (register.is_some() && !matches!(operation, Operation::Piece { .. })) {
+            return Ok(None);
+        }
+        match operation {
+            Operation::Register { register: r } if stack.is_empty() => register = Some(r),
+            Operation::Address { address } => stack.push(Value::Constant(address)),
+            Operation::UnsignedConstant { value } => stack.push(Value::Constant(value)),
+            Operation::SignedConstant { value } => stack.push(Value::Constant(value as u64)),
+            Operation::RegisterOffset {
+                register, offset, ..
+            } => stack.push(Value::Register(register, offset)),
+            Operation::FrameOffset { offset } => stack.push(Value::FrameBase(offset)),
+            Operation::CallFrameCFA => stack.push(Value::Cfa(0)),
+            Operation::PlusConstant { value } => match stack.pop() {
+                Some(top) => stack.push(top.add(value as i64)),
+                None => return Ok(None),
+            },
+            Operation::Plus | Operation::Minus => {
+                let (Some(Value::Constant(delta)), Some(top)) = (stack.pop(), stack.pop()) else {
+                    return Ok(None);
+                };
+                let delta = match operation {
+                    Operation::Minus => (delta as i64).wrapping_neg(),
+                    _ => delta as i64,
+                };
+                stack.push(top.add(delta));
+            }
+            Operation::Piece { .. } => pieces += 1,
+            Operation::Nop => {}
+            _ => return Ok(None),
+        }
+    }
+
+    if let Some(register) = register {
+        return Ok(Some(VariableLocation::Register(register)));
+    }
+    Ok(stack.pop().map(|value| match value {
+        Value::Constant(address) => VariableLocation::Address(address),
+        Value::Register(register, offset) => VariableLocation::RegisterOffset { register, offset },
+        Value::FrameBase(offset) => VariableLocation::FrameOffset(offset),
+        Value::Cfa(offset) => VariableLocation::CfaOffset(offset),
+    }))
+}
+
+/// The locations of the value `attr` of a `DW_AT_location` or `DW_AT_frame_base` attribute of an
+/// entry of `unit`, either a single expression or a location list.
+///
+/// Entries of a location list the evaluation doesn't support are left out, as are those with
+/// empty address ranges.
+pub fn variable_locations<R: Reader>(
+    dwarf: &Dwarf<R>,
+    unit: &Unit<R>,
+    attr: AttributeValue<R>,
+) -> Result<Vec<LocationRange>, Error> {
+    if let AttributeValue::Exprloc(expression) = attr {
+        let location = evaluate_location(expression, unit.encoding())?;
+        return Ok(location
+            .map(|location| LocationRange {
+                range: None,
+                location,
+            })
+            .into_iter()
+            .collect());
+    }
+
+    let Some(offset) = dwarf.attr_locations_offset(unit, attr)? else {
+        return Ok(vec![]);
+    };
+    let mut locations = vec![];
+    let mut entries = dwarf.locations(unit, offset)?;
+    while let Some(entry) = entries.next()? {
+        if entry.range.begin >= entry.range.end {
+            continue;
+        }
+        if let Some(location) = evaluate_location(entry.data, unit.encoding())? {
+            locations.push(LocationRange {
+                range: Some(entry.range.begin..entry.range.end),
+                location,
+            });
+        }
+    }
+    Ok(locations)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gimli::{constants, EndianSlice, Format, LittleEndian};
+
+    fn evaluate(bytes: &[u8]) -> Option<VariableLocation> {
+        let encoding = Encoding {
+            format: Format::Dwarf32,
+            version: 5,
+            address_size: 8,
+        };
+        let expression = Expression(EndianSlice::new(bytes, LittleEndian));
+        evaluate_location(expression, encoding).unwrap()
+    }
+
+    #[test]
+    fn evaluates_locations() {
+        let rbp = Register(6);
+        assert_eq!(
+            evaluate(&[constants::DW_OP_reg6.0]),
+            Some(VariableLocation::Register(rbp))
+        );
+        // DW_OP_breg6 -24
+        assert_eq!(
+            evaluate(&[constants::DW_OP_breg6.0, 0x68]),
+            Some(VariableLocation::RegisterOffset {
+                register: rbp,
+                offset: -24
+            })
+        );
+        // DW_OP_fbreg -20
+        assert_eq!(
+            evaluate(&[constants::DW_OP_fbreg.0, 0x6c]),
+            Some(VariableLocation::FrameOffset(-20))
+        );
+        // DW_OP_call_frame_cfa; DW_OP_plus_uconst 8
+        assert_eq!(
+            evaluate(&[
+                constants::DW_OP_call_frame_cfa.0,
+                constants::DW_OP_plus_uconst.0,
+                8
+            ]),
+            Some(VariableLocation::CfaOffset(8))
+        );
+        // DW_OP_breg7 0; DW_OP_lit16; DW_OP_minus
+        assert_eq!(
+            evaluate(&[
+                constants::DW_OP_breg7.0,
+                0,
+                constants::DW_OP_lit16.0,
+                constants::DW_OP_minus.0
+            ]),
+            Some(VariableLocation::RegisterOffset {
+                register: Register(7),
+                offset: -16
+            })
+        );
+        // DW_OP_reg0; DW_OP_piece 4
+        assert_eq!(
+            evaluate(&[constants::DW_OP_reg0.0, constants::DW_OP_piece.0, 4]),
+            Some(VariableLocation::Register(Register(0)))
+        );
+    }
+
+    #[test]
+    fn rejects_unsupported_expressions() {
+        // DW_OP_lit1; DW_OP_stack_value
+        assert_eq!(
+            evaluate(&[constants::DW_OP_lit1.0, constants::DW_OP_stack_value.0]),
+            None
+        );
+        // DW_OP_reg0; DW_OP_piece 4; DW_OP_reg1; DW_OP_piece 4
+        assert_eq!(
+            evaluate(&[
+                constants::DW_OP_reg0.0,
+                constants::DW_OP_piece.0,
+                4,
+                constants::DW_OP_reg1.0,
+                constants::DW_OP_piece.0,
+                4
+            ]),
+            None
+        );
+        assert_eq!(evaluate(&[]), None);
+    }
+
+    #[test]
+    fn applies_frame_base() {
+        let variable = VariableLocation::FrameOffset(-20);
+        assert_eq!(
+            variable.with_frame_base(VariableLocation::CfaOffset(0)),
+            Some(VariableLocation::CfaOffset(-20))
+        );
+        assert_eq!(
+            variable.with_frame_base(VariableLocation::Register(Register(6))),
+            Some(VariableLocation::RegisterOffset {
+                register: Register(6),
+                offset: -20
+            })
+        );
+        assert_eq!(
+            variable.with_frame_base(VariableLocation::Address(0x1000)),
+            None
+        );
+        let register = VariableLocation::Register(Register(0));
+        assert_eq!(
+            register.with_frame_base(VariableLocation::CfaOffset(0)),
+            Some(register)
+        );
+    }
+}
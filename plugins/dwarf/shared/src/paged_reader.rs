@@ -0,0 +1,368 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A [`gimli::Reader`] that reads sections from the view a page at a time.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::fmt;
+use std::rc::Rc;
+
+use gimli::{Endianity, Reader, ReaderOffsetId};
+
+use binaryninja::{
+    binary_view::{BinaryView, BinaryViewExt},
+    rc::Ref,
+};
+
+/// Size of the pages sections are read in.
+pub const PAGE_SIZE: usize = 0x10000;
+
+/// Number of pages kept per section, 16 MiB with the default page size.
+pub const MAX_CACHED_PAGES: usize = 256;
+
+type ReadPage = dyn Fn(u64, usize) -> Vec<u8>;
+
+struct PageCache {
+    read_page: Box<ReadPage>,
+    len: u64,
+    page_size: usize,
+    capacity: usize,
+    /// Most recently used page first.
+    pages: RefCell<VecDeque<(u64, Rc<[u8]>)>>,
+}
+
+impl PageCache {
+    fn page(&self, index: u64) -> Rc<[u8]> {
+        let mut pages = self.pages.borrow_mut();
+        if let Some(position) = pages.iter().position(|(i, _)| *i == index) {
+            let entry = pages.remove(position).unwrap();
+            let data = entry.1.clone();
+            pages.push_front(entry);
+            return data;
+        }
+
+        let start = index * self.page_size as u64;
+        let len = (self.len - start).min(self.page_size as u64) as usize;
+        let mut data = (self.read_page)(start, len);
+        // Bytes not backed by the file read as zero, like they do in the view
+        data.resize(len, 0);
+        let data: Rc<[u8]> = data.into();
+        if pages.len() >= self.capacity {
+            pages.pop_back();
+        }
+        pages.push_front((index, data.clone()));
+        data
+    }
+
+    /// Calls `f` with the pieces of each page from `start` to `end`, until it returns `false`.
+    fn for_each_chunk(&self, start: u64, end: u64, mut f: impl FnMut(u64, &[u8]) -> bool) {
+        let page_size = self.page_size as u64;
+        let mut offset = start;
+        while offset < end {
+            let page = self.page(offset / page_size);
+            let in_page = (offset % page_size) as usize;
+            let len = (page.len() - in_page).min((end - offset) as usize);
+            if !f(offset, &page[in_page..in_page + len]) {
+                return;
+            }
+            offset += len as u64;
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Backing {
+    Memory(Rc<[u8]>),
+    Paged(Rc<PageCache>),
+}
+
+/// Reader over a section that is either held in memory, or read from the view on demand.
+///
+/// Debug info sections of large binaries can be several gigabytes, reading them into memory up
+/// front does not work for those. Pages are read on first access and only the most recently used
+/// ones are kept.
+///
+/// Cloning and splitting the reader is cheap, all clones share the same page cache.
+#[derive(Clone)]
+pub struct PagedReader<Endian: Endianity> {
+    backing: Backing,
+    /// Base of the offset ids, unique to the section.
+    id: u64,
+    start: usize,
+    end: usize,
+    endian: Endian,
+}
+
+impl<Endian: Endianity> PagedReader<Endian> {
+    /// Read the `len` bytes at `address` in `view` on demand, keeping [`MAX_CACHED_PAGES`] pages
+    /// of [`PAGE_SIZE`] bytes.
+    pub fn new(view: &BinaryView, address: u64, len: usize, endian: Endian) -> Self {
+        Self::with_page_size(view, address, len, endian, PAGE_SIZE, MAX_CACHED_PAGES)
+    }
+
+    pub fn with_page_size(
+        view: &BinaryView,
+        address: u64,
+        len: usize,
+        endian: Endian,
+        page_size: usize,
+        max_cached_pages: usize,
+    ) -> Self {
+        let view: Ref<BinaryView> = view.to_owned();
+        let read_page = move |offset: u64, len: usize| view.read_vec(address + offset, len);
+        Self::from_fn(read_page, address, len, endian, page_size, max_cached_pages)
+    }
+
+    fn from_fn(
+        read_page: impl Fn(u64, usize) -> Vec<u8> + 'static,
+        id: u64,
+        len: usize,
+        endian: Endian,
+        page_size: usize,
+        max_cached_pages: usize,
+    ) -> Self {
+        assert!(page_size > 0, "page size must be non-zero");
+        let cache = PageCache {
+            read_page: Box::new(read_page),
+            len: len as u64,
+            page_size,
+            capacity: max_cached_pages.max(1),
+            pages: RefCell::new(VecDeque::new()),
+        };
+        Self {
+            backing: Backing::Paged(Rc::new(cache)),
+            id,
+            start: 0,
+            end: len,
+            endian,
+        }
+    }
+
+    /// Reader over data that is already in memory, e.g. a decompressed section.
+    pub fn from_data(data: Rc<[u8]>, endian: Endian) -> Self {
+        let id = data.as_ptr() as u64;
+        let end = data.len();
+        Self {
+            backing: Backing::Memory(data),
+            id,
+            start: 0,
+            end,
+            endian,
+        }
+    }
+
+    pub fn is_paged(&self) -> bool {
+        matches!(self.backing, Backing::Paged(_))
+    }
+
+    fn eof(&self) -> gimli::Error {
+        gimli::Error::UnexpectedEof(Reader::offset_id(self))
+    }
+
+    fn check_len(&self, len: usize) -> gimli::Result<()> {
+        match len > self.end - self.start {
+            true => Err(self.eof()),
+            false => Ok(()),
+        }
+    }
+}
+
+impl<Endian: Endianity> fmt::Debug for PagedReader<Endian> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PagedReader")
+            .field("paged", &self.is_paged())
+            .field("start", &self.start)
+            .field("end", &self.end)
+            .field("endian", &self.endian)
+            .finish()
+    }
+}
+
+impl<Endian: Endianity> Reader for PagedReader<Endian> {
+    type Endian = Endian;
+    type Offset = usize;
+
+    fn endian(&self) -> Endian {
+        self.endian
+    }
+
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    fn empty(&mut self) {
+        self.start = self.end;
+    }
+
+    fn truncate(&mut self, len: usize) -> gimli::Result<()> {
+        self.check_len(len)?;
+        self.end = self.start + len;
+        Ok(())
+    }
+
+    fn offset_from(&self, base: &Self) -> usize {
+        self.start - base.start
+    }
+
+    fn offset_id(&self) -> ReaderOffsetId {
+        ReaderOffsetId(self.id + self.start as u64)
+    }
+
+    fn lookup_offset_id(&self, id: ReaderOffsetId) -> Option<usize> {
+        let start = self.id + self.start as u64;
+        let end = self.id + self.end as u64;
+        (start..=end)
+            .contains(&id.0)
+            .then(|| (id.0 - start) as usize)
+    }
+
+    fn find(&self, byte: u8) -> gimli::Result<usize> {
+        match &self.backing {
+            Backing::Memory(data) => data[self.start..self.end].iter().position(|b| *b == byte),
+            Backing::Paged(cache) => {
+                let mut found = None;
+                cache.for_each_chunk(self.start as u64, self.end as u64, |offset, chunk| {
+                    found = chunk
+                        .iter()
+                        .position(|b| *b == byte)
+                        .map(|i| offset as usize + i - self.start);
+                    found.is_none()
+                });
+                found
+            }
+        }
+        .ok_or_else(|| self.eof())
+    }
+
+    fn skip(&mut self, len: usize) -> gimli::Result<()> {
+        self.check_len(len)?;
+        self.start += len;
+        Ok(())
+    }
+
+    fn split(&mut self, len: usize) -> gimli::Result<Self> {
+        self.check_len(len)?;
+        let mut head = self.clone();
+        head.end = self.start + len;
+        self.start += len;
+        Ok(head)
+    }
+
+    fn to_slice(&self) -> gimli::Result<Cow<'_, [u8]>> {
+        match &self.backing {
+            Backing::Memory(data) => Ok(Cow::Borrowed(&data[self.start..self.end])),
+            Backing::Paged(_) => {
+                let mut buf = vec![0; self.len()];
+                self.clone().read_slice(&mut buf)?;
+                Ok(Cow::Owned(buf))
+            }
+        }
+    }
+
+    fn to_string(&self) -> gimli::Result<Cow<'_, str>> {
+        match self.to_slice()? {
+            Cow::Borrowed(bytes) => std::str::from_utf8(bytes).map(Cow::Borrowed),
+            Cow::Owned(bytes) => String::from_utf8(bytes)
+                .map(Cow::Owned)
+                .map_err(|e| e.utf8_error()),
+        }
+        .map_err(|_| gimli::Error::BadUtf8)
+    }
+
+    fn to_string_lossy(&self) -> gimli::Result<Cow<'_, str>> {
+        match self.to_slice()? {
+            Cow::Borrowed(bytes) => Ok(String::from_utf8_lossy(bytes)),
+            Cow::Owned(bytes) => Ok(Cow::Owned(String::from_utf8_lossy(&bytes).into_owned())),
+        }
+    }
+
+    fn read_slice(&mut self, buf: &mut [u8]) -> gimli::Result<()> {
+        self.check_len(buf.len())?;
+        let end = self.start + buf.len();
+        match &self.backing {
+            Backing::Memory(data) => buf.copy_from_slice(&data[self.start..end]),
+            Backing::Paged(cache) => {
+                cache.for_each_chunk(self.start as u64, end as u64, |offset, chunk| {
+                    let at = offset as usize - self.start;
+                    buf[at..at + chunk.len()].copy_from_slice(chunk);
+                    true
+                });
+            }
+        }
+        self.start = end;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gimli::LittleEndian;
+
+    fn paged(data: &[u8], page_size: usize, max_cached_pages: usize) -> PagedReader<LittleEndian> {
+        let data = data.to_vec();
+        let len = data.len();
+        PagedReader::from_fn(
+            move |offset, len| data[offset as usize..offset as usize + len].to_vec(),
+            0x1000,
+            len,
+            LittleEndian,
+            page_size,
+            max_cached_pages,
+        )
+    }
+
+    #[test]
+    fn reads_across_pages() {
+        let data: Vec<u8> = (0..=255).collect();
+        let mut reader = paged(&data, 16, 2);
+        reader.skip(14).unwrap();
+        assert_eq!(
+            reader.read_u32().unwrap(),
+            u32::from_le_bytes([14, 15, 16, 17])
+        );
+
+        let mut head = reader.split(40).unwrap();
+        assert_eq!(head.len(), 40);
+        assert_eq!(reader.read_u8().unwrap(), 58);
+        assert_eq!(head.to_slice().unwrap().as_ref(), &data[18..58]);
+        assert_eq!(head.find(50).unwrap(), 32);
+        assert!(head.find(60).is_err());
+
+        let mut buf = [0; 64];
+        assert!(head.read_slice(&mut buf).is_err());
+        reader.read_slice(&mut buf).unwrap();
+        assert_eq!(&buf[..], &data[59..123]);
+    }
+
+    #[test]
+    fn matches_memory_reader() {
+        let data = b"first\0second string\0".to_vec();
+        let mut memory = PagedReader::from_data(Rc::from(data.as_slice()), LittleEndian);
+        let mut paged = paged(&data, 4, 1);
+        for _ in 0..2 {
+            let expected = memory.read_null_terminated_slice().unwrap();
+            let actual = paged.read_null_terminated_slice().unwrap();
+            assert_eq!(expected.to_string().unwrap(), actual.to_string().unwrap());
+        }
+        assert!(memory.is_empty() && paged.is_empty());
+        assert_eq!(
+            paged.lookup_offset_id(paged.offset_id()),
+            Some(0),
+            "offset ids resolve against the reader they came from"
+        );
+    }
+}
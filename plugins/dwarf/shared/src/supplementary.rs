@@ -0,0 +1,303 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Supplementary object files, as produced by `dwz`.
+
+use std::path::{Path, PathBuf};
+
+use gimli::{
+    AttributeValue, DebugInfoOffset, Dwarf, EndianSlice, Endianity, Reader, Unit, UnitOffset,
+};
+
+use binaryninja::binary_view::{BinaryView, BinaryViewExt};
+
+use crate::{get_endian, Error};
+
+/// Where a [`SupplementaryLink`] was read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SupplementaryLinkKind {
+    /// The DWARF 5 `.debug_sup` section.
+    DebugSup,
+    /// The `.gnu_debugaltlink` section, its checksum is the build id of the supplementary file.
+    GnuDebugAltLink,
+}
+
+/// Reference from a file to the supplementary file holding part of its debug info.
+///
+/// Debug info shared between several files can be moved into a supplementary file (for example
+/// by `dwz`), which the files then refer to with `DW_FORM_ref_sup`/`DW_FORM_GNU_ref_alt`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SupplementaryLink {
+    pub kind: SupplementaryLinkKind,
+    /// Path of the supplementary file as recorded when it was created, often relative.
+    pub filename: String,
+    /// Identifies the supplementary file, empty if the producer did not record one.
+    pub checksum: Vec<u8>,
+}
+
+impl SupplementaryLink {
+    /// The checksum as a lowercase hex string, which for both kinds produced by `dwz` is the
+    /// build id of the supplementary file.
+    pub fn build_id(&self) -> Option<String> {
+        if self.checksum.is_empty() {
+            return None;
+        }
+        Some(self.checksum.iter().map(|b| format!("{:02x}", b)).collect())
+    }
+}
+
+/// Contents of a `.debug_sup` section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugSup {
+    pub version: u16,
+    /// Set in the supplementary file itself, unset in the files referring to it.
+    pub is_supplementary: bool,
+    pub filename: String,
+    pub checksum: Vec<u8>,
+}
+
+/// Parse the contents of a `.debug_sup` section.
+pub fn parse_debug_sup<Endian: Endianity>(data: &[u8], endian: Endian) -> Result<DebugSup, Error> {
+    let mut reader = EndianSlice::new(data, endian);
+    let version = reader.read_u16()?;
+    if version != 5 {
+        return Err(Error::UnsupportedVersion(".debug_sup", version));
+    }
+    let is_supplementary = reader.read_u8()? != 0;
+    let filename = reader
+        .read_null_terminated_slice()?
+        .to_string_lossy()
+        .into_owned();
+    let checksum_len = reader.read_uleb128()? as usize;
+    let checksum = reader.split(checksum_len)?.to_vec();
+    Ok(DebugSup {
+        version,
+        is_supplementary,
+        filename,
+        checksum,
+    })
+}
+
+/// Parse the contents of a `.gnu_debugaltlink` section, a path followed by the build id.
+pub fn parse_gnu_debugaltlink(data: &[u8]) -> Option<SupplementaryLink> {
+    let (filename, build_id) = data.split_at(data.iter().position(|b| *b == 0)?);
+    Some(SupplementaryLink {
+        kind: SupplementaryLinkKind::GnuDebugAltLink,
+        filename: String::from_utf8_lossy(filename).into_owned(),
+        checksum: build_id[1..].to_vec(),
+    })
+}
+
+fn read_named_section(view: &BinaryView, name: &str) -> Option<Vec<u8>> {
+    let macho_name = format!("__{}", &name[1..]);
+    [Some(view.to_owned()), view.raw_view()]
+        .into_iter()
+        .flatten()
+        .find_map(|view| {
+            let section = view
+                .section_by_name(name)
+                .or_else(|| view.section_by_name(macho_name.as_str()))?;
+            Some(view.read_vec(section.start(), section.len()))
+        })
+}
+
+/// The `.debug_sup` section of `view`, if it has one.
+pub fn debug_sup(view: &BinaryView) -> Option<Result<DebugSup, Error>> {
+    let data = read_named_section(view, ".debug_sup")?;
+    Some(parse_debug_sup(&data, get_endian(view)))
+}
+
+/// Whether `view` is itself a supplementary file.
+pub fn is_supplementary_file(view: &BinaryView) -> bool {
+    matches!(debug_sup(view), Some(Ok(sup)) if sup.is_supplementary)
+}
+
+/// The supplementary file `view` refers to, preferring `.debug_sup` over `.gnu_debugaltlink`.
+///
+/// A malformed `.debug_sup` is skipped, use [`debug_sup`] to get the error.
+pub fn supplementary_link(view: &BinaryView) -> Option<SupplementaryLink> {
+    if let Some(Ok(sup)) = debug_sup(view) {
+        if !sup.is_supplementary {
+            return Some(SupplementaryLink {
+                kind: SupplementaryLinkKind::DebugSup,
+                filename: sup.filename,
+                checksum: sup.checksum,
+            });
+        }
+    }
+    parse_gnu_debugaltlink(&read_named_section(view, ".gnu_debugaltlink")?)
+}
+
+/// Look for the file `link` refers to on disk.
+///
+/// Relative paths are resolved against the directory of `view`, then each of `search_dirs`, both
+/// as recorded and by file name alone.
+pub fn find_supplementary_file(
+    view: &BinaryView,
+    link: &SupplementaryLink,
+    search_dirs: &[PathBuf],
+) -> Option<PathBuf> {
+    let recorded = Path::new(&link.filename);
+    if recorded.is_absolute() {
+        return recorded.is_file().then(|| recorded.to_path_buf());
+    }
+    let view_path = PathBuf::from(view.file().filename().to_string());
+    let view_dir = view_path.parent().map(Path::to_path_buf);
+    view_dir
+        .iter()
+        .chain(search_dirs)
+        .flat_map(|dir| {
+            let by_name = recorded.file_name().map(|name| dir.join(name));
+            [Some(dir.join(recorded)), by_name]
+        })
+        .flatten()
+        .find(|candidate| candidate.is_file())
+}
+
+/// Check that `sup_view` is the supplementary file `link` refers to.
+///
+/// Only links from `.debug_sup` can be checked, other links are assumed to match.
+pub fn matches_supplementary_file(link: &SupplementaryLink, sup_view: &BinaryView) -> bool {
+    if link.kind != SupplementaryLinkKind::DebugSup || link.checksum.is_empty() {
+        return true;
+    }
+    match debug_sup(sup_view) {
+        Some(Ok(sup)) => sup.is_supplementary && sup.checksum == link.checksum,
+        _ => false,
+    }
+}
+
+/// A DIE that a reference resolved to, along with the file and unit it is in.
+pub struct DieRef<'a, R: Reader> {
+    pub dwarf: &'a Dwarf<R>,
+    pub unit: &'a Unit<R>,
+    pub offset: UnitOffset<R::Offset>,
+}
+
+/// Follows references between DIEs, including those into the supplementary file.
+///
+/// Holds the parsed units of a file and of its supplementary file, as loaded by
+/// [`gimli::Dwarf::load_sup`] or [`crate::DwarfReaderContext::load_sup`].
+pub struct DieResolver<R: Reader> {
+    units: Vec<Unit<R>>,
+    sup_units: Vec<Unit<R>>,
+}
+
+impl<R: Reader> DieResolver<R> {
+    /// Parse all units of `dwarf` and of its supplementary file.
+    pub fn new(dwarf: &Dwarf<R>) -> Result<Self, Error> {
+        let sup_units = match dwarf.sup() {
+            Some(sup) => parse_units(sup)?,
+            None => vec![],
+        };
+        Ok(Self {
+            units: parse_units(dwarf)?,
+            sup_units,
+        })
+    }
+
+    pub fn units(&self) -> &[Unit<R>] {
+        &self.units
+    }
+
+    pub fn sup_units(&self) -> &[Unit<R>] {
+        &self.sup_units
+    }
+
+    /// Resolve a reference `value` of an attribute of a DIE in `unit`.
+    ///
+    /// `dwarf` is the file `unit` belongs to, which is the supplementary file (without one of its
+    /// own) for DIEs in the supplementary units.
+    pub fn resolve<'a>(
+        &'a self,
+        dwarf: &'a Dwarf<R>,
+        unit: &'a Unit<R>,
+        value: AttributeValue<R>,
+    ) -> Option<DieRef<'a, R>> {
+        let find =
+            |dwarf: &'a Dwarf<R>, units: &'a [Unit<R>], offset: DebugInfoOffset<R::Offset>| {
+                units.iter().find_map(|unit| {
+                    let offset = offset.to_unit_offset(&unit.header)?;
+                    Some(DieRef {
+                        dwarf,
+                        unit,
+                        offset,
+                    })
+                })
+            };
+        match value {
+            AttributeValue::UnitRef(offset) => Some(DieRef {
+                dwarf,
+                unit,
+                offset,
+            }),
+            // A file with a supplementary file is the main file, only its own units are in scope
+            AttributeValue::DebugInfoRef(offset) if dwarf.sup().is_some() => {
+                find(dwarf, &self.units, offset)
+            }
+            // Otherwise this is either the supplementary file referring to itself, or a main file
+            // without a supplementary file
+            AttributeValue::DebugInfoRef(offset) => {
+                find(dwarf, &self.sup_units, offset).or_else(|| find(dwarf, &self.units, offset))
+            }
+            AttributeValue::DebugInfoRefSup(offset) => find(dwarf.sup()?, &self.sup_units, offset),
+            _ => None,
+        }
+    }
+}
+
+fn parse_units<R: Reader>(dwarf: &Dwarf<R>) -> Result<Vec<Unit<R>>, Error> {
+    let mut units = vec![];
+    let mut headers = dwarf.units();
+    while let Some(header) = headers.next()? {
+        units.push(dwarf.unit(header)?);
+    }
+    Ok(units)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use gimli::LittleEndian;
+
+    #[test]
+    fn parses_debug_sup() {
+        let mut data = vec![5, 0, 0];
+        data.extend_from_slice(b"../shared.debug\0");
+        data.push(4);
+        data.extend_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+        let sup = parse_debug_sup(&data, LittleEndian).unwrap();
+        assert!(!sup.is_supplementary);
+        assert_eq!(sup.filename, "../shared.debug");
+        assert_eq!(sup.checksum, [0xde, 0xad, 0xbe, 0xef]);
+
+        data[0] = 4;
+        assert!(parse_debug_sup(&data, LittleEndian).is_err());
+        assert!(parse_debug_sup(&data[..8], LittleEndian).is_err());
+    }
+
+    #[test]
+    fn parses_gnu_debugaltlink() {
+        let mut data = b"/usr/lib/debug/.dwz/x86_64-linux-gnu/libc6.debug\0".to_vec();
+        data.extend_from_slice(&[0x01, 0x23, 0xab]);
+        let link = parse_gnu_debugaltlink(&data).unwrap();
+        assert_eq!(link.kind, SupplementaryLinkKind::GnuDebugAltLink);
+        assert_eq!(
+            link.filename,
+            "/usr/lib/debug/.dwz/x86_64-linux-gnu/libc6.debug"
+        );
+        assert_eq!(link.build_id().as_deref(), Some("0123ab"));
+        assert!(parse_gnu_debugaltlink(b"no terminator").is_none());
+    }
+}
@@ -1,15 +1,102 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use anyhow::Result;
 
 use idb_rs::id0::ID0Section;
 use idb_rs::til;
 
+// IDA function flag for functions identified as library code, set by FLIRT
+const FUNC_LIB: u16 = 0x4;
+
+// Index of the additional flags (`aflags`) of an address in its netnode, see `nalt.hpp`
+const NALT_AFLAGS: u64 = 8;
+// Additional flags of names taken from the symbols of the input file by the loader
+const AFL_PUBNAM: u32 = 0x4;
+const AFL_WEAKNAM: u32 = 0x8;
+// Additional flag of items from the standard library, set by FLIRT
+const AFL_LIB: u32 = 0x400;
+
+// Prefixes of the dummy names IDA generates on its own, see `Options > Names`
+const IDA_DUMMY_NAME_PREFIXES: &[&str] = &[
+    "sub_",
+    "nullsub_",
+    "j_",
+    "loc_",
+    "locret_",
+    "off_",
+    "seg_",
+    "asc_",
+    "byte_",
+    "word_",
+    "dword_",
+    "qword_",
+    "xmmword_",
+    "ymmword_",
+    "byte3_",
+    "tbyte_",
+    "flt_",
+    "dbl_",
+    "packreal_",
+    "stru_",
+    "algn_",
+    "unk_",
+    "funcs_",
+    "def_",
+    "jpt_",
+    "custdata_",
+];
+
+/// Where the name of an address came from in the IDB
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NameSource {
+    /// Named by the user
+    User,
+    /// Library function name applied by FLIRT
+    Library,
+    /// Name generated by IDA auto analysis, or taken from the symbols of the input file
+    Auto,
+}
+
+impl NameSource {
+    /// Classify `label` using the additional flags of its address, see [`AddrInfo::flags`].
+    pub fn from_flags(label: &str, flags: u32, is_library_function: bool) -> Self {
+        if is_library_function || flags & AFL_LIB != 0 {
+            NameSource::Library
+        } else if is_ida_dummy_name(label) || flags & (AFL_PUBNAM | AFL_WEAKNAM) != 0 {
+            // Symbols of the input file are recovered by Binary Ninja itself
+            NameSource::Auto
+        } else {
+            NameSource::User
+        }
+    }
+
+    /// Component the names from this source are imported into
+    pub fn component(&self) -> &'static str {
+        match self {
+            NameSource::User => "IDA User Names",
+            NameSource::Library => "IDA Library Names",
+            NameSource::Auto => "IDA Auto Names",
+        }
+    }
+}
+
+// Dummy names are a known prefix followed by the hex address, e.g. `sub_401000`
+pub fn is_ida_dummy_name(label: &str) -> bool {
+    IDA_DUMMY_NAME_PREFIXES.iter().any(|prefix| {
+        label
+            .strip_prefix(prefix)
+            .is_some_and(|rest| !rest.is_empty() && rest.chars().all(|c| c.is_ascii_hexdigit()))
+    })
+}
+
 #[derive(Default)]
 pub struct AddrInfo<'a> {
     // TODO does binja diferenciate comments types on the API?
     pub comments: Vec<&'a [u8]>,
     pub label: Option<&'a str>,
+    pub label_source: Option<NameSource>,
+    /// Additional flags (`aflags`) of the address
+    pub flags: u32,
     // TODO make this a ref
     pub ty: Option<til::Type>,
 }
@@ -17,19 +104,22 @@ pub struct AddrInfo<'a> {
 pub fn get_info(id0: &ID0Section, version: u16) -> Result<HashMap<u64, AddrInfo<'_>>> {
     let mut addr_info: HashMap<u64, AddrInfo> = HashMap::new();
 
+    let mut library_functions = HashSet::new();
     // the old style comments, most likely empty on new versions
-    let old_comments = id0.functions_and_comments()?.filter_map(|fc| {
+    for fc in id0.functions_and_comments()? {
         use idb_rs::id0::FunctionsAndComments::*;
-        match fc {
-            Err(e) => Some(Err(e)),
-            Ok(Comment { address, comment }) => Some(Ok((address, comment))),
-            Ok(Name | Function(_) | Unknown { .. }) => None,
+        match fc? {
+            Comment { address, comment } => {
+                let comment = comment.message();
+                addr_info.entry(address).or_default().comments.push(comment);
+            }
+            Function(function) => {
+                if function.flags & FUNC_LIB != 0 {
+                    library_functions.insert(function.address.start);
+                }
+            }
+            Name | Unknown { .. } => {}
         }
-    });
-    for old_comment in old_comments {
-        let (addr, comment) = old_comment?;
-        let comment = comment.message();
-        addr_info.entry(addr).or_default().comments.push(comment);
     }
 
     // comments defined on the address information
@@ -49,9 +139,40 @@ pub fn get_info(id0: &ID0Section, version: u16) -> Result<HashMap<u64, AddrInfo<
                     panic!("Duplicated type for an address should be impossible this is most likelly a programing error")
                 }
             }
-            Other { .. } => {}
+            Other { key, value } => {
+                if let Some(flags) = parse_aflags(key, value) {
+                    entry.flags = flags;
+                }
+            }
         }
     }
 
+    for (addr, info) in addr_info.iter_mut() {
+        info.label_source = info.label.map(|label| {
+            NameSource::from_flags(label, info.flags, library_functions.contains(addr))
+        });
+    }
+
     Ok(addr_info)
 }
+
+// The key of an address netnode value is `.`, the address, the tag and the index, the last two
+// being as wide as the address
+fn parse_aflags(key: &[u8], value: &[u8]) -> Option<u32> {
+    let width = key.len().checked_sub(2)? / 2;
+    if !matches!(width, 4 | 8) || key.len() != 2 + width * 2 {
+        return None;
+    }
+    let (tag, index) = key[1 + width..].split_first()?;
+    let index = index
+        .iter()
+        .fold(0u64, |acc, byte| acc << 8 | u64::from(*byte));
+    if *tag != b'A' || index != NALT_AFLAGS || value.len() > 8 {
+        return None;
+    }
+    let flags = value
+        .iter()
+        .rev()
+        .fold(0u64, |acc, byte| acc << 8 | u64::from(*byte));
+    Some(flags as u32)
+}
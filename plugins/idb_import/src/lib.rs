@@ -2,20 +2,27 @@ mod types;
 use types::*;
 mod addr_info;
 use addr_info::*;
+mod names;
+use names::ImportedName;
 
 use binaryninja::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
 use binaryninja::debuginfo::{
     CustomDebugInfoParser, DebugFunctionInfo, DebugInfo, DebugInfoParser,
 };
+use binaryninja::import_diagnostics::ImportDiagnostics;
 
 use idb_rs::id0::{ID0Section, IDBParam1, IDBParam2};
 use idb_rs::til::section::TILSection;
 use idb_rs::til::TypeVariant as TILTypeVariant;
 
-use log::{error, trace, warn, LevelFilter};
+use log::{error, trace, LevelFilter};
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 use binaryninja::logger::Logger;
+use binaryninja::progress::ProgressScope;
+
+const IDB_PARSER_NAME: &str = "IDB Parser";
+const TIL_PARSER_NAME: &str = "TIL Parser";
 
 struct IDBDebugInfoParser;
 impl CustomDebugInfoParser for IDBDebugInfoParser {
@@ -34,15 +41,19 @@ impl CustomDebugInfoParser for IDBDebugInfoParser {
         debug_info: &mut DebugInfo,
         bv: &BinaryView,
         debug_file: &BinaryView,
-        progress: Box<dyn Fn(usize, usize) -> Result<(), ()>>,
+        progress: ProgressScope,
     ) -> bool {
-        match parse_idb_info(debug_info, bv, debug_file, progress) {
+        let mut diagnostics = ImportDiagnostics::new(IDB_PARSER_NAME);
+        let result = match parse_idb_info(debug_info, bv, debug_file, progress, &mut diagnostics) {
             Ok(()) => true,
             Err(error) => {
                 error!("Unable to parse IDB file: {error}");
+                diagnostics.warn(format!("Unable to parse IDB file: {error}"));
                 false
             }
-        }
+        };
+        diagnostics.store(bv);
+        result
     }
 }
 
@@ -59,17 +70,21 @@ impl CustomDebugInfoParser for TILDebugInfoParser {
     fn parse_info(
         &self,
         debug_info: &mut DebugInfo,
-        _bv: &BinaryView,
+        bv: &BinaryView,
         debug_file: &BinaryView,
-        progress: Box<dyn Fn(usize, usize) -> Result<(), ()>>,
+        progress: ProgressScope,
     ) -> bool {
-        match parse_til_info(debug_info, debug_file, progress) {
+        let mut diagnostics = ImportDiagnostics::new(TIL_PARSER_NAME);
+        let result = match parse_til_info(debug_info, debug_file, progress, &mut diagnostics) {
             Ok(()) => true,
             Err(error) => {
                 error!("Unable to parse TIL file: {error}");
+                diagnostics.warn(format!("Unable to parse TIL file: {error}"));
                 false
             }
-        }
+        };
+        diagnostics.store(bv);
+        result
     }
 }
 
@@ -109,7 +124,8 @@ fn parse_idb_info(
     debug_info: &mut DebugInfo,
     bv: &BinaryView,
     debug_file: &BinaryView,
-    progress: Box<dyn Fn(usize, usize) -> Result<(), ()>>,
+    progress: ProgressScope,
+    diagnostics: &mut ImportDiagnostics,
 ) -> Result<()> {
     trace!("Opening a IDB file");
     let file = BinaryViewReader {
@@ -119,19 +135,19 @@ fn parse_idb_info(
     trace!("Parsing a IDB file");
     let file = std::io::BufReader::new(file);
     let mut parser = idb_rs::IDBParser::new(file)?;
+    let parts = progress.subscopes(&[1, 1]);
     if let Some(til_section) = parser.til_section_offset() {
         trace!("Parsing the TIL section");
         let til = parser.read_til_section(til_section)?;
-        // progress 0%-50%
-        import_til_section(debug_info, debug_file, &til, progress)?;
+        import_til_section(debug_info, debug_file, &til, &parts[0], diagnostics)?;
     }
 
     if let Some(id0_section) = parser.id0_section_offset() {
         trace!("Parsing the ID0 section");
         let id0 = parser.read_id0_section(id0_section)?;
-        // progress 50%-100%
-        parse_id0_section_info(debug_info, bv, debug_file, &id0)?;
+        parse_id0_section_info(debug_info, bv, debug_file, &id0, &parts[1], diagnostics)?;
     }
+    let _ = parts[1].finish();
 
     Ok(())
 }
@@ -139,7 +155,8 @@ fn parse_idb_info(
 fn parse_til_info(
     debug_info: &mut DebugInfo,
     debug_file: &BinaryView,
-    progress: Box<dyn Fn(usize, usize) -> Result<(), ()>>,
+    progress: ProgressScope,
+    diagnostics: &mut ImportDiagnostics,
 ) -> Result<()> {
     trace!("Opening a TIL file");
     let file = BinaryViewReader {
@@ -149,16 +166,19 @@ fn parse_til_info(
     let mut file = std::io::BufReader::new(file);
     trace!("Parsing the TIL section");
     let til = TILSection::read(&mut file, idb_rs::IDBSectionCompression::None)?;
-    import_til_section(debug_info, debug_file, &til, progress)
+    import_til_section(debug_info, debug_file, &til, &progress, diagnostics)
 }
 
 pub fn import_til_section(
     debug_info: &mut DebugInfo,
     debug_file: &BinaryView,
     til: &TILSection,
-    progress: impl Fn(usize, usize) -> Result<(), ()>,
+    progress: &ProgressScope,
+    diagnostics: &mut ImportDiagnostics,
 ) -> Result<()> {
-    let types = types::translate_til_types(debug_file.default_arch().unwrap(), til, progress)?;
+    let types = types::translate_til_types(debug_file.default_arch().unwrap(), til, |cur, max| {
+        progress.report(cur, max)
+    })?;
 
     // print any errors
     for ty in &types {
@@ -175,6 +195,11 @@ pub fn import_til_section(
                     "Unable to parse type `{}`: {error}",
                     ty.name.as_utf8_lossy(),
                 );
+                diagnostics.skip(
+                    "type",
+                    None,
+                    format!("unable to parse `{}`: {error}", ty.name.as_utf8_lossy()),
+                );
             }
             TranslateTypeResult::PartiallyTranslated(_, error) => {
                 if let Some(error) = error {
@@ -182,11 +207,15 @@ pub fn import_til_section(
                         "Unable to parse type `{}` correctly: {error}",
                         ty.name.as_utf8_lossy(),
                     );
+                    diagnostics.warn(format!(
+                        "Type `{}` was only partially parsed: {error}",
+                        ty.name.as_utf8_lossy(),
+                    ));
                 } else {
-                    warn!(
+                    diagnostics.warn(format!(
                         "Type `{}` maybe not be fully translated",
                         ty.name.as_utf8_lossy(),
-                    );
+                    ));
                 }
             }
             TranslateTypeResult::Translated(_) => {}
@@ -198,8 +227,15 @@ pub fn import_til_section(
         if let TranslateTypeResult::Translated(bn_ty)
         | TranslateTypeResult::PartiallyTranslated(bn_ty, _) = &ty.ty
         {
-            if !debug_info.add_type(ty.name.as_utf8_lossy(), bn_ty, &[/* TODO */]) {
-                error!("Unable to add type `{}`", ty.name.as_utf8_lossy())
+            if debug_info.add_type(ty.name.as_utf8_lossy(), bn_ty, &[/* TODO */]) {
+                diagnostics.count("types");
+            } else {
+                error!("Unable to add type `{}`", ty.name.as_utf8_lossy());
+                diagnostics.skip(
+                    "type",
+                    None,
+                    format!("unable to add `{}`", ty.name.as_utf8_lossy()),
+                );
             }
         }
     }
@@ -223,18 +259,28 @@ fn parse_id0_section_info(
     bv: &BinaryView,
     debug_file: &BinaryView,
     id0: &ID0Section,
+    progress: &ProgressScope,
+    diagnostics: &mut ImportDiagnostics,
 ) -> Result<()> {
     let version = match id0.ida_info()? {
         idb_rs::id0::IDBParam::V1(IDBParam1 { version, .. })
         | idb_rs::id0::IDBParam::V2(IDBParam2 { version, .. }) => version,
     };
 
-    for (addr, info) in get_info(id0, version)? {
+    let mut names = vec![];
+    let addr_info = get_info(id0, version)?;
+    let total = addr_info.len();
+    for (index, (addr, info)) in addr_info.into_iter().enumerate() {
+        if progress.report(index, total).is_err() {
+            return Err(anyhow!("IDB import aborted"));
+        }
         // just in case we change this struct in the future, this line will for us to review this code
         // TODO merge this data with folder locations
         let AddrInfo {
             comments,
             label,
+            label_source,
+            flags: _,
             ty,
         } = info;
         // TODO set comments to address here
@@ -250,7 +296,7 @@ fn parse_id0_section_info(
             .and_then(|ty| match translate_ephemeral_type(debug_file, ty) {
                 TranslateTypeResult::Translated(result) => Some(result),
                 TranslateTypeResult::PartiallyTranslated(result, None) => {
-                    warn!("Unable to fully translate the type at {addr:#x}");
+                    diagnostics.warn(format!("Unable to fully translate the type at {addr:#x}"));
                     Some(result)
                 }
                 TranslateTypeResult::NotYet => {
@@ -264,54 +310,69 @@ fn parse_id0_section_info(
                 }
             });
 
+        // IDA dummy names (`sub_401000`, ...) are left for Binary Ninja to generate itself
+        let label = label.filter(|label| !is_ida_dummy_name(label));
+        if let (Some(name), Some(source @ (NameSource::User | NameSource::Library))) =
+            (label, label_source)
+        {
+            names.push(ImportedName {
+                addr,
+                name: name.to_string(),
+                source,
+                is_function: matches!(
+                    ty.as_ref().map(|ty| &ty.type_variant),
+                    Some(TILTypeVariant::Function(_))
+                ),
+            });
+        }
+        // Where a name came from is kept as the component it is imported into
+        let components: Vec<&str> = label
+            .and(label_source)
+            .map(|source| source.component())
+            .into_iter()
+            .collect();
+
         match (label, &ty, bnty) {
             (_, Some(ty), bnty) if matches!(&ty.type_variant, TILTypeVariant::Function(_)) => {
                 if bnty.is_none() {
                     error!("Unable to convert the function type at {addr:#x}",)
                 }
-                if !debug_info.add_function(DebugFunctionInfo::new(
+                if debug_info.add_function(DebugFunctionInfo::new(
                     None,
                     None,
                     label.map(str::to_string),
                     bnty,
                     Some(addr),
                     None,
-                    vec![],
+                    components.iter().map(|c| c.to_string()).collect(),
                     vec![],
                 )) {
-                    error!("Unable to add the function at {addr:#x}")
+                    diagnostics.count("functions");
+                } else {
+                    error!("Unable to add the function at {addr:#x}");
+                    diagnostics.skip("function", Some(addr), "unable to add the function");
                 }
             }
             (_, Some(_ty), Some(bnty)) => {
-                if !debug_info.add_data_variable(addr, &bnty, label, &[]) {
-                    error!("Unable to add the type at {addr:#x}")
+                if debug_info.add_data_variable(addr, &bnty, label, &components) {
+                    diagnostics.count("data variables");
+                } else {
+                    error!("Unable to add the type at {addr:#x}");
+                    diagnostics.skip("data variable", Some(addr), "unable to add the type");
                 }
             }
             (_, Some(_ty), None) => {
                 // TODO types come from the TIL sections, can we make all types be just NamedTypes?
                 error!("Unable to convert type {addr:#x}");
+                diagnostics.skip("type", Some(addr), "unable to convert the type");
                 // TODO how to add a label without a type associacted with it?
                 if let Some(name) = label {
-                    if !debug_info.add_data_variable(
-                        addr,
-                        &binaryninja::types::Type::void(),
-                        Some(name),
-                        &[],
-                    ) {
-                        error!("Unable to add the label at {addr:#x}")
-                    }
+                    add_label(debug_info, bv, diagnostics, addr, name, &components);
                 }
             }
             (Some(name), None, None) => {
                 // TODO how to add a label without a type associacted with it?
-                if !debug_info.add_data_variable(
-                    addr,
-                    &binaryninja::types::Type::void(),
-                    Some(name),
-                    &[],
-                ) {
-                    error!("Unable to add the label at {addr:#x}")
-                }
+                add_label(debug_info, bv, diagnostics, addr, name, &components);
             }
 
             // just comments at this address
@@ -320,17 +381,58 @@ fn parse_id0_section_info(
             (_, None, Some(_)) => unreachable!(),
         }
     }
+    names::keep(bv, names);
 
     Ok(())
 }
 
+fn add_label(
+    debug_info: &mut DebugInfo,
+    bv: &BinaryView,
+    diagnostics: &mut ImportDiagnostics,
+    addr: u64,
+    name: &str,
+    components: &[&str],
+) {
+    // Names of functions without a type, such as those of library functions, name the function
+    if !bv.functions_at(addr).is_empty() {
+        if debug_info.add_function(DebugFunctionInfo::new(
+            None,
+            None,
+            Some(name.to_string()),
+            None,
+            Some(addr),
+            None,
+            components.iter().map(|c| c.to_string()).collect(),
+            vec![],
+        )) {
+            diagnostics.count("functions");
+        } else {
+            error!("Unable to add the function at {addr:#x}");
+            diagnostics.skip("function", Some(addr), format!("unable to add `{name}`"));
+        }
+        return;
+    }
+    if debug_info.add_data_variable(
+        addr,
+        &binaryninja::types::Type::void(),
+        Some(name),
+        components,
+    ) {
+        diagnostics.count("labels");
+    } else {
+        error!("Unable to add the label at {addr:#x}");
+        diagnostics.skip("label", Some(addr), format!("unable to add `{name}`"));
+    }
+}
+
 #[allow(non_snake_case)]
 #[no_mangle]
 pub extern "C" fn CorePluginInit() -> bool {
     Logger::new("IDB Import")
         .with_level(LevelFilter::Error)
         .init();
-    DebugInfoParser::register("IDB Parser", IDBDebugInfoParser);
-    DebugInfoParser::register("TIL Parser", TILDebugInfoParser);
-    true
+    DebugInfoParser::register(IDB_PARSER_NAME, IDBDebugInfoParser);
+    DebugInfoParser::register(TIL_PARSER_NAME, TILDebugInfoParser);
+    names::register_activity()
 }
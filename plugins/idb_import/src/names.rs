@@ -0,0 +1,99 @@
+// A debug info parser must not change the view it parses, and debug info only creates auto
+// symbols. The names the user gave in IDA are kept here until the debug info is applied, then
+// defined as user symbols by a module activity, and the FLIRT names as library function symbols.
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use binaryninja::binary_view::{BinaryView, BinaryViewExt};
+use binaryninja::symbol::{Symbol, SymbolType};
+use binaryninja::workflow::{Activity, AnalysisContext, Workflow};
+
+use log::error;
+
+use crate::addr_info::NameSource;
+
+const ACTIVITY_NAME: &str = "analysis.plugins.idbImport.applyNames";
+const ACTIVITY_CONFIG: &str = r#"{
+    "name": "analysis.plugins.idbImport.applyNames",
+    "title": "Apply IDA Names",
+    "description": "This analysis step defines the names of the last IDB import as user symbols for the names given by the user in IDA, and as library function symbols for the names FLIRT applied.",
+    "eligibility": {
+        "auto": {},
+        "runOnce": false
+    }
+}"#;
+
+/// A name imported from the IDB, named by the user or by FLIRT.
+pub(crate) struct ImportedName {
+    pub(crate) addr: u64,
+    pub(crate) name: String,
+    pub(crate) source: NameSource,
+    /// The IDB gives the address a function type
+    pub(crate) is_function: bool,
+}
+
+// By session id of the view parsed
+static IMPORTED_NAMES: Mutex<BTreeMap<usize, Vec<ImportedName>>> = Mutex::new(BTreeMap::new());
+
+/// Keep the names of a run of the parser on `view` until its debug info is applied.
+pub(crate) fn keep(view: &BinaryView, names: Vec<ImportedName>) {
+    IMPORTED_NAMES
+        .lock()
+        .unwrap()
+        .insert(view.file().session_id(), names);
+}
+
+/// Define the names of the last run of the parser on `view`.
+fn apply(view: &BinaryView) {
+    let Some(names) = IMPORTED_NAMES
+        .lock()
+        .unwrap()
+        .remove(&view.file().session_id())
+    else {
+        return;
+    };
+
+    for ImportedName {
+        addr,
+        name,
+        source,
+        is_function,
+    } in names
+    {
+        let is_function = is_function || !view.functions_at(addr).is_empty();
+        match source {
+            NameSource::User => {
+                let sym_type = match is_function {
+                    true => SymbolType::Function,
+                    false => SymbolType::Data,
+                };
+                view.define_user_symbol(&Symbol::builder(sym_type, name, addr).create());
+            }
+            NameSource::Library if is_function => {
+                view.define_auto_symbol(
+                    &Symbol::builder(SymbolType::LibraryFunction, name, addr).create(),
+                );
+            }
+            // Other names are auto symbols of the debug info already
+            NameSource::Library | NameSource::Auto => {}
+        }
+    }
+}
+
+/// Define the names of each import once the analysis it starts completes.
+pub(crate) fn register_activity() -> bool {
+    let workflow = Workflow::instance("core.module.metaAnalysis").clone("core.module.metaAnalysis");
+    let activity =
+        Activity::new_with_action(ACTIVITY_CONFIG, |ctx: &AnalysisContext| apply(&ctx.view()));
+    if workflow.register_activity(&activity).is_err() {
+        error!("Failed to register the IDA name activity");
+        return false;
+    }
+    workflow.insert("core.module.notifyCompletion", [ACTIVITY_NAME]);
+    if workflow.register().is_err() {
+        error!("Failed to register the IDA name activity");
+        return false;
+    }
+    true
+}
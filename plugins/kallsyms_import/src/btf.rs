@@ -0,0 +1,457 @@
+//! Minimal reader for the BPF Type Format (`.BTF`) data embedded in kernels.
+
+use std::collections::HashMap;
+use std::fmt;
+
+pub const BTF_MAGIC: u16 = 0xeb9f;
+pub const HEADER_LEN: usize = 24;
+
+const BTF_KIND_INT: u32 = 1;
+const BTF_KIND_PTR: u32 = 2;
+const BTF_KIND_ARRAY: u32 = 3;
+const BTF_KIND_STRUCT: u32 = 4;
+const BTF_KIND_UNION: u32 = 5;
+const BTF_KIND_ENUM: u32 = 6;
+const BTF_KIND_FWD: u32 = 7;
+const BTF_KIND_TYPEDEF: u32 = 8;
+const BTF_KIND_VOLATILE: u32 = 9;
+const BTF_KIND_CONST: u32 = 10;
+const BTF_KIND_RESTRICT: u32 = 11;
+const BTF_KIND_FUNC: u32 = 12;
+const BTF_KIND_FUNC_PROTO: u32 = 13;
+const BTF_KIND_VAR: u32 = 14;
+const BTF_KIND_DATASEC: u32 = 15;
+const BTF_KIND_FLOAT: u32 = 16;
+const BTF_KIND_DECL_TAG: u32 = 17;
+const BTF_KIND_TYPE_TAG: u32 = 18;
+const BTF_KIND_ENUM64: u32 = 19;
+
+const BTF_INT_SIGNED: u32 = 1;
+const BTF_INT_CHAR: u32 = 2;
+const BTF_INT_BOOL: u32 = 4;
+
+pub type TypeId = u32;
+
+#[derive(Debug)]
+pub enum BtfError {
+    BadMagic,
+    Truncated,
+    UnknownKind(u32),
+}
+
+impl fmt::Display for BtfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BtfError::BadMagic => write!(f, "missing BTF magic"),
+            BtfError::Truncated => write!(f, "BTF data is truncated"),
+            BtfError::UnknownKind(kind) => write!(f, "unknown BTF kind {kind}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Member {
+    pub name: String,
+    pub type_id: TypeId,
+    pub bit_offset: u32,
+    /// Non-zero for bitfield members.
+    pub bit_size: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Param {
+    pub name: String,
+    pub type_id: TypeId,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BtfKind {
+    Void,
+    Int {
+        size: u32,
+        signed: bool,
+        is_char: bool,
+        is_bool: bool,
+    },
+    Pointer(TypeId),
+    Array {
+        element: TypeId,
+        count: u32,
+    },
+    Struct {
+        size: u32,
+        members: Vec<Member>,
+    },
+    Union {
+        size: u32,
+        members: Vec<Member>,
+    },
+    Enum {
+        size: u32,
+        signed: bool,
+        values: Vec<(String, i64)>,
+    },
+    Forward {
+        is_union: bool,
+    },
+    Typedef(TypeId),
+    Volatile(TypeId),
+    Const(TypeId),
+    Restrict(TypeId),
+    Function(TypeId),
+    FunctionProto {
+        return_type: TypeId,
+        params: Vec<Param>,
+        variadic: bool,
+    },
+    Variable(TypeId),
+    DataSection,
+    Float {
+        size: u32,
+    },
+    DeclTag(TypeId),
+    TypeTag(TypeId),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BtfType {
+    pub name: String,
+    pub kind: BtfKind,
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    big_endian: bool,
+}
+
+impl Reader<'_> {
+    fn u16(&self, pos: usize) -> Result<u16, BtfError> {
+        let bytes = self.data.get(pos..pos + 2).ok_or(BtfError::Truncated)?;
+        let bytes = [bytes[0], bytes[1]];
+        Ok(match self.big_endian {
+            true => u16::from_be_bytes(bytes),
+            false => u16::from_le_bytes(bytes),
+        })
+    }
+
+    fn u32(&self, pos: usize) -> Result<u32, BtfError> {
+        let bytes = self.data.get(pos..pos + 4).ok_or(BtfError::Truncated)?;
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        Ok(match self.big_endian {
+            true => u32::from_be_bytes(bytes),
+            false => u32::from_le_bytes(bytes),
+        })
+    }
+}
+
+/// The length of the BTF data starting with `header`, or `None` if it is not a BTF header.
+pub fn data_len(header: &[u8]) -> Option<usize> {
+    let big_endian = match header.get(..2)? {
+        magic if *magic == BTF_MAGIC.to_le_bytes() => false,
+        magic if *magic == BTF_MAGIC.to_be_bytes() => true,
+        _ => return None,
+    };
+    let reader = Reader {
+        data: header,
+        big_endian,
+    };
+    let field = |pos| reader.u32(pos).ok().map(|value| value as usize);
+    // Only version 1 exists, its header is always 24 bytes long
+    if header.get(2) != Some(&1) || field(4)? != HEADER_LEN {
+        return None;
+    }
+    let type_end = field(8)?.checked_add(field(12)?)?;
+    let str_end = field(16)?.checked_add(field(20)?)?;
+    Some(HEADER_LEN + type_end.max(str_end))
+}
+
+/// The offset and length of the first BTF data whose header is contained in `data`.
+pub fn find(data: &[u8]) -> Option<(usize, usize)> {
+    let last = data.len().checked_sub(HEADER_LEN)?;
+    (0..=last).find_map(|pos| Some((pos, data_len(&data[pos..pos + HEADER_LEN])?)))
+}
+
+/// The types and functions of BTF data, as embedded by kernels built with
+/// `CONFIG_DEBUG_INFO_BTF`. See `Documentation/bpf/btf.rst` for the format.
+pub struct Btf {
+    types: Vec<BtfType>,
+    functions: HashMap<String, TypeId>,
+}
+
+impl Btf {
+    pub fn parse(data: &[u8]) -> Result<Self, BtfError> {
+        let mut reader = Reader {
+            data,
+            big_endian: false,
+        };
+        match reader.u16(0)? {
+            BTF_MAGIC => {}
+            magic if magic.swap_bytes() == BTF_MAGIC => reader.big_endian = true,
+            _ => return Err(BtfError::BadMagic),
+        }
+
+        let header_len = reader.u32(4)? as usize;
+        if header_len < HEADER_LEN {
+            return Err(BtfError::Truncated);
+        }
+        let type_start = header_len + reader.u32(8)? as usize;
+        let type_end = type_start + reader.u32(12)? as usize;
+        let str_start = header_len + reader.u32(16)? as usize;
+        let str_end = str_start + reader.u32(20)? as usize;
+        if type_end > data.len() || str_end > data.len() {
+            return Err(BtfError::Truncated);
+        }
+
+        let strings = &data[str_start..str_end];
+        let string = |offset: u32| -> String {
+            let Some(tail) = strings.get(offset as usize..) else {
+                return String::new();
+            };
+            let len = tail.iter().position(|&b| b == 0).unwrap_or(tail.len());
+            String::from_utf8_lossy(&tail[..len]).into_owned()
+        };
+
+        // Type ids start at 1, id 0 is always void
+        let mut types = vec![BtfType {
+            name: String::new(),
+            kind: BtfKind::Void,
+        }];
+        let mut pos = type_start;
+        while pos < type_end {
+            let name = string(reader.u32(pos)?);
+            let info = reader.u32(pos + 4)?;
+            let size_or_type = reader.u32(pos + 8)?;
+            pos += 12;
+
+            let vlen = (info & 0xffff) as usize;
+            let kind_flag = info >> 31 != 0;
+            let kind = match (info >> 24) & 0x1f {
+                BTF_KIND_INT => {
+                    let encoding = reader.u32(pos)?;
+                    pos += 4;
+                    let encoding = (encoding >> 24) & 0xf;
+                    BtfKind::Int {
+                        size: size_or_type,
+                        signed: encoding & BTF_INT_SIGNED != 0,
+                        is_char: encoding & BTF_INT_CHAR != 0,
+                        is_bool: encoding & BTF_INT_BOOL != 0,
+                    }
+                }
+                BTF_KIND_PTR => BtfKind::Pointer(size_or_type),
+                BTF_KIND_ARRAY => {
+                    let element = reader.u32(pos)?;
+                    let count = reader.u32(pos + 8)?;
+                    pos += 12;
+                    BtfKind::Array { element, count }
+                }
+                kind @ (BTF_KIND_STRUCT | BTF_KIND_UNION) => {
+                    let mut members = Vec::with_capacity(vlen);
+                    for _ in 0..vlen {
+                        let offset = reader.u32(pos + 8)?;
+                        // With the kind flag set the offset also encodes the bitfield size
+                        let (bit_offset, bit_size) = match kind_flag {
+                            true => (offset & 0xffffff, offset >> 24),
+                            false => (offset, 0),
+                        };
+                        members.push(Member {
+                            name: string(reader.u32(pos)?),
+                            type_id: reader.u32(pos + 4)?,
+                            bit_offset,
+                            bit_size,
+                        });
+                        pos += 12;
+                    }
+                    match kind {
+                        BTF_KIND_STRUCT => BtfKind::Struct {
+                            size: size_or_type,
+                            members,
+                        },
+                        _ => BtfKind::Union {
+                            size: size_or_type,
+                            members,
+                        },
+                    }
+                }
+                kind @ (BTF_KIND_ENUM | BTF_KIND_ENUM64) => {
+                    let mut values = Vec::with_capacity(vlen);
+                    for _ in 0..vlen {
+                        let name = string(reader.u32(pos)?);
+                        let value = match kind {
+                            BTF_KIND_ENUM if kind_flag => reader.u32(pos + 4)? as i32 as i64,
+                            BTF_KIND_ENUM => reader.u32(pos + 4)? as i64,
+                            _ => {
+                                let low = reader.u32(pos + 4)? as u64;
+                                let high = reader.u32(pos + 8)? as u64;
+                                (high << 32 | low) as i64
+                            }
+                        };
+                        values.push((name, value));
+                        pos += if kind == BTF_KIND_ENUM { 8 } else { 12 };
+                    }
+                    BtfKind::Enum {
+                        size: size_or_type,
+                        signed: kind_flag,
+                        values,
+                    }
+                }
+                BTF_KIND_FWD => BtfKind::Forward {
+                    is_union: kind_flag,
+                },
+                BTF_KIND_TYPEDEF => BtfKind::Typedef(size_or_type),
+                BTF_KIND_VOLATILE => BtfKind::Volatile(size_or_type),
+                BTF_KIND_CONST => BtfKind::Const(size_or_type),
+                BTF_KIND_RESTRICT => BtfKind::Restrict(size_or_type),
+                BTF_KIND_FUNC => BtfKind::Function(size_or_type),
+                BTF_KIND_FUNC_PROTO => {
+                    let mut params = Vec::with_capacity(vlen);
+                    for _ in 0..vlen {
+                        params.push(Param {
+                            name: string(reader.u32(pos)?),
+                            type_id: reader.u32(pos + 4)?,
+                        });
+                        pos += 8;
+                    }
+                    // A trailing unnamed void parameter marks a variadic function
+                    let variadic = params
+                        .last()
+                        .is_some_and(|param| param.type_id == 0 && param.name.is_empty());
+                    if variadic {
+                        params.pop();
+                    }
+                    BtfKind::FunctionProto {
+                        return_type: size_or_type,
+                        params,
+                        variadic,
+                    }
+                }
+                BTF_KIND_VAR => {
+                    pos += 4;
+                    BtfKind::Variable(size_or_type)
+                }
+                BTF_KIND_DATASEC => {
+                    pos += 12 * vlen;
+                    BtfKind::DataSection
+                }
+                BTF_KIND_FLOAT => BtfKind::Float { size: size_or_type },
+                BTF_KIND_DECL_TAG => {
+                    pos += 4;
+                    BtfKind::DeclTag(size_or_type)
+                }
+                BTF_KIND_TYPE_TAG => BtfKind::TypeTag(size_or_type),
+                kind => return Err(BtfError::UnknownKind(kind)),
+            };
+            types.push(BtfType { name, kind });
+        }
+
+        let functions = types
+            .iter()
+            .filter_map(|ty| match ty.kind {
+                BtfKind::Function(proto) if !ty.name.is_empty() => Some((ty.name.clone(), proto)),
+                _ => None,
+            })
+            .collect();
+        Ok(Self { types, functions })
+    }
+
+    pub fn get(&self, id: TypeId) -> Option<&BtfType> {
+        self.types.get(id as usize)
+    }
+
+    pub fn len(&self) -> usize {
+        self.types.len()
+    }
+
+    /// The id of the `FUNC_PROTO` describing the function with the given name.
+    pub fn function_proto(&self, name: &str) -> Option<TypeId> {
+        self.functions.get(name).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push_type(types: &mut Vec<u8>, name_off: u32, kind: u32, vlen: u32, size_or_type: u32) {
+        types.extend_from_slice(&name_off.to_le_bytes());
+        types.extend_from_slice(&(kind << 24 | vlen).to_le_bytes());
+        types.extend_from_slice(&size_or_type.to_le_bytes());
+    }
+
+    fn sample() -> Vec<u8> {
+        let strings = b"\0int\0task_struct\0pid\0do_exit\0code\0";
+        let mut types = Vec::new();
+        // [1] int
+        push_type(&mut types, 1, BTF_KIND_INT, 0, 4);
+        types.extend_from_slice(&(BTF_INT_SIGNED << 24 | 32).to_le_bytes());
+        // [2] struct task_struct { int pid; }
+        push_type(&mut types, 5, BTF_KIND_STRUCT, 1, 4);
+        for value in [17u32, 1, 0] {
+            types.extend_from_slice(&value.to_le_bytes());
+        }
+        // [3] void (int code)
+        push_type(&mut types, 0, BTF_KIND_FUNC_PROTO, 1, 0);
+        for value in [29u32, 1] {
+            types.extend_from_slice(&value.to_le_bytes());
+        }
+        // [4] do_exit
+        push_type(&mut types, 21, BTF_KIND_FUNC, 0, 3);
+
+        let mut data = Vec::new();
+        data.extend_from_slice(&BTF_MAGIC.to_le_bytes());
+        data.extend_from_slice(&[1, 0]);
+        for value in [
+            HEADER_LEN as u32,
+            0,
+            types.len() as u32,
+            types.len() as u32,
+            strings.len() as u32,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(&types);
+        data.extend_from_slice(strings);
+        data
+    }
+
+    #[test]
+    fn test_parse() {
+        let data = sample();
+
+        let btf = Btf::parse(&data).expect("Failed to parse BTF");
+        assert_eq!(btf.len(), 5);
+        assert_eq!(
+            btf.get(2).unwrap().kind,
+            BtfKind::Struct {
+                size: 4,
+                members: vec![Member {
+                    name: "pid".to_string(),
+                    type_id: 1,
+                    bit_offset: 0,
+                    bit_size: 0,
+                }],
+            }
+        );
+        assert_eq!(btf.function_proto("do_exit"), Some(3));
+        assert_eq!(
+            btf.get(3).unwrap().kind,
+            BtfKind::FunctionProto {
+                return_type: 0,
+                params: vec![Param {
+                    name: "code".to_string(),
+                    type_id: 1,
+                }],
+                variadic: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_find() {
+        let btf = sample();
+        let mut data = vec![0xeb; 0x100];
+        data.extend_from_slice(&btf);
+        data.extend_from_slice(&[0; 0x10]);
+        assert_eq!(find(&data), Some((0x100, btf.len())));
+        assert_eq!(find(&data[..0x100 + HEADER_LEN - 1]), None);
+    }
+}
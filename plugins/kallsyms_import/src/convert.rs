@@ -0,0 +1,193 @@
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroUsize;
+
+use binaryninja::debuginfo::DebugInfo;
+use binaryninja::rc::Ref;
+use binaryninja::types::{
+    EnumerationBuilder, FunctionParameter, MemberAccess, MemberScope, NamedTypeReference,
+    NamedTypeReferenceClass, StructureBuilder, StructureType, Type,
+};
+
+use log::warn;
+
+use crate::btf::{Btf, BtfKind, Member, TypeId};
+
+/// Converts BTF types to Binary Ninja types. Named aggregates, enums and typedefs are referenced by
+/// name and their definitions are collected so they can be added to the debug info afterwards.
+pub struct TypeConverter<'a> {
+    btf: &'a Btf,
+    address_size: usize,
+    types: HashMap<TypeId, Ref<Type>>,
+    referenced: HashSet<TypeId>,
+    pending: Vec<TypeId>,
+}
+
+impl<'a> TypeConverter<'a> {
+    pub fn new(btf: &'a Btf, address_size: usize) -> Self {
+        Self {
+            btf,
+            address_size,
+            types: HashMap::new(),
+            referenced: HashSet::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// The type of the function with the given name, if BTF describes it.
+    pub fn function_type(&mut self, name: &str) -> Option<Ref<Type>> {
+        let proto = self.btf.function_proto(name)?;
+        Some(self.convert(proto))
+    }
+
+    /// Add the definitions of every named type referenced so far.
+    pub fn add_named_types(&mut self, debug_info: &DebugInfo) {
+        while let Some(id) = self.pending.pop() {
+            let Some(ty) = self.btf.get(id) else {
+                continue;
+            };
+            let name = ty.name.clone();
+            let definition = self.definition(id);
+            if !debug_info.add_type(name.as_str(), &definition, &[]) {
+                warn!("Unable to add type `{}`", name);
+            }
+        }
+    }
+
+    fn convert(&mut self, id: TypeId) -> Ref<Type> {
+        if let Some(ty) = self.types.get(&id) {
+            return ty.clone();
+        }
+        let Some(btf_type) = self.btf.get(id) else {
+            warn!("Reference to missing BTF type {}", id);
+            return Type::void();
+        };
+
+        let class = match btf_type.kind {
+            _ if btf_type.name.is_empty() => None,
+            BtfKind::Struct { .. } => Some(NamedTypeReferenceClass::StructNamedTypeClass),
+            BtfKind::Union { .. } => Some(NamedTypeReferenceClass::UnionNamedTypeClass),
+            BtfKind::Enum { .. } => Some(NamedTypeReferenceClass::EnumNamedTypeClass),
+            BtfKind::Typedef(_) => Some(NamedTypeReferenceClass::TypedefNamedTypeClass),
+            BtfKind::Forward { is_union: true } => {
+                Some(NamedTypeReferenceClass::UnionNamedTypeClass)
+            }
+            BtfKind::Forward { is_union: false } => {
+                Some(NamedTypeReferenceClass::StructNamedTypeClass)
+            }
+            _ => None,
+        };
+        let ty = match class {
+            Some(class) => {
+                // Forward declarations have nothing to define, the full type is elsewhere
+                if !matches!(btf_type.kind, BtfKind::Forward { .. }) && self.referenced.insert(id) {
+                    self.pending.push(id);
+                }
+                Type::named_type(&NamedTypeReference::new(class, btf_type.name.as_str()))
+            }
+            None => self.definition(id),
+        };
+        self.types.insert(id, ty.clone());
+        ty
+    }
+
+    fn definition(&mut self, id: TypeId) -> Ref<Type> {
+        let btf = self.btf;
+        let Some(btf_type) = btf.get(id) else {
+            return Type::void();
+        };
+        match &btf_type.kind {
+            BtfKind::Void | BtfKind::DataSection | BtfKind::Forward { .. } => Type::void(),
+            BtfKind::Int { is_bool: true, .. } => Type::bool(),
+            BtfKind::Int {
+                size: 1,
+                is_char: true,
+                ..
+            } => Type::char(),
+            BtfKind::Int { size, signed, .. } => Type::int(*size as usize, *signed),
+            BtfKind::Float { size } => Type::float(*size as usize),
+            BtfKind::Pointer(target) => {
+                let target = self.convert(*target);
+                Type::pointer_of_width(&target, self.address_size, false, false, None)
+            }
+            BtfKind::Array { element, count } => {
+                let element = self.convert(*element);
+                Type::array(&element, *count as u64)
+            }
+            BtfKind::Struct { size, members } => {
+                self.structure(*size, members, StructureType::StructStructureType)
+            }
+            BtfKind::Union { size, members } => {
+                self.structure(*size, members, StructureType::UnionStructureType)
+            }
+            BtfKind::Enum {
+                size,
+                signed,
+                values,
+            } => {
+                let mut builder = EnumerationBuilder::new();
+                for (name, value) in values {
+                    builder.insert(name.as_str(), *value as u64);
+                }
+                let width = NonZeroUsize::new(*size as usize).unwrap_or(NonZeroUsize::MIN);
+                Type::enumeration(&builder.finalize(), width, *signed)
+            }
+            BtfKind::Typedef(target)
+            | BtfKind::Restrict(target)
+            | BtfKind::Function(target)
+            | BtfKind::Variable(target)
+            | BtfKind::DeclTag(target)
+            | BtfKind::TypeTag(target) => self.convert(*target),
+            BtfKind::Const(target) => self
+                .convert(*target)
+                .to_builder()
+                .set_const(true)
+                .finalize(),
+            BtfKind::Volatile(target) => self
+                .convert(*target)
+                .to_builder()
+                .set_volatile(true)
+                .finalize(),
+            BtfKind::FunctionProto {
+                return_type,
+                params,
+                variadic,
+            } => {
+                let return_type = self.convert(*return_type);
+                let params = params
+                    .iter()
+                    .map(|param| {
+                        FunctionParameter::new(
+                            self.convert(param.type_id),
+                            param.name.clone(),
+                            None,
+                        )
+                    })
+                    .collect();
+                Type::function(&return_type, params, *variadic)
+            }
+        }
+    }
+
+    fn structure(
+        &mut self,
+        size: u32,
+        members: &[Member],
+        structure_type: StructureType,
+    ) -> Ref<Type> {
+        let mut builder = StructureBuilder::new();
+        builder.structure_type(structure_type).width(size as u64);
+        for member in members {
+            let ty = self.convert(member.type_id);
+            // Bitfields are placed at the byte containing their first bit
+            builder.insert(
+                &ty,
+                member.name.as_str(),
+                (member.bit_offset / 8) as u64,
+                false,
+                MemberAccess::NoAccess,
+                MemberScope::NoScope,
+            );
+        }
+        Type::structure(&builder.finalize())
+    }
+}
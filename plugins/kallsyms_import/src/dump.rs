@@ -0,0 +1,96 @@
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KernelSymbol {
+    pub address: u64,
+    /// The `nm` style type character, e.g. `T` for global text or `d` for local data.
+    pub kind: char,
+    pub name: String,
+    /// Set for symbols that belong to a loadable module rather than the kernel image.
+    pub module: Option<String>,
+}
+
+impl KernelSymbol {
+    pub fn new(address: u64, kind: char, name: &str) -> Self {
+        Self {
+            address,
+            kind,
+            name: name.to_string(),
+            module: None,
+        }
+    }
+
+    pub fn is_function(&self) -> bool {
+        matches!(self.kind, 'T' | 't' | 'W' | 'w')
+    }
+
+    pub fn is_absolute(&self) -> bool {
+        matches!(self.kind, 'A' | 'a')
+    }
+}
+
+fn parse_line(line: &str) -> Option<KernelSymbol> {
+    // ffffffffc0a01000 t foo_init	[foo]
+    let mut parts = line.split_whitespace();
+    let address = u64::from_str_radix(parts.next()?, 16).ok()?;
+    let mut kind = parts.next()?.chars();
+    let (Some(kind), None) = (kind.next(), kind.next()) else {
+        return None;
+    };
+    if !kind.is_ascii_alphabetic() && kind != '?' {
+        return None;
+    }
+    let name = parts.next()?;
+    let mut symbol = KernelSymbol::new(address, kind, name);
+    if let Some(module) = parts.next() {
+        symbol.module = Some(module.strip_prefix('[')?.strip_suffix(']')?.to_string());
+    }
+    Some(symbol)
+}
+
+/// Whether the text looks like a `/proc/kallsyms` or `nm` dump.
+pub fn looks_like_dump(contents: &str) -> bool {
+    let mut lines = contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(16);
+    let mut seen = false;
+    for line in lines.by_ref() {
+        if parse_line(line).is_none() {
+            return false;
+        }
+        seen = true;
+    }
+    seen
+}
+
+pub fn parse_dump(contents: &str) -> Vec<KernelSymbol> {
+    contents.lines().filter_map(parse_line).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dump() {
+        let dump = "\
+0000000000000000 A fixed_percpu_data
+ffffffff81000000 T _stext
+ffffffff81000040 t secondary_startup_64_no_verify
+ffffffffc0a01000 t foo_init\t[foo]
+";
+        assert!(looks_like_dump(dump));
+        assert_eq!(
+            parse_dump(dump),
+            vec![
+                KernelSymbol::new(0, 'A', "fixed_percpu_data"),
+                KernelSymbol::new(0xffffffff81000000, 'T', "_stext"),
+                KernelSymbol::new(0xffffffff81000040, 't', "secondary_startup_64_no_verify"),
+                KernelSymbol {
+                    module: Some("foo".to_string()),
+                    ..KernelSymbol::new(0xffffffffc0a01000, 't', "foo_init")
+                },
+            ]
+        );
+        assert!(!looks_like_dump("ELF\x02\x01\x01"));
+    }
+}
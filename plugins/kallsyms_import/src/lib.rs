@@ -0,0 +1,338 @@
+mod btf;
+mod convert;
+mod dump;
+mod table;
+
+use std::ops::Range;
+
+use binaryninja::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
+use binaryninja::command::register_command;
+use binaryninja::debuginfo::{
+    CustomDebugInfoParser, DebugFunctionInfo, DebugInfo, DebugInfoParser,
+};
+use binaryninja::logger::Logger;
+use binaryninja::progress::ProgressScope;
+use binaryninja::rc::Ref;
+use binaryninja::types::{MemberAccess, MemberScope, StructureBuilder, Type};
+
+use log::{debug, error, info, warn, LevelFilter};
+
+use btf::Btf;
+use convert::TypeConverter;
+use dump::KernelSymbol;
+
+const PARSER_NAME: &str = "Kallsyms";
+// Structure laying out the per-CPU variables, to be applied at the per-CPU base of a CPU
+const PER_CPU_TYPE_NAME: &str = "kallsyms_per_cpu";
+// Enough of the file to tell whether it is a text dump without reading all of it
+const SNIFF_LEN: u64 = 0x1000;
+// Raw images are scanned for the BTF header this many bytes at a time
+const BTF_SCAN_LEN: u64 = 0x10_0000;
+
+fn text_dump(view: &BinaryView, len: u64) -> Option<String> {
+    let contents = view.read_vec(view.start(), len.min(view.len()) as usize);
+    let contents = String::from_utf8(contents).ok()?;
+    dump::looks_like_dump(&contents).then_some(contents)
+}
+
+fn read_symbols(bv: &BinaryView, debug_file: &BinaryView) -> Vec<KernelSymbol> {
+    if text_dump(debug_file, SNIFF_LEN).is_some() {
+        let contents = debug_file.read_vec(debug_file.start(), debug_file.len() as usize);
+        return dump::parse_dump(&String::from_utf8_lossy(&contents));
+    }
+
+    // Otherwise look for the compressed table in the kernel image itself
+    let mut ranges: Vec<Range<u64>> = debug_file
+        .segments()
+        .iter()
+        .map(|segment| segment.address_range())
+        .collect();
+    if ranges.is_empty() {
+        ranges.push(debug_file.start()..debug_file.start() + debug_file.len());
+    }
+    for range in ranges {
+        let data = debug_file.read_vec(range.start, (range.end - range.start) as usize);
+        if let Some(symbols) = table::find_kallsyms(&data, bv.address_size()) {
+            debug!(
+                "Found kallsyms table in {:#x}-{:#x}",
+                range.start, range.end
+            );
+            return symbols;
+        }
+    }
+    Vec::new()
+}
+
+fn find_btf(view: &BinaryView) -> Option<Range<u64>> {
+    let end = view.start() + view.len();
+    let mut address = view.start();
+    while address < end {
+        // Overlap the next block so that a header crossing into it is still read whole
+        let len = (end - address).min(BTF_SCAN_LEN + btf::HEADER_LEN as u64 - 1);
+        let data = view.read_vec(address, len as usize);
+        if let Some((offset, len)) = btf::find(&data) {
+            let start = address + offset as u64;
+            return Some(start..end.min(start + len as u64));
+        }
+        address += BTF_SCAN_LEN;
+    }
+    None
+}
+
+fn load_btf(view: &BinaryView) -> Option<Btf> {
+    let range = match view.section_by_name(".BTF") {
+        Some(section) => section.address_range(),
+        // Raw images have no sections, the BTF data starts with its header
+        None => find_btf(view)?,
+    };
+    let data = view.read_vec(range.start, (range.end - range.start) as usize);
+    match Btf::parse(&data) {
+        Ok(btf) => {
+            debug!("Loaded {} BTF types", btf.len());
+            Some(btf)
+        }
+        Err(e) => {
+            warn!("Failed to parse BTF at {:#x}: {}", range.start, e);
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Region {
+    Init,
+    Exit,
+    PerCpu,
+}
+
+impl Region {
+    /// Component the functions and variables in the region are imported into
+    fn component(self) -> &'static str {
+        match self {
+            Region::Init => "Kernel Init",
+            Region::Exit => "Kernel Exit",
+            Region::PerCpu => "Per-CPU",
+        }
+    }
+}
+
+/// With absolute per-CPU symbols the per-CPU variables are offsets into the area of each CPU,
+/// described as a structure whose members only mark where each variable starts.
+fn per_cpu_type(symbols: &[(u64, &str)]) -> Ref<Type> {
+    let marker = Type::int(1, false);
+    let mut structure = StructureBuilder::new();
+    for (offset, name) in symbols {
+        structure.insert(
+            &marker,
+            *name,
+            *offset,
+            false,
+            MemberAccess::NoAccess,
+            MemberScope::NoScope,
+        );
+    }
+    Type::structure(&structure.finalize())
+}
+
+/// Address ranges of the init, exit and per-CPU areas, taken from the sections when the view has
+/// them and otherwise from the linker symbols bracketing them.
+struct Regions(Vec<(Range<u64>, Region)>);
+
+impl Regions {
+    fn new(bv: &BinaryView, symbols: &[KernelSymbol]) -> Self {
+        let mut regions = Vec::new();
+        for (name, region) in [
+            (".init.text", Region::Init),
+            (".init.data", Region::Init),
+            (".exit.text", Region::Exit),
+            (".exit.data", Region::Exit),
+            (".data..percpu", Region::PerCpu),
+        ] {
+            if let Some(section) = bv.section_by_name(name) {
+                regions.push((section.address_range(), region));
+            }
+        }
+
+        let address_of = |name: &str| {
+            symbols
+                .iter()
+                .find(|symbol| symbol.module.is_none() && symbol.name == name)
+                .map(|symbol| symbol.address)
+        };
+        for (start, end, region) in [
+            ("_sinittext", "_einittext", Region::Init),
+            ("__init_begin", "__init_end", Region::Init),
+            ("__per_cpu_start", "__per_cpu_end", Region::PerCpu),
+        ] {
+            if regions.iter().any(|(_, existing)| *existing == region) {
+                continue;
+            }
+            if let (Some(start), Some(end)) = (address_of(start), address_of(end)) {
+                regions.push((start..end, region));
+            }
+        }
+        Self(regions)
+    }
+
+    fn region_of(&self, address: u64) -> Option<Region> {
+        self.0
+            .iter()
+            .find(|(range, _)| range.contains(&address))
+            .map(|(_, region)| *region)
+    }
+}
+
+struct KallsymsDebugInfoParser;
+impl CustomDebugInfoParser for KallsymsDebugInfoParser {
+    fn is_valid(&self, view: &BinaryView) -> bool {
+        let file_name = if let Some(project_file) = view.file().project_file() {
+            project_file.name()
+        } else {
+            view.file().filename()
+        };
+        file_name.as_str().to_lowercase().contains("kallsyms")
+            || text_dump(view, SNIFF_LEN).is_some()
+            || view.section_by_name(".BTF").is_some()
+            || view.section_by_name("__ksymtab").is_some()
+    }
+
+    fn parse_info(
+        &self,
+        debug_info: &mut DebugInfo,
+        bv: &BinaryView,
+        debug_file: &BinaryView,
+        progress: ProgressScope,
+    ) -> bool {
+        let symbols = read_symbols(bv, debug_file);
+        if symbols.is_empty() {
+            error!("No kallsyms symbols found");
+            return false;
+        }
+        if symbols.iter().all(|symbol| symbol.address == 0) {
+            error!("All kallsyms addresses are zero, the dump was likely read without root or with kptr_restrict set");
+            return false;
+        }
+        debug!("Found {} kallsyms symbols", symbols.len());
+
+        let btf = load_btf(bv);
+        let mut converter = btf
+            .as_ref()
+            .map(|btf| TypeConverter::new(btf, bv.address_size()));
+        let regions = Regions::new(bv, &symbols);
+
+        let mut per_cpu_symbols = Vec::new();
+        let mut outside_view = 0;
+        let mut module_symbols = 0;
+        for (i, symbol) in symbols.iter().enumerate() {
+            if progress.report(i, symbols.len()).is_err() {
+                return false;
+            }
+            if symbol.is_absolute() {
+                continue;
+            }
+
+            let region = regions.region_of(symbol.address);
+            if !bv.offset_valid(symbol.address) {
+                if region == Some(Region::PerCpu) {
+                    // With absolute per-CPU symbols these are offsets from each CPU's area
+                    per_cpu_symbols.push((symbol.address, symbol.name.as_str()));
+                } else if symbol.module.is_some() {
+                    module_symbols += 1;
+                } else {
+                    outside_view += 1;
+                }
+                continue;
+            }
+
+            let components: Vec<&str> = region.map(Region::component).into_iter().collect();
+            if symbol.is_function() {
+                let function_type = converter
+                    .as_mut()
+                    .and_then(|converter| converter.function_type(&symbol.name));
+                if !debug_info.add_function(DebugFunctionInfo::new(
+                    None,
+                    None,
+                    Some(symbol.name.clone()),
+                    function_type,
+                    Some(symbol.address),
+                    None,
+                    components.iter().map(|c| c.to_string()).collect(),
+                    vec![],
+                )) {
+                    error!("Unable to add the function at {:#x}", symbol.address)
+                }
+            } else if !debug_info.add_data_variable(
+                symbol.address,
+                &Type::void(),
+                Some(symbol.name.as_str()),
+                &components,
+            ) {
+                error!("Unable to add the label at {:#x}", symbol.address)
+            }
+        }
+
+        if let Some(converter) = converter.as_mut() {
+            converter.add_named_types(debug_info);
+        }
+        if !per_cpu_symbols.is_empty()
+            && !debug_info.add_type(
+                PER_CPU_TYPE_NAME,
+                &per_cpu_type(&per_cpu_symbols),
+                &[Region::PerCpu.component()],
+            )
+        {
+            warn!("Unable to add type `{}`", PER_CPU_TYPE_NAME);
+        }
+        if module_symbols > 0 {
+            info!(
+                "Skipped {} symbols belonging to loaded modules",
+                module_symbols
+            );
+        }
+        if outside_view > symbols.len() / 2 {
+            let text = symbols
+                .iter()
+                .find(|symbol| symbol.name == "_text" || symbol.name == "_stext");
+            match text {
+                Some(text) => warn!(
+                    "{} of {} kallsyms symbols are outside of the view, rebase the view so the kernel text starts at {:#x}",
+                    outside_view,
+                    symbols.len(),
+                    text.address
+                ),
+                None => warn!(
+                    "{} of {} kallsyms symbols are outside of the view, the view is likely loaded at the wrong base address",
+                    outside_view,
+                    symbols.len()
+                ),
+            }
+        } else if outside_view > 0 {
+            debug!("Skipped {} symbols outside of the view", outside_view);
+        }
+        true
+    }
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "C" fn CorePluginInit() -> bool {
+    Logger::new("Kallsyms Import")
+        .with_level(LevelFilter::Error)
+        .init();
+    DebugInfoParser::register(PARSER_NAME, KallsymsDebugInfoParser);
+    register_command(
+        "Kallsyms\\Import Symbols from Kernel Image",
+        "Recover the kallsyms table embedded in the kernel image and apply its symbols",
+        |view: &BinaryView| {
+            let Ok(parser) = DebugInfoParser::from_name(PARSER_NAME) else {
+                error!("The kallsyms debug info parser is not registered");
+                return;
+            };
+            match parser.parse_debug_info(view, view, None) {
+                Some(debug_info) => view.apply_debug_info(&debug_info),
+                None => error!("No kallsyms table found in the kernel image"),
+            }
+        },
+    );
+    true
+}
@@ -0,0 +1,472 @@
+//! Recovery of the compressed symbol table (`CONFIG_KALLSYMS`) embedded in a kernel image.
+
+use crate::dump::KernelSymbol;
+
+const DIGIT_TOKENS: &[u8] = b"0\x001\x002\x003\x004\x005\x006\x007\x008\x009\x00";
+const FIRST_DIGIT_TOKEN: usize = b'0' as usize;
+const TOKEN_COUNT: usize = 256;
+const MARKER_STRIDE: usize = 256;
+// Largest possible compressed name entry: two length bytes and up to 512 token bytes
+const MAX_NAME_ENTRY: usize = 514;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Endian {
+    Little,
+    Big,
+}
+
+impl Endian {
+    fn read<const N: usize>(self, data: &[u8], pos: usize) -> Option<[u8; N]> {
+        let mut bytes: [u8; N] = data.get(pos..pos.checked_add(N)?)?.try_into().ok()?;
+        if self == Endian::Big {
+            bytes.reverse();
+        }
+        Some(bytes)
+    }
+
+    fn u16(self, data: &[u8], pos: usize) -> Option<u16> {
+        self.read(data, pos).map(u16::from_le_bytes)
+    }
+
+    fn u32(self, data: &[u8], pos: usize) -> Option<u32> {
+        self.read(data, pos).map(u32::from_le_bytes)
+    }
+
+    fn word(self, data: &[u8], pos: usize, size: usize) -> Option<u64> {
+        match size {
+            4 => self.u32(data, pos).map(u64::from),
+            8 => self.read(data, pos).map(u64::from_le_bytes),
+            _ => None,
+        }
+    }
+}
+
+fn align_up(value: usize, align: usize) -> usize {
+    value.div_ceil(align) * align
+}
+
+fn read_cstr(data: &[u8], pos: usize) -> Option<&[u8]> {
+    let len = data.get(pos..)?.iter().position(|&b| b == 0)?;
+    Some(&data[pos..pos + len])
+}
+
+struct TokenTable {
+    start: usize,
+    index_end: usize,
+    tokens: Vec<Vec<u8>>,
+    endian: Endian,
+}
+
+fn find_token_table(data: &[u8]) -> Option<TokenTable> {
+    let mut search_from = 0;
+    while let Some(found) = data
+        .get(search_from..)?
+        .windows(DIGIT_TOKENS.len())
+        .position(|window| window == DIGIT_TOKENS)
+    {
+        let digits = search_from + found;
+        if let Some(table) = token_table_at(data, digits) {
+            return Some(table);
+        }
+        search_from = digits + 1;
+    }
+    None
+}
+
+fn token_table_at(data: &[u8], digits: usize) -> Option<TokenTable> {
+    // The tokens from `0` onwards can be read forwards until the end of the table...
+    let mut tokens_after = Vec::with_capacity(TOKEN_COUNT - FIRST_DIGIT_TOKEN);
+    let mut pos = digits;
+    for _ in FIRST_DIGIT_TOKEN..TOKEN_COUNT {
+        let token = read_cstr(data, pos).filter(|token| !token.is_empty())?;
+        tokens_after.push(token);
+        pos += token.len() + 1;
+    }
+    let table_end = pos;
+
+    // ...and those before it backwards, except for the first whose start we cannot see
+    let mut tokens_before = Vec::with_capacity(FIRST_DIGIT_TOKEN);
+    let mut token_start = digits;
+    for _ in 1..FIRST_DIGIT_TOKEN {
+        let nul = token_start.checked_sub(1).filter(|&nul| data[nul] == 0)?;
+        let start = data[..nul]
+            .iter()
+            .rposition(|&b| b == 0)
+            .map_or(0, |prev_nul| prev_nul + 1);
+        if start == nul {
+            return None;
+        }
+        tokens_before.push(&data[start..nul]);
+        token_start = start;
+    }
+    tokens_before.reverse();
+
+    // Offsets of tokens 1..256 relative to token 1
+    let mut relative_offsets = Vec::with_capacity(TOKEN_COUNT - 1);
+    let mut offset = 0;
+    for token in tokens_before.iter().chain(tokens_after.iter()) {
+        relative_offsets.push(offset);
+        offset += token.len() + 1;
+    }
+
+    // The token index follows the table (after alignment) and tells us where the first token starts
+    for index_pos in table_end..table_end + 16 {
+        for endian in [Endian::Little, Endian::Big] {
+            if endian.u16(data, index_pos) != Some(0) {
+                continue;
+            }
+            let Some(first_len) = endian.u16(data, index_pos + 2).map(usize::from) else {
+                continue;
+            };
+            if first_len < 2 || first_len > token_start {
+                continue;
+            }
+            let matches = relative_offsets.iter().enumerate().all(|(i, offset)| {
+                endian.u16(data, index_pos + 2 * (i + 1)) == u16::try_from(first_len + offset).ok()
+            });
+            let start = token_start - first_len;
+            let first_token = &data[start..token_start - 1];
+            if !matches || first_token.contains(&0) {
+                continue;
+            }
+
+            let mut tokens = Vec::with_capacity(TOKEN_COUNT);
+            tokens.push(first_token.to_vec());
+            tokens.extend(tokens_before.iter().map(|token| token.to_vec()));
+            tokens.extend(tokens_after.iter().map(|token| token.to_vec()));
+            return Some(TokenTable {
+                start,
+                index_end: index_pos + 2 * TOKEN_COUNT,
+                tokens,
+                endian,
+            });
+        }
+    }
+    None
+}
+
+struct Markers {
+    start: usize,
+    offsets: Vec<u64>,
+}
+
+fn find_markers(data: &[u8], table_start: usize, endian: Endian, size: usize) -> Option<Markers> {
+    // Skip any alignment padding between the markers and the token table
+    let mut end = table_start / size * size;
+    while endian.word(data, end.checked_sub(size)?, size)? == 0 {
+        end -= size;
+        if table_start - end >= 8 {
+            return None;
+        }
+    }
+
+    // Markers are strictly increasing offsets into the names, starting at zero
+    let mut offsets = Vec::new();
+    let mut start = end;
+    let mut next = None;
+    loop {
+        start = start.checked_sub(size)?;
+        let offset = endian.word(data, start, size)?;
+        if next.is_some_and(|next| {
+            offset >= next || next - offset > (MARKER_STRIDE * MAX_NAME_ENTRY) as u64
+        }) {
+            return None;
+        }
+        offsets.push(offset);
+        if offset == 0 {
+            break;
+        }
+        next = Some(offset);
+    }
+    offsets.reverse();
+    (offsets.len() >= 2).then_some(Markers { start, offsets })
+}
+
+struct Names {
+    num_syms_pos: usize,
+    names: Vec<(char, String)>,
+}
+
+fn parse_names(
+    data: &[u8],
+    names_start: usize,
+    num_syms: usize,
+    markers: &Markers,
+    tokens: &[Vec<u8>],
+) -> Option<Vec<(char, String)>> {
+    let mut names = Vec::with_capacity(num_syms);
+    let mut pos = names_start;
+    for i in 0..num_syms {
+        if i % MARKER_STRIDE == 0
+            && markers.offsets.get(i / MARKER_STRIDE)? != &((pos - names_start) as u64)
+        {
+            return None;
+        }
+        // Since 6.1 entries longer than 127 tokens use a second length byte
+        let mut len = *data.get(pos)? as usize;
+        pos += 1;
+        if len & 0x80 != 0 {
+            len = (len & 0x7f) | ((*data.get(pos)? as usize) << 7);
+            pos += 1;
+        }
+        let compressed = data.get(pos..pos + len).filter(|_| len > 0)?;
+        pos += len;
+        if pos > markers.start {
+            return None;
+        }
+        let expanded: Vec<u8> = compressed
+            .iter()
+            .flat_map(|&token| tokens[token as usize].iter().copied())
+            .collect();
+        let (&kind, name) = expanded.split_first()?;
+        names.push((kind as char, String::from_utf8_lossy(name).into_owned()));
+    }
+    // The names run right up to the markers, modulo alignment
+    (markers.start - pos < 8).then_some(names)
+}
+
+fn find_names(data: &[u8], markers: &Markers, endian: Endian, tokens: &[Vec<u8>]) -> Option<Names> {
+    let marker_count = markers.offsets.len();
+    let min_syms = (marker_count - 1) * MARKER_STRIDE + 1;
+    let max_syms = marker_count * MARKER_STRIDE;
+
+    // The names start no later than the last marker allows, and the final block is bounded in size
+    let latest_start = markers
+        .start
+        .checked_sub(*markers.offsets.last()? as usize)?;
+    let earliest_start = latest_start.saturating_sub(MARKER_STRIDE * MAX_NAME_ENTRY + 16);
+    for num_syms_pos in (earliest_start..latest_start)
+        .rev()
+        .filter(|pos| pos % 4 == 0)
+    {
+        let Some(num_syms) = endian.u32(data, num_syms_pos).map(|n| n as usize) else {
+            continue;
+        };
+        if !(min_syms..=max_syms).contains(&num_syms) {
+            continue;
+        }
+        let mut candidates = vec![num_syms_pos + 4, align_up(num_syms_pos + 4, 8)];
+        candidates.dedup();
+        for names_start in candidates {
+            if let Some(names) = parse_names(data, names_start, num_syms, markers, tokens) {
+                return Some(Names {
+                    num_syms_pos,
+                    names,
+                });
+            }
+        }
+    }
+    None
+}
+
+enum AddressTable {
+    Absolute(usize),
+    Relative { offsets: usize, base: usize },
+}
+
+fn decode_addresses(
+    data: &[u8],
+    table: &AddressTable,
+    count: usize,
+    endian: Endian,
+    ptr_size: usize,
+) -> Option<Vec<u64>> {
+    match *table {
+        AddressTable::Absolute(start) => (0..count)
+            .map(|i| endian.word(data, start + i * ptr_size, ptr_size))
+            .collect(),
+        AddressTable::Relative { offsets, base } => {
+            let base = endian.word(data, base, ptr_size)?;
+            let offsets: Vec<i32> = (0..count)
+                .map(|i| {
+                    endian
+                        .u32(data, offsets + i * 4)
+                        .map(|offset| offset as i32)
+                })
+                .collect::<Option<_>>()?;
+            // With `CONFIG_KALLSYMS_ABSOLUTE_PERCPU` most offsets are negative and positive ones are
+            // absolute per-cpu addresses, otherwise every offset is unsigned from the base
+            let absolute_percpu = offsets.iter().filter(|&&offset| offset < 0).count() > count / 2;
+            Some(
+                offsets
+                    .iter()
+                    .map(|&offset| match offset {
+                        offset if !absolute_percpu => base.wrapping_add(offset as u32 as u64),
+                        offset if offset >= 0 => offset as u64,
+                        offset => base.wrapping_sub(1).wrapping_sub(offset as i64 as u64),
+                    })
+                    .collect(),
+            )
+        }
+    }
+}
+
+// The kallsyms table is sorted by address, so the right candidate decodes to a sorted list
+fn sorted_score(addresses: &[u64]) -> usize {
+    let sorted = addresses.windows(2).filter(|w| w[0] <= w[1]).count();
+    let distinct = addresses.windows(2).filter(|w| w[0] != w[1]).count();
+    if sorted * 10 < addresses.len() * 9 || distinct * 2 < addresses.len() {
+        return 0;
+    }
+    sorted
+}
+
+fn find_addresses(
+    data: &[u8],
+    names: &Names,
+    token_table: &TokenTable,
+    ptr_size: usize,
+) -> Option<Vec<u64>> {
+    let count = names.names.len();
+    let endian = token_table.endian;
+    let mut candidates = Vec::new();
+
+    // Before `kallsyms_num_syms` (older kernels)
+    for end in [names.num_syms_pos, names.num_syms_pos.saturating_sub(4)] {
+        if let Some(start) = end.checked_sub(count * ptr_size) {
+            candidates.push(AddressTable::Absolute(start));
+        }
+        if let Some(base) = end.checked_sub(ptr_size) {
+            for offsets_end in [base, base.saturating_sub(4)] {
+                if let Some(offsets) = offsets_end.checked_sub(count * 4) {
+                    candidates.push(AddressTable::Relative { offsets, base });
+                }
+            }
+        }
+    }
+
+    // After `kallsyms_token_index` (6.4 and later)
+    for start in [
+        align_up(token_table.index_end, 4),
+        align_up(token_table.index_end, ptr_size),
+    ] {
+        candidates.push(AddressTable::Absolute(align_up(start, ptr_size)));
+        let offsets_end = start + count * 4;
+        for base in [offsets_end, align_up(offsets_end, ptr_size)] {
+            candidates.push(AddressTable::Relative {
+                offsets: start,
+                base,
+            });
+        }
+    }
+
+    candidates
+        .iter()
+        .filter_map(|table| decode_addresses(data, table, count, endian, ptr_size))
+        .map(|addresses| (sorted_score(&addresses), addresses))
+        .filter(|(score, _)| *score > 0)
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, addresses)| addresses)
+}
+
+/// Locate and decode the kallsyms tables in `data`, an image of the kernel in memory order.
+///
+/// The tables are emitted by `scripts/kallsyms.c` as a group of adjacent arrays:
+///
+/// ```text
+/// kallsyms_offsets / kallsyms_addresses   (before 6.4)
+/// kallsyms_relative_base                  (before 6.4)
+/// kallsyms_num_syms
+/// kallsyms_names
+/// kallsyms_markers
+/// kallsyms_token_table
+/// kallsyms_token_index
+/// kallsyms_offsets / kallsyms_addresses   (6.4 and later)
+/// kallsyms_relative_base                  (6.4 and later)
+/// ```
+///
+/// None of these are exported, so we anchor on the token table (the single character tokens for
+/// `0` through `9` are always present) and work outwards, validating each array against the next.
+pub fn find_kallsyms(data: &[u8], ptr_size: usize) -> Option<Vec<KernelSymbol>> {
+    let token_table = find_token_table(data)?;
+    let names = [4, ptr_size].iter().find_map(|&size| {
+        let markers = find_markers(data, token_table.start, token_table.endian, size)?;
+        find_names(data, &markers, token_table.endian, &token_table.tokens)
+    })?;
+    let addresses = find_addresses(data, &names, &token_table, ptr_size)?;
+    Some(
+        names
+            .names
+            .into_iter()
+            .zip(addresses)
+            .map(|((kind, name), address)| KernelSymbol {
+                address,
+                kind,
+                name,
+                module: None,
+            })
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pad(data: &mut Vec<u8>, align: usize) {
+        data.resize(align_up(data.len(), align), 0);
+    }
+
+    // Lay out the tables the way `scripts/kallsyms.c` does for a 64-bit little endian kernel
+    // before 6.4, using single character tokens so names compress to themselves
+    fn build_image(names: &[String], base: u64) -> Vec<u8> {
+        let mut data = vec![0xaa; 0x40];
+
+        for i in 0..names.len() {
+            let offset = -1 - (i as i32 * 0x10);
+            data.extend_from_slice(&offset.to_le_bytes());
+        }
+        pad(&mut data, 8);
+        data.extend_from_slice(&base.to_le_bytes());
+        data.extend_from_slice(&(names.len() as u32).to_le_bytes());
+        pad(&mut data, 8);
+
+        let names_start = data.len();
+        let mut markers = Vec::new();
+        for (i, name) in names.iter().enumerate() {
+            if i % MARKER_STRIDE == 0 {
+                markers.push((data.len() - names_start) as u32);
+            }
+            let entry = format!("T{name}");
+            data.push(entry.len() as u8);
+            data.extend_from_slice(entry.as_bytes());
+        }
+        pad(&mut data, 8);
+        for marker in markers {
+            data.extend_from_slice(&marker.to_le_bytes());
+        }
+        pad(&mut data, 8);
+
+        let token_start = data.len();
+        let mut token_index = Vec::new();
+        for token in 0..TOKEN_COUNT {
+            token_index.push((data.len() - token_start) as u16);
+            match token {
+                0 => data.extend_from_slice(b"__"),
+                token => data.push(token as u8),
+            }
+            data.push(0);
+        }
+        pad(&mut data, 8);
+        for offset in token_index {
+            data.extend_from_slice(&offset.to_le_bytes());
+        }
+        data.extend_from_slice(&[0xbb; 0x40]);
+        data
+    }
+
+    #[test]
+    fn test_find_kallsyms() {
+        let base = 0xffffffff81000000;
+        let names: Vec<String> = (0..300).map(|i| format!("func_{i}")).collect();
+        let data = build_image(&names, base);
+
+        let symbols = find_kallsyms(&data, 8).expect("Failed to find kallsyms");
+        assert_eq!(symbols.len(), names.len());
+        for (i, symbol) in symbols.iter().enumerate() {
+            assert_eq!(symbol.name, names[i]);
+            assert_eq!(symbol.kind, 'T');
+            assert_eq!(symbol.address, base + i as u64 * 0x10);
+        }
+    }
+}
@@ -0,0 +1,174 @@
+mod parser;
+
+use binaryninja::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
+use binaryninja::debuginfo::{
+    CustomDebugInfoParser, DebugFunctionInfo, DebugInfo, DebugInfoParser,
+};
+use binaryninja::import_diagnostics::ImportDiagnostics;
+use binaryninja::logger::Logger;
+use binaryninja::progress::ProgressScope;
+use binaryninja::section::Semantics;
+use binaryninja::types::Type;
+
+use log::{debug, error, warn, LevelFilter};
+
+use parser::{MapSymbol, SymbolKind};
+
+const MAP_FILE_EXTENSIONS: &[&str] = &[".map", ".sym", ".syms"];
+
+const PARSER_NAME: &str = "Map File Parser";
+
+struct MapFileDebugInfoParser;
+impl CustomDebugInfoParser for MapFileDebugInfoParser {
+    fn is_valid(&self, view: &BinaryView) -> bool {
+        let file_name = if let Some(project_file) = view.file().project_file() {
+            project_file.name()
+        } else {
+            view.file().filename()
+        };
+        let file_name = file_name.as_str().to_lowercase();
+        MAP_FILE_EXTENSIONS
+            .iter()
+            .any(|extension| file_name.ends_with(extension))
+    }
+
+    fn parse_info(
+        &self,
+        debug_info: &mut DebugInfo,
+        bv: &BinaryView,
+        debug_file: &BinaryView,
+        progress: ProgressScope,
+    ) -> bool {
+        let contents = debug_file.read_vec(debug_file.start(), debug_file.len() as usize);
+        let contents = String::from_utf8_lossy(&contents);
+        let symbols = parser::parse(&contents);
+        let mut diagnostics = ImportDiagnostics::new(PARSER_NAME);
+        if symbols.is_empty() {
+            error!("No symbols found in map file");
+            diagnostics.warn("No symbols found in map file");
+            diagnostics.store(bv);
+            return false;
+        }
+        debug!(
+            "Parsed {} symbols from {:?} map file",
+            symbols.len(),
+            parser::detect_format(&contents)
+        );
+
+        let mut skipped = 0;
+        for (i, symbol) in symbols.iter().enumerate() {
+            if progress.report(i, symbols.len()).is_err() {
+                return false;
+            }
+            match symbol_kind(bv, symbol) {
+                Some(SymbolKind::Function) => {
+                    if debug_info.add_function(DebugFunctionInfo::new(
+                        None,
+                        None,
+                        Some(symbol.name.clone()),
+                        None,
+                        Some(symbol.address),
+                        None,
+                        vec![],
+                        vec![],
+                    )) {
+                        diagnostics.count("functions");
+                    } else {
+                        error!("Unable to add the function at {:#x}", symbol.address);
+                        diagnostics.skip(
+                            "function",
+                            Some(symbol.address),
+                            format!("unable to add `{}`", symbol.name),
+                        );
+                    }
+                }
+                Some(_) => {
+                    // TODO how to add a label without a type associacted with it?
+                    if debug_info.add_data_variable(
+                        symbol.address,
+                        &Type::void(),
+                        Some(symbol.name.as_str()),
+                        &[],
+                    ) {
+                        diagnostics.count("labels");
+                    } else {
+                        error!("Unable to add the label at {:#x}", symbol.address);
+                        diagnostics.skip(
+                            "label",
+                            Some(symbol.address),
+                            format!("unable to add `{}`", symbol.name),
+                        );
+                    }
+                }
+                None => {
+                    skipped += 1;
+                    diagnostics.skip(
+                        "symbol",
+                        Some(symbol.address),
+                        format!("`{}` is outside of the view's sections", symbol.name),
+                    );
+                }
+            }
+        }
+        if skipped > 0 {
+            diagnostics.warn(format!(
+                "Skipped {} of {} map file symbols outside of the view's sections",
+                skipped,
+                symbols.len()
+            ));
+        }
+        diagnostics.store(bv);
+        true
+    }
+}
+
+// Map files for a different build (or a different base address) are common, so only accept
+// symbols that land in the view, and prefer the section semantics over what the map file says
+fn symbol_kind(bv: &BinaryView, symbol: &MapSymbol) -> Option<SymbolKind> {
+    let sections = bv.sections_at(symbol.address);
+    let Some(section) = sections.iter().next() else {
+        if bv.sections().is_empty() && bv.offset_valid(symbol.address) {
+            // Without sections (e.g. raw firmware) fall back to segment permissions
+            return Some(match symbol.kind {
+                SymbolKind::Unknown if bv.offset_executable(symbol.address) => SymbolKind::Function,
+                SymbolKind::Unknown => SymbolKind::Data,
+                kind => kind,
+            });
+        }
+        debug!(
+            "Symbol `{}` at {:#x} is outside of any section",
+            symbol.name, symbol.address
+        );
+        return None;
+    };
+
+    match (symbol.kind, section.semantics()) {
+        (SymbolKind::Function, Semantics::ReadOnlyCode) => Some(SymbolKind::Function),
+        (SymbolKind::Function, _) => {
+            warn!(
+                "Function `{}` at {:#x} is in non-code section `{}`, adding it as data",
+                symbol.name,
+                symbol.address,
+                section.name()
+            );
+            Some(SymbolKind::Data)
+        }
+        (SymbolKind::Unknown, Semantics::ReadOnlyCode) => Some(SymbolKind::Function),
+        (SymbolKind::Unknown, Semantics::DefaultSection)
+            if bv.offset_executable(symbol.address) =>
+        {
+            Some(SymbolKind::Function)
+        }
+        _ => Some(SymbolKind::Data),
+    }
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "C" fn CorePluginInit() -> bool {
+    Logger::new("Map Import")
+        .with_level(LevelFilter::Error)
+        .init();
+    DebugInfoParser::register(PARSER_NAME, MapFileDebugInfoParser);
+    true
+}
@@ -0,0 +1,271 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapFormat {
+    /// GNU ld `-Map` output
+    Gnu,
+    /// MSVC `link /MAP` output
+    Msvc,
+    /// One symbol per line, either `addr name` or nm style `addr type name`
+    SymbolList,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymbolKind {
+    Function,
+    Data,
+    /// The map file does not say, decide based on where the address lands
+    Unknown,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MapSymbol {
+    pub address: u64,
+    pub name: String,
+    pub kind: SymbolKind,
+}
+
+impl MapSymbol {
+    fn new(address: u64, name: &str, kind: SymbolKind) -> Self {
+        Self {
+            address,
+            name: name.to_string(),
+            kind,
+        }
+    }
+}
+
+pub fn detect_format(contents: &str) -> MapFormat {
+    if contents.contains("Publics by Value") {
+        MapFormat::Msvc
+    } else if contents.contains("Linker script and memory map") {
+        MapFormat::Gnu
+    } else {
+        MapFormat::SymbolList
+    }
+}
+
+pub fn parse(contents: &str) -> Vec<MapSymbol> {
+    match detect_format(contents) {
+        MapFormat::Gnu => parse_gnu(contents),
+        MapFormat::Msvc => parse_msvc(contents),
+        MapFormat::SymbolList => parse_symbol_list(contents),
+    }
+}
+
+fn parse_hex(s: &str) -> Option<u64> {
+    let s = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    u64::from_str_radix(s, 16).ok()
+}
+
+fn is_symbol_name(s: &str) -> bool {
+    s.chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || matches!(c, '_' | '$' | '.' | '?' | '@'))
+}
+
+fn section_kind(section: &str) -> SymbolKind {
+    if section.starts_with(".text") || section.starts_with(".init") || section.starts_with(".fini")
+    {
+        SymbolKind::Function
+    } else if section.is_empty() {
+        SymbolKind::Unknown
+    } else {
+        SymbolKind::Data
+    }
+}
+
+// Symbol lines in the memory map are indented and consist of just an address and a name:
+//  .text          0x0000000008000000      0x1a4 startup.o
+//                 0x0000000008000000                Reset_Handler
+fn parse_gnu(contents: &str) -> Vec<MapSymbol> {
+    let mut symbols = Vec::new();
+    let mut in_memory_map = false;
+    let mut current_section = "";
+    for line in contents.lines() {
+        if line.starts_with("Linker script and memory map") {
+            in_memory_map = true;
+            continue;
+        }
+        if !in_memory_map || line.trim().is_empty() {
+            continue;
+        }
+
+        // Output sections start at column 0, input sections are indented by a single space
+        if !line.starts_with("  ") {
+            if let Some(section) = line.split_whitespace().next() {
+                if section.starts_with('.') {
+                    current_section = section;
+                }
+            }
+            continue;
+        }
+
+        let mut parts = line.split_whitespace();
+        let (Some(address), Some(name), None) = (parts.next(), parts.next(), parts.next()) else {
+            continue;
+        };
+        let Some(address) = address
+            .starts_with("0x")
+            .then(|| parse_hex(address))
+            .flatten()
+        else {
+            continue;
+        };
+        if !is_symbol_name(name) {
+            continue;
+        }
+        symbols.push(MapSymbol::new(address, name, section_kind(current_section)));
+    }
+    symbols
+}
+
+//   Address         Publics by Value              Rva+Base               Lib:Object
+//  0001:00000000       _main                      00401000 f   main.obj
+fn parse_msvc(contents: &str) -> Vec<MapSymbol> {
+    let mut symbols = Vec::new();
+    let mut in_symbols = false;
+    for line in contents.lines() {
+        let trimmed = line.trim();
+        if trimmed.contains("Publics by Value") || trimmed.starts_with("Static symbols") {
+            in_symbols = true;
+            continue;
+        }
+        if trimmed.starts_with("entry point at") {
+            in_symbols = false;
+            continue;
+        }
+        if !in_symbols {
+            continue;
+        }
+
+        let mut parts = trimmed.split_whitespace();
+        let (Some(segment_offset), Some(name), Some(rva_base)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        let Some((segment, _offset)) = segment_offset.split_once(':') else {
+            continue;
+        };
+        // Segment 0 holds absolute symbols which do not refer to an address in the image
+        if parse_hex(segment).is_none_or(|segment| segment == 0) {
+            continue;
+        }
+        let Some(address) = parse_hex(rva_base) else {
+            continue;
+        };
+        let kind = if parts.next() == Some("f") {
+            SymbolKind::Function
+        } else {
+            SymbolKind::Unknown
+        };
+        symbols.push(MapSymbol::new(address, name, kind));
+    }
+    symbols
+}
+
+fn parse_symbol_list(contents: &str) -> Vec<MapSymbol> {
+    let mut symbols = Vec::new();
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with("//") {
+            continue;
+        }
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let symbol = match parts.as_slice() {
+            [address, name] => parse_hex(address).map(|a| (a, *name, SymbolKind::Unknown)),
+            [address, ty, name] if ty.len() == 1 => parse_hex(address).map(|a| {
+                let kind = match ty.chars().next() {
+                    Some('T' | 't' | 'W' | 'w') => SymbolKind::Function,
+                    Some('D' | 'd' | 'B' | 'b' | 'R' | 'r' | 'G' | 'g' | 'S' | 's' | 'V' | 'v') => {
+                        SymbolKind::Data
+                    }
+                    _ => SymbolKind::Unknown,
+                };
+                (a, *name, kind)
+            }),
+            _ => None,
+        };
+        if let Some((address, name, kind)) = symbol {
+            if is_symbol_name(name) {
+                symbols.push(MapSymbol::new(address, name, kind));
+            }
+        }
+    }
+    symbols
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gnu() {
+        let map = "\
+Memory Configuration
+
+Linker script and memory map
+
+.text           0x0000000008000000      0x1a4
+ .text          0x0000000008000000       0x20 startup.o
+                0x0000000008000000                Reset_Handler
+                0x0000000008000010                main
+                0x0000000008000020                . = ALIGN (0x4)
+.data           0x0000000020000000        0x8
+ .data          0x0000000020000000        0x8 main.o
+                0x0000000020000000                counter
+";
+        assert_eq!(detect_format(map), MapFormat::Gnu);
+        assert_eq!(
+            parse(map),
+            vec![
+                MapSymbol::new(0x8000000, "Reset_Handler", SymbolKind::Function),
+                MapSymbol::new(0x8000010, "main", SymbolKind::Function),
+                MapSymbol::new(0x20000000, "counter", SymbolKind::Data),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_msvc() {
+        let map = "\
+  Address         Publics by Value              Rva+Base               Lib:Object
+
+ 0000:00000000       ___safe_se_handler_count   00000000     <absolute>
+ 0001:00000000       _main                      00401000 f   main.obj
+ 0002:00000010       _global_counter            00402010     main.obj
+
+ entry point at        0001:00000000
+";
+        assert_eq!(detect_format(map), MapFormat::Msvc);
+        assert_eq!(
+            parse(map),
+            vec![
+                MapSymbol::new(0x401000, "_main", SymbolKind::Function),
+                MapSymbol::new(0x402010, "_global_counter", SymbolKind::Unknown),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_symbol_list() {
+        let map = "\
+# exported from the vendor SDK
+0x1000 reset
+00002000 T start_kernel
+00003000 d some_table
+not a symbol line
+";
+        assert_eq!(detect_format(map), MapFormat::SymbolList);
+        assert_eq!(
+            parse(map),
+            vec![
+                MapSymbol::new(0x1000, "reset", SymbolKind::Unknown),
+                MapSymbol::new(0x2000, "start_kernel", SymbolKind::Function),
+                MapSymbol::new(0x3000, "some_table", SymbolKind::Data),
+            ]
+        );
+    }
+}
@@ -0,0 +1,313 @@
+//! A small integer expression evaluator for proving branch conditions.
+
+/// The most variable assignments a condition is evaluated for before giving up.
+const MAX_ASSIGNMENTS: usize = 1 << 16;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+    SignExtend,
+    ZeroExtend,
+    LowPart,
+    BoolToInt,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    And,
+    Or,
+    Xor,
+    Lsl,
+    Lsr,
+    Asr,
+    Rol,
+    Ror,
+    Mul,
+    Divu,
+    Divs,
+    Modu,
+    Mods,
+    CmpE,
+    CmpNe,
+    CmpSlt,
+    CmpUlt,
+    CmpSle,
+    CmpUle,
+    CmpSge,
+    CmpUge,
+    CmpSgt,
+    CmpUgt,
+    TestBit,
+}
+
+/// An integer expression, every node has the size in bytes of its result.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expr {
+    Const {
+        value: u64,
+        size: usize,
+    },
+    /// A variable, `id` indexes the domains passed to [`prove`].
+    Var {
+        id: usize,
+        size: usize,
+    },
+    Unary {
+        op: UnaryOp,
+        size: usize,
+        src: Box<Expr>,
+    },
+    Binary {
+        op: BinaryOp,
+        size: usize,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+}
+
+/// The values a variable can take.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Domain {
+    Values(Vec<u64>),
+    /// Any value of the variable's size, only enumerable for single byte variables.
+    Any,
+}
+
+// A size of zero is unknown, as given to comparisons, so is not masked
+fn mask(size: usize) -> u64 {
+    match size {
+        1..=7 => (1 << (size * 8)) - 1,
+        _ => u64::MAX,
+    }
+}
+
+fn sign_extend(value: u64, size: usize) -> i64 {
+    match size {
+        1..=7 => {
+            let shift = 64 - size * 8;
+            ((value << shift) as i64) >> shift
+        }
+        _ => value as i64,
+    }
+}
+
+impl Expr {
+    pub fn size(&self) -> usize {
+        match self {
+            Expr::Const { size, .. }
+            | Expr::Var { size, .. }
+            | Expr::Unary { size, .. }
+            | Expr::Binary { size, .. } => *size,
+        }
+    }
+
+    fn variables(&self, vars: &mut Vec<(usize, usize)>) {
+        match self {
+            Expr::Const { .. } => {}
+            Expr::Var { id, size } => {
+                if !vars.iter().any(|(var, _)| var == id) {
+                    vars.push((*id, *size));
+                }
+            }
+            Expr::Unary { src, .. } => src.variables(vars),
+            Expr::Binary { left, right, .. } => {
+                left.variables(vars);
+                right.variables(vars);
+            }
+        }
+    }
+
+    /// Evaluate with `values[id]` for each variable, `None` for division by zero.
+    pub fn evaluate(&self, values: &[u64]) -> Option<u64> {
+        let size = self.size();
+        let result = match self {
+            Expr::Const { value, .. } => *value,
+            Expr::Var { id, .. } => *values.get(*id)?,
+            Expr::Unary { op, src, .. } => {
+                let value = src.evaluate(values)?;
+                match op {
+                    UnaryOp::Neg => value.wrapping_neg(),
+                    UnaryOp::Not => !value,
+                    UnaryOp::SignExtend => sign_extend(value, src.size()) as u64,
+                    UnaryOp::ZeroExtend | UnaryOp::LowPart | UnaryOp::BoolToInt => value,
+                }
+            }
+            Expr::Binary {
+                op, left, right, ..
+            } => {
+                let operand_size = left.size();
+                let bits = (operand_size * 8).clamp(1, 64) as u32;
+                let (a, b) = (left.evaluate(values)?, right.evaluate(values)?);
+                let (sa, sb) = (sign_extend(a, operand_size), sign_extend(b, right.size()));
+                let shift = (b % bits as u64) as u32;
+                match op {
+                    BinaryOp::Add => a.wrapping_add(b),
+                    BinaryOp::Sub => a.wrapping_sub(b),
+                    BinaryOp::And => a & b,
+                    BinaryOp::Or => a | b,
+                    BinaryOp::Xor => a ^ b,
+                    BinaryOp::Lsl => a.checked_shl(b as u32).unwrap_or(0),
+                    BinaryOp::Lsr => a.checked_shr(b as u32).unwrap_or(0),
+                    BinaryOp::Asr => sa.checked_shr(b as u32).unwrap_or(sa >> 63) as u64,
+                    // Bits rotated past the operand size are masked off below
+                    BinaryOp::Rol => (a << shift) | a.checked_shr(bits - shift).unwrap_or(0),
+                    BinaryOp::Ror => (a >> shift) | a.checked_shl(bits - shift).unwrap_or(0),
+                    BinaryOp::Mul => a.wrapping_mul(b),
+                    BinaryOp::Divu => a.checked_div(b)?,
+                    BinaryOp::Divs => sa.checked_div(sb)? as u64,
+                    BinaryOp::Modu => a.checked_rem(b)?,
+                    BinaryOp::Mods => sa.checked_rem(sb)? as u64,
+                    BinaryOp::CmpE => (a == b) as u64,
+                    BinaryOp::CmpNe => (a != b) as u64,
+                    BinaryOp::CmpSlt => (sa < sb) as u64,
+                    BinaryOp::CmpUlt => (a < b) as u64,
+                    BinaryOp::CmpSle => (sa <= sb) as u64,
+                    BinaryOp::CmpUle => (a <= b) as u64,
+                    BinaryOp::CmpSge => (sa >= sb) as u64,
+                    BinaryOp::CmpUge => (a >= b) as u64,
+                    BinaryOp::CmpSgt => (sa > sb) as u64,
+                    BinaryOp::CmpUgt => (a > b) as u64,
+                    BinaryOp::TestBit => (b < 64 && a & (1 << b) != 0) as u64,
+                }
+            }
+        };
+        Some(result & mask(size))
+    }
+}
+
+/// Prove whether `condition` is always true or always false, given the values each variable can
+/// take. Returns `None` if it depends on the variables or there are too many values to check.
+pub fn prove(condition: &Expr, domains: &[Domain]) -> Option<bool> {
+    let mut vars = Vec::new();
+    condition.variables(&mut vars);
+
+    let mut candidates: Vec<(usize, Vec<u64>)> = Vec::with_capacity(vars.len());
+    let mut assignments: usize = 1;
+    for (id, size) in vars {
+        let values = match domains.get(id)? {
+            Domain::Values(values) if !values.is_empty() => values.clone(),
+            Domain::Any if size == 1 => (0..=0xff).collect(),
+            _ => return None,
+        };
+        assignments = assignments.checked_mul(values.len())?;
+        if assignments > MAX_ASSIGNMENTS {
+            return None;
+        }
+        candidates.push((id, values));
+    }
+
+    let slots = candidates.iter().map(|(id, _)| id + 1).max().unwrap_or(0);
+    let mut values = vec![0u64; slots];
+    let mut outcome = None;
+    for mut assignment in 0..assignments {
+        for (id, domain) in &candidates {
+            values[*id] = domain[assignment % domain.len()];
+            assignment /= domain.len();
+        }
+        let result = condition.evaluate(&values)? != 0;
+        match outcome {
+            None => outcome = Some(result),
+            Some(previous) if previous != result => return None,
+            Some(_) => {}
+        }
+    }
+    outcome
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn var(id: usize, size: usize) -> Box<Expr> {
+        Box::new(Expr::Var { id, size })
+    }
+
+    fn constant(value: u64, size: usize) -> Box<Expr> {
+        Box::new(Expr::Const { value, size })
+    }
+
+    fn binary(op: BinaryOp, size: usize, left: Box<Expr>, right: Box<Expr>) -> Box<Expr> {
+        Box::new(Expr::Binary {
+            op,
+            size,
+            left,
+            right,
+        })
+    }
+
+    #[test]
+    fn constant_condition() {
+        let condition = binary(BinaryOp::CmpE, 1, constant(7, 4), constant(7, 4));
+        assert_eq!(prove(&condition, &[]), Some(true));
+        let condition = binary(BinaryOp::CmpSlt, 1, constant(1, 4), constant(0xffffffff, 4));
+        assert_eq!(prove(&condition, &[]), Some(false));
+    }
+
+    #[test]
+    fn opaque_byte_predicate() {
+        // x * (x + 1) is always even
+        let product = binary(
+            BinaryOp::Mul,
+            1,
+            var(0, 1),
+            binary(BinaryOp::Add, 1, var(0, 1), constant(1, 1)),
+        );
+        let condition = binary(
+            BinaryOp::CmpE,
+            1,
+            binary(BinaryOp::And, 1, product, constant(1, 1)),
+            constant(0, 1),
+        );
+        assert_eq!(prove(&condition, &[Domain::Any]), Some(true));
+    }
+
+    #[test]
+    fn dependent_condition() {
+        let condition = binary(BinaryOp::CmpUgt, 1, var(0, 4), constant(10, 4));
+        assert_eq!(prove(&condition, &[Domain::Values(vec![1, 20])]), None);
+        assert_eq!(
+            prove(&condition, &[Domain::Values(vec![11, 20])]),
+            Some(true)
+        );
+        // Unconstrained wide variables can not be enumerated
+        assert_eq!(prove(&condition, &[Domain::Any]), None);
+    }
+
+    #[test]
+    fn division_by_zero() {
+        let condition = binary(
+            BinaryOp::CmpE,
+            1,
+            binary(BinaryOp::Divu, 4, constant(1, 4), var(0, 4)),
+            constant(0, 4),
+        );
+        assert_eq!(prove(&condition, &[Domain::Values(vec![0, 2])]), None);
+    }
+
+    #[test]
+    fn sized_arithmetic() {
+        let sum = Expr::Binary {
+            op: BinaryOp::Add,
+            size: 1,
+            left: constant(0xff, 1),
+            right: constant(2, 1),
+        };
+        assert_eq!(sum.evaluate(&[]), Some(1));
+        let extended = Expr::Unary {
+            op: UnaryOp::SignExtend,
+            size: 4,
+            src: constant(0x80, 1),
+        };
+        assert_eq!(extended.evaluate(&[]), Some(0xffffff80));
+        let rotated = Expr::Binary {
+            op: BinaryOp::Rol,
+            size: 1,
+            left: constant(0x81, 1),
+            right: constant(1, 1),
+        };
+        assert_eq!(rotated.evaluate(&[]), Some(0x03));
+    }
+}
@@ -0,0 +1,254 @@
+mod eval;
+mod lift;
+
+use std::collections::{BTreeMap, HashMap};
+
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::function::{Function, FunctionUpdateType};
+use binaryninja::logger::Logger;
+use binaryninja::low_level_il::expression::LowLevelExpressionIndex;
+use binaryninja::low_level_il::function::LiftedNonSSA;
+use binaryninja::low_level_il::instruction::{
+    InstructionHandler, LowLevelILInstructionKind, LowLevelInstructionIndex,
+};
+use binaryninja::low_level_il::lifting::LowLevelILLabel;
+use binaryninja::medium_level_il::{
+    MediumLevelILInstructionKind, MediumLevelILLiftedInstruction,
+    MediumLevelILLiftedInstructionKind, MediumLevelInstructionIndex,
+};
+use binaryninja::tags::TagReferenceType;
+use binaryninja::workflow::{Activity, AnalysisContext, Workflow};
+
+use log::{debug, LevelFilter};
+
+const WORKFLOW_NAME: &str = "plugins.function.opaquePredicates";
+const WORKFLOW_CONFIG: &str = r#"{
+    "title": "Opaque Predicate Repair",
+    "description": "Function analysis that removes branches proven to always or never be taken, for obfuscated code. Only the analysis is changed, not the file.",
+    "targetType": "function"
+}"#;
+
+const DETECT_ACTIVITY_NAME: &str = "analysis.plugins.opaquePredicates.detect";
+const DETECT_ACTIVITY_CONFIG: &str = r#"{
+    "name": "analysis.plugins.opaquePredicates.detect",
+    "title": "Detect Opaque Predicates",
+    "description": "This analysis step proves branch conditions constant using dataflow and by evaluating them for every value their variables can take.",
+    "eligibility": {
+        "auto": { "default": true }
+    }
+}"#;
+
+const APPLY_ACTIVITY_NAME: &str = "analysis.plugins.opaquePredicates.apply";
+const APPLY_ACTIVITY_CONFIG: &str = r#"{
+    "name": "analysis.plugins.opaquePredicates.apply",
+    "title": "Remove Opaque Predicates",
+    "description": "This analysis step replaces branches with a proven outcome by a jump to the block that is always taken.",
+    "eligibility": {
+        "auto": { "default": true }
+    }
+}"#;
+
+const TAG_TYPE_NAME: &str = "Opaque Predicate";
+const TAG_TYPE_ICON: &str = "🔀";
+
+const ALWAYS_TAKEN: &str = "Branch is always taken";
+const NEVER_TAKEN: &str = "Branch is never taken";
+
+// Conditions are proven on MLIL, which is only available after the LLIL the branches are removed
+// from was generated, so the outcomes are kept for the next analysis of the function as auto tags
+// on the branches. The tags also hold the bytes of the branch, so outcomes of a branch that was
+// patched since are dropped.
+
+fn branch_bytes(function: &Function, address: u64) -> Option<Vec<u8>> {
+    let view = function.view();
+    let len = view.instruction_len(&function.arch(), address)?;
+    Some(view.read_vec(address, len))
+}
+
+fn tag_data(taken: bool, bytes: &[u8]) -> String {
+    let description = match taken {
+        true => ALWAYS_TAKEN,
+        false => NEVER_TAKEN,
+    };
+    let bytes: Vec<String> = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!("{} [{}]", description, bytes.join(" "))
+}
+
+fn parse_tag_data(data: &str) -> Option<(bool, Vec<u8>)> {
+    let (description, bytes) = data.strip_suffix(']')?.split_once(" [")?;
+    let taken = match description {
+        ALWAYS_TAKEN => true,
+        NEVER_TAKEN => false,
+        _ => return None,
+    };
+    let bytes = bytes
+        .split(' ')
+        .map(|byte| u8::from_str_radix(byte, 16).ok())
+        .collect::<Option<_>>()?;
+    Some((taken, bytes))
+}
+
+/// The proven outcomes of the branches of `function` by address, removing the tags of those whose
+/// bytes changed.
+fn outcomes(function: &Function) -> HashMap<u64, bool> {
+    let mut outcomes = HashMap::new();
+    let Some(tag_type) = function.view().tag_type_by_name(TAG_TYPE_NAME) else {
+        return outcomes;
+    };
+    for tag_ref in &function.all_tags_of_type(&tag_type) {
+        if !tag_ref.auto_defined || tag_ref.reference_type != TagReferenceType::AddressTagReference
+        {
+            continue;
+        }
+        match parse_tag_data(tag_ref.tag.data().as_str()) {
+            Some((taken, bytes))
+                if branch_bytes(function, tag_ref.addr).as_deref() == Some(&bytes[..]) =>
+            {
+                outcomes.insert(tag_ref.addr, taken);
+            }
+            _ => function.remove_tag(&tag_ref.tag, Some(tag_ref.addr), false, tag_ref.arch),
+        }
+    }
+    outcomes
+}
+
+fn prove_branch(condition: &MediumLevelILLiftedInstruction) -> Option<bool> {
+    lift::dataflow_outcome(condition).or_else(|| {
+        let condition = lift::lift_condition(condition)?;
+        eval::prove(&condition.expr, &condition.domains)
+    })
+}
+
+fn detect_activity(ctx: &AnalysisContext) {
+    let Some(mlil) = ctx.mlil_function() else {
+        return;
+    };
+    let function = ctx.function();
+
+    let mut found: BTreeMap<u64, Option<bool>> = BTreeMap::new();
+    for index in 0..mlil.instruction_count() {
+        let Some(instr) = mlil.instruction_from_index(MediumLevelInstructionIndex(index)) else {
+            continue;
+        };
+        if !matches!(instr.kind, MediumLevelILInstructionKind::If(_)) {
+            continue;
+        }
+        let MediumLevelILLiftedInstructionKind::If(op) = instr.lift().kind else {
+            continue;
+        };
+        let outcome = prove_branch(&op.condition);
+        // Branches are matched to LLIL by address, several at one address can't be told apart
+        found
+            .entry(instr.address)
+            .and_modify(|existing| *existing = None)
+            .or_insert(outcome);
+    }
+
+    let known = outcomes(&function);
+    let new_outcomes: Vec<(u64, bool)> = found
+        .into_iter()
+        .filter_map(|(address, outcome)| Some((address, outcome?)))
+        .filter(|(address, _)| !known.contains_key(address))
+        .collect();
+    if new_outcomes.is_empty() {
+        return;
+    }
+
+    let view = function.view();
+    let tag_type = view
+        .tag_type_by_name(TAG_TYPE_NAME)
+        .unwrap_or_else(|| view.create_tag_type(TAG_TYPE_NAME, TAG_TYPE_ICON));
+    for (address, taken) in &new_outcomes {
+        let Some(bytes) = branch_bytes(&function, *address) else {
+            continue;
+        };
+        function.add_tag(
+            &tag_type,
+            tag_data(*taken, &bytes),
+            Some(*address),
+            false,
+            None,
+        );
+    }
+    debug!(
+        "Proved {} opaque predicates in function {:#x}",
+        new_outcomes.len(),
+        function.start()
+    );
+    // The branches are removed from LLIL when the function is analyzed again
+    function.reanalyze(FunctionUpdateType::FullAutoFunctionUpdate);
+}
+
+fn apply_activity(ctx: &AnalysisContext) {
+    let function = ctx.function();
+    let outcomes = outcomes(&function);
+    if outcomes.is_empty() {
+        return;
+    }
+    let Some(llil) = (unsafe { ctx.llil_function::<LiftedNonSSA>() }) else {
+        return;
+    };
+
+    let mut removed = 0;
+    for index in 0..llil.instruction_count() {
+        let Some(instr) = llil.instruction_from_index(LowLevelInstructionIndex(index)) else {
+            continue;
+        };
+        let Some(taken) = outcomes.get(&instr.address()) else {
+            continue;
+        };
+        let LowLevelILInstructionKind::If(op) = instr.kind() else {
+            continue;
+        };
+        let target = match taken {
+            true => op.true_target(),
+            false => op.false_target(),
+        };
+        // A resolved label refers to the instruction index in its operand
+        let mut label = LowLevelILLabel {
+            location: None,
+            resolved: true,
+            expr_ref: LowLevelExpressionIndex(0),
+            operand: target.index.0,
+        };
+        let goto = llil.goto(&mut label);
+        if unsafe { llil.replace_expression(instr.expr_idx(), goto) } {
+            removed += 1;
+        }
+    }
+
+    if removed > 0 {
+        debug!(
+            "Removed {} opaque predicates from function {:#x}",
+            removed,
+            function.start()
+        );
+        llil.generate_ssa_form();
+    }
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "C" fn CorePluginInit() -> bool {
+    Logger::new("Opaque Predicates")
+        .with_level(LevelFilter::Info)
+        .init();
+
+    let workflow = Workflow::instance("core.function.metaAnalysis").clone(WORKFLOW_NAME);
+    let detect_activity = Activity::new_with_action(DETECT_ACTIVITY_CONFIG, detect_activity);
+    let apply_activity = Activity::new_with_action(APPLY_ACTIVITY_CONFIG, apply_activity);
+    if workflow.register_activity(&detect_activity).is_err()
+        || workflow.register_activity(&apply_activity).is_err()
+    {
+        log::error!("Failed to register the opaque predicate activities");
+        return false;
+    }
+    // Removing branches before tail call translation lets the rest of analysis see the new flow
+    workflow.insert("core.function.translateTailCalls", [APPLY_ACTIVITY_NAME]);
+    workflow.insert("core.function.generateHighLevelIL", [DETECT_ACTIVITY_NAME]);
+    if workflow.register_with_config(WORKFLOW_CONFIG).is_err() {
+        log::error!("Failed to register the `{}` workflow", WORKFLOW_NAME);
+        return false;
+    }
+    true
+}
@@ -0,0 +1,176 @@
+use binaryninja::medium_level_il::operation::{LiftedBinaryOp, LiftedUnaryOp};
+use binaryninja::medium_level_il::{
+    MediumLevelILLiftedInstruction, MediumLevelILLiftedInstructionKind as Kind,
+};
+use binaryninja::variable::{PossibleValueSet, Variable};
+
+use crate::eval::{BinaryOp, Domain, Expr, UnaryOp};
+
+/// The most values taken from a value set or range before treating the variable as unconstrained.
+const MAX_DOMAIN_VALUES: u64 = 0x100;
+
+/// A branch condition in the form [`crate::eval::prove`] takes.
+pub struct Condition {
+    pub expr: Expr,
+    pub domains: Vec<Domain>,
+}
+
+/// Lift an MLIL condition, with each variable constrained to the values dataflow found for it.
+/// Returns `None` if the condition uses anything other than integer arithmetic on variables.
+pub fn lift_condition(condition: &MediumLevelILLiftedInstruction) -> Option<Condition> {
+    let mut lifter = Lifter::default();
+    let expr = lifter.lift(condition)?;
+    Some(Condition {
+        expr,
+        domains: lifter.domains,
+    })
+}
+
+/// The outcome dataflow already determined for the condition, if any.
+pub fn dataflow_outcome(condition: &MediumLevelILLiftedInstruction) -> Option<bool> {
+    match possible_values(condition)? {
+        PossibleValueSet::ConstantValue { value } => Some(value != 0),
+        PossibleValueSet::InSetOfValues { values } if !values.is_empty() => {
+            let taken = values.iter().all(|value| *value != 0);
+            let not_taken = values.iter().all(|value| *value == 0);
+            (taken || not_taken).then_some(taken)
+        }
+        _ => None,
+    }
+}
+
+fn possible_values(instr: &MediumLevelILLiftedInstruction) -> Option<PossibleValueSet> {
+    let instr = instr.function.instruction_from_expr_index(instr.index)?;
+    Some(instr.possible_values())
+}
+
+fn range_values(start: u64, end: u64, step: u64, values: &mut Vec<u64>) -> Option<()> {
+    let step = step.max(1);
+    let count = end.checked_sub(start)? / step + 1;
+    if values.len() as u64 + count > MAX_DOMAIN_VALUES {
+        return None;
+    }
+    values.extend((0..count).map(|i| start + i * step));
+    Some(())
+}
+
+fn domain(instr: &MediumLevelILLiftedInstruction) -> Domain {
+    let values = match possible_values(instr) {
+        Some(PossibleValueSet::ConstantValue { value })
+        | Some(PossibleValueSet::ConstantPointerValue { value }) => Some(vec![value as u64]),
+        Some(PossibleValueSet::InSetOfValues { values })
+            if values.len() as u64 <= MAX_DOMAIN_VALUES =>
+        {
+            Some(values.into_iter().map(|value| value as u64).collect())
+        }
+        Some(PossibleValueSet::UnsignedRangeValue { ranges, .. }) => {
+            let mut values = Vec::new();
+            ranges
+                .iter()
+                .try_for_each(|range| range_values(range.start, range.end, range.step, &mut values))
+                .map(|_| values)
+        }
+        Some(PossibleValueSet::SignedRangeValue { ranges, .. }) => {
+            let mut values = Vec::new();
+            ranges
+                .iter()
+                .try_for_each(|range| {
+                    // Offset by the start so the range can be walked unsigned
+                    let len = range.end.checked_sub(range.start)? as u64;
+                    let before = values.len();
+                    range_values(0, len, range.step, &mut values)?;
+                    for value in &mut values[before..] {
+                        *value = (range.start as u64).wrapping_add(*value);
+                    }
+                    Some(())
+                })
+                .map(|_| values)
+        }
+        _ => None,
+    };
+    values.map(Domain::Values).unwrap_or(Domain::Any)
+}
+
+#[derive(Default)]
+struct Lifter {
+    vars: Vec<Variable>,
+    domains: Vec<Domain>,
+}
+
+impl Lifter {
+    fn lift(&mut self, instr: &MediumLevelILLiftedInstruction) -> Option<Expr> {
+        let size = instr.size;
+        match &instr.kind {
+            Kind::Const(op) | Kind::ConstPtr(op) => Some(Expr::Const {
+                value: op.constant,
+                size,
+            }),
+            Kind::Var(op) => Some(self.var(instr, op.src)),
+            Kind::Neg(op) => self.unary(UnaryOp::Neg, size, op),
+            Kind::Not(op) => self.unary(UnaryOp::Not, size, op),
+            Kind::Sx(op) => self.unary(UnaryOp::SignExtend, size, op),
+            Kind::Zx(op) => self.unary(UnaryOp::ZeroExtend, size, op),
+            Kind::LowPart(op) => self.unary(UnaryOp::LowPart, size, op),
+            Kind::BoolToInt(op) => self.unary(UnaryOp::BoolToInt, size, op),
+            Kind::Add(op) => self.binary(BinaryOp::Add, size, op),
+            Kind::Sub(op) => self.binary(BinaryOp::Sub, size, op),
+            Kind::And(op) => self.binary(BinaryOp::And, size, op),
+            Kind::Or(op) => self.binary(BinaryOp::Or, size, op),
+            Kind::Xor(op) => self.binary(BinaryOp::Xor, size, op),
+            Kind::Lsl(op) => self.binary(BinaryOp::Lsl, size, op),
+            Kind::Lsr(op) => self.binary(BinaryOp::Lsr, size, op),
+            Kind::Asr(op) => self.binary(BinaryOp::Asr, size, op),
+            Kind::Rol(op) => self.binary(BinaryOp::Rol, size, op),
+            Kind::Ror(op) => self.binary(BinaryOp::Ror, size, op),
+            Kind::Mul(op) => self.binary(BinaryOp::Mul, size, op),
+            Kind::Divu(op) => self.binary(BinaryOp::Divu, size, op),
+            Kind::Divs(op) => self.binary(BinaryOp::Divs, size, op),
+            Kind::Modu(op) => self.binary(BinaryOp::Modu, size, op),
+            Kind::Mods(op) => self.binary(BinaryOp::Mods, size, op),
+            Kind::CmpE(op) => self.binary(BinaryOp::CmpE, size, op),
+            Kind::CmpNe(op) => self.binary(BinaryOp::CmpNe, size, op),
+            Kind::CmpSlt(op) => self.binary(BinaryOp::CmpSlt, size, op),
+            Kind::CmpUlt(op) => self.binary(BinaryOp::CmpUlt, size, op),
+            Kind::CmpSle(op) => self.binary(BinaryOp::CmpSle, size, op),
+            Kind::CmpUle(op) => self.binary(BinaryOp::CmpUle, size, op),
+            Kind::CmpSge(op) => self.binary(BinaryOp::CmpSge, size, op),
+            Kind::CmpUge(op) => self.binary(BinaryOp::CmpUge, size, op),
+            Kind::CmpSgt(op) => self.binary(BinaryOp::CmpSgt, size, op),
+            Kind::CmpUgt(op) => self.binary(BinaryOp::CmpUgt, size, op),
+            Kind::TestBit(op) => self.binary(BinaryOp::TestBit, size, op),
+            _ => None,
+        }
+    }
+
+    fn var(&mut self, instr: &MediumLevelILLiftedInstruction, var: Variable) -> Expr {
+        let id = match self.vars.iter().position(|existing| *existing == var) {
+            Some(id) => id,
+            None => {
+                self.vars.push(var);
+                self.domains.push(domain(instr));
+                self.vars.len() - 1
+            }
+        };
+        Expr::Var {
+            id,
+            size: instr.size,
+        }
+    }
+
+    fn unary(&mut self, op: UnaryOp, size: usize, operand: &LiftedUnaryOp) -> Option<Expr> {
+        Some(Expr::Unary {
+            op,
+            size,
+            src: Box::new(self.lift(&operand.src)?),
+        })
+    }
+
+    fn binary(&mut self, op: BinaryOp, size: usize, operands: &LiftedBinaryOp) -> Option<Expr> {
+        Some(Expr::Binary {
+            op,
+            size,
+            left: Box::new(self.lift(&operands.left)?),
+            right: Box::new(self.lift(&operands.right)?),
+        })
+    }
+}
@@ -13,12 +13,9 @@
 // limitations under the License.
 #![allow(dead_code)]
 
-use std::collections::HashMap;
 use std::env::{current_dir, current_exe, temp_dir};
 use std::io::Cursor;
-use std::path::PathBuf;
-use std::str::FromStr;
-use std::sync::mpsc;
+use std::path::{Path, PathBuf};
 use std::{env, fs};
 
 use anyhow::{anyhow, Result};
@@ -27,11 +24,12 @@ use pdb::PDB;
 
 use binaryninja::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
 use binaryninja::debuginfo::{CustomDebugInfoParser, DebugInfo, DebugInfoParser};
-use binaryninja::download_provider::{DownloadInstanceInputOutputCallbacks, DownloadProvider};
 use binaryninja::interaction::{MessageBoxButtonResult, MessageBoxButtonSet};
 use binaryninja::logger::Logger;
+use binaryninja::progress::ProgressScope;
 use binaryninja::settings::{QueryOptions, Settings};
-use binaryninja::string::BnString;
+use binaryninja::symbol_server::{parse_symbol_path, SymbolFileId, SymbolServerError, SymbolStore};
+use binaryninja::workflow::{Activity, AnalysisContext, Workflow};
 use binaryninja::{interaction, user_directory};
 use parser::PDBParserInstance;
 
@@ -120,161 +118,43 @@ fn active_local_cache(view: Option<&BinaryView>) -> Result<String> {
 }
 
 fn parse_sym_srv(symbol_path: &str, default_store: String) -> Result<impl Iterator<Item = String>> {
-    // https://docs.microsoft.com/en-us/windows/win32/debug/using-symsrv
-    // Why
-
-    // ... the symbol path (_NT_SYMBOL_PATH environment variable) can be made up of several path
-    // elements separated by semicolons. If any one or more of these path elements begins with
-    // the text "srv*", then the element is a symbol server and will use SymSrv to locate
-    // symbol files.
-
-    // If the "srv*" text is not specified but the actual path element is a symbol server store,
-    // then the symbol handler will act as if "srv*" were specified. The symbol handler makes
-    // this determination by searching for the existence of a file called "pingme.txt" in
-    // the root directory of the specified path.
-
-    // ... symbol servers are made up of symbol store elements separated by asterisks. There can
-    // be up to 10 symbol stores after the "srv*" prefix.
-
-    let mut sym_srv_results: Vec<String> = vec![];
-
-    // 'path elements separated by semicolons'
-    for path_element in symbol_path.split(';') {
-        // 'begins with the text "srv*"'
-        if path_element.to_lowercase().starts_with("srv*") {
-            // 'symbol store elements separated by asterisks'
-            for store_element in path_element[4..].split('*') {
-                if store_element.is_empty() {
-                    sym_srv_results.push(default_store.clone());
-                } else {
-                    sym_srv_results.push(store_element.to_string());
-                }
-            }
-        } else if PathBuf::from(path_element).exists() {
-            // 'searching for the existence of a file called "pingme.txt" in the root directory'
-            let pingme_txt = path_element.to_string() + "/" + "pingme.txt";
-            if PathBuf::from(pingme_txt).exists() {
-                sym_srv_results.push(path_element.to_string());
-            }
-        }
-    }
-
-    Ok(sym_srv_results.into_iter())
-}
-
-fn read_from_sym_store(bv: &BinaryView, path: &str) -> Result<(bool, Vec<u8>)> {
-    if !path.contains("://") {
-        // Local file
-        info!("Read local file: {}", path);
-        let conts = fs::read(path)?;
-        return Ok((false, conts));
-    }
-
-    let mut query_options = QueryOptions::new_with_view(bv);
-    if !Settings::new().get_bool_with_opts("network.pdbAutoDownload", &mut query_options) {
-        return Err(anyhow!("Auto download disabled"));
-    }
-
-    // Download from remote
-    let (tx, rx) = mpsc::channel();
-    let write = move |data: &[u8]| -> usize {
-        if tx.send(Vec::from(data)).is_ok() {
-            data.len()
-        } else {
-            0
-        }
-    };
-
-    info!("GET: {}", path);
-
-    let dp =
-        DownloadProvider::try_default().map_err(|_| anyhow!("No default download provider"))?;
-    let mut inst = dp
-        .create_instance()
-        .map_err(|_| anyhow!("Couldn't create download instance"))?;
-    let result = inst
-        .perform_custom_request(
-            "GET",
-            path,
-            HashMap::<BnString, BnString>::new(),
-            DownloadInstanceInputOutputCallbacks {
-                read: None,
-                write: Some(Box::new(write)),
-                progress: None,
-            },
-        )
-        .map_err(|e| anyhow!(e.to_string()))?;
-    if result.status_code != 200 {
-        return Err(anyhow!("Path does not exist"));
-    }
-
-    let mut expected_length = None;
-    for (k, v) in result.headers.iter() {
-        if k.to_lowercase() == "content-length" {
-            expected_length = Some(usize::from_str(v)?);
-        }
-    }
-
-    let mut data = vec![];
-    while let Ok(packet) = rx.try_recv() {
-        data.extend(packet.into_iter());
-    }
-
-    if let Some(length) = expected_length {
-        if data.len() != length {
-            return Err(anyhow!(format!(
-                "Bad length: expected {} got {}",
-                length,
-                data.len()
-            )));
-        }
-    }
-
-    Ok((true, data))
+    // The stores of every chain in the path, in the order they are searched
+    let stores = parse_symbol_path(symbol_path, Path::new(&default_store));
+    Ok(stores
+        .into_iter()
+        .flatten()
+        .map(|store| store.to_string())
+        .collect::<Vec<_>>()
+        .into_iter())
 }
 
 fn search_sym_store(
     bv: &BinaryView,
     store_path: String,
     pdb_info: &PDBInfo,
+    progress: &ProgressScope,
 ) -> Result<Option<Vec<u8>>> {
-    // https://www.technlg.net/windows/symbol-server-path-windbg-debugging/
-    // For symbol servers, to identify the files path easily, Windbg uses the format
-    // binaryname.pdb/GUID
-
-    // Doesn't actually say what the format is, just gives an example:
-    // https://docs.microsoft.com/en-us/windows/win32/debug/using-symstore
-    // In this example, the lookup path for the acpi.dbg symbol file might look something
-    // like this: \\mybuilds\symsrv\acpi.dbg\37cdb03962040.
-    let base_path = store_path + "/" + &pdb_info.file_name + "/" + &pdb_info.guid_age_string;
-
-    // Three files may exist inside the lookup directory:
-    // 1. If the file was stored, then acpi.dbg will exist there.
-    // 2. If a pointer was stored, then a file called file.ptr will exist and contain the path
-    // to the actual symbol file.
-    // 3. A file called refs.ptr, which contains a list of all the current locations for
-    // acpi.dbg with this timestamp and image size that are currently added to the
-    // symbol store.
-
-    // We don't care about #3 because it says we don't
-
-    let direct_path = base_path.clone() + "/" + &pdb_info.file_name;
-    if let Ok((_remote, conts)) = read_from_sym_store(bv, &direct_path) {
-        return Ok(Some(conts));
+    let store = SymbolStore::parse(&store_path);
+    let mut query_options = QueryOptions::new_with_view(bv);
+    if store.is_remote()
+        && !Settings::new().get_bool_with_opts("network.pdbAutoDownload", &mut query_options)
+    {
+        return Ok(None);
     }
 
-    let file_ptr = base_path.clone() + "/" + "file.ptr";
-    if let Ok((_remote, conts)) = read_from_sym_store(bv, &file_ptr) {
-        let path = String::from_utf8(conts)?;
-        // PATH:https://full/path
-        if let Some(stripped) = path.strip_prefix("PATH:") {
-            if let Ok((_remote, conts)) = read_from_sym_store(bv, stripped) {
-                return Ok(Some(conts));
-            }
-        }
-    }
+    // The store may hold the PDB itself, compressed in a cabinet, or a file.ptr with its path
+    let id = SymbolFileId::new(&pdb_info.file_name, &pdb_info.guid_age_string)?;
+    info!("Searching {} for {}", store, id);
+    let progress = progress.clone();
+    Ok(store.fetch(&id, move |_, _| !progress.is_cancelled())?)
+}
 
-    Ok(None)
+/// Whether the import failed because it was cancelled, by a download or the parse.
+fn is_cancelled(e: &anyhow::Error) -> bool {
+    matches!(
+        e.downcast_ref::<SymbolServerError>(),
+        Some(SymbolServerError::Cancelled)
+    )
 }
 
 fn parse_pdb_info(view: &BinaryView) -> Option<PDBInfo> {
@@ -356,7 +236,7 @@ impl PDBParser {
         conts: &Vec<u8>,
         debug_info: &mut DebugInfo,
         view: &BinaryView,
-        progress: &dyn Fn(usize, usize) -> Result<(), ()>,
+        progress: &ProgressScope,
         check_guid: bool,
         did_download: bool,
     ) -> Result<()> {
@@ -406,7 +286,10 @@ impl PDBParser {
                 }
             }
 
-            if did_download && settings.get_bool("pdb.files.localStoreCache") {
+            if did_download
+                && settings
+                    .get_bool_with_opts("pdb.files.localStoreCache", &mut settings_query_opts)
+            {
                 match active_local_cache(Some(view)) {
                     Ok(cache) => {
                         let mut cab_path = PathBuf::from(&cache);
@@ -519,9 +402,7 @@ impl PDBParser {
                 return Err(e);
             }
         };
-        match inst.try_parse_info(Box::new(|cur, max| {
-            (*progress)(cur, max).map_err(|_| anyhow!("Cancelled"))
-        })) {
+        match inst.try_parse_info(progress) {
             Ok(()) => {
                 info!("Parsed pdb");
                 Ok(())
@@ -548,7 +429,7 @@ impl CustomDebugInfoParser for PDBParser {
         debug_info: &mut DebugInfo,
         view: &BinaryView,
         debug_file: &BinaryView,
-        progress: Box<dyn Fn(usize, usize) -> Result<(), ()>>,
+        progress: ProgressScope,
     ) -> bool {
         if is_pdb(debug_file) {
             match self.load_from_file(
@@ -560,7 +441,7 @@ impl CustomDebugInfoParser for PDBParser {
                 false,
             ) {
                 Ok(_) => return true,
-                Err(e) if e.to_string() == "Cancelled" => return false,
+                Err(e) if is_cancelled(&e) => return false,
                 Err(_) => {
                     error!("Chosen PDB file failed to load");
                     return false;
@@ -579,17 +460,18 @@ impl CustomDebugInfoParser for PDBParser {
                 };
                 if let Ok(stores) = stores {
                     for store in stores {
-                        match search_sym_store(view, store.clone(), &info) {
+                        match search_sym_store(view, store.clone(), &info, &progress) {
                             Ok(Some(conts)) => {
                                 match self
                                     .load_from_file(&conts, debug_info, view, &progress, true, true)
                                 {
                                     Ok(_) => return true,
-                                    Err(e) if e.to_string() == "Cancelled" => return false,
+                                    Err(e) if is_cancelled(&e) => return false,
                                     Err(e) => debug!("Skipping, {}", e.to_string()),
                                 }
                             }
                             Ok(None) => {}
+                            Err(e) if is_cancelled(&e) => return false,
                             e => error!("Error searching symbol store {}: {:?}", store, e),
                         }
                     }
@@ -603,10 +485,9 @@ impl CustomDebugInfoParser for PDBParser {
                         .load_from_file(&conts, debug_info, view, &progress, true, false)
                     {
                         Ok(_) => return true,
-                        Err(e) if e.to_string() == "Cancelled" => return false,
+                        Err(e) if is_cancelled(&e) => return false,
                         Err(e) => debug!("Skipping, {}", e.to_string()),
                     },
-                    Err(e) if e.to_string() == "Cancelled" => return false,
                     Err(e) => debug!("Could not read pdb: {}", e.to_string()),
                 }
             }
@@ -625,26 +506,26 @@ impl CustomDebugInfoParser for PDBParser {
                         .load_from_file(&conts, debug_info, view, &progress, true, false)
                     {
                         Ok(_) => return true,
-                        Err(e) if e.to_string() == "Cancelled" => return false,
+                        Err(e) if is_cancelled(&e) => return false,
                         Err(e) => debug!("Skipping, {}", e.to_string()),
                     },
-                    Err(e) if e.to_string() == "Cancelled" => return false,
                     Err(e) => debug!("Could not read pdb: {}", e.to_string()),
                 }
             }
 
             // Check the local symbol store
             if let Ok(local_store_path) = active_local_cache(Some(view)) {
-                match search_sym_store(view, local_store_path.clone(), &info) {
+                match search_sym_store(view, local_store_path.clone(), &info, &progress) {
                     Ok(Some(conts)) => {
                         match self.load_from_file(&conts, debug_info, view, &progress, true, false)
                         {
                             Ok(_) => return true,
-                            Err(e) if e.to_string() == "Cancelled" => return false,
+                            Err(e) if is_cancelled(&e) => return false,
                             Err(e) => debug!("Skipping, {}", e.to_string()),
                         }
                     }
                     Ok(None) => {}
+                    Err(e) if is_cancelled(&e) => return false,
                     e => error!(
                         "Error searching local symbol store {}: {:?}",
                         local_store_path, e
@@ -658,15 +539,16 @@ impl CustomDebugInfoParser for PDBParser {
                 .get_string_list_with_opts("pdb.files.symbolServerList", &mut query_options);
 
             for server in server_list.iter() {
-                match search_sym_store(view, server.to_string(), &info) {
+                match search_sym_store(view, server.to_string(), &info, &progress) {
                     Ok(Some(conts)) => {
                         match self.load_from_file(&conts, debug_info, view, &progress, true, true) {
                             Ok(_) => return true,
-                            Err(e) if e.to_string() == "Cancelled" => return false,
+                            Err(e) if is_cancelled(&e) => return false,
                             Err(e) => debug!("Skipping, {}", e.to_string()),
                         }
                     }
                     Ok(None) => {}
+                    Err(e) if is_cancelled(&e) => return false,
                     e => error!("Error searching remote symbol server {}: {:?}", server, e),
                 }
             }
@@ -863,6 +745,54 @@ fn init_plugin() -> bool {
         }"#,
     );
 
+    // Also registered by the DWARF import, whichever plugin loads first registers it
+    settings.register_setting_json(
+        "analysis.debugInfo.componentsFromSourcePaths",
+        r#"{
+            "title" : "Create Components from Source Paths",
+            "type" : "boolean",
+            "default" : false,
+            "description" : "Place functions into components following the directories of the source files they were compiled from.",
+            "ignore" : []
+        }"#,
+    );
+
+    register_components_activity()
+}
+
+const COMPONENTS_ACTIVITY_NAME: &str = "analysis.plugins.pdbImport.applyComponents";
+const COMPONENTS_ACTIVITY_CONFIG: &str = r#"{
+    "name": "analysis.plugins.pdbImport.applyComponents",
+    "title": "Place PDB Functions into Components",
+    "description": "This analysis step places the functions of the PDB import into the components of the object files they were compiled into.",
+    "eligibility": {
+        "auto": {},
+        "runOnce": false
+    }
+}"#;
+
+/// Place the imported functions into their components once they exist in the view.
+fn register_components_activity() -> bool {
+    let workflow = Workflow::instance("core.module.metaAnalysis").clone("core.module.metaAnalysis");
+    let activity =
+        Activity::new_with_action(COMPONENTS_ACTIVITY_CONFIG, |ctx: &AnalysisContext| {
+            let view = ctx.view();
+            if Settings::new().get_bool_with_opts(
+                "analysis.debugInfo.componentsFromSourcePaths",
+                &mut QueryOptions::new_with_view(&view),
+            ) {
+                view.debug_info().apply_components(&view, "PDB");
+            }
+        });
+    if workflow.register_activity(&activity).is_err() {
+        error!("Failed to register the PDB component activity");
+        return false;
+    }
+    workflow.insert("core.module.notifyCompletion", [COMPONENTS_ACTIVITY_NAME]);
+    if workflow.register().is_err() {
+        error!("Failed to register the PDB component activity");
+        return false;
+    }
     true
 }
 
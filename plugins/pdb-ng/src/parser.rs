@@ -27,10 +27,12 @@ use binaryninja::architecture::{Architecture, CoreArchitecture};
 use binaryninja::binary_view::{BinaryView, BinaryViewExt};
 use binaryninja::calling_convention::CoreCallingConvention;
 use binaryninja::confidence::{Conf, MIN_CONFIDENCE};
-use binaryninja::debuginfo::{DebugFunctionInfo, DebugInfo};
+use binaryninja::debuginfo::{source_path_components, DebugFunctionInfo, DebugInfo};
 use binaryninja::platform::Platform;
+use binaryninja::progress::ProgressScope;
 use binaryninja::rc::Ref;
 use binaryninja::settings::{QueryOptions, Settings};
+use binaryninja::symbol_server::SymbolServerError;
 use binaryninja::types::{
     EnumerationBuilder, NamedTypeReference, NamedTypeReferenceClass, QualifiedName,
     StructureBuilder, StructureType, Type, TypeClass,
@@ -99,6 +101,8 @@ pub struct PDBParserInstance<'a, S: Source<'a> + 'a> {
     pub(crate) addressed_symbols: BTreeMap<u64, Vec<ParsedSymbol>>,
     /// CPU type of the currently parsing module
     pub(crate) module_cpu_type: Option<CPUType>,
+    /// Procedure address -> Object file of the module defining it
+    pub(crate) procedure_object_files: BTreeMap<u64, String>,
 }
 
 impl<'a, S: Source<'a> + 'a> PDBParserInstance<'a, S> {
@@ -158,15 +162,14 @@ impl<'a, S: Source<'a> + 'a> PDBParserInstance<'a, S> {
             indexed_symbols: Default::default(),
             addressed_symbols: Default::default(),
             module_cpu_type: None,
+            procedure_object_files: Default::default(),
         })
     }
 
     /// Try to parse the pdb into the DebugInfo
-    pub fn try_parse_info(
-        &mut self,
-        progress: Box<dyn Fn(usize, usize) -> Result<()> + 'a>,
-    ) -> Result<()> {
-        self.parse_types(Self::split_progress(&progress, 0, &[1.0, 3.0, 0.5, 0.5]))?;
+    pub fn try_parse_info(&mut self, progress: &ProgressScope) -> Result<()> {
+        let parts = progress.subscopes(&[2, 6, 1, 1]);
+        self.parse_types(&parts[0])?;
         for (name, ty) in self.named_types.iter() {
             self.debug_info.add_type(name, ty.as_ref(), &[]); // TODO : Components
         }
@@ -180,21 +183,14 @@ impl<'a, S: Source<'a> + 'a> PDBParserInstance<'a, S> {
             .settings
             .get_bool_with_opts("pdb.features.parseSymbols", &mut self.settings_query_opts)
         {
-            let (symbols, functions) =
-                self.parse_symbols(Self::split_progress(&progress, 1, &[1.0, 3.0, 0.5, 0.5]))?;
+            let (symbols, functions) = self.parse_symbols(&parts[1])?;
 
             if self.settings.get_bool_with_opts(
                 "pdb.features.createMissingNamedTypes",
                 &mut self.settings_query_opts,
             ) {
-                self.resolve_missing_ntrs(
-                    &symbols,
-                    Self::split_progress(&progress, 2, &[1.0, 3.0, 0.5, 0.5]),
-                )?;
-                self.resolve_missing_ntrs(
-                    &functions,
-                    Self::split_progress(&progress, 3, &[1.0, 3.0, 0.5, 0.5]),
-                )?;
+                self.resolve_missing_ntrs(&symbols, &parts[2])?;
+                self.resolve_missing_ntrs(&functions, &parts[3])?;
             }
 
             info!("PDB found {} types", self.named_types.len());
@@ -246,6 +242,13 @@ impl<'a, S: Source<'a> + 'a> PDBParserInstance<'a, S> {
                 }
             }
 
+            let components = match self.settings.get_bool_with_opts(
+                "analysis.debugInfo.componentsFromSourcePaths",
+                &mut self.settings_query_opts,
+            ) {
+                true => self.object_file_components(),
+                false => BTreeMap::new(),
+            };
             for sym in functions {
                 match sym {
                     ParsedSymbol::Procedure(ParsedProcedure {
@@ -275,7 +278,7 @@ impl<'a, S: Source<'a> + 'a> PDBParserInstance<'a, S> {
                             }),
                             Some(address),
                             Some(self.platform.clone()),
-                            vec![], // TODO : Components
+                            components.get(&address).cloned().unwrap_or_default(),
                             vec![], //TODO: local variables
                         ));
                     }
@@ -287,6 +290,38 @@ impl<'a, S: Source<'a> + 'a> PDBParserInstance<'a, S> {
         Ok(())
     }
 
+    /// Components of the object file of each procedure, below the directory all the object files
+    /// were built in.
+    fn object_file_components(&self) -> BTreeMap<u64, Vec<String>> {
+        let paths: BTreeMap<u64, Vec<String>> = self
+            .procedure_object_files
+            .iter()
+            .map(|(&address, path)| (address, source_path_components(path, None)))
+            .collect();
+
+        let mut shared: Option<&[String]> = None;
+        for parts in paths.values() {
+            let directory = &parts[..parts.len().saturating_sub(1)];
+            shared = Some(match shared {
+                None => directory,
+                Some(shared) => {
+                    let len = shared
+                        .iter()
+                        .zip(directory)
+                        .take_while(|(a, b)| a == b)
+                        .count();
+                    &shared[..len]
+                }
+            });
+        }
+        let shared_len = shared.map_or(0, |shared| shared.len());
+
+        paths
+            .into_iter()
+            .map(|(address, parts)| (address, parts[shared_len..].to_vec()))
+            .collect()
+    }
+
     fn collect_name(
         &self,
         name: &NamedTypeReference,
@@ -358,7 +393,7 @@ impl<'a, S: Source<'a> + 'a> PDBParserInstance<'a, S> {
     fn resolve_missing_ntrs(
         &mut self,
         symbols: &Vec<ParsedSymbol>,
-        progress: Box<dyn Fn(usize, usize) -> Result<()> + '_>,
+        progress: &ProgressScope,
     ) -> Result<()> {
         let mut unknown_names = HashMap::new();
         let mut known_names = self
@@ -394,7 +429,9 @@ impl<'a, S: Source<'a> + 'a> PDBParserInstance<'a, S> {
                 }
                 _ => {}
             }
-            progress(i, count)?;
+            progress
+                .report(i, count)
+                .map_err(|_| SymbolServerError::Cancelled)?;
         }
 
         for (name, class) in unknown_names.into_iter() {
@@ -467,39 +504,4 @@ impl<'a, S: Source<'a> + 'a> PDBParserInstance<'a, S> {
             );
         }
     }
-
-    pub(crate) fn split_progress<'b, F: Fn(usize, usize) -> Result<()> + 'b>(
-        original_fn: F,
-        subpart: usize,
-        subpart_weights: &[f64],
-    ) -> Box<dyn Fn(usize, usize) -> Result<()> + 'b> {
-        // Normalize weights
-        let weight_sum: f64 = subpart_weights.iter().sum();
-        if weight_sum < 0.0001 {
-            return Box::new(|_, _| Ok(()));
-        }
-
-        // Keep a running count of weights for the start
-        let mut subpart_starts = vec![];
-        let mut start = 0f64;
-        for w in subpart_weights {
-            subpart_starts.push(start);
-            start += *w;
-        }
-
-        let subpart_start = subpart_starts[subpart] / weight_sum;
-        let weight = subpart_weights[subpart] / weight_sum;
-
-        Box::new(move |cur: usize, max: usize| {
-            // Just use a large number for easy divisibility
-            let steps = 1000000f64;
-            let subpart_size = steps * weight;
-            let subpart_progress = ((cur as f64) / (max as f64)) * subpart_size;
-
-            original_fn(
-                (subpart_start * steps + subpart_progress) as usize,
-                steps as usize,
-            )
-        })
-    }
 }
@@ -40,7 +40,9 @@ use binaryninja::architecture::{Architecture, ArchitectureExt, Register, Registe
 use binaryninja::binary_view::BinaryViewBase;
 use binaryninja::confidence::{Conf, MAX_CONFIDENCE, MIN_CONFIDENCE};
 use binaryninja::demangle::demangle_ms;
+use binaryninja::progress::ProgressScope;
 use binaryninja::rc::Ref;
+use binaryninja::symbol_server::SymbolServerError;
 use binaryninja::types::{FunctionParameter, QualifiedName, StructureBuilder, Type, TypeClass};
 use binaryninja::variable::{Variable, VariableSourceType};
 
@@ -135,7 +137,7 @@ pub enum ParsedSymbol {
 impl<'a, S: Source<'a> + 'a> PDBParserInstance<'a, S> {
     pub fn parse_symbols(
         &mut self,
-        progress: Box<dyn Fn(usize, usize) -> Result<()> + '_>,
+        progress: &ProgressScope,
     ) -> Result<(Vec<ParsedSymbol>, Vec<ParsedSymbol>)> {
         let mut module_count = 0usize;
         let dbg = self.pdb.debug_information()?;
@@ -165,14 +167,18 @@ impl<'a, S: Source<'a> + 'a> PDBParserInstance<'a, S> {
             self.parsed_symbols.push(sym);
         }
 
-        (progress)(1, module_count + 1)?;
+        progress
+            .report(1, module_count + 1)
+            .map_err(|_| SymbolServerError::Cancelled)?;
 
         let dbg = self.pdb.debug_information()?;
         let mut modules = dbg.modules()?;
         let mut i = 0;
         while let Some(module) = modules.next()? {
             i += 1;
-            (progress)(i + 1, module_count + 1)?;
+            progress
+                .report(i + 1, module_count + 1)
+                .map_err(|_| SymbolServerError::Cancelled)?;
 
             self.log(|| {
                 format!(
@@ -184,7 +190,12 @@ impl<'a, S: Source<'a> + 'a> PDBParserInstance<'a, S> {
             if let Some(module_info) = self.pdb.module_info(&module)? {
                 let symbols = module_info.symbols()?;
                 let parsed = self.parse_mod_symbols(symbols)?;
+                let object_file = module.object_file_name().to_string();
                 for sym in parsed {
+                    if let ParsedSymbol::Procedure(ParsedProcedure { address, .. }) = &sym {
+                        self.procedure_object_files
+                            .insert(*address, object_file.clone());
+                    }
                     match &sym {
                         ParsedSymbol::Data(ParsedDataSymbol {
                             name: SymbolNames { raw_name, .. },
@@ -23,7 +23,9 @@ use binaryninja::binary_view::BinaryViewExt;
 use binaryninja::calling_convention::CoreCallingConvention;
 use binaryninja::confidence::{Conf, MAX_CONFIDENCE};
 use binaryninja::platform::Platform;
+use binaryninja::progress::ProgressScope;
 use binaryninja::rc::Ref;
+use binaryninja::symbol_server::SymbolServerError;
 use binaryninja::types::{
     BaseStructure, EnumerationBuilder, EnumerationMember, FunctionParameter, MemberAccess,
     MemberScope, NamedTypeReference, NamedTypeReferenceClass, QualifiedName, StructureBuilder,
@@ -254,10 +256,7 @@ impl TryFrom<u8> for CV_call_t {
 /// wrangle otherwise.
 impl<'a, S: Source<'a> + 'a> PDBParserInstance<'a, S> {
     /// Parse all the types in a pdb
-    pub fn parse_types(
-        &mut self,
-        progress: Box<dyn Fn(usize, usize) -> Result<()> + '_>,
-    ) -> Result<()> {
+    pub fn parse_types(&mut self, progress: &ProgressScope) -> Result<()> {
         // Hack: This is needed for primitive types but it's not defined in the pdb itself
         self.named_types
             .insert("HRESULT".into(), Type::int(4, true));
@@ -318,7 +317,9 @@ impl<'a, S: Source<'a> + 'a> PDBParserInstance<'a, S> {
         let mut i = 0;
         while let Some(ty) = types.next()? {
             i += 1;
-            (progress)(i, type_count * 2)?;
+            progress
+                .report(i, type_count * 2)
+                .map_err(|_| SymbolServerError::Cancelled)?;
 
             match ty.parse() {
                 Ok(TypeData::Class(_)) | Ok(TypeData::Enumeration(_)) | Ok(TypeData::Union(_)) => {
@@ -337,7 +338,9 @@ impl<'a, S: Source<'a> + 'a> PDBParserInstance<'a, S> {
         let mut postpass_types = type_information.iter();
         while let Some(ty) = postpass_types.next()? {
             i += 1;
-            (progress)(i, type_count * 2)?;
+            progress
+                .report(i, type_count * 2)
+                .map_err(|_| SymbolServerError::Cancelled)?;
 
             self.handle_type_index(ty.index(), &mut finder)?;
         }
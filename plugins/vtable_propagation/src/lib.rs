@@ -0,0 +1,96 @@
+//! Types C++ objects from the vtables their constructors store.
+
+mod naming;
+mod propagate;
+
+use binaryninja::logger::Logger;
+use binaryninja::settings::Settings;
+use binaryninja::workflow::{Activity, ActivityConfig, Workflow};
+
+use log::LevelFilter;
+
+const WORKFLOW_NAME: &str = "plugins.function.vtablePropagation";
+const WORKFLOW_CONFIG: &str = r#"{
+    "title": "Vtable Type Propagation",
+    "description": "Function analysis that types the this pointer of constructors from the vtable they store, and propagates the class into virtual methods and callees.",
+    "targetType": "function"
+}"#;
+
+const ACTIVITY_NAME: &str = "analysis.plugins.vtablePropagation.propagate";
+const MODULE_ACTIVITY_NAME: &str = "analysis.plugins.vtablePropagation.propagateQueued";
+
+const PROPAGATE_TO_CALLEES_SETTING: &str = "analysis.plugins.vtablePropagation.propagateToCallees";
+const TYPE_VIRTUAL_METHODS_SETTING: &str = "analysis.plugins.vtablePropagation.typeVirtualMethods";
+
+/// Source the class and vtable types are defined with.
+const TYPE_SOURCE: &str = "vtable_propagation";
+
+fn register_settings() {
+    let settings = Settings::new();
+    settings.register_setting_json(
+        PROPAGATE_TO_CALLEES_SETTING,
+        r#"{
+            "title" : "Propagate Classes to Callees",
+            "type" : "boolean",
+            "default" : true,
+            "description" : "Type the first parameter of functions called with a typed this pointer as the same class.",
+            "ignore" : ["SettingsProjectScope"]
+        }"#,
+    );
+    settings.register_setting_json(
+        TYPE_VIRTUAL_METHODS_SETTING,
+        r#"{
+            "title" : "Type Virtual Methods",
+            "type" : "boolean",
+            "default" : true,
+            "description" : "Type the this pointer of the functions in a vtable as the class the vtable belongs to. Methods inherited from a base class are typed as the first class found using them.",
+            "ignore" : ["SettingsProjectScope"]
+        }"#,
+    );
+}
+
+#[allow(non_snake_case)]
+#[no_mangle]
+pub extern "C" fn CorePluginInit() -> bool {
+    Logger::new("Vtable Propagation")
+        .with_level(LevelFilter::Info)
+        .init();
+
+    register_settings();
+
+    let workflow = Workflow::instance("core.function.metaAnalysis").clone(WORKFLOW_NAME);
+    let config = ActivityConfig::new(ACTIVITY_NAME)
+        .title("Propagate Vtable Types")
+        .description("This analysis step types the this pointer of functions storing a vtable into it, and queues the functions with a class to propagate it.")
+        .auto(true);
+    let activity = Activity::new_with_action(&config, propagate::propagate_activity);
+    if workflow.register_activity(&activity).is_err() {
+        log::error!("Failed to register the vtable propagation activity");
+        return false;
+    }
+    // Vtable stores and calls are read from MLIL, the new types are in place before HLIL
+    workflow.insert("core.function.generateHighLevelIL", [ACTIVITY_NAME]);
+    if workflow.register_with_config(WORKFLOW_CONFIG).is_err() {
+        log::error!("Failed to register the `{}` workflow", WORKFLOW_NAME);
+        return false;
+    }
+
+    // Other functions are only typed once the analysis of the view completes
+    let module_workflow =
+        Workflow::instance("core.module.metaAnalysis").clone("core.module.metaAnalysis");
+    let config = ActivityConfig::new(MODULE_ACTIVITY_NAME)
+        .title("Propagate Vtable Types to Other Functions")
+        .description("This analysis step types the this pointer of the virtual methods and callees of the functions with a class.")
+        .auto(true);
+    let activity = Activity::new_with_action(&config, propagate::propagate_queued_activity);
+    if module_workflow.register_activity(&activity).is_err() {
+        log::error!("Failed to register the vtable propagation module activity");
+        return false;
+    }
+    module_workflow.insert("core.module.notifyCompletion", [MODULE_ACTIVITY_NAME]);
+    if module_workflow.register().is_err() {
+        log::error!("Failed to register the vtable propagation module activity");
+        return false;
+    }
+    true
+}
@@ -0,0 +1,67 @@
+/// The class whose vtable a demangled symbol names, `vtable for Foo` for the Itanium ABI and
+/// ``const Foo::`vftable'`` for MSVC.
+pub fn class_from_vtable_symbol(name: &str) -> Option<&str> {
+    let class = match name.strip_prefix("vtable for ") {
+        Some(class) => class,
+        None => {
+            let name = name.strip_prefix("const ").unwrap_or(name);
+            name.split_once("::`vftable'")?.0
+        }
+    };
+    let class = class.trim();
+    (!class.is_empty()).then_some(class)
+}
+
+/// Name of a class whose vtable has no symbol.
+pub fn fallback_class_name(vtable: u64) -> String {
+    format!("class_{:x}", vtable)
+}
+
+pub fn vtable_type_name(class: &str) -> String {
+    format!("{}::VTable", class)
+}
+
+pub fn slot_member_name(index: usize) -> String {
+    format!("vFunc_{}", index)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_itanium_vtable_symbol() {
+        assert_eq!(class_from_vtable_symbol("vtable for Shape"), Some("Shape"));
+        assert_eq!(
+            class_from_vtable_symbol("vtable for geometry::Circle"),
+            Some("geometry::Circle")
+        );
+        assert_eq!(class_from_vtable_symbol("vtable for "), None);
+        assert_eq!(class_from_vtable_symbol("_ZTV5Shape"), None);
+    }
+
+    #[test]
+    fn test_msvc_vtable_symbol() {
+        assert_eq!(
+            class_from_vtable_symbol("const Shape::`vftable'"),
+            Some("Shape")
+        );
+        assert_eq!(
+            class_from_vtable_symbol("geometry::Circle::`vftable'"),
+            Some("geometry::Circle")
+        );
+        assert_eq!(
+            class_from_vtable_symbol("const Circle::`vftable'{for `Drawable'}"),
+            Some("Circle")
+        );
+        assert_eq!(class_from_vtable_symbol("??_7Shape@@6B@"), None);
+        assert_eq!(class_from_vtable_symbol("sub_401000"), None);
+    }
+
+    #[test]
+    fn test_generated_names() {
+        assert_eq!(fallback_class_name(0x4020a0), "class_4020a0");
+        assert_eq!(vtable_type_name("Shape"), "Shape::VTable");
+        assert_eq!(slot_member_name(3), "vFunc_3");
+    }
+}
@@ -0,0 +1,371 @@
+use std::collections::{BTreeSet, HashSet};
+use std::sync::Mutex;
+
+use binaryninja::architecture::CoreArchitecture;
+use binaryninja::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
+use binaryninja::confidence::Conf;
+use binaryninja::function::Function;
+use binaryninja::medium_level_il::{
+    MediumLevelILFunction, MediumLevelILLiftedInstruction, MediumLevelILLiftedInstructionKind,
+};
+use binaryninja::platform::Platform;
+use binaryninja::rc::Ref;
+use binaryninja::settings::{QueryOptions, Settings};
+use binaryninja::types::{
+    MemberAccess, MemberScope, NamedTypeReference, NamedTypeReferenceClass, StructureBuilder, Type,
+};
+use binaryninja::variable::Variable;
+use binaryninja::workflow::AnalysisContext;
+use log::debug;
+
+use crate::naming;
+use crate::{PROPAGATE_TO_CALLEES_SETTING, TYPE_SOURCE, TYPE_VIRTUAL_METHODS_SETTING};
+
+/// Confidence of the `this` type of a function that stores a vtable into it. It is higher than the
+/// confidence of propagated types, so a base class constructor keeps its own class when it is called
+/// from the constructor of a derived class.
+const CONSTRUCTOR_CONFIDENCE: u8 = 200;
+const PROPAGATED_CONFIDENCE: u8 = 160;
+
+/// The most entries read from a vtable.
+const MAX_VTABLE_SLOTS: u64 = 0x200;
+
+/// View metadata key of the classes defined from vtables. Functions with a pointer to one of these
+/// as their first parameter propagate it further to their callees.
+const CLASSES_METADATA_KEY: &str = "vtable_propagation_classes";
+
+/// View metadata key of the functions whose class is still to be propagated to other functions.
+const QUEUED_METADATA_KEY: &str = "vtable_propagation_queued_functions";
+
+/// Serializes the updates of the view metadata, functions are analyzed concurrently.
+static METADATA_LOCK: Mutex<()> = Mutex::new(());
+
+fn known_classes(view: &BinaryView) -> Vec<String> {
+    view.query_metadata(CLASSES_METADATA_KEY)
+        .and_then(|classes| Vec::<String>::try_from(&*classes).ok())
+        .unwrap_or_default()
+}
+
+fn is_known_class(view: &BinaryView, class: &str) -> bool {
+    known_classes(view).iter().any(|known| known == class)
+}
+
+fn add_known_class(view: &BinaryView, class: &str) {
+    let _lock = METADATA_LOCK.lock().unwrap();
+    let mut classes = known_classes(view);
+    if !classes.iter().any(|known| known == class) {
+        classes.push(class.to_string());
+        view.store_metadata(CLASSES_METADATA_KEY, classes, true);
+    }
+}
+
+fn queue_function(view: &BinaryView, function: &Function) {
+    let _lock = METADATA_LOCK.lock().unwrap();
+    let mut queued = view
+        .query_metadata(QUEUED_METADATA_KEY)
+        .and_then(|queued| Vec::<u64>::try_from(&*queued).ok())
+        .unwrap_or_default();
+    if !queued.contains(&function.start()) {
+        queued.push(function.start());
+        view.store_metadata(QUEUED_METADATA_KEY, &queued, true);
+    }
+}
+
+fn take_queued_functions(view: &BinaryView) -> BTreeSet<u64> {
+    let _lock = METADATA_LOCK.lock().unwrap();
+    let Some(queued) = view.query_metadata(QUEUED_METADATA_KEY) else {
+        return BTreeSet::new();
+    };
+    view.remove_metadata(QUEUED_METADATA_KEY);
+    Vec::<u64>::try_from(&*queued)
+        .unwrap_or_default()
+        .into_iter()
+        .collect()
+}
+
+/// How a function uses its first parameter.
+#[derive(Default)]
+struct ThisUses {
+    /// The last constant stored at offset zero of the parameter.
+    vtable: Option<u64>,
+    /// Functions called with the parameter as their first argument.
+    callees: Vec<u64>,
+}
+
+fn constant(expr: &MediumLevelILLiftedInstruction) -> Option<u64> {
+    match &expr.kind {
+        MediumLevelILLiftedInstructionKind::Const(op)
+        | MediumLevelILLiftedInstructionKind::ConstPtr(op) => Some(op.constant),
+        _ => None,
+    }
+}
+
+fn is_alias(expr: &MediumLevelILLiftedInstruction, aliases: &HashSet<Variable>) -> bool {
+    match &expr.kind {
+        MediumLevelILLiftedInstructionKind::Var(op) => aliases.contains(&op.src),
+        _ => false,
+    }
+}
+
+fn scan_this_uses(mlil: &MediumLevelILFunction, this: Variable) -> ThisUses {
+    let mut aliases = HashSet::from([this]);
+    let mut uses = ThisUses::default();
+    for instr in mlil.instructions() {
+        match instr.lift().kind {
+            MediumLevelILLiftedInstructionKind::SetVar(op) if is_alias(&op.src, &aliases) => {
+                aliases.insert(op.dest);
+            }
+            MediumLevelILLiftedInstructionKind::Store(op) if is_alias(&op.dest, &aliases) => {
+                uses.vtable = constant(&op.src).or(uses.vtable);
+            }
+            MediumLevelILLiftedInstructionKind::StoreStruct(op)
+                if op.offset == 0 && is_alias(&op.dest, &aliases) =>
+            {
+                uses.vtable = constant(&op.src).or(uses.vtable);
+            }
+            MediumLevelILLiftedInstructionKind::Call(op)
+                if op.params.first().is_some_and(|arg| is_alias(arg, &aliases)) =>
+            {
+                uses.callees.extend(constant(&op.dest));
+            }
+            _ => {}
+        }
+    }
+    uses
+}
+
+/// The functions a vtable points to, empty if `vtable` does not point to any.
+fn vtable_slots(view: &BinaryView, platform: &Platform, vtable: u64) -> Vec<Ref<Function>> {
+    let width = view.address_size() as u64;
+    let mut slots = Vec::new();
+    for index in 0..MAX_VTABLE_SLOTS {
+        let address = vtable + index * width;
+        // A symbol after the first entry starts the next object
+        if index > 0 && view.symbol_by_address(address).is_some() {
+            break;
+        }
+        let Ok(target) = view.read_pointer(address) else {
+            break;
+        };
+        let Some(function) = view.function_at(platform, target) else {
+            break;
+        };
+        slots.push(function);
+    }
+    slots
+}
+
+fn class_name(view: &BinaryView, vtable: u64) -> String {
+    // MSVC names the vtable itself, the Itanium ABI names the object the vtable starts two
+    // pointers into
+    let width = view.address_size() as u64;
+    [Some(vtable), vtable.checked_sub(2 * width)]
+        .into_iter()
+        .flatten()
+        .filter_map(|address| view.symbol_by_address(address))
+        .find_map(|symbol| {
+            let name = symbol.full_name().to_string();
+            naming::class_from_vtable_symbol(&name).map(str::to_owned)
+        })
+        .unwrap_or_else(|| naming::fallback_class_name(vtable))
+}
+
+fn named_struct(name: &str) -> Ref<Type> {
+    Type::named_type(&NamedTypeReference::new(
+        NamedTypeReferenceClass::StructNamedTypeClass,
+        name,
+    ))
+}
+
+/// Define the class and its vtable type unless a class of that name came from elsewhere, and
+/// return a pointer to the class.
+fn define_class(
+    view: &BinaryView,
+    arch: &CoreArchitecture,
+    class: &str,
+    slots: &[Ref<Function>],
+) -> Ref<Type> {
+    let class_exists = view.type_by_name(class).is_some();
+    if class_exists && !is_known_class(view, class) {
+        return Type::pointer(arch, &named_struct(class));
+    }
+
+    // More entries of the vtable are found as more functions are analyzed
+    let vtable_name = naming::vtable_type_name(class);
+    let known_slots = view
+        .type_by_name(vtable_name.as_str())
+        .and_then(|ty| ty.get_structure())
+        .map_or(0, |structure| structure.members().len());
+    if known_slots < slots.len() {
+        let mut vtable = StructureBuilder::new();
+        for (index, slot) in slots.iter().enumerate() {
+            vtable.append(
+                &Type::pointer(arch, &slot.function_type()),
+                naming::slot_member_name(index),
+                MemberAccess::PublicAccess,
+                MemberScope::NoScope,
+            );
+        }
+        view.define_auto_type(
+            vtable_name.as_str(),
+            TYPE_SOURCE,
+            &Type::structure(&vtable.finalize()),
+        );
+    }
+
+    if !class_exists {
+        let mut structure = StructureBuilder::new();
+        structure.append(
+            &Type::pointer(arch, &named_struct(&vtable_name)),
+            "vtable",
+            MemberAccess::PublicAccess,
+            MemberScope::NoScope,
+        );
+        view.define_auto_type(class, TYPE_SOURCE, &Type::structure(&structure.finalize()));
+        add_known_class(view, class);
+    }
+    Type::pointer(arch, &named_struct(class))
+}
+
+/// The class a function received through its first parameter, if it is one defined from a vtable.
+fn propagated_class(view: &BinaryView, function: &Function) -> Option<String> {
+    let first = function.function_type().parameters()?.into_iter().next()?;
+    let target = first.ty.contents.target()?;
+    let class = target
+        .contents
+        .get_named_type_reference()?
+        .name()
+        .to_string();
+    is_known_class(view, &class).then_some(class)
+}
+
+/// Change the type of the first parameter of `function` to `this_type`, unless the function has a
+/// user type or the parameter already has a type with at least `confidence`.
+fn apply_this_type(function: &Function, this_type: &Type, confidence: u8) -> bool {
+    if function.has_user_type() {
+        return false;
+    }
+    let Some(mut params) = function.function_type().parameters() else {
+        return false;
+    };
+    let Some(first) = params.first_mut() else {
+        return false;
+    };
+    if first.ty.confidence >= confidence {
+        return false;
+    }
+    let Some(calling_convention) = function.calling_convention() else {
+        return false;
+    };
+    first.ty = Conf::new(this_type.to_owned(), confidence);
+
+    let function_type = Type::function_with_opts(
+        &function.return_type(),
+        &params,
+        function.has_variable_arguments().contents,
+        calling_convention,
+        function.stack_adjustment(),
+    );
+    function.apply_auto_discovered_type(&function_type);
+    true
+}
+
+/// The vtable `uses` stores and the functions it points to, if it stores one.
+fn stored_vtable(
+    view: &BinaryView,
+    platform: &Platform,
+    uses: &ThisUses,
+) -> Option<(u64, Vec<Ref<Function>>)> {
+    uses.vtable
+        .map(|vtable| (vtable, vtable_slots(view, platform, vtable)))
+        .filter(|(_, slots)| !slots.is_empty())
+}
+
+/// Type the `this` pointer of a function storing a vtable into it, and queue the functions with a
+/// class for the module pass to propagate it to other functions.
+pub fn propagate_activity(ctx: &AnalysisContext) {
+    let function = ctx.function();
+    let Some(mlil) = ctx.mlil_function() else {
+        return;
+    };
+    let Some(this) = function.parameter_variables().contents.first().copied() else {
+        return;
+    };
+    let view = function.view();
+    let arch = function.arch();
+    let platform = function.platform();
+
+    let uses = scan_this_uses(&mlil, this);
+    match stored_vtable(&view, &platform, &uses) {
+        Some((vtable, slots)) => {
+            let class = class_name(&view, vtable);
+            let this_type = define_class(&view, &arch, &class, &slots);
+            if apply_this_type(&function, &this_type, CONSTRUCTOR_CONFIDENCE) {
+                debug!(
+                    "Function {:#x} stores the vtable of `{}` at {:#x}",
+                    function.start(),
+                    class,
+                    vtable
+                );
+            }
+        }
+        None if propagated_class(&view, &function).is_some() => {}
+        None => return,
+    }
+    queue_function(&view, &function);
+}
+
+/// Propagate the class of each function queued by [`propagate_activity`] to the functions in its
+/// vtable and to its callees, once the analysis of the view completes.
+pub fn propagate_queued_activity(ctx: &AnalysisContext) {
+    let view = ctx.view();
+    let settings = Settings::new();
+    let mut options = QueryOptions::new_with_view(&view);
+    let type_virtual_methods =
+        settings.get_bool_with_opts(TYPE_VIRTUAL_METHODS_SETTING, &mut options);
+    let propagate_to_callees =
+        settings.get_bool_with_opts(PROPAGATE_TO_CALLEES_SETTING, &mut options);
+
+    let mut changed = false;
+    for address in take_queued_functions(&view) {
+        for function in &view.functions_at(address) {
+            let Some(mlil) = function.medium_level_il_if_available() else {
+                continue;
+            };
+            let Some(this) = function.parameter_variables().contents.first().copied() else {
+                continue;
+            };
+            let arch = function.arch();
+            let platform = function.platform();
+
+            let uses = scan_this_uses(&mlil, this);
+            let mut targets = Vec::new();
+            let this_type = match stored_vtable(&view, &platform, &uses) {
+                Some((vtable, slots)) => {
+                    if type_virtual_methods {
+                        targets.extend(slots);
+                    }
+                    Type::pointer(&arch, &named_struct(&class_name(&view, vtable)))
+                }
+                None => match propagated_class(&view, &function) {
+                    Some(class) => Type::pointer(&arch, &named_struct(&class)),
+                    None => continue,
+                },
+            };
+            if propagate_to_callees {
+                targets.extend(
+                    uses.callees
+                        .iter()
+                        .filter_map(|&callee| view.function_at(&platform, callee)),
+                );
+            }
+            for target in &targets {
+                changed |= apply_this_type(target, &this_type, PROPAGATED_CONFIDENCE);
+            }
+        }
+    }
+    // The functions typed here are analyzed again, and queue themselves for their own callees
+    if changed {
+        view.update_analysis();
+    }
+}
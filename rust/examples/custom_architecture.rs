@@ -0,0 +1,509 @@
+//! A tiny architecture plugin: "acc8", an 8-bit accumulator machine with 16-bit addresses.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use binaryninja::architecture::{
+    register_architecture, Architecture, ArchitectureExt, BranchKind, CoreArchitecture,
+    CustomArchitectureHandle, FlagClassId, FlagCondition, FlagGroupId, FlagId, FlagRole,
+    FlagWriteId, ImplicitRegisterExtend, InstructionInfo, IntrinsicId, RegisterId,
+    UnusedRegisterStack, UnusedRegisterStackInfo,
+};
+use binaryninja::binary_view::{BinaryView, BinaryViewExt};
+use binaryninja::calling_convention::ConventionBuilder;
+use binaryninja::confidence::{Conf, MAX_CONFIDENCE};
+use binaryninja::disassembly::{InstructionTextToken, InstructionTextTokenKind};
+use binaryninja::file_metadata::FileMetadata;
+use binaryninja::low_level_il::instruction::InstructionHandler;
+use binaryninja::low_level_il::{LowLevelILRegister, MutableLiftedILFunction};
+use binaryninja::platform::Platform;
+use binaryninja::rc::Ref;
+use binaryninja::types::{NameAndType, Type};
+use binaryninja::{architecture, Endianness};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Register {
+    A,
+    Sp,
+}
+
+impl architecture::Register for Register {
+    type InfoType = Self;
+
+    fn name(&self) -> Cow<str> {
+        match self {
+            Self::A => "a".into(),
+            Self::Sp => "sp".into(),
+        }
+    }
+
+    fn info(&self) -> Self::InfoType {
+        *self
+    }
+
+    fn id(&self) -> RegisterId {
+        match self {
+            Self::A => 0,
+            Self::Sp => 1,
+        }
+        .into()
+    }
+}
+
+impl architecture::RegisterInfo for Register {
+    type RegType = Self;
+
+    fn parent(&self) -> Option<Self::RegType> {
+        None
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            Self::A => 1,
+            Self::Sp => 2,
+        }
+    }
+
+    fn offset(&self) -> usize {
+        0
+    }
+
+    fn implicit_extend(&self) -> ImplicitRegisterExtend {
+        ImplicitRegisterExtend::NoExtend
+    }
+}
+
+impl From<Register> for LowLevelILRegister<Register> {
+    fn from(register: Register) -> Self {
+        LowLevelILRegister::ArchReg(register)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Flag {
+    Z,
+}
+
+impl architecture::Flag for Flag {
+    type FlagClass = FlagClass;
+
+    fn name(&self) -> Cow<str> {
+        "z".into()
+    }
+
+    fn role(&self, _class: Option<Self::FlagClass>) -> FlagRole {
+        FlagRole::ZeroFlagRole
+    }
+
+    fn id(&self) -> FlagId {
+        0.into()
+    }
+}
+
+/// The flags written by an instruction, referenced from the lifted IL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum FlagWrite {
+    Z,
+}
+
+impl architecture::FlagWrite for FlagWrite {
+    type FlagType = Flag;
+    type FlagClass = FlagClass;
+
+    fn name(&self) -> Cow<str> {
+        "z".into()
+    }
+
+    fn class(&self) -> Option<Self::FlagClass> {
+        None
+    }
+
+    // Flag write ids start at 1, 0 means no flags are written
+    fn id(&self) -> FlagWriteId {
+        1.into()
+    }
+
+    fn flags_written(&self) -> Vec<Self::FlagType> {
+        vec![Flag::Z]
+    }
+}
+
+/// acc8 has a single class of flag writes and no flag groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum FlagClass {}
+
+impl architecture::FlagClass for FlagClass {
+    fn name(&self) -> Cow<str> {
+        match *self {}
+    }
+
+    fn id(&self) -> FlagClassId {
+        match *self {}
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum FlagGroup {}
+
+impl architecture::FlagGroup for FlagGroup {
+    type FlagType = Flag;
+    type FlagClass = FlagClass;
+
+    fn name(&self) -> Cow<str> {
+        match *self {}
+    }
+
+    fn id(&self) -> FlagGroupId {
+        match *self {}
+    }
+
+    fn flags_required(&self) -> Vec<Self::FlagType> {
+        match *self {}
+    }
+
+    fn flag_conditions(&self) -> HashMap<Self::FlagClass, FlagCondition> {
+        match *self {}
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Intrinsic {
+    Out,
+}
+
+impl architecture::Intrinsic for Intrinsic {
+    fn name(&self) -> Cow<str> {
+        "_out".into()
+    }
+
+    fn id(&self) -> IntrinsicId {
+        0.into()
+    }
+
+    fn inputs(&self) -> Vec<NameAndType> {
+        vec![
+            NameAndType::new("port", Conf::new(Type::int(1, false), MAX_CONFIDENCE)),
+            NameAndType::new("value", Conf::new(Type::int(1, false), MAX_CONFIDENCE)),
+        ]
+    }
+
+    fn outputs(&self) -> Vec<Conf<Ref<Type>>> {
+        vec![]
+    }
+}
+
+/// The instructions of acc8, `addi` sets the zero flag, `jz` branches on it and `out` is lifted to
+/// an intrinsic.
+///
+/// | Encoding       | Instruction    |
+/// |----------------|----------------|
+/// | `00`           | `nop`          |
+/// | `01 ii`        | `ldi a, ii`    |
+/// | `02 ii`        | `addi a, ii`   |
+/// | `03 lo hi`     | `jmp hilo`     |
+/// | `04 lo hi`     | `jz hilo`      |
+/// | `05 lo hi`     | `call hilo`    |
+/// | `06`           | `ret`          |
+/// | `07 pp`        | `out pp, a`    |
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Instruction {
+    Nop,
+    Ldi(u8),
+    Addi(u8),
+    Jmp(u16),
+    Jz(u16),
+    Call(u16),
+    Ret,
+    Out(u8),
+}
+
+impl Instruction {
+    fn decode(data: &[u8]) -> Option<(usize, Self)> {
+        let imm8 = || data.get(1).copied();
+        let imm16 = || Some(u16::from_le_bytes([*data.get(1)?, *data.get(2)?]));
+        match data.first()? {
+            0x00 => Some((1, Self::Nop)),
+            0x01 => Some((2, Self::Ldi(imm8()?))),
+            0x02 => Some((2, Self::Addi(imm8()?))),
+            0x03 => Some((3, Self::Jmp(imm16()?))),
+            0x04 => Some((3, Self::Jz(imm16()?))),
+            0x05 => Some((3, Self::Call(imm16()?))),
+            0x06 => Some((1, Self::Ret)),
+            0x07 => Some((2, Self::Out(imm8()?))),
+            _ => None,
+        }
+    }
+}
+
+struct Acc8 {
+    handle: CoreArchitecture,
+    custom_handle: CustomArchitectureHandle<Acc8>,
+}
+
+impl Architecture for Acc8 {
+    type Handle = CustomArchitectureHandle<Self>;
+    type RegisterStackInfo = UnusedRegisterStackInfo<Self::Register>;
+    type RegisterStack = UnusedRegisterStack<Self::Register>;
+    type Register = Register;
+    type RegisterInfo = Register;
+    type Flag = Flag;
+    type FlagWrite = FlagWrite;
+    type FlagClass = FlagClass;
+    type FlagGroup = FlagGroup;
+    type Intrinsic = Intrinsic;
+
+    fn endianness(&self) -> Endianness {
+        Endianness::LittleEndian
+    }
+
+    fn address_size(&self) -> usize {
+        2
+    }
+
+    fn default_integer_size(&self) -> usize {
+        1
+    }
+
+    fn instruction_alignment(&self) -> usize {
+        1
+    }
+
+    fn max_instr_len(&self) -> usize {
+        3
+    }
+
+    fn opcode_display_len(&self) -> usize {
+        self.max_instr_len()
+    }
+
+    fn associated_arch_by_addr(&self, _addr: u64) -> CoreArchitecture {
+        self.handle
+    }
+
+    fn instruction_info(&self, data: &[u8], addr: u64) -> Option<InstructionInfo> {
+        let (len, inst) = Instruction::decode(data)?;
+        let mut info = InstructionInfo::new(len, 0);
+        match inst {
+            Instruction::Jmp(target) => info.add_branch(BranchKind::Unconditional(target as u64)),
+            Instruction::Jz(target) => {
+                info.add_branch(BranchKind::True(target as u64));
+                info.add_branch(BranchKind::False(addr + len as u64));
+            }
+            Instruction::Call(target) => info.add_branch(BranchKind::Call(target as u64)),
+            Instruction::Ret => info.add_branch(BranchKind::FunctionReturn),
+            _ => {}
+        }
+        Some(info)
+    }
+
+    fn instruction_text(
+        &self,
+        data: &[u8],
+        _addr: u64,
+    ) -> Option<(usize, Vec<InstructionTextToken>)> {
+        let (len, inst) = Instruction::decode(data)?;
+        let mnemonic = |name: &str| {
+            InstructionTextToken::new(
+                format!("{:<6}", name),
+                InstructionTextTokenKind::Instruction,
+            )
+        };
+        let a = || InstructionTextToken::new("a", InstructionTextTokenKind::Register);
+        let separator =
+            || InstructionTextToken::new(", ", InstructionTextTokenKind::OperandSeparator);
+        let integer = |value: u8| {
+            InstructionTextToken::new(
+                format!("{:#x}", value),
+                InstructionTextTokenKind::Integer {
+                    value: value as u64,
+                    size: Some(1),
+                },
+            )
+        };
+        let address = |value: u16| {
+            InstructionTextToken::new(
+                format!("{:#06x}", value),
+                InstructionTextTokenKind::PossibleAddress {
+                    value: value as u64,
+                    size: Some(2),
+                },
+            )
+        };
+
+        let tokens = match inst {
+            Instruction::Nop => vec![mnemonic("nop")],
+            Instruction::Ldi(imm) => vec![mnemonic("ldi"), a(), separator(), integer(imm)],
+            Instruction::Addi(imm) => vec![mnemonic("addi"), a(), separator(), integer(imm)],
+            Instruction::Jmp(target) => vec![mnemonic("jmp"), address(target)],
+            Instruction::Jz(target) => vec![mnemonic("jz"), address(target)],
+            Instruction::Call(target) => vec![mnemonic("call"), address(target)],
+            Instruction::Ret => vec![mnemonic("ret")],
+            Instruction::Out(port) => vec![mnemonic("out"), integer(port), separator(), a()],
+        };
+        Some((len, tokens))
+    }
+
+    fn instruction_llil(
+        &self,
+        data: &[u8],
+        addr: u64,
+        il: &mut MutableLiftedILFunction<Self>,
+    ) -> Option<(usize, bool)> {
+        let (len, inst) = Instruction::decode(data)?;
+        match inst {
+            Instruction::Nop => il.nop().append(),
+            Instruction::Ldi(imm) => il
+                .set_reg(1, Register::A, il.const_int(1, imm as u64))
+                .append(),
+            Instruction::Addi(imm) => {
+                let sum = il
+                    .add(1, il.reg(1, Register::A), il.const_int(1, imm as u64))
+                    .with_flag_write(FlagWrite::Z);
+                il.set_reg(1, Register::A, sum).append();
+            }
+            Instruction::Jmp(target) => il.goto_address(target as u64),
+            Instruction::Jz(target) => il.branch_if(
+                il.flag_cond(FlagCondition::LLFC_E),
+                target as u64,
+                addr + len as u64,
+            ),
+            Instruction::Call(target) => il.call(il.const_ptr(target as u64)).append(),
+            Instruction::Ret => il.ret(il.pop(2)).append(),
+            Instruction::Out(port) => il
+                .intrinsic(
+                    MutableLiftedILFunction::<Self>::NO_OUTPUTS,
+                    Intrinsic::Out,
+                    [il.const_int(1, port as u64), il.reg(1, Register::A)],
+                )
+                .append(),
+        }
+        Some((len, true))
+    }
+
+    fn flags_required_for_flag_condition(
+        &self,
+        condition: FlagCondition,
+        _class: Option<Self::FlagClass>,
+    ) -> Vec<Self::Flag> {
+        match condition {
+            FlagCondition::LLFC_E | FlagCondition::LLFC_NE => vec![Flag::Z],
+            _ => vec![],
+        }
+    }
+
+    fn registers_all(&self) -> Vec<Self::Register> {
+        vec![Register::A, Register::Sp]
+    }
+
+    fn registers_full_width(&self) -> Vec<Self::Register> {
+        self.registers_all()
+    }
+
+    fn flags(&self) -> Vec<Self::Flag> {
+        vec![Flag::Z]
+    }
+
+    fn flag_write_types(&self) -> Vec<Self::FlagWrite> {
+        vec![FlagWrite::Z]
+    }
+
+    fn stack_pointer_reg(&self) -> Option<Self::Register> {
+        Some(Register::Sp)
+    }
+
+    fn register_from_id(&self, id: RegisterId) -> Option<Self::Register> {
+        match id.0 {
+            0 => Some(Register::A),
+            1 => Some(Register::Sp),
+            _ => None,
+        }
+    }
+
+    fn flag_from_id(&self, id: FlagId) -> Option<Self::Flag> {
+        (id.0 == 0).then_some(Flag::Z)
+    }
+
+    fn flag_write_from_id(&self, id: FlagWriteId) -> Option<Self::FlagWrite> {
+        (id.0 == 1).then_some(FlagWrite::Z)
+    }
+
+    fn intrinsics(&self) -> Vec<Self::Intrinsic> {
+        vec![Intrinsic::Out]
+    }
+
+    fn intrinsic_from_id(&self, id: IntrinsicId) -> Option<Self::Intrinsic> {
+        (id.0 == 0).then_some(Intrinsic::Out)
+    }
+
+    fn handle(&self) -> Self::Handle {
+        self.custom_handle
+    }
+}
+
+impl AsRef<CoreArchitecture> for Acc8 {
+    fn as_ref(&self) -> &CoreArchitecture {
+        &self.handle
+    }
+}
+
+/// Writes `a` to port 0x10 and calls a helper, looping for as long as the addition leaves `a` zero.
+const PROGRAM: &[u8] = &[
+    0x01, 0x00, // 0x00: ldi  a, 0x0
+    0x07, 0x10, // 0x02: out  0x10, a
+    0x05, 0x0d, 0x00, // 0x04: call 0x000d
+    0x02, 0x01, // 0x07: addi a, 0x1
+    0x04, 0x02, 0x00, // 0x09: jz   0x0002
+    0x06, // 0x0c: ret
+    0x00, // 0x0d: nop
+    0x06, // 0x0e: ret
+];
+
+// In a real plugin the registration in `main` would live in `CorePluginInit`.
+fn main() {
+    println!("Starting session...");
+    let _headless_session =
+        binaryninja::headless::Session::new().expect("Failed to initialize session");
+
+    println!("Registering architecture...");
+    let arch = register_architecture("acc8", |custom_handle, handle| Acc8 {
+        handle,
+        custom_handle,
+    });
+    let default_cc = ConventionBuilder::new(arch)
+        .return_int_reg("a")
+        .register("default");
+    arch.set_default_calling_convention(&default_cc);
+    let platform = Platform::new(arch, "acc8");
+
+    let file = FileMetadata::new();
+    let view = BinaryView::from_data(&file, PROGRAM).expect("Failed to create view");
+    view.set_default_arch(arch);
+    view.set_default_platform(&platform);
+    let func = view
+        .add_auto_function(&platform, 0)
+        .expect("Failed to add function");
+    view.update_analysis_and_wait();
+
+    println!("Function count: {}", view.functions().len());
+    println!("Disassembly:");
+    for basic_block in &func.basic_blocks() {
+        for addr in basic_block.as_ref() {
+            let data = view.read_vec(addr, arch.max_instr_len());
+            if let Some((_, tokens)) = arch.instruction_text(&data, addr) {
+                let line = tokens
+                    .iter()
+                    .map(|token| token.to_string())
+                    .collect::<String>();
+                println!("{addr:#06x}    {line}");
+            }
+        }
+    }
+
+    println!("Low level IL:");
+    let llil = func.low_level_il().expect("Failed to get LLIL");
+    for basic_block in &llil.basic_blocks() {
+        for instr in basic_block.iter() {
+            println!("{:#06x}    {:?}", instr.address(), instr.kind());
+        }
+    }
+}
@@ -2,18 +2,9 @@ use binaryninja::binary_view::BinaryViewExt;
 use binaryninja::low_level_il::expression::{ExpressionHandler, LowLevelILExpressionKind};
 use binaryninja::low_level_il::instruction::InstructionHandler;
 use binaryninja::low_level_il::VisitorAction;
-use binaryninja::workflow::{Activity, AnalysisContext, Workflow};
+use binaryninja::workflow::{Activity, ActivityConfig, AnalysisContext, Workflow};
 
 const RUST_ACTIVITY_NAME: &str = "analysis.plugins.rustexample";
-const RUST_ACTIVITY_CONFIG: &str = r#"{
-    "name": "analysis.plugins.rustexample",
-    "title" : "Rust Example",
-    "description": "This analysis step logs out some information about the function...",
-    "eligibility": {
-        "auto": { "default": true },
-        "runOnce": false
-    }
-}"#;
 
 fn example_activity(analysis_context: &AnalysisContext) {
     let func = analysis_context.function();
@@ -54,7 +45,11 @@ pub fn main() {
     println!("Registering workflow...");
     let old_meta_workflow = Workflow::instance("core.function.metaAnalysis");
     let meta_workflow = old_meta_workflow.clone("core.function.metaAnalysis");
-    let activity = Activity::new_with_action(RUST_ACTIVITY_CONFIG, example_activity);
+    let config = ActivityConfig::new(RUST_ACTIVITY_NAME)
+        .title("Rust Example")
+        .description("This analysis step logs out some information about the function...")
+        .auto(true);
+    let activity = Activity::new_with_action(config, example_activity);
     meta_workflow.register_activity(&activity).unwrap();
     meta_workflow.insert("core.function.runFunctionRecognizers", [RUST_ACTIVITY_NAME]);
     // Re-register the meta workflow with our changes.
@@ -0,0 +1,175 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Overflow-checked address arithmetic that knows about the segments of a view.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
+use crate::rc::Ref;
+use crate::segment::Segment;
+
+/// An address in a view, with overflow-checked arithmetic that knows about its segments.
+///
+/// Reads past the end of a segment don't fail, they return less data than asked for, or none.
+/// Computing addresses with the checked methods catches these mistakes where the address is
+/// computed:
+///
+/// ```no_run
+/// use binaryninja::address::Addr;
+/// use binaryninja::binary_view::{BinaryViewBase, BinaryViewExt};
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// let header = Addr(view.entry_point());
+/// // The table must lie in the same segment as the header it follows
+/// let table = header.checked_add_within_segment(&view, 0x40).expect("table past segment end");
+/// let entries = table.checked_range_within_segment(&view, 16 * 8).expect("table too long");
+/// let bytes = view.read_vec(entries.start, 16 * 8);
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Addr(pub u64);
+
+impl Addr {
+    pub fn checked_add(self, offset: u64) -> Option<Addr> {
+        self.0.checked_add(offset).map(Addr)
+    }
+
+    pub fn checked_sub(self, offset: u64) -> Option<Addr> {
+        self.0.checked_sub(offset).map(Addr)
+    }
+
+    /// The address `delta` bytes away, forwards or backwards.
+    pub fn checked_offset(self, delta: i64) -> Option<Addr> {
+        self.0.checked_add_signed(delta).map(Addr)
+    }
+
+    /// The number of bytes from `other` to this address, `None` if `other` is after it.
+    pub fn checked_distance_from(self, other: Addr) -> Option<u64> {
+        self.0.checked_sub(other.0)
+    }
+
+    /// The segment containing this address.
+    pub fn segment(self, view: &BinaryView) -> Option<Ref<Segment>> {
+        view.segment_at(self.0)
+    }
+
+    /// The range of the segment containing this address, or of the whole view if it has no
+    /// segments.
+    pub fn segment_range(self, view: &BinaryView) -> Option<Range<u64>> {
+        match self.segment(view) {
+            Some(segment) => Some(segment.address_range()),
+            None if view.segments().is_empty() => {
+                let range = view.start()..view.end();
+                range.contains(&self.0).then_some(range)
+            }
+            None => None,
+        }
+    }
+
+    /// The address `offset` bytes after this one, if both are in the same segment.
+    pub fn checked_add_within_segment(self, view: &BinaryView, offset: u64) -> Option<Addr> {
+        add_within(&self.segment_range(view)?, self.0, offset).map(Addr)
+    }
+
+    /// The address `delta` bytes away from this one, if both are in the same segment.
+    pub fn checked_offset_within_segment(self, view: &BinaryView, delta: i64) -> Option<Addr> {
+        offset_within(&self.segment_range(view)?, self.0, delta).map(Addr)
+    }
+
+    /// The `len` bytes starting at this address, if they all lie in its segment.
+    pub fn checked_range_within_segment(self, view: &BinaryView, len: u64) -> Option<Range<u64>> {
+        range_within(&self.segment_range(view)?, self.0, len)
+    }
+}
+
+impl From<u64> for Addr {
+    fn from(addr: u64) -> Self {
+        Addr(addr)
+    }
+}
+
+impl From<Addr> for u64 {
+    fn from(addr: Addr) -> Self {
+        addr.0
+    }
+}
+
+impl fmt::Display for Addr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+impl fmt::LowerHex for Addr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl fmt::UpperHex for Addr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::UpperHex::fmt(&self.0, f)
+    }
+}
+
+fn add_within(range: &Range<u64>, addr: u64, offset: u64) -> Option<u64> {
+    let result = addr.checked_add(offset)?;
+    (range.contains(&addr) && range.contains(&result)).then_some(result)
+}
+
+fn offset_within(range: &Range<u64>, addr: u64, delta: i64) -> Option<u64> {
+    let result = addr.checked_add_signed(delta)?;
+    (range.contains(&addr) && range.contains(&result)).then_some(result)
+}
+
+/// The `len` bytes at `addr`, if they are all in `range`.
+fn range_within(range: &Range<u64>, addr: u64, len: u64) -> Option<Range<u64>> {
+    let end = addr.checked_add(len)?;
+    (range.contains(&addr) && end <= range.end).then_some(addr..end)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stays_within_range() {
+        let segment = 0x1000..0x2000;
+        assert_eq!(add_within(&segment, 0x1000, 0xfff), Some(0x1fff));
+        assert_eq!(add_within(&segment, 0x1000, 0x1000), None);
+        assert_eq!(add_within(&segment, 0x800, 0x900), None);
+        assert_eq!(add_within(&segment, 0x1000, u64::MAX), None);
+
+        assert_eq!(offset_within(&segment, 0x1800, -0x800), Some(0x1000));
+        assert_eq!(offset_within(&segment, 0x1800, -0x801), None);
+        assert_eq!(offset_within(&segment, 0x1800, 0x7ff), Some(0x1fff));
+        assert_eq!(offset_within(&segment, 0x1800, i64::MIN), None);
+
+        assert_eq!(range_within(&segment, 0x1ff0, 0x10), Some(0x1ff0..0x2000));
+        assert_eq!(range_within(&segment, 0x1ff0, 0x11), None);
+        assert_eq!(range_within(&segment, 0x2000, 0), None);
+        assert_eq!(range_within(&segment, 0x1000, u64::MAX), None);
+    }
+
+    #[test]
+    fn checks_overflow() {
+        assert_eq!(Addr(u64::MAX).checked_add(1), None);
+        assert_eq!(Addr(0).checked_sub(1), None);
+        assert_eq!(Addr(0x10).checked_offset(-0x10), Some(Addr(0)));
+        assert_eq!(Addr(0x10).checked_distance_from(Addr(0x4)), Some(0xc));
+        assert_eq!(Addr(0x4).checked_distance_from(Addr(0x10)), None);
+        assert_eq!(Addr(0x1234).to_string(), "0x1234");
+    }
+}
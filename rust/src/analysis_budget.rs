@@ -0,0 +1,404 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Time and memory budgets for analysis passes.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock, Weak};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+/// How often the watchdog checks running passes against their budgets.
+const WATCHDOG_INTERVAL: Duration = Duration::from_millis(25);
+
+type ExceededCallback = dyn Fn(&BudgetExceeded) + Send + Sync;
+
+/// Limits on the resources a single run of a pass may use.
+///
+/// A run is watched from a background thread. When it goes over one of its limits a warning is
+/// logged and the `on_exceeded` callback is called, even if the pass never returns. The pass
+/// itself is expected to stop early once its [`BudgetToken`] reports the budget as exceeded.
+///
+/// With no limits set, passes are still timed but never cancelled.
+#[derive(Clone, Default)]
+pub struct AnalysisBudget {
+    time_limit: Option<Duration>,
+    memory_limit: Option<u64>,
+    on_exceeded: Option<Arc<ExceededCallback>>,
+}
+
+impl AnalysisBudget {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Wall-clock time a single run may take.
+    pub fn with_time_limit(mut self, limit: Duration) -> Self {
+        self.time_limit = Some(limit);
+        self
+    }
+
+    /// Growth in bytes of the resident memory of the process a single run may cause.
+    ///
+    /// Memory is measured for the whole process, so passes running at the same time are charged
+    /// for each other's allocations. Only supported on Linux, ignored elsewhere.
+    pub fn with_memory_limit(mut self, bytes: u64) -> Self {
+        self.memory_limit = Some(bytes);
+        self
+    }
+
+    /// Called once, from the watchdog thread, when a run goes over budget.
+    ///
+    /// Use this to ask the pass to stop through some other channel, or to abort analysis
+    /// altogether with [`crate::binary_view::BinaryViewExt::abort_analysis`].
+    pub fn on_exceeded<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&BudgetExceeded) + Send + Sync + 'static,
+    {
+        self.on_exceeded = Some(Arc::new(callback));
+        self
+    }
+
+    pub fn time_limit(&self) -> Option<Duration> {
+        self.time_limit
+    }
+
+    pub fn memory_limit(&self) -> Option<u64> {
+        self.memory_limit
+    }
+
+    /// Start watching a run of the pass called `name`, until the returned guard is dropped.
+    pub fn start(&self, name: impl Into<String>) -> BudgetGuard {
+        let state = Arc::new(BudgetState {
+            name: name.into(),
+            time_limit: self.time_limit,
+            memory_limit: self.memory_limit,
+            start: Instant::now(),
+            start_memory: self.memory_limit.and_then(|_| resident_memory()),
+            on_exceeded: self.on_exceeded.clone(),
+            exceeded: OnceLock::new(),
+            finished: AtomicBool::new(false),
+        });
+        if self.time_limit.is_some() || self.memory_limit.is_some() {
+            watchdog().watch(&state);
+        }
+        BudgetGuard {
+            token: BudgetToken { state },
+        }
+    }
+
+    /// Run `f` under this budget, see [`AnalysisBudget::start`].
+    pub fn run<T>(&self, name: impl Into<String>, f: impl FnOnce(&BudgetToken) -> T) -> T {
+        let guard = self.start(name);
+        f(guard.token())
+    }
+}
+
+impl fmt::Debug for AnalysisBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnalysisBudget")
+            .field("time_limit", &self.time_limit)
+            .field("memory_limit", &self.memory_limit)
+            .field("on_exceeded", &self.on_exceeded.is_some())
+            .finish()
+    }
+}
+
+/// The limit a run went over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BudgetLimit {
+    Time(Duration),
+    /// Bytes of resident memory growth.
+    Memory(u64),
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("`{name}` exceeded its {} after {elapsed:?}", limit_description(.limit))]
+pub struct BudgetExceeded {
+    /// Name the run was started with.
+    pub name: String,
+    pub limit: BudgetLimit,
+    /// Time since the run started when the limit was found to be exceeded.
+    pub elapsed: Duration,
+}
+
+fn limit_description(limit: &BudgetLimit) -> String {
+    match limit {
+        BudgetLimit::Time(limit) => format!("time limit of {:?}", limit),
+        BudgetLimit::Memory(limit) => format!("memory limit of {} bytes", limit),
+    }
+}
+
+/// Handle a pass uses to check on its budget.
+#[derive(Clone, Debug)]
+pub struct BudgetToken {
+    state: Arc<BudgetState>,
+}
+
+impl BudgetToken {
+    pub fn name(&self) -> &str {
+        &self.state.name
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.state.start.elapsed()
+    }
+
+    /// Time left before the time limit is reached, `None` without a time limit.
+    pub fn remaining_time(&self) -> Option<Duration> {
+        let limit = self.state.time_limit?;
+        Some(limit.saturating_sub(self.elapsed()))
+    }
+
+    /// Whether the run has gone over budget.
+    pub fn is_exceeded(&self) -> bool {
+        self.check().is_err()
+    }
+
+    /// `Err` once the run has gone over budget, at which point the pass should wrap up and return.
+    ///
+    /// The time limit is checked on every call, the memory limit only by the watchdog.
+    pub fn check(&self) -> Result<(), BudgetExceeded> {
+        match self.state.check_time() {
+            Some(exceeded) => Err(exceeded.clone()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A run being watched, which ends when this is dropped.
+#[derive(Debug)]
+pub struct BudgetGuard {
+    token: BudgetToken,
+}
+
+impl BudgetGuard {
+    pub fn token(&self) -> &BudgetToken {
+        &self.token
+    }
+}
+
+impl Drop for BudgetGuard {
+    fn drop(&mut self) {
+        self.token.state.finished.store(true, Ordering::Relaxed);
+        if self.token.state.exceeded.get().is_some() {
+            log::warn!(
+                "`{}` finished {:?} after it started, over its budget",
+                self.token.state.name,
+                self.token.elapsed()
+            );
+        }
+    }
+}
+
+struct BudgetState {
+    name: String,
+    time_limit: Option<Duration>,
+    memory_limit: Option<u64>,
+    start: Instant,
+    start_memory: Option<u64>,
+    on_exceeded: Option<Arc<ExceededCallback>>,
+    /// Set once, by whichever of the pass or the watchdog notices first.
+    exceeded: OnceLock<BudgetExceeded>,
+    finished: AtomicBool,
+}
+
+impl BudgetState {
+    fn check_time(&self) -> Option<&BudgetExceeded> {
+        if let Some(exceeded) = self.exceeded.get() {
+            return Some(exceeded);
+        }
+        let limit = self.time_limit?;
+        let elapsed = self.start.elapsed();
+        if elapsed <= limit {
+            return None;
+        }
+        Some(self.exceed(BudgetLimit::Time(limit), elapsed))
+    }
+
+    fn check_memory(&self, current_memory: Option<u64>) -> Option<&BudgetExceeded> {
+        let limit = self.memory_limit?;
+        let growth = current_memory?.saturating_sub(self.start_memory?);
+        if growth <= limit {
+            return None;
+        }
+        Some(self.exceed(BudgetLimit::Memory(limit), self.start.elapsed()))
+    }
+
+    fn exceed(&self, limit: BudgetLimit, elapsed: Duration) -> &BudgetExceeded {
+        self.exceeded.get_or_init(|| BudgetExceeded {
+            name: self.name.clone(),
+            limit,
+            elapsed,
+        })
+    }
+}
+
+impl fmt::Debug for BudgetState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BudgetState")
+            .field("name", &self.name)
+            .field("time_limit", &self.time_limit)
+            .field("memory_limit", &self.memory_limit)
+            .field("start", &self.start)
+            .field("exceeded", &self.exceeded.get())
+            .finish()
+    }
+}
+
+/// Background thread checking all running budgets, started on first use.
+struct Watchdog {
+    watched: Mutex<Vec<Weak<BudgetState>>>,
+    wakeup: Condvar,
+}
+
+fn watchdog() -> &'static Watchdog {
+    static WATCHDOG: OnceLock<&'static Watchdog> = OnceLock::new();
+    WATCHDOG.get_or_init(|| {
+        let watchdog: &'static Watchdog = Box::leak(Box::new(Watchdog {
+            watched: Mutex::new(Vec::new()),
+            wakeup: Condvar::new(),
+        }));
+        thread::Builder::new()
+            .name("analysis budget watchdog".to_string())
+            .spawn(move || watchdog.run())
+            .expect("failed to start the analysis budget watchdog");
+        watchdog
+    })
+}
+
+impl Watchdog {
+    fn watch(&self, state: &Arc<BudgetState>) {
+        self.watched.lock().unwrap().push(Arc::downgrade(state));
+        self.wakeup.notify_one();
+    }
+
+    fn run(&self) {
+        let mut watched = self.watched.lock().unwrap();
+        loop {
+            watched = match watched.is_empty() {
+                true => self.wakeup.wait(watched).unwrap(),
+                false => {
+                    self.wakeup
+                        .wait_timeout(watched, WATCHDOG_INTERVAL)
+                        .unwrap()
+                        .0
+                }
+            };
+
+            let current_memory = resident_memory();
+            let mut newly_exceeded = Vec::new();
+            watched.retain(|state| {
+                let Some(state) = state.upgrade() else {
+                    return false;
+                };
+                if state.finished.load(Ordering::Relaxed) {
+                    return false;
+                }
+                // The pass may have noticed first, the callback is still called from here
+                let exceeded = state
+                    .check_time()
+                    .or_else(|| state.check_memory(current_memory));
+                match exceeded {
+                    Some(exceeded) => {
+                        newly_exceeded.push((state.clone(), exceeded.clone()));
+                        false
+                    }
+                    None => true,
+                }
+            });
+
+            // Callbacks run without the lock so they can start budgets of their own
+            drop(watched);
+            for (state, exceeded) in newly_exceeded {
+                log::warn!("{}, cancelling it", exceeded);
+                if let Some(callback) = &state.on_exceeded {
+                    callback(&exceeded);
+                }
+            }
+            watched = self.watched.lock().unwrap();
+        }
+    }
+}
+
+/// Resident memory of the process in bytes, where supported.
+fn resident_memory() -> Option<u64> {
+    #[cfg(target_os = "linux")]
+    {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        parse_vm_rss(&status)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        None
+    }
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_vm_rss(status: &str) -> Option<u64> {
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    let kilobytes: u64 = line["VmRSS:".len()..]
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()?;
+    Some(kilobytes * 1024)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn time_limit_is_enforced() {
+        let (sender, receiver) = mpsc::channel();
+        let sender = Mutex::new(sender);
+        let budget = AnalysisBudget::new()
+            .with_time_limit(Duration::from_millis(20))
+            .on_exceeded(move |exceeded| sender.lock().unwrap().send(exceeded.clone()).unwrap());
+
+        let exceeded = budget.run("slow pass", |token| {
+            assert!(token.check().is_ok());
+            while token.check().is_ok() {
+                thread::sleep(Duration::from_millis(5));
+            }
+            // Stay running until the watchdog has called back, it skips finished runs
+            let reported = receiver.recv_timeout(Duration::from_secs(30)).unwrap();
+            assert_eq!(reported.name, "slow pass");
+            token.check().unwrap_err()
+        });
+        assert_eq!(exceeded.limit, BudgetLimit::Time(Duration::from_millis(20)));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn unlimited_budget_never_exceeds() {
+        let budget = AnalysisBudget::new();
+        let guard = budget.start("fast pass");
+        assert!(guard.token().check().is_ok());
+        assert_eq!(guard.token().remaining_time(), None);
+    }
+
+    #[test]
+    fn parses_resident_memory() {
+        let status = "Name:\tcat\nVmPeak:\t  9000 kB\nVmRSS:\t    1234 kB\nThreads:\t1\n";
+        assert_eq!(parse_vm_rss(status), Some(1234 * 1024));
+        assert_eq!(parse_vm_rss("Name:\tcat\n"), None);
+    }
+}
@@ -0,0 +1,143 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Callbacks and futures for the completion of analysis, without blocking a thread on it.
+
+use std::ffi::c_void;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+use binaryninjacore_sys::{
+    BNAddAnalysisCompletionEvent, BNAnalysisCompletionEvent, BNCancelAnalysisCompletionEvent,
+    BNFreeAnalysisCompletionEvent,
+};
+
+use crate::binary_view::BinaryView;
+use crate::rc::Ref;
+
+type Callback = Box<dyn FnOnce(&BinaryView) + Send>;
+
+/// The callback of an event and the view it is passed, taken by whichever of firing and
+/// cancelling happens first.
+type PendingCallback = Mutex<Option<(Ref<BinaryView>, Callback)>>;
+
+/// A callback run once, the next time analysis of a view completes.
+///
+/// Dropping the event cancels the callback if it has not run yet.
+#[must_use = "dropping the event cancels the callback"]
+pub struct AnalysisCompletionEvent {
+    handle: *mut BNAnalysisCompletionEvent,
+    pending: Arc<PendingCallback>,
+}
+
+unsafe impl Send for AnalysisCompletionEvent {}
+unsafe impl Sync for AnalysisCompletionEvent {}
+
+impl AnalysisCompletionEvent {
+    pub fn new<F>(view: &BinaryView, callback: F) -> Self
+    where
+        F: FnOnce(&BinaryView) + Send + 'static,
+    {
+        unsafe extern "C" fn on_complete(ctxt: *mut c_void) {
+            ffi_wrap!("AnalysisCompletionEvent::on_complete", {
+                // Completion events fire once, so the context reference is released here
+                let pending = unsafe { Arc::from_raw(ctxt as *const PendingCallback) };
+                let taken = pending.lock().unwrap().take();
+                if let Some((view, callback)) = taken {
+                    callback(&view);
+                }
+            })
+        }
+
+        let pending: Arc<PendingCallback> =
+            Arc::new(Mutex::new(Some((view.to_owned(), Box::new(callback)))));
+        let ctxt = Arc::into_raw(pending.clone());
+        let handle = unsafe {
+            BNAddAnalysisCompletionEvent(view.handle, ctxt as *mut c_void, Some(on_complete))
+        };
+        Self { handle, pending }
+    }
+
+    /// Whether the callback has run or been cancelled.
+    pub fn is_finished(&self) -> bool {
+        self.pending.lock().unwrap().is_none()
+    }
+
+    /// Cancel the callback if it has not run yet.
+    pub fn cancel(&self) {
+        if !self.handle.is_null() {
+            unsafe { BNCancelAnalysisCompletionEvent(self.handle) };
+        }
+        // NOTE: The context reference of a cancelled event is leaked, only the callback is freed.
+        self.pending.lock().unwrap().take();
+    }
+}
+
+impl Drop for AnalysisCompletionEvent {
+    fn drop(&mut self) {
+        self.cancel();
+        if !self.handle.is_null() {
+            unsafe { BNFreeAnalysisCompletionEvent(self.handle) };
+        }
+    }
+}
+
+#[derive(Default)]
+struct CompletionState {
+    complete: bool,
+    waker: Option<Waker>,
+}
+
+/// A future resolving the next time analysis of a view completes, see
+/// [`BinaryViewExt::update_analysis_async`](crate::binary_view::BinaryViewExt::update_analysis_async).
+///
+/// It does not depend on any particular async runtime. Dropping it cancels the wait.
+#[must_use = "futures do nothing unless polled"]
+pub struct AnalysisCompletion {
+    state: Arc<Mutex<CompletionState>>,
+    _event: AnalysisCompletionEvent,
+}
+
+impl AnalysisCompletion {
+    pub fn new(view: &BinaryView) -> Self {
+        let state = Arc::new(Mutex::new(CompletionState::default()));
+        let event_state = state.clone();
+        let event = AnalysisCompletionEvent::new(view, move |_| {
+            let mut state = event_state.lock().unwrap();
+            state.complete = true;
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        });
+        Self {
+            state,
+            _event: event,
+        }
+    }
+}
+
+impl Future for AnalysisCompletion {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.complete {
+            return Poll::Ready(());
+        }
+        state.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
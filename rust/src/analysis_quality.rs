@@ -0,0 +1,285 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Quality metrics computed once analysis of a view completes.
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::{Once, RwLock};
+
+use crate::binary_view::{
+    register_binary_view_event, BinaryView, BinaryViewBase, BinaryViewEventHandler,
+    BinaryViewEventType, BinaryViewExt,
+};
+use crate::metadata::Metadata;
+use crate::rc::Ref;
+
+/// View metadata key the metrics are stored under, by metric name.
+pub const QUALITY_METADATA_KEY: &str = "analysis_quality";
+
+/// A single measurement of how well a view was analyzed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QualityMetric {
+    /// Unique name of the metric, a metric with the same name replaces the previous value.
+    pub name: String,
+    pub value: f64,
+    /// Whether the value is bad enough that the analysis should be reviewed by a human.
+    pub needs_review: bool,
+}
+
+impl QualityMetric {
+    pub fn new(name: impl Into<String>, value: f64, needs_review: bool) -> Self {
+        Self {
+            name: name.into(),
+            value,
+            needs_review,
+        }
+    }
+
+    fn to_metadata(&self) -> Ref<Metadata> {
+        let entries: HashMap<&str, Ref<Metadata>> = HashMap::from([
+            ("value", self.value.into()),
+            ("needs_review", self.needs_review.into()),
+        ]);
+        entries.into()
+    }
+
+    fn from_metadata(name: String, metadata: &Metadata) -> Option<Self> {
+        let entries = HashMap::<String, Ref<Metadata>>::try_from(metadata).ok()?;
+        Some(Self {
+            name,
+            value: entries.get("value")?.get_double().ok()?,
+            needs_review: entries.get("needs_review")?.get_boolean().ok()?,
+        })
+    }
+}
+
+/// Computes [`QualityMetric`]s for a view after its analysis completed.
+///
+/// Register validators with [`register_analysis_validator`]. Closures taking a view and returning
+/// the metrics are validators too.
+pub trait AnalysisValidator: 'static + Send + Sync {
+    fn validate(&self, view: &BinaryView) -> Vec<QualityMetric>;
+}
+
+impl<T> AnalysisValidator for T
+where
+    T: 'static + Send + Sync + Fn(&BinaryView) -> Vec<QualityMetric>,
+{
+    fn validate(&self, view: &BinaryView) -> Vec<QualityMetric> {
+        self(view)
+    }
+}
+
+static VALIDATORS: RwLock<Vec<Box<dyn AnalysisValidator>>> = RwLock::new(Vec::new());
+static REGISTER_EVENT: Once = Once::new();
+
+struct ValidatorEventHandler;
+
+impl BinaryViewEventHandler for ValidatorEventHandler {
+    fn on_event(&self, binary_view: &BinaryView) {
+        validate_analysis(binary_view);
+    }
+}
+
+/// Registers a validator which is run for every view once its initial analysis completes.
+///
+/// The metrics are stored in the view metadata and reported by [`BinaryViewExt::analysis_info`],
+/// so batch pipelines can flag analyses that need a human to look at them:
+///
+/// ```no_run
+/// use binaryninja::analysis_quality::{
+///     register_analysis_validator, FunctionCoverage, UnresolvedBranches,
+/// };
+/// use binaryninja::binary_view::BinaryViewExt;
+///
+/// register_analysis_validator(FunctionCoverage { min_ratio: 0.5 });
+/// register_analysis_validator(UnresolvedBranches { max_count: 10 });
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// let info = view.analysis_info().unwrap();
+/// if info.needs_review() {
+///     for metric in info.quality_metrics.iter().filter(|m| m.needs_review) {
+///         println!("{}: {}", metric.name, metric.value);
+///     }
+/// }
+/// ```
+pub fn register_analysis_validator<V: AnalysisValidator>(validator: V) {
+    VALIDATORS.write().unwrap().push(Box::new(validator));
+    REGISTER_EVENT.call_once(|| {
+        register_binary_view_event(
+            BinaryViewEventType::BinaryViewInitialAnalysisCompletionEvent,
+            ValidatorEventHandler,
+        )
+    });
+}
+
+/// Runs all registered validators on `view` and stores their metrics, replacing earlier values
+/// of the same metrics.
+///
+/// Validators only run on their own after the initial analysis, call this to update the metrics
+/// after later changes to the view.
+pub fn validate_analysis(view: &BinaryView) -> Vec<QualityMetric> {
+    let metrics: Vec<QualityMetric> = VALIDATORS
+        .read()
+        .unwrap()
+        .iter()
+        .flat_map(|validator| validator.validate(view))
+        .collect();
+    if metrics.is_empty() {
+        return metrics;
+    }
+
+    let mut stored: HashMap<String, Ref<Metadata>> = view
+        .get_metadata::<HashMap<String, Ref<Metadata>>, _>(QUALITY_METADATA_KEY)
+        .and_then(|stored| stored.ok())
+        .unwrap_or_default();
+    for metric in &metrics {
+        stored.insert(metric.name.clone(), metric.to_metadata());
+    }
+    view.store_metadata(QUALITY_METADATA_KEY, stored, true);
+    metrics
+}
+
+/// The metrics stored for `view`, sorted by name.
+pub fn quality_metrics(view: &BinaryView) -> Vec<QualityMetric> {
+    let Some(Ok(stored)) =
+        view.get_metadata::<HashMap<String, Ref<Metadata>>, _>(QUALITY_METADATA_KEY)
+    else {
+        return vec![];
+    };
+    let mut metrics: Vec<QualityMetric> = stored
+        .into_iter()
+        .filter_map(|(name, metadata)| QualityMetric::from_metadata(name, &metadata))
+        .collect();
+    metrics.sort_by(|a, b| a.name.cmp(&b.name));
+    metrics
+}
+
+/// Measures the share of executable bytes that belong to a function, as `functionCoverage`.
+///
+/// A low coverage usually means the entry points or the platform were not detected correctly.
+/// Views without executable segments are measured against all of their bytes.
+pub struct FunctionCoverage {
+    /// Coverage below this ratio, between 0 and 1, is flagged for review.
+    pub min_ratio: f64,
+}
+
+impl AnalysisValidator for FunctionCoverage {
+    fn validate(&self, view: &BinaryView) -> Vec<QualityMetric> {
+        let mut regions: Vec<Range<u64>> = view
+            .segments()
+            .iter()
+            .filter(|segment| segment.executable())
+            .map(|segment| segment.address_range())
+            .collect();
+        if regions.is_empty() {
+            regions.push(view.start()..view.start() + view.len());
+        }
+        let functions: Vec<Range<u64>> = view
+            .functions()
+            .iter()
+            .flat_map(|function| {
+                function
+                    .address_ranges()
+                    .iter()
+                    .map(|range| range.start..range.end)
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        let total: u64 = merge_ranges(&regions).iter().map(|r| r.end - r.start).sum();
+        let ratio = covered_bytes(&functions, &regions) as f64 / total.max(1) as f64;
+        vec![QualityMetric::new(
+            "functionCoverage",
+            ratio,
+            ratio < self.min_ratio,
+        )]
+    }
+}
+
+/// Counts the indirect branches analysis could not resolve a target for, as
+/// `unresolvedIndirectBranches`.
+pub struct UnresolvedBranches {
+    /// More unresolved branches than this are flagged for review.
+    pub max_count: usize,
+}
+
+impl AnalysisValidator for UnresolvedBranches {
+    fn validate(&self, view: &BinaryView) -> Vec<QualityMetric> {
+        let count: usize = view
+            .functions()
+            .iter()
+            .filter(|function| function.has_unresolved_indirect_branches())
+            .map(|function| function.unresolved_indirect_branches().len())
+            .sum();
+        vec![QualityMetric::new(
+            "unresolvedIndirectBranches",
+            count as f64,
+            count > self.max_count,
+        )]
+    }
+}
+
+/// Number of bytes of the union of `ranges` that lie within the union of `regions`.
+fn covered_bytes(ranges: &[Range<u64>], regions: &[Range<u64>]) -> u64 {
+    let ranges = merge_ranges(ranges);
+    let regions = merge_ranges(regions);
+    let mut covered = 0;
+    for range in &ranges {
+        for region in &regions {
+            let start = range.start.max(region.start);
+            let end = range.end.min(region.end);
+            covered += end.saturating_sub(start);
+        }
+    }
+    covered
+}
+
+pub(crate) fn merge_ranges(ranges: &[Range<u64>]) -> Vec<Range<u64>> {
+    let mut sorted: Vec<Range<u64>> = ranges.iter().filter(|r| !r.is_empty()).cloned().collect();
+    sorted.sort_by_key(|r| r.start);
+    let mut merged: Vec<Range<u64>> = Vec::with_capacity(sorted.len());
+    for range in sorted {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn merges_overlapping_ranges() {
+        let merged = merge_ranges(&[10..20, 0..5, 15..30, 5..8, 40..40]);
+        assert_eq!(merged, vec![0..8, 10..30]);
+    }
+
+    #[test]
+    fn counts_bytes_within_regions() {
+        // Two functions sharing a block, one partially outside the executable regions
+        let functions = [
+            0x1000..0x1100,
+            0x1080..0x1200,
+            0x1f00..0x2100,
+            0x3000..0x3010,
+        ];
+        let regions = [0x1000..0x2000, 0x3000..0x4000];
+        assert_eq!(covered_bytes(&functions, &regions), 0x200 + 0x100 + 0x10);
+    }
+}
@@ -29,23 +29,24 @@ use crate::{
     relocation::CoreRelocationHandler,
     string::BnStrCompatible,
     string::*,
-    types::{NameAndType, Type},
+    types::{FunctionParameter, MemberAccess, MemberScope, NameAndType, StructureBuilder, Type},
     Endianness,
 };
 use std::ops::Deref;
 use std::{
     borrow::{Borrow, Cow},
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     ffi::{c_char, c_int, c_void, CStr, CString},
     fmt::Display,
     hash::Hash,
     mem::MaybeUninit,
+    sync::Mutex,
 };
 
 use crate::function_recognizer::FunctionRecognizer;
 use crate::relocation::{CustomRelocationHandlerHandle, RelocationHandler};
 
-use crate::confidence::Conf;
+use crate::confidence::{Conf, MAX_CONFIDENCE};
 use crate::low_level_il::expression::ValueExpr;
 use crate::low_level_il::lifting::{
     get_default_flag_cond_llil, get_default_flag_write_llil, LowLevelILFlagWriteOp,
@@ -1943,6 +1944,100 @@ macro_rules! cc_func {
     };
 }
 
+/// The layout of a register, see [`ArchitectureExt::register_descriptions`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RegisterDescription {
+    pub id: RegisterId,
+    pub name: String,
+    /// Size of the register in bytes.
+    pub size: usize,
+    /// Offset in bytes of the register within its full width register.
+    pub offset: usize,
+    /// The full width register containing this register, its own id if it is full width.
+    pub full_width: RegisterId,
+    pub implicit_extend: ImplicitRegisterExtend,
+    pub global: bool,
+    pub system: bool,
+}
+
+impl RegisterDescription {
+    pub fn is_full_width(&self) -> bool {
+        self.full_width == self.id
+    }
+}
+
+/// A flag and its role when written without a flag class, see
+/// [`ArchitectureExt::flag_descriptions`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FlagDescription {
+    pub id: FlagId,
+    pub name: String,
+    pub role: FlagRole,
+}
+
+/// The registers backing a register stack, see [`ArchitectureExt::register_stack_descriptions`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RegisterStackDescription {
+    pub id: RegisterStackId,
+    pub name: String,
+    /// The registers holding the stack entries.
+    pub storage_registers: Vec<RegisterId>,
+    /// The registers naming the entries relative to the top of the stack, such as `st0`.
+    pub top_relative_registers: Vec<RegisterId>,
+    /// The register holding the index of the top of the stack.
+    pub stack_top: RegisterId,
+}
+
+/// An intrinsic and its signature, see [`ArchitectureExt::intrinsic_descriptions`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntrinsicDescription {
+    pub id: IntrinsicId,
+    pub name: String,
+    pub class: BNIntrinsicClass,
+    pub inputs: Vec<NameAndType>,
+    pub outputs: Vec<Conf<Ref<Type>>>,
+}
+
+impl IntrinsicDescription {
+    /// A function type with the inputs of the intrinsic as parameters.
+    ///
+    /// Intrinsics with several outputs return a structure with one `outN` member per output.
+    pub fn prototype(&self) -> Ref<Type> {
+        let parameters = self
+            .inputs
+            .iter()
+            .map(|input| FunctionParameter::new(input.ty.clone(), input.name.clone(), None))
+            .collect();
+        let return_type = match self.outputs.as_slice() {
+            [] => Conf::new(Type::void(), MAX_CONFIDENCE),
+            [output] => output.clone(),
+            outputs => {
+                let mut builder = StructureBuilder::new();
+                for (i, output) in outputs.iter().enumerate() {
+                    builder.append(
+                        output,
+                        format!("out{i}"),
+                        MemberAccess::PublicAccess,
+                        MemberScope::NoScope,
+                    );
+                }
+                Conf::new(Type::structure(&builder.finalize()), MAX_CONFIDENCE)
+            }
+        };
+        Type::function(&return_type, parameters, false)
+    }
+}
+
+/// The intrinsic prototypes registered with [`ArchitectureExt::register_intrinsic_prototype`],
+/// by architecture and intrinsic name.
+static INTRINSIC_PROTOTYPES: Mutex<BTreeMap<(String, String), Ref<Type>>> =
+    Mutex::new(BTreeMap::new());
+
+fn registered_intrinsic_prototype(arch: &CoreArchitecture, name: &str) -> Option<Ref<Type>> {
+    let key = (arch.name().to_string(), name.to_string());
+    INTRINSIC_PROTOTYPES.lock().unwrap().get(&key).cloned()
+}
+
 /// Contains helper methods for all types implementing 'Architecture'
 pub trait ArchitectureExt: Architecture {
     fn register_by_name<S: BnStrCompatible>(&self, name: S) -> Option<Self::Register> {
@@ -1956,6 +2051,123 @@ pub trait ArchitectureExt: Architecture {
         }
     }
 
+    /// The layout of every register of the architecture, sub-registers included.
+    fn register_descriptions(&self) -> Vec<RegisterDescription> {
+        let global: Vec<RegisterId> = self.registers_global().iter().map(|r| r.id()).collect();
+        let system: Vec<RegisterId> = self.registers_system().iter().map(|r| r.id()).collect();
+        self.registers_all()
+            .iter()
+            .map(|reg| {
+                let info = reg.info();
+                RegisterDescription {
+                    id: reg.id(),
+                    name: reg.name().into_owned(),
+                    size: info.size(),
+                    offset: info.offset(),
+                    full_width: info.parent().map_or(reg.id(), |parent| parent.id()),
+                    implicit_extend: info.implicit_extend(),
+                    global: global.contains(&reg.id()),
+                    system: system.contains(&reg.id()),
+                }
+            })
+            .collect()
+    }
+
+    /// The flags of the architecture.
+    fn flag_descriptions(&self) -> Vec<FlagDescription> {
+        self.flags()
+            .iter()
+            .map(|flag| FlagDescription {
+                id: flag.id(),
+                name: flag.name().into_owned(),
+                role: flag.role(None),
+            })
+            .collect()
+    }
+
+    /// The layout of the system registers of the architecture, such as the ARM registers
+    /// accessed with `MRS` and `MSR`.
+    fn system_register_descriptions(&self) -> Vec<RegisterDescription> {
+        self.register_descriptions()
+            .into_iter()
+            .filter(|reg| reg.system)
+            .collect()
+    }
+
+    /// The intrinsics the lifter of the architecture uses for instructions without an IL
+    /// equivalent.
+    fn intrinsic_descriptions(&self) -> Vec<IntrinsicDescription> {
+        self.intrinsics()
+            .iter()
+            .map(|intrinsic| IntrinsicDescription {
+                id: intrinsic.id(),
+                name: intrinsic.name().into_owned(),
+                class: intrinsic.class(),
+                inputs: intrinsic.inputs(),
+                outputs: intrinsic.outputs(),
+            })
+            .collect()
+    }
+
+    fn intrinsic_by_name(&self, name: &str) -> Option<Self::Intrinsic> {
+        self.intrinsics()
+            .into_iter()
+            .find(|intrinsic| intrinsic.name() == name)
+    }
+
+    /// Registers `prototype` as the function type of the intrinsic `name`, overriding the one
+    /// derived from its inputs and outputs by [`Self::intrinsic_prototype`].
+    ///
+    /// For architectures implemented in Rust, see [`register_architecture`], the core is given the
+    /// parameters of the prototype as the inputs of the intrinsic and its return type, unless it is
+    /// `void`, as its output. Architectures implemented in the core keep their own types, only
+    /// [`Self::intrinsic_prototype`] returns the registered prototype for them.
+    fn register_intrinsic_prototype(&self, name: &str, prototype: &Type) {
+        let key = (self.as_ref().name().to_string(), name.to_string());
+        INTRINSIC_PROTOTYPES
+            .lock()
+            .unwrap()
+            .insert(key, prototype.to_owned());
+    }
+
+    /// The function type of the intrinsic `name`, the one registered with
+    /// [`Self::register_intrinsic_prototype`] if any, else
+    /// [`IntrinsicDescription::prototype`].
+    fn intrinsic_prototype(&self, name: &str) -> Option<Ref<Type>> {
+        if let Some(prototype) = registered_intrinsic_prototype(self.as_ref(), name) {
+            return Some(prototype);
+        }
+        self.intrinsic_descriptions()
+            .into_iter()
+            .find(|intrinsic| intrinsic.name == name)
+            .map(|intrinsic| intrinsic.prototype())
+    }
+
+    /// The register stacks of the architecture, such as the x87 floating point stack.
+    fn register_stack_descriptions(&self) -> Vec<RegisterStackDescription> {
+        let reg_range = |(first, count): (Self::Register, usize)| {
+            (0..count as u32)
+                .map(|i| RegisterId(first.id().0 + i))
+                .collect::<Vec<_>>()
+        };
+        self.register_stacks()
+            .iter()
+            .map(|stack| {
+                let info = stack.info();
+                RegisterStackDescription {
+                    id: stack.id(),
+                    name: stack.name().into_owned(),
+                    storage_registers: reg_range(info.storage_regs()),
+                    top_relative_registers: info
+                        .top_relative_regs()
+                        .map(reg_range)
+                        .unwrap_or_default(),
+                    stack_top: info.stack_top_reg().id(),
+                }
+            })
+            .collect()
+    }
+
     fn calling_conventions(&self) -> Array<CoreCallingConvention> {
         unsafe {
             let mut count = 0;
@@ -2871,7 +3083,17 @@ where
             return std::ptr::null_mut();
         };
 
-        let inputs = intrinsic.inputs();
+        let prototype = registered_intrinsic_prototype(custom_arch.as_ref(), &intrinsic.name());
+        let inputs = match prototype.and_then(|prototype| prototype.parameters()) {
+            Some(parameters) => parameters
+                .into_iter()
+                .map(|parameter| NameAndType {
+                    name: parameter.name,
+                    ty: parameter.ty,
+                })
+                .collect(),
+            None => intrinsic.inputs(),
+        };
         // NOTE: The into_raw will leak and be freed later by `cb_free_name_and_types`.
         let raw_inputs: Box<[_]> = inputs.into_iter().map(NameAndType::into_raw).collect();
 
@@ -2926,7 +3148,16 @@ where
             return std::ptr::null_mut();
         };
 
-        let outputs = intrinsic.outputs();
+        let prototype = registered_intrinsic_prototype(custom_arch.as_ref(), &intrinsic.name());
+        let outputs = match prototype.and_then(|prototype| prototype.return_value()) {
+            Some(return_type)
+                if return_type.contents.type_class() == BNTypeClass::VoidTypeClass =>
+            {
+                vec![]
+            }
+            Some(return_type) => vec![return_type],
+            None => intrinsic.outputs(),
+        };
         let raw_outputs: Box<[BNTypeWithConfidence]> = outputs
             .into_iter()
             // Leaked to be freed later by `cb_free_type_list`.
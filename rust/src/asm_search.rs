@@ -0,0 +1,485 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Search for instruction sequences by their disassembly, using assembly templates with wildcards.
+
+use thiserror::Error;
+
+use crate::architecture::{Architecture, CoreArchitecture};
+use crate::binary_view::{BinaryView, BinaryViewExt};
+use crate::disassembly::{InstructionTextToken, InstructionTextTokenKind};
+use crate::function::Function;
+
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum PatternError {
+    #[error("pattern has no instructions")]
+    Empty,
+    #[error("instruction {0} of the pattern has an empty operand")]
+    EmptyOperand(usize),
+}
+
+/// An instruction split into its mnemonic and operands, as matched by an [`InstructionPattern`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DisassembledInstruction {
+    pub address: u64,
+    pub length: usize,
+    pub mnemonic: String,
+    pub operands: Vec<Operand>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Operand {
+    pub text: String,
+    /// The value of the operand if it is a single integer or address.
+    pub value: Option<u64>,
+}
+
+impl DisassembledInstruction {
+    /// Disassemble the instruction at `address` with `arch`.
+    pub fn disassemble(arch: &CoreArchitecture, view: &BinaryView, address: u64) -> Option<Self> {
+        let data = view.read_vec(address, arch.max_instr_len());
+        let (length, tokens) = arch.instruction_text(&data, address)?;
+        Some(Self::from_tokens(address, length, &tokens))
+    }
+
+    /// Split instruction text into the mnemonic and operands. Separators inside memory operands,
+    /// such as in `[x0, #0x8]`, don't start a new operand.
+    pub fn from_tokens(address: u64, length: usize, tokens: &[InstructionTextToken]) -> Self {
+        let mut mnemonic = None;
+        let mut operands = Vec::new();
+        let mut text = String::new();
+        let mut values = Vec::new();
+        let mut depth = 0usize;
+
+        let mut finish_operand = |text: &mut String, values: &mut Vec<u64>| {
+            let trimmed = text.trim();
+            if !trimmed.is_empty() {
+                operands.push(Operand {
+                    text: trimmed.to_string(),
+                    value: match values.as_slice() {
+                        [value] => Some(*value),
+                        _ => None,
+                    },
+                });
+            }
+            text.clear();
+            values.clear();
+        };
+
+        for token in tokens {
+            match &token.kind {
+                InstructionTextTokenKind::Instruction if mnemonic.is_none() => {
+                    mnemonic = Some(token.text.trim().to_string());
+                    continue;
+                }
+                // Comments and hints are not part of the instruction
+                InstructionTextTokenKind::Annotation | InstructionTextTokenKind::Comment { .. } => {
+                    continue;
+                }
+                _ if mnemonic.is_none() => {
+                    // Architectures that don't mark the mnemonic start with it as text
+                    if !token.text.trim().is_empty() {
+                        mnemonic = Some(token.text.trim().to_string());
+                    }
+                    continue;
+                }
+                InstructionTextTokenKind::OperandSeparator if depth == 0 => {
+                    finish_operand(&mut text, &mut values);
+                    continue;
+                }
+                InstructionTextTokenKind::BeginMemoryOperand => depth += 1,
+                InstructionTextTokenKind::EndMemoryOperand => depth = depth.saturating_sub(1),
+                InstructionTextTokenKind::Integer { value, .. }
+                | InstructionTextTokenKind::PossibleAddress { value, .. }
+                | InstructionTextTokenKind::CodeRelativeAddress { value, .. }
+                | InstructionTextTokenKind::CodeSymbol { value, .. }
+                | InstructionTextTokenKind::DataSymbol { value, .. }
+                | InstructionTextTokenKind::ExternalSymbol { value } => values.push(*value),
+                _ => {}
+            }
+            text.push_str(&token.text);
+        }
+        finish_operand(&mut text, &mut values);
+
+        Self {
+            address,
+            length,
+            mnemonic: mnemonic.unwrap_or_default(),
+            operands,
+        }
+    }
+}
+
+/// Text captured by a `?` of the pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Capture {
+    pub text: String,
+    /// The value of the captured text if it is an integer, or of the whole operand when the `?`
+    /// matched one.
+    pub value: Option<u64>,
+}
+
+/// A site matching an [`InstructionPattern`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PatternMatch {
+    pub address: u64,
+    /// Bytes from `address` to the end of the last matched instruction.
+    pub length: usize,
+    pub arch: CoreArchitecture,
+    pub captures: Vec<Capture>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Piece {
+    Literal(String),
+    Wildcard,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct PatternInstruction {
+    /// `None` matches any mnemonic.
+    mnemonic: Option<String>,
+    operands: Vec<Vec<Piece>>,
+}
+
+/// A sequence of instructions to search for, matched against the disassembly of each architecture
+/// so it still matches when a compiler picks another register or encoding.
+///
+/// A template is a list of instructions separated by `;` or newlines, each a mnemonic followed by
+/// comma separated operands. A `?` operand matches any single operand, and a `?` within an operand,
+/// such as `[rbp-?]`, matches any text. Each `?` captures what it matched, in order. A `?` mnemonic
+/// matches any instruction with the same number of operands. Case and whitespace are ignored.
+///
+/// ```no_run
+/// use binaryninja::asm_search::InstructionPattern;
+///
+/// let bv = binaryninja::load("example").unwrap();
+/// let pattern = InstructionPattern::parse("mov rdi, ?; call ?").unwrap();
+/// for site in pattern.find_in_view(&bv) {
+///     let (argument, target) = (&site.captures[0], &site.captures[1]);
+///     println!("{:#x}: call {} with {}", site.address, target.text, argument.text);
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InstructionPattern {
+    instructions: Vec<PatternInstruction>,
+}
+
+/// Lowercase and drop whitespace, so `DWORD PTR [eax + 4]` and `dword ptr [eax+4]` compare equal.
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+fn parse_integer(text: &str) -> Option<u64> {
+    let text = text.trim_start_matches(['#', '$']);
+    match text.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+/// Split template operands on commas outside of brackets, so `[x0, #?]` stays one operand.
+fn split_operands(operands: &str) -> Vec<&str> {
+    let mut split = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in operands.char_indices() {
+        match c {
+            '[' | '{' | '(' => depth += 1,
+            ']' | '}' | ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                split.push(&operands[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    split.push(&operands[start..]);
+    split
+}
+
+/// Match `text` against `pieces`, pushing the text of each wildcard to `captures`.
+fn match_pieces(pieces: &[Piece], text: &str, captures: &mut Vec<String>) -> bool {
+    match pieces.split_first() {
+        None => text.is_empty(),
+        Some((Piece::Literal(literal), rest)) => text
+            .strip_prefix(literal.as_str())
+            .is_some_and(|text| match_pieces(rest, text, captures)),
+        Some((Piece::Wildcard, rest)) => {
+            // Wildcards match at least one character, as little as possible
+            for (end, _) in text.char_indices().skip(1).chain([(text.len(), ' ')]) {
+                captures.push(text[..end].to_string());
+                if match_pieces(rest, &text[end..], captures) {
+                    return true;
+                }
+                captures.pop();
+            }
+            false
+        }
+    }
+}
+
+impl InstructionPattern {
+    pub fn parse(template: &str) -> Result<Self, PatternError> {
+        let mut instructions = Vec::new();
+        for line in template.split([';', '\n']) {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (mnemonic, operands) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+            let operands = match operands.trim() {
+                "" => Vec::new(),
+                operands => split_operands(operands)
+                    .into_iter()
+                    .map(|operand| {
+                        let operand = normalize(operand);
+                        if operand.is_empty() {
+                            return Err(PatternError::EmptyOperand(instructions.len()));
+                        }
+                        let mut pieces = Vec::new();
+                        for (i, literal) in operand.split('?').enumerate() {
+                            if i > 0 {
+                                pieces.push(Piece::Wildcard);
+                            }
+                            if !literal.is_empty() {
+                                pieces.push(Piece::Literal(literal.to_string()));
+                            }
+                        }
+                        Ok(pieces)
+                    })
+                    .collect::<Result<_, _>>()?,
+            };
+            instructions.push(PatternInstruction {
+                mnemonic: (mnemonic != "?").then(|| normalize(mnemonic)),
+                operands,
+            });
+        }
+        if instructions.is_empty() {
+            return Err(PatternError::Empty);
+        }
+        Ok(Self { instructions })
+    }
+
+    /// The number of instructions the pattern matches.
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    /// Match the pattern against the start of `instructions`, returning the captures.
+    pub fn match_instructions(
+        &self,
+        instructions: &[DisassembledInstruction],
+    ) -> Option<Vec<Capture>> {
+        if instructions.len() < self.instructions.len() {
+            return None;
+        }
+        let mut captures = Vec::new();
+        for (pattern, instr) in self.instructions.iter().zip(instructions) {
+            if pattern
+                .mnemonic
+                .as_ref()
+                .is_some_and(|mnemonic| *mnemonic != normalize(&instr.mnemonic))
+            {
+                return None;
+            }
+            if pattern.operands.len() != instr.operands.len() {
+                return None;
+            }
+            for (pieces, operand) in pattern.operands.iter().zip(&instr.operands) {
+                let mut texts = Vec::new();
+                if !match_pieces(pieces, &normalize(&operand.text), &mut texts) {
+                    return None;
+                }
+                let whole_operand = pieces.as_slice() == [Piece::Wildcard];
+                captures.extend(texts.into_iter().map(|text| Capture {
+                    value: match whole_operand {
+                        true => operand.value.or_else(|| parse_integer(&text)),
+                        false => parse_integer(&text),
+                    },
+                    // Report the operand as disassembled rather than normalized
+                    text: match whole_operand {
+                        true => operand.text.clone(),
+                        false => text,
+                    },
+                }));
+            }
+        }
+        Some(captures)
+    }
+
+    /// Find all sites in `function`. Matches don't span basic blocks.
+    pub fn find_in_function(&self, function: &Function) -> Vec<PatternMatch> {
+        let view = function.view();
+        let mut matches = Vec::new();
+        for block in function.basic_blocks().iter() {
+            let arch = block.arch();
+            let instructions: Vec<DisassembledInstruction> = block
+                .iter()
+                .filter_map(|address| DisassembledInstruction::disassemble(&arch, &view, address))
+                .collect();
+            for start in 0..instructions.len() {
+                let window = &instructions[start..];
+                if let Some(captures) = self.match_instructions(window) {
+                    let last = &window[self.instructions.len() - 1];
+                    matches.push(PatternMatch {
+                        address: window[0].address,
+                        length: (last.address + last.length as u64 - window[0].address) as usize,
+                        arch,
+                        captures,
+                    });
+                }
+            }
+        }
+        matches.sort_by_key(|site| site.address);
+        matches
+    }
+
+    /// Find all sites in the functions of `view`, in address order. Code outside of functions is
+    /// not searched.
+    pub fn find_in_view(&self, view: &BinaryView) -> Vec<PatternMatch> {
+        let mut matches: Vec<PatternMatch> = view
+            .functions()
+            .iter()
+            .flat_map(|function| self.find_in_function(&function))
+            .collect();
+        // Functions can share blocks
+        matches.sort_by_key(|site| site.address);
+        matches.dedup_by(|a, b| a.address == b.address && a.arch == b.arch);
+        matches
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn instruction(
+        address: u64,
+        mnemonic: &str,
+        operands: &[(&str, Option<u64>)],
+    ) -> DisassembledInstruction {
+        DisassembledInstruction {
+            address,
+            length: 4,
+            mnemonic: mnemonic.to_string(),
+            operands: operands
+                .iter()
+                .map(|(text, value)| Operand {
+                    text: text.to_string(),
+                    value: *value,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn pattern_parse() {
+        assert_eq!(InstructionPattern::parse(" ; \n"), Err(PatternError::Empty));
+        assert_eq!(
+            InstructionPattern::parse("nop; mov eax, , ebx"),
+            Err(PatternError::EmptyOperand(1))
+        );
+        let pattern = InstructionPattern::parse("mov rdi, ?\ncall ?; ret").unwrap();
+        assert_eq!(pattern.len(), 3);
+    }
+
+    #[test]
+    fn pattern_match_captures() {
+        let pattern = InstructionPattern::parse("MOV rdi, ?; call ?").unwrap();
+        let instructions = [
+            instruction(0x1000, "mov", &[("rdi", None), ("0x2000", Some(0x2000))]),
+            instruction(0x1004, "call", &[("puts", Some(0x3000))]),
+        ];
+        let captures = pattern.match_instructions(&instructions).unwrap();
+        assert_eq!(
+            captures,
+            [
+                Capture {
+                    text: "0x2000".to_string(),
+                    value: Some(0x2000)
+                },
+                Capture {
+                    text: "puts".to_string(),
+                    value: Some(0x3000)
+                },
+            ]
+        );
+        // Too few instructions, or the wrong register
+        assert!(pattern.match_instructions(&instructions[..1]).is_none());
+        let other = [
+            instruction(0x1000, "mov", &[("rsi", None), ("0x2000", Some(0x2000))]),
+            instructions[1].clone(),
+        ];
+        assert!(pattern.match_instructions(&other).is_none());
+    }
+
+    #[test]
+    fn pattern_match_within_operand() {
+        let pattern = InstructionPattern::parse("? ?, dword ptr [rbp - ?]").unwrap();
+        let instructions = [instruction(
+            0x1000,
+            "mov",
+            &[("eax", None), ("dword [rbp-0x14]", Some(0x14))],
+        )];
+        assert!(pattern.match_instructions(&instructions).is_none());
+        let instructions = [instruction(
+            0x1000,
+            "mov",
+            &[("eax", None), ("DWORD PTR [rbp-0x14]", Some(0x14))],
+        )];
+        let captures = pattern.match_instructions(&instructions).unwrap();
+        assert_eq!(captures[0].text, "eax");
+        assert_eq!(captures[1].text, "0x14");
+        assert_eq!(captures[1].value, Some(0x14));
+    }
+
+    #[test]
+    fn split_tokens() {
+        use InstructionTextTokenKind::*;
+        let tokens = [
+            InstructionTextToken::new("ldr", Instruction),
+            InstructionTextToken::new("     ", Text),
+            InstructionTextToken::new("x1", Register),
+            InstructionTextToken::new(", ", OperandSeparator),
+            InstructionTextToken::new("[", BeginMemoryOperand),
+            InstructionTextToken::new("x0", Register),
+            InstructionTextToken::new(", ", OperandSeparator),
+            InstructionTextToken::new(
+                "#0x8",
+                Integer {
+                    value: 8,
+                    size: None,
+                },
+            ),
+            InstructionTextToken::new("]", EndMemoryOperand),
+        ];
+        let instr = DisassembledInstruction::from_tokens(0x1000, 4, &tokens);
+        assert_eq!(instr.mnemonic, "ldr");
+        assert_eq!(instr.operands.len(), 2);
+        assert_eq!(instr.operands[0].text, "x1");
+        assert_eq!(instr.operands[1].text, "[x0, #0x8]");
+        assert_eq!(instr.operands[1].value, Some(8));
+        let pattern = InstructionPattern::parse("ldr ?, [x0, #?]").unwrap();
+        let captures = pattern.match_instructions(&[instr]).unwrap();
+        assert_eq!(captures[1].value, Some(8));
+    }
+}
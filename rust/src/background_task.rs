@@ -17,9 +17,12 @@
 use binaryninjacore_sys::*;
 
 use std::result;
+use std::sync::Mutex;
 
+use crate::main_thread::execute_on_main_thread;
 use crate::rc::*;
 use crate::string::*;
+use crate::worker_thread::execute_on_worker_thread;
 
 pub type Result<R> = result::Result<R, ()>;
 
@@ -128,3 +131,64 @@ impl ToOwned for BackgroundTask {
 
 unsafe impl Send for BackgroundTask {}
 unsafe impl Sync for BackgroundTask {}
+
+/// Run `job` on a worker thread, tracked by a new [`BackgroundTask`], and hand its result to
+/// `on_complete` on the main thread.
+///
+/// `job` is given the task to report its progress and, if `can_cancel` is set, to poll
+/// [`BackgroundTask::is_cancelled`] and return early. The task is finished right before
+/// `on_complete` runs.
+///
+/// ```no_run
+/// use binaryninja::background_task::spawn_analysis_job;
+///
+/// let task = spawn_analysis_job(
+///     "Counting",
+///     true,
+///     |task| {
+///         let mut count = 0u64;
+///         while count < 1_000_000 && !task.is_cancelled() {
+///             count += 1;
+///             if count % 1000 == 0 {
+///                 task.set_progress_text(format!("Counting ({})", count));
+///             }
+///         }
+///         count
+///     },
+///     |count| log::info!("Counted to {}", count),
+/// );
+/// // Cancelling the task makes the job stop early.
+/// task.cancel();
+/// ```
+pub fn spawn_analysis_job<S, F, T, C>(
+    initial_text: S,
+    can_cancel: bool,
+    job: F,
+    on_complete: C,
+) -> Ref<BackgroundTask>
+where
+    S: BnStrCompatible,
+    F: FnOnce(&BackgroundTask) -> T + Send + 'static,
+    T: Send + 'static,
+    C: FnOnce(T) + Send + 'static,
+{
+    let task = BackgroundTask::new(initial_text, can_cancel);
+    let job_task = task.clone();
+    // The worker and main thread APIs take `Fn`, the closures are only ever taken once
+    let job = Mutex::new(Some((job, on_complete)));
+    execute_on_worker_thread(task.progress_text(), move || {
+        let Some((job, on_complete)) = job.lock().unwrap().take() else {
+            return;
+        };
+        let result = job(&job_task);
+        let task = job_task.clone();
+        let completion = Mutex::new(Some((on_complete, result)));
+        execute_on_main_thread(move || {
+            if let Some((on_complete, result)) = completion.lock().unwrap().take() {
+                task.finish();
+                on_complete(result);
+            }
+        });
+    });
+    task
+}
@@ -13,6 +13,7 @@
 // limitations under the License.
 
 use crate::architecture::CoreArchitecture;
+use crate::disassembly::{DisassemblySettings, DisassemblyTextLine};
 use crate::function::Function;
 use crate::rc::*;
 use crate::BranchType;
@@ -143,6 +144,18 @@ impl<C: BlockContext> BasicBlock<C> {
         unsafe { BNGetBasicBlockLength(self.handle) }
     }
 
+    /// The lines of the block as rendered with `settings`, or with the default settings.
+    pub fn disassembly_text(
+        &self,
+        settings: Option<&DisassemblySettings>,
+    ) -> Array<DisassemblyTextLine> {
+        let settings = settings.map_or(std::ptr::null_mut(), |settings| settings.handle);
+        let mut count = 0;
+        let lines = unsafe { BNGetBasicBlockDisassemblyText(self.handle, settings, &mut count) };
+        assert!(!lines.is_null());
+        unsafe { Array::new(lines, count, ()) }
+    }
+
     pub fn incoming_edges(&self) -> Array<Edge<C>> {
         unsafe {
             let mut count = 0;
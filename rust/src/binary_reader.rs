@@ -18,6 +18,7 @@ use binaryninjacore_sys::*;
 use std::fmt::Debug;
 
 use crate::binary_view::{BinaryView, BinaryViewBase};
+use crate::disassembly::StringType;
 use crate::Endianness;
 
 use crate::rc::Ref;
@@ -171,3 +172,256 @@ impl BinaryReaderOptions {
         self
     }
 }
+
+/// Values that can be decoded from a fixed number of bytes, read with
+/// [`crate::binary_view::BinaryViewExt::read_value`].
+///
+/// Implemented for the primitive integer and float types and arrays of them. Implement it for
+/// your own structures to read them in one go:
+///
+/// ```no_run
+/// use binaryninja::binary_reader::FromBytes;
+/// use binaryninja::binary_view::BinaryViewExt;
+/// use binaryninja::Endianness;
+///
+/// struct Elf32Rel {
+///     offset: u32,
+///     info: u32,
+/// }
+///
+/// impl FromBytes for Elf32Rel {
+///     const SIZE: usize = 8;
+///
+///     fn from_bytes(bytes: &[u8], endianness: Endianness) -> Self {
+///         Self {
+///             offset: u32::from_bytes(&bytes[0..4], endianness),
+///             info: u32::from_bytes(&bytes[4..8], endianness),
+///         }
+///     }
+/// }
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// let rel: Elf32Rel = view.read_value(0x1000).unwrap();
+/// ```
+pub trait FromBytes: Sized {
+    /// Number of bytes the value is decoded from.
+    const SIZE: usize;
+
+    /// Decode the value from the first [`FromBytes::SIZE`] bytes of `bytes`.
+    ///
+    /// Panics if `bytes` is shorter than that.
+    fn from_bytes(bytes: &[u8], endianness: Endianness) -> Self;
+}
+
+macro_rules! from_bytes_impl {
+    ($($t:ty),*) => {
+        $(
+            impl FromBytes for $t {
+                const SIZE: usize = std::mem::size_of::<$t>();
+
+                fn from_bytes(bytes: &[u8], endianness: Endianness) -> Self {
+                    let bytes = bytes[..Self::SIZE].try_into().unwrap();
+                    match endianness {
+                        Endianness::LittleEndian => <$t>::from_le_bytes(bytes),
+                        Endianness::BigEndian => <$t>::from_be_bytes(bytes),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+from_bytes_impl!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128, f32, f64);
+
+impl<T: FromBytes, const N: usize> FromBytes for [T; N] {
+    const SIZE: usize = T::SIZE * N;
+
+    fn from_bytes(bytes: &[u8], endianness: Endianness) -> Self {
+        std::array::from_fn(|i| T::from_bytes(&bytes[i * T::SIZE..], endianness))
+    }
+}
+
+/// Decode an unsigned LEB128 value, returning it and the number of bytes it took.
+///
+/// `None` if `bytes` ends before the value does or the value does not fit in 64 bits.
+pub(crate) fn decode_uleb128(bytes: &[u8]) -> Option<(u64, usize)> {
+    let mut value = 0u64;
+    for (i, byte) in bytes.iter().enumerate() {
+        let shift = i * 7;
+        let bits = (byte & 0x7f) as u64;
+        if shift >= 64 || (shift > 57 && bits >> (64 - shift) != 0) {
+            return None;
+        }
+        value |= bits << shift;
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+/// Decode a signed LEB128 value, returning it and the number of bytes it took.
+pub(crate) fn decode_sleb128(bytes: &[u8]) -> Option<(i64, usize)> {
+    let mut value = 0i64;
+    for (i, byte) in bytes.iter().enumerate() {
+        let shift = i * 7;
+        if shift >= 64 {
+            return None;
+        }
+        value |= ((byte & 0x7f) as i64) << shift;
+        if byte & 0x80 == 0 {
+            // Sign extend from the last bit read
+            if shift + 7 < 64 && byte & 0x40 != 0 {
+                value |= -1i64 << (shift + 7);
+            }
+            return Some((value, i + 1));
+        }
+    }
+    None
+}
+
+/// Number of bytes in a code unit of `encoding`.
+pub(crate) fn code_unit_size(encoding: StringType) -> usize {
+    match encoding {
+        StringType::AsciiString | StringType::Utf8String => 1,
+        StringType::Utf16String => 2,
+        StringType::Utf32String => 4,
+    }
+}
+
+/// The number of code units of `encoding` before the first NUL code unit in `bytes`.
+pub(crate) fn find_string_terminator(bytes: &[u8], encoding: StringType) -> Option<usize> {
+    bytes
+        .chunks_exact(code_unit_size(encoding))
+        .position(|unit| unit.iter().all(|b| *b == 0))
+}
+
+/// Decode the whole code units of `encoding` in `bytes` as a string.
+///
+/// `None` if they are not valid in `encoding`, ASCII strings may only contain bytes below `0x80`.
+pub(crate) fn decode_string(
+    bytes: &[u8],
+    encoding: StringType,
+    endianness: Endianness,
+) -> Option<String> {
+    match encoding {
+        StringType::AsciiString => bytes
+            .is_ascii()
+            .then(|| bytes.iter().map(|b| *b as char).collect()),
+        StringType::Utf8String => std::str::from_utf8(bytes).ok().map(str::to_string),
+        StringType::Utf16String => {
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|unit| u16::from_bytes(unit, endianness))
+                .collect();
+            String::from_utf16(&units).ok()
+        }
+        StringType::Utf32String => bytes
+            .chunks_exact(4)
+            .map(|unit| char::from_u32(u32::from_bytes(unit, endianness)))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn decodes_primitives() {
+        let bytes = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+        assert_eq!(
+            u32::from_bytes(&bytes, Endianness::LittleEndian),
+            0x04030201
+        );
+        assert_eq!(u32::from_bytes(&bytes, Endianness::BigEndian), 0x01020304);
+        assert_eq!(i16::from_bytes(&[0xff, 0xfe], Endianness::BigEndian), -2);
+        assert_eq!(
+            f32::from_bytes(&1.5f32.to_le_bytes(), Endianness::LittleEndian),
+            1.5
+        );
+        assert_eq!(
+            <[u16; 3]>::from_bytes(&bytes, Endianness::LittleEndian),
+            [0x0201, 0x0403, 0x0605]
+        );
+        assert_eq!(<[u16; 3]>::SIZE, 6);
+    }
+
+    #[test]
+    fn decodes_leb128() {
+        assert_eq!(decode_uleb128(&[0x02]), Some((2, 1)));
+        assert_eq!(decode_uleb128(&[0xe5, 0x8e, 0x26, 0xff]), Some((624485, 3)));
+        assert_eq!(decode_uleb128(&[0x80, 0x80]), None);
+        assert_eq!(
+            decode_uleb128(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]),
+            Some((u64::MAX, 10))
+        );
+        assert_eq!(
+            decode_uleb128(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02]),
+            None
+        );
+
+        assert_eq!(decode_sleb128(&[0x02]), Some((2, 1)));
+        assert_eq!(decode_sleb128(&[0x7e]), Some((-2, 1)));
+        assert_eq!(decode_sleb128(&[0xc0, 0xbb, 0x78]), Some((-123456, 3)));
+        assert_eq!(
+            decode_sleb128(&[0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x80, 0x7f]),
+            Some((i64::MIN, 10))
+        );
+    }
+    #[test]
+    fn decodes_strings() {
+        let le = Endianness::LittleEndian;
+        assert_eq!(
+            decode_string(b"atox", StringType::AsciiString, le).as_deref(),
+            Some("atox")
+        );
+        assert_eq!(
+            decode_string(&[0x61, 0xe9], StringType::AsciiString, le),
+            None
+        );
+        assert_eq!(
+            decode_string("h\u{e9}".as_bytes(), StringType::Utf8String, le).as_deref(),
+            Some("h\u{e9}")
+        );
+        assert_eq!(
+            decode_string(&[0x61, 0xe9], StringType::Utf8String, le),
+            None
+        );
+        assert_eq!(
+            decode_string(
+                &[0x00, 0x61, 0xd8, 0x3d, 0xde, 0x00],
+                StringType::Utf16String,
+                Endianness::BigEndian
+            )
+            .as_deref(),
+            Some("a\u{1f600}")
+        );
+        // An unpaired surrogate
+        assert_eq!(
+            decode_string(&[0x3d, 0xd8], StringType::Utf16String, le),
+            None
+        );
+        assert_eq!(
+            decode_string(&[0x00, 0xf6, 0x01, 0x00], StringType::Utf32String, le).as_deref(),
+            Some("\u{1f600}")
+        );
+        assert_eq!(
+            decode_string(&[0x00, 0x00, 0x11, 0x00], StringType::Utf32String, le),
+            None
+        );
+
+        assert_eq!(
+            find_string_terminator(b"ab\0c", StringType::Utf8String),
+            Some(2)
+        );
+        assert_eq!(
+            find_string_terminator(&[0x61, 0x00, 0x00, 0x00], StringType::Utf16String),
+            Some(1)
+        );
+        assert_eq!(
+            find_string_terminator(&[0x00, 0x61, 0x00], StringType::Utf16String),
+            None
+        );
+    }
+}
@@ -23,47 +23,73 @@
 
 use binaryninjacore_sys::*;
 
+use crate::analysis_completion::{AnalysisCompletion, AnalysisCompletionEvent};
+use crate::analysis_quality::{self, QualityMetric};
 use crate::architecture::{Architecture, CoreArchitecture};
 use crate::basic_block::BasicBlock;
-use crate::component::{Component, IntoComponentGuid};
+use crate::binary_reader::{
+    code_unit_size, decode_sleb128, decode_string, decode_uleb128, find_string_terminator,
+    FromBytes,
+};
+use crate::component::{Component, ComponentBuilder, IntoComponentGuid};
 use crate::confidence::Conf;
 use crate::data_buffer::DataBuffer;
+use crate::data_notification::{BinaryDataNotification, DataNotificationHandle};
+use crate::database::kvs::KeyValueStore;
+use crate::database::snapshot::SnapshotId;
 use crate::debuginfo::DebugInfo;
+use crate::disassembly::{DisassemblySettings, StringType};
+use crate::entropy::ByteHistogram;
 use crate::external_library::{ExternalLibrary, ExternalLocation};
 use crate::file_accessor::FileAccessor;
 use crate::file_metadata::FileMetadata;
 use crate::flowgraph::FlowGraph;
-use crate::function::{Function, NativeBlock};
+use crate::function::{Function, FunctionViewType, NativeBlock, SystemCallSite};
+use crate::headers::{ElfHeader, MachOHeader, PeHeader};
+use crate::heat_map::HeatMap;
+use crate::instruction_iter::InstructionIter;
 use crate::linear_view::{LinearDisassemblyLine, LinearViewCursor};
-use crate::metadata::Metadata;
-use crate::platform::Platform;
+use crate::memory_map::MemoryMap;
+use crate::metadata::{Metadata, MetadataType};
+use crate::platform::{Platform, SystemCallInfo};
 use crate::progress::{NoProgressCallback, ProgressCallback};
 use crate::project::file::ProjectFile;
 use crate::rc::*;
-use crate::references::{CodeReference, DataReference};
-use crate::relocation::Relocation;
+use crate::references::{
+    classify_data_accesses, CodeReference, DataReference, DataVariableAccess,
+    DataVariableAccessKind,
+};
+use crate::relocation::{Relocation, RelocationInfo};
+use crate::search::{self, BytePattern, FindFlag, SearchQuery};
 use crate::section::{Section, SectionBuilder};
 use crate::segment::{Segment, SegmentBuilder};
 use crate::settings::Settings;
 use crate::string::*;
 use crate::symbol::{Symbol, SymbolType};
-use crate::tags::{Tag, TagType};
+use crate::symbol_index::SymbolIndex;
+use crate::tags::{Tag, TagReference, TagType};
+use crate::type_archive::{TypeArchive, TypeArchiveSyncStatus};
 use crate::type_container::TypeContainer;
-use crate::type_library::TypeLibrary;
+use crate::type_library::{ImportTypingReport, TypeLibrary, TypedImport};
 use crate::types::{
     NamedTypeReference, QualifiedName, QualifiedNameAndType, QualifiedNameTypeAndId, Type,
 };
 use crate::variable::DataVariable;
 use crate::Endianness;
-use std::collections::HashMap;
+use crate::Error;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::ffi::{c_char, c_void};
+use std::ops::Deref;
 use std::ops::Range;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 use std::{result, slice};
 // TODO : general reorg of modules related to bv
 
-pub type Result<R> = result::Result<R, ()>;
+pub type Result<R> = result::Result<R, Error>;
+
 pub type BinaryViewEventType = BNBinaryViewEventType;
 pub type AnalysisState = BNAnalysisState;
 pub type ModificationStatus = BNModificationStatus;
@@ -153,19 +179,114 @@ pub trait BinaryViewBase: AsRef<BinaryView> {
     }
 }
 
+/// A function that is being analyzed, see [`AnalysisInfo::active_info`].
 #[derive(Debug, Clone)]
 pub struct ActiveAnalysisInfo {
     pub func: Ref<Function>,
+    /// Time spent analyzing the function in the current update, in milliseconds.
     pub analysis_time: u64,
+    /// Number of times the function was analyzed in the current update.
     pub update_count: usize,
+    /// Number of times the function was submitted for analysis in the current update.
     pub submit_count: usize,
 }
 
+impl ActiveAnalysisInfo {
+    pub fn analysis_duration(&self) -> Duration {
+        Duration::from_millis(self.analysis_time)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AnalysisInfo {
     pub state: AnalysisState,
     pub analysis_time: u64,
     pub active_info: Vec<ActiveAnalysisInfo>,
+    /// Metrics of registered [`crate::analysis_quality::AnalysisValidator`]s, sorted by name.
+    pub quality_metrics: Vec<QualityMetric>,
+}
+
+impl AnalysisInfo {
+    /// Whether any of the quality metrics flagged the analysis for review by a human.
+    pub fn needs_review(&self) -> bool {
+        self.quality_metrics
+            .iter()
+            .any(|metric| metric.needs_review)
+    }
+
+    /// The active functions analyzed for longer than `threshold`, longest first.
+    pub fn running_longer_than(&self, threshold: Duration) -> Vec<&ActiveAnalysisInfo> {
+        let mut stalled: Vec<_> = self
+            .active_info
+            .iter()
+            .filter(|info| info.analysis_duration() > threshold)
+            .collect();
+        stalled.sort_by_key(|info| std::cmp::Reverse(info.analysis_time));
+        stalled
+    }
+}
+
+/// Symbol updates of a view suspended by [`BinaryViewExt::bulk_modify_symbols`], which resume
+/// when this is dropped.
+#[must_use]
+pub struct BulkModifySymbolsGuard {
+    view: Ref<BinaryView>,
+}
+
+impl Drop for BulkModifySymbolsGuard {
+    fn drop(&mut self) {
+        unsafe { BNEndBulkModifySymbols(self.view.handle) }
+    }
+}
+
+/// A string found by analysis, see [`BinaryViewExt::strings`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StringReference {
+    pub start: u64,
+    /// Length of the string in bytes.
+    pub length: usize,
+    pub string_type: StringType,
+}
+
+impl StringReference {
+    pub(crate) fn from_raw(value: &BNStringReference) -> Self {
+        Self {
+            start: value.start,
+            length: value.length,
+            string_type: value.type_,
+        }
+    }
+
+    pub fn range(&self) -> Range<u64> {
+        self.start..self.start + self.length as u64
+    }
+
+    /// Read the string from `view` and decode it, code units wider than a byte are read in the
+    /// default endianness of the view.
+    pub fn text(&self, view: &BinaryView) -> Result<String> {
+        let bytes = view.read_vec(self.start, self.length);
+        if bytes.len() != self.length {
+            return Err(short_read(self.start, self.length));
+        }
+        decode_string(&bytes, self.string_type, view.default_endianness())
+            .ok_or_else(|| invalid_string(self.start, self.string_type))
+    }
+}
+
+impl CoreArrayProvider for StringReference {
+    type Raw = BNStringReference;
+    type Context = ();
+    type Wrapped<'a> = Self;
+}
+
+unsafe impl CoreArrayProviderInner for StringReference {
+    unsafe fn free(raw: *mut Self::Raw, _count: usize, _context: &Self::Context) {
+        BNFreeStringReferenceList(raw)
+    }
+
+    unsafe fn wrap_raw<'a>(raw: &'a Self::Raw, _context: &'a Self::Context) -> Self::Wrapped<'a> {
+        Self::from_raw(raw)
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -205,6 +326,40 @@ pub trait BinaryViewExt: BinaryViewBase {
         unsafe { BnString::from_raw(ptr) }
     }
 
+    /// The headers of the file backing this view, `None` if that is not an ELF file.
+    ///
+    /// The loaders of the core only keep what analysis needs from the headers, so they are parsed
+    /// again from the raw view of the file.
+    ///
+    /// ```no_run
+    /// use binaryninja::binary_view::BinaryViewExt;
+    ///
+    /// let view = binaryninja::load("/bin/cat").unwrap();
+    /// if let Some(elf) = view.elf_header() {
+    ///     println!("needs {:?}", elf.needed_libraries);
+    ///     for section in &elf.section_headers {
+    ///         let segment = elf.segment_of(section).map(|segment| segment.vaddr);
+    ///         println!("{} is loaded by the segment at {:x?}", section.name, segment);
+    ///     }
+    /// }
+    /// ```
+    fn elf_header(&self) -> Option<ElfHeader> {
+        let raw_view = self.raw_view()?;
+        ElfHeader::parse(|offset, len| raw_view.read_vec(offset, len))
+    }
+
+    /// The headers of the file backing this view, `None` if that is not a PE file.
+    fn pe_header(&self) -> Option<PeHeader> {
+        let raw_view = self.raw_view()?;
+        PeHeader::parse(|offset, len| raw_view.read_vec(offset, len))
+    }
+
+    /// The headers of the file backing this view, `None` if that is not a Mach-O file.
+    fn macho_header(&self) -> Option<MachOHeader> {
+        let raw_view = self.raw_view()?;
+        MachOHeader::parse(|offset, len| raw_view.read_vec(offset, len))
+    }
+
     /// Reads up to `len` bytes from address `offset`
     fn read_vec(&self, offset: u64, len: usize) -> Vec<u8> {
         let mut ret = vec![0; len];
@@ -224,6 +379,333 @@ pub trait BinaryViewExt: BinaryViewBase {
         read_size
     }
 
+    /// Read a `T` at `offset`, in the default endianness of the view.
+    ///
+    /// Fails if the view does not have all [`FromBytes::SIZE`] bytes of it.
+    fn read_value<T: FromBytes>(&self, offset: u64) -> Result<T> {
+        self.read_value_with_endianness(offset, self.default_endianness())
+    }
+
+    /// Read a `T` at `offset`, in the given endianness.
+    fn read_value_with_endianness<T: FromBytes>(
+        &self,
+        offset: u64,
+        endianness: Endianness,
+    ) -> Result<T> {
+        let bytes = self.read_vec(offset, T::SIZE);
+        if bytes.len() != T::SIZE {
+            return Err(short_read(offset, T::SIZE));
+        }
+        Ok(T::from_bytes(&bytes, endianness))
+    }
+
+    fn read_u8(&self, offset: u64) -> Result<u8> {
+        self.read_value(offset)
+    }
+
+    fn read_u16(&self, offset: u64) -> Result<u16> {
+        self.read_value(offset)
+    }
+
+    fn read_u32(&self, offset: u64) -> Result<u32> {
+        self.read_value(offset)
+    }
+
+    fn read_u64(&self, offset: u64) -> Result<u64> {
+        self.read_value(offset)
+    }
+
+    fn read_i8(&self, offset: u64) -> Result<i8> {
+        self.read_value(offset)
+    }
+
+    fn read_i16(&self, offset: u64) -> Result<i16> {
+        self.read_value(offset)
+    }
+
+    fn read_i32(&self, offset: u64) -> Result<i32> {
+        self.read_value(offset)
+    }
+
+    fn read_i64(&self, offset: u64) -> Result<i64> {
+        self.read_value(offset)
+    }
+
+    fn read_f32(&self, offset: u64) -> Result<f32> {
+        self.read_value(offset)
+    }
+
+    fn read_f64(&self, offset: u64) -> Result<f64> {
+        self.read_value(offset)
+    }
+
+    /// Read a pointer sized value at `offset`, see [`BinaryViewBase::address_size`].
+    fn read_pointer(&self, offset: u64) -> Result<u64> {
+        match self.address_size() {
+            1 => self.read_u8(offset).map(u64::from),
+            2 => self.read_u16(offset).map(u64::from),
+            4 => self.read_u32(offset).map(u64::from),
+            8 => self.read_u64(offset),
+            size => Err(Error::InvalidArgument(format!(
+                "unsupported address size {}",
+                size
+            ))),
+        }
+    }
+
+    /// Read a string of at most `max_len` code units of `encoding` at `offset`, terminated by a
+    /// NUL code unit. Code units wider than a byte are read in the default endianness of the view.
+    ///
+    /// Fails if there is no terminator within `max_len` code units or the string is not valid in
+    /// `encoding`, ASCII strings may only contain bytes below `0x80`.
+    fn read_cstring(&self, offset: u64, max_len: usize, encoding: StringType) -> Result<String> {
+        read_terminated_string(
+            self.as_ref(),
+            offset,
+            max_len,
+            encoding,
+            self.default_endianness(),
+        )
+    }
+
+    /// Read a NUL terminated UTF-16 string of at most `max_len` code units at `offset`.
+    fn read_utf16_string(
+        &self,
+        offset: u64,
+        max_len: usize,
+        endianness: Endianness,
+    ) -> Result<String> {
+        read_terminated_string(
+            self.as_ref(),
+            offset,
+            max_len,
+            StringType::Utf16String,
+            endianness,
+        )
+    }
+
+    /// Read a string at `offset` made of an unsigned length of `prefix_size` bytes followed by that
+    /// many code units of `encoding`, returning it and its length in bytes including the prefix.
+    ///
+    /// Fails if the length is more than `max_len` or the string is not valid in `encoding`.
+    fn read_length_prefixed_string(
+        &self,
+        offset: u64,
+        prefix_size: usize,
+        max_len: usize,
+        encoding: StringType,
+    ) -> Result<(String, usize)> {
+        let len = match prefix_size {
+            1 => self.read_u8(offset)? as u64,
+            2 => self.read_u16(offset)? as u64,
+            4 => self.read_u32(offset)? as u64,
+            8 => self.read_u64(offset)?,
+            size => {
+                return Err(Error::InvalidArgument(format!(
+                    "unsupported length prefix size {}",
+                    size
+                )))
+            }
+        };
+        if len > max_len as u64 {
+            return Err(Error::Parse(format!(
+                "string at {:#x}, length {} is more than {}",
+                offset, len, max_len
+            )));
+        }
+        let size = len as usize * code_unit_size(encoding);
+        let start = offset + prefix_size as u64;
+        let bytes = self.read_vec(start, size);
+        if bytes.len() != size {
+            return Err(short_read(start, size));
+        }
+        let string = decode_string(&bytes, encoding, self.default_endianness())
+            .ok_or_else(|| invalid_string(offset, encoding))?;
+        Ok((string, prefix_size + size))
+    }
+
+    /// The strings found by analysis, in address order.
+    ///
+    /// ```no_run
+    /// use binaryninja::binary_view::BinaryViewExt;
+    /// use binaryninja::disassembly::StringType;
+    ///
+    /// let view = binaryninja::load("/bin/cat").unwrap();
+    /// let wide = view
+    ///     .strings()
+    ///     .iter()
+    ///     .filter(|string| string.string_type == StringType::Utf16String && string.length >= 16)
+    ///     .filter_map(|string| string.text(&view).ok())
+    ///     .collect::<Vec<_>>();
+    /// ```
+    fn strings(&self) -> Array<StringReference> {
+        let mut count = 0;
+        let strings = unsafe { BNGetStrings(self.as_ref().handle, &mut count) };
+        unsafe { Array::new(strings, count, ()) }
+    }
+
+    /// The strings found by analysis in `range`.
+    fn strings_in_range(&self, range: Range<u64>) -> Array<StringReference> {
+        let mut count = 0;
+        let strings = unsafe {
+            BNGetStringsInRange(
+                self.as_ref().handle,
+                range.start,
+                range.end.saturating_sub(range.start),
+                &mut count,
+            )
+        };
+        unsafe { Array::new(strings, count, ()) }
+    }
+
+    /// The string found by analysis containing `addr`.
+    fn string_at(&self, addr: u64) -> Option<StringReference> {
+        let mut string = BNStringReference::default();
+        let found = unsafe { BNGetStringAtAddress(self.as_ref().handle, addr, &mut string) };
+        found.then(|| StringReference::from_raw(&string))
+    }
+
+    /// Read an unsigned LEB128 value at `offset`, returning it and its length in bytes.
+    fn read_uleb128(&self, offset: u64) -> Result<(u64, usize)> {
+        // A 64-bit value takes at most 10 bytes
+        let bytes = self.read_vec(offset, 10);
+        decode_uleb128(&bytes)
+            .ok_or_else(|| Error::Parse(format!("unsigned LEB128 value at {:#x}", offset)))
+    }
+
+    /// Read a signed LEB128 value at `offset`, returning it and its length in bytes.
+    fn read_sleb128(&self, offset: u64) -> Result<(i64, usize)> {
+        let bytes = self.read_vec(offset, 10);
+        decode_sleb128(&bytes)
+            .ok_or_else(|| Error::Parse(format!("signed LEB128 value at {:#x}", offset)))
+    }
+
+    /// The [`ModificationStatus`] of each of the `len` bytes starting at `offset`.
+    fn modifications(&self, offset: u64, len: usize) -> Vec<ModificationStatus> {
+        let mut result = vec![ModificationStatus::Original; len];
+        let count = unsafe {
+            BNGetModificationArray(self.as_ref().handle, offset, result.as_mut_ptr(), len)
+        };
+        result.truncate(count);
+        result
+    }
+
+    /// The ranges of the view that have been changed or inserted since it was opened.
+    ///
+    /// Only the segments of the view are scanned, or the whole view if it has none.
+    fn modified_ranges(&self) -> Vec<Range<u64>> {
+        // Bytes queried per call into the core
+        const CHUNK_SIZE: u64 = 0x10000;
+
+        let mut modified: Vec<Range<u64>> = Vec::new();
+        for range in mapped_ranges(self.as_ref()) {
+            let mut offset = range.start;
+            while offset < range.end {
+                let len = (range.end - offset).min(CHUNK_SIZE);
+                for (i, status) in self.modifications(offset, len as usize).iter().enumerate() {
+                    if *status == ModificationStatus::Original {
+                        continue;
+                    }
+                    let address = offset + i as u64;
+                    match modified.last_mut() {
+                        Some(last) if last.end == address => last.end += 1,
+                        _ => modified.push(address..address + 1),
+                    }
+                }
+                offset += len;
+            }
+        }
+        modified
+    }
+
+    /// Read the mapped regions of the view in order, in blocks of at most `chunk_size` bytes.
+    ///
+    /// Only one block is held in memory at a time, so passes over the whole view, such as hashing
+    /// or entropy calculation, use constant memory. Bytes that can't be read, such as the
+    /// uninitialized part of a segment, are skipped.
+    ///
+    /// ```no_run
+    /// # use binaryninja::binary_view::BinaryViewExt;
+    /// # let bv = binaryninja::load("example").unwrap();
+    /// let mut blocks = bv.iter_blocks(0x10000);
+    /// let mut checksum = 0u64;
+    /// while let Some((_address, data)) = blocks.next_block() {
+    ///     checksum = data.iter().fold(checksum, |sum, byte| sum.wrapping_add(*byte as u64));
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_size` is zero.
+    fn iter_blocks(&self, chunk_size: usize) -> BlockIter<'_> {
+        assert!(chunk_size > 0, "block size must not be zero");
+        let view = self.as_ref();
+        let mut ranges = mapped_ranges(view);
+        ranges.reverse();
+        BlockIter {
+            view,
+            ranges,
+            buffer: vec![0; chunk_size],
+        }
+    }
+
+    /// The entropy of each `block_size` block of `range`, see [`ByteHistogram::entropy`].
+    ///
+    /// The last block is shorter if `range` is not a multiple of `block_size`. Bytes that can't be
+    /// read are left out of the entropy of their block, blocks that can't be read at all have an
+    /// entropy of 0.0. The view is read in chunks of at most a megabyte, which are processed on all
+    /// available threads, or on the rayon thread pool with the `rayon` feature.
+    ///
+    /// Compressed and encrypted data has close to the maximum entropy, so this is a quick way to
+    /// find packed code:
+    ///
+    /// ```no_run
+    /// use binaryninja::binary_view::{BinaryViewBase, BinaryViewExt};
+    ///
+    /// let view = binaryninja::load("/bin/cat").unwrap();
+    /// let block_size = 0x1000;
+    /// for (i, entropy) in view.entropy(view.start()..view.end(), block_size).iter().enumerate() {
+    ///     if *entropy > 0.9 {
+    ///         println!("{:#x} looks packed", view.start() + (i * block_size) as u64);
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is zero.
+    fn entropy(&self, range: Range<u64>, block_size: usize) -> Vec<f64> {
+        crate::entropy::view_entropy(self.as_ref(), range, block_size)
+    }
+
+    /// How often each byte value occurs in `range`, leaving out bytes that can't be read.
+    fn byte_histogram(&self, range: Range<u64>) -> ByteHistogram {
+        crate::entropy::view_histogram(self.as_ref(), range)
+    }
+
+    /// Copy this view and its analysis into a new file backed by a temporary database.
+    ///
+    /// Changes to the copy, such as patches or mass retyping, do not affect this view or its
+    /// database, which makes it suitable for experiments that may have to be thrown away. The
+    /// copy is closed and its database deleted when the returned [`ScratchView`] is dropped.
+    fn duplicate_in_memory(&self) -> Result<ScratchView> {
+        let view = self.as_ref();
+        let file = view.file();
+        let raw_view = file
+            .view_of_type("Raw")
+            .ok_or_else(|| Error::NotFound("raw view".to_string()))?;
+        let contents = raw_view.read_buffer(0, raw_view.len() as usize)?;
+        let (data, cache) = file.snapshot_data();
+        ScratchView::create(
+            &file,
+            contents.get_data(),
+            &data,
+            &cache,
+            "Scratch copy",
+            view.view_type().as_str(),
+        )
+    }
+
     fn notify_data_written(&self, offset: u64, len: usize) {
         unsafe {
             BNNotifyDataWritten(self.as_ref().handle, offset, len);
@@ -291,6 +773,54 @@ pub trait BinaryViewExt: BinaryViewBase {
         }
     }
 
+    /// Start updating analysis, returning a future that resolves once it completes.
+    ///
+    /// Unlike [`BinaryViewExt::update_analysis_and_wait`] this does not block the calling thread,
+    /// and the future works with any executor:
+    ///
+    /// ```no_run
+    /// use binaryninja::binary_view::{BinaryView, BinaryViewExt};
+    ///
+    /// async fn analyze(view: &BinaryView) -> usize {
+    ///     view.update_analysis_async().await;
+    ///     view.functions().len()
+    /// }
+    /// ```
+    fn update_analysis_async(&self) -> AnalysisCompletion {
+        let completion = AnalysisCompletion::new(self.as_ref());
+        self.update_analysis();
+        completion
+    }
+
+    /// Run `callback` the next time analysis completes, until the returned event is dropped.
+    ///
+    /// ```no_run
+    /// use binaryninja::binary_view::{BinaryView, BinaryViewExt};
+    ///
+    /// let view = binaryninja::load("/bin/cat").unwrap();
+    /// let event = view.on_analysis_complete(|view: &BinaryView| {
+    ///     log::info!("{} functions", view.functions().len());
+    /// });
+    /// view.update_analysis();
+    /// // Dropping the event before analysis completes cancels the callback
+    /// # drop(event);
+    /// ```
+    fn on_analysis_complete<F>(&self, callback: F) -> AnalysisCompletionEvent
+    where
+        F: FnOnce(&BinaryView) + Send + 'static,
+    {
+        AnalysisCompletionEvent::new(self.as_ref(), callback)
+    }
+
+    /// Start sending the changes made to this view to `notification`, until the returned handle
+    /// is dropped or unregistered.
+    fn register_data_notification<T: BinaryDataNotification>(
+        &self,
+        notification: T,
+    ) -> DataNotificationHandle<T> {
+        DataNotificationHandle::register(self.as_ref(), notification)
+    }
+
     fn abort_analysis(&self) {
         unsafe { BNAbortAnalysis(self.as_ref().handle) }
     }
@@ -298,14 +828,15 @@ pub trait BinaryViewExt: BinaryViewBase {
     fn analysis_info(&self) -> Result<AnalysisInfo> {
         let info_ref = unsafe { BNGetAnalysisInfo(self.as_ref().handle) };
         if info_ref.is_null() {
-            return Err(());
+            return Err(Error::NullHandle("BNGetAnalysisInfo"));
         }
         let info = unsafe { *info_ref };
         let active_infos = unsafe { slice::from_raw_parts(info.activeInfo, info.count) };
 
         let mut active_info_list = vec![];
         for active_info in active_infos {
-            let func = unsafe { Function::ref_from_raw(active_info.func) };
+            // The functions are released with the info
+            let func = unsafe { Function::from_raw(active_info.func) }.to_owned();
             active_info_list.push(ActiveAnalysisInfo {
                 func,
                 analysis_time: active_info.analysisTime,
@@ -317,13 +848,47 @@ pub trait BinaryViewExt: BinaryViewBase {
         let result = AnalysisInfo {
             state: info.state,
             analysis_time: info.analysisTime,
-            active_info: vec![],
+            active_info: active_info_list,
+            quality_metrics: analysis_quality::quality_metrics(self.as_ref()),
         };
 
         unsafe { BNFreeAnalysisInfo(info_ref) };
         Ok(result)
     }
 
+    /// Functions waiting to be analyzed in the next update, see [`Function::is_update_needed`].
+    fn functions_pending_analysis(&self) -> Vec<Ref<Function>> {
+        self.functions()
+            .iter()
+            .filter(|func| func.is_update_needed())
+            .map(|func| func.to_owned())
+            .collect()
+    }
+
+    /// Cancel the analysis of the functions that have been analyzed for longer than `threshold`
+    /// in the current update, see [`Function::cancel_analysis`]. Returns the cancelled functions.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use binaryninja::binary_view::BinaryViewExt;
+    /// # let view = binaryninja::load("/bin/cat").unwrap();
+    /// for func in view.cancel_stalled_analysis(Duration::from_secs(30)) {
+    ///     println!("Skipped analysis of {:#x}", func.start());
+    /// }
+    /// ```
+    fn cancel_stalled_analysis(&self, threshold: Duration) -> Vec<Ref<Function>> {
+        let Ok(info) = self.analysis_info() else {
+            return vec![];
+        };
+        info.running_longer_than(threshold)
+            .into_iter()
+            .map(|active| {
+                active.func.cancel_analysis();
+                active.func.clone()
+            })
+            .collect()
+    }
+
     fn analysis_progress(&self) -> AnalysisProgress {
         let progress = unsafe { BNGetAnalysisProgress(self.as_ref().handle) };
         AnalysisProgress {
@@ -417,6 +982,12 @@ pub trait BinaryViewExt: BinaryViewBase {
         }
     }
 
+    /// An index of the current symbols for fast prefix and fuzzy name queries, see
+    /// [`SymbolIndex::symbols_matching`].
+    fn symbol_index(&self) -> SymbolIndex {
+        SymbolIndex::new(self.as_ref())
+    }
+
     fn symbols_by_name<S: BnStrCompatible>(&self, name: S) -> Array<Symbol> {
         let raw_name = name.into_bytes_with_nul();
 
@@ -507,13 +1078,80 @@ pub trait BinaryViewExt: BinaryViewBase {
             );
 
             if raw_sym.is_null() {
-                return Err(());
+                return Err(Error::NullHandle("BNDefineAutoSymbolAndVariableOrFunction"));
             }
 
             Ok(Symbol::ref_from_raw(raw_sym))
         }
     }
 
+    /// Define many auto symbols at once, with symbol updates suspended until all are defined.
+    fn define_auto_symbols<T, S>(&self, symbols: T) -> usize
+    where
+        T: IntoIterator<Item = S>,
+        S: AsRef<Symbol>,
+    {
+        self.define_auto_symbols_with_progress(symbols, NoProgressCallback)
+    }
+
+    /// Define many auto symbols at once, see [`BinaryViewExt::define_auto_symbols`].
+    ///
+    /// Returns the number of symbols defined, which is less than given if `progress` cancelled.
+    fn define_auto_symbols_with_progress<T, S, P>(&self, symbols: T, progress: P) -> usize
+    where
+        T: IntoIterator<Item = S>,
+        S: AsRef<Symbol>,
+        P: ProgressCallback,
+    {
+        define_symbols(self.as_ref(), symbols, progress, |view, sym| {
+            view.define_auto_symbol(sym)
+        })
+    }
+
+    /// Define many user symbols at once, with symbol updates suspended until all are defined.
+    fn define_user_symbols<T, S>(&self, symbols: T) -> usize
+    where
+        T: IntoIterator<Item = S>,
+        S: AsRef<Symbol>,
+    {
+        self.define_user_symbols_with_progress(symbols, NoProgressCallback)
+    }
+
+    /// Define many user symbols at once, see [`BinaryViewExt::define_user_symbols`].
+    ///
+    /// Returns the number of symbols defined, which is less than given if `progress` cancelled.
+    fn define_user_symbols_with_progress<T, S, P>(&self, symbols: T, progress: P) -> usize
+    where
+        T: IntoIterator<Item = S>,
+        S: AsRef<Symbol>,
+        P: ProgressCallback,
+    {
+        define_symbols(self.as_ref(), symbols, progress, |view, sym| {
+            view.define_user_symbol(sym)
+        })
+    }
+
+    /// Suspend symbol updates and their notifications until the returned guard is dropped, at
+    /// which point all symbols changed in the meantime are updated at once.
+    ///
+    /// ```no_run
+    /// use binaryninja::binary_view::BinaryViewExt;
+    /// use binaryninja::symbol::{SymbolBuilder, SymbolType};
+    ///
+    /// let view = binaryninja::load("/bin/cat").unwrap();
+    /// let _bulk = view.bulk_modify_symbols();
+    /// for (i, addr) in [0x1000, 0x1010, 0x1020].into_iter().enumerate() {
+    ///     let sym = SymbolBuilder::new(SymbolType::Data, format!("entry_{}", i), addr).create();
+    ///     view.define_user_symbol(&sym);
+    /// }
+    /// ```
+    fn bulk_modify_symbols(&self) -> BulkModifySymbolsGuard {
+        unsafe { BNBeginBulkModifySymbols(self.as_ref().handle) };
+        BulkModifySymbolsGuard {
+            view: self.as_ref().to_owned(),
+        }
+    }
+
     fn undefine_auto_symbol(&self, sym: &Symbol) {
         unsafe {
             BNUndefineAutoSymbol(self.as_ref().handle, sym.handle);
@@ -578,6 +1216,41 @@ pub trait BinaryViewExt: BinaryViewBase {
         }
     }
 
+    /// Comment at `addr` outside of any function, see [`Function::comment_at`] for comments in
+    /// functions.
+    fn comment_at(&self, addr: u64) -> BnString {
+        unsafe { BnString::from_raw(BNGetGlobalCommentForAddress(self.as_ref().handle, addr)) }
+    }
+
+    fn set_comment_at<S: BnStrCompatible>(&self, addr: u64, comment: S) {
+        let raw = comment.into_bytes_with_nul();
+
+        unsafe {
+            BNSetGlobalCommentForAddress(
+                self.as_ref().handle,
+                addr,
+                raw.as_ref().as_ptr() as *const _,
+            );
+        }
+    }
+
+    /// Addresses with a comment outside of any function.
+    fn commented_addresses(&self) -> Vec<u64> {
+        unsafe {
+            let mut count = 0;
+            let addresses = BNGetGlobalCommentedAddresses(self.as_ref().handle, &mut count);
+            let res = std::slice::from_raw_parts(addresses, count).to_vec();
+            BNFreeAddressList(addresses);
+            res
+        }
+    }
+
+    /// Summary of the functions, data, tags and comments of the view in at most `bin_count`
+    /// bins, see [`HeatMap`].
+    fn heat_map(&self, bin_count: usize) -> HeatMap {
+        HeatMap::new(self.as_ref(), bin_count)
+    }
+
     fn define_auto_type<T: Into<QualifiedName>, S: BnStrCompatible>(
         &self,
         name: T,
@@ -820,6 +1493,11 @@ pub trait BinaryViewExt: BinaryViewBase {
         }
     }
 
+    /// The memory map of the view, describing the objects mapped at each address.
+    fn memory_map(&self) -> MemoryMap {
+        MemoryMap::new(self.as_ref())
+    }
+
     /// Adds a segment to the view.
     ///
     /// NOTE: Consider using [BinaryViewExt::begin_bulk_add_segments] and [BinaryViewExt::end_bulk_add_segments]
@@ -828,6 +1506,30 @@ pub trait BinaryViewExt: BinaryViewBase {
         segment.create(self.as_ref());
     }
 
+    /// Removes the most recently added auto segment that starts at or contains `range.start`.
+    ///
+    /// NOTE: Removing auto segments is not saved to the database, it must be done again every time
+    /// the database is loaded.
+    fn remove_auto_segment(&self, range: Range<u64>) {
+        let length = range.end.wrapping_sub(range.start);
+        unsafe { BNRemoveAutoSegment(self.as_ref().handle, range.start, length) }
+    }
+
+    /// Removes the most recently added user segment that starts at or contains `range.start`.
+    fn remove_user_segment(&self, range: Range<u64>) {
+        let length = range.end.wrapping_sub(range.start);
+        unsafe { BNRemoveUserSegment(self.as_ref().handle, range.start, length) }
+    }
+
+    /// Removes `segment` and adds `replacement` in its place, see [`Segment::to_builder`].
+    fn replace_segment(&self, segment: &Segment, replacement: SegmentBuilder) {
+        match segment.auto_defined() {
+            true => self.remove_auto_segment(segment.address_range()),
+            false => self.remove_user_segment(segment.address_range()),
+        }
+        self.add_segment(replacement);
+    }
+
     // TODO: Replace with BulkModify guard.
     /// Start adding segments in bulk. Useful for adding large numbers of segments.
     ///
@@ -882,6 +1584,19 @@ pub trait BinaryViewExt: BinaryViewBase {
         }
     }
 
+    /// Removes `section` and adds `replacement` in its place, see [`Section::to_builder`].
+    fn replace_section<S: BnStrCompatible>(
+        &self,
+        section: &Section,
+        replacement: SectionBuilder<S>,
+    ) {
+        match section.auto_defined() {
+            true => self.remove_auto_section(section.name()),
+            false => self.remove_user_section(section.name()),
+        }
+        self.add_section(replacement);
+    }
+
     fn section_by_name<S: BnStrCompatible>(&self, name: S) -> Option<Ref<Section>> {
         unsafe {
             let raw_name = name.into_bytes_with_nul();
@@ -968,7 +1683,7 @@ pub trait BinaryViewExt: BinaryViewBase {
             let func = BNCreateUserFunction(self.as_ref().handle, plat.handle, addr);
 
             if func.is_null() {
-                return Err(());
+                return Err(Error::NullHandle("BNCreateUserFunction"));
             }
 
             Ok(Function::ref_from_raw(func))
@@ -1029,6 +1744,12 @@ pub trait BinaryViewExt: BinaryViewBase {
         }
     }
 
+    /// Decode the instructions starting in `range`, each with the architecture in effect at its
+    /// address, skipping data. See [`InstructionIter`].
+    fn instructions_in(&self, range: Range<u64>) -> InstructionIter {
+        InstructionIter::new(self.as_ref(), range)
+    }
+
     fn function_at(&self, platform: &Platform, addr: u64) -> Option<Ref<Function>> {
         unsafe {
             let raw_func_ptr = BNGetAnalysisFunction(self.as_ref().handle, platform.handle, addr);
@@ -1073,21 +1794,261 @@ pub trait BinaryViewExt: BinaryViewBase {
         }
     }
 
+    /// Retrieves the system calls made by all functions in the view.
+    ///
+    /// See [`Function::system_call_sites`] for more information.
+    fn system_call_sites(&self) -> Vec<SystemCallSite> {
+        self.functions()
+            .iter()
+            .flat_map(|func| func.system_call_sites())
+            .collect()
+    }
+
+    /// Resolves the system call `number` for `platform`.
+    ///
+    /// System calls overridden with [`Self::set_user_system_call`] take precedence over those
+    /// provided by the platform, unless the user type of the override was undefined.
+    fn system_call_info(&self, platform: &Platform, number: u32) -> Option<SystemCallInfo> {
+        let overridden_name = self
+            .query_metadata(SYSTEM_CALL_OVERRIDES_KEY)
+            .and_then(|overrides| overrides.get(number.to_string()).ok().flatten())
+            .and_then(|name| String::try_from(name.as_ref()).ok());
+        if let Some(name) = overridden_name {
+            if let Some(ty) = self.type_by_name(name.as_str()) {
+                return Some(SystemCallInfo::new(number, name, ty));
+            }
+        }
+
+        let name = platform.system_call_name(number)?;
+        let ty = platform.system_call_type(number)?;
+        Some(SystemCallInfo::new(number, name.to_string(), ty))
+    }
+
+    /// Overrides the name and prototype of the system call `number` for this view, for use with
+    /// custom kernels whose system calls are unknown to the platform.
+    ///
+    /// The prototype is defined as a user type named `name`. To annotate the existing call sites with
+    /// the overridden prototypes see [`Self::apply_user_system_calls`].
+    fn set_user_system_call<T: Into<QualifiedName>>(&self, number: u32, name: T, ty: &Type) {
+        let name = name.into();
+        self.define_user_type(name.clone(), ty);
+        let overrides = self
+            .query_metadata(SYSTEM_CALL_OVERRIDES_KEY)
+            .unwrap_or_else(|| Metadata::new_of_type(MetadataType::KeyValueDataType));
+        let name_md: Ref<Metadata> = name.to_string().into();
+        let _ = overrides.insert(number.to_string(), &name_md);
+        self.store_metadata(SYSTEM_CALL_OVERRIDES_KEY, overrides, false);
+    }
+
+    /// Removes the override of the system call `number` set by [`Self::set_user_system_call`].
+    ///
+    /// The user type defining the prototype is kept.
+    fn remove_user_system_call(&self, number: u32) {
+        if let Some(overrides) = self.query_metadata(SYSTEM_CALL_OVERRIDES_KEY) {
+            let _ = overrides.remove_key(number.to_string());
+            self.store_metadata(SYSTEM_CALL_OVERRIDES_KEY, overrides, false);
+        }
+    }
+
+    /// Applies the prototypes of overridden system calls to their call sites as user call type
+    /// adjustments.
+    fn apply_user_system_calls(&self) {
+        let Some(overrides) = self.query_metadata(SYSTEM_CALL_OVERRIDES_KEY) else {
+            return;
+        };
+        for func in &self.functions() {
+            let platform = func.platform();
+            for site in func.system_call_sites() {
+                let Some(number) = site.number else {
+                    continue;
+                };
+                if !matches!(overrides.get(number.to_string()), Ok(Some(_))) {
+                    continue;
+                }
+                if let Some(info) = self.system_call_info(&platform, number) {
+                    func.set_user_call_type_adjustment(site.address, Some(&info.ty), None);
+                }
+            }
+        }
+    }
+
     fn read_buffer(&self, offset: u64, len: usize) -> Result<DataBuffer> {
         let read_buffer = unsafe { BNReadViewBuffer(self.as_ref().handle, offset, len) };
         if read_buffer.is_null() {
-            Err(())
+            Err(Error::NullHandle("BNReadViewBuffer"))
         } else {
             Ok(DataBuffer::from_raw(read_buffer))
         }
     }
 
-    fn debug_info(&self) -> Ref<DebugInfo> {
-        unsafe { DebugInfo::ref_from_raw(BNGetDebugInfo(self.as_ref().handle)) }
+    /// The address of the next occurrence of `data` at or after `start`.
+    fn find_next_data(&self, start: u64, data: &[u8], flags: FindFlag) -> Option<u64> {
+        let buffer = DataBuffer::new(data).ok()?;
+        let mut result = 0;
+        let found = unsafe {
+            BNFindNextData(
+                self.as_ref().handle,
+                start,
+                buffer.as_raw(),
+                &mut result,
+                flags,
+            )
+        };
+        found.then_some(result)
     }
 
-    fn set_debug_info(&self, debug_info: &DebugInfo) {
-        unsafe { BNSetDebugInfo(self.as_ref().handle, debug_info.handle) }
+    /// The address of the next line at or after `start` containing `text`, when rendered in
+    /// `view_type` with `settings`.
+    fn find_next_text(
+        &self,
+        start: u64,
+        text: &str,
+        settings: &DisassemblySettings,
+        flags: FindFlag,
+        view_type: FunctionViewType,
+    ) -> Option<u64> {
+        let text = text.into_bytes_with_nul();
+        let raw_view_type = FunctionViewType::into_raw(view_type);
+        let mut result = 0;
+        let found = unsafe {
+            BNFindNextText(
+                self.as_ref().handle,
+                start,
+                text.as_ptr() as *const c_char,
+                &mut result,
+                settings.handle,
+                flags,
+                raw_view_type,
+            )
+        };
+        FunctionViewType::free_raw(raw_view_type);
+        found.then_some(result)
+    }
+
+    /// The address of the next instruction at or after `start` using `constant`.
+    fn find_next_constant(
+        &self,
+        start: u64,
+        constant: u64,
+        settings: &DisassemblySettings,
+        view_type: FunctionViewType,
+    ) -> Option<u64> {
+        let raw_view_type = FunctionViewType::into_raw(view_type);
+        let mut result = 0;
+        let found = unsafe {
+            BNFindNextConstant(
+                self.as_ref().handle,
+                start,
+                constant,
+                &mut result,
+                settings.handle,
+                raw_view_type,
+            )
+        };
+        FunctionViewType::free_raw(raw_view_type);
+        found.then_some(result)
+    }
+
+    /// Find every occurrence of `data` in `range`, passing each to `on_match` until it returns
+    /// `false`. Returns whether the search ran to completion.
+    fn find_all_data<P, F>(
+        &self,
+        range: Range<u64>,
+        data: &[u8],
+        flags: FindFlag,
+        mut progress: P,
+        mut on_match: F,
+    ) -> bool
+    where
+        P: ProgressCallback,
+        F: FnMut(u64, &[u8]) -> bool,
+    {
+        unsafe extern "C" fn cb_match<F: FnMut(u64, &[u8]) -> bool>(
+            ctxt: *mut c_void,
+            addr: u64,
+            matched: *mut BNDataBuffer,
+        ) -> bool {
+            let on_match = &mut *(ctxt as *mut F);
+            // The match is owned by the core
+            let matched = std::mem::ManuallyDrop::new(DataBuffer::from_raw(matched));
+            on_match(addr, matched.get_data())
+        }
+
+        let Ok(buffer) = DataBuffer::new(data) else {
+            return false;
+        };
+        unsafe {
+            BNFindAllDataWithProgress(
+                self.as_ref().handle,
+                range.start,
+                range.end,
+                buffer.as_raw(),
+                flags,
+                &mut progress as *mut P as *mut c_void,
+                Some(P::cb_progress_callback),
+                &mut on_match as *mut F as *mut c_void,
+                Some(cb_match::<F>),
+            )
+        }
+    }
+
+    /// Match `pattern` over every segment of the view, scanning on all available threads.
+    ///
+    /// Matches are passed to `on_match` in address order until it returns `false`, and
+    /// `progress` is called with the number of chunks scanned. Returns whether the search ran to
+    /// completion.
+    fn find_all<P, F>(&self, pattern: &BytePattern, progress: P, on_match: F) -> bool
+    where
+        P: ProgressCallback,
+        F: FnMut(u64) -> bool,
+    {
+        search::find_all(self.as_ref(), pattern, progress, on_match)
+    }
+
+    /// The address of every match of `pattern`, see [`BinaryViewExt::find_all`].
+    fn find_all_pattern(&self, pattern: &BytePattern) -> Vec<u64> {
+        let mut matches = Vec::new();
+        self.find_all(pattern, NoProgressCallback, |address| {
+            matches.push(address);
+            true
+        });
+        matches
+    }
+
+    /// Run `query` in the core, passing the address and bytes of each match to `on_match` until
+    /// it returns `false`. Returns whether the search ran to completion.
+    fn search<F>(&self, query: &SearchQuery, mut on_match: F) -> bool
+    where
+        F: FnMut(u64, &[u8]) -> bool,
+    {
+        unsafe extern "C" fn cb_match<F: FnMut(u64, &[u8]) -> bool>(
+            ctxt: *mut c_void,
+            addr: u64,
+            matched: *mut BNDataBuffer,
+        ) -> bool {
+            let on_match = &mut *(ctxt as *mut F);
+            // The match is owned by the core
+            let matched = std::mem::ManuallyDrop::new(DataBuffer::from_raw(matched));
+            on_match(addr, matched.get_data())
+        }
+
+        let query = query.to_json().into_bytes_with_nul();
+        unsafe {
+            BNSearch(
+                self.as_ref().handle,
+                query.as_ptr() as *const c_char,
+                &mut on_match as *mut F as *mut c_void,
+                Some(cb_match::<F>),
+            )
+        }
+    }
+
+    fn debug_info(&self) -> Ref<DebugInfo> {
+        unsafe { DebugInfo::ref_from_raw(BNGetDebugInfo(self.as_ref().handle)) }
+    }
+
+    fn set_debug_info(&self, debug_info: &DebugInfo) {
+        unsafe { BNSetDebugInfo(self.as_ref().handle, debug_info.handle) }
     }
 
     fn apply_debug_info(&self, debug_info: &DebugInfo) {
@@ -1115,7 +2076,7 @@ pub trait BinaryViewExt: BinaryViewBase {
         };
 
         if settings_handle.is_null() {
-            Err(())
+            Err(Error::NullHandle("BNBinaryViewGetLoadSettings"))
         } else {
             Ok(unsafe { Settings::from_raw(settings_handle) })
         }
@@ -1207,6 +2168,96 @@ pub trait BinaryViewExt: BinaryViewBase {
         unsafe { BNRemoveUserDataTag(self.as_ref().handle, addr, tag.handle) }
     }
 
+    /// The tag types of the view.
+    fn tag_types(&self) -> Array<TagType> {
+        let mut count = 0;
+        let handle = unsafe { BNGetTagTypes(self.as_ref().handle, &mut count) };
+        unsafe { Array::new(handle, count, ()) }
+    }
+
+    /// Every use of a tag in the view: data tags, function tags and address tags of functions.
+    fn all_tags(&self) -> Array<TagReference> {
+        let mut count = 0;
+        let handle = unsafe { BNGetAllTagReferences(self.as_ref().handle, &mut count) };
+        unsafe { Array::new(handle, count, ()) }
+    }
+
+    /// Every use of a tag of `tag_type` in the view, see [`BinaryViewExt::all_tags`].
+    fn all_tags_of_type(&self, tag_type: &TagType) -> Array<TagReference> {
+        let mut count = 0;
+        let handle = unsafe {
+            BNGetAllTagReferencesOfType(self.as_ref().handle, tag_type.handle, &mut count)
+        };
+        unsafe { Array::new(handle, count, ()) }
+    }
+
+    /// The data tags of the view, tags at addresses outside of functions.
+    ///
+    /// * `auto` - If `None`, gets all tags, if `true`, gets auto tags, if `false`, gets user tags
+    fn data_tags(&self, auto: Option<bool>) -> Array<TagReference> {
+        let mut count = 0;
+        let handle = unsafe {
+            match auto {
+                None => BNGetDataTagReferences(self.as_ref().handle, &mut count),
+                Some(true) => BNGetAutoDataTagReferences(self.as_ref().handle, &mut count),
+                Some(false) => BNGetUserDataTagReferences(self.as_ref().handle, &mut count),
+            }
+        };
+        unsafe { Array::new(handle, count, ()) }
+    }
+
+    /// The data tags at `addr`.
+    ///
+    /// * `auto` - If `None`, gets all tags, if `true`, gets auto tags, if `false`, gets user tags
+    fn data_tags_at(&self, addr: u64, auto: Option<bool>) -> Array<Tag> {
+        let mut count = 0;
+        let handle = unsafe {
+            match auto {
+                None => BNGetDataTags(self.as_ref().handle, addr, &mut count),
+                Some(true) => BNGetAutoDataTags(self.as_ref().handle, addr, &mut count),
+                Some(false) => BNGetUserDataTags(self.as_ref().handle, addr, &mut count),
+            }
+        };
+        unsafe { Array::new(handle, count, ()) }
+    }
+
+    /// The data tags of `tag_type` at `addr`.
+    ///
+    /// * `auto` - If `None`, gets all tags, if `true`, gets auto tags, if `false`, gets user tags
+    fn data_tags_of_type_at(
+        &self,
+        addr: u64,
+        tag_type: &TagType,
+        auto: Option<bool>,
+    ) -> Array<Tag> {
+        let view = self.as_ref().handle;
+        let mut count = 0;
+        let handle = unsafe {
+            match auto {
+                None => BNGetDataTagsOfType(view, addr, tag_type.handle, &mut count),
+                Some(true) => BNGetAutoDataTagsOfType(view, addr, tag_type.handle, &mut count),
+                Some(false) => BNGetUserDataTagsOfType(view, addr, tag_type.handle, &mut count),
+            }
+        };
+        unsafe { Array::new(handle, count, ()) }
+    }
+
+    /// The data tags in `range`.
+    ///
+    /// * `auto` - If `None`, gets all tags, if `true`, gets auto tags, if `false`, gets user tags
+    fn data_tags_in_range(&self, range: Range<u64>, auto: Option<bool>) -> Array<TagReference> {
+        let view = self.as_ref().handle;
+        let mut count = 0;
+        let handle = unsafe {
+            match auto {
+                None => BNGetDataTagsInRange(view, range.start, range.end, &mut count),
+                Some(true) => BNGetAutoDataTagsInRange(view, range.start, range.end, &mut count),
+                Some(false) => BNGetUserDataTagsInRange(view, range.start, range.end, &mut count),
+            }
+        };
+        unsafe { Array::new(handle, count, ()) }
+    }
+
     /// Retrieves a list of the next disassembly lines.
     ///
     /// `get_next_linear_disassembly_lines` retrieves an [Array] over [LinearDisassemblyLine] objects for the
@@ -1274,7 +2325,7 @@ pub trait BinaryViewExt: BinaryViewBase {
         T: for<'a> TryFrom<&'a Metadata>,
     {
         self.query_metadata(key)
-            .map(|md| T::try_from(md.as_ref()).map_err(|_| ()))
+            .map(|md| T::try_from(md.as_ref()).map_err(|_| Error::TypeMismatch))
     }
 
     fn store_metadata<V, S: BnStrCompatible>(&self, key: S, value: V, is_auto: bool)
@@ -1292,6 +2343,81 @@ pub trait BinaryViewExt: BinaryViewBase {
         };
     }
 
+    /// The metadata of the view, a store of every key with its value.
+    fn metadata(&self) -> Ref<Metadata> {
+        unsafe { Metadata::ref_from_raw(BNBinaryViewGetMetadata(self.as_ref().handle)) }
+    }
+
+    /// The store of the keys stored with `is_auto` set.
+    fn auto_metadata(&self) -> Ref<Metadata> {
+        unsafe { Metadata::ref_from_raw(BNBinaryViewGetAutoMetadata(self.as_ref().handle)) }
+    }
+
+    /// The keys with metadata, sorted.
+    fn metadata_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .metadata()
+            .get_value_store()
+            .map(|store| store.into_keys().map(|key| key.to_string()).collect())
+            .unwrap_or_default();
+        keys.sort();
+        keys
+    }
+
+    /// The metadata of the keys starting with `prefix`, e.g. `"myPlugin."` for the keys of a
+    /// plugin.
+    fn metadata_with_prefix<S: AsRef<str>>(&self, prefix: S) -> BTreeMap<String, Ref<Metadata>> {
+        let prefix = prefix.as_ref();
+        self.metadata()
+            .get_value_store()
+            .map(|store| {
+                store
+                    .into_iter()
+                    .filter(|(key, _)| key.as_str().starts_with(prefix))
+                    .map(|(key, value)| (key.to_string(), value))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Remove the metadata of the keys starting with `prefix`, returning how many were removed.
+    fn remove_metadata_with_prefix<S: AsRef<str>>(&self, prefix: S) -> usize {
+        let keys = self
+            .metadata_with_prefix(prefix)
+            .into_keys()
+            .collect::<Vec<_>>();
+        for key in &keys {
+            self.remove_metadata(key.as_str());
+        }
+        keys.len()
+    }
+
+    /// Store `value` serialized into metadata, see [`to_metadata`](crate::metadata::to_metadata).
+    #[cfg(feature = "serde")]
+    fn store_serde_metadata<T, S: BnStrCompatible>(
+        &self,
+        key: S,
+        value: &T,
+        is_auto: bool,
+    ) -> Result<()>
+    where
+        T: serde::Serialize + ?Sized,
+    {
+        let md = crate::metadata::to_metadata(value)?;
+        self.store_metadata(key, md, is_auto);
+        Ok(())
+    }
+
+    /// The metadata of `key` deserialized into a `T`, see [`BinaryViewExt::store_serde_metadata`].
+    #[cfg(feature = "serde")]
+    fn get_serde_metadata<T, S: BnStrCompatible>(&self, key: S) -> Option<Result<T>>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.query_metadata(key)
+            .map(|md| crate::metadata::from_metadata(&md))
+    }
+
     fn remove_metadata<S: BnStrCompatible>(&self, key: S) {
         unsafe {
             BNBinaryViewRemoveMetadata(
@@ -1371,6 +2497,117 @@ pub trait BinaryViewExt: BinaryViewBase {
         }
     }
 
+    /// Retrieves the instructions which access the data variable at `addr`, classified by how they
+    /// access it.
+    ///
+    /// Unlike [`Self::code_refs_to_addr`] this inspects the MLIL of each referencing function, so
+    /// callers can tell the instructions initializing a global apart from those only reading it.
+    /// If no data variable is defined at `addr` only accesses of that exact address are considered.
+    ///
+    /// NOTE: This will generate MLIL for every function referencing the data variable.
+    fn data_variable_accesses(&self, addr: u64) -> Vec<DataVariableAccess> {
+        let width = self
+            .data_variable_at_address(addr)
+            .map(|var| var.ty.contents.width())
+            .unwrap_or(1)
+            .max(1);
+        let range = addr..addr.saturating_add(width);
+
+        let mut visited = HashSet::new();
+        let mut accesses = Vec::new();
+        for code_ref in &self.code_refs_into_range(range.clone()) {
+            let Some(func) = code_ref.func else {
+                continue;
+            };
+            let Ok(mlil) = func.medium_level_il() else {
+                continue;
+            };
+            let location = (func.arch(), code_ref.address);
+            let Some(instr) = mlil.instruction_at(location) else {
+                continue;
+            };
+            // Multiple references can resolve to the same MLIL instruction.
+            if !visited.insert((func.start(), instr.expr_index.0)) {
+                continue;
+            }
+
+            let mut kinds = Vec::new();
+            classify_data_accesses(&instr.lift(), &range, &mut kinds);
+            accesses.extend(kinds.into_iter().map(|kind| DataVariableAccess {
+                kind,
+                instruction: instr.clone(),
+            }));
+        }
+        accesses
+    }
+
+    /// Retrieves the instructions which store to the data variable at `addr`.
+    ///
+    /// See [`Self::data_variable_accesses`] for more information.
+    fn data_variable_writers(&self, addr: u64) -> Vec<DataVariableAccess> {
+        self.data_variable_accesses(addr)
+            .into_iter()
+            .filter(|access| access.kind == DataVariableAccessKind::Write)
+            .collect()
+    }
+
+    /// Retrieves the instructions which load from the data variable at `addr`.
+    ///
+    /// See [`Self::data_variable_accesses`] for more information.
+    fn data_variable_readers(&self, addr: u64) -> Vec<DataVariableAccess> {
+        self.data_variable_accesses(addr)
+            .into_iter()
+            .filter(|access| access.kind == DataVariableAccessKind::Read)
+            .collect()
+    }
+
+    /// Adds a user-defined data cross-reference from the data at `from_addr` to `to_addr`.
+    ///
+    /// User data references will be added to the undo buffer.
+    fn add_user_data_ref(&self, from_addr: u64, to_addr: u64) {
+        unsafe { BNAddUserDataReference(self.as_ref().handle, from_addr, to_addr) }
+    }
+
+    /// Removes a user-defined data cross-reference, if there is no such cross-reference no action
+    /// is performed.
+    fn remove_user_data_ref(&self, from_addr: u64, to_addr: u64) {
+        unsafe { BNRemoveUserDataReference(self.as_ref().handle, from_addr, to_addr) }
+    }
+
+    /// Adds a user-defined code cross-reference from the instruction at `from_addr` to `to_addr`
+    /// in every function containing `from_addr`.
+    ///
+    /// See [`Function::add_user_code_ref`] to add the reference to a single function.
+    fn add_user_code_ref(&self, from_addr: u64, to_addr: u64) {
+        for func in &self.functions_containing(from_addr) {
+            func.add_user_code_ref(from_addr, to_addr, None);
+        }
+    }
+
+    /// Removes a user-defined code cross-reference from every function containing `from_addr`.
+    fn remove_user_code_ref(&self, from_addr: u64, to_addr: u64) {
+        for func in &self.functions_containing(from_addr) {
+            func.remove_user_code_ref(from_addr, to_addr, None);
+        }
+    }
+
+    /// Adds a user-defined type cross-reference from the instruction at `from_addr` to the type
+    /// `name` in every function containing `from_addr`.
+    fn add_user_type_ref<T: Into<QualifiedName>>(&self, from_addr: u64, name: T) {
+        let name = name.into();
+        for func in &self.functions_containing(from_addr) {
+            func.add_user_type_ref(from_addr, name.clone(), None);
+        }
+    }
+
+    /// Removes a user-defined type cross-reference from every function containing `from_addr`.
+    fn remove_user_type_ref<T: Into<QualifiedName>>(&self, from_addr: u64, name: T) {
+        let name = name.into();
+        for func in &self.functions_containing(from_addr) {
+            func.remove_user_type_ref(from_addr, name.clone(), None);
+        }
+    }
+
     /// Retrieves a list of [CodeReference]s for locations in code that use a given named type.
     fn code_refs_using_type_name<T: Into<QualifiedName>>(&self, name: T) -> Array<CodeReference> {
         let mut raw_name = QualifiedName::into_raw(name.into());
@@ -1395,6 +2632,51 @@ pub trait BinaryViewExt: BinaryViewBase {
         }
     }
 
+    /// Defines a relocation at `addr` that writes the address `target`, as described by `info`.
+    ///
+    /// The relocation is applied by the relocation handler `arch` registered for this view type,
+    /// so loaders should define relocations while the view is being initialized.
+    fn define_relocation<A: Architecture>(
+        &self,
+        arch: &A,
+        info: &RelocationInfo,
+        target: u64,
+        addr: u64,
+    ) {
+        let mut raw_info = info.as_raw();
+        unsafe {
+            BNDefineRelocation(
+                self.as_ref().handle,
+                arch.as_ref().handle,
+                &mut raw_info,
+                target,
+                addr,
+            )
+        }
+    }
+
+    /// Defines a relocation at `addr` that writes the address of the symbol `target`.
+    ///
+    /// Use this for imports, where the address of the symbol is only known once it is resolved.
+    fn define_symbol_relocation<A: Architecture>(
+        &self,
+        arch: &A,
+        info: &RelocationInfo,
+        target: &Symbol,
+        addr: u64,
+    ) {
+        let mut raw_info = info.as_raw();
+        unsafe {
+            BNDefineSymbolRelocation(
+                self.as_ref().handle,
+                arch.as_ref().handle,
+                &mut raw_info,
+                target.handle,
+                addr,
+            )
+        }
+    }
+
     fn relocations_at(&self, addr: u64) -> Array<Relocation> {
         unsafe {
             let mut count = 0;
@@ -1422,6 +2704,25 @@ pub trait BinaryViewExt: BinaryViewBase {
             .collect()
     }
 
+    /// The ranges written by relocations that overlap `range`.
+    fn relocation_ranges_in(&self, range: Range<u64>) -> Vec<Range<u64>> {
+        unsafe {
+            let mut count = 0;
+            let raw_ranges = BNGetRelocationRangesInRange(
+                self.as_ref().handle,
+                range.start,
+                range.end.saturating_sub(range.start),
+                &mut count,
+            );
+            let ranges = std::slice::from_raw_parts(raw_ranges, count)
+                .iter()
+                .map(|range| range.start..range.end)
+                .collect();
+            BNFreeRelocationRanges(raw_ranges);
+            ranges
+        }
+    }
+
     fn component_by_guid<S: BnStrCompatible>(&self, guid: S) -> Option<Ref<Component>> {
         let name = guid.into_bytes_with_nul();
         let result = unsafe {
@@ -1449,6 +2750,40 @@ pub trait BinaryViewExt: BinaryViewBase {
         NonNull::new(result).map(|h| unsafe { Component::ref_from_raw(h) })
     }
 
+    /// Creates a new component named `name`, placed under `parent` or the root component.
+    ///
+    /// Sibling components with the same name are given a unique display name.
+    fn create_component<S: Into<String>>(
+        &self,
+        name: S,
+        parent: Option<&Component>,
+    ) -> Ref<Component> {
+        let builder = ComponentBuilder::new(self.as_ref().to_owned()).name(name);
+        match parent {
+            Some(parent) => builder.parent(parent.guid().to_string()).finalize(),
+            None => builder.finalize(),
+        }
+    }
+
+    /// The component at the end of `path`, a list of component names starting below the root
+    /// component, creating any that do not exist yet.
+    fn create_component_path<S: AsRef<str>>(&self, path: &[S]) -> Option<Ref<Component>> {
+        let mut component = self.root_component()?;
+        for name in path {
+            let name = name.as_ref();
+            let child = component
+                .components()
+                .iter()
+                .find(|child| child.name().as_str() == name)
+                .map(|child| child.to_owned());
+            component = match child {
+                Some(child) => child,
+                None => self.create_component(name, Some(&component)),
+            };
+        }
+        Some(component)
+    }
+
     fn remove_component(&self, component: &Component) -> bool {
         unsafe { BNRemoveComponent(self.as_ref().handle, component.handle.as_ptr()) }
     }
@@ -1609,6 +2944,13 @@ pub trait BinaryViewExt: BinaryViewBase {
         NonNull::new(result).map(|h| unsafe { TypeLibrary::from_raw(h) })
     }
 
+    /// The type libraries available for type/import resolution, see [`BinaryViewExt::add_type_library`].
+    fn type_libraries(&self) -> Array<TypeLibrary> {
+        let mut count = 0;
+        let result = unsafe { BNGetBinaryViewTypeLibraries(self.as_ref().handle, &mut count) };
+        unsafe { Array::new(result, count, ()) }
+    }
+
     /// Should be called by custom py:py:class:`BinaryView` implementations
     /// when they have successfully imported an object from a type library (eg a symbol's type).
     /// Values recorded with this function will then be queryable via [BinaryViewExt::lookup_imported_object_library].
@@ -1806,27 +3148,543 @@ pub trait BinaryViewExt: BinaryViewBase {
         let name = QualifiedName::from_owned_raw(result_name);
         Some((lib, name))
     }
-    //
-    // fn type_archives(&self) -> Array<TypeArchive> {
-    //     let mut ids: *mut *mut c_char = std::ptr::null_mut();
-    //     let mut paths: *mut *mut c_char = std::ptr::null_mut();
-    //     let count = unsafe { BNBinaryViewGetTypeArchives(self.as_ref().handle, &mut ids, &mut paths) };
-    //     let path_list = unsafe { Array::<BnString>::new(paths, count, ()) };
-    //     let ids_list = unsafe { std::slice::from_raw_parts(ids, count).to_vec() };
-    //     let archives = ids_list.iter().filter_map(|id| {
-    //         let archive_raw = unsafe { BNBinaryViewGetTypeArchive(self.as_ref().handle, *id) };
-    //         match archive_raw.is_null() {
-    //             true => None,
-    //             false => Some(archive_raw)
-    //         }
-    //     }).collect();
-    //     unsafe { BNFreeStringList(ids, count) };
-    //     Array::new(archives)
-    // }
+
+    /// Type the imports of the view with the objects of its type libraries, the way the loaders
+    /// of the standard formats do, for views whose loader doesn't.
+    ///
+    /// Import address symbols become pointers to the type of the import, and imported functions
+    /// and data are given the type itself. Imports are looked up by raw name, without the
+    /// `__imp_` prefix of import address symbols, in the libraries of
+    /// [`BinaryViewExt::type_libraries`] in order. Where the import was found is recorded, see
+    /// [`BinaryViewExt::lookup_imported_object_library`].
+    fn apply_type_library_to_imports(&self) -> Result<ImportTypingReport> {
+        let libraries = self.type_libraries();
+        let platform = self.default_platform();
+        let mut report = ImportTypingReport::default();
+        for sym_type in [
+            SymbolType::ImportAddress,
+            SymbolType::ImportedFunction,
+            SymbolType::ImportedData,
+            SymbolType::External,
+        ] {
+            for symbol in self.symbols_of_type(sym_type).iter() {
+                let raw_name = symbol.raw_name();
+                let raw_name = raw_name.as_str();
+                let name = match sym_type {
+                    SymbolType::ImportAddress => {
+                        raw_name.strip_prefix("__imp_").unwrap_or(raw_name)
+                    }
+                    _ => raw_name,
+                };
+                let found = libraries
+                    .iter()
+                    .find(|lib| lib.get_named_object(QualifiedName::from(name)).is_some())
+                    .and_then(|lib| {
+                        let ty = self.import_type_object(name, Some(lib.new_reference()))?;
+                        Some((lib, ty))
+                    });
+                let Some((lib, ty)) = found else {
+                    report.unmatched.push(symbol.to_owned());
+                    continue;
+                };
+
+                let addr = symbol.address();
+                match (sym_type, &platform) {
+                    (SymbolType::ImportAddress, _) => {
+                        let pointer =
+                            Type::pointer_of_width(&ty, self.address_size(), false, false, None);
+                        self.define_auto_data_var(addr, &pointer);
+                    }
+                    (_, Some(platform)) => {
+                        self.define_auto_symbol_with_type(&symbol, platform, ty.as_ref())?;
+                    }
+                    (_, None) => self.define_auto_data_var(addr, &ty),
+                }
+                if let Some(platform) = &platform {
+                    self.record_imported_object_library(lib, name, addr, platform);
+                }
+                report.applied.push(TypedImport {
+                    symbol: symbol.to_owned(),
+                    library: lib.name().map(|name| name.to_string()).unwrap_or_default(),
+                    name: name.to_string(),
+                    ty,
+                });
+            }
+        }
+        Ok(report)
+    }
+
+    /// The ids and paths of the type archives attached to the view, connected or not.
+    fn attached_type_archives(&self) -> HashMap<String, PathBuf> {
+        let mut ids = std::ptr::null_mut();
+        let mut paths = std::ptr::null_mut();
+        let count =
+            unsafe { BNBinaryViewGetTypeArchives(self.as_ref().handle, &mut ids, &mut paths) };
+        let ids = unsafe { Array::<BnString>::new(ids, count, ()) };
+        let paths = unsafe { Array::<BnString>::new(paths, count, ()) };
+        ids.iter()
+            .zip(paths.iter())
+            .map(|(id, path)| (id.to_string(), PathBuf::from(path.to_string())))
+            .collect()
+    }
+
+    /// The attached type archives that are connected, which are those whose files could be
+    /// opened.
+    fn type_archives(&self) -> Vec<Ref<TypeArchive>> {
+        self.attached_type_archives()
+            .keys()
+            .filter_map(|id| self.type_archive_by_id(id.as_str()))
+            .collect()
+    }
+
+    /// The attached type archive with the id `id`, if it is connected.
+    fn type_archive_by_id<S: BnStrCompatible>(&self, id: S) -> Option<Ref<TypeArchive>> {
+        let id = id.into_bytes_with_nul();
+        let result = unsafe {
+            BNBinaryViewGetTypeArchive(self.as_ref().handle, id.as_ref().as_ptr() as *const c_char)
+        };
+        NonNull::new(result).map(|handle| unsafe { TypeArchive::ref_from_raw(handle) })
+    }
+
+    /// The path of the attached type archive with the id `id`.
+    fn type_archive_path<S: BnStrCompatible>(&self, id: S) -> Option<PathBuf> {
+        let id = id.into_bytes_with_nul();
+        let result = unsafe {
+            BNBinaryViewGetTypeArchivePath(
+                self.as_ref().handle,
+                id.as_ref().as_ptr() as *const c_char,
+            )
+        };
+        (!result.is_null())
+            .then(|| PathBuf::from(unsafe { BnString::from_raw(result) }.to_string()))
+    }
+
+    /// Attach `archive` to the view, so its types can be pulled into and pushed from the view.
+    ///
+    /// Returns the connected archive, `None` if it couldn't be attached.
+    fn attach_type_archive(&self, archive: &TypeArchive) -> Option<Ref<TypeArchive>> {
+        let id = archive.id()?;
+        let path = archive.path()?;
+        let path = path.into_bytes_with_nul();
+        let result = unsafe {
+            BNBinaryViewAttachTypeArchive(
+                self.as_ref().handle,
+                id.as_ptr(),
+                path.as_ptr() as *const c_char,
+            )
+        };
+        NonNull::new(result).map(|handle| unsafe { TypeArchive::ref_from_raw(handle) })
+    }
+
+    /// Detach the type archive with the id `id`, the types pulled from it stay in the view.
+    fn detach_type_archive<S: BnStrCompatible>(&self, id: S) -> bool {
+        let id = id.into_bytes_with_nul();
+        unsafe {
+            BNBinaryViewDetachTypeArchive(
+                self.as_ref().handle,
+                id.as_ref().as_ptr() as *const c_char,
+            )
+        }
+    }
+
+    /// Pull types from the attached `archive` into the view, along with the types they
+    /// reference.
+    ///
+    /// Returns the ids of the updated types in the archive mapped to their ids in the view.
+    fn pull_types_from_archive<I, S>(
+        &self,
+        archive: &TypeArchive,
+        archive_type_ids: I,
+    ) -> Option<HashMap<String, String>>
+    where
+        I: IntoIterator<Item = S>,
+        S: BnStrCompatible,
+    {
+        let archive_id = archive.id()?;
+        let type_ids: Vec<_> = archive_type_ids
+            .into_iter()
+            .map(|id| id.into_bytes_with_nul())
+            .collect();
+        let type_ids_raw: Vec<*const c_char> = type_ids
+            .iter()
+            .map(|id| id.as_ref().as_ptr() as *const c_char)
+            .collect();
+        let mut updated_archive_type_ids = std::ptr::null_mut();
+        let mut updated_view_type_ids = std::ptr::null_mut();
+        let mut count = 0;
+        let success = unsafe {
+            BNBinaryViewPullTypeArchiveTypes(
+                self.as_ref().handle,
+                archive_id.as_ptr(),
+                type_ids_raw.as_ptr(),
+                type_ids_raw.len(),
+                &mut updated_archive_type_ids,
+                &mut updated_view_type_ids,
+                &mut count,
+            )
+        };
+        if !success {
+            return None;
+        }
+        let archive_type_ids =
+            unsafe { Array::<BnString>::new(updated_archive_type_ids, count, ()) };
+        let view_type_ids = unsafe { Array::<BnString>::new(updated_view_type_ids, count, ()) };
+        Some(
+            archive_type_ids
+                .iter()
+                .zip(view_type_ids.iter())
+                .map(|(archive_id, view_id)| (archive_id.to_string(), view_id.to_string()))
+                .collect(),
+        )
+    }
+
+    /// Push types of the view to the attached `archive`, along with the types they reference.
+    ///
+    /// Returns the ids of the updated types in the view mapped to their ids in the archive.
+    fn push_types_to_archive<I, S>(
+        &self,
+        archive: &TypeArchive,
+        type_ids: I,
+    ) -> Option<HashMap<String, String>>
+    where
+        I: IntoIterator<Item = S>,
+        S: BnStrCompatible,
+    {
+        let archive_id = archive.id()?;
+        let type_ids: Vec<_> = type_ids
+            .into_iter()
+            .map(|id| id.into_bytes_with_nul())
+            .collect();
+        let type_ids_raw: Vec<*const c_char> = type_ids
+            .iter()
+            .map(|id| id.as_ref().as_ptr() as *const c_char)
+            .collect();
+        let mut updated_view_type_ids = std::ptr::null_mut();
+        let mut updated_archive_type_ids = std::ptr::null_mut();
+        let mut count = 0;
+        let success = unsafe {
+            BNBinaryViewPushTypeArchiveTypes(
+                self.as_ref().handle,
+                archive_id.as_ptr(),
+                type_ids_raw.as_ptr(),
+                type_ids_raw.len(),
+                &mut updated_view_type_ids,
+                &mut updated_archive_type_ids,
+                &mut count,
+            )
+        };
+        if !success {
+            return None;
+        }
+        let view_type_ids = unsafe { Array::<BnString>::new(updated_view_type_ids, count, ()) };
+        let archive_type_ids =
+            unsafe { Array::<BnString>::new(updated_archive_type_ids, count, ()) };
+        Some(
+            view_type_ids
+                .iter()
+                .zip(archive_type_ids.iter())
+                .map(|(view_id, archive_id)| (view_id.to_string(), archive_id.to_string()))
+                .collect(),
+        )
+    }
+
+    /// The id of the archive the view type with id `type_id` was pulled from or pushed to, and
+    /// its id in that archive.
+    fn associated_type_archive_type<S: BnStrCompatible>(
+        &self,
+        type_id: S,
+    ) -> Option<(BnString, BnString)> {
+        let type_id = type_id.into_bytes_with_nul();
+        let mut archive_id = std::ptr::null_mut();
+        let mut archive_type_id = std::ptr::null_mut();
+        let success = unsafe {
+            BNBinaryViewGetAssociatedTypeArchiveTypeTarget(
+                self.as_ref().handle,
+                type_id.as_ref().as_ptr() as *const c_char,
+                &mut archive_id,
+                &mut archive_type_id,
+            )
+        };
+        success.then(|| unsafe {
+            (
+                BnString::from_raw(archive_id),
+                BnString::from_raw(archive_type_id),
+            )
+        })
+    }
+
+    /// Whether the view type with id `type_id` differs from its type in the archive it is
+    /// associated with, and which side changed.
+    fn type_archive_sync_status<S: BnStrCompatible>(&self, type_id: S) -> TypeArchiveSyncStatus {
+        let type_id = type_id.into_bytes_with_nul();
+        unsafe {
+            BNBinaryViewGetTypeArchiveSyncStatus(
+                self.as_ref().handle,
+                type_id.as_ref().as_ptr() as *const c_char,
+            )
+        }
+    }
+
+    /// Stop syncing the view type with id `type_id` with the archive it is associated with.
+    fn disassociate_type_archive_type<S: BnStrCompatible>(&self, type_id: S) -> bool {
+        let type_id = type_id.into_bytes_with_nul();
+        unsafe {
+            BNBinaryViewDisassociateTypeArchiveType(
+                self.as_ref().handle,
+                type_id.as_ref().as_ptr() as *const c_char,
+            )
+        }
+    }
 }
 
 impl<T: BinaryViewBase> BinaryViewExt for T {}
 
+/// Metadata key under which user system call overrides are stored, see [`BinaryViewExt::set_user_system_call`].
+const SYSTEM_CALL_OVERRIDES_KEY: &str = "system_call_overrides";
+
+/// The error of a string read at `offset` that found no terminator within `max_len` bytes, of
+/// which `read` could be read.
+fn unterminated_string(offset: u64, max_len: usize, read: usize) -> Error {
+    match read > max_len {
+        true => Error::Parse(format!(
+            "string at {:#x}, no terminator within {} bytes",
+            offset, max_len
+        )),
+        false => short_read(offset, read + 1),
+    }
+}
+
+/// The error of a string at `offset` that is not valid in `encoding`.
+fn invalid_string(offset: u64, encoding: StringType) -> Error {
+    let name = match encoding {
+        StringType::AsciiString => "ASCII",
+        StringType::Utf8String => "UTF-8",
+        StringType::Utf16String => "UTF-16",
+        StringType::Utf32String => "UTF-32",
+    };
+    Error::Parse(format!("{} string at {:#x}", name, offset))
+}
+
+fn read_terminated_string(
+    view: &BinaryView,
+    offset: u64,
+    max_len: usize,
+    encoding: StringType,
+    endianness: Endianness,
+) -> Result<String> {
+    let unit_size = code_unit_size(encoding);
+    let bytes = view.read_vec(offset, max_len.saturating_add(1).saturating_mul(unit_size));
+    let Some(len) = find_string_terminator(&bytes, encoding) else {
+        return Err(unterminated_string(
+            offset,
+            max_len.saturating_mul(unit_size),
+            bytes.len(),
+        ));
+    };
+    decode_string(&bytes[..len * unit_size], encoding, endianness)
+        .ok_or_else(|| invalid_string(offset, encoding))
+}
+
+fn define_symbols<T, S, P, F>(view: &BinaryView, symbols: T, mut progress: P, define: F) -> usize
+where
+    T: IntoIterator<Item = S>,
+    S: AsRef<Symbol>,
+    P: ProgressCallback,
+    F: Fn(&BinaryView, &Symbol),
+{
+    let symbols: Vec<S> = symbols.into_iter().collect();
+    let _bulk = view.bulk_modify_symbols();
+    for (index, sym) in symbols.iter().enumerate() {
+        define(view, sym.as_ref());
+        // SAFETY: The context is the progress callback the function is called for
+        let keep_going =
+            unsafe { P::cb_progress_callback(progress.into_raw(), index + 1, symbols.len()) };
+        if !keep_going {
+            return index + 1;
+        }
+    }
+    symbols.len()
+}
+
+/// The error of a read of `len` bytes at `offset` that went past the end of the view.
+fn short_read(offset: u64, len: usize) -> Error {
+    Error::Io(std::io::Error::new(
+        std::io::ErrorKind::UnexpectedEof,
+        format!("read of {} bytes at {:#x} is out of bounds", len, offset),
+    ))
+}
+
+/// The segments of the view in address order with overlaps merged, or the whole view if it has no
+/// segments.
+fn mapped_ranges(view: &BinaryView) -> Vec<Range<u64>> {
+    let mut segments: Vec<Range<u64>> = view
+        .segments()
+        .iter()
+        .map(|segment| segment.address_range())
+        .collect();
+    if segments.is_empty() {
+        let whole = view.start()..view.start() + view.len();
+        return vec![whole];
+    }
+    segments.sort_by_key(|range| range.start);
+
+    let mut ranges: Vec<Range<u64>> = Vec::with_capacity(segments.len());
+    for segment in segments {
+        match ranges.last_mut() {
+            Some(last) if segment.start <= last.end => last.end = last.end.max(segment.end),
+            _ => ranges.push(segment),
+        }
+    }
+    ranges
+}
+
+/// The first address after `address` a read of `view` could succeed at: the next valid offset, or
+/// the start of the next segment or section.
+fn next_readable_start(view: &BinaryView, address: u64) -> Option<u64> {
+    let segment_starts: Vec<u64> = view
+        .segments()
+        .iter()
+        .map(|segment| segment.address_range().start)
+        .collect();
+    let section_starts: Vec<u64> = view
+        .sections()
+        .iter()
+        .map(|section| section.start())
+        .collect();
+    std::iter::once(view.next_valid_offset_after(address))
+        .chain(segment_starts)
+        .chain(section_starts)
+        .filter(|&start| start > address)
+        .min()
+}
+
+/// Blocks of the mapped regions of a view, see [`BinaryViewExt::iter_blocks`].
+///
+/// Each block borrows a buffer reused for the next one, so this is not an [`Iterator`], use
+/// [`BlockIter::next_block`] in a `while let` loop instead.
+pub struct BlockIter<'a> {
+    view: &'a BinaryView,
+    /// Ranges left to read, the next one last.
+    ranges: Vec<Range<u64>>,
+    buffer: Vec<u8>,
+}
+
+impl BlockIter<'_> {
+    /// Read the next block, returning its address and contents.
+    pub fn next_block(&mut self) -> Option<(u64, &[u8])> {
+        loop {
+            let range = self.ranges.last_mut()?;
+            if range.start >= range.end {
+                self.ranges.pop();
+                continue;
+            }
+            let address = range.start;
+            let len = (range.end - address).min(self.buffer.len() as u64) as usize;
+            let read = self.view.read(&mut self.buffer[..len], address);
+            if read == 0 {
+                // Nothing can be read from here, skip to where the next read could succeed
+                let next = next_readable_start(self.view, address).unwrap_or(range.end);
+                range.start = next.min(range.end);
+                continue;
+            }
+            range.start += read as u64;
+            return Some((address, &self.buffer[..read]));
+        }
+    }
+}
+
+fn scratch_database_path(file: &FileMetadata) -> PathBuf {
+    static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    std::env::temp_dir().join(format!(
+        "binaryninja-scratch-{}-{}-{}.bndb",
+        std::process::id(),
+        file.session_id(),
+        id
+    ))
+}
+
+/// Copy of a view backed by a temporary database, see [`BinaryViewExt::duplicate_in_memory`] and
+/// [`Snapshot::open_scratch_view`](crate::database::snapshot::Snapshot::open_scratch_view).
+///
+/// Dereferences to the copied view. Dropping it closes the copy and deletes its database, so
+/// references to the view must not be kept past that.
+pub struct ScratchView {
+    view: Ref<BinaryView>,
+    path: PathBuf,
+}
+
+impl ScratchView {
+    /// Open the `view_type` view of a new temporary database, holding the file `contents` and the
+    /// analysis state `data` as a snapshot named `name`.
+    pub(crate) fn create(
+        file: &FileMetadata,
+        contents: &[u8],
+        data: &KeyValueStore,
+        cache: &KeyValueStore,
+        name: &str,
+        view_type: &str,
+    ) -> Result<Self> {
+        let scratch_file = FileMetadata::with_filename(file.filename().as_str());
+        let scratch_raw_view = BinaryView::from_data(&scratch_file, contents)?;
+        let path = scratch_database_path(file);
+        if !scratch_file.create_database(&path) {
+            return Err(Error::CoreCallFailed("BNCreateDatabase"));
+        }
+        let Some(database) = scratch_file.database() else {
+            scratch_file.close();
+            let _ = std::fs::remove_file(&path);
+            return Err(Error::NullHandle("BNGetFileMetadataDatabase"));
+        };
+
+        // The new database only has the raw view, add the analysis state as its latest snapshot
+        let parents: Vec<SnapshotId> = database
+            .current_snapshot()
+            .map(|s| s.id())
+            .into_iter()
+            .collect();
+        let snapshot = database.write_snapshot_data(&parents, &scratch_raw_view, name, data, false);
+        database.set_current_snapshot_id(snapshot);
+        // The analysis cache only speeds up opening the database, it is fine to go without it
+        let _ = database.write_analysis_cache(cache);
+        scratch_file.close();
+
+        // Open the database again, so all views are created from the snapshot
+        let scratch_view = FileMetadata::new()
+            .open_database(path.to_string_lossy().as_ref())
+            .map(|raw_view| raw_view.file().view_of_type(view_type));
+        match scratch_view {
+            Ok(Some(view)) => Ok(ScratchView { view, path }),
+            result => {
+                let _ = std::fs::remove_file(&path);
+                Err(result
+                    .err()
+                    .unwrap_or_else(|| Error::NotFound(format!("`{}` view", view_type))))
+            }
+        }
+    }
+
+    /// Path of the temporary database backing the copy.
+    pub fn database_path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Deref for ScratchView {
+    type Target = BinaryView;
+
+    fn deref(&self) -> &Self::Target {
+        &self.view
+    }
+}
+
+impl Drop for ScratchView {
+    fn drop(&mut self) {
+        self.view.file().close();
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
 #[derive(PartialEq, Eq, Hash)]
 pub struct BinaryView {
     pub(crate) handle: *mut BNBinaryView,
@@ -1849,7 +3707,7 @@ impl BinaryView {
             unsafe { BNCreateBinaryDataViewFromFilename(meta.handle, file.as_ptr() as *mut _) };
 
         if handle.is_null() {
-            return Err(());
+            return Err(Error::NullHandle("BNCreateBinaryDataViewFromFilename"));
         }
 
         unsafe { Ok(Ref::new(Self { handle })) }
@@ -1859,7 +3717,7 @@ impl BinaryView {
         let handle = unsafe { BNCreateBinaryDataViewFromFile(meta.handle, &mut file.api_object) };
 
         if handle.is_null() {
-            return Err(());
+            return Err(Error::NullHandle("BNCreateBinaryDataViewFromFile"));
         }
 
         unsafe { Ok(Ref::new(Self { handle })) }
@@ -1871,7 +3729,7 @@ impl BinaryView {
         };
 
         if handle.is_null() {
-            return Err(());
+            return Err(Error::NullHandle("BNCreateBinaryDataViewFromData"));
         }
 
         unsafe { Ok(Ref::new(Self { handle })) }
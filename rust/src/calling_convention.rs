@@ -500,6 +500,101 @@ impl CoreCallingConvention {
 
         unsafe { Array::<Variable>::new(raw_vars_ptr, count, ()) }.to_vec()
     }
+
+    /// The architecture the calling convention belongs to.
+    pub fn arch(&self) -> CoreArchitecture {
+        self.arch_handle
+    }
+
+    /// The ABI details of the calling convention, with its registers resolved.
+    pub fn info(&self) -> CallingConventionInfo {
+        let arch = self.arch_handle;
+        let resolve = |ids: Vec<RegisterId>| -> Vec<CoreRegister> {
+            ids.into_iter()
+                .filter_map(|id| arch.register_from_id(id))
+                .collect()
+        };
+        let resolve_one = |id: Option<RegisterId>| id.and_then(|id| arch.register_from_id(id));
+        CallingConventionInfo {
+            name: self.name().to_string(),
+            arch,
+            int_arg_registers: resolve(self.int_arg_registers()),
+            float_arg_registers: resolve(self.float_arg_registers()),
+            caller_saved_registers: resolve(self.caller_saved_registers()),
+            callee_saved_registers: resolve(self.callee_saved_registers()),
+            implicitly_defined_registers: resolve(self.implicitly_defined_registers()),
+            return_int_reg: resolve_one(self.return_int_reg()),
+            return_hi_int_reg: resolve_one(self.return_hi_int_reg()),
+            return_float_reg: resolve_one(self.return_float_reg()),
+            global_pointer_reg: resolve_one(self.global_pointer_reg()),
+            arg_registers_shared_index: self.arg_registers_shared_index(),
+            reserved_stack_space_for_arg_registers: self.reserved_stack_space_for_arg_registers(),
+            stack_adjusted_on_return: self.stack_adjusted_on_return(),
+            is_eligible_for_heuristics: self.is_eligible_for_heuristics(),
+            arg_registers_used_for_var_args: self.are_argument_registers_used_for_var_args(),
+        }
+    }
+}
+
+/// The ABI details of a [`CoreCallingConvention`], see [`CoreCallingConvention::info`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallingConventionInfo {
+    pub name: String,
+    pub arch: CoreArchitecture,
+    /// Registers holding integer arguments, in argument order.
+    pub int_arg_registers: Vec<CoreRegister>,
+    /// Registers holding floating point arguments, in argument order.
+    pub float_arg_registers: Vec<CoreRegister>,
+    /// Registers a call may clobber.
+    pub caller_saved_registers: Vec<CoreRegister>,
+    /// Registers a call preserves.
+    pub callee_saved_registers: Vec<CoreRegister>,
+    /// Registers with a known value on entry, such as the global pointer.
+    pub implicitly_defined_registers: Vec<CoreRegister>,
+    pub return_int_reg: Option<CoreRegister>,
+    /// Register holding the high half of integer return values wider than a register.
+    pub return_hi_int_reg: Option<CoreRegister>,
+    pub return_float_reg: Option<CoreRegister>,
+    pub global_pointer_reg: Option<CoreRegister>,
+    /// Whether integer and floating point arguments consume the same argument slots, as in the
+    /// Windows x64 convention.
+    pub arg_registers_shared_index: bool,
+    /// Whether the caller reserves stack space for the register arguments.
+    pub reserved_stack_space_for_arg_registers: bool,
+    /// Whether the callee removes the stack arguments on return.
+    pub stack_adjusted_on_return: bool,
+    pub is_eligible_for_heuristics: bool,
+    pub arg_registers_used_for_var_args: bool,
+}
+
+impl CallingConventionInfo {
+    /// The integer and floating point argument registers, `None` for arguments passed on the
+    /// stack, for parameters of the given kinds (`true` for floating point).
+    ///
+    /// Follows [`CallingConventionInfo::arg_registers_shared_index`], so on conventions sharing
+    /// the index a floating point argument also consumes an integer register slot.
+    pub fn arg_register_assignment(&self, params_are_float: &[bool]) -> Vec<Option<CoreRegister>> {
+        let (mut next_int, mut next_float) = (0, 0);
+        params_are_float
+            .iter()
+            .map(|&is_float| {
+                let reg = if is_float {
+                    self.float_arg_registers.get(next_float).copied()
+                } else {
+                    self.int_arg_registers.get(next_int).copied()
+                };
+                if self.arg_registers_shared_index {
+                    next_int += 1;
+                    next_float += 1;
+                } else if is_float {
+                    next_float += 1;
+                } else {
+                    next_int += 1;
+                }
+                reg
+            })
+            .collect()
+    }
 }
 
 unsafe impl Send for CoreCallingConvention {}
@@ -574,7 +669,6 @@ impl CallingConvention for CoreCallingConvention {
         unsafe {
             let mut count = 0;
             let regs_ptr = BNGetCalleeSavedRegisters(self.handle, &mut count);
-            BNFreeRegisterList(regs_ptr);
             let regs: Vec<RegisterId> = std::slice::from_raw_parts(regs_ptr, count)
                 .iter()
                 .copied()
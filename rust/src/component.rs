@@ -97,6 +97,19 @@ impl Component {
         unsafe { BNComponentAddFunctionReference(self.handle.as_ptr(), func.handle) }
     }
 
+    /// Move function to this component, removing it from every other component that contains it.
+    pub fn move_function(&self, func: &Function) -> bool {
+        if !self.add_function(func) {
+            return false;
+        }
+        for parent in &func.parent_components() {
+            if *parent != *self {
+                parent.remove_function(func);
+            }
+        }
+        true
+    }
+
     /// Check whether this component contains a function.
     pub fn contains_function(&self, func: &Function) -> bool {
         unsafe { BNComponentContainsFunction(self.handle.as_ptr(), func.handle) }
@@ -122,11 +135,7 @@ impl Component {
     /// This function has no effect when used from the root component.
     /// Use `BinaryView.remove_component` to Remove a component from the tree entirely.
     pub fn remove_component(&self, component: &Component) -> bool {
-        self.view()
-            .unwrap()
-            .root_component()
-            .unwrap()
-            .add_component(component)
+        unsafe { BNComponentRemoveComponent(component.handle.as_ptr()) }
     }
 
     /// Add data variable to this component.
@@ -134,6 +143,22 @@ impl Component {
         unsafe { BNComponentAddDataVariable(self.handle.as_ptr(), data_variable.address) }
     }
 
+    /// Move data variable to this component, removing it from every other component that contains it.
+    pub fn move_data_variable(&self, data_variable: &DataVariable) -> bool {
+        if !self.add_data_variable(data_variable) {
+            return false;
+        }
+        let Some(view) = self.view() else {
+            return true;
+        };
+        for parent in &view.data_variable_parent_components(data_variable) {
+            if *parent != *self {
+                parent.remove_data_variable(data_variable);
+            }
+        }
+        true
+    }
+
     /// Check whether this component contains a data variable.
     pub fn contains_data_variable(&self, data_variable: &DataVariable) -> bool {
         unsafe { BNComponentContainsDataVariable(self.handle.as_ptr(), data_variable.address) }
@@ -29,6 +29,7 @@ use crate::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt, Result};
 use crate::platform::Platform;
 use crate::settings::Settings;
 use crate::Endianness;
+use crate::Error;
 
 use crate::rc::*;
 use crate::string::*;
@@ -39,6 +40,116 @@ use crate::string::*;
 /// the core. The `BinaryViewType` argument passed to `constructor` is the object that the
 /// `AsRef<BinaryViewType>`
 /// implementation of the `CustomBinaryViewType` must return.
+///
+/// A loader is made of two parts: a [`CustomBinaryViewType`], registered once with this function,
+/// which recognizes the data and hands out load settings, and a [`CustomBinaryView`], created for
+/// every file the type accepts, which maps the data into memory. For example, a loader for firmware
+/// images made of a `FW01` magic, a little endian load address and the image itself:
+///
+/// ```no_run
+/// use binaryninja::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
+/// use binaryninja::custom_binary_view::*;
+/// use binaryninja::rc::Ref;
+/// use binaryninja::segment::SegmentBuilder;
+/// use binaryninja::{Endianness, Error};
+///
+/// const HEADER_SIZE: u64 = 8;
+///
+/// struct FirmwareViewType {
+///     core: BinaryViewType,
+/// }
+///
+/// impl AsRef<BinaryViewType> for FirmwareViewType {
+///     fn as_ref(&self) -> &BinaryViewType {
+///         &self.core
+///     }
+/// }
+///
+/// impl BinaryViewTypeBase for FirmwareViewType {
+///     fn is_valid_for(&self, data: &BinaryView) -> bool {
+///         data.read_vec(0, 4) == b"FW01"
+///     }
+/// }
+///
+/// impl CustomBinaryViewType for FirmwareViewType {
+///     fn create_custom_view<'builder>(
+///         &self,
+///         data: &BinaryView,
+///         builder: CustomViewBuilder<'builder, Self>,
+///     ) -> Result<CustomView<'builder>, Error> {
+///         let load_address = data.read_u32(4)? as u64;
+///         builder.create::<FirmwareView>(data, load_address)
+///     }
+/// }
+///
+/// struct FirmwareView {
+///     core: Ref<BinaryView>,
+///     load_address: u64,
+///     len: u64,
+/// }
+///
+/// impl AsRef<BinaryView> for FirmwareView {
+///     fn as_ref(&self) -> &BinaryView {
+///         &self.core
+///     }
+/// }
+///
+/// unsafe impl CustomBinaryView for FirmwareView {
+///     type Args = u64;
+///
+///     fn new(handle: &BinaryView, _load_address: &u64) -> Result<Self, Error> {
+///         Ok(Self {
+///             core: handle.to_owned(),
+///             load_address: 0,
+///             len: 0,
+///         })
+///     }
+///
+///     fn init(&mut self, load_address: u64) -> Result<(), Error> {
+///         let parent = self.core.parent_view().ok_or(Error::NullHandle("parent view"))?;
+///         self.load_address = load_address;
+///         self.len = parent.len().saturating_sub(HEADER_SIZE);
+///         let image = load_address..load_address + self.len;
+///         self.add_segment(
+///             SegmentBuilder::new(image)
+///                 .parent_backing(HEADER_SIZE..HEADER_SIZE + self.len)
+///                 .readable(true)
+///                 .executable(true)
+///                 .contains_code(true),
+///         );
+///         Ok(())
+///     }
+/// }
+///
+/// impl BinaryViewBase for FirmwareView {
+///     fn start(&self) -> u64 {
+///         self.load_address
+///     }
+///
+///     fn len(&self) -> u64 {
+///         self.len
+///     }
+///
+///     fn entry_point(&self) -> u64 {
+///         self.load_address
+///     }
+///
+///     fn default_endianness(&self) -> Endianness {
+///         Endianness::LittleEndian
+///     }
+///
+///     fn address_size(&self) -> usize {
+///         4
+///     }
+/// }
+///
+/// #[no_mangle]
+/// #[allow(non_snake_case)]
+/// pub extern "C" fn CorePluginInit() -> bool {
+///     register_view_type(c"FW01", c"FW01 Firmware", |core| FirmwareViewType { core });
+///     true
+/// }
+/// ```
 pub fn register_view_type<S, T, F>(name: S, long_name: S, constructor: F) -> &'static T
 where
     S: BnStrCompatible,
@@ -97,8 +208,11 @@ where
                     // to the core -- we're transferring ownership of the Ref here
                     Ref::into_raw(bv.handle).handle
                 }
-                Err(_) => {
-                    log::error!("CustomBinaryViewType::create_custom_view returned Err");
+                Err(e) => {
+                    log::error!(
+                        "CustomBinaryViewType::create_custom_view returned Err: {}",
+                        e
+                    );
                     ptr::null_mut()
                 }
             }
@@ -125,8 +239,8 @@ where
                     // to the core -- we're transferring ownership of the Ref here
                     Ref::into_raw(bv.handle).handle
                 }
-                Err(_) => {
-                    log::error!("CustomBinaryViewType::parse returned Err");
+                Err(e) => {
+                    log::error!("CustomBinaryViewType::parse returned Err: {}", e);
                     ptr::null_mut()
                 }
             }
@@ -183,7 +297,9 @@ where
     }
 }
 
+/// The queries the core makes of a view type, before any view of it is created.
 pub trait BinaryViewTypeBase: AsRef<BinaryViewType> {
+    /// Whether `data`, usually the raw view of a file, can be opened as a view of this type.
     fn is_valid_for(&self, data: &BinaryView) -> bool;
 
     fn is_deprecated(&self) -> bool {
@@ -205,6 +321,9 @@ pub trait BinaryViewTypeBase: AsRef<BinaryViewType> {
         }
     }
 
+    /// The settings shown to the user when opening `data` with options, like the load address.
+    ///
+    /// Returning `None` uses the settings the core provides for every view type.
     fn load_settings_for_data(&self, _data: &BinaryView) -> Option<Ref<Settings>> {
         None
     }
@@ -246,7 +365,7 @@ pub trait BinaryViewTypeExt: BinaryViewTypeBase {
                 "failed to create BinaryView of BinaryViewType '{}'",
                 self.name()
             );
-            return Err(());
+            return Err(Error::NullHandle("BNCreateBinaryViewOfType"));
         }
 
         unsafe { Ok(BinaryView::ref_from_raw(handle)) }
@@ -260,7 +379,7 @@ pub trait BinaryViewTypeExt: BinaryViewTypeBase {
                 "failed to parse BinaryView of BinaryViewType '{}'",
                 self.name()
             );
-            return Err(());
+            return Err(Error::NullHandle("BNParseBinaryViewOfType"));
         }
 
         unsafe { Ok(BinaryView::ref_from_raw(handle)) }
@@ -302,7 +421,13 @@ impl BinaryViewType {
         let handle = unsafe { BNGetBinaryViewTypeByName(bytes.as_ref().as_ptr() as *const _) };
         match handle.is_null() {
             false => Ok(unsafe { BinaryViewType::from_raw(handle) }),
-            true => Err(()),
+            true => {
+                let name = String::from_utf8_lossy(bytes.as_ref());
+                Err(Error::NotFound(format!(
+                    "binary view type `{}`",
+                    name.trim_end_matches('\0')
+                )))
+            }
         }
     }
 }
@@ -357,13 +482,18 @@ impl AsRef<BinaryViewType> for BinaryViewType {
 unsafe impl Send for BinaryViewType {}
 unsafe impl Sync for BinaryViewType {}
 
+/// A view type implemented in Rust, registered with [`register_view_type`].
 pub trait CustomBinaryViewType: 'static + BinaryViewTypeBase + Sync {
+    /// Create the view of `data`, by calling [`CustomViewBuilder::create`] on `builder`.
     fn create_custom_view<'builder>(
         &self,
         data: &BinaryView,
         builder: CustomViewBuilder<'builder, Self>,
     ) -> Result<CustomView<'builder>>;
 
+    /// Create a view of `data` only to read its headers, for instance to fill in load settings.
+    ///
+    /// Defaults to [`CustomBinaryViewType::create_custom_view`].
     fn parse_custom_view<'builder>(
         &self,
         data: &BinaryView,
@@ -380,10 +510,18 @@ pub struct CustomViewBuilder<'a, T: CustomBinaryViewType + ?Sized> {
     actual_parent: &'a BinaryView,
 }
 
+/// A view implemented in Rust, created by [`CustomViewBuilder::create`].
+///
+/// # Safety
+///
+/// The `AsRef<BinaryView>` implementation must return the `handle` passed to [`CustomBinaryView::new`].
 pub unsafe trait CustomBinaryView: 'static + BinaryViewBase + Sync + Sized {
+    /// The arguments passed from [`CustomViewBuilder::create`] to the view once the core initializes it.
     type Args: Send;
 
+    /// Construct the view around `handle`, which must not be used to query the view yet.
     fn new(handle: &BinaryView, args: &Self::Args) -> Result<Self>;
+    /// Finish setting up the view, typically by adding its segments and sections.
     fn init(&mut self, args: Self::Args) -> Result<()>;
 }
 
@@ -456,7 +594,10 @@ impl<'a, T: CustomBinaryViewType> CustomViewBuilder<'a, T> {
                 bv.handle
             );
 
-            return Err(());
+            return Err(Error::InvalidArgument(format!(
+                "a view of type `{}` already exists",
+                view_name.as_str()
+            )));
         }
 
         // struct representing the context of a BNCustomBinaryView. Can be safely
@@ -510,13 +651,19 @@ impl<'a, T: CustomBinaryViewType> CustomViewBuilder<'a, T> {
                             context.state = CustomViewContextState::Initialized { view };
                             true
                         }
-                        Err(_) => {
-                            log::error!("CustomBinaryView::init failed; custom view returned Err");
+                        Err(e) => {
+                            log::error!(
+                                "CustomBinaryView::init failed; custom view returned Err: {}",
+                                e
+                            );
                             false
                         }
                     },
-                    Err(_) => {
-                        log::error!("CustomBinaryView::new failed; custom view returned Err");
+                    Err(e) => {
+                        log::error!(
+                            "CustomBinaryView::new failed; custom view returned Err: {}",
+                            e
+                        );
                         false
                     }
                 }
@@ -0,0 +1,593 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fine-grained notifications of changes made to a view.
+
+use std::ffi::{c_char, c_void};
+use std::ptr::NonNull;
+
+use binaryninjacore_sys::*;
+
+use crate::binary_view::{BinaryView, StringReference};
+use crate::component::Component;
+use crate::function::Function;
+use crate::rc::Ref;
+use crate::section::Section;
+use crate::segment::Segment;
+use crate::string::raw_to_string;
+use crate::symbol::Symbol;
+use crate::types::{QualifiedName, Type};
+use crate::variable::DataVariable;
+
+/// Receives the changes made to a view, see [`BinaryViewExt::register_data_notification`].
+///
+/// Every method does nothing by default, implement the ones for the changes you care about.
+/// Notifications are sent from whichever thread made the change, often an analysis thread, so
+/// they can arrive concurrently.
+///
+/// Unlike [`register_binary_view_event`](crate::binary_view::register_binary_view_event), which
+/// reports the lifecycle of every view, a notification is registered on one view:
+///
+/// ```no_run
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// use binaryninja::binary_view::{BinaryView, BinaryViewExt};
+/// use binaryninja::data_notification::BinaryDataNotification;
+/// use binaryninja::symbol::Symbol;
+///
+/// #[derive(Default)]
+/// struct RenameCounter(AtomicUsize);
+///
+/// impl BinaryDataNotification for RenameCounter {
+///     fn on_symbol_updated(&self, _view: &BinaryView, symbol: &Symbol) {
+///         log::info!("renamed to {}", symbol.full_name());
+///         self.0.fetch_add(1, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// let handle = view.register_data_notification(RenameCounter::default());
+/// // ...
+/// let counter = handle.unregister();
+/// println!("{} renames", counter.0.load(Ordering::Relaxed));
+/// ```
+///
+/// [`BinaryViewExt::register_data_notification`]: crate::binary_view::BinaryViewExt::register_data_notification
+#[allow(unused_variables)]
+pub trait BinaryDataNotification: 'static + Send + Sync {
+    /// `len` bytes at `offset` were overwritten.
+    fn on_data_written(&self, view: &BinaryView, offset: u64, len: usize) {}
+    /// `len` bytes were inserted at `offset`, moving everything after them.
+    fn on_data_inserted(&self, view: &BinaryView, offset: u64, len: usize) {}
+    /// `len` bytes at `offset` were removed, moving everything after them.
+    fn on_data_removed(&self, view: &BinaryView, offset: u64, len: u64) {}
+
+    fn on_function_added(&self, view: &BinaryView, func: &Function) {}
+    fn on_function_removed(&self, view: &BinaryView, func: &Function) {}
+    /// The analysis of `func` was updated.
+    fn on_function_updated(&self, view: &BinaryView, func: &Function) {}
+    /// `func` was marked for reanalysis, [`Self::on_function_updated`] follows once it is done.
+    fn on_function_update_requested(&self, view: &BinaryView, func: &Function) {}
+
+    fn on_data_variable_added(&self, view: &BinaryView, var: &DataVariable) {}
+    fn on_data_variable_removed(&self, view: &BinaryView, var: &DataVariable) {}
+    fn on_data_variable_updated(&self, view: &BinaryView, var: &DataVariable) {}
+    /// The metadata of the data at `offset` changed.
+    fn on_data_metadata_updated(&self, view: &BinaryView, offset: u64) {}
+
+    fn on_symbol_added(&self, view: &BinaryView, symbol: &Symbol) {}
+    fn on_symbol_removed(&self, view: &BinaryView, symbol: &Symbol) {}
+    fn on_symbol_updated(&self, view: &BinaryView, symbol: &Symbol) {}
+
+    fn on_string_found(&self, view: &BinaryView, string: &StringReference) {}
+    fn on_string_removed(&self, view: &BinaryView, string: &StringReference) {}
+
+    /// The type `name` was defined or redefined as `ty`.
+    fn on_type_defined(&self, view: &BinaryView, name: &QualifiedName, ty: &Type) {}
+    fn on_type_undefined(&self, view: &BinaryView, name: &QualifiedName, ty: &Type) {}
+    /// The references to the type `name` changed.
+    fn on_type_reference_changed(&self, view: &BinaryView, name: &QualifiedName, ty: &Type) {}
+    /// The references to the field at `offset` in the type `name` changed.
+    fn on_type_field_reference_changed(
+        &self,
+        view: &BinaryView,
+        name: &QualifiedName,
+        offset: u64,
+    ) {
+    }
+
+    fn on_segment_added(&self, view: &BinaryView, segment: &Segment) {}
+    fn on_segment_removed(&self, view: &BinaryView, segment: &Segment) {}
+    fn on_segment_updated(&self, view: &BinaryView, segment: &Segment) {}
+
+    fn on_section_added(&self, view: &BinaryView, section: &Section) {}
+    fn on_section_removed(&self, view: &BinaryView, section: &Section) {}
+    fn on_section_updated(&self, view: &BinaryView, section: &Section) {}
+
+    fn on_component_added(&self, view: &BinaryView, component: &Component) {}
+    fn on_component_removed(
+        &self,
+        view: &BinaryView,
+        former_parent: &Component,
+        component: &Component,
+    ) {
+    }
+    fn on_component_name_updated(
+        &self,
+        view: &BinaryView,
+        previous_name: &str,
+        component: &Component,
+    ) {
+    }
+
+    /// The view was rebased, `new_view` replaces `old_view`.
+    fn on_rebased(&self, old_view: &BinaryView, new_view: &BinaryView) {}
+}
+
+/// A registered [`BinaryDataNotification`], dropping it unregisters the notification.
+#[must_use = "dropping the handle unregisters the notification"]
+pub struct DataNotificationHandle<T: BinaryDataNotification> {
+    view: Ref<BinaryView>,
+    // Boxed so the core keeps seeing the same callbacks until they are unregistered
+    callbacks: Box<BNBinaryDataNotification>,
+    notification: *mut T,
+}
+
+unsafe impl<T: BinaryDataNotification> Send for DataNotificationHandle<T> {}
+unsafe impl<T: BinaryDataNotification> Sync for DataNotificationHandle<T> {}
+
+impl<T: BinaryDataNotification> DataNotificationHandle<T> {
+    pub(crate) fn register(view: &BinaryView, notification: T) -> Self {
+        // SAFETY freed when the handle is dropped or unregistered
+        let notification = Box::into_raw(Box::new(notification));
+        let mut callbacks = Box::new(callbacks::<T>(notification as *mut c_void));
+        unsafe { BNRegisterDataNotification(view.handle, callbacks.as_mut()) };
+        Self {
+            view: view.to_owned(),
+            callbacks,
+            notification,
+        }
+    }
+
+    pub fn notification(&self) -> &T {
+        unsafe { &*self.notification }
+    }
+
+    /// Stop receiving notifications, returning the notification object.
+    pub fn unregister(mut self) -> T {
+        let notification = self.take();
+        // SAFETY `take` nulled out the pointer, so dropping the handle won't free it again
+        *unsafe { Box::from_raw(notification) }
+    }
+
+    /// Unregister the callbacks, handing the notification object over to the caller.
+    fn take(&mut self) -> *mut T {
+        unsafe { BNUnregisterDataNotification(self.view.handle, self.callbacks.as_mut()) };
+        std::mem::replace(&mut self.notification, std::ptr::null_mut())
+    }
+}
+
+impl<T: BinaryDataNotification> Drop for DataNotificationHandle<T> {
+    fn drop(&mut self) {
+        if !self.notification.is_null() {
+            let notification = self.take();
+            drop(unsafe { Box::from_raw(notification) });
+        }
+    }
+}
+
+fn callbacks<T: BinaryDataNotification>(context: *mut c_void) -> BNBinaryDataNotification {
+    BNBinaryDataNotification {
+        context,
+        dataWritten: Some(cb_data_written::<T>),
+        dataInserted: Some(cb_data_inserted::<T>),
+        dataRemoved: Some(cb_data_removed::<T>),
+        functionAdded: Some(cb_function_added::<T>),
+        functionRemoved: Some(cb_function_removed::<T>),
+        functionUpdated: Some(cb_function_updated::<T>),
+        functionUpdateRequested: Some(cb_function_update_requested::<T>),
+        dataVariableAdded: Some(cb_data_variable_added::<T>),
+        dataVariableRemoved: Some(cb_data_variable_removed::<T>),
+        dataVariableUpdated: Some(cb_data_variable_updated::<T>),
+        dataMetadataUpdated: Some(cb_data_metadata_updated::<T>),
+        symbolAdded: Some(cb_symbol_added::<T>),
+        symbolRemoved: Some(cb_symbol_removed::<T>),
+        symbolUpdated: Some(cb_symbol_updated::<T>),
+        stringFound: Some(cb_string_found::<T>),
+        stringRemoved: Some(cb_string_removed::<T>),
+        typeDefined: Some(cb_type_defined::<T>),
+        typeUndefined: Some(cb_type_undefined::<T>),
+        typeReferenceChanged: Some(cb_type_reference_changed::<T>),
+        typeFieldReferenceChanged: Some(cb_type_field_reference_changed::<T>),
+        segmentAdded: Some(cb_segment_added::<T>),
+        segmentRemoved: Some(cb_segment_removed::<T>),
+        segmentUpdated: Some(cb_segment_updated::<T>),
+        sectionAdded: Some(cb_section_added::<T>),
+        sectionRemoved: Some(cb_section_removed::<T>),
+        sectionUpdated: Some(cb_section_updated::<T>),
+        componentAdded: Some(cb_component_added::<T>),
+        componentRemoved: Some(cb_component_removed::<T>),
+        componentNameUpdated: Some(cb_component_name_updated::<T>),
+        rebased: Some(cb_rebased::<T>),
+        ..Default::default()
+    }
+}
+
+unsafe extern "C" fn cb_data_written<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    offset: u64,
+    len: usize,
+) {
+    ffi_wrap!("BinaryDataNotification::on_data_written", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        ctxt.on_data_written(&BinaryView::from_raw(view), offset, len);
+    })
+}
+
+unsafe extern "C" fn cb_data_inserted<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    offset: u64,
+    len: usize,
+) {
+    ffi_wrap!("BinaryDataNotification::on_data_inserted", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        ctxt.on_data_inserted(&BinaryView::from_raw(view), offset, len);
+    })
+}
+
+unsafe extern "C" fn cb_data_removed<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    offset: u64,
+    len: u64,
+) {
+    ffi_wrap!("BinaryDataNotification::on_data_removed", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        ctxt.on_data_removed(&BinaryView::from_raw(view), offset, len);
+    })
+}
+
+unsafe extern "C" fn cb_function_added<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    func: *mut BNFunction,
+) {
+    ffi_wrap!("BinaryDataNotification::on_function_added", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        ctxt.on_function_added(&BinaryView::from_raw(view), &Function::from_raw(func));
+    })
+}
+
+unsafe extern "C" fn cb_function_removed<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    func: *mut BNFunction,
+) {
+    ffi_wrap!("BinaryDataNotification::on_function_removed", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        ctxt.on_function_removed(&BinaryView::from_raw(view), &Function::from_raw(func));
+    })
+}
+
+unsafe extern "C" fn cb_function_updated<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    func: *mut BNFunction,
+) {
+    ffi_wrap!("BinaryDataNotification::on_function_updated", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        ctxt.on_function_updated(&BinaryView::from_raw(view), &Function::from_raw(func));
+    })
+}
+
+unsafe extern "C" fn cb_function_update_requested<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    func: *mut BNFunction,
+) {
+    ffi_wrap!("BinaryDataNotification::on_function_update_requested", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        ctxt.on_function_update_requested(&BinaryView::from_raw(view), &Function::from_raw(func));
+    })
+}
+
+unsafe extern "C" fn cb_data_variable_added<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    var: *mut BNDataVariable,
+) {
+    ffi_wrap!("BinaryDataNotification::on_data_variable_added", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        let var = DataVariable::from_raw(unsafe { &*var });
+        ctxt.on_data_variable_added(&BinaryView::from_raw(view), &var);
+    })
+}
+
+unsafe extern "C" fn cb_data_variable_removed<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    var: *mut BNDataVariable,
+) {
+    ffi_wrap!("BinaryDataNotification::on_data_variable_removed", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        let var = DataVariable::from_raw(unsafe { &*var });
+        ctxt.on_data_variable_removed(&BinaryView::from_raw(view), &var);
+    })
+}
+
+unsafe extern "C" fn cb_data_variable_updated<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    var: *mut BNDataVariable,
+) {
+    ffi_wrap!("BinaryDataNotification::on_data_variable_updated", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        let var = DataVariable::from_raw(unsafe { &*var });
+        ctxt.on_data_variable_updated(&BinaryView::from_raw(view), &var);
+    })
+}
+
+unsafe extern "C" fn cb_data_metadata_updated<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    offset: u64,
+) {
+    ffi_wrap!("BinaryDataNotification::on_data_metadata_updated", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        ctxt.on_data_metadata_updated(&BinaryView::from_raw(view), offset);
+    })
+}
+
+unsafe extern "C" fn cb_symbol_added<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    symbol: *mut BNSymbol,
+) {
+    ffi_wrap!("BinaryDataNotification::on_symbol_added", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        ctxt.on_symbol_added(&BinaryView::from_raw(view), &Symbol::from_raw(symbol));
+    })
+}
+
+unsafe extern "C" fn cb_symbol_removed<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    symbol: *mut BNSymbol,
+) {
+    ffi_wrap!("BinaryDataNotification::on_symbol_removed", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        ctxt.on_symbol_removed(&BinaryView::from_raw(view), &Symbol::from_raw(symbol));
+    })
+}
+
+unsafe extern "C" fn cb_symbol_updated<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    symbol: *mut BNSymbol,
+) {
+    ffi_wrap!("BinaryDataNotification::on_symbol_updated", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        ctxt.on_symbol_updated(&BinaryView::from_raw(view), &Symbol::from_raw(symbol));
+    })
+}
+
+unsafe extern "C" fn cb_string_found<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    string_type: BNStringType,
+    offset: u64,
+    len: usize,
+) {
+    ffi_wrap!("BinaryDataNotification::on_string_found", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        let string = StringReference {
+            start: offset,
+            length: len,
+            string_type,
+        };
+        ctxt.on_string_found(&BinaryView::from_raw(view), &string);
+    })
+}
+
+unsafe extern "C" fn cb_string_removed<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    string_type: BNStringType,
+    offset: u64,
+    len: usize,
+) {
+    ffi_wrap!("BinaryDataNotification::on_string_removed", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        let string = StringReference {
+            start: offset,
+            length: len,
+            string_type,
+        };
+        ctxt.on_string_removed(&BinaryView::from_raw(view), &string);
+    })
+}
+
+unsafe extern "C" fn cb_type_defined<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    name: *mut BNQualifiedName,
+    ty: *mut BNType,
+) {
+    ffi_wrap!("BinaryDataNotification::on_type_defined", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        let name = QualifiedName::from_raw(unsafe { &*name });
+        ctxt.on_type_defined(&BinaryView::from_raw(view), &name, &Type::from_raw(ty));
+    })
+}
+
+unsafe extern "C" fn cb_type_undefined<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    name: *mut BNQualifiedName,
+    ty: *mut BNType,
+) {
+    ffi_wrap!("BinaryDataNotification::on_type_undefined", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        let name = QualifiedName::from_raw(unsafe { &*name });
+        ctxt.on_type_undefined(&BinaryView::from_raw(view), &name, &Type::from_raw(ty));
+    })
+}
+
+unsafe extern "C" fn cb_type_reference_changed<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    name: *mut BNQualifiedName,
+    ty: *mut BNType,
+) {
+    ffi_wrap!("BinaryDataNotification::on_type_reference_changed", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        let name = QualifiedName::from_raw(unsafe { &*name });
+        ctxt.on_type_reference_changed(&BinaryView::from_raw(view), &name, &Type::from_raw(ty));
+    })
+}
+
+unsafe extern "C" fn cb_type_field_reference_changed<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    name: *mut BNQualifiedName,
+    offset: u64,
+) {
+    ffi_wrap!("BinaryDataNotification::on_type_field_reference_changed", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        let name = QualifiedName::from_raw(unsafe { &*name });
+        ctxt.on_type_field_reference_changed(&BinaryView::from_raw(view), &name, offset);
+    })
+}
+
+unsafe extern "C" fn cb_segment_added<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    segment: *mut BNSegment,
+) {
+    ffi_wrap!("BinaryDataNotification::on_segment_added", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        ctxt.on_segment_added(&BinaryView::from_raw(view), &Segment::from_raw(segment));
+    })
+}
+
+unsafe extern "C" fn cb_segment_removed<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    segment: *mut BNSegment,
+) {
+    ffi_wrap!("BinaryDataNotification::on_segment_removed", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        ctxt.on_segment_removed(&BinaryView::from_raw(view), &Segment::from_raw(segment));
+    })
+}
+
+unsafe extern "C" fn cb_segment_updated<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    segment: *mut BNSegment,
+) {
+    ffi_wrap!("BinaryDataNotification::on_segment_updated", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        ctxt.on_segment_updated(&BinaryView::from_raw(view), &Segment::from_raw(segment));
+    })
+}
+
+unsafe extern "C" fn cb_section_added<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    section: *mut BNSection,
+) {
+    ffi_wrap!("BinaryDataNotification::on_section_added", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        ctxt.on_section_added(&BinaryView::from_raw(view), &Section::from_raw(section));
+    })
+}
+
+unsafe extern "C" fn cb_section_removed<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    section: *mut BNSection,
+) {
+    ffi_wrap!("BinaryDataNotification::on_section_removed", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        ctxt.on_section_removed(&BinaryView::from_raw(view), &Section::from_raw(section));
+    })
+}
+
+unsafe extern "C" fn cb_section_updated<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    section: *mut BNSection,
+) {
+    ffi_wrap!("BinaryDataNotification::on_section_updated", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        ctxt.on_section_updated(&BinaryView::from_raw(view), &Section::from_raw(section));
+    })
+}
+
+unsafe extern "C" fn cb_component_added<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    component: *mut BNComponent,
+) {
+    ffi_wrap!("BinaryDataNotification::on_component_added", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        let component = Component::from_raw(NonNull::new(component).unwrap());
+        ctxt.on_component_added(&BinaryView::from_raw(view), &component);
+    })
+}
+
+unsafe extern "C" fn cb_component_removed<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    former_parent: *mut BNComponent,
+    component: *mut BNComponent,
+) {
+    ffi_wrap!("BinaryDataNotification::on_component_removed", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        let former_parent = Component::from_raw(NonNull::new(former_parent).unwrap());
+        let component = Component::from_raw(NonNull::new(component).unwrap());
+        ctxt.on_component_removed(&BinaryView::from_raw(view), &former_parent, &component);
+    })
+}
+
+unsafe extern "C" fn cb_component_name_updated<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    previous_name: *mut c_char,
+    component: *mut BNComponent,
+) {
+    ffi_wrap!("BinaryDataNotification::on_component_name_updated", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        let previous_name = raw_to_string(previous_name).unwrap_or_default();
+        let component = Component::from_raw(NonNull::new(component).unwrap());
+        ctxt.on_component_name_updated(&BinaryView::from_raw(view), &previous_name, &component);
+    })
+}
+
+unsafe extern "C" fn cb_rebased<T: BinaryDataNotification>(
+    ctxt: *mut c_void,
+    old_view: *mut BNBinaryView,
+    new_view: *mut BNBinaryView,
+) {
+    ffi_wrap!("BinaryDataNotification::on_rebased", {
+        let ctxt = unsafe { &*(ctxt as *const T) };
+        ctxt.on_rebased(
+            &BinaryView::from_raw(old_view),
+            &BinaryView::from_raw(new_view),
+        );
+    })
+}
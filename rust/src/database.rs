@@ -1,3 +1,4 @@
+pub mod diff;
 pub mod kvs;
 pub mod snapshot;
 pub mod undo;
@@ -10,12 +11,14 @@ use std::ptr::NonNull;
 
 use crate::binary_view::BinaryView;
 use crate::data_buffer::DataBuffer;
+use crate::database::diff::SnapshotChangeset;
 use crate::database::kvs::KeyValueStore;
 use crate::database::snapshot::{Snapshot, SnapshotId};
 use crate::file_metadata::FileMetadata;
 use crate::progress::{NoProgressCallback, ProgressCallback};
 use crate::rc::{Array, Ref, RefCountable};
 use crate::string::{BnStrCompatible, BnString};
+use crate::Error;
 
 pub struct Database {
     pub(crate) handle: NonNull<BNDatabase>,
@@ -114,6 +117,57 @@ impl Database {
         SnapshotId(new_id)
     }
 
+    /// Create a snapshot of the current state of `view` named `name`, as a child of the current
+    /// snapshot, and make it the current snapshot.
+    ///
+    /// ```no_run
+    /// # use binaryninja::binary_view::BinaryViewExt;
+    /// # let view = binaryninja::load("/bin/cat.bndb").unwrap();
+    /// let database = view.file().database().unwrap();
+    /// let snapshot = database.create_snapshot(&view, "Before renaming").unwrap();
+    /// // Only keep the contents of the ten latest snapshots
+    /// database.trim_old_snapshots(10).unwrap();
+    /// ```
+    pub fn create_snapshot<N: BnStrCompatible>(
+        &self,
+        view: &BinaryView,
+        name: N,
+    ) -> Result<SnapshotId, Error> {
+        let (data, _) = self.file().snapshot_data();
+        let parents: Vec<SnapshotId> = self
+            .current_snapshot()
+            .map(|s| s.id())
+            .into_iter()
+            .collect();
+        let id = self.write_snapshot_data(&parents, view, name, &data, false);
+        if id.0 < 0 {
+            return Err(Error::CoreCallFailed("BNWriteDatabaseSnapshotData"));
+        }
+        self.set_current_snapshot_id(id);
+        Ok(id)
+    }
+
+    /// Trim the contents of the snapshots before the `keep` latest ones in the history of the
+    /// current snapshot, see [`Database::trim_snapshot`]. Returns the trimmed snapshots.
+    pub fn trim_old_snapshots(&self, keep: usize) -> Result<Vec<SnapshotId>, Error> {
+        let mut trimmed = Vec::new();
+        let mut snapshot = self.current_snapshot().map(|s| s.id());
+        let mut depth = 0;
+        while let Some(id) = snapshot {
+            let current = self
+                .snapshot_by_id(id)
+                .ok_or_else(|| Error::NotFound(format!("snapshot {}", id)))?;
+            if depth >= keep && current.has_contents() {
+                self.trim_snapshot(id)
+                    .map_err(|_| Error::CoreCallFailed("BNTrimDatabaseSnapshot"))?;
+                trimmed.push(id);
+            }
+            snapshot = current.first_parent().map(|parent| parent.id());
+            depth += 1;
+        }
+        Ok(trimmed)
+    }
+
     /// Trim a snapshot's contents in the database by id, but leave the parent/child
     /// hierarchy intact. Future references to this snapshot will return False for has_contents
     pub fn trim_snapshot(&self, id: SnapshotId) -> Result<(), ()> {
@@ -215,6 +269,40 @@ impl Database {
     pub fn snapshot_has_data(&self, id: SnapshotId) -> bool {
         unsafe { BNSnapshotHasData(self.handle.as_ptr(), id.0) }
     }
+
+    /// Symbols, types, comments and functions that changed from snapshot `old` to `new`,
+    /// comparing their `view_type` views.
+    ///
+    /// Both snapshots are opened from temporary copies, neither the database nor the currently
+    /// open views are modified.
+    ///
+    /// ```no_run
+    /// use binaryninja::binary_view::BinaryViewExt;
+    /// use binaryninja::database::diff::Change;
+    ///
+    /// let view = binaryninja::load("/tmp/target.bndb").unwrap();
+    /// let database = view.file().database().unwrap();
+    /// let current = database.current_snapshot().unwrap();
+    /// let previous = current.first_parent().unwrap();
+    /// let changes = database
+    ///     .diff_snapshots(&previous, &current, view.view_type().as_str())
+    ///     .unwrap();
+    /// for change in &changes.functions {
+    ///     if let Change::Modified { key, old, new } = change {
+    ///         println!("{:#x}: {} -> {}", key, old.name, new.name);
+    ///     }
+    /// }
+    /// ```
+    pub fn diff_snapshots(
+        &self,
+        old: &Snapshot,
+        new: &Snapshot,
+        view_type: &str,
+    ) -> Result<SnapshotChangeset, Error> {
+        let old_view = old.open_scratch_view(view_type)?;
+        let new_view = new.open_scratch_view(view_type)?;
+        Ok(SnapshotChangeset::between(&old_view, &new_view))
+    }
 }
 
 impl Debug for Database {
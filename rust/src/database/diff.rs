@@ -0,0 +1,198 @@
+//! Semantic differences between two snapshots, or any two views of the same file.
+
+use std::collections::BTreeMap;
+
+use crate::binary_view::{BinaryView, BinaryViewExt};
+use crate::symbol::SymbolType;
+
+/// How a single item, identified by `key`, differs between the old and the new view.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change<K, V> {
+    Added { key: K, new: V },
+    Removed { key: K, old: V },
+    Modified { key: K, old: V, new: V },
+}
+
+impl<K, V> Change<K, V> {
+    pub fn key(&self) -> &K {
+        match self {
+            Change::Added { key, .. }
+            | Change::Removed { key, .. }
+            | Change::Modified { key, .. } => key,
+        }
+    }
+}
+
+/// The symbol at an address, as compared between views.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolSummary {
+    pub name: String,
+    pub sym_type: SymbolType,
+}
+
+/// The parts of a function that are compared between views.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionSummary {
+    pub name: String,
+    /// The function type as displayed, e.g. `int32_t(char* arg1)`.
+    pub function_type: String,
+    pub comment: String,
+}
+
+/// Where a comment is placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CommentLocation {
+    /// Start of the function the comment belongs to, `None` for comments outside of functions.
+    pub function: Option<u64>,
+    pub address: u64,
+}
+
+/// Everything that differs between two views, each list sorted by key.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SnapshotChangeset {
+    /// Symbols by address.
+    pub symbols: Vec<Change<u64, SymbolSummary>>,
+    /// Type definitions, as displayed, by type name.
+    pub types: Vec<Change<String, String>>,
+    pub comments: Vec<Change<CommentLocation, String>>,
+    /// Functions by start address.
+    pub functions: Vec<Change<u64, FunctionSummary>>,
+}
+
+impl SnapshotChangeset {
+    /// Compare the analysis of two views, usually of the same file at different points in time.
+    pub fn between(old: &BinaryView, new: &BinaryView) -> Self {
+        Self {
+            symbols: diff_maps(&symbols(old), &symbols(new)),
+            types: diff_maps(&types(old), &types(new)),
+            comments: diff_maps(&comments(old), &comments(new)),
+            functions: diff_maps(&functions(old), &functions(new)),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+            && self.types.is_empty()
+            && self.comments.is_empty()
+            && self.functions.is_empty()
+    }
+
+    /// Total number of changes.
+    pub fn len(&self) -> usize {
+        self.symbols.len() + self.types.len() + self.comments.len() + self.functions.len()
+    }
+}
+
+fn symbols(view: &BinaryView) -> BTreeMap<u64, SymbolSummary> {
+    // Only the symbol that is displayed for an address is compared
+    view.symbols()
+        .iter()
+        .filter_map(|symbol| view.symbol_by_address(symbol.address()))
+        .map(|symbol| {
+            let summary = SymbolSummary {
+                name: symbol.full_name().to_string(),
+                sym_type: symbol.sym_type(),
+            };
+            (symbol.address(), summary)
+        })
+        .collect()
+}
+
+fn types(view: &BinaryView) -> BTreeMap<String, String> {
+    view.types()
+        .iter()
+        .map(|named_type| (named_type.name.to_string(), named_type.ty.to_string()))
+        .collect()
+}
+
+fn comments(view: &BinaryView) -> BTreeMap<CommentLocation, String> {
+    let mut comments: BTreeMap<CommentLocation, String> = view
+        .commented_addresses()
+        .into_iter()
+        .map(|address| {
+            let location = CommentLocation {
+                function: None,
+                address,
+            };
+            (location, view.comment_at(address).to_string())
+        })
+        .collect();
+    for function in view.functions().iter() {
+        for comment in function.comments().iter() {
+            let location = CommentLocation {
+                function: Some(function.start()),
+                address: comment.addr,
+            };
+            comments.insert(location, comment.comment.to_string());
+        }
+    }
+    comments
+}
+
+fn functions(view: &BinaryView) -> BTreeMap<u64, FunctionSummary> {
+    view.functions()
+        .iter()
+        .map(|function| {
+            let summary = FunctionSummary {
+                name: function.symbol().full_name().to_string(),
+                function_type: function.function_type().to_string(),
+                comment: function.comment().to_string(),
+            };
+            (function.start(), summary)
+        })
+        .collect()
+}
+
+/// Changes from `old` to `new`, sorted by key.
+fn diff_maps<K: Ord + Clone, V: PartialEq + Clone>(
+    old: &BTreeMap<K, V>,
+    new: &BTreeMap<K, V>,
+) -> Vec<Change<K, V>> {
+    let mut changes: Vec<Change<K, V>> = old
+        .iter()
+        .filter_map(|(key, old_value)| match new.get(key) {
+            None => Some(Change::Removed {
+                key: key.clone(),
+                old: old_value.clone(),
+            }),
+            Some(new_value) if new_value != old_value => Some(Change::Modified {
+                key: key.clone(),
+                old: old_value.clone(),
+                new: new_value.clone(),
+            }),
+            Some(_) => None,
+        })
+        .collect();
+    changes.extend(new.iter().filter(|(key, _)| !old.contains_key(*key)).map(
+        |(key, new_value)| Change::Added {
+            key: key.clone(),
+            new: new_value.clone(),
+        },
+    ));
+    changes.sort_by(|a, b| a.key().cmp(b.key()));
+    changes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn diffs_by_key() {
+        let old = BTreeMap::from([(1, "a"), (2, "b"), (4, "d")]);
+        let new = BTreeMap::from([(0, "z"), (2, "b"), (4, "e")]);
+        assert_eq!(
+            diff_maps(&old, &new),
+            vec![
+                Change::Added { key: 0, new: "z" },
+                Change::Removed { key: 1, old: "a" },
+                Change::Modified {
+                    key: 4,
+                    old: "d",
+                    new: "e"
+                },
+            ]
+        );
+        assert!(diff_maps(&new, &new).is_empty());
+    }
+}
@@ -2,11 +2,13 @@ use crate::data_buffer::DataBuffer;
 use crate::rc::{Array, Ref, RefCountable};
 use crate::string::{BnStrCompatible, BnString};
 use binaryninjacore_sys::{
-    BNBeginKeyValueStoreNamespace, BNEndKeyValueStoreNamespace, BNFreeKeyValueStore,
-    BNGetKeyValueStoreBuffer, BNGetKeyValueStoreDataSize, BNGetKeyValueStoreKeys,
-    BNGetKeyValueStoreNamespaceSize, BNGetKeyValueStoreSerializedData, BNGetKeyValueStoreValueSize,
-    BNGetKeyValueStoreValueStorageSize, BNIsKeyValueStoreEmpty, BNKeyValueStore,
-    BNNewKeyValueStoreReference, BNSetKeyValueStoreBuffer,
+    BNBeginKeyValueStoreNamespace, BNCreateKeyValueStore, BNCreateKeyValueStoreFromDataBuffer,
+    BNEndKeyValueStoreNamespace, BNFreeKeyValueStore, BNGetKeyValueStoreBuffer,
+    BNGetKeyValueStoreDataSize, BNGetKeyValueStoreKeys, BNGetKeyValueStoreNamespaceSize,
+    BNGetKeyValueStoreSerializedData, BNGetKeyValueStoreValue, BNGetKeyValueStoreValueHash,
+    BNGetKeyValueStoreValueSize, BNGetKeyValueStoreValueStorageSize, BNIsKeyValueStoreEmpty,
+    BNKeyValueStore, BNKeyValueStoreHasValue, BNNewKeyValueStoreReference,
+    BNSetKeyValueStoreBuffer, BNSetKeyValueStoreValue,
 };
 use std::collections::HashMap;
 use std::ffi::c_char;
@@ -23,6 +25,18 @@ impl KeyValueStore {
         Ref::new(Self { handle })
     }
 
+    /// Create an empty store.
+    pub fn new() -> Ref<Self> {
+        let result = unsafe { BNCreateKeyValueStore() };
+        unsafe { Self::ref_from_raw(NonNull::new(result).unwrap()) }
+    }
+
+    /// Create a store from its serialized representation, see [`KeyValueStore::serialized_data`].
+    pub fn from_serialized_data(data: &DataBuffer) -> Option<Ref<Self>> {
+        let result = unsafe { BNCreateKeyValueStoreFromDataBuffer(data.as_raw()) };
+        NonNull::new(result).map(|handle| unsafe { Self::ref_from_raw(handle) })
+    }
+
     pub fn to_hashmap(&self) -> HashMap<String, DataBuffer> {
         let mut hashmap = HashMap::with_capacity(self.keys().len());
         for key in self.keys().iter() {
@@ -49,6 +63,42 @@ impl KeyValueStore {
         NonNull::new(result).map(|_| DataBuffer::from_raw(result))
     }
 
+    /// If the kvs has a value for the key
+    pub fn has_value<S: BnStrCompatible>(&self, key: S) -> bool {
+        let key_raw = key.into_bytes_with_nul();
+        let key_ptr = key_raw.as_ref().as_ptr() as *const c_char;
+        unsafe { BNKeyValueStoreHasValue(self.handle.as_ptr(), key_ptr) }
+    }
+
+    /// Get the value for a single key, as a string
+    pub fn string_value<S: BnStrCompatible>(&self, key: S) -> Option<BnString> {
+        let key_raw = key.into_bytes_with_nul();
+        let key_ptr = key_raw.as_ref().as_ptr() as *const c_char;
+        let result = unsafe { BNGetKeyValueStoreValue(self.handle.as_ptr(), key_ptr) };
+        NonNull::new(result).map(|_| unsafe { BnString::from_raw(result) })
+    }
+
+    /// Get the hash of the value for a single key
+    pub fn value_hash<S: BnStrCompatible>(&self, key: S) -> Option<DataBuffer> {
+        let key_raw = key.into_bytes_with_nul();
+        let key_ptr = key_raw.as_ref().as_ptr() as *const c_char;
+        let result = unsafe { BNGetKeyValueStoreValueHash(self.handle.as_ptr(), key_ptr) };
+        NonNull::new(result).map(|_| DataBuffer::from_raw(result))
+    }
+
+    /// Set the value for a single key, as a string
+    pub fn set_string_value<K: BnStrCompatible, V: BnStrCompatible>(
+        &self,
+        key: K,
+        value: V,
+    ) -> bool {
+        let key_raw = key.into_bytes_with_nul();
+        let key_ptr = key_raw.as_ref().as_ptr() as *const c_char;
+        let value_raw = value.into_bytes_with_nul();
+        let value_ptr = value_raw.as_ref().as_ptr() as *const c_char;
+        unsafe { BNSetKeyValueStoreValue(self.handle.as_ptr(), key_ptr, value_ptr) }
+    }
+
     /// Set the value for a single key
     pub fn set_value<S: BnStrCompatible>(&self, key: S, value: &DataBuffer) -> bool {
         let key_raw = key.into_bytes_with_nul();
@@ -1,3 +1,4 @@
+use crate::binary_view::ScratchView;
 use crate::data_buffer::DataBuffer;
 use crate::database::kvs::KeyValueStore;
 use crate::database::undo::UndoEntry;
@@ -5,6 +6,7 @@ use crate::database::Database;
 use crate::progress::ProgressCallback;
 use crate::rc::{Array, CoreArrayProvider, CoreArrayProviderInner, Guard, Ref, RefCountable};
 use crate::string::{BnStrCompatible, BnString};
+use crate::Error;
 use binaryninjacore_sys::{
     BNCollaborationFreeSnapshotIdList, BNFreeSnapshot, BNFreeSnapshotList, BNGetSnapshotChildren,
     BNGetSnapshotDatabase, BNGetSnapshotFileContents, BNGetSnapshotFileContentsHash,
@@ -72,9 +74,9 @@ impl Snapshot {
     }
 
     /// Get the first parent of the snapshot, or None if it has no parents
-    pub fn first_parent(&self) -> Option<Snapshot> {
+    pub fn first_parent(&self) -> Option<Ref<Snapshot>> {
         let result = unsafe { BNGetSnapshotFirstParent(self.handle.as_ptr()) };
-        NonNull::new(result).map(|s| unsafe { Snapshot::from_raw(s) })
+        NonNull::new(result).map(|s| unsafe { Snapshot::ref_from_raw(s) })
     }
 
     /// Get a list of all parent snapshots of the snapshot
@@ -193,6 +195,24 @@ impl Snapshot {
         }
     }
 
+    /// Open the `view_type` view of the file as it was in this snapshot.
+    ///
+    /// The view is backed by a temporary copy of the snapshot, the database of this snapshot is
+    /// not modified.
+    pub fn open_scratch_view(&self, view_type: &str) -> Result<ScratchView, Error> {
+        let contents = self
+            .file_contents()
+            .ok_or_else(|| Error::NotFound(format!("contents of snapshot {}", self.id())))?;
+        ScratchView::create(
+            &self.database().file(),
+            contents.get_data(),
+            &self.read_data(),
+            &KeyValueStore::new(),
+            self.name().as_str(),
+            view_type,
+        )
+    }
+
     /// Determine if this snapshot has another as an ancestor
     pub fn has_ancestor(self, other: &Snapshot) -> bool {
         unsafe { BNSnapshotHasAncestor(self.handle.as_ptr(), other.handle.as_ptr()) }
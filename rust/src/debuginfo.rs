@@ -31,6 +31,7 @@
 //! use binaryninja::{
 //!     binary_view::BinaryView,
 //!     debuginfo::{CustomDebugInfoParser, DebugInfo, DebugInfoParser},
+//!     progress::ProgressScope,
 //! };
 //!
 //! struct ExampleDebugInfoParser;
@@ -45,7 +46,7 @@
 //!         _debug_info: &mut DebugInfo,
 //!         _view: &BinaryView,
 //!         _debug_file: &BinaryView,
-//!         _progress: Box<dyn Fn(usize, usize) -> Result<(), ()>>,
+//!         _progress: ProgressScope,
 //!     ) -> bool {
 //!         println!("Parsing info");
 //!         true
@@ -74,13 +75,17 @@
 //! `DebugInfo` object just returned. This is automatic when opening a binary view with multiple valid debug info parsers. If you
 //! wish to set the debug info for a binary view without applying it as well, you can call `binaryninja::binaryview::BinaryView::set_debug_info`.
 
+pub mod locator;
+
 use binaryninjacore_sys::*;
 use std::ffi::c_void;
+use std::path::PathBuf;
 
-use crate::progress::{NoProgressCallback, ProgressCallback};
+use crate::progress::{NoProgressCallback, ProgressCallback, ProgressScope};
 use crate::variable::{NamedDataVariableWithType, NamedVariableWithType};
+use crate::Error;
 use crate::{
-    binary_view::BinaryView,
+    binary_view::{BinaryView, BinaryViewExt},
     platform::Platform,
     rc::*,
     string::{raw_to_string, BnStrCompatible, BnString},
@@ -91,12 +96,14 @@ use crate::{
 pub trait CustomDebugInfoParser: 'static + Sync {
     fn is_valid(&self, view: &BinaryView) -> bool;
 
+    /// Parse debug info for `view` from `debug_file`. Once [`ProgressScope::report`] returns `Err`
+    /// the user has cancelled the parse, and the parser should return early.
     fn parse_info(
         &self,
         debug_info: &mut DebugInfo,
         view: &BinaryView,
         debug_file: &BinaryView,
-        progress: Box<dyn Fn(usize, usize) -> Result<(), ()>>,
+        progress: ProgressScope,
     ) -> bool;
 }
 
@@ -115,12 +122,16 @@ impl DebugInfoParser {
     }
 
     /// Returns debug info parser of the given name, if it exists
-    pub fn from_name<S: BnStrCompatible>(name: S) -> Result<Ref<Self>, ()> {
+    pub fn from_name<S: BnStrCompatible>(name: S) -> Result<Ref<Self>, Error> {
         let name = name.into_bytes_with_nul();
         let parser = unsafe { BNGetDebugInfoParserByName(name.as_ref().as_ptr() as *mut _) };
 
         if parser.is_null() {
-            Err(())
+            let name = String::from_utf8_lossy(name.as_ref());
+            Err(Error::NotFound(format!(
+                "debug info parser `{}`",
+                name.trim_end_matches('\0')
+            )))
         } else {
             unsafe { Ok(Self::from_raw(parser)) }
         }
@@ -245,16 +256,7 @@ impl DebugInfoParser {
                     &mut debug_info,
                     &view,
                     &debug_file,
-                    Box::new(move |cur: usize, max: usize| match progress {
-                        Some(func) => {
-                            if func(progress_ctxt, cur, max) {
-                                Ok(())
-                            } else {
-                                Err(())
-                            }
-                        }
-                        _ => Ok(()),
-                    }),
+                    ProgressScope::from_raw(progress, progress_ctxt),
                 )
             })
         }
@@ -310,6 +312,14 @@ unsafe impl CoreArrayProviderInner for DebugInfoParser {
     }
 }
 
+/// The separate debug file of `view` on the local machine, such as the `.dSYM` bundle of a Mach-O
+/// binary or the file named by the build id or `.gnu_debuglink` of an ELF binary.
+///
+/// The directories searched are those set for `view`, see [`locator::DebugFileLocator`].
+pub fn find_external_debug_file(view: &BinaryView) -> Option<PathBuf> {
+    locator::DebugFileLocator::from_settings(view).find(view)
+}
+
 ///////////////////////
 // DebugFunctionInfo
 
@@ -364,6 +374,62 @@ impl DebugFunctionInfo {
     }
 }
 
+impl DebugFunctionInfo {
+    /// The address of the function, zero if it is not known.
+    pub fn address(&self) -> u64 {
+        self.address
+    }
+
+    /// Names of the nested components the function belongs to, outermost first.
+    pub fn components(&self) -> &[String] {
+        &self.components
+    }
+
+    /// The local variables of the function, in registers or on the stack.
+    pub fn local_variables(&self) -> &[NamedVariableWithType] {
+        &self.local_variables
+    }
+}
+
+/// The nested components for a function from the source file at `path`, see
+/// [`DebugFunctionInfo::components`].
+///
+/// `path` is split into its directories and file name, relative to `root` if it is inside of it,
+/// such as the compilation directory of the source file. Both `/` and `\` separate directories.
+///
+/// ```
+/// use binaryninja::debuginfo::source_path_components;
+///
+/// assert_eq!(
+///     source_path_components("/build/app/src/net/socket.c", Some("/build/app")),
+///     ["src", "net", "socket.c"]
+/// );
+/// assert_eq!(source_path_components("lib\\util.c", None), ["lib", "util.c"]);
+/// ```
+pub fn source_path_components(path: &str, root: Option<&str>) -> Vec<String> {
+    fn normalize(path: &str) -> Vec<&str> {
+        let mut parts = Vec::new();
+        for part in path.split(['/', '\\']) {
+            match part {
+                "" | "." => {}
+                ".." => {
+                    parts.pop();
+                }
+                part => parts.push(part),
+            }
+        }
+        parts
+    }
+
+    let parts = normalize(path);
+    match root.map(normalize) {
+        Some(root) if !root.is_empty() && parts.starts_with(&root) && parts.len() > root.len() => {
+            parts[root.len()..].iter().map(|s| s.to_string()).collect()
+        }
+        _ => parts.iter().map(|s| s.to_string()).collect(),
+    }
+}
+
 impl DebugFunctionInfo {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
@@ -494,6 +560,34 @@ impl DebugInfo {
         result
     }
 
+    /// Place every function the parser named `parser_name` added to the debug info that is
+    /// defined in `view` into the nested components named by its
+    /// [`DebugFunctionInfo::components`], creating them as needed.
+    ///
+    /// Parsers call this once their debug info was applied to `view`, typically from a module
+    /// analysis activity. Returns the number of functions added to a component.
+    pub fn apply_components<S: BnStrCompatible>(&self, view: &BinaryView, parser_name: S) -> usize {
+        let mut added = 0;
+        for info in self.functions_by_name(parser_name) {
+            if info.address == 0 || info.components.is_empty() {
+                continue;
+            }
+            let functions = view.functions_at(info.address);
+            if functions.is_empty() {
+                continue;
+            }
+            let Some(component) = view.create_component_path(&info.components) else {
+                continue;
+            };
+            for function in &functions {
+                if component.add_function(&function) {
+                    added += 1;
+                }
+            }
+        }
+        added
+    }
+
     /// Returns all data variables within the parser
     pub fn data_variables_by_name<S: BnStrCompatible>(
         &self,
@@ -0,0 +1,586 @@
+//! Finding the separate debug file of a binary on the local machine.
+
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
+use crate::settings::{QueryOptions, Settings};
+use crate::Endianness;
+
+/// Setting enabling the search of the debug directories.
+pub const ENABLE_DEBUG_DIRECTORIES_SETTING: &str = "analysis.debugInfo.enableDebugDirectories";
+/// Setting holding the debug directories searched after the default ones.
+pub const DEBUG_DIRECTORIES_SETTING: &str = "analysis.debugInfo.debugDirectories";
+/// Setting holding the directories searched for `.dSYM` bundles.
+pub const DSYM_DIRECTORIES_SETTING: &str = "analysis.debugInfo.dsymDirectories";
+/// Setting enabling the search of Spotlight for `.dSYM` bundles, on macOS.
+pub const SPOTLIGHT_SETTING: &str = "analysis.debugInfo.searchSpotlight";
+
+/// Debug directory of most Linux distributions.
+const DEFAULT_DEBUG_DIRECTORY: &str = "/usr/lib/debug";
+/// Note type of a GNU build id note (`NT_GNU_BUILD_ID`).
+const NT_GNU_BUILD_ID: u32 = 3;
+
+const MH_MAGIC: u32 = 0xfeedface;
+const MH_MAGIC_64: u32 = 0xfeedfacf;
+const FAT_MAGIC: u32 = 0xcafebabe;
+const FAT_MAGIC_64: u32 = 0xcafebabf;
+const LC_UUID: u32 = 0x1b;
+// Java class files share the fat magic, with a version number where the arch count would be
+const MAX_FAT_ARCHS: u32 = 32;
+
+/// Where a binary says its debug file is, from its `.gnu_debuglink` section.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DebugLink {
+    pub file_name: String,
+    /// CRC-32 of the contents of the debug file.
+    pub crc: u32,
+}
+
+/// Searches the local machine for the debug files of binaries.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DebugFileLocator {
+    debug_directories: Vec<PathBuf>,
+    dsym_directories: Vec<PathBuf>,
+    spotlight: bool,
+}
+
+impl Default for DebugFileLocator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DebugFileLocator {
+    /// A locator searching `/usr/lib/debug`, and Spotlight on macOS.
+    pub fn new() -> Self {
+        Self {
+            debug_directories: vec![PathBuf::from(DEFAULT_DEBUG_DIRECTORY)],
+            dsym_directories: vec![],
+            spotlight: cfg!(target_os = "macos"),
+        }
+    }
+
+    /// A locator searching the directories set for `view`.
+    ///
+    /// The settings are registered by the debug info parsers using them, those that aren't keep
+    /// the defaults of [`DebugFileLocator::new`].
+    pub fn from_settings(view: &BinaryView) -> Self {
+        let mut query_options = QueryOptions::new_with_view(view);
+        let settings = Settings::new();
+        let mut locator = Self::new();
+        let mut directories = |key: &str| -> Vec<PathBuf> {
+            match settings.contains(key) {
+                true => settings
+                    .get_string_list_with_opts(key, &mut query_options)
+                    .iter()
+                    .map(|dir| PathBuf::from(dir.to_string()))
+                    .collect(),
+                false => vec![],
+            }
+        };
+        locator
+            .debug_directories
+            .extend(directories(DEBUG_DIRECTORIES_SETTING));
+        locator.dsym_directories = directories(DSYM_DIRECTORIES_SETTING);
+
+        let mut query_options = QueryOptions::new_with_view(view);
+        if settings.contains(ENABLE_DEBUG_DIRECTORIES_SETTING)
+            && !settings.get_bool_with_opts(ENABLE_DEBUG_DIRECTORIES_SETTING, &mut query_options)
+        {
+            locator.debug_directories.clear();
+        }
+        if settings.contains(SPOTLIGHT_SETTING) {
+            locator.spotlight &= settings.get_bool_with_opts(SPOTLIGHT_SETTING, &mut query_options);
+        }
+        locator
+    }
+
+    /// Search `directory` for build ids and debug links, after the directories already added.
+    pub fn add_debug_directory(&mut self, directory: impl Into<PathBuf>) {
+        self.debug_directories.push(directory.into());
+    }
+
+    /// Search `directory` for `.dSYM` bundles, after the directories already added.
+    pub fn add_dsym_directory(&mut self, directory: impl Into<PathBuf>) {
+        self.dsym_directories.push(directory.into());
+    }
+
+    /// Whether to ask Spotlight for `.dSYM` bundles. Only has an effect on macOS.
+    pub fn set_spotlight(&mut self, spotlight: bool) {
+        self.spotlight = spotlight && cfg!(target_os = "macos");
+    }
+
+    pub fn debug_directories(&self) -> &[PathBuf] {
+        &self.debug_directories
+    }
+
+    pub fn dsym_directories(&self) -> &[PathBuf] {
+        &self.dsym_directories
+    }
+
+    /// The debug file of `view`, for Mach-O and ELF views.
+    pub fn find(&self, view: &BinaryView) -> Option<PathBuf> {
+        let binary_path = PathBuf::from(view.file().filename().to_string());
+        match view.type_name().as_str() {
+            "Mach-O" => {
+                let raw_view = view.raw_view()?;
+                let uuids = macho_uuids(&mut |offset, len| {
+                    let data = raw_view.read_vec(offset, len);
+                    (data.len() == len).then_some(data)
+                });
+                self.find_for_uuids(&binary_path, &uuids)
+            }
+            "ELF" => {
+                if let Some(path) = build_id(view).and_then(|id| self.find_for_build_id(&id)) {
+                    return Some(path);
+                }
+                let link = debug_link(view)?;
+                self.find_for_debug_link(&binary_path, &link)
+            }
+            _ => None,
+        }
+    }
+
+    /// The debug file stored under `build_id`, a lowercase hex string, in a debug directory.
+    ///
+    /// Both the `.build-id` layout of GDB and the layout of debuginfod caches are searched.
+    pub fn find_for_build_id(&self, build_id: &str) -> Option<PathBuf> {
+        // The id ends up in paths
+        if build_id.len() < 3 || !build_id.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let (prefix, rest) = build_id.split_at(2);
+        self.debug_directories.iter().find_map(|directory| {
+            [
+                directory
+                    .join(".build-id")
+                    .join(prefix)
+                    .join(format!("{}.debug", rest)),
+                directory.join(prefix).join(format!("{}.debug", rest)),
+                directory.join(prefix).join(rest).join("elf"),
+            ]
+            .into_iter()
+            .find(|path| path.is_file())
+        })
+    }
+
+    /// The debug file `link` of the binary at `binary_path`, checked against the CRC of the link.
+    ///
+    /// The file is looked for next to the binary, in a `.debug` directory next to it, and under the
+    /// path of the binary in the debug directories.
+    pub fn find_for_debug_link(&self, binary_path: &Path, link: &DebugLink) -> Option<PathBuf> {
+        if link.file_name.is_empty() || link.file_name.contains(['/', '\\']) {
+            return None;
+        }
+        let binary_dir = binary_path.parent().unwrap_or(Path::new(""));
+        let mut candidates = vec![
+            binary_dir.join(&link.file_name),
+            binary_dir.join(".debug").join(&link.file_name),
+        ];
+        // The directory of the binary is repeated under the debug directory
+        let relative_dir: PathBuf = binary_dir
+            .components()
+            .filter(|component| matches!(component, std::path::Component::Normal(_)))
+            .collect();
+        for directory in &self.debug_directories {
+            candidates.push(directory.join(&relative_dir).join(&link.file_name));
+        }
+
+        candidates.into_iter().find(|candidate| {
+            // A binary may link to a file of its own name in another directory
+            let is_binary = fs::canonicalize(candidate).ok() == fs::canonicalize(binary_path).ok();
+            !is_binary
+                && candidate.is_file()
+                && fs::read(candidate).is_ok_and(|data| debug_link_crc(&data) == link.crc)
+        })
+    }
+
+    /// The DWARF file of a `.dSYM` bundle matching one of `uuids`, the UUIDs of the binary at
+    /// `binary_path`.
+    ///
+    /// The bundle is looked for next to the binary, in the dSYM directories, and on macOS with
+    /// Spotlight, which indexes the UUIDs of the bundles it finds.
+    pub fn find_for_uuids(&self, binary_path: &Path, uuids: &[[u8; 16]]) -> Option<PathBuf> {
+        if uuids.is_empty() {
+            return None;
+        }
+        let mut bundles = vec![];
+        let file_name = binary_path.file_name().unwrap_or_default();
+        let mut sibling = binary_path.as_os_str().to_owned();
+        sibling.push(".dSYM");
+        bundles.push(PathBuf::from(sibling));
+        for directory in &self.dsym_directories {
+            if is_dsym_bundle(directory) {
+                bundles.push(directory.clone());
+            }
+            let mut named = directory.join(file_name).into_os_string();
+            named.push(".dSYM");
+            bundles.push(PathBuf::from(named));
+            // Other bundles in the directory, which may be named after an app rather than
+            // the binary
+            if let Ok(entries) = fs::read_dir(directory) {
+                bundles.extend(
+                    entries
+                        .flatten()
+                        .map(|entry| entry.path())
+                        .filter(|path| is_dsym_bundle(path)),
+                );
+            }
+        }
+        if self.spotlight {
+            for uuid in uuids {
+                bundles.extend(spotlight_dsym_bundles(uuid));
+            }
+        }
+
+        bundles
+            .iter()
+            .find_map(|bundle| dsym_dwarf_file(bundle, uuids))
+    }
+}
+
+fn is_dsym_bundle(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension == "dSYM")
+        && path.is_dir()
+}
+
+/// The file in `Contents/Resources/DWARF` of `bundle` with one of `uuids`.
+fn dsym_dwarf_file(bundle: &Path, uuids: &[[u8; 16]]) -> Option<PathBuf> {
+    let entries = fs::read_dir(bundle.join("Contents/Resources/DWARF")).ok()?;
+    entries.flatten().map(|entry| entry.path()).find(|path| {
+        let Ok(mut file) = File::open(path) else {
+            return false;
+        };
+        let file_uuids = macho_uuids(&mut |offset, len| {
+            let mut data = vec![0; len];
+            file.seek(SeekFrom::Start(offset)).ok()?;
+            file.read_exact(&mut data).ok()?;
+            Some(data)
+        });
+        file_uuids.iter().any(|uuid| uuids.contains(uuid))
+    })
+}
+
+/// The bundles Spotlight has indexed with `uuid` (`com_apple_xcode_dsym_uuids`).
+fn spotlight_dsym_bundles(uuid: &[u8; 16]) -> Vec<PathBuf> {
+    let query = format!("com_apple_xcode_dsym_uuids == {}", format_uuid(uuid));
+    let Ok(output) = Command::new("mdfind").arg(query).output() else {
+        return vec![];
+    };
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// `uuid` as Spotlight and `dwarfdump --uuid` show it, in uppercase hex groups of 8-4-4-4-12.
+pub fn format_uuid(uuid: &[u8; 16]) -> String {
+    let hex: String = uuid.iter().map(|b| format!("{:02X}", b)).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// The UUIDs of the `LC_UUID` load commands of a Mach-O file, one per architecture of a fat file.
+///
+/// `read` returns `len` bytes at `offset` of the file, `None` past its end.
+pub fn macho_uuids(read: &mut dyn FnMut(u64, usize) -> Option<Vec<u8>>) -> Vec<[u8; 16]> {
+    let Some(magic) = read(0, 4) else {
+        return vec![];
+    };
+    let magic_be = u32::from_be_bytes(magic[..4].try_into().unwrap());
+    let arch_size = match magic_be {
+        FAT_MAGIC => 20,
+        FAT_MAGIC_64 => 32,
+        _ => return macho_uuid(read, 0).into_iter().collect(),
+    };
+
+    // Fat headers are always big endian
+    let Some(count) = read(4, 4).map(|b| u32::from_be_bytes(b[..4].try_into().unwrap())) else {
+        return vec![];
+    };
+    if count > MAX_FAT_ARCHS {
+        return vec![];
+    }
+    let mut uuids = vec![];
+    for i in 0..count as u64 {
+        let Some(arch) = read(8 + i * arch_size, arch_size as usize) else {
+            break;
+        };
+        let offset = match magic_be {
+            FAT_MAGIC => u32::from_be_bytes(arch[8..12].try_into().unwrap()) as u64,
+            _ => u64::from_be_bytes(arch[8..16].try_into().unwrap()),
+        };
+        uuids.extend(macho_uuid(read, offset));
+    }
+    uuids
+}
+
+/// The UUID of the thin Mach-O file at `base`.
+fn macho_uuid(read: &mut dyn FnMut(u64, usize) -> Option<Vec<u8>>, base: u64) -> Option<[u8; 16]> {
+    let header = read(base, 28)?;
+    let magic = u32::from_le_bytes(header[..4].try_into().unwrap());
+    let (big_endian, header_size) = match (magic, magic.swap_bytes()) {
+        (MH_MAGIC, _) => (false, 28),
+        (MH_MAGIC_64, _) => (false, 32),
+        (_, MH_MAGIC) => (true, 28),
+        (_, MH_MAGIC_64) => (true, 32),
+        _ => return None,
+    };
+    let u32_at = |data: &[u8], offset: usize| {
+        let bytes = data[offset..offset + 4].try_into().unwrap();
+        match big_endian {
+            true => u32::from_be_bytes(bytes),
+            false => u32::from_le_bytes(bytes),
+        }
+    };
+    let command_count = u32_at(&header, 16);
+    let commands_size = u32_at(&header, 20) as usize;
+    let commands = read(base + header_size, commands_size)?;
+
+    let mut offset = 0;
+    for _ in 0..command_count {
+        let command = commands.get(offset..offset + 8)?;
+        let (cmd, size) = (u32_at(command, 0), u32_at(command, 4) as usize);
+        if cmd == LC_UUID {
+            return commands.get(offset + 8..offset + 24)?.try_into().ok();
+        }
+        if size < 8 {
+            return None;
+        }
+        offset += size;
+    }
+    None
+}
+
+/// Contents of the section named `name` of `view`, or of its raw view, which holds the sections
+/// that are not loaded.
+fn section_contents(view: &BinaryView, name: &str) -> Option<(Vec<u8>, Endianness)> {
+    let views = [Some(view.to_owned()), view.raw_view()];
+    views.into_iter().flatten().find_map(|view| {
+        let section = view.section_by_name(name)?;
+        let data = view.read_vec(section.start(), section.len());
+        Some((data, view.default_endianness()))
+    })
+}
+
+/// The build id of `view`, as a lowercase hex string.
+fn build_id(view: &BinaryView) -> Option<String> {
+    let (note, endianness) = section_contents(view, ".note.gnu.build-id")?;
+    parse_build_id_note(&note, endianness)
+}
+
+fn parse_build_id_note(note: &[u8], endianness: Endianness) -> Option<String> {
+    let u32_at = |offset: usize| -> Option<u32> {
+        let bytes = note.get(offset..offset + 4)?.try_into().ok()?;
+        Some(match endianness {
+            Endianness::LittleEndian => u32::from_le_bytes(bytes),
+            Endianness::BigEndian => u32::from_be_bytes(bytes),
+        })
+    };
+    let (name_len, desc_len) = (u32_at(0)? as usize, u32_at(4)? as usize);
+    if u32_at(8)? != NT_GNU_BUILD_ID {
+        return None;
+    }
+    // The descriptor starts after the name, padded to 4 bytes
+    let desc_start = 12 + name_len.next_multiple_of(4);
+    let desc = note.get(desc_start..desc_start + desc_len)?;
+    (!desc.is_empty()).then(|| desc.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Whether `view` records anything [`DebugFileLocator::find`] looks its debug file up by. Only
+/// checks the view type and section names, without reading them or searching any directory.
+pub fn may_have_debug_file(view: &BinaryView) -> bool {
+    match view.type_name().as_str() {
+        "Mach-O" => view.raw_view().is_some(),
+        "ELF" => [Some(view.to_owned()), view.raw_view()]
+            .into_iter()
+            .flatten()
+            .any(|view| {
+                view.section_by_name(".note.gnu.build-id").is_some()
+                    || view.section_by_name(".gnu_debuglink").is_some()
+            }),
+        _ => false,
+    }
+}
+
+/// The debug link of `view`, from its `.gnu_debuglink` section.
+pub fn debug_link(view: &BinaryView) -> Option<DebugLink> {
+    let (section, endianness) = section_contents(view, ".gnu_debuglink")?;
+    parse_debug_link(&section, endianness)
+}
+
+/// Parse a `.gnu_debuglink` section: the file name, padded to 4 bytes, then the CRC.
+pub fn parse_debug_link(section: &[u8], endianness: Endianness) -> Option<DebugLink> {
+    let name_len = section.iter().position(|&b| b == 0)?;
+    let file_name = std::str::from_utf8(&section[..name_len]).ok()?;
+    let crc_offset = (name_len + 1).next_multiple_of(4);
+    let crc = section.get(crc_offset..crc_offset + 4)?.try_into().ok()?;
+    let crc = match endianness {
+        Endianness::LittleEndian => u32::from_le_bytes(crc),
+        Endianness::BigEndian => u32::from_be_bytes(crc),
+    };
+    Some(DebugLink {
+        file_name: file_name.to_string(),
+        crc,
+    })
+}
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = match crc & 1 {
+                1 => (crc >> 1) ^ 0xedb88320,
+                _ => crc >> 1,
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// The CRC-32 a debug link records for `data`, the same as that of zlib.
+pub fn debug_link_crc(data: &[u8]) -> u32 {
+    let crc = data.iter().fold(!0u32, |crc, &b| {
+        CRC_TABLE[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8)
+    });
+    !crc
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// A thin 64-bit little endian Mach-O header with a segment command and an `LC_UUID`.
+    fn macho(uuid: [u8; 16]) -> Vec<u8> {
+        let mut commands = vec![];
+        commands.extend(0x19u32.to_le_bytes());
+        commands.extend(72u32.to_le_bytes());
+        commands.extend([0; 64]);
+        commands.extend(LC_UUID.to_le_bytes());
+        commands.extend(24u32.to_le_bytes());
+        commands.extend(uuid);
+        let mut file = MH_MAGIC_64.to_le_bytes().to_vec();
+        file.extend([0; 12]);
+        file.extend(2u32.to_le_bytes());
+        file.extend((commands.len() as u32).to_le_bytes());
+        file.extend([0; 8]);
+        file.extend(commands);
+        file
+    }
+
+    fn uuids_of(data: &[u8]) -> Vec<[u8; 16]> {
+        macho_uuids(&mut |offset, len| {
+            let start = offset as usize;
+            data.get(start..start + len).map(<[u8]>::to_vec)
+        })
+    }
+
+    #[test]
+    fn reads_macho_uuids() {
+        let (a, b) = ([0xaa; 16], [0xbb; 16]);
+        assert_eq!(uuids_of(&macho(a)), [a]);
+
+        // A fat file with both at page aligned offsets
+        let mut fat = FAT_MAGIC.to_be_bytes().to_vec();
+        fat.extend(2u32.to_be_bytes());
+        for offset in [0x1000u32, 0x2000] {
+            fat.extend([0; 8]);
+            fat.extend(offset.to_be_bytes());
+            fat.extend([0; 8]);
+        }
+        fat.resize(0x1000, 0);
+        fat.extend(macho(a));
+        fat.resize(0x2000, 0);
+        fat.extend(macho(b));
+        assert_eq!(uuids_of(&fat), [a, b]);
+
+        assert!(uuids_of(b"\x7fELF").is_empty());
+        assert!(uuids_of(&macho(a)[..40]).is_empty());
+        assert_eq!(
+            format_uuid(&[
+                0x1d, 0x22, 0x5f, 0x32, 0xb8, 0x6d, 0x4e, 0x27, 0x8c, 0x4b, 0xf4, 0x9c, 0xb5, 0xd1,
+                0xa7, 0x34
+            ]),
+            "1D225F32-B86D-4E27-8C4B-F49CB5D1A734"
+        );
+    }
+
+    #[test]
+    fn parses_debug_links() {
+        assert_eq!(debug_link_crc(b"123456789"), 0xcbf43926);
+        let mut section = b"cat.debug\0\0\0".to_vec();
+        section.extend(0xcbf43926u32.to_le_bytes());
+        assert_eq!(
+            parse_debug_link(&section, Endianness::LittleEndian),
+            Some(DebugLink {
+                file_name: "cat.debug".to_string(),
+                crc: 0xcbf43926
+            })
+        );
+        assert!(parse_debug_link(&section[..14], Endianness::LittleEndian).is_none());
+
+        let mut note = vec![4, 0, 0, 0, 3, 0, 0, 0, 3, 0, 0, 0];
+        note.extend(b"GNU\0\xde\xad\x01");
+        assert_eq!(
+            parse_build_id_note(&note, Endianness::LittleEndian).as_deref(),
+            Some("dead01")
+        );
+    }
+
+    #[test]
+    fn finds_debug_files() {
+        let root = tempfile::tempdir().unwrap();
+        let binary = root.path().join("bin/cat");
+        fs::create_dir_all(root.path().join("bin/.debug")).unwrap();
+        fs::write(&binary, b"binary").unwrap();
+        fs::write(root.path().join("bin/cat.debug"), b"stale").unwrap();
+        fs::write(root.path().join("bin/.debug/cat.debug"), b"debug").unwrap();
+        let link = DebugLink {
+            file_name: "cat.debug".to_string(),
+            crc: debug_link_crc(b"debug"),
+        };
+
+        let mut locator = DebugFileLocator::new();
+        locator.set_spotlight(false);
+        assert_eq!(
+            locator.find_for_debug_link(&binary, &link),
+            Some(root.path().join("bin/.debug/cat.debug"))
+        );
+
+        let debug_dir = root.path().join("debug");
+        let build_id_path = debug_dir.join(".build-id/ab/cdef.debug");
+        fs::create_dir_all(build_id_path.parent().unwrap()).unwrap();
+        fs::write(&build_id_path, b"debug").unwrap();
+        locator.add_debug_directory(&debug_dir);
+        assert_eq!(locator.find_for_build_id("abcdef"), Some(build_id_path));
+        assert_eq!(locator.find_for_build_id("../../x"), None);
+
+        let dwarf_dir = root.path().join("dsyms/App.dSYM/Contents/Resources/DWARF");
+        fs::create_dir_all(&dwarf_dir).unwrap();
+        fs::write(dwarf_dir.join("cat"), macho([0xaa; 16])).unwrap();
+        assert_eq!(locator.find_for_uuids(&binary, &[[0xaa; 16]]), None);
+        locator.add_dsym_directory(root.path().join("dsyms"));
+        assert_eq!(
+            locator.find_for_uuids(&binary, &[[0xaa; 16]]),
+            Some(dwarf_dir.join("cat"))
+        );
+        assert_eq!(locator.find_for_uuids(&binary, &[[0xbb; 16]]), None);
+    }
+}
@@ -0,0 +1,479 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An on-disk cache of the decompiled (HLIL) text of every function in a view.
+
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::CString;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use binaryninjacore_sys::{
+    BNInstructionTextToken, BNInstructionTextTokenContext, BNInstructionTextTokenType,
+};
+use thiserror::Error;
+
+use crate::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
+use crate::disassembly::{DisassemblyTextLine, InstructionTextToken};
+use crate::function::Function;
+use crate::string::raw_to_string;
+
+const MAGIC: &[u8; 4] = b"BNDC";
+const VERSION: u32 = 1;
+const FINGERPRINT_CHUNK_SIZE: usize = 0x100000;
+
+#[derive(Error, Debug)]
+pub enum DecompilationCacheError {
+    #[error("i/o error: {0}")]
+    Io(#[from] io::Error),
+    #[error("not a decompilation cache")]
+    InvalidMagic,
+    #[error("unsupported decompilation cache version {0}")]
+    UnsupportedVersion(u32),
+    #[error("corrupt decompilation cache: {0}")]
+    Corrupt(&'static str),
+}
+
+/// The decompiled text of a single function.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CachedFunction {
+    pub start: u64,
+    pub name: String,
+    pub lines: Vec<DisassemblyTextLine>,
+}
+
+/// The decompiled text of every function in a view, which can be saved and reloaded later without
+/// running analysis again.
+///
+/// The format is a string table followed by the token stream of each function, all integers are
+/// LEB128 encoded. Tags, highlights and type info attached to lines are not stored.
+///
+/// ```no_run
+/// use binaryninja::decompilation_cache::DecompilationCache;
+/// use binaryninja::headless::Session;
+///
+/// let session = Session::new().expect("Failed to initialize session");
+/// let view = session.load("/bin/cat").expect("Failed to load view");
+/// DecompilationCache::from_view(&view)
+///     .save("/tmp/cat.hlilcache")
+///     .expect("Failed to save cache");
+///
+/// // Later, without analysis...
+/// let cache = DecompilationCache::load("/tmp/cat.hlilcache").expect("Failed to load cache");
+/// for function in cache.functions() {
+///     println!("{}:", function.name);
+///     for line in &function.lines {
+///         println!("    {}", line);
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DecompilationCache {
+    fingerprint: u64,
+    functions: BTreeMap<u64, CachedFunction>,
+}
+
+impl DecompilationCache {
+    /// Decompile every function in the view, generating HLIL for functions that do not have it yet.
+    pub fn from_view(view: &BinaryView) -> Self {
+        let mut cache = Self {
+            fingerprint: Self::fingerprint(view),
+            functions: BTreeMap::new(),
+        };
+        for function in view.functions().iter() {
+            if let Some(cached) = Self::decompile(&function) {
+                cache.insert(cached);
+            }
+        }
+        cache
+    }
+
+    fn decompile(function: &Function) -> Option<CachedFunction> {
+        let hlil = function.high_level_il(true).ok()?;
+        Some(CachedFunction {
+            start: function.start(),
+            name: function.symbol().full_name().to_string(),
+            lines: hlil.root().lines().iter().collect(),
+        })
+    }
+
+    /// A hash of the original file contents, used to check the cache belongs to a view.
+    pub fn fingerprint(view: &BinaryView) -> u64 {
+        let file = view.file();
+        let raw_view = file.view_of_type("Raw");
+        let view = raw_view.as_deref().unwrap_or(view);
+        // FNV-1a
+        let mut hash: u64 = 0xcbf29ce484222325;
+        let mut offset = view.start();
+        let end = view.start() + view.len();
+        while offset < end {
+            let len = FINGERPRINT_CHUNK_SIZE.min((end - offset) as usize);
+            for byte in view.read_vec(offset, len) {
+                hash ^= byte as u64;
+                hash = hash.wrapping_mul(0x100000001b3);
+            }
+            offset += len as u64;
+        }
+        hash
+    }
+
+    /// Whether the cache was created from the same file as `view`.
+    pub fn is_valid_for(&self, view: &BinaryView) -> bool {
+        self.fingerprint == Self::fingerprint(view)
+    }
+
+    pub fn insert(&mut self, function: CachedFunction) {
+        self.functions.insert(function.start, function);
+    }
+
+    /// Re-decompile a single function, e.g. after it was changed.
+    pub fn update(&mut self, function: &Function) {
+        match Self::decompile(function) {
+            Some(cached) => self.insert(cached),
+            None => {
+                self.functions.remove(&function.start());
+            }
+        }
+    }
+
+    pub fn function(&self, start: u64) -> Option<&CachedFunction> {
+        self.functions.get(&start)
+    }
+
+    pub fn functions(&self) -> impl Iterator<Item = &CachedFunction> {
+        self.functions.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.functions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty()
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), DecompilationCacheError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, DecompilationCacheError> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> Result<(), DecompilationCacheError> {
+        let mut strings = StringTable::default();
+        let mut body = Vec::new();
+        write_uint(&mut body, self.functions.len() as u64);
+        for function in self.functions.values() {
+            write_uint(&mut body, function.start);
+            write_uint(&mut body, strings.index(&function.name));
+            write_uint(&mut body, function.lines.len() as u64);
+            for line in &function.lines {
+                write_uint(&mut body, line.address.wrapping_sub(function.start));
+                write_uint(&mut body, line.instruction_index as u64);
+                write_uint(&mut body, line.tokens.len() as u64);
+                for token in &line.tokens {
+                    write_token(&mut body, &mut strings, token);
+                }
+            }
+        }
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        writer.write_all(&self.fingerprint.to_le_bytes())?;
+        let mut header = Vec::new();
+        write_uint(&mut header, strings.strings.len() as u64);
+        for string in &strings.strings {
+            write_uint(&mut header, string.len() as u64);
+            header.extend_from_slice(string.as_bytes());
+        }
+        writer.write_all(&header)?;
+        writer.write_all(&body)?;
+        Ok(())
+    }
+
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self, DecompilationCacheError> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        let mut input = Input { data: &data };
+
+        if input.take(MAGIC.len())? != MAGIC {
+            return Err(DecompilationCacheError::InvalidMagic);
+        }
+        let version = u32::from_le_bytes(input.take(4)?.try_into().unwrap());
+        if version != VERSION {
+            return Err(DecompilationCacheError::UnsupportedVersion(version));
+        }
+        let fingerprint = u64::from_le_bytes(input.take(8)?.try_into().unwrap());
+
+        let string_count = input.len()?;
+        let mut strings = Vec::with_capacity(string_count.min(data.len()));
+        for _ in 0..string_count {
+            let len = input.len()?;
+            let string = std::str::from_utf8(input.take(len)?)
+                .map_err(|_| DecompilationCacheError::Corrupt("invalid string"))?;
+            strings.push(string.to_string());
+        }
+        let string = |input: &mut Input| -> Result<String, DecompilationCacheError> {
+            strings
+                .get(input.len()?)
+                .cloned()
+                .ok_or(DecompilationCacheError::Corrupt("invalid string index"))
+        };
+
+        let mut functions = BTreeMap::new();
+        for _ in 0..input.len()? {
+            let start = input.uint()?;
+            let name = string(&mut input)?;
+            let line_count = input.len()?;
+            let mut lines = Vec::with_capacity(line_count.min(data.len()));
+            for _ in 0..line_count {
+                let address = start.wrapping_add(input.uint()?);
+                let instruction_index = input.len()?;
+                let token_count = input.len()?;
+                let mut tokens = Vec::with_capacity(token_count.min(data.len()));
+                for _ in 0..token_count {
+                    tokens.push(read_token(&mut input, &string)?);
+                }
+                lines.push(DisassemblyTextLine {
+                    address,
+                    instruction_index,
+                    tokens,
+                    ..Default::default()
+                });
+            }
+            functions.insert(start, CachedFunction { start, name, lines });
+        }
+        Ok(Self {
+            fingerprint,
+            functions,
+        })
+    }
+}
+
+#[derive(Default)]
+struct StringTable {
+    strings: Vec<String>,
+    indices: HashMap<String, u64>,
+}
+
+impl StringTable {
+    fn index(&mut self, string: &str) -> u64 {
+        if let Some(index) = self.indices.get(string) {
+            return *index;
+        }
+        let index = self.strings.len() as u64;
+        self.strings.push(string.to_string());
+        self.indices.insert(string.to_string(), index);
+        index
+    }
+}
+
+fn write_uint(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+struct Input<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Input<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecompilationCacheError> {
+        if len > self.data.len() {
+            return Err(DecompilationCacheError::Corrupt("unexpected end of data"));
+        }
+        let (taken, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    fn uint(&mut self) -> Result<u64, DecompilationCacheError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(DecompilationCacheError::Corrupt("integer overflow"))
+    }
+
+    fn len(&mut self) -> Result<usize, DecompilationCacheError> {
+        usize::try_from(self.uint()?)
+            .map_err(|_| DecompilationCacheError::Corrupt("length overflow"))
+    }
+}
+
+fn write_token(out: &mut Vec<u8>, strings: &mut StringTable, token: &InstructionTextToken) {
+    // Going through the raw token flattens the kind into the fields the core uses
+    let raw = InstructionTextToken::into_raw(token.clone());
+    let type_names: Vec<String> = match raw.typeNames.is_null() {
+        true => Vec::new(),
+        false => unsafe { std::slice::from_raw_parts(raw.typeNames, raw.namesCount) }
+            .iter()
+            .filter_map(|&name| raw_to_string(name))
+            .collect(),
+    };
+    write_uint(out, raw.type_ as u64);
+    write_uint(out, strings.index(&token.text));
+    write_uint(out, raw.value);
+    write_uint(out, raw.size as u64);
+    write_uint(out, raw.operand as u64);
+    write_uint(out, raw.context as u64);
+    out.push(raw.confidence);
+    write_uint(out, raw.address);
+    write_uint(out, raw.exprIndex as u64);
+    write_uint(out, type_names.len() as u64);
+    for name in &type_names {
+        write_uint(out, strings.index(name));
+    }
+    InstructionTextToken::free_raw(raw);
+}
+
+/// The token type stored as its value in `binaryninjacore.h`.
+fn token_type(raw: u64) -> Option<BNInstructionTextTokenType> {
+    Some(match raw {
+        0 => BNInstructionTextTokenType::TextToken,
+        1 => BNInstructionTextTokenType::InstructionToken,
+        2 => BNInstructionTextTokenType::OperandSeparatorToken,
+        3 => BNInstructionTextTokenType::RegisterToken,
+        4 => BNInstructionTextTokenType::IntegerToken,
+        5 => BNInstructionTextTokenType::PossibleAddressToken,
+        6 => BNInstructionTextTokenType::BeginMemoryOperandToken,
+        7 => BNInstructionTextTokenType::EndMemoryOperandToken,
+        8 => BNInstructionTextTokenType::FloatingPointToken,
+        9 => BNInstructionTextTokenType::AnnotationToken,
+        10 => BNInstructionTextTokenType::CodeRelativeAddressToken,
+        11 => BNInstructionTextTokenType::ArgumentNameToken,
+        12 => BNInstructionTextTokenType::HexDumpByteValueToken,
+        13 => BNInstructionTextTokenType::HexDumpSkippedByteToken,
+        14 => BNInstructionTextTokenType::HexDumpInvalidByteToken,
+        15 => BNInstructionTextTokenType::HexDumpTextToken,
+        16 => BNInstructionTextTokenType::OpcodeToken,
+        17 => BNInstructionTextTokenType::StringToken,
+        18 => BNInstructionTextTokenType::CharacterConstantToken,
+        19 => BNInstructionTextTokenType::KeywordToken,
+        20 => BNInstructionTextTokenType::TypeNameToken,
+        21 => BNInstructionTextTokenType::FieldNameToken,
+        22 => BNInstructionTextTokenType::NameSpaceToken,
+        23 => BNInstructionTextTokenType::NameSpaceSeparatorToken,
+        24 => BNInstructionTextTokenType::TagToken,
+        25 => BNInstructionTextTokenType::StructOffsetToken,
+        26 => BNInstructionTextTokenType::StructOffsetByteValueToken,
+        27 => BNInstructionTextTokenType::StructureHexDumpTextToken,
+        28 => BNInstructionTextTokenType::GotoLabelToken,
+        29 => BNInstructionTextTokenType::CommentToken,
+        30 => BNInstructionTextTokenType::PossibleValueToken,
+        31 => BNInstructionTextTokenType::PossibleValueTypeToken,
+        32 => BNInstructionTextTokenType::ArrayIndexToken,
+        33 => BNInstructionTextTokenType::IndentationToken,
+        34 => BNInstructionTextTokenType::UnknownMemoryToken,
+        35 => BNInstructionTextTokenType::EnumerationMemberToken,
+        36 => BNInstructionTextTokenType::OperationToken,
+        37 => BNInstructionTextTokenType::BaseStructureNameToken,
+        38 => BNInstructionTextTokenType::BaseStructureSeparatorToken,
+        39 => BNInstructionTextTokenType::BraceToken,
+        64 => BNInstructionTextTokenType::CodeSymbolToken,
+        65 => BNInstructionTextTokenType::DataSymbolToken,
+        66 => BNInstructionTextTokenType::LocalVariableToken,
+        67 => BNInstructionTextTokenType::ImportToken,
+        68 => BNInstructionTextTokenType::AddressDisplayToken,
+        69 => BNInstructionTextTokenType::IndirectImportToken,
+        70 => BNInstructionTextTokenType::ExternalSymbolToken,
+        71 => BNInstructionTextTokenType::StackVariableToken,
+        72 => BNInstructionTextTokenType::AddressSeparatorToken,
+        73 => BNInstructionTextTokenType::CollapsedInformationToken,
+        74 => BNInstructionTextTokenType::CollapseStateIndicatorToken,
+        _ => return None,
+    })
+}
+
+/// The token context stored as its value in `binaryninjacore.h`.
+fn token_context(raw: u64) -> Option<BNInstructionTextTokenContext> {
+    Some(match raw {
+        0 => BNInstructionTextTokenContext::NoTokenContext,
+        1 => BNInstructionTextTokenContext::LocalVariableTokenContext,
+        2 => BNInstructionTextTokenContext::DataVariableTokenContext,
+        3 => BNInstructionTextTokenContext::FunctionReturnTokenContext,
+        4 => BNInstructionTextTokenContext::InstructionAddressTokenContext,
+        5 => BNInstructionTextTokenContext::ILInstructionIndexTokenContext,
+        6 => BNInstructionTextTokenContext::ConstDataTokenContext,
+        7 => BNInstructionTextTokenContext::ConstStringDataTokenContext,
+        8 => BNInstructionTextTokenContext::StringReferenceTokenContext,
+        9 => BNInstructionTextTokenContext::StringDataVariableTokenContext,
+        10 => BNInstructionTextTokenContext::StringDisplayTokenContext,
+        11 => BNInstructionTextTokenContext::ContentCollapsedContext,
+        12 => BNInstructionTextTokenContext::ContentExpandedContext,
+        13 => BNInstructionTextTokenContext::ContentCollapsiblePadding,
+        _ => return None,
+    })
+}
+
+fn read_token(
+    input: &mut Input,
+    string: &impl Fn(&mut Input) -> Result<String, DecompilationCacheError>,
+) -> Result<InstructionTextToken, DecompilationCacheError> {
+    let token_type =
+        token_type(input.uint()?).ok_or(DecompilationCacheError::Corrupt("unknown token type"))?;
+    let text = CString::new(string(input)?)
+        .map_err(|_| DecompilationCacheError::Corrupt("invalid token text"))?;
+    let value = input.uint()?;
+    let size = input.len()?;
+    let operand = input.len()?;
+    let context = token_context(input.uint()?)
+        .ok_or(DecompilationCacheError::Corrupt("unknown token context"))?;
+    let confidence = input.take(1)?[0];
+    let address = input.uint()?;
+    let expr_index = input.len()?;
+    let type_names = (0..input.len()?)
+        .map(|_| {
+            CString::new(string(input)?)
+                .map_err(|_| DecompilationCacheError::Corrupt("invalid type name"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut type_name_ptrs: Vec<_> = type_names
+        .iter()
+        .map(|name| name.as_ptr() as *mut _)
+        .collect();
+
+    let raw = BNInstructionTextToken {
+        type_: token_type,
+        text: text.as_ptr() as *mut _,
+        value,
+        width: 0,
+        size,
+        operand,
+        context,
+        confidence,
+        address,
+        typeNames: type_name_ptrs.as_mut_ptr(),
+        namesCount: type_name_ptrs.len(),
+        exprIndex: expr_index,
+    };
+    // `from_raw` copies everything out of the raw token, so our strings only need to outlive it
+    Ok(InstructionTextToken::from_raw(&raw))
+}
@@ -19,6 +19,7 @@ use std::ffi::{c_char, c_void};
 
 use crate::architecture::CoreArchitecture;
 use crate::binary_view::BinaryView;
+use crate::platform::Platform;
 use crate::string::{raw_to_string, BnStrCompatible, BnString};
 use crate::types::{QualifiedName, Type};
 
@@ -26,6 +27,10 @@ use crate::rc::*;
 
 pub type Result<R> = std::result::Result<R, ()>;
 
+/// Demangle `mangled_name` with the first registered [`Demangler`] that recognizes it.
+///
+/// * `view` - View whose settings the demangler may consult, e.g. to simplify templates
+/// * `simplify` - Whether to simplify the names of templates from the standard library
 pub fn demangle_generic<S: BnStrCompatible>(
     arch: &CoreArchitecture,
     mangled_name: S,
@@ -58,6 +63,7 @@ pub fn demangle_generic<S: BnStrCompatible>(
     }
 }
 
+/// Demangle a name mangled by LLVM, Itanium or Rust style, without a type.
 pub fn demangle_llvm<S: BnStrCompatible>(mangled_name: S, simplify: bool) -> Option<QualifiedName> {
     let mangled_name_bwn = mangled_name.into_bytes_with_nul();
     let mangled_name_ptr = mangled_name_bwn.as_ref();
@@ -72,21 +78,36 @@ pub fn demangle_llvm<S: BnStrCompatible>(mangled_name: S, simplify: bool) -> Opt
         )
     };
 
-    match res {
-        true => {
-            assert!(!out_name.is_null());
-            let names: Vec<_> = unsafe { ArrayGuard::<BnString>::new(out_name, out_size, ()) }
-                .iter()
-                .map(str::to_string)
-                .collect();
-            unsafe { BNFreeDemangledName(&mut out_name, out_size) };
+    res.then(|| take_demangled_name(out_name, out_size))
+}
 
-            Some(names.into())
-        }
-        false => None,
-    }
+/// Like [`demangle_llvm`], simplifying names as the settings of `view` say to.
+pub fn demangle_llvm_with_options<S: BnStrCompatible>(
+    mangled_name: S,
+    view: Option<&BinaryView>,
+) -> Option<QualifiedName> {
+    let mangled_name_bwn = mangled_name.into_bytes_with_nul();
+    let mut out_name: *mut *mut c_char = std::ptr::null_mut();
+    let mut out_size: usize = 0;
+    let res = unsafe {
+        BNDemangleLLVMWithOptions(
+            mangled_name_bwn.as_ref().as_ptr() as *const c_char,
+            &mut out_name,
+            &mut out_size,
+            view.map_or(std::ptr::null(), |v| v.handle as *const _),
+        )
+    };
+
+    res.then(|| take_demangled_name(out_name, out_size))
 }
 
+/// Whether `mangled_name` is mangled with the GNU3 (Itanium C++ ABI) scheme.
+pub fn is_gnu3_mangled_string<S: BnStrCompatible>(mangled_name: S) -> bool {
+    let mangled_name_bwn = mangled_name.into_bytes_with_nul();
+    unsafe { BNIsGNU3MangledString(mangled_name_bwn.as_ref().as_ptr() as *const c_char) }
+}
+
+/// Demangle a name mangled with the GNU3 (Itanium C++ ABI) scheme, with the type it encodes.
 pub fn demangle_gnu3<S: BnStrCompatible>(
     arch: &CoreArchitecture,
     mangled_name: S,
@@ -108,26 +129,34 @@ pub fn demangle_gnu3<S: BnStrCompatible>(
         )
     };
 
-    match res {
-        true => {
-            assert!(!out_name.is_null());
-            let names: Vec<_> = unsafe { ArrayGuard::<BnString>::new(out_name, out_size, ()) }
-                .iter()
-                .map(str::to_string)
-                .collect();
-            unsafe { BNFreeDemangledName(&mut out_name, out_size) };
-
-            let out_type = match out_type.is_null() {
-                true => None,
-                false => Some(unsafe { Type::ref_from_raw(out_type) }),
-            };
-
-            Some((names.into(), out_type))
-        }
-        false => None,
-    }
+    res.then(|| take_demangled(out_type, out_name, out_size))
+}
+
+/// Like [`demangle_gnu3`], simplifying names as the settings of `view` say to.
+pub fn demangle_gnu3_with_options<S: BnStrCompatible>(
+    arch: &CoreArchitecture,
+    mangled_name: S,
+    view: Option<&BinaryView>,
+) -> Option<(QualifiedName, Option<Ref<Type>>)> {
+    let mangled_name_bwn = mangled_name.into_bytes_with_nul();
+    let mut out_type: *mut BNType = std::ptr::null_mut();
+    let mut out_name: *mut *mut c_char = std::ptr::null_mut();
+    let mut out_size: usize = 0;
+    let res = unsafe {
+        BNDemangleGNU3WithOptions(
+            arch.handle,
+            mangled_name_bwn.as_ref().as_ptr() as *const c_char,
+            &mut out_type,
+            &mut out_name,
+            &mut out_size,
+            view.map_or(std::ptr::null(), |v| v.handle as *const _),
+        )
+    };
+
+    res.then(|| take_demangled(out_type, out_name, out_size))
 }
 
+/// Demangle a name mangled by MSVC, with the type it encodes.
 pub fn demangle_ms<S: BnStrCompatible>(
     arch: &CoreArchitecture,
     mangled_name: S,
@@ -150,24 +179,78 @@ pub fn demangle_ms<S: BnStrCompatible>(
         )
     };
 
-    match res {
-        true => {
-            assert!(!out_name.is_null());
-            let names: Vec<_> = unsafe { ArrayGuard::<BnString>::new(out_name, out_size, ()) }
-                .iter()
-                .map(str::to_string)
-                .collect();
-            unsafe { BNFreeDemangledName(&mut out_name, out_size) };
-
-            let out_type = match out_type.is_null() {
-                true => None,
-                false => Some(unsafe { Type::ref_from_raw(out_type) }),
-            };
-
-            Some((names.into(), out_type))
-        }
-        false => None,
-    }
+    res.then(|| take_demangled(out_type, out_name, out_size))
+}
+
+/// Like [`demangle_ms`], simplifying names as the settings of `view` say to.
+pub fn demangle_ms_with_options<S: BnStrCompatible>(
+    arch: &CoreArchitecture,
+    mangled_name: S,
+    view: Option<&BinaryView>,
+) -> Option<(QualifiedName, Option<Ref<Type>>)> {
+    let mangled_name_bwn = mangled_name.into_bytes_with_nul();
+    let mut out_type: *mut BNType = std::ptr::null_mut();
+    let mut out_name: *mut *mut c_char = std::ptr::null_mut();
+    let mut out_size: usize = 0;
+    let res = unsafe {
+        BNDemangleMSWithOptions(
+            arch.handle,
+            mangled_name_bwn.as_ref().as_ptr() as *const c_char,
+            &mut out_type,
+            &mut out_name,
+            &mut out_size,
+            view.map_or(std::ptr::null(), |v| v.handle as *const _),
+        )
+    };
+
+    res.then(|| take_demangled(out_type, out_name, out_size))
+}
+
+/// Like [`demangle_ms`], with the calling conventions of `platform` in the type.
+pub fn demangle_ms_platform<S: BnStrCompatible>(
+    platform: &Platform,
+    mangled_name: S,
+    simplify: bool,
+) -> Option<(QualifiedName, Option<Ref<Type>>)> {
+    let mangled_name_bwn = mangled_name.into_bytes_with_nul();
+    let mut out_type: *mut BNType = std::ptr::null_mut();
+    let mut out_name: *mut *mut c_char = std::ptr::null_mut();
+    let mut out_size: usize = 0;
+    let res = unsafe {
+        BNDemangleMSPlatform(
+            platform.handle,
+            mangled_name_bwn.as_ref().as_ptr() as *const c_char,
+            &mut out_type,
+            &mut out_name,
+            &mut out_size,
+            simplify,
+        )
+    };
+
+    res.then(|| take_demangled(out_type, out_name, out_size))
+}
+
+/// Take the name and type output by a successful `BNDemangle*` call.
+fn take_demangled(
+    out_type: *mut BNType,
+    out_name: *mut *mut c_char,
+    out_size: usize,
+) -> (QualifiedName, Option<Ref<Type>>) {
+    let out_type = match out_type.is_null() {
+        true => None,
+        false => Some(unsafe { Type::ref_from_raw(out_type) }),
+    };
+    (take_demangled_name(out_name, out_size), out_type)
+}
+
+fn take_demangled_name(mut out_name: *mut *mut c_char, out_size: usize) -> QualifiedName {
+    assert!(!out_name.is_null());
+    let names: Vec<_> = unsafe { ArrayGuard::<BnString>::new(out_name, out_size, ()) }
+        .iter()
+        .map(str::to_string)
+        .collect();
+    unsafe { BNFreeDemangledName(&mut out_name, out_size) };
+    names.into()
 }
 
 #[derive(PartialEq, Eq, Hash)]
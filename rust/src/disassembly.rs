@@ -27,6 +27,8 @@ use std::convert::From;
 use std::fmt::{Display, Formatter};
 
 pub type DisassemblyOption = BNDisassemblyOption;
+pub type DisassemblyAddressMode = BNDisassemblyAddressMode;
+pub type DisassemblyCallParameterHints = BNDisassemblyCallParameterHints;
 pub type InstructionTextTokenType = BNInstructionTextTokenType;
 pub type StringType = BNStringType;
 
@@ -959,6 +961,17 @@ impl From<InstructionTextTokenContext> for BNInstructionTextTokenContext {
     }
 }
 
+/// Options for rendering disassembly and IL as text, used by the linear view, flow graphs and the
+/// text of functions and basic blocks.
+///
+/// ```no_run
+/// use binaryninja::disassembly::{DisassemblyOption, DisassemblySettings};
+///
+/// // Show the bytes of each instruction, on lines of at most 120 characters
+/// let settings = DisassemblySettings::default_linear_settings().duplicate();
+/// settings.set_option(DisassemblyOption::ShowOpcode, true);
+/// settings.set_width(120);
+/// ```
 // TODO: Make a builder for this.
 #[derive(PartialEq, Eq, Hash)]
 pub struct DisassemblySettings {
@@ -966,12 +979,35 @@ pub struct DisassemblySettings {
 }
 
 impl DisassemblySettings {
+    pub(crate) unsafe fn ref_from_raw(handle: *mut BNDisassemblySettings) -> Ref<Self> {
+        debug_assert!(!handle.is_null());
+        Ref::new(Self { handle })
+    }
+
     pub fn new() -> Ref<Self> {
-        unsafe {
-            let handle = BNCreateDisassemblySettings();
-            debug_assert!(!handle.is_null());
-            Ref::new(Self { handle })
-        }
+        unsafe { Self::ref_from_raw(BNCreateDisassemblySettings()) }
+    }
+
+    /// The settings the user has configured for disassembly.
+    ///
+    /// These are shared, use [`DisassemblySettings::duplicate`] to change them for a listing.
+    pub fn default_settings() -> Ref<Self> {
+        unsafe { Self::ref_from_raw(BNDefaultDisassemblySettings()) }
+    }
+
+    /// The settings the user has configured for the graph view.
+    pub fn default_graph_settings() -> Ref<Self> {
+        unsafe { Self::ref_from_raw(BNDefaultGraphDisassemblySettings()) }
+    }
+
+    /// The settings the user has configured for the linear view.
+    pub fn default_linear_settings() -> Ref<Self> {
+        unsafe { Self::ref_from_raw(BNDefaultLinearDisassemblySettings()) }
+    }
+
+    /// A copy of the settings that can be changed independently.
+    pub fn duplicate(&self) -> Ref<Self> {
+        unsafe { Self::ref_from_raw(BNDuplicateDisassemblySettings(self.handle)) }
     }
 
     pub fn set_option(&self, option: DisassemblyOption, state: bool) {
@@ -981,6 +1017,61 @@ impl DisassemblySettings {
     pub fn is_option_set(&self, option: DisassemblyOption) -> bool {
         unsafe { BNIsDisassemblySettingsOptionSet(self.handle, option) }
     }
+
+    /// The width in characters that lines are wrapped at.
+    pub fn width(&self) -> usize {
+        unsafe { BNGetDisassemblyWidth(self.handle) }
+    }
+
+    pub fn set_width(&self, width: usize) {
+        unsafe { BNSetDisassemblyWidth(self.handle, width) }
+    }
+
+    /// The width in characters that symbols are truncated to.
+    pub fn maximum_symbol_width(&self) -> usize {
+        unsafe { BNGetDisassemblyMaximumSymbolWidth(self.handle) }
+    }
+
+    pub fn set_maximum_symbol_width(&self, width: usize) {
+        unsafe { BNSetDisassemblyMaximumSymbolWidth(self.handle, width) }
+    }
+
+    /// The width in characters of the gutter before each line, which holds e.g. tag icons.
+    pub fn gutter_width(&self) -> usize {
+        unsafe { BNGetDisassemblyGutterWidth(self.handle) }
+    }
+
+    pub fn set_gutter_width(&self, width: usize) {
+        unsafe { BNSetDisassemblyGutterWidth(self.handle, width) }
+    }
+
+    /// How addresses are shown when [`DisassemblyOption::ShowAddress`] is set.
+    pub fn address_mode(&self) -> DisassemblyAddressMode {
+        unsafe { BNGetDisassemblyAddressMode(self.handle) }
+    }
+
+    pub fn set_address_mode(&self, mode: DisassemblyAddressMode) {
+        unsafe { BNSetDisassemblyAddressMode(self.handle, mode) }
+    }
+
+    /// The base addresses are shown relative to with
+    /// [`DisassemblyAddressMode::RelativeToAddressBaseOffsetDisassemblyAddressMode`].
+    pub fn address_base_offset(&self) -> u64 {
+        unsafe { BNGetDisassemblyAddressBaseOffset(self.handle) }
+    }
+
+    pub fn set_address_base_offset(&self, offset: u64) {
+        unsafe { BNSetDisassemblyAddressBaseOffset(self.handle, offset) }
+    }
+
+    /// When the names of parameters are shown next to the arguments of calls.
+    pub fn call_parameter_hints(&self) -> DisassemblyCallParameterHints {
+        unsafe { BNGetDisassemblyCallParameterHints(self.handle) }
+    }
+
+    pub fn set_call_parameter_hints(&self, hints: DisassemblyCallParameterHints) {
+        unsafe { BNSetDisassemblyCallParameterHints(self.handle, hints) }
+    }
 }
 
 impl ToOwned for DisassemblySettings {
@@ -0,0 +1,232 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Shannon entropy and byte histograms of the contents of a view.
+
+use std::ops::Range;
+
+use crate::binary_view::{BinaryView, BinaryViewExt};
+use crate::search::map_parallel;
+
+/// The most bytes read from the view at once.
+const READ_CHUNK_SIZE: u64 = 0x100000;
+
+/// How often each byte value occurs in some data.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ByteHistogram {
+    counts: [u64; 256],
+}
+
+impl ByteHistogram {
+    pub fn new() -> Self {
+        Self { counts: [0; 256] }
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Self {
+        let mut histogram = Self::new();
+        histogram.add(data);
+        histogram
+    }
+
+    /// Count the bytes of `data`.
+    pub fn add(&mut self, data: &[u8]) {
+        for byte in data {
+            self.counts[*byte as usize] += 1;
+        }
+    }
+
+    /// Add the counts of `other`, as if its data had been added to this histogram.
+    pub fn merge(&mut self, other: &ByteHistogram) {
+        for (count, other) in self.counts.iter_mut().zip(other.counts.iter()) {
+            *count += other;
+        }
+    }
+
+    pub fn count(&self, byte: u8) -> u64 {
+        self.counts[byte as usize]
+    }
+
+    /// The counts of each byte value, indexed by the value.
+    pub fn counts(&self) -> &[u64; 256] {
+        &self.counts
+    }
+
+    /// The number of bytes counted.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The number of byte values that occur at least once.
+    pub fn distinct(&self) -> usize {
+        self.counts.iter().filter(|count| **count != 0).count()
+    }
+
+    /// The byte value that occurs most often and its count, the lowest value on ties.
+    pub fn most_common(&self) -> Option<(u8, u64)> {
+        let (byte, count) = self
+            .counts
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, count)| **count)?;
+        (*count != 0).then_some((byte as u8, *count))
+    }
+
+    /// The Shannon entropy of the counted bytes, normalized to be 0.0 when a single value
+    /// occurs and 1.0 when all 256 values are equally common. The entropy of no bytes is 0.0.
+    pub fn entropy(&self) -> f64 {
+        let total = self.total();
+        if total == 0 {
+            return 0.0;
+        }
+        let bits: f64 = self
+            .counts
+            .iter()
+            .filter(|count| **count != 0)
+            .map(|count| {
+                let p = *count as f64 / total as f64;
+                -p * p.log2()
+            })
+            .sum();
+        bits / 8.0
+    }
+
+    /// The fraction of the counted bytes that are printable ASCII or whitespace.
+    pub fn printable_ratio(&self) -> f64 {
+        let total = self.total();
+        if total == 0 {
+            return 0.0;
+        }
+        let printable: u64 = (0..=255u8)
+            .filter(|byte| byte.is_ascii_graphic() || byte.is_ascii_whitespace())
+            .map(|byte| self.count(byte))
+            .sum();
+        printable as f64 / total as f64
+    }
+}
+
+impl Default for ByteHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The normalized Shannon entropy of `data`, see [`ByteHistogram::entropy`].
+pub fn shannon_entropy(data: &[u8]) -> f64 {
+    ByteHistogram::from_bytes(data).entropy()
+}
+
+/// Split `range` into chunks of whole blocks of at most [`READ_CHUNK_SIZE`] bytes.
+fn read_chunks(range: Range<u64>, block_size: u64) -> Vec<Range<u64>> {
+    let chunk_size = (READ_CHUNK_SIZE / block_size).max(1) * block_size;
+    let mut chunks = Vec::new();
+    let mut start = range.start;
+    while start < range.end {
+        let end = start.saturating_add(chunk_size).min(range.end);
+        chunks.push(start..end);
+        start = end;
+    }
+    chunks
+}
+
+/// Call `f` with each chunk of `range` and collect the results in order.
+fn map_chunks<T, F>(range: Range<u64>, block_size: u64, f: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(Range<u64>) -> T + Send + Sync,
+{
+    map_parallel(read_chunks(range, block_size), f)
+}
+
+/// Call `f` with the bytes of each `block_size` block of `chunk`. Blocks past the end of a short
+/// read are read again on their own, as the view may only be readable again further on.
+fn for_each_block(view: &BinaryView, chunk: Range<u64>, block_size: u64, mut f: impl FnMut(&[u8])) {
+    let data = view.read_vec(chunk.start, (chunk.end - chunk.start) as usize);
+    let mut start = chunk.start;
+    while start < chunk.end {
+        let end = start.saturating_add(block_size).min(chunk.end);
+        let offset = (start - chunk.start) as usize;
+        let len = (end - start) as usize;
+        match data.get(offset..offset + len) {
+            Some(block) => f(block),
+            None => f(&view.read_vec(start, len)),
+        }
+        start = end;
+    }
+}
+
+pub(crate) fn view_entropy(view: &BinaryView, range: Range<u64>, block_size: usize) -> Vec<f64> {
+    assert!(block_size > 0, "block size must not be zero");
+    let block_size = block_size as u64;
+    map_chunks(range, block_size, |chunk| {
+        let mut entropy = Vec::new();
+        for_each_block(view, chunk, block_size, |block| {
+            entropy.push(shannon_entropy(block))
+        });
+        entropy
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+pub(crate) fn view_histogram(view: &BinaryView, range: Range<u64>) -> ByteHistogram {
+    // Blocks only matter for the parts of a chunk that can't be read at once
+    let histograms = map_chunks(range, READ_CHUNK_SIZE, |chunk| {
+        let mut histogram = ByteHistogram::new();
+        for_each_block(view, chunk, 0x1000, |block| histogram.add(block));
+        histogram
+    });
+    let mut result = ByteHistogram::new();
+    for histogram in &histograms {
+        result.merge(histogram);
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn entropy_of_bytes() {
+        assert_eq!(shannon_entropy(&[]), 0.0);
+        assert_eq!(shannon_entropy(&[0x41; 64]), 0.0);
+        let all: Vec<u8> = (0..=255).collect();
+        assert!((shannon_entropy(&all) - 1.0).abs() < 1e-9);
+        // Two equally common values take one of eight bits
+        assert!((shannon_entropy(&[0, 1, 0, 1]) - 0.125).abs() < 1e-9);
+    }
+
+    #[test]
+    fn histogram_counts() {
+        let mut histogram = ByteHistogram::from_bytes(b"hello\n");
+        histogram.merge(&ByteHistogram::from_bytes(&[0, 0]));
+        assert_eq!(histogram.count(b'l'), 2);
+        assert_eq!(histogram.total(), 8);
+        assert_eq!(histogram.distinct(), 6);
+        assert_eq!(histogram.most_common(), Some((0, 2)));
+        assert_eq!(histogram.printable_ratio(), 0.75);
+        assert_eq!(ByteHistogram::new().most_common(), None);
+    }
+
+    #[test]
+    fn chunks_hold_whole_blocks() {
+        let chunks = read_chunks(0x10..0x10 + 3 * READ_CHUNK_SIZE / 2, 0x300);
+        assert_eq!(chunks.len(), 2);
+        assert_eq!((chunks[0].end - chunks[0].start) % 0x300, 0);
+        assert_eq!(chunks[1].end, 0x10 + 3 * READ_CHUNK_SIZE / 2);
+        assert_eq!(read_chunks(0..0, 0x100), []);
+    }
+}
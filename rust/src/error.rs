@@ -0,0 +1,79 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The error type of fallible operations across the crate.
+
+use std::string::FromUtf8Error;
+
+use thiserror::Error;
+
+use crate::search::BytePatternError;
+use crate::signatures::SignatureError;
+use crate::symbol_server::SymbolServerError;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Most failures come from a core call that gives no reason, the error at least says which call
+/// failed.
+#[derive(Error, Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The named core function returned a null handle.
+    #[error("`{0}` returned a null handle")]
+    NullHandle(&'static str),
+    /// The named core function reported failure.
+    #[error("`{0}` failed")]
+    CoreCallFailed(&'static str),
+    #[error("invalid argument: {0}")]
+    InvalidArgument(String),
+    #[error("{0} not found")]
+    NotFound(String),
+    /// A value, such as metadata, is not of the type it was read as.
+    #[error("value is not of the requested type")]
+    TypeMismatch,
+    /// Input, such as a file read by a view, is malformed.
+    #[error("failed to parse {0}")]
+    Parse(String),
+    #[error(transparent)]
+    BytePattern(#[from] BytePatternError),
+    #[error(transparent)]
+    Signature(#[from] SignatureError),
+    #[error(transparent)]
+    SymbolServer(#[from] SymbolServerError),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Utf8(#[from] std::str::Utf8Error),
+}
+
+impl From<FromUtf8Error> for Error {
+    fn from(err: FromUtf8Error) -> Self {
+        Error::Utf8(err.utf8_error())
+    }
+}
+
+/// Lets functions returning `Result<T, ()>` use `?` on results of this crate.
+///
+/// ```no_run
+/// use binaryninja::binary_view::BinaryViewExt;
+///
+/// fn load_settings(view: &binaryninja::binary_view::BinaryView) -> Result<(), ()> {
+///     let settings = view.load_settings("ELF")?;
+///     println!("{} load settings", settings.keys().len());
+///     Ok(())
+/// }
+/// ```
+impl From<Error> for () {
+    fn from(_: Error) {}
+}
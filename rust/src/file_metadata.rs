@@ -13,27 +13,36 @@
 // limitations under the License.
 
 use crate::binary_view::BinaryView;
+use crate::database::kvs::KeyValueStore;
 use crate::database::Database;
 use crate::rc::*;
 use crate::string::*;
+use crate::Error;
 use binaryninjacore_sys::{
     BNBeginUndoActions, BNCloseFile, BNCommitUndoActions, BNCreateDatabase, BNCreateFileMetadata,
     BNFileMetadata, BNFileMetadataGetSessionId, BNFreeFileMetadata, BNGetCurrentOffset,
     BNGetCurrentView, BNGetExistingViews, BNGetFileMetadataDatabase, BNGetFileViewOfType,
-    BNGetFilename, BNGetProjectFile, BNIsAnalysisChanged, BNIsBackedByDatabase, BNIsFileModified,
-    BNMarkFileModified, BNMarkFileSaved, BNNavigate, BNNewFileReference,
+    BNGetFilename, BNGetProjectFile, BNGetSnapshotData, BNIsAnalysisChanged, BNIsBackedByDatabase,
+    BNIsFileModified, BNMarkFileModified, BNMarkFileSaved, BNNavigate, BNNewFileReference,
     BNOpenDatabaseForConfiguration, BNOpenExistingDatabase, BNRedo, BNRevertUndoActions,
     BNSaveAutoSnapshot, BNSetFilename, BNUndo,
 };
 use binaryninjacore_sys::{BNCreateDatabaseWithProgress, BNOpenExistingDatabaseWithProgress};
+use binaryninjacore_sys::{
+    BNCreateSaveSettings, BNFreeSaveSettings, BNGetSaveSettingsName, BNIsSaveSettingsOptionSet,
+    BNNewSaveSettingsReference, BNSaveOption, BNSaveSettings, BNSetSaveSettingsName,
+    BNSetSaveSettingsOption,
+};
 use std::ffi::c_void;
 use std::fmt::Debug;
 use std::path::Path;
 
-use crate::progress::ProgressCallback;
+use crate::progress::{NoProgressCallback, ProgressCallback};
 use crate::project::file::ProjectFile;
 use std::ptr::{self, NonNull};
 
+pub type SaveOption = BNSaveOption;
+
 #[derive(PartialEq, Eq, Hash)]
 pub struct FileMetadata {
     pub(crate) handle: *mut BNFileMetadata,
@@ -169,14 +178,14 @@ impl FileMetadata {
         unsafe { BNGetCurrentOffset(self.handle) }
     }
 
-    pub fn navigate_to<S: BnStrCompatible>(&self, view: S, offset: u64) -> Result<(), ()> {
+    pub fn navigate_to<S: BnStrCompatible>(&self, view: S, offset: u64) -> Result<(), Error> {
         let view = view.into_bytes_with_nul();
 
         unsafe {
             if BNNavigate(self.handle, view.as_ref().as_ptr() as *const _, offset) {
                 Ok(())
             } else {
-                Err(())
+                Err(Error::CoreCallFailed("BNNavigate"))
             }
         }
     }
@@ -247,6 +256,27 @@ impl FileMetadata {
         }
     }
 
+    /// Equivalent to [`FileMetadata::create_database`] but with the given [`SaveSettings`].
+    pub fn create_database_with_settings(
+        &self,
+        file_path: impl AsRef<Path>,
+        settings: &SaveSettings,
+    ) -> bool {
+        // Databases are created with the root view (Raw).
+        let Some(raw_view) = self.view_of_type("Raw") else {
+            return false;
+        };
+
+        let file_path = file_path.as_ref().into_bytes_with_nul();
+        unsafe {
+            BNCreateDatabase(
+                raw_view.handle,
+                file_path.as_ptr() as *mut _,
+                settings.handle,
+            )
+        }
+    }
+
     pub fn save_auto_snapshot(&self) -> bool {
         // Snapshots are saved with the root view (Raw).
         let Some(raw_view) = self.view_of_type("Raw") else {
@@ -256,31 +286,41 @@ impl FileMetadata {
         unsafe { BNSaveAutoSnapshot(raw_view.handle, ptr::null_mut() as *mut _) }
     }
 
+    /// Equivalent to [`FileMetadata::save_auto_snapshot`] but with the given [`SaveSettings`].
+    pub fn save_auto_snapshot_with_settings(&self, settings: &SaveSettings) -> bool {
+        // Snapshots are saved with the root view (Raw).
+        let Some(raw_view) = self.view_of_type("Raw") else {
+            return false;
+        };
+
+        unsafe { BNSaveAutoSnapshot(raw_view.handle, settings.handle) }
+    }
+
     pub fn open_database_for_configuration<S: BnStrCompatible>(
         &self,
         filename: S,
-    ) -> Result<Ref<BinaryView>, ()> {
+    ) -> Result<Ref<BinaryView>, Error> {
         let filename = filename.into_bytes_with_nul();
         unsafe {
             let bv =
                 BNOpenDatabaseForConfiguration(self.handle, filename.as_ref().as_ptr() as *const _);
 
             if bv.is_null() {
-                Err(())
+                Err(Error::NullHandle("BNOpenDatabaseForConfiguration"))
             } else {
                 Ok(BinaryView::ref_from_raw(bv))
             }
         }
     }
 
-    pub fn open_database<S: BnStrCompatible>(&self, filename: S) -> Result<Ref<BinaryView>, ()> {
+    pub fn open_database<S: BnStrCompatible>(&self, filename: S) -> Result<Ref<BinaryView>, Error> {
         let filename = filename.into_bytes_with_nul();
         let filename_ptr = filename.as_ref().as_ptr() as *mut _;
 
         let view = unsafe { BNOpenExistingDatabase(self.handle, filename_ptr) };
 
         if view.is_null() {
-            Err(())
+            Err(Error::NullHandle("BNOpenExistingDatabase"))
         } else {
             Ok(unsafe { BinaryView::ref_from_raw(view) })
         }
@@ -290,7 +330,7 @@ impl FileMetadata {
         &self,
         filename: S,
         mut progress: P,
-    ) -> Result<Ref<BinaryView>, ()> {
+    ) -> Result<Ref<BinaryView>, Error> {
         let filename = filename.into_bytes_with_nul();
         let filename_ptr = filename.as_ref().as_ptr() as *mut _;
 
@@ -304,12 +344,31 @@ impl FileMetadata {
         };
 
         if view.is_null() {
-            Err(())
+            Err(Error::NullHandle("BNOpenExistingDatabaseWithProgress"))
         } else {
             Ok(unsafe { BinaryView::ref_from_raw(view) })
         }
     }
 
+    /// Serialize the current state of all views, as it would be stored in a new snapshot.
+    ///
+    /// Returns the snapshot data and the analysis cache, neither requires the file to be backed by
+    /// a database.
+    pub fn snapshot_data(&self) -> (Ref<KeyValueStore>, Ref<KeyValueStore>) {
+        let data = KeyValueStore::new();
+        let cache = KeyValueStore::new();
+        unsafe {
+            BNGetSnapshotData(
+                self.handle,
+                data.handle.as_ptr(),
+                cache.handle.as_ptr(),
+                ptr::null_mut(),
+                Some(NoProgressCallback::cb_progress_callback),
+            )
+        };
+        (data, cache)
+    }
+
     /// Get the current database
     pub fn database(&self) -> Option<Ref<Database>> {
         let result = unsafe { BNGetFileMetadataDatabase(self.handle) };
@@ -353,3 +412,81 @@ unsafe impl RefCountable for FileMetadata {
         BNFreeFileMetadata(handle.handle);
     }
 }
+
+/// Options used when creating or saving a database, see [`FileMetadata::create_database_with_settings`].
+#[derive(PartialEq, Eq, Hash)]
+pub struct SaveSettings {
+    pub(crate) handle: *mut BNSaveSettings,
+}
+
+impl SaveSettings {
+    pub(crate) unsafe fn ref_from_raw(handle: *mut BNSaveSettings) -> Ref<Self> {
+        debug_assert!(!handle.is_null());
+        Ref::new(Self { handle })
+    }
+
+    pub fn new() -> Ref<Self> {
+        unsafe { Self::ref_from_raw(BNCreateSaveSettings()) }
+    }
+
+    pub fn is_option_set(&self, option: SaveOption) -> bool {
+        unsafe { BNIsSaveSettingsOptionSet(self.handle, option) }
+    }
+
+    pub fn set_option(&self, option: SaveOption, state: bool) -> &Self {
+        unsafe { BNSetSaveSettingsOption(self.handle, option, state) };
+        self
+    }
+
+    /// The name given to the snapshot created by the save.
+    pub fn name(&self) -> BnString {
+        unsafe { BnString::from_raw(BNGetSaveSettingsName(self.handle)) }
+    }
+
+    pub fn set_name<S: BnStrCompatible>(&self, name: S) -> &Self {
+        let name = name.into_bytes_with_nul();
+        unsafe { BNSetSaveSettingsName(self.handle, name.as_ref().as_ptr() as *const _) };
+        self
+    }
+}
+
+impl Debug for SaveSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SaveSettings")
+            .field("name", &self.name())
+            .field(
+                "remove_undo_data",
+                &self.is_option_set(SaveOption::RemoveUndoData),
+            )
+            .field(
+                "trim_snapshots",
+                &self.is_option_set(SaveOption::TrimSnapshots),
+            )
+            .field(
+                "purge_original_filename_path",
+                &self.is_option_set(SaveOption::PurgeOriginalFilenamePath),
+            )
+            .finish()
+    }
+}
+
+unsafe impl Send for SaveSettings {}
+unsafe impl Sync for SaveSettings {}
+
+impl ToOwned for SaveSettings {
+    type Owned = Ref<Self>;
+
+    fn to_owned(&self) -> Self::Owned {
+        unsafe { RefCountable::inc_ref(self) }
+    }
+}
+
+unsafe impl RefCountable for SaveSettings {
+    unsafe fn inc_ref(handle: &Self) -> Ref<Self> {
+        Self::ref_from_raw(BNNewSaveSettingsReference(handle.handle))
+    }
+
+    unsafe fn dec_ref(handle: &Self) {
+        BNFreeSaveSettings(handle.handle);
+    }
+}
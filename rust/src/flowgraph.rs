@@ -16,17 +16,54 @@
 
 use binaryninjacore_sys::*;
 
+use crate::basic_block::{BasicBlock, BlockContext};
+use crate::binary_view::BinaryView;
 use crate::disassembly::DisassemblyTextLine;
+use crate::function::{Function, HighlightColor, NativeBlock};
 
 use crate::rc::*;
+use crate::string::{BnStrCompatible, BnString};
 
-use std::marker::PhantomData;
+use std::ffi::{c_char, c_void};
+use std::fmt::Debug;
+use std::ptr::NonNull;
+use std::sync::mpsc;
 
 pub type BranchType = BNBranchType;
 pub type EdgePenStyle = BNEdgePenStyle;
 pub type ThemeColor = BNThemeColor;
 pub type FlowGraphOption = BNFlowGraphOption;
 
+/// A graph of [`FlowGraphNode`]s holding lines of text, connected by styled edges.
+///
+/// Graphs are positioned by a layout of the core, additional layouts can be added with
+/// [`register_flow_graph_layout`].
+///
+/// ```no_run
+/// use binaryninja::binary_view::BinaryViewExt;
+/// use binaryninja::flowgraph::{BranchType, EdgeStyle, FlowGraph, FlowGraphNode};
+/// use binaryninja::function::{HighlightColor, HighlightStandardColor};
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// let graph = FlowGraph::new();
+/// let entry = FlowGraphNode::new(&graph);
+/// entry.set_lines(["entry".into()]);
+/// entry.set_highlight(HighlightColor::StandardHighlightColor {
+///     color: HighlightStandardColor::GreenHighlightColor,
+///     alpha: 255,
+/// });
+/// let exit = FlowGraphNode::new(&graph);
+/// exit.set_lines(["exit".into()]);
+/// graph.append(&entry);
+/// graph.append(&exit);
+/// entry.add_outgoing_edge(BranchType::UnconditionalBranch, &exit, EdgeStyle::default());
+///
+/// let laid_out = graph.layout_and_wait();
+/// for node in &laid_out.nodes() {
+///     println!("{:?} at {:?}", node.lines(), node.position());
+/// }
+/// view.show_graph_report("Example", &graph);
+/// ```
 #[derive(PartialEq, Eq, Hash)]
 pub struct FlowGraph {
     pub(crate) handle: *mut BNFlowGraph,
@@ -45,6 +82,100 @@ impl FlowGraph {
         unsafe { BNAddFlowGraphNode(self.handle, node.handle) }
     }
 
+    /// The nodes of the graph in the order they were appended.
+    pub fn nodes(&self) -> Array<FlowGraphNode> {
+        let mut count = 0;
+        let result = unsafe { BNGetFlowGraphNodes(self.handle, &mut count) };
+        assert!(!result.is_null());
+        unsafe { Array::new(result, count, ()) }
+    }
+
+    /// The node at `index`, as returned by [`FlowGraph::append`].
+    pub fn node(&self, index: usize) -> Option<Ref<FlowGraphNode>> {
+        let result = unsafe { BNGetFlowGraphNode(self.handle, index) };
+        (!result.is_null()).then(|| unsafe { Ref::new(FlowGraphNode::from_raw(result)) })
+    }
+
+    /// The nodes that intersect the given region of the laid out graph.
+    pub fn nodes_in_region(
+        &self,
+        left: i32,
+        top: i32,
+        right: i32,
+        bottom: i32,
+    ) -> Array<FlowGraphNode> {
+        let mut count = 0;
+        let result = unsafe {
+            BNGetFlowGraphNodesInRegion(self.handle, left, top, right, bottom, &mut count)
+        };
+        assert!(!result.is_null());
+        unsafe { Array::new(result, count, ()) }
+    }
+
+    pub fn has_nodes(&self) -> bool {
+        unsafe { BNFlowGraphHasNodes(self.handle) }
+    }
+
+    /// Whether `node` is part of this graph.
+    pub fn contains(&self, node: &FlowGraphNode) -> bool {
+        unsafe { BNIsNodeValidForFlowGraph(self.handle, node.handle) }
+    }
+
+    /// The width of the laid out graph.
+    pub fn width(&self) -> i32 {
+        unsafe { BNGetFlowGraphWidth(self.handle) }
+    }
+
+    /// The height of the laid out graph.
+    pub fn height(&self) -> i32 {
+        unsafe { BNGetFlowGraphHeight(self.handle) }
+    }
+
+    /// Set the width of the graph, for use by a [`FlowGraphLayoutHandler`].
+    pub fn set_width(&self, width: i32) {
+        unsafe { BNFlowGraphSetWidth(self.handle, width) }
+    }
+
+    /// Set the height of the graph, for use by a [`FlowGraphLayoutHandler`].
+    pub fn set_height(&self, height: i32) {
+        unsafe { BNFlowGraphSetHeight(self.handle, height) }
+    }
+
+    pub fn horizontal_node_margin(&self) -> i32 {
+        unsafe { BNGetHorizontalFlowGraphNodeMargin(self.handle) }
+    }
+
+    pub fn vertical_node_margin(&self) -> i32 {
+        unsafe { BNGetVerticalFlowGraphNodeMargin(self.handle) }
+    }
+
+    /// Set the space left between nodes by the layout.
+    pub fn set_node_margins(&self, horizontal: i32, vertical: i32) {
+        unsafe { BNSetFlowGraphNodeMargins(self.handle, horizontal, vertical) }
+    }
+
+    /// The view the graph shows, used to navigate from the lines of its nodes.
+    pub fn view(&self) -> Option<Ref<BinaryView>> {
+        let result = unsafe { BNGetViewForFlowGraph(self.handle) };
+        (!result.is_null()).then(|| unsafe { BinaryView::ref_from_raw(result) })
+    }
+
+    pub fn set_view(&self, view: Option<&BinaryView>) {
+        let view = view.map_or(std::ptr::null_mut(), |view| view.handle);
+        unsafe { BNSetViewForFlowGraph(self.handle, view) }
+    }
+
+    /// The function the graph shows.
+    pub fn function(&self) -> Option<Ref<Function>> {
+        let result = unsafe { BNGetFunctionForFlowGraph(self.handle) };
+        (!result.is_null()).then(|| unsafe { Function::ref_from_raw(result) })
+    }
+
+    pub fn set_function(&self, function: Option<&Function>) {
+        let function = function.map_or(std::ptr::null_mut(), |function| function.handle);
+        unsafe { BNSetFunctionForFlowGraph(self.handle, function) }
+    }
+
     pub fn set_option(&self, option: FlowGraphOption, value: bool) {
         unsafe { BNSetFlowGraphOption(self.handle, option, value) }
     }
@@ -52,6 +183,44 @@ impl FlowGraph {
     pub fn is_option_set(&self, option: FlowGraphOption) -> bool {
         unsafe { BNIsFlowGraphOptionSet(self.handle, option) }
     }
+
+    pub fn is_layout_complete(&self) -> bool {
+        unsafe { BNIsFlowGraphLayoutComplete(self.handle) }
+    }
+
+    /// Lay out the graph in the background, calling `on_complete` from another thread once the
+    /// laid out graph is available from [`FlowGraphLayoutRequest::graph`].
+    ///
+    /// Dropping the request before then aborts the layout.
+    pub fn start_layout<F>(&self, on_complete: F) -> FlowGraphLayoutRequest
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        extern "C" fn cb_complete(ctxt: *mut c_void) {
+            ffi_wrap!("FlowGraph::cb_complete", unsafe {
+                let on_complete = &*(ctxt as *const Box<dyn Fn() + Send + Sync>);
+                on_complete()
+            })
+        }
+
+        let on_complete: Box<Box<dyn Fn() + Send + Sync>> = Box::new(Box::new(on_complete));
+        let ctxt = &*on_complete as *const Box<dyn Fn() + Send + Sync> as *mut c_void;
+        let handle = unsafe { BNStartFlowGraphLayout(self.handle, ctxt, Some(cb_complete)) };
+        FlowGraphLayoutRequest {
+            handle: NonNull::new(handle).unwrap(),
+            _on_complete: on_complete,
+        }
+    }
+
+    /// Lay out the graph and wait for the layout to complete, returning the laid out graph.
+    pub fn layout_and_wait(&self) -> Ref<FlowGraph> {
+        let (sender, receiver) = mpsc::channel();
+        let request = self.start_layout(move || {
+            let _ = sender.send(());
+        });
+        let _ = receiver.recv();
+        request.graph()
+    }
 }
 
 unsafe impl RefCountable for FlowGraph {
@@ -75,21 +244,30 @@ impl ToOwned for FlowGraph {
 }
 
 #[derive(PartialEq, Eq, Hash)]
-pub struct FlowGraphNode<'a> {
+pub struct FlowGraphNode {
     pub(crate) handle: *mut BNFlowGraphNode,
-    _data: PhantomData<&'a ()>,
 }
 
-impl<'a> FlowGraphNode<'a> {
+impl FlowGraphNode {
     pub(crate) unsafe fn from_raw(raw: *mut BNFlowGraphNode) -> Self {
-        Self {
-            handle: raw,
-            _data: PhantomData,
-        }
+        Self { handle: raw }
     }
 
-    pub fn new(graph: &FlowGraph) -> Self {
-        unsafe { FlowGraphNode::from_raw(BNCreateFlowGraphNode(graph.handle)) }
+    /// Create a node for `graph`, which shows it once it is added with [`FlowGraph::append`].
+    pub fn new(graph: &FlowGraph) -> Ref<Self> {
+        unsafe { Ref::new(FlowGraphNode::from_raw(BNCreateFlowGraphNode(graph.handle))) }
+    }
+
+    /// The graph the node was created for.
+    pub fn graph(&self) -> Ref<FlowGraph> {
+        unsafe { Ref::new(FlowGraph::from_raw(BNGetFlowGraphNodeOwner(self.handle))) }
+    }
+
+    pub fn lines(&self) -> Array<DisassemblyTextLine> {
+        let mut count = 0;
+        let result = unsafe { BNGetFlowGraphNodeLines(self.handle, &mut count) };
+        assert!(!result.is_null());
+        unsafe { Array::new(result, count, ()) }
     }
 
     pub fn set_lines(&self, lines: impl IntoIterator<Item = DisassemblyTextLine>) {
@@ -106,23 +284,125 @@ impl<'a> FlowGraphNode<'a> {
         }
     }
 
+    pub fn outgoing_edges(&self) -> Array<FlowGraphEdge> {
+        let mut count = 0;
+        let result = unsafe { BNGetFlowGraphNodeOutgoingEdges(self.handle, &mut count) };
+        assert!(!result.is_null());
+        unsafe { Array::new(result, count, ()) }
+    }
+
+    pub fn incoming_edges(&self) -> Array<FlowGraphEdge> {
+        let mut count = 0;
+        let result = unsafe { BNGetFlowGraphNodeIncomingEdges(self.handle, &mut count) };
+        assert!(!result.is_null());
+        unsafe { Array::new(result, count, ()) }
+    }
+
     pub fn add_outgoing_edge(
         &self,
         type_: BranchType,
-        target: &'a FlowGraphNode,
+        target: &FlowGraphNode,
         edge_style: EdgeStyle,
     ) {
         unsafe {
             BNAddFlowGraphNodeOutgoingEdge(self.handle, type_, target.handle, edge_style.into())
         }
     }
+
+    /// Set the points the outgoing edge at `edge_index` is drawn through, for use by a
+    /// [`FlowGraphLayoutHandler`].
+    pub fn set_outgoing_edge_points(&self, edge_index: usize, points: &[Point]) {
+        let mut raw_points: Vec<BNPoint> = points.iter().map(|point| (*point).into()).collect();
+        unsafe {
+            BNFlowGraphNodeSetOutgoingEdgePoints(
+                self.handle,
+                edge_index,
+                raw_points.as_mut_ptr(),
+                raw_points.len(),
+            )
+        }
+    }
+
+    pub fn highlight(&self) -> HighlightColor {
+        unsafe { BNGetFlowGraphNodeHighlight(self.handle) }.into()
+    }
+
+    /// Highlight the whole node, shown when the graph has the
+    /// [`FlowGraphOption::FlowGraphUsesBlockHighlights`] option set.
+    pub fn set_highlight(&self, color: HighlightColor) {
+        unsafe { BNSetFlowGraphNodeHighlight(self.handle, color.into()) }
+    }
+
+    /// The position of the top left corner of the node in the laid out graph.
+    pub fn position(&self) -> (i32, i32) {
+        unsafe {
+            (
+                BNGetFlowGraphNodeX(self.handle),
+                BNGetFlowGraphNodeY(self.handle),
+            )
+        }
+    }
+
+    /// Move the node, for use by a [`FlowGraphLayoutHandler`].
+    pub fn set_position(&self, x: i32, y: i32) {
+        unsafe {
+            BNFlowGraphNodeSetX(self.handle, x);
+            BNFlowGraphNodeSetY(self.handle, y);
+        }
+    }
+
+    /// The width of the node, known once the graph is laid out.
+    pub fn width(&self) -> i32 {
+        unsafe { BNGetFlowGraphNodeWidth(self.handle) }
+    }
+
+    /// The height of the node, known once the graph is laid out.
+    pub fn height(&self) -> i32 {
+        unsafe { BNGetFlowGraphNodeHeight(self.handle) }
+    }
+
+    /// Set the region the node is visible in, for use by a [`FlowGraphLayoutHandler`].
+    pub fn set_visibility_region(&self, x: i32, y: i32, width: i32, height: i32) {
+        unsafe { BNFlowGraphNodeSetVisibilityRegion(self.handle, x, y, width, height) }
+    }
+
+    /// The basic block of a function shown by the node.
+    ///
+    /// Returns `None` for nodes without a basic block and for nodes of IL basic blocks.
+    pub fn basic_block(&self) -> Option<Ref<BasicBlock<NativeBlock>>> {
+        let result = unsafe { BNGetFlowGraphBasicBlock(self.handle) };
+        if result.is_null() {
+            return None;
+        }
+        let block = unsafe { BasicBlock::ref_from_raw(result, NativeBlock::new()) };
+        match unsafe { BNIsILBasicBlock(result) } {
+            true => None,
+            false => Some(block),
+        }
+    }
+
+    /// Associate the node with `block`, so that the node navigates to it.
+    pub fn set_basic_block<C: BlockContext>(&self, block: Option<&BasicBlock<C>>) {
+        let block = block.map_or(std::ptr::null_mut(), |block| block.handle);
+        unsafe { BNSetFlowGraphBasicBlock(self.handle, block) }
+    }
 }
 
-unsafe impl RefCountable for FlowGraphNode<'_> {
+impl Debug for FlowGraphNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FlowGraphNode")
+            .field("position", &self.position())
+            .field("width", &self.width())
+            .field("height", &self.height())
+            .field("lines", &self.lines().to_vec())
+            .finish()
+    }
+}
+
+unsafe impl RefCountable for FlowGraphNode {
     unsafe fn inc_ref(handle: &Self) -> Ref<Self> {
         Ref::new(Self {
             handle: BNNewFlowGraphNodeReference(handle.handle),
-            _data: PhantomData,
         })
     }
 
@@ -131,7 +411,7 @@ unsafe impl RefCountable for FlowGraphNode<'_> {
     }
 }
 
-impl ToOwned for FlowGraphNode<'_> {
+impl ToOwned for FlowGraphNode {
     type Owned = Ref<Self>;
 
     fn to_owned(&self) -> Self::Owned {
@@ -139,6 +419,91 @@ impl ToOwned for FlowGraphNode<'_> {
     }
 }
 
+impl CoreArrayProvider for FlowGraphNode {
+    type Raw = *mut BNFlowGraphNode;
+    type Context = ();
+    type Wrapped<'a> = Guard<'a, Self>;
+}
+
+unsafe impl CoreArrayProviderInner for FlowGraphNode {
+    unsafe fn free(raw: *mut Self::Raw, count: usize, _context: &Self::Context) {
+        BNFreeFlowGraphNodeList(raw, count)
+    }
+
+    unsafe fn wrap_raw<'a>(raw: &'a Self::Raw, context: &'a Self::Context) -> Self::Wrapped<'a> {
+        Guard::new(Self::from_raw(*raw), context)
+    }
+}
+
+/// An edge from a node, see [`FlowGraphNode::outgoing_edges`] and
+/// [`FlowGraphNode::incoming_edges`].
+#[derive(Clone, PartialEq, Debug)]
+pub struct FlowGraphEdge {
+    pub branch_type: BranchType,
+    /// The node at the other end of the edge, the source of an incoming edge.
+    pub target: Ref<FlowGraphNode>,
+    /// The points the edge is drawn through once the graph is laid out.
+    pub points: Vec<Point>,
+    pub back_edge: bool,
+    pub style: EdgeStyle,
+}
+
+impl FlowGraphEdge {
+    pub(crate) fn from_raw(value: &BNFlowGraphEdge) -> Self {
+        let points = match value.points.is_null() {
+            false => unsafe { std::slice::from_raw_parts(value.points, value.pointCount) },
+            true => &[],
+        };
+        Self {
+            branch_type: value.type_,
+            target: unsafe { FlowGraphNode::from_raw(value.target) }.to_owned(),
+            points: points.iter().map(|point| (*point).into()).collect(),
+            back_edge: value.backEdge,
+            style: value.style.into(),
+        }
+    }
+}
+
+impl CoreArrayProvider for FlowGraphEdge {
+    type Raw = BNFlowGraphEdge;
+    type Context = ();
+    type Wrapped<'a> = Self;
+}
+
+unsafe impl CoreArrayProviderInner for FlowGraphEdge {
+    unsafe fn free(raw: *mut Self::Raw, count: usize, _context: &Self::Context) {
+        BNFreeFlowGraphNodeEdgeList(raw, count)
+    }
+
+    unsafe fn wrap_raw<'a>(raw: &'a Self::Raw, _context: &'a Self::Context) -> Self::Wrapped<'a> {
+        Self::from_raw(raw)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl From<BNPoint> for Point {
+    fn from(point: BNPoint) -> Self {
+        Self {
+            x: point.x,
+            y: point.y,
+        }
+    }
+}
+
+impl From<Point> for BNPoint {
+    fn from(point: Point) -> Self {
+        Self {
+            x: point.x,
+            y: point.y,
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub struct EdgeStyle {
     style: EdgePenStyle,
@@ -177,3 +542,142 @@ impl From<EdgeStyle> for BNEdgeStyle {
         }
     }
 }
+
+/// A pending layout of a graph started with [`FlowGraph::start_layout`], aborted when dropped.
+pub struct FlowGraphLayoutRequest {
+    handle: NonNull<BNFlowGraphLayoutRequest>,
+    // Called by the core until the request is aborted, so it must outlive the handle.
+    _on_complete: Box<Box<dyn Fn() + Send + Sync>>,
+}
+
+impl FlowGraphLayoutRequest {
+    /// The laid out graph, a copy of the graph the layout was started for.
+    pub fn graph(&self) -> Ref<FlowGraph> {
+        let result = unsafe { BNGetGraphForFlowGraphLayoutRequest(self.handle.as_ptr()) };
+        unsafe { Ref::new(FlowGraph::from_raw(result)) }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        unsafe { BNIsFlowGraphLayoutRequestComplete(self.handle.as_ptr()) }
+    }
+
+    /// Stop the layout, after which the completion callback is no longer called.
+    pub fn abort(&self) {
+        unsafe { BNAbortFlowGraphLayoutRequest(self.handle.as_ptr()) }
+    }
+}
+
+impl Drop for FlowGraphLayoutRequest {
+    fn drop(&mut self) {
+        // The callback may be running on another thread until the core has cleared it
+        self.abort();
+        unsafe { BNFreeFlowGraphLayoutRequest(self.handle.as_ptr()) }
+    }
+}
+
+/// Positions the nodes of a graph, see [`register_flow_graph_layout`].
+pub trait FlowGraphLayoutHandler: 'static + Sync {
+    /// Lay out `nodes` of `graph` with [`FlowGraphNode::set_position`] and the other setters
+    /// meant for layouts, and set the size of the graph. Returns whether the layout succeeded.
+    fn layout(&self, graph: &FlowGraph, nodes: &[Ref<FlowGraphNode>]) -> bool;
+}
+
+/// Register a custom layout with the core, to be found with [`CoreFlowGraphLayout::by_name`].
+pub fn register_flow_graph_layout<S, L>(name: S, layout: L) -> CoreFlowGraphLayout
+where
+    S: BnStrCompatible,
+    L: FlowGraphLayoutHandler,
+{
+    extern "C" fn cb_layout<L: FlowGraphLayoutHandler>(
+        ctxt: *mut c_void,
+        graph: *mut BNFlowGraph,
+        nodes: *mut *mut BNFlowGraphNode,
+        node_count: usize,
+    ) -> bool {
+        ffi_wrap!("FlowGraphLayoutHandler::cb_layout", unsafe {
+            let layout = &*(ctxt as *const L);
+            let graph = FlowGraph::from_raw(graph);
+            let nodes: Vec<Ref<FlowGraphNode>> = std::slice::from_raw_parts(nodes, node_count)
+                .iter()
+                .map(|node| FlowGraphNode::from_raw(*node).to_owned())
+                .collect();
+            layout.layout(&graph, &nodes)
+        })
+    }
+
+    let name = name.into_bytes_with_nul();
+    let ctxt = Box::leak(Box::new(layout));
+    let mut callbacks = BNCustomFlowGraphLayout {
+        context: ctxt as *mut L as *mut c_void,
+        layout: Some(cb_layout::<L>),
+    };
+    let result = unsafe {
+        BNRegisterFlowGraphLayout(name.as_ref().as_ptr() as *const c_char, &mut callbacks)
+    };
+    unsafe { CoreFlowGraphLayout::from_raw(NonNull::new(result).unwrap()) }
+}
+
+/// A layout registered with the core.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[repr(transparent)]
+pub struct CoreFlowGraphLayout {
+    handle: NonNull<BNFlowGraphLayout>,
+}
+
+impl CoreFlowGraphLayout {
+    pub(crate) unsafe fn from_raw(handle: NonNull<BNFlowGraphLayout>) -> Self {
+        Self { handle }
+    }
+
+    pub fn all() -> Array<CoreFlowGraphLayout> {
+        let mut count = 0;
+        let result = unsafe { BNGetFlowGraphLayouts(&mut count) };
+        assert!(!result.is_null());
+        unsafe { Array::new(result, count, ()) }
+    }
+
+    pub fn by_name<S: BnStrCompatible>(name: S) -> Option<CoreFlowGraphLayout> {
+        let name = name.into_bytes_with_nul();
+        let result = unsafe { BNGetFlowGraphLayoutByName(name.as_ref().as_ptr() as *const c_char) };
+        NonNull::new(result).map(|handle| unsafe { Self::from_raw(handle) })
+    }
+
+    pub fn name(&self) -> BnString {
+        let result = unsafe { BNGetFlowGraphLayoutName(self.handle.as_ptr()) };
+        assert!(!result.is_null());
+        unsafe { BnString::from_raw(result) }
+    }
+
+    /// Lay out `nodes` of `graph` with this layout, returning whether it succeeded.
+    pub fn layout(&self, graph: &FlowGraph, nodes: &[&FlowGraphNode]) -> bool {
+        let mut raw_nodes: Vec<*mut BNFlowGraphNode> =
+            nodes.iter().map(|node| node.handle).collect();
+        unsafe {
+            BNFlowGraphLayoutLayout(
+                self.handle.as_ptr(),
+                graph.handle,
+                raw_nodes.as_mut_ptr(),
+                raw_nodes.len(),
+            )
+        }
+    }
+}
+
+unsafe impl Send for CoreFlowGraphLayout {}
+unsafe impl Sync for CoreFlowGraphLayout {}
+
+impl CoreArrayProvider for CoreFlowGraphLayout {
+    type Raw = *mut BNFlowGraphLayout;
+    type Context = ();
+    type Wrapped<'a> = Self;
+}
+
+unsafe impl CoreArrayProviderInner for CoreFlowGraphLayout {
+    unsafe fn free(raw: *mut Self::Raw, _count: usize, _context: &Self::Context) {
+        BNFreeFlowGraphLayoutList(raw)
+    }
+
+    unsafe fn wrap_raw<'a>(raw: &'a Self::Raw, _context: &'a Self::Context) -> Self::Wrapped<'a> {
+        Self::from_raw(NonNull::new(*raw).unwrap())
+    }
+}
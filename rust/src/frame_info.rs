@@ -0,0 +1,323 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Call frame information, as recorded by debug information importers.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::binary_view::{BinaryView, BinaryViewExt};
+use crate::metadata::Metadata;
+use crate::rc::Ref;
+
+/// View metadata key of the map from importer name to the frame information of its last import.
+pub const FRAME_INFO_METADATA_KEY: &str = "frame_info";
+
+/// Register index stored for a CFA computed by an expression.
+const EXPRESSION_CFA: u64 = u64::MAX;
+
+/// How the canonical frame address is computed.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Cfa {
+    /// The CFA is the value of `register` plus `offset`.
+    RegisterOffset { register: String, offset: i64 },
+    /// The CFA is computed by a DWARF expression.
+    Expression,
+}
+
+/// A register of the caller saved on the stack.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SavedRegister {
+    pub register: String,
+    /// Where the register is saved, relative to the CFA.
+    pub cfa_offset: i64,
+}
+
+/// The frame layout over a range of addresses.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FrameRow {
+    pub range: Range<u64>,
+    pub cfa: Cfa,
+    pub saved_registers: Vec<SavedRegister>,
+}
+
+/// The frame layouts of the functions of a view, found by one run of an importer.
+///
+/// Unwind tables describe, for every address of a function, where the canonical frame address
+/// (the value of the stack pointer at the call) is and where the registers of the caller are
+/// saved. The core has no place for them, so importers collect a table while they parse and
+/// [`store`](FrameInfoTable::store) it on the view they imported into:
+///
+/// ```no_run
+/// use binaryninja::binary_view::BinaryViewBase;
+/// use binaryninja::frame_info::{Cfa, FrameInfoTable};
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// for table in FrameInfoTable::for_view(&view) {
+///     if let Some(row) = table.row_at(view.entry_point()) {
+///         if let Cfa::RegisterOffset { register, offset } = &row.cfa {
+///             println!("CFA is {} + {:#x}", register, offset);
+///         }
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FrameInfoTable {
+    importer: String,
+    /// Sorted by start address and not overlapping.
+    rows: Vec<FrameRow>,
+}
+
+impl FrameInfoTable {
+    /// An empty table for `importer`, usually the name its parser is registered under.
+    pub fn new(importer: impl Into<String>) -> Self {
+        Self {
+            importer: importer.into(),
+            rows: vec![],
+        }
+    }
+
+    pub fn importer(&self) -> &str {
+        &self.importer
+    }
+
+    /// Add `row` to the table, unless it is empty or overlaps a row already in it.
+    pub fn add(&mut self, row: FrameRow) {
+        if row.range.is_empty() {
+            return;
+        }
+        let index = self
+            .rows
+            .partition_point(|other| other.range.start < row.range.start);
+        let overlaps_before = index > 0 && self.rows[index - 1].range.end > row.range.start;
+        let overlaps_after = self
+            .rows
+            .get(index)
+            .is_some_and(|other| other.range.start < row.range.end);
+        if !overlaps_before && !overlaps_after {
+            self.rows.insert(index, row);
+        }
+    }
+
+    /// The rows of the table, in address order.
+    pub fn rows(&self) -> &[FrameRow] {
+        &self.rows
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// The frame layout at `addr`.
+    pub fn row_at(&self, addr: u64) -> Option<&FrameRow> {
+        let index = self.rows.partition_point(|row| row.range.start <= addr);
+        let row = self.rows.get(index.checked_sub(1)?)?;
+        row.range.contains(&addr).then_some(row)
+    }
+
+    /// Move every row by `delta` bytes, such as when the view is loaded at a different base than
+    /// the debug information assumes.
+    pub fn rebase(&mut self, delta: u64) {
+        for row in &mut self.rows {
+            row.range = row.range.start.wrapping_add(delta)..row.range.end.wrapping_add(delta);
+        }
+        self.rows.sort_by_key(|row| row.range.start);
+    }
+
+    /// Store the table on `view`, replacing the one of the last import of the same importer.
+    pub fn store(&self, view: &BinaryView) {
+        let mut all = view
+            .query_metadata(FRAME_INFO_METADATA_KEY)
+            .and_then(|metadata| HashMap::<String, Ref<Metadata>>::try_from(&*metadata).ok())
+            .unwrap_or_default();
+        all.insert(self.importer.clone(), self.to_metadata());
+        view.store_metadata(FRAME_INFO_METADATA_KEY, all, true);
+    }
+
+    /// The frame information of the last import of every importer into `view`, by importer name.
+    pub fn for_view(view: &BinaryView) -> Vec<FrameInfoTable> {
+        let Some(metadata) = view.query_metadata(FRAME_INFO_METADATA_KEY) else {
+            return vec![];
+        };
+        let mut all: Vec<_> = HashMap::<String, Ref<Metadata>>::try_from(&*metadata)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(importer, metadata)| Self::from_metadata(importer, &metadata))
+            .collect();
+        all.sort_by(|a, b| a.importer.cmp(&b.importer));
+        all
+    }
+
+    /// The frame information of the last import of `importer` into `view`.
+    pub fn for_importer(view: &BinaryView, importer: &str) -> Option<FrameInfoTable> {
+        let metadata = view.query_metadata(FRAME_INFO_METADATA_KEY)?;
+        let metadata = metadata.get(importer).ok()??;
+        Some(Self::from_metadata(importer.to_string(), &metadata))
+    }
+
+    // Rows are spread over lists of numbers, with register names stored once
+    fn to_metadata(&self) -> Ref<Metadata> {
+        let mut registers: Vec<String> = vec![];
+        let mut register_index = |name: &str| match registers.iter().position(|r| r == name) {
+            Some(index) => index as u64,
+            None => {
+                registers.push(name.to_string());
+                registers.len() as u64 - 1
+            }
+        };
+
+        let (mut starts, mut ends) = (vec![], vec![]);
+        let (mut cfa_registers, mut cfa_offsets) = (vec![], vec![]);
+        let (mut saved_counts, mut saved_registers, mut saved_offsets) = (vec![], vec![], vec![]);
+        for row in &self.rows {
+            starts.push(row.range.start);
+            ends.push(row.range.end);
+            match &row.cfa {
+                Cfa::RegisterOffset { register, offset } => {
+                    cfa_registers.push(register_index(register));
+                    cfa_offsets.push(*offset);
+                }
+                Cfa::Expression => {
+                    cfa_registers.push(EXPRESSION_CFA);
+                    cfa_offsets.push(0);
+                }
+            }
+            saved_counts.push(row.saved_registers.len() as u64);
+            for saved in &row.saved_registers {
+                saved_registers.push(register_index(&saved.register));
+                saved_offsets.push(saved.cfa_offset);
+            }
+        }
+
+        let mut fields = HashMap::<&str, Ref<Metadata>>::new();
+        fields.insert("registers", registers.into());
+        fields.insert("starts", (&starts).into());
+        fields.insert("ends", (&ends).into());
+        fields.insert("cfa_registers", (&cfa_registers).into());
+        fields.insert("cfa_offsets", (&cfa_offsets).into());
+        fields.insert("saved_counts", (&saved_counts).into());
+        fields.insert("saved_registers", (&saved_registers).into());
+        fields.insert("saved_offsets", (&saved_offsets).into());
+        fields.into()
+    }
+
+    fn from_metadata(importer: String, metadata: &Metadata) -> Self {
+        let mut table = Self::new(importer);
+        let fields = HashMap::<String, Ref<Metadata>>::try_from(metadata).unwrap_or_default();
+        let unsigned = |name: &str| {
+            fields
+                .get(name)
+                .and_then(|field| Vec::<u64>::try_from(&**field).ok())
+                .unwrap_or_default()
+        };
+        let signed = |name: &str| {
+            fields
+                .get(name)
+                .and_then(|field| Vec::<i64>::try_from(&**field).ok())
+                .unwrap_or_default()
+        };
+        let registers = fields
+            .get("registers")
+            .and_then(|field| Vec::<String>::try_from(&**field).ok())
+            .unwrap_or_default();
+        let (starts, ends) = (unsigned("starts"), unsigned("ends"));
+        let (cfa_registers, cfa_offsets) = (unsigned("cfa_registers"), signed("cfa_offsets"));
+        let saved_counts = unsigned("saved_counts");
+        let (saved_registers, saved_offsets) =
+            (unsigned("saved_registers"), signed("saved_offsets"));
+        let register = |index: u64| registers.get(index as usize).cloned();
+
+        let mut saved = saved_registers.into_iter().zip(saved_offsets);
+        for (i, &start) in starts.iter().enumerate() {
+            let saved_registers = saved
+                .by_ref()
+                .take(saved_counts.get(i).copied().unwrap_or(0) as usize)
+                .filter_map(|(index, cfa_offset)| {
+                    Some(SavedRegister {
+                        register: register(index)?,
+                        cfa_offset,
+                    })
+                })
+                .collect();
+            let cfa = match cfa_registers.get(i) {
+                Some(&EXPRESSION_CFA) => Cfa::Expression,
+                Some(&index) => match (register(index), cfa_offsets.get(i)) {
+                    (Some(register), Some(&offset)) => Cfa::RegisterOffset { register, offset },
+                    _ => continue,
+                },
+                None => continue,
+            };
+            let Some(&end) = ends.get(i) else {
+                continue;
+            };
+            table.add(FrameRow {
+                range: start..end,
+                cfa,
+                saved_registers,
+            });
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn row(range: Range<u64>, offset: i64, saved: &[(&str, i64)]) -> FrameRow {
+        FrameRow {
+            range,
+            cfa: Cfa::RegisterOffset {
+                register: "rsp".to_string(),
+                offset,
+            },
+            saved_registers: saved
+                .iter()
+                .map(|&(register, cfa_offset)| SavedRegister {
+                    register: register.to_string(),
+                    cfa_offset,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn looks_up_rows() {
+        let mut table = FrameInfoTable::new("DWARF");
+        table.add(row(0x1001..0x1004, 16, &[("rbp", -16)]));
+        table.add(row(0x1000..0x1001, 8, &[]));
+        // Overlaps the rows already in the table
+        table.add(row(0x1003..0x1010, 32, &[]));
+        table.add(FrameRow {
+            range: 0x1004..0x1008,
+            cfa: Cfa::Expression,
+            saved_registers: vec![],
+        });
+
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.row_at(0x1000).unwrap().range, 0x1000..0x1001);
+        let saved = &table.row_at(0x1003).unwrap().saved_registers;
+        assert_eq!(saved[0].register, "rbp");
+        assert_eq!(table.row_at(0x1005).unwrap().cfa, Cfa::Expression);
+        assert!(table.row_at(0x1008).is_none());
+
+        table.rebase(0x10);
+        assert!(table.row_at(0x1000).is_none());
+        assert_eq!(table.row_at(0x1010).unwrap().range, 0x1010..0x1011);
+    }
+}
@@ -18,7 +18,7 @@ use crate::{
     architecture::{Architecture, CoreArchitecture, CoreRegister, Register},
     basic_block::{BasicBlock, BlockContext},
     binary_view::{BinaryView, BinaryViewExt},
-    calling_convention::CoreCallingConvention,
+    calling_convention::{CallingConvention, CoreCallingConvention},
     component::Component,
     disassembly::{DisassemblySettings, DisassemblyTextLine},
     flowgraph::FlowGraph,
@@ -41,7 +41,11 @@ use crate::architecture::RegisterId;
 use crate::confidence::Conf;
 use crate::high_level_il::HighLevelILFunction;
 use crate::low_level_il::{LiftedILFunction, RegularLowLevelILFunction};
-use crate::medium_level_il::MediumLevelILFunction;
+use crate::medium_level_il::{
+    MediumLevelILFunction, MediumLevelILInstruction, MediumLevelILInstructionKind,
+};
+use crate::source_lines::{SourceLineTable, SourceLocation};
+use crate::thunk::{self, Thunk, ThunkKind};
 use crate::variable::{
     IndirectBranchInfo, MergedVariable, NamedVariableWithType, RegisterValue, RegisterValueType,
     StackVariableReference, Variable,
@@ -403,6 +407,14 @@ impl Function {
         unsafe { Array::new(lines, count, self.to_owned()) }
     }
 
+    /// The source location the instruction at `addr` was compiled from, according to the line
+    /// tables imported into the view, see [`SourceLineTable`].
+    pub fn source_location_at(&self, addr: u64) -> Option<SourceLocation> {
+        SourceLineTable::for_view(&self.view())
+            .iter()
+            .find_map(|table| table.location_at(addr))
+    }
+
     pub fn basic_blocks(&self) -> Array<BasicBlock<NativeBlock>> {
         unsafe {
             let mut count = 0;
@@ -602,6 +614,132 @@ impl Function {
         }
     }
 
+    /// The stack variable covering `offset` in the stack frame, if any.
+    ///
+    /// Unlike [`Function::stack_var_at_frame_offset`] this looks at the whole stack layout of the
+    /// function rather than the variables in use at one instruction.
+    pub fn stack_var_at_offset(&self, offset: i64) -> Option<NamedVariableWithType> {
+        self.stack_layout().iter().find(|var| {
+            let width = var.ty.contents.width().max(1) as i64;
+            (var.variable.storage..var.variable.storage.saturating_add(width)).contains(&offset)
+        })
+    }
+
+    pub fn create_user_stack_var<'a, S: BnStrCompatible, C: Into<Conf<&'a Type>>>(
+        &self,
+        offset: i64,
+        var_type: C,
+        name: S,
+    ) {
+        let mut owned_raw_var_ty = Conf::<&Type>::into_raw(var_type.into());
+        let name = name.into_bytes_with_nul();
+        unsafe {
+            BNCreateUserStackVariable(
+                self.handle,
+                offset,
+                &mut owned_raw_var_ty,
+                name.as_ref().as_ptr() as *const c_char,
+            )
+        }
+    }
+
+    pub fn delete_user_stack_var(&self, offset: i64) {
+        unsafe { BNDeleteUserStackVariable(self.handle, offset) }
+    }
+
+    pub fn create_auto_stack_var<'a, S: BnStrCompatible, C: Into<Conf<&'a Type>>>(
+        &self,
+        offset: i64,
+        var_type: C,
+        name: S,
+    ) {
+        let mut owned_raw_var_ty = Conf::<&Type>::into_raw(var_type.into());
+        let name = name.into_bytes_with_nul();
+        unsafe {
+            BNCreateAutoStackVariable(
+                self.handle,
+                offset,
+                &mut owned_raw_var_ty,
+                name.as_ref().as_ptr() as *const c_char,
+            )
+        }
+    }
+
+    pub fn delete_auto_stack_var(&self, offset: i64) {
+        unsafe { BNDeleteAutoStackVariable(self.handle, offset) }
+    }
+
+    pub fn create_user_var<'a, S: BnStrCompatible, C: Into<Conf<&'a Type>>>(
+        &self,
+        var: &Variable,
+        var_type: C,
+        name: S,
+        ignore_disjoint_uses: bool,
+    ) {
+        let raw_var = BNVariable::from(var);
+        let mut owned_raw_var_ty = Conf::<&Type>::into_raw(var_type.into());
+        let name = name.into_bytes_with_nul();
+        unsafe {
+            BNCreateUserVariable(
+                self.handle,
+                &raw_var,
+                &mut owned_raw_var_ty,
+                name.as_ref().as_ptr() as *const c_char,
+                ignore_disjoint_uses,
+            )
+        }
+    }
+
+    pub fn create_auto_var<'a, S: BnStrCompatible, C: Into<Conf<&'a Type>>>(
+        &self,
+        var: &Variable,
+        var_type: C,
+        name: S,
+        ignore_disjoint_uses: bool,
+    ) {
+        let raw_var = BNVariable::from(var);
+        let mut owned_raw_var_ty = Conf::<&Type>::into_raw(var_type.into());
+        let name = name.into_bytes_with_nul();
+        unsafe {
+            BNCreateAutoVariable(
+                self.handle,
+                &raw_var,
+                &mut owned_raw_var_ty,
+                name.as_ref().as_ptr() as *const c_char,
+                ignore_disjoint_uses,
+            )
+        }
+    }
+
+    pub fn delete_user_var(&self, var: &Variable) {
+        let raw_var = BNVariable::from(var);
+        unsafe { BNDeleteUserVariable(self.handle, &raw_var) }
+    }
+
+    /// The type of `var`, `None` if the variable has no type.
+    pub fn variable_type(&self, var: &Variable) -> Option<Conf<Ref<Type>>> {
+        let raw_var = BNVariable::from(var);
+        let result = unsafe { BNGetVariableType(self.handle, &raw_var) };
+        match result.type_.is_null() {
+            false => Some(Conf::<Ref<Type>>::from_owned_raw(result)),
+            true => None,
+        }
+    }
+
+    /// Renames `var` as a user variable, keeping its type.
+    pub fn rename_var<S: BnStrCompatible>(&self, var: &Variable, name: S) {
+        let var_type = self
+            .variable_type(var)
+            .unwrap_or_else(|| Conf::new(Type::void(), 0));
+        self.create_user_var(var, &var_type, name, false);
+    }
+
+    /// Sets the type of `var` as a user variable, keeping its name.
+    pub fn set_user_var_type<'a, C: Into<Conf<&'a Type>>>(&self, var: &Variable, var_type: C) {
+        let name = self.variable_name(var);
+        self.create_user_var(var, var_type, name, false);
+    }
+
     /// Gets number of bytes removed from the stack after return
     pub fn stack_adjustment(&self) -> Conf<i64> {
         unsafe { BNGetFunctionStackAdjustment(self.handle) }.into()
@@ -627,12 +765,24 @@ impl Function {
         unsafe { BNSetAutoFunctionStackAdjustment(self.handle, &mut value_raw) }
     }
 
+    /// Gets the number of bytes removed from the stack by the call at `addr`.
+    ///
+    /// * `addr` - virtual address of the call instruction
+    /// * `arch` - (optional) Architecture of the instruction if different from self.arch
     pub fn call_stack_adjustment(&self, addr: u64, arch: Option<CoreArchitecture>) -> Conf<i64> {
         let arch = arch.unwrap_or_else(|| self.arch());
         let result = unsafe { BNGetCallStackAdjustment(self.handle, arch.handle, addr) };
         result.into()
     }
 
+    /// Overrides the number of bytes removed from the stack by the call at `addr`.
+    ///
+    /// This is useful when a single callee is invoked with differing stack cleanup, e.g. a
+    /// variadic `stdcall` style function.
+    ///
+    /// * `addr` - virtual address of the call instruction to adjust
+    /// * `adjust` - number of bytes removed from the stack after the call returns
+    /// * `arch` - (optional) Architecture of the instruction if different from self.arch
     pub fn set_user_call_stack_adjustment<I>(
         &self,
         addr: u64,
@@ -675,6 +825,10 @@ impl Function {
         }
     }
 
+    /// Gets the call type override at a call site, if any.
+    ///
+    /// * `addr` - virtual address of the call instruction
+    /// * `arch` - (optional) Architecture of the instruction if different from self.arch
     pub fn call_type_adjustment(
         &self,
         addr: u64,
@@ -716,29 +870,33 @@ impl Function {
         unsafe { BNSetUserCallTypeAdjustment(self.handle, arch.handle, addr, adjust_ptr) }
     }
 
+    /// Sets or removes the automatic call type override at a call site to the given type.
+    ///
+    /// See [Function::set_user_call_type_adjustment] for more information.
     pub fn set_auto_call_type_adjustment<'a, I>(
         &self,
         addr: u64,
-        adjust_type: I,
+        adjust_type: Option<I>,
         arch: Option<CoreArchitecture>,
     ) where
         I: Into<Conf<&'a Type>>,
     {
         let arch = arch.unwrap_or_else(|| self.arch());
-        let adjust_type: Conf<&Type> = adjust_type.into();
-        unsafe {
-            BNSetAutoCallTypeAdjustment(
-                self.handle,
-                arch.handle,
-                addr,
-                &mut BNTypeWithConfidence {
-                    type_: adjust_type.contents.handle,
-                    confidence: adjust_type.confidence,
-                },
-            )
-        }
+        let mut adjust_type = adjust_type.map(|adjust_type| {
+            let adjust_type = adjust_type.into();
+            BNTypeWithConfidence {
+                type_: adjust_type.contents.handle,
+                confidence: adjust_type.confidence,
+            }
+        });
+        let adjust_ptr = adjust_type
+            .as_mut()
+            .map(|x| x as *mut _)
+            .unwrap_or(std::ptr::null_mut());
+        unsafe { BNSetAutoCallTypeAdjustment(self.handle, arch.handle, addr, adjust_ptr) }
     }
 
+    /// Gets the register stack adjustments made by the call at `addr`.
     pub fn call_reg_stack_adjustment(
         &self,
         addr: u64,
@@ -752,8 +910,13 @@ impl Function {
         unsafe { Array::new(adjust, count, ()) }
     }
 
+    /// Overrides the register stack adjustments made by the call at `addr`.
+    ///
+    /// * `addr` - virtual address of the call instruction to adjust
+    /// * `adjust` - the adjustment of each register stack modified by the call
+    /// * `arch` - (optional) Architecture of the instruction if different from self.arch
     pub fn set_user_call_reg_stack_adjustment<I>(
-        self,
+        &self,
         addr: u64,
         adjust: I,
         arch: Option<CoreArchitecture>,
@@ -922,35 +1085,37 @@ impl Function {
         }
     }
 
-    pub fn set_user_parameter_variables<I>(&self, values: I, confidence: u8)
+    pub fn set_user_parameter_variables<C>(&self, values: C)
     where
-        I: IntoIterator<Item = Variable>,
+        C: Into<Conf<Vec<Variable>>>,
     {
-        let vars: Vec<BNVariable> = values.into_iter().map(Into::into).collect();
+        let values: Conf<Vec<Variable>> = values.into();
+        let vars: Vec<BNVariable> = values.contents.into_iter().map(Into::into).collect();
         unsafe {
             BNSetUserFunctionParameterVariables(
                 self.handle,
                 &mut BNParameterVariablesWithConfidence {
                     vars: vars.as_ptr() as *mut _,
                     count: vars.len(),
-                    confidence,
+                    confidence: values.confidence,
                 },
             )
         }
     }
 
-    pub fn set_auto_parameter_variables<I>(&self, values: I, confidence: u8)
+    pub fn set_auto_parameter_variables<C>(&self, values: C)
     where
-        I: IntoIterator<Item = Variable>,
+        C: Into<Conf<Vec<Variable>>>,
     {
-        let vars: Vec<BNVariable> = values.into_iter().map(Into::into).collect();
+        let values: Conf<Vec<Variable>> = values.into();
+        let vars: Vec<BNVariable> = values.contents.into_iter().map(Into::into).collect();
         unsafe {
             BNSetAutoFunctionParameterVariables(
                 self.handle,
                 &mut BNParameterVariablesWithConfidence {
                     vars: vars.as_ptr() as *mut _,
                     count: vars.len(),
-                    confidence,
+                    confidence: values.confidence,
                 },
             )
         }
@@ -1033,6 +1198,15 @@ impl Function {
         unsafe { BNSetFunctionAnalysisSkipOverride(self.handle, override_) }
     }
 
+    /// Skip all further analysis of this function, so one pathological function does not hold up
+    /// the analysis of the rest of the view.
+    ///
+    /// The function stays skipped until the override is reset with
+    /// [`Function::set_analysis_skip_override`] or [`Function::set_analysis_skipped`].
+    pub fn cancel_analysis(&self) {
+        self.set_analysis_skip_override(FunctionAnalysisSkipOverride::AlwaysSkipFunctionAnalysis);
+    }
+
     ///Whether the function's IL should be inlined into all callers' IL
     pub fn inline_during_analysis(&self) -> Conf<bool> {
         let result = unsafe { BNIsFunctionInlinedDuringAnalysis(self.handle) };
@@ -1216,7 +1390,7 @@ impl Function {
     /// fun.remove_user_code_ref(0x1337, 0x400000, None);
     /// ```
     pub fn remove_user_code_ref(
-        self,
+        &self,
         from_addr: u64,
         to_addr: u64,
         arch: Option<CoreArchitecture>,
@@ -1520,6 +1694,81 @@ impl Function {
         unsafe { Array::new(tags, count, ()) }
     }
 
+    /// The tags of `tag_type` at the address.
+    ///
+    /// * `auto` - If `None`, gets all tags, if `true`, gets auto tags, if `false`, gets user tags
+    pub fn tags_of_type_at(
+        &self,
+        addr: u64,
+        tag_type: &TagType,
+        auto: Option<bool>,
+        arch: Option<CoreArchitecture>,
+    ) -> Array<Tag> {
+        let arch = arch.unwrap_or_else(|| self.arch());
+        let mut count = 0;
+
+        let tags = unsafe {
+            match auto {
+                None => BNGetAddressTagsOfType(
+                    self.handle,
+                    arch.handle,
+                    addr,
+                    tag_type.handle,
+                    &mut count,
+                ),
+                Some(true) => BNGetAutoAddressTagsOfType(
+                    self.handle,
+                    arch.handle,
+                    addr,
+                    tag_type.handle,
+                    &mut count,
+                ),
+                Some(false) => BNGetUserAddressTagsOfType(
+                    self.handle,
+                    arch.handle,
+                    addr,
+                    tag_type.handle,
+                    &mut count,
+                ),
+            }
+        };
+        assert!(!tags.is_null());
+        unsafe { Array::new(tags, count, ()) }
+    }
+
+    /// The function tags of the function, with their references.
+    ///
+    /// * `auto` - If `None`, gets all tags, if `true`, gets auto tags, if `false`, gets user tags
+    pub fn function_tag_references(&self, auto: Option<bool>) -> Array<TagReference> {
+        let mut count = 0;
+        let tags = unsafe {
+            match auto {
+                None => BNGetFunctionTagReferences(self.handle, &mut count),
+                Some(true) => BNGetAutoFunctionTagReferences(self.handle, &mut count),
+                Some(false) => BNGetUserFunctionTagReferences(self.handle, &mut count),
+            }
+        };
+        assert!(!tags.is_null());
+        unsafe { Array::new(tags, count, ()) }
+    }
+
+    /// Both the function tags and the address tags of the function.
+    pub fn all_tags(&self) -> Array<TagReference> {
+        let mut count = 0;
+        let tags = unsafe { BNGetFunctionAllTagReferences(self.handle, &mut count) };
+        assert!(!tags.is_null());
+        unsafe { Array::new(tags, count, ()) }
+    }
+
+    /// The function tags and address tags of `tag_type` of the function.
+    pub fn all_tags_of_type(&self, tag_type: &TagType) -> Array<TagReference> {
+        let mut count = 0;
+        let tags =
+            unsafe { BNGetFunctionTagReferencesOfType(self.handle, tag_type.handle, &mut count) };
+        assert!(!tags.is_null());
+        unsafe { Array::new(tags, count, ()) }
+    }
+
     /// List of indirect branches
     pub fn indirect_branches(&self) -> Array<IndirectBranchInfo> {
         let mut count = 0;
@@ -2199,6 +2448,24 @@ impl Function {
         self.view().code_refs_to_addr(self.start())
     }
 
+    /// The thunk this function is, if all it does is jump to another function or an import.
+    ///
+    /// A kind set with [`Function::set_user_thunk_kind`] takes precedence over the one found by
+    /// looking at the low level IL of the function.
+    pub fn thunk(&self) -> Option<Thunk> {
+        thunk::classify(self)
+    }
+
+    /// Set whether this function is a thunk and of what kind, `None` meaning it is not one.
+    pub fn set_user_thunk_kind(&self, kind: Option<ThunkKind>) {
+        thunk::set_user_kind(&self.view(), self.start(), Some(kind))
+    }
+
+    /// Forget the kind set with [`Function::set_user_thunk_kind`].
+    pub fn clear_user_thunk_kind(&self) {
+        thunk::set_user_kind(&self.view(), self.start(), None)
+    }
+
     /// Calling convention used by the function
     pub fn calling_convention(&self) -> Option<Conf<Ref<CoreCallingConvention>>> {
         let result = unsafe { BNGetFunctionCallingConvention(self.handle) };
@@ -2343,6 +2610,48 @@ impl Function {
         unsafe { BNSetAutoFunctionReturnRegisters(self.handle, &mut regs) }
     }
 
+    /// Retrieves the system calls made by this function.
+    ///
+    /// The system call number is resolved using the value of the first argument register of the
+    /// platform's system call convention at the call site. Use [`BinaryViewExt::system_call_info`]
+    /// to look up the name and prototype of a resolved system call.
+    pub fn system_call_sites(&self) -> Vec<SystemCallSite> {
+        let Ok(mlil) = self.medium_level_il() else {
+            return vec![];
+        };
+        let number_reg = self
+            .platform()
+            .get_syscall_convention()
+            .and_then(|cc| cc.int_arg_registers().first().copied());
+
+        let mut sites = Vec::new();
+        for block in &mlil.basic_blocks() {
+            for instr in block.iter() {
+                if !matches!(
+                    instr.kind,
+                    MediumLevelILInstructionKind::Syscall(_)
+                        | MediumLevelILInstructionKind::SyscallUntyped(_)
+                ) {
+                    continue;
+                }
+                let number = number_reg.and_then(|reg| {
+                    let value = self.register_value_at(instr.address, reg, None);
+                    match value.state {
+                        RegisterValueType::ConstantValue
+                        | RegisterValueType::ConstantPointerValue => Some(value.value as u32),
+                        _ => None,
+                    }
+                });
+                sites.push(SystemCallSite {
+                    address: instr.address,
+                    number,
+                    instruction: instr,
+                });
+            }
+        }
+        sites
+    }
+
     /// Flow graph of unresolved stack adjustments
     pub fn unresolved_stack_adjustment_graph(&self) -> Option<Ref<FlowGraph>> {
         let graph = unsafe { BNGetUnresolvedStackAdjustmentGraph(self.handle) };
@@ -2526,6 +2835,16 @@ unsafe impl CoreArrayProviderInner for PerformanceInfo {
     }
 }
 
+/// A system call made by a [`Function`], see [`Function::system_call_sites`].
+#[derive(Debug, Clone)]
+pub struct SystemCallSite {
+    pub address: u64,
+    /// The system call number, `None` if it could not be determined from the data flow.
+    pub number: Option<u32>,
+    /// The MLIL system call instruction.
+    pub instruction: MediumLevelILInstruction,
+}
+
 // NOTE: only exists as part of an Array, never owned
 pub struct UnresolvedIndirectBranches(u64);
 
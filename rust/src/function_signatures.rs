@@ -0,0 +1,157 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Carry function prototypes from one build of a program to the next through a [`TypeLibrary`].
+
+use std::collections::HashMap;
+
+use crate::binary_view::{BinaryView, BinaryViewExt};
+use crate::metadata::Metadata;
+use crate::platform::Platform;
+use crate::rc::Ref;
+use crate::type_library::TypeLibrary;
+
+/// Type library metadata key of the map from function GUID to the name of its named object.
+pub const FUNCTION_GUIDS_METADATA_KEY: &str = "function_guids";
+
+/// Stable GUID of the function `name` on `platform`.
+///
+/// The same platform and name give the same GUID in every build and every session.
+pub fn function_guid(platform: &Platform, name: &str) -> String {
+    guid_for_key(&format!("{}!{}", platform.name(), name))
+}
+
+/// Export the prototypes of all named functions of `view` into `library`.
+///
+/// Functions that only have a default name (`sub_...`) or are for another architecture than the
+/// library are skipped. Signatures already in the library are kept, unless a function of the
+/// same name is exported again. Returns the number of exported prototypes.
+///
+/// Each function is also given a GUID derived from its platform and name, which
+/// [`apply_function_signatures`] uses to find the prototypes for the functions of a later build.
+pub fn export_function_signatures(view: &BinaryView, library: &TypeLibrary) -> usize {
+    let mut guids = function_guids(library);
+    let mut exported = 0;
+    for function in view.functions().iter() {
+        let platform = function.platform();
+        if platform.arch() != library.arch() {
+            continue;
+        }
+        let Some(symbol) = view.symbol_by_address(function.start()) else {
+            continue;
+        };
+        let name = symbol.full_name().to_string();
+        view.export_object_to_library(library, name.as_str(), &function.function_type());
+        library.add_platform(&platform);
+        guids.insert(function_guid(&platform, &name), name);
+        exported += 1;
+    }
+
+    let guids: HashMap<String, Ref<Metadata>> = guids
+        .into_iter()
+        .map(|(guid, name)| (guid, name.into()))
+        .collect();
+    let guids: Ref<Metadata> = guids.into();
+    library.store_metadata(FUNCTION_GUIDS_METADATA_KEY, &guids);
+    exported
+}
+
+/// Apply the prototypes exported into `library` to the functions of `view` with the same
+/// platform and name, as user types.
+///
+/// Types the prototypes refer to are imported from the library as needed. Returns the number of
+/// functions whose prototype was applied.
+///
+/// ```no_run
+/// use binaryninja::function_signatures::{apply_function_signatures, export_function_signatures};
+/// use binaryninja::binary_view::BinaryViewExt;
+/// use binaryninja::type_library::TypeLibrary;
+///
+/// let old = binaryninja::load("/tmp/v1.bndb").unwrap();
+/// let arch = old.default_arch().unwrap();
+/// let library = TypeLibrary::new(arch, "program-signatures");
+/// export_function_signatures(&old, &library);
+/// library.finalize();
+///
+/// let new = binaryninja::load("/tmp/v2").unwrap();
+/// let applied = apply_function_signatures(&new, &library);
+/// println!("Applied {} prototypes", applied);
+/// ```
+pub fn apply_function_signatures(view: &BinaryView, library: &TypeLibrary) -> usize {
+    let guids = function_guids(library);
+    if guids.is_empty() {
+        return 0;
+    }
+
+    let mut applied = 0;
+    for function in view.functions().iter() {
+        let Some(symbol) = view.symbol_by_address(function.start()) else {
+            continue;
+        };
+        let guid = function_guid(&function.platform(), symbol.full_name().as_str());
+        let Some(name) = guids.get(&guid) else {
+            continue;
+        };
+        if let Some(ty) = view.import_type_object(name.as_str(), Some(library.new_reference())) {
+            function.set_user_type(&ty);
+            applied += 1;
+        }
+    }
+    applied
+}
+
+/// The function GUIDs stored in `library` by [`export_function_signatures`].
+pub fn function_guids(library: &TypeLibrary) -> HashMap<String, String> {
+    let Some(metadata) = library.query_metadata(FUNCTION_GUIDS_METADATA_KEY) else {
+        return HashMap::new();
+    };
+    HashMap::<String, Ref<Metadata>>::try_from(&metadata)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(guid, name)| Some((guid, name.get_string().ok()?.to_string())))
+        .collect()
+}
+
+/// Format a 128-bit FNV-1a hash of `key` as a GUID.
+fn guid_for_key(key: &str) -> String {
+    const OFFSET_BASIS: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+    let hash = key.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ byte as u128).wrapping_mul(PRIME)
+    });
+    let hex = format!("{:032x}", hash);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn guids_are_stable() {
+        // FNV-1a 128 of the empty string is the offset basis
+        assert_eq!(guid_for_key(""), "6c62272e-07bb-0142-62b8-21756295c58d");
+        let guid = guid_for_key("linux-x86_64!main");
+        assert_eq!(guid.len(), 36);
+        assert_eq!(guid, guid_for_key("linux-x86_64!main"));
+        assert_ne!(guid, guid_for_key("linux-x86!main"));
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The headers of the ELF, PE and Mach-O file backing a view.
+
+mod elf;
+mod macho;
+mod pe;
+
+pub use elf::*;
+pub use macho::*;
+pub use pe::*;
+
+use crate::Endianness;
+
+/// The most entries read from a single table, so that a corrupt count can't make parsing take
+/// forever.
+const MAX_TABLE_ENTRIES: usize = 0x10000;
+
+/// The longest string read from a string table.
+const MAX_STRING_LEN: usize = 0x1000;
+
+/// Decodes the integers of a file in its byte order.
+#[derive(Copy, Clone, Debug)]
+struct ByteOrder {
+    little_endian: bool,
+}
+
+impl ByteOrder {
+    fn endianness(&self) -> Endianness {
+        match self.little_endian {
+            true => Endianness::LittleEndian,
+            false => Endianness::BigEndian,
+        }
+    }
+
+    fn uint(&self, data: &[u8], offset: usize, size: usize) -> Option<u64> {
+        let bytes = data.get(offset..offset.checked_add(size)?)?;
+        let value = bytes.iter().enumerate().fold(0u64, |value, (i, b)| {
+            let shift = match self.little_endian {
+                true => i * 8,
+                false => (size - 1 - i) * 8,
+            };
+            value | (*b as u64) << shift
+        });
+        Some(value)
+    }
+
+    fn u16(&self, data: &[u8], offset: usize) -> Option<u16> {
+        self.uint(data, offset, 2).map(|value| value as u16)
+    }
+
+    fn u32(&self, data: &[u8], offset: usize) -> Option<u32> {
+        self.uint(data, offset, 4).map(|value| value as u32)
+    }
+
+    fn u64(&self, data: &[u8], offset: usize) -> Option<u64> {
+        self.uint(data, offset, 8)
+    }
+}
+
+/// The string at the start of `data`, up to its first NUL.
+fn c_string(data: &[u8]) -> String {
+    let len = data.iter().position(|b| *b == 0).unwrap_or(data.len());
+    String::from_utf8_lossy(&data[..len]).into_owned()
+}
+
+/// The NUL terminated string at file `offset`.
+fn read_c_string(read: &impl Fn(u64, usize) -> Vec<u8>, offset: u64) -> String {
+    c_string(&read(offset, MAX_STRING_LEN))
+}
+
+/// Reads `count` entries of `size` bytes at file `offset`, at most [`MAX_TABLE_ENTRIES`].
+fn read_table(
+    read: &impl Fn(u64, usize) -> Vec<u8>,
+    offset: u64,
+    size: usize,
+    count: usize,
+) -> Vec<u8> {
+    read(offset, size * count.min(MAX_TABLE_ENTRIES))
+}
+
+/// Reads entries of `size` bytes at file `offset` up to the first that is all zeros, which ends
+/// tables like the PE import directory, and at most [`MAX_TABLE_ENTRIES`].
+fn read_terminated_table(
+    read: &impl Fn(u64, usize) -> Vec<u8>,
+    offset: u64,
+    size: usize,
+) -> Vec<u8> {
+    const CHUNK_ENTRIES: usize = 64;
+    let mut table = Vec::new();
+    while table.len() < size * MAX_TABLE_ENTRIES {
+        let chunk = read(offset + table.len() as u64, size * CHUNK_ENTRIES);
+        for entry in chunk.chunks_exact(size) {
+            if entry.iter().all(|b| *b == 0) {
+                return table;
+            }
+            table.extend_from_slice(entry);
+        }
+        if chunk.len() < size * CHUNK_ENTRIES {
+            break;
+        }
+    }
+    table
+}
+
+#[cfg(test)]
+pub(crate) fn reader(data: &[u8]) -> impl Fn(u64, usize) -> Vec<u8> + '_ {
+    move |offset, len| {
+        let start = (offset as usize).min(data.len());
+        data[start..(start + len).min(data.len())].to_vec()
+    }
+}
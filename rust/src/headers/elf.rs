@@ -0,0 +1,484 @@
+use super::{read_c_string, read_table, ByteOrder};
+use crate::section::ElfSectionFlags;
+use crate::Endianness;
+
+/// The file header of an ELF file, with its program headers, section headers and dynamic section.
+#[derive(Clone, Debug)]
+pub struct ElfHeader {
+    /// ELFCLASS64, otherwise ELFCLASS32.
+    pub is_64: bool,
+    pub endianness: Endianness,
+    pub os_abi: u8,
+    /// `e_type`, e.g. `ET_EXEC` or `ET_DYN`.
+    pub file_type: u16,
+    pub machine: u16,
+    pub entry: u64,
+    pub flags: u32,
+    pub program_headers: Vec<ElfProgramHeader>,
+    pub section_headers: Vec<ElfSectionHeader>,
+    /// The entries of the `PT_DYNAMIC` segment, without the terminating `DT_NULL`.
+    pub dynamic_entries: Vec<ElfDynamicEntry>,
+    /// The libraries named by the `DT_NEEDED` entries.
+    pub needed_libraries: Vec<String>,
+    /// The name given by the `DT_SONAME` entry.
+    pub soname: Option<String>,
+}
+
+/// A program header, describing a segment.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ElfProgramHeader {
+    /// `p_type`, e.g. [`ElfProgramHeader::LOAD`].
+    pub segment_type: u32,
+    pub flags: u32,
+    pub offset: u64,
+    pub vaddr: u64,
+    pub paddr: u64,
+    pub file_size: u64,
+    pub mem_size: u64,
+    pub align: u64,
+}
+
+/// A section header, with its name read from the section name string table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ElfSectionHeader {
+    pub name: String,
+    /// `sh_type`, e.g. [`ElfSectionHeader::NOBITS`].
+    pub section_type: u32,
+    pub flags: ElfSectionFlags,
+    pub addr: u64,
+    pub offset: u64,
+    pub size: u64,
+    pub link: u32,
+    pub info: u32,
+    pub align: u64,
+    pub entry_size: u64,
+}
+
+/// An entry of the dynamic section.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ElfDynamicEntry {
+    /// `d_tag`, e.g. [`ElfDynamicEntry::NEEDED`].
+    pub tag: i64,
+    pub value: u64,
+}
+
+impl ElfProgramHeader {
+    pub const LOAD: u32 = 1;
+    pub const DYNAMIC: u32 = 2;
+    pub const INTERP: u32 = 3;
+    pub const NOTE: u32 = 4;
+    pub const TLS: u32 = 7;
+    pub const GNU_EH_FRAME: u32 = 0x6474e550;
+    pub const GNU_STACK: u32 = 0x6474e551;
+    pub const GNU_RELRO: u32 = 0x6474e552;
+
+    /// The range of the file this segment is read from.
+    pub fn file_range(&self) -> std::ops::Range<u64> {
+        self.offset..self.offset.saturating_add(self.file_size)
+    }
+
+    /// The range of addresses this segment is loaded at.
+    pub fn address_range(&self) -> std::ops::Range<u64> {
+        self.vaddr..self.vaddr.saturating_add(self.mem_size)
+    }
+
+    /// Whether `section` is part of this segment.
+    ///
+    /// Sections that are loaded (`SHF_ALLOC`) are matched by address, others by file offset and
+    /// only against segments that are not loaded, like `PT_NOTE`.
+    pub fn contains(&self, section: &ElfSectionHeader) -> bool {
+        if section.section_type == ElfSectionHeader::NULL {
+            return false;
+        }
+        let (start, range) = match section.flags.contains(ElfSectionFlags::ALLOC) {
+            true => (section.addr, self.address_range()),
+            false if section.section_type == ElfSectionHeader::NOBITS => return false,
+            false if self.segment_type == Self::LOAD => return false,
+            false => (section.offset, self.file_range()),
+        };
+        match section.size {
+            0 => range.start <= start && start <= range.end,
+            size => range.start <= start && start.saturating_add(size) <= range.end,
+        }
+    }
+}
+
+impl ElfSectionHeader {
+    pub const NULL: u32 = 0;
+    pub const PROGBITS: u32 = 1;
+    pub const SYMTAB: u32 = 2;
+    pub const STRTAB: u32 = 3;
+    pub const RELA: u32 = 4;
+    pub const DYNAMIC: u32 = 6;
+    pub const NOTE: u32 = 7;
+    pub const NOBITS: u32 = 8;
+    pub const REL: u32 = 9;
+    pub const DYNSYM: u32 = 11;
+}
+
+impl ElfDynamicEntry {
+    pub const NULL: i64 = 0;
+    pub const NEEDED: i64 = 1;
+    pub const STRTAB: i64 = 5;
+    pub const SYMTAB: i64 = 6;
+    pub const INIT: i64 = 12;
+    pub const FINI: i64 = 13;
+    pub const SONAME: i64 = 14;
+    pub const RPATH: i64 = 15;
+    pub const RUNPATH: i64 = 29;
+}
+
+impl ElfHeader {
+    /// Parse the ELF file that `read(offset, len)` reads from, `None` if it is not one.
+    ///
+    /// `read` returns fewer bytes for reads past the end of the file.
+    pub fn parse(read: impl Fn(u64, usize) -> Vec<u8>) -> Option<Self> {
+        let ident = read(0, 16);
+        if ident.len() < 16 || &ident[..4] != b"\x7fELF" {
+            return None;
+        }
+        let is_64 = match ident[4] {
+            1 => false,
+            2 => true,
+            _ => return None,
+        };
+        let order = ByteOrder {
+            little_endian: match ident[5] {
+                1 => true,
+                2 => false,
+                _ => return None,
+            },
+        };
+        let elf = ElfLayout { is_64, order };
+
+        let header = read(0, if is_64 { 0x40 } else { 0x34 });
+        let word = elf.word_size();
+        // e_entry, e_phoff and e_shoff are address sized, the fields after them are not
+        let phoff = elf.word(&header, 0x18 + word)?;
+        let shoff = elf.word(&header, 0x18 + 2 * word)?;
+        let rest = 0x18 + 3 * word;
+        let mut result = ElfHeader {
+            is_64,
+            endianness: order.endianness(),
+            os_abi: ident[7],
+            file_type: order.u16(&header, 0x10)?,
+            machine: order.u16(&header, 0x12)?,
+            entry: elf.word(&header, 0x18)?,
+            flags: order.u32(&header, rest)?,
+            program_headers: Vec::new(),
+            section_headers: Vec::new(),
+            dynamic_entries: Vec::new(),
+            needed_libraries: Vec::new(),
+            soname: None,
+        };
+        let phentsize = order.u16(&header, rest + 6)? as usize;
+        let phnum = order.u16(&header, rest + 8)? as usize;
+        let shentsize = order.u16(&header, rest + 10)? as usize;
+        let shnum = order.u16(&header, rest + 12)? as usize;
+        let shstrndx = order.u16(&header, rest + 14)? as u32;
+
+        result.section_headers = elf.read_section_headers(&read, shoff, shentsize, shnum, shstrndx);
+        if phoff != 0 && phentsize != 0 {
+            let table = read_table(&read, phoff, phentsize, phnum);
+            result.program_headers = table
+                .chunks_exact(phentsize)
+                .map_while(|data| elf.parse_program_header(data))
+                .collect();
+        }
+        result.read_dynamic(&elf, &read);
+        Some(result)
+    }
+
+    /// The section header named `name`.
+    pub fn section(&self, name: &str) -> Option<&ElfSectionHeader> {
+        self.section_headers
+            .iter()
+            .find(|section| section.name == name)
+    }
+
+    /// The loadable segment that `section` is part of, `None` if it is not loaded.
+    pub fn segment_of(&self, section: &ElfSectionHeader) -> Option<&ElfProgramHeader> {
+        self.program_headers.iter().find(|segment| {
+            segment.segment_type == ElfProgramHeader::LOAD && segment.contains(section)
+        })
+    }
+
+    /// The sections that are part of `segment`.
+    pub fn sections_of<'a>(
+        &'a self,
+        segment: &'a ElfProgramHeader,
+    ) -> impl Iterator<Item = &'a ElfSectionHeader> {
+        self.section_headers
+            .iter()
+            .filter(move |section| segment.contains(section))
+    }
+
+    /// The file offset that the address `addr` is loaded from.
+    pub fn address_to_offset(&self, addr: u64) -> Option<u64> {
+        self.program_headers
+            .iter()
+            .filter(|segment| segment.segment_type == ElfProgramHeader::LOAD)
+            .find(|segment| addr >= segment.vaddr && addr - segment.vaddr < segment.file_size)
+            .map(|segment| segment.offset + (addr - segment.vaddr))
+    }
+
+    /// The value of the first dynamic entry with `tag`.
+    pub fn dynamic_value(&self, tag: i64) -> Option<u64> {
+        self.dynamic_entries
+            .iter()
+            .find(|entry| entry.tag == tag)
+            .map(|entry| entry.value)
+    }
+
+    fn read_dynamic(&mut self, elf: &ElfLayout, read: &impl Fn(u64, usize) -> Vec<u8>) {
+        let Some(dynamic) = self
+            .program_headers
+            .iter()
+            .find(|segment| segment.segment_type == ElfProgramHeader::DYNAMIC)
+        else {
+            return;
+        };
+        let entry_size = 2 * elf.word_size();
+        let count = dynamic.file_size as usize / entry_size;
+        let table = read_table(read, dynamic.offset, entry_size, count);
+        self.dynamic_entries = table
+            .chunks_exact(entry_size)
+            .map_while(|data| {
+                let tag = elf.word(data, 0)?;
+                // d_tag is signed, sign extend it for ELF32
+                let tag = match elf.is_64 {
+                    true => tag as i64,
+                    false => tag as u32 as i32 as i64,
+                };
+                let value = elf.word(data, elf.word_size())?;
+                Some(ElfDynamicEntry { tag, value })
+            })
+            .take_while(|entry| entry.tag != ElfDynamicEntry::NULL)
+            .collect();
+
+        // The string table is given by address, the other entries by offset into it
+        let Some(strtab) = self
+            .dynamic_value(ElfDynamicEntry::STRTAB)
+            .and_then(|addr| self.address_to_offset(addr))
+        else {
+            return;
+        };
+        let string = |offset: u64| read_c_string(read, strtab + offset);
+        self.needed_libraries = self
+            .dynamic_entries
+            .iter()
+            .filter(|entry| entry.tag == ElfDynamicEntry::NEEDED)
+            .map(|entry| string(entry.value))
+            .collect();
+        self.soname = self.dynamic_value(ElfDynamicEntry::SONAME).map(string);
+    }
+}
+
+/// The class and byte order of an ELF file, which decide the layout of its structures.
+struct ElfLayout {
+    is_64: bool,
+    order: ByteOrder,
+}
+
+impl ElfLayout {
+    fn word_size(&self) -> usize {
+        if self.is_64 {
+            8
+        } else {
+            4
+        }
+    }
+
+    /// Read an address sized field.
+    fn word(&self, data: &[u8], offset: usize) -> Option<u64> {
+        self.order.uint(data, offset, self.word_size())
+    }
+
+    fn parse_program_header(&self, data: &[u8]) -> Option<ElfProgramHeader> {
+        let order = &self.order;
+        // p_flags comes right after p_type in ELF64 and after p_memsz in ELF32
+        match self.is_64 {
+            true => Some(ElfProgramHeader {
+                segment_type: order.u32(data, 0)?,
+                flags: order.u32(data, 4)?,
+                offset: order.u64(data, 8)?,
+                vaddr: order.u64(data, 16)?,
+                paddr: order.u64(data, 24)?,
+                file_size: order.u64(data, 32)?,
+                mem_size: order.u64(data, 40)?,
+                align: order.u64(data, 48)?,
+            }),
+            false => Some(ElfProgramHeader {
+                segment_type: order.u32(data, 0)?,
+                offset: order.u32(data, 4)? as u64,
+                vaddr: order.u32(data, 8)? as u64,
+                paddr: order.u32(data, 12)? as u64,
+                file_size: order.u32(data, 16)? as u64,
+                mem_size: order.u32(data, 20)? as u64,
+                flags: order.u32(data, 24)?,
+                align: order.u32(data, 28)? as u64,
+            }),
+        }
+    }
+
+    fn parse_section_header(&self, data: &[u8]) -> Option<(u32, ElfSectionHeader)> {
+        // sh_name and sh_type, then address sized fields except for sh_link and sh_info
+        let word = self.word_size();
+        let order = &self.order;
+        let header = ElfSectionHeader {
+            name: String::new(),
+            section_type: order.u32(data, 4)?,
+            flags: ElfSectionFlags(self.word(data, 8)?),
+            addr: self.word(data, 8 + word)?,
+            offset: self.word(data, 8 + 2 * word)?,
+            size: self.word(data, 8 + 3 * word)?,
+            link: order.u32(data, 8 + 4 * word)?,
+            info: order.u32(data, 12 + 4 * word)?,
+            align: self.word(data, 16 + 4 * word)?,
+            entry_size: self.word(data, 16 + 5 * word)?,
+        };
+        Some((order.u32(data, 0)?, header))
+    }
+
+    fn read_section_headers(
+        &self,
+        read: &impl Fn(u64, usize) -> Vec<u8>,
+        shoff: u64,
+        shentsize: usize,
+        mut shnum: usize,
+        mut shstrndx: u32,
+    ) -> Vec<ElfSectionHeader> {
+        const SHN_XINDEX: u32 = 0xffff;
+
+        if shoff == 0 || shentsize == 0 {
+            return Vec::new();
+        }
+        // Section 0 holds the real count and string table index when they don't fit the file header
+        let Some((_, first)) = self.parse_section_header(&read(shoff, shentsize)) else {
+            return Vec::new();
+        };
+        if shnum == 0 {
+            shnum = first.size as usize;
+        }
+        if shstrndx == SHN_XINDEX {
+            shstrndx = first.link;
+        }
+
+        let table = read_table(read, shoff, shentsize, shnum);
+        let mut headers: Vec<(u32, ElfSectionHeader)> = table
+            .chunks_exact(shentsize)
+            .map_while(|data| self.parse_section_header(data))
+            .collect();
+        let string_table = headers
+            .get(shstrndx as usize)
+            .map(|(_, header)| read(header.offset, header.size as usize))
+            .unwrap_or_default();
+        for (name_offset, header) in &mut headers {
+            if let Some(name) = string_table.get(*name_offset as usize..) {
+                header.name = super::c_string(name);
+            }
+        }
+        headers.into_iter().map(|(_, header)| header).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::headers::reader;
+
+    /// A little endian ELF64 shared object with a `PT_LOAD` and `PT_DYNAMIC` segment, `.dynstr`,
+    /// `.bss` and `.shstrtab`.
+    fn elf64() -> Vec<u8> {
+        let section_names = b"\0.dynstr\0.bss\0.shstrtab\0";
+        let dynamic_strings = b"\0libc.so.6\0libtest.so\0";
+        let mut data = vec![0u8; 0x200];
+        data[..8].copy_from_slice(b"\x7fELF\x02\x01\x01\x00");
+        data[0x10..0x12].copy_from_slice(&3u16.to_le_bytes());
+        data[0x12..0x14].copy_from_slice(&62u16.to_le_bytes());
+        data[0x18..0x20].copy_from_slice(&0x1040u64.to_le_bytes());
+        data[0x20..0x28].copy_from_slice(&0x40u64.to_le_bytes());
+        data[0x28..0x30].copy_from_slice(&0x200u64.to_le_bytes());
+        data[0x36..0x38].copy_from_slice(&56u16.to_le_bytes());
+        data[0x38..0x3a].copy_from_slice(&2u16.to_le_bytes());
+        data[0x3a..0x3c].copy_from_slice(&64u16.to_le_bytes());
+        data[0x3c..0x3e].copy_from_slice(&4u16.to_le_bytes());
+        data[0x3e..0x40].copy_from_slice(&3u16.to_le_bytes());
+
+        let segment = |data: &mut Vec<u8>, at: usize, ty: u32, offset: u64, size: u64| {
+            data[at..at + 4].copy_from_slice(&ty.to_le_bytes());
+            data[at + 8..at + 16].copy_from_slice(&offset.to_le_bytes());
+            data[at + 16..at + 24].copy_from_slice(&(0x1000 + offset).to_le_bytes());
+            data[at + 32..at + 40].copy_from_slice(&size.to_le_bytes());
+            data[at + 40..at + 48].copy_from_slice(&(size + 0x10).to_le_bytes());
+        };
+        segment(&mut data, 0x40, ElfProgramHeader::LOAD, 0, 0x1f0);
+        segment(&mut data, 0x78, ElfProgramHeader::DYNAMIC, 0x100, 0x40);
+
+        // DT_NEEDED libc.so.6, DT_SONAME libtest.so, DT_STRTAB at 0x1180 and DT_NULL
+        let dynamic = [(1u64, 1u64), (14, 11), (5, 0x1180), (0, 0)];
+        for (i, (tag, value)) in dynamic.iter().enumerate() {
+            data[0x100 + i * 16..][..8].copy_from_slice(&tag.to_le_bytes());
+            data[0x108 + i * 16..][..8].copy_from_slice(&value.to_le_bytes());
+        }
+        data[0x180..][..dynamic_strings.len()].copy_from_slice(dynamic_strings);
+        data[0x1c0..][..section_names.len()].copy_from_slice(section_names);
+
+        let section = |name: u32, ty: u32, flags: u64, addr: u64, offset: u64, size: u64| {
+            let mut header = vec![0u8; 64];
+            header[0..4].copy_from_slice(&name.to_le_bytes());
+            header[4..8].copy_from_slice(&ty.to_le_bytes());
+            header[8..16].copy_from_slice(&flags.to_le_bytes());
+            header[16..24].copy_from_slice(&addr.to_le_bytes());
+            header[24..32].copy_from_slice(&offset.to_le_bytes());
+            header[32..40].copy_from_slice(&size.to_le_bytes());
+            header
+        };
+        let alloc = ElfSectionFlags::ALLOC.bits();
+        let write = alloc | ElfSectionFlags::WRITE.bits();
+        data.extend(section(0, 0, 0, 0, 0, 0));
+        data.extend(section(1, 3, alloc, 0x1180, 0x180, 0x17));
+        data.extend(section(9, 8, write, 0x11f0, 0x1f0, 0x10));
+        data.extend(section(14, 3, 0, 0, 0x1c0, 0x18));
+        data
+    }
+
+    #[test]
+    fn parses_elf_headers() {
+        let data = elf64();
+        let elf = ElfHeader::parse(reader(&data)).unwrap();
+        assert!(elf.is_64);
+        assert_eq!(elf.endianness, Endianness::LittleEndian);
+        assert_eq!(elf.file_type, 3);
+        assert_eq!(elf.machine, 62);
+        assert_eq!(elf.entry, 0x1040);
+        assert_eq!(elf.program_headers.len(), 2);
+        assert_eq!(elf.program_headers[1].vaddr, 0x1100);
+
+        let names: Vec<_> = elf
+            .section_headers
+            .iter()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(names, ["", ".dynstr", ".bss", ".shstrtab"]);
+        let dynstr = elf.section(".dynstr").unwrap();
+        assert_eq!(elf.segment_of(dynstr), Some(&elf.program_headers[0]));
+        // .bss is only in memory, past the end of the file contents of the segment
+        let bss = elf.section(".bss").unwrap();
+        assert_eq!(elf.segment_of(bss), Some(&elf.program_headers[0]));
+        assert!(elf.segment_of(elf.section(".shstrtab").unwrap()).is_none());
+        let loaded: Vec<_> = elf.sections_of(&elf.program_headers[0]).collect();
+        assert_eq!(loaded, [dynstr, bss]);
+
+        assert_eq!(elf.dynamic_entries.len(), 3);
+        assert_eq!(elf.needed_libraries, ["libc.so.6"]);
+        assert_eq!(elf.soname.as_deref(), Some("libtest.so"));
+        assert_eq!(elf.address_to_offset(0x1180), Some(0x180));
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(ElfHeader::parse(reader(b"MZ\x90\x00")).is_none());
+        assert!(ElfHeader::parse(reader(b"\x7fELF")).is_none());
+    }
+}
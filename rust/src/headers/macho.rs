@@ -0,0 +1,287 @@
+use super::{c_string, ByteOrder};
+use crate::Endianness;
+
+/// The header of a Mach-O file, with the segments and libraries of its load commands.
+///
+/// Universal files are not parsed, the view of one of their architectures is a Mach-O file.
+#[derive(Clone, Debug)]
+pub struct MachOHeader {
+    pub is_64: bool,
+    pub endianness: Endianness,
+    pub cpu_type: u32,
+    pub cpu_subtype: u32,
+    /// `filetype`, e.g. `MH_EXECUTE` or `MH_DYLIB`.
+    pub file_type: u32,
+    pub flags: u32,
+    pub segments: Vec<MachOSegment>,
+    /// The libraries of the `LC_LOAD_DYLIB` commands and their weak, re-exported, lazy and upward
+    /// variants.
+    pub libraries: Vec<String>,
+    /// The name of the `LC_ID_DYLIB` command of a library.
+    pub install_name: Option<String>,
+    /// File offset of the entry point given by the `LC_MAIN` command.
+    pub entry_offset: Option<u64>,
+    pub uuid: Option<[u8; 16]>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MachOSegment {
+    pub name: String,
+    pub vm_addr: u64,
+    pub vm_size: u64,
+    pub file_offset: u64,
+    pub file_size: u64,
+    pub max_prot: u32,
+    pub init_prot: u32,
+    pub flags: u32,
+    pub sections: Vec<MachOSection>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MachOSection {
+    pub name: String,
+    pub segment_name: String,
+    pub addr: u64,
+    pub size: u64,
+    /// File offset of the contents, 0 for zero filled sections.
+    pub offset: u32,
+    /// Alignment as a power of two.
+    pub align: u32,
+    pub flags: u32,
+}
+
+/// The most bytes of load commands read, so that a corrupt size can't exhaust memory.
+const MAX_COMMANDS_SIZE: usize = 0x1000000;
+
+const LC_SEGMENT: u32 = 0x1;
+const LC_LOAD_DYLIB: u32 = 0xc;
+const LC_ID_DYLIB: u32 = 0xd;
+const LC_UUID: u32 = 0x1b;
+const LC_SEGMENT_64: u32 = 0x19;
+const LC_LAZY_LOAD_DYLIB: u32 = 0x20;
+const LC_LOAD_WEAK_DYLIB: u32 = 0x80000018;
+const LC_REEXPORT_DYLIB: u32 = 0x8000001f;
+const LC_LOAD_UPWARD_DYLIB: u32 = 0x80000023;
+const LC_MAIN: u32 = 0x80000028;
+
+impl MachOHeader {
+    pub const MH_EXECUTE: u32 = 0x2;
+    pub const MH_DYLIB: u32 = 0x6;
+    pub const MH_BUNDLE: u32 = 0x8;
+    pub const MH_DSYM: u32 = 0xa;
+
+    /// Parse the Mach-O file that `read(offset, len)` reads from, `None` if it is not one.
+    ///
+    /// `read` returns fewer bytes for reads past the end of the file.
+    pub fn parse(read: impl Fn(u64, usize) -> Vec<u8>) -> Option<Self> {
+        let magic = read(0, 4);
+        let (is_64, little_endian) = match magic.as_slice() {
+            b"\xce\xfa\xed\xfe" => (false, true),
+            b"\xcf\xfa\xed\xfe" => (true, true),
+            b"\xfe\xed\xfa\xce" => (false, false),
+            b"\xfe\xed\xfa\xcf" => (true, false),
+            _ => return None,
+        };
+        let order = ByteOrder { little_endian };
+        let header_size = if is_64 { 32 } else { 28 };
+        let header = read(0, header_size);
+        let command_count = order.u32(&header, 16)? as usize;
+        let commands_size = order.u32(&header, 20)? as usize;
+        let mut result = MachOHeader {
+            is_64,
+            endianness: order.endianness(),
+            cpu_type: order.u32(&header, 4)?,
+            cpu_subtype: order.u32(&header, 8)?,
+            file_type: order.u32(&header, 12)?,
+            flags: order.u32(&header, 24)?,
+            segments: Vec::new(),
+            libraries: Vec::new(),
+            install_name: None,
+            entry_offset: None,
+            uuid: None,
+        };
+
+        let commands = read(header_size as u64, commands_size.min(MAX_COMMANDS_SIZE));
+        let mut offset = 0;
+        for _ in 0..command_count {
+            let (Some(cmd), Some(size)) = (
+                order.u32(&commands, offset),
+                order.u32(&commands, offset + 4),
+            ) else {
+                break;
+            };
+            let Some(command) = commands.get(offset..offset + size as usize) else {
+                break;
+            };
+            if size < 8 {
+                break;
+            }
+            result.parse_command(&order, cmd, command);
+            offset += size as usize;
+        }
+        Some(result)
+    }
+
+    /// The segment named `name`, e.g. `__TEXT`.
+    pub fn segment(&self, name: &str) -> Option<&MachOSegment> {
+        self.segments.iter().find(|segment| segment.name == name)
+    }
+
+    /// The section named `name` in the segment named `segment_name`.
+    pub fn section(&self, segment_name: &str, name: &str) -> Option<&MachOSection> {
+        self.segment(segment_name)?
+            .sections
+            .iter()
+            .find(|section| section.name == name)
+    }
+
+    fn parse_command(&mut self, order: &ByteOrder, cmd: u32, command: &[u8]) {
+        // The name of a dylib command is at an offset from the start of the command
+        let dylib_name = || {
+            let offset = order.u32(command, 8)? as usize;
+            command.get(offset..).map(c_string)
+        };
+        match cmd {
+            LC_SEGMENT | LC_SEGMENT_64 => {
+                if let Some(segment) = parse_segment(order, cmd == LC_SEGMENT_64, command) {
+                    self.segments.push(segment);
+                }
+            }
+            LC_LOAD_DYLIB | LC_LOAD_WEAK_DYLIB | LC_REEXPORT_DYLIB | LC_LAZY_LOAD_DYLIB
+            | LC_LOAD_UPWARD_DYLIB => self.libraries.extend(dylib_name()),
+            LC_ID_DYLIB => self.install_name = dylib_name(),
+            LC_MAIN => self.entry_offset = order.u64(command, 8),
+            LC_UUID => self.uuid = command.get(8..24).and_then(|uuid| uuid.try_into().ok()),
+            _ => {}
+        }
+    }
+}
+
+fn parse_segment(order: &ByteOrder, is_64: bool, command: &[u8]) -> Option<MachOSegment> {
+    // Addresses, sizes and offsets are address sized, the fields after them are not
+    let word = if is_64 { 8 } else { 4 };
+    let field = |offset: usize| order.uint(command, offset, word);
+    let rest = 24 + 4 * word;
+    let section_count = order.u32(command, rest + 8)? as usize;
+    let mut segment = MachOSegment {
+        name: c_string(command.get(8..24)?),
+        vm_addr: field(24)?,
+        vm_size: field(24 + word)?,
+        file_offset: field(24 + 2 * word)?,
+        file_size: field(24 + 3 * word)?,
+        max_prot: order.u32(command, rest)?,
+        init_prot: order.u32(command, rest + 4)?,
+        flags: order.u32(command, rest + 12)?,
+        sections: Vec::new(),
+    };
+
+    let section_size = if is_64 { 80 } else { 68 };
+    let sections = command.get(rest + 16..).unwrap_or_default();
+    segment.sections = sections
+        .chunks_exact(section_size)
+        .take(section_count)
+        .map_while(|data| {
+            let field = |offset: usize| order.uint(data, offset, word);
+            let rest = 32 + 2 * word;
+            Some(MachOSection {
+                name: c_string(&data[..16]),
+                segment_name: c_string(&data[16..32]),
+                addr: field(32)?,
+                size: field(32 + word)?,
+                offset: order.u32(data, rest)?,
+                align: order.u32(data, rest + 4)?,
+                flags: order.u32(data, rest + 16)?,
+            })
+        })
+        .collect();
+    Some(segment)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::headers::reader;
+
+    fn name(name: &str) -> [u8; 16] {
+        let mut bytes = [0u8; 16];
+        bytes[..name.len()].copy_from_slice(name.as_bytes());
+        bytes
+    }
+
+    /// A little endian 64-bit dylib with a `__TEXT` segment holding `__text`, and a dependency.
+    fn macho64() -> Vec<u8> {
+        let mut segment = Vec::new();
+        segment.extend(LC_SEGMENT_64.to_le_bytes());
+        segment.extend((72u32 + 80).to_le_bytes());
+        segment.extend(name("__TEXT"));
+        for value in [0x1000u64, 0x1000, 0, 0x1000] {
+            segment.extend(value.to_le_bytes());
+        }
+        for value in [5u32, 5, 1, 0] {
+            segment.extend(value.to_le_bytes());
+        }
+        segment.extend(name("__text"));
+        segment.extend(name("__TEXT"));
+        segment.extend(0x1800u64.to_le_bytes());
+        segment.extend(0x100u64.to_le_bytes());
+        for value in [0x800u32, 4, 0, 0, 0x80000400, 0, 0, 0] {
+            segment.extend(value.to_le_bytes());
+        }
+
+        let dylib = |cmd: u32, name: &[u8]| {
+            let mut command = Vec::new();
+            command.extend(cmd.to_le_bytes());
+            command.extend((24 + name.len() as u32).to_le_bytes());
+            command.extend(24u32.to_le_bytes());
+            command.extend([0u8; 12]);
+            command.extend(name);
+            command
+        };
+        let load = dylib(LC_LOAD_DYLIB, b"/usr/lib/libSystem.B.dylib\0\0\0\0\0\0");
+        let id = dylib(LC_ID_DYLIB, b"@rpath/libtest.dylib\0\0\0\0");
+
+        let mut data = Vec::new();
+        data.extend(b"\xcf\xfa\xed\xfe");
+        let commands_size = segment.len() + load.len() + id.len();
+        for value in [
+            0x0100000cu32,
+            0,
+            MachOHeader::MH_DYLIB,
+            3,
+            commands_size as u32,
+            0,
+            0,
+        ] {
+            data.extend(value.to_le_bytes());
+        }
+        data.extend(segment);
+        data.extend(load);
+        data.extend(id);
+        data
+    }
+
+    #[test]
+    fn parses_macho_headers() {
+        let data = macho64();
+        let macho = MachOHeader::parse(reader(&data)).unwrap();
+        assert!(macho.is_64);
+        assert_eq!(macho.cpu_type, 0x0100000c);
+        assert_eq!(macho.file_type, MachOHeader::MH_DYLIB);
+        assert_eq!(macho.segments.len(), 1);
+        let text = macho.segment("__TEXT").unwrap();
+        assert_eq!(text.vm_addr, 0x1000);
+        assert_eq!(text.init_prot, 5);
+        let section = macho.section("__TEXT", "__text").unwrap();
+        assert_eq!(section.addr, 0x1800);
+        assert_eq!(section.offset, 0x800);
+        assert_eq!(section.flags, 0x80000400);
+        assert_eq!(macho.libraries, ["/usr/lib/libSystem.B.dylib"]);
+        assert_eq!(macho.install_name.as_deref(), Some("@rpath/libtest.dylib"));
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(MachOHeader::parse(reader(b"\xca\xfe\xba\xbe")).is_none());
+        assert!(MachOHeader::parse(reader(b"MZ")).is_none());
+    }
+}
@@ -0,0 +1,438 @@
+use super::{c_string, read_c_string, read_table, read_terminated_table, ByteOrder};
+
+const ORDER: ByteOrder = ByteOrder {
+    little_endian: true,
+};
+
+/// The COFF and optional headers of a PE file, with its sections, imports and exports.
+#[derive(Clone, Debug)]
+pub struct PeHeader {
+    pub machine: u16,
+    pub timestamp: u32,
+    pub characteristics: u16,
+    /// The optional header is PE32+, otherwise PE32.
+    pub is_pe32_plus: bool,
+    pub image_base: u64,
+    /// Relative virtual address of the entry point, 0 if there is none.
+    pub entry_point: u32,
+    pub section_alignment: u32,
+    pub file_alignment: u32,
+    pub size_of_image: u32,
+    pub size_of_headers: u32,
+    pub subsystem: u16,
+    pub dll_characteristics: u16,
+    /// The data directories, indexed by constants like [`PeHeader::IMPORT_DIRECTORY`].
+    pub data_directories: Vec<PeDataDirectory>,
+    pub sections: Vec<PeSectionHeader>,
+    /// The libraries of the import directory and what is imported from each.
+    pub imports: Vec<PeImportDescriptor>,
+    /// The exports of the export directory, by ordinal.
+    pub exports: Vec<PeExport>,
+}
+
+/// The location of a data directory, like the import table.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PeDataDirectory {
+    pub rva: u32,
+    pub size: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PeSectionHeader {
+    pub name: String,
+    pub virtual_address: u32,
+    pub virtual_size: u32,
+    pub raw_offset: u32,
+    pub raw_size: u32,
+    pub characteristics: u32,
+}
+
+/// The imports of one library.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PeImportDescriptor {
+    pub library: String,
+    /// Relative virtual address of the import address table the loader fills in.
+    pub iat_rva: u32,
+    pub functions: Vec<PeImport>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PeImport {
+    Name { hint: u16, name: String },
+    Ordinal(u16),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PeExport {
+    pub name: Option<String>,
+    pub ordinal: u32,
+    pub rva: u32,
+    /// The `library.function` this export forwards to, its `rva` is that of the string.
+    pub forwarder: Option<String>,
+}
+
+impl PeHeader {
+    pub const EXPORT_DIRECTORY: usize = 0;
+    pub const IMPORT_DIRECTORY: usize = 1;
+    pub const RESOURCE_DIRECTORY: usize = 2;
+    pub const EXCEPTION_DIRECTORY: usize = 3;
+    pub const BASE_RELOCATION_DIRECTORY: usize = 5;
+    pub const DEBUG_DIRECTORY: usize = 6;
+    pub const TLS_DIRECTORY: usize = 9;
+    pub const LOAD_CONFIG_DIRECTORY: usize = 10;
+    pub const IAT_DIRECTORY: usize = 12;
+    pub const DELAY_IMPORT_DIRECTORY: usize = 13;
+
+    /// Parse the PE file that `read(offset, len)` reads from, `None` if it is not one.
+    ///
+    /// `read` returns fewer bytes for reads past the end of the file.
+    pub fn parse(read: impl Fn(u64, usize) -> Vec<u8>) -> Option<Self> {
+        let dos_header = read(0, 0x40);
+        if !dos_header.starts_with(b"MZ") {
+            return None;
+        }
+        let pe_offset = ORDER.u32(&dos_header, 0x3c)? as u64;
+        let headers = read(pe_offset, 24);
+        if !headers.starts_with(b"PE\0\0") {
+            return None;
+        }
+        let section_count = ORDER.u16(&headers, 6)? as usize;
+        let optional_header_size = ORDER.u16(&headers, 20)? as usize;
+        let optional_offset = pe_offset + 24;
+        let optional = read(optional_offset, optional_header_size);
+        let is_pe32_plus = match ORDER.u16(&optional, 0)? {
+            0x10b => false,
+            0x20b => true,
+            _ => return None,
+        };
+        let (image_base, directories_offset) = match is_pe32_plus {
+            true => (ORDER.u64(&optional, 24)?, 112),
+            false => (ORDER.u32(&optional, 28)? as u64, 96),
+        };
+        let directory_count = ORDER.u32(&optional, directories_offset - 4)? as usize;
+        let data_directories = (0..directory_count.min(16))
+            .map_while(|i| {
+                let offset = directories_offset + i * 8;
+                Some(PeDataDirectory {
+                    rva: ORDER.u32(&optional, offset)?,
+                    size: ORDER.u32(&optional, offset + 4)?,
+                })
+            })
+            .collect();
+
+        let section_table = read_table(
+            &read,
+            optional_offset + optional_header_size as u64,
+            40,
+            section_count,
+        );
+        let sections = section_table
+            .chunks_exact(40)
+            .map_while(|data| {
+                Some(PeSectionHeader {
+                    name: c_string(&data[..8]),
+                    virtual_size: ORDER.u32(data, 8)?,
+                    virtual_address: ORDER.u32(data, 12)?,
+                    raw_size: ORDER.u32(data, 16)?,
+                    raw_offset: ORDER.u32(data, 20)?,
+                    characteristics: ORDER.u32(data, 36)?,
+                })
+            })
+            .collect();
+
+        let mut header = PeHeader {
+            machine: ORDER.u16(&headers, 4)?,
+            timestamp: ORDER.u32(&headers, 8)?,
+            characteristics: ORDER.u16(&headers, 22)?,
+            is_pe32_plus,
+            image_base,
+            entry_point: ORDER.u32(&optional, 16)?,
+            section_alignment: ORDER.u32(&optional, 32)?,
+            file_alignment: ORDER.u32(&optional, 36)?,
+            size_of_image: ORDER.u32(&optional, 56)?,
+            size_of_headers: ORDER.u32(&optional, 60)?,
+            subsystem: ORDER.u16(&optional, 68)?,
+            dll_characteristics: ORDER.u16(&optional, 70)?,
+            data_directories,
+            sections,
+            imports: Vec::new(),
+            exports: Vec::new(),
+        };
+        header.imports = header.read_imports(&read);
+        header.exports = header.read_exports(&read);
+        Some(header)
+    }
+
+    /// The data directory at `index`, `None` if it is missing or empty.
+    pub fn data_directory(&self, index: usize) -> Option<PeDataDirectory> {
+        self.data_directories
+            .get(index)
+            .copied()
+            .filter(|directory| directory.rva != 0)
+    }
+
+    /// The section header named `name`.
+    pub fn section(&self, name: &str) -> Option<&PeSectionHeader> {
+        self.sections.iter().find(|section| section.name == name)
+    }
+
+    /// The section that the relative virtual address `rva` is in.
+    pub fn section_of_rva(&self, rva: u32) -> Option<&PeSectionHeader> {
+        self.sections.iter().find(|section| {
+            let size = section.virtual_size.max(section.raw_size);
+            rva >= section.virtual_address && rva - section.virtual_address < size
+        })
+    }
+
+    /// The file offset that the relative virtual address `rva` is loaded from.
+    pub fn rva_to_offset(&self, rva: u32) -> Option<u64> {
+        match self.section_of_rva(rva) {
+            Some(section) => {
+                let delta = rva - section.virtual_address;
+                (delta < section.raw_size).then(|| section.raw_offset as u64 + delta as u64)
+            }
+            // The headers are mapped as they are in the file
+            None => (rva < self.size_of_headers).then_some(rva as u64),
+        }
+    }
+
+    fn read_imports(&self, read: &impl Fn(u64, usize) -> Vec<u8>) -> Vec<PeImportDescriptor> {
+        let Some(offset) = self
+            .data_directory(Self::IMPORT_DIRECTORY)
+            .and_then(|directory| self.rva_to_offset(directory.rva))
+        else {
+            return Vec::new();
+        };
+        let table = read_terminated_table(read, offset, 20);
+        table
+            .chunks_exact(20)
+            .map_while(|data| {
+                // OriginalFirstThunk, TimeDateStamp, ForwarderChain, Name and FirstThunk
+                let lookup_rva = ORDER.u32(data, 0)?;
+                let name_rva = ORDER.u32(data, 12)?;
+                let iat_rva = ORDER.u32(data, 16)?;
+                (name_rva != 0 || iat_rva != 0).then_some((lookup_rva, name_rva, iat_rva))
+            })
+            .map(|(lookup_rva, name_rva, iat_rva)| PeImportDescriptor {
+                library: self
+                    .rva_to_offset(name_rva)
+                    .map(|offset| read_c_string(read, offset))
+                    .unwrap_or_default(),
+                iat_rva,
+                // Bound imports overwrite the address table, the lookup table keeps the names
+                functions: match lookup_rva {
+                    0 => self.read_import_thunks(read, iat_rva),
+                    rva => self.read_import_thunks(read, rva),
+                },
+            })
+            .collect()
+    }
+
+    fn read_import_thunks(&self, read: &impl Fn(u64, usize) -> Vec<u8>, rva: u32) -> Vec<PeImport> {
+        let Some(offset) = self.rva_to_offset(rva) else {
+            return Vec::new();
+        };
+        let (size, ordinal_flag) = match self.is_pe32_plus {
+            true => (8, 1u64 << 63),
+            false => (4, 1u64 << 31),
+        };
+        let table = read_terminated_table(read, offset, size);
+        table
+            .chunks_exact(size)
+            .filter_map(|data| ORDER.uint(data, 0, size))
+            .map(|thunk| match thunk & ordinal_flag {
+                0 => {
+                    let entry = self.rva_to_offset(thunk as u32).map(|offset| {
+                        let data = read(offset, 2);
+                        let hint = ORDER.u16(&data, 0).unwrap_or(0);
+                        (hint, read_c_string(read, offset + 2))
+                    });
+                    let (hint, name) = entry.unwrap_or_default();
+                    PeImport::Name { hint, name }
+                }
+                _ => PeImport::Ordinal(thunk as u16),
+            })
+            .collect()
+    }
+
+    fn read_exports(&self, read: &impl Fn(u64, usize) -> Vec<u8>) -> Vec<PeExport> {
+        let Some(directory) = self.data_directory(Self::EXPORT_DIRECTORY) else {
+            return Vec::new();
+        };
+        let Some(data) = self
+            .rva_to_offset(directory.rva)
+            .map(|offset| read(offset, 40))
+        else {
+            return Vec::new();
+        };
+        let fields = || -> Option<_> {
+            Some((
+                ORDER.u32(&data, 16)?,
+                ORDER.u32(&data, 20)? as usize,
+                ORDER.u32(&data, 24)? as usize,
+                ORDER.u32(&data, 28)?,
+                ORDER.u32(&data, 32)?,
+                ORDER.u32(&data, 36)?,
+            ))
+        };
+        let Some((base, function_count, name_count, functions_rva, names_rva, ordinals_rva)) =
+            fields()
+        else {
+            return Vec::new();
+        };
+        let table = |rva: u32, size: usize, count: usize| {
+            self.rva_to_offset(rva)
+                .map(|offset| read_table(read, offset, size, count))
+                .unwrap_or_default()
+        };
+        let functions = table(functions_rva, 4, function_count);
+        let names = table(names_rva, 4, name_count);
+        let ordinals = table(ordinals_rva, 2, name_count);
+
+        let mut exports: Vec<PeExport> = functions
+            .chunks_exact(4)
+            .enumerate()
+            .filter_map(|(i, data)| {
+                let rva = ORDER.u32(data, 0)?;
+                (rva != 0).then(|| PeExport {
+                    name: None,
+                    ordinal: base.wrapping_add(i as u32),
+                    rva,
+                    // Forwarders point back into the export directory instead of at code
+                    forwarder: (rva >= directory.rva && rva - directory.rva < directory.size)
+                        .then(|| self.rva_to_offset(rva))
+                        .flatten()
+                        .map(|offset| read_c_string(read, offset)),
+                })
+            })
+            .collect();
+        for (name, index) in names.chunks_exact(4).zip(ordinals.chunks_exact(2)) {
+            let (Some(name_rva), Some(index)) = (ORDER.u32(name, 0), ORDER.u16(index, 0)) else {
+                continue;
+            };
+            let ordinal = base.wrapping_add(index as u32);
+            let Some(export) = exports.iter_mut().find(|export| export.ordinal == ordinal) else {
+                continue;
+            };
+            export.name = self
+                .rva_to_offset(name_rva)
+                .map(|offset| read_c_string(read, offset));
+        }
+        exports
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::headers::reader;
+
+    /// A PE32+ DLL with a `.rdata` section holding an import of `KERNEL32.dll` and two exports.
+    fn pe64() -> Vec<u8> {
+        let mut data = vec![0u8; 0x600];
+        let mut put =
+            |offset: usize, bytes: &[u8]| data[offset..][..bytes.len()].copy_from_slice(bytes);
+        put(0, b"MZ");
+        put(0x3c, &0x80u32.to_le_bytes());
+        put(0x80, b"PE\0\0");
+        put(0x84, &0x8664u16.to_le_bytes());
+        put(0x86, &1u16.to_le_bytes());
+        put(0x94, &240u16.to_le_bytes());
+        put(0x96, &0x2022u16.to_le_bytes());
+        // The optional header at 0x98
+        put(0x98, &0x20bu16.to_le_bytes());
+        put(0x98 + 16, &0x1010u32.to_le_bytes());
+        put(0x98 + 24, &0x180000000u64.to_le_bytes());
+        put(0x98 + 60, &0x200u32.to_le_bytes());
+        put(0x98 + 108, &16u32.to_le_bytes());
+        // Export directory at 0x1100 and import directory at 0x1000
+        put(0x98 + 112, &0x1100u32.to_le_bytes());
+        put(0x98 + 116, &0x100u32.to_le_bytes());
+        put(0x98 + 120, &0x1000u32.to_le_bytes());
+        put(0x98 + 124, &0x28u32.to_le_bytes());
+        // .rdata maps RVA 0x1000 to file offset 0x200
+        put(0x188, b".rdata\0\0");
+        put(0x188 + 8, &0x400u32.to_le_bytes());
+        put(0x188 + 12, &0x1000u32.to_le_bytes());
+        put(0x188 + 16, &0x400u32.to_le_bytes());
+        put(0x188 + 20, &0x200u32.to_le_bytes());
+
+        // One import descriptor with a lookup table at 0x1040 and name at 0x1080
+        put(0x200, &0x1040u32.to_le_bytes());
+        put(0x200 + 12, &0x1080u32.to_le_bytes());
+        put(0x200 + 16, &0x1060u32.to_le_bytes());
+        put(0x240, &0x1090u64.to_le_bytes());
+        put(0x248, &(1u64 << 63 | 17).to_le_bytes());
+        put(0x280, b"KERNEL32.dll\0");
+        put(0x290, &7u16.to_le_bytes());
+        put(0x292, b"ExitProcess\0");
+
+        // Two exported functions with ordinal base 1, the second forwarded and only the first named
+        put(0x300 + 16, &1u32.to_le_bytes());
+        put(0x300 + 20, &2u32.to_le_bytes());
+        put(0x300 + 24, &1u32.to_le_bytes());
+        put(0x300 + 28, &0x1140u32.to_le_bytes());
+        put(0x300 + 32, &0x1150u32.to_le_bytes());
+        put(0x300 + 36, &0x1158u32.to_le_bytes());
+        put(0x340, &0x2000u32.to_le_bytes());
+        put(0x344, &0x1160u32.to_le_bytes());
+        put(0x350, &0x1170u32.to_le_bytes());
+        put(0x358, &0u16.to_le_bytes());
+        put(0x360, b"NTDLL.RtlExit\0");
+        put(0x370, b"Shutdown\0");
+        data
+    }
+
+    #[test]
+    fn parses_pe_headers() {
+        let data = pe64();
+        let pe = PeHeader::parse(reader(&data)).unwrap();
+        assert!(pe.is_pe32_plus);
+        assert_eq!(pe.machine, 0x8664);
+        assert_eq!(pe.image_base, 0x180000000);
+        assert_eq!(pe.entry_point, 0x1010);
+        assert_eq!(pe.data_directories.len(), 16);
+        assert_eq!(pe.section(".rdata").unwrap().raw_offset, 0x200);
+        assert_eq!(pe.rva_to_offset(0x1080), Some(0x280));
+        assert_eq!(pe.rva_to_offset(0x40), Some(0x40));
+        assert_eq!(pe.rva_to_offset(0x3000), None);
+
+        assert_eq!(pe.imports.len(), 1);
+        assert_eq!(pe.imports[0].library, "KERNEL32.dll");
+        assert_eq!(pe.imports[0].iat_rva, 0x1060);
+        assert_eq!(
+            pe.imports[0].functions,
+            [
+                PeImport::Name {
+                    hint: 7,
+                    name: "ExitProcess".into()
+                },
+                PeImport::Ordinal(17)
+            ]
+        );
+
+        assert_eq!(
+            pe.exports,
+            [
+                PeExport {
+                    name: Some("Shutdown".into()),
+                    ordinal: 1,
+                    rva: 0x2000,
+                    forwarder: None,
+                },
+                PeExport {
+                    name: None,
+                    ordinal: 2,
+                    rva: 0x1160,
+                    forwarder: Some("NTDLL.RtlExit".into()),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(PeHeader::parse(reader(b"\x7fELF\x02\x01")).is_none());
+        assert!(PeHeader::parse(reader(b"MZ")).is_none());
+    }
+}
@@ -20,13 +20,18 @@ use std::io;
 use std::path::{Path, PathBuf};
 use thiserror::Error;
 
+use crate::binary_view::{
+    register_binary_view_event, BinaryView, BinaryViewEventHandler, BinaryViewEventType,
+};
 use crate::enterprise::release_license;
 use crate::main_thread::{MainThreadAction, MainThreadHandler};
 use crate::progress::ProgressCallback;
 use crate::rc::Ref;
+use crate::settings::{QueryOptions, Settings, SettingsScope};
+use crate::worker_thread::set_worker_thread_count;
 use binaryninjacore_sys::{BNInitPlugins, BNInitRepoPlugins};
 use std::sync::mpsc::Sender;
-use std::sync::Mutex;
+use std::sync::{Mutex, Once};
 use std::thread::JoinHandle;
 use std::time::Duration;
 
@@ -91,6 +96,8 @@ pub struct InitializationOptions {
     pub floating_license_duration: Duration,
     /// The bundled plugin directory to use.
     pub bundled_plugin_directory: PathBuf,
+    /// Whether analysis should run the same way every time, see [`InitializationOptions::with_deterministic_analysis`].
+    pub deterministic_analysis: bool,
 }
 
 impl InitializationOptions {
@@ -130,6 +137,17 @@ impl InitializationOptions {
         self.floating_license_duration = duration;
         self
     }
+
+    /// Make repeated runs over the same binary produce the same analysis, at the cost of speed.
+    ///
+    /// Functions are analyzed one at a time by a single worker thread, so they are processed and
+    /// named in the same order every run, and analysis is not cut short by time limits, which
+    /// depend on the load of the machine. The time limits are only turned off for the views opened
+    /// in this session, the settings of the user are left unchanged.
+    pub fn with_deterministic_analysis(mut self, deterministic: bool) -> Self {
+        self.deterministic_analysis = deterministic;
+        self
+    }
 }
 
 impl Default for InitializationOptions {
@@ -141,6 +159,7 @@ impl Default for InitializationOptions {
             floating_license_duration: Duration::from_secs(900),
             bundled_plugin_directory: bundled_plugin_directory()
                 .expect("Failed to get bundled plugin directory"),
+            deterministic_analysis: false,
         }
     }
 }
@@ -192,6 +211,10 @@ pub fn init_with_opts(options: InitializationOptions) -> Result<(), Initializati
         BNInitRepoPlugins();
     }
 
+    if options.deterministic_analysis {
+        enable_deterministic_analysis();
+    }
+
     if !is_license_validated() {
         // Unfortunately you must have a valid license to use Binary Ninja.
         Err(InitializationError::InvalidLicense)
@@ -200,6 +223,42 @@ pub fn init_with_opts(options: InitializationOptions) -> Result<(), Initializati
     }
 }
 
+/// Settings bounding analysis by wall clock time, which makes its result depend on the machine.
+const ANALYSIS_TIME_LIMIT_SETTINGS: &[&str] = &[
+    "analysis.limits.maxFunctionAnalysisTime",
+    "analysis.limits.maxAnalysisTime",
+];
+
+/// Turns off the analysis time limits of every view in its resource scope, so the settings of the
+/// user are left alone.
+struct DeterministicAnalysisHandler;
+
+impl BinaryViewEventHandler for DeterministicAnalysisHandler {
+    fn on_event(&self, view: &BinaryView) {
+        let settings = Settings::new();
+        let options =
+            QueryOptions::new_with_view(view).with_scope(SettingsScope::SettingsResourceScope);
+        for key in ANALYSIS_TIME_LIMIT_SETTINGS {
+            if settings.contains(*key) {
+                // Zero disables the limit
+                settings.set_integer_with_opts(*key, 0, &options);
+            }
+        }
+    }
+}
+
+fn enable_deterministic_analysis() {
+    static REGISTER_HANDLER: Once = Once::new();
+    set_worker_thread_count(1);
+    // Views are finalized before their initial analysis starts
+    REGISTER_HANDLER.call_once(|| {
+        register_binary_view_event(
+            BinaryViewEventType::BinaryViewFinalizationEvent,
+            DeterministicAnalysisHandler,
+        )
+    });
+}
+
 #[derive(Debug)]
 pub struct HeadlessMainThreadSender {
     sender: Sender<Ref<MainThreadAction>>,
@@ -0,0 +1,289 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Address-keyed summaries of a view, for rendering byte map overviews.
+
+use std::io::{self, Write};
+use std::ops::Range;
+
+use binaryninjacore_sys::*;
+
+use crate::analysis_quality::merge_ranges;
+use crate::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
+
+/// The items within one bin of a [`HeatMap`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeatMapBin {
+    pub start: u64,
+    pub end: u64,
+    pub function_starts: usize,
+    pub data_variables: usize,
+    /// Both data tags and tags within functions.
+    pub tags: usize,
+    /// Both comments outside and within functions.
+    pub comments: usize,
+    /// Bytes covered by the basic blocks of functions.
+    pub code_bytes: u64,
+    /// Bytes covered by data variables.
+    pub data_bytes: u64,
+}
+
+impl HeatMapBin {
+    pub fn len(&self) -> u64 {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// The ratio, between 0 and 1, of the bytes of the bin covered by code.
+    pub fn code_coverage(&self) -> f64 {
+        self.code_bytes as f64 / self.len().max(1) as f64
+    }
+
+    /// The ratio, between 0 and 1, of the bytes of the bin covered by data variables.
+    pub fn data_coverage(&self) -> f64 {
+        self.data_bytes as f64 / self.len().max(1) as f64
+    }
+}
+
+/// A summary of a range of addresses, split into bins of equal size.
+///
+/// Each bin counts the function starts, data variables, tags and comments in it, along with the
+/// bytes covered by code and data. Functions, data variables, comments and tags of the view are
+/// each listed by one query, and the basic blocks and comments of each function by one query per
+/// function, rather than querying every address:
+///
+/// ```no_run
+/// use binaryninja::binary_view::BinaryViewExt;
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// let heat_map = view.heat_map(64);
+/// for bin in heat_map.bins() {
+///     println!(
+///         "{:#x}: {:>3.0}% code, {} functions",
+///         bin.start,
+///         bin.code_coverage() * 100.0,
+///         bin.function_starts
+///     );
+/// }
+/// heat_map.write_csv(std::io::stdout()).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeatMap {
+    range: Range<u64>,
+    bin_size: u64,
+    bins: Vec<HeatMapBin>,
+}
+
+impl HeatMap {
+    /// Summarize all of `view` in at most `bin_count` bins.
+    pub fn new(view: &BinaryView, bin_count: usize) -> Self {
+        let range = view.start()..view.start() + view.len();
+        Self::for_range(view, range, bin_count)
+    }
+
+    /// Summarize the addresses in `range` of `view` in at most `bin_count` bins.
+    pub fn for_range(view: &BinaryView, range: Range<u64>, bin_count: usize) -> Self {
+        let mut heat_map = Self::empty(range, bin_count);
+
+        let mut code = Vec::new();
+        for function in view.functions().iter() {
+            if let Some(bin) = heat_map.bin_mut(function.start()) {
+                bin.function_starts += 1;
+            }
+            for comment in function.comments().iter() {
+                if let Some(bin) = heat_map.bin_mut(comment.addr) {
+                    bin.comments += 1;
+                }
+            }
+            code.extend(
+                function
+                    .basic_blocks()
+                    .iter()
+                    .map(|block| block.start_index()..block.end_index()),
+            );
+        }
+        heat_map.add_ranges(&code, |bin, bytes| bin.code_bytes += bytes);
+
+        let mut data = Vec::new();
+        for data_variable in view.data_variables().iter() {
+            if let Some(bin) = heat_map.bin_mut(data_variable.address) {
+                bin.data_variables += 1;
+            }
+            let width = data_variable.ty.contents.width().max(1);
+            data.push(data_variable.address..data_variable.address.saturating_add(width));
+        }
+        heat_map.add_ranges(&data, |bin, bytes| bin.data_bytes += bytes);
+
+        for addr in view.commented_addresses() {
+            if let Some(bin) = heat_map.bin_mut(addr) {
+                bin.comments += 1;
+            }
+        }
+
+        // Only the addresses are needed, so don't wrap every reference in a `TagReference`
+        unsafe {
+            let mut count = 0;
+            let refs = BNGetAllTagReferences(view.handle, &mut count);
+            if !refs.is_null() {
+                for tag_ref in std::slice::from_raw_parts(refs, count) {
+                    if let Some(bin) = heat_map.bin_mut(tag_ref.addr) {
+                        bin.tags += 1;
+                    }
+                }
+                BNFreeTagReferences(refs, count);
+            }
+        }
+
+        heat_map
+    }
+
+    fn empty(range: Range<u64>, bin_count: usize) -> Self {
+        let len = range.end.saturating_sub(range.start);
+        let bin_size = len.div_ceil(bin_count.max(1) as u64).max(1);
+        let bins = (range.start..range.end)
+            .step_by(bin_size as usize)
+            .map(|start| HeatMapBin {
+                start,
+                end: start.saturating_add(bin_size).min(range.end),
+                ..Default::default()
+            })
+            .collect();
+        Self {
+            range,
+            bin_size,
+            bins,
+        }
+    }
+
+    fn bin_index(&self, addr: u64) -> Option<usize> {
+        if !self.range.contains(&addr) {
+            return None;
+        }
+        Some(((addr - self.range.start) / self.bin_size) as usize)
+    }
+
+    fn bin_mut(&mut self, addr: u64) -> Option<&mut HeatMapBin> {
+        let index = self.bin_index(addr)?;
+        self.bins.get_mut(index)
+    }
+
+    /// Add the bytes of the union of `ranges` that fall in each bin.
+    fn add_ranges(&mut self, ranges: &[Range<u64>], add: impl Fn(&mut HeatMapBin, u64)) {
+        for range in merge_ranges(ranges) {
+            let start = range.start.max(self.range.start);
+            let end = range.end.min(self.range.end);
+            if start >= end {
+                continue;
+            }
+            let first = self.bin_index(start).unwrap_or(0);
+            let last = self.bin_index(end - 1).unwrap_or(first);
+            for bin in &mut self.bins[first..=last] {
+                let overlap = end.min(bin.end).saturating_sub(start.max(bin.start));
+                add(bin, overlap);
+            }
+        }
+    }
+
+    /// The range of addresses summarized.
+    pub fn range(&self) -> Range<u64> {
+        self.range.clone()
+    }
+
+    /// The number of addresses in every bin but possibly the last.
+    pub fn bin_size(&self) -> u64 {
+        self.bin_size
+    }
+
+    pub fn bins(&self) -> &[HeatMapBin] {
+        &self.bins
+    }
+
+    /// The bin containing `addr`, if it is within [`HeatMap::range`].
+    pub fn bin_for(&self, addr: u64) -> Option<&HeatMapBin> {
+        self.bins.get(self.bin_index(addr)?)
+    }
+
+    /// Write the bins as CSV, one row per bin, preceded by a header row.
+    pub fn write_csv<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writeln!(
+            writer,
+            "start,end,function_starts,data_variables,tags,comments,code_bytes,data_bytes"
+        )?;
+        for bin in &self.bins {
+            writeln!(
+                writer,
+                "{:#x},{:#x},{},{},{},{},{},{}",
+                bin.start,
+                bin.end,
+                bin.function_starts,
+                bin.data_variables,
+                bin.tags,
+                bin.comments,
+                bin.code_bytes,
+                bin.data_bytes
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn splits_range_into_bins() {
+        let heat_map = HeatMap::empty(0x1000..0x1010, 3);
+        assert_eq!(heat_map.bin_size(), 6);
+        let bounds: Vec<_> = heat_map.bins().iter().map(|b| (b.start, b.end)).collect();
+        assert_eq!(
+            bounds,
+            vec![(0x1000, 0x1006), (0x1006, 0x100c), (0x100c, 0x1010)]
+        );
+        assert_eq!(heat_map.bin_for(0x100b).unwrap().start, 0x1006);
+        assert!(heat_map.bin_for(0x1010).is_none());
+        assert!(HeatMap::empty(0x1000..0x1000, 4).bins().is_empty());
+    }
+
+    #[test]
+    fn distributes_range_bytes_across_bins() {
+        let mut heat_map = HeatMap::empty(0x1000..0x1010, 4);
+        // Overlapping ranges are only counted once, bytes outside the range not at all
+        heat_map.add_ranges(
+            &[0x0ff0..0x1002, 0x1001..0x1009, 0x100f..0x1020],
+            |bin, bytes| bin.code_bytes += bytes,
+        );
+        let code: Vec<_> = heat_map.bins().iter().map(|b| b.code_bytes).collect();
+        assert_eq!(code, vec![4, 4, 1, 1]);
+        assert_eq!(heat_map.bins()[0].code_coverage(), 1.0);
+        assert_eq!(heat_map.bins()[2].code_coverage(), 0.25);
+    }
+
+    #[test]
+    fn writes_csv() {
+        let mut heat_map = HeatMap::empty(0..0x20, 2);
+        heat_map.bin_mut(0x18).unwrap().function_starts += 1;
+        let mut csv = Vec::new();
+        heat_map.write_csv(&mut csv).unwrap();
+        assert_eq!(
+            String::from_utf8(csv).unwrap(),
+            "start,end,function_starts,data_variables,tags,comments,code_bytes,data_bytes\n\
+             0x0,0x10,0,0,0,0,0,0\n\
+             0x10,0x20,1,0,0,0,0,0\n"
+        );
+    }
+}
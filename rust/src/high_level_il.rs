@@ -9,3 +9,4 @@ pub use self::block::*;
 pub use self::function::*;
 pub use self::instruction::*;
 pub use self::lift::*;
+pub use crate::low_level_il::VisitorAction;
@@ -5,6 +5,8 @@ use binaryninjacore_sys::*;
 
 use super::{HighLevelILBlock, HighLevelILInstruction, HighLevelInstructionIndex};
 use crate::basic_block::BasicBlock;
+use crate::disassembly::DisassemblySettings;
+use crate::flowgraph::FlowGraph;
 use crate::function::{Function, Location};
 use crate::rc::{Array, Ref, RefCountable};
 use crate::variable::{SSAVariable, Variable};
@@ -65,10 +67,35 @@ impl HighLevelILFunction {
         unsafe { BNGetHighLevelILInstructionCount(self.handle) }
     }
 
+    /// All instructions of the function in index order, each with the address it was lifted from.
+    pub fn instructions(&self) -> impl Iterator<Item = HighLevelILInstruction> + '_ {
+        (0..self.instruction_count())
+            .filter_map(|index| self.instruction_from_index(HighLevelInstructionIndex(index)))
+    }
+
     pub fn expression_count(&self) -> usize {
         unsafe { BNGetHighLevelILExprCount(self.handle) }
     }
 
+    /// Replace the expression at `expr_index` with the one at `replacement`, for instance with one
+    /// of its own sub expressions to rewrite it.
+    ///
+    /// # Safety
+    ///
+    /// The replacement must be valid in place of the replaced expression, the function must be
+    /// regenerated before the change takes effect.
+    pub unsafe fn replace_expression(
+        &self,
+        expr_index: HighLevelInstructionIndex,
+        replacement: HighLevelInstructionIndex,
+    ) -> bool {
+        if expr_index.0 >= self.expression_count() || replacement.0 >= self.expression_count() {
+            return false;
+        }
+        BNReplaceHighLevelILExpr(self.handle, expr_index.0, replacement.0);
+        true
+    }
+
     pub fn ssa_form(&self) -> HighLevelILFunction {
         let ssa = unsafe { BNGetHighLevelILSSAForm(self.handle) };
         assert!(!ssa.is_null());
@@ -94,6 +121,12 @@ impl HighLevelILFunction {
         unsafe { Array::new(blocks, count, context) }
     }
 
+    pub fn create_graph(&self, settings: Option<&DisassemblySettings>) -> Ref<FlowGraph> {
+        let settings = settings.map_or(std::ptr::null_mut(), |settings| settings.handle);
+        let graph = unsafe { BNCreateHighLevelILFunctionGraph(self.handle, settings) };
+        unsafe { Ref::new(FlowGraph::from_raw(graph)) }
+    }
+
     pub fn as_ast(&self) -> Ref<HighLevelILFunction> {
         Self {
             handle: self.handle,
@@ -0,0 +1,314 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Warnings, skipped items and counts collected while importing debug information.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::binary_view::{BinaryView, BinaryViewExt};
+use crate::metadata::Metadata;
+use crate::rc::{Array, Ref};
+
+/// View metadata key of the map from importer name to the diagnostics of its last import.
+pub const IMPORT_DIAGNOSTICS_METADATA_KEY: &str = "import_diagnostics";
+
+/// An item an importer found but did not import.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SkippedItem {
+    /// What was skipped, e.g. `"function"` or `"type"`.
+    pub kind: String,
+    /// Where the item would have been imported, if it has an address.
+    pub address: Option<u64>,
+    pub reason: String,
+}
+
+impl fmt::Display for SkippedItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.address {
+            Some(address) => write!(f, "{} at {:#x}: {}", self.kind, address, self.reason),
+            None => write!(f, "{}: {}", self.kind, self.reason),
+        }
+    }
+}
+
+/// The outcome of one run of an importer.
+///
+/// Importers collect diagnostics while they parse, and [`store`](ImportDiagnostics::store) them on
+/// the view they imported into once they are done. The diagnostics of the last import of every
+/// importer can then be listed for display:
+///
+/// ```no_run
+/// use binaryninja::import_diagnostics::ImportDiagnostics;
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// for diagnostics in ImportDiagnostics::for_view(&view) {
+///     println!("{}", diagnostics);
+///     for skipped in diagnostics.skipped() {
+///         println!("  skipped {}", skipped);
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImportDiagnostics {
+    importer: String,
+    warnings: Vec<String>,
+    skipped: Vec<SkippedItem>,
+    counts: BTreeMap<String, u64>,
+}
+
+impl ImportDiagnostics {
+    /// Empty diagnostics for `importer`, usually the name its parser is registered under.
+    pub fn new(importer: impl Into<String>) -> Self {
+        Self {
+            importer: importer.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn importer(&self) -> &str {
+        &self.importer
+    }
+
+    /// Record a warning, and log it.
+    pub fn warn(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        log::warn!("{}", message);
+        self.warnings.push(message);
+    }
+
+    /// Record that an item of `kind` was not imported, and why.
+    pub fn skip(
+        &mut self,
+        kind: impl Into<String>,
+        address: Option<u64>,
+        reason: impl Into<String>,
+    ) {
+        let item = SkippedItem {
+            kind: kind.into(),
+            address,
+            reason: reason.into(),
+        };
+        log::debug!("Skipped {}", item);
+        self.skipped.push(item);
+    }
+
+    /// Count one imported item of `kind`.
+    pub fn count(&mut self, kind: &str) {
+        self.add_count(kind, 1);
+    }
+
+    /// Count `count` imported items of `kind`.
+    pub fn add_count(&mut self, kind: &str, count: u64) {
+        *self.counts.entry(kind.to_string()).or_default() += count;
+    }
+
+    /// Add the warnings, skipped items and counts of `other`, such as those of a later run of the
+    /// importer adding to what an earlier run imported.
+    pub fn merge(&mut self, other: &ImportDiagnostics) {
+        self.warnings.extend_from_slice(&other.warnings);
+        self.skipped.extend_from_slice(&other.skipped);
+        for (kind, &count) in &other.counts {
+            self.add_count(kind, count);
+        }
+    }
+
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    pub fn skipped(&self) -> &[SkippedItem] {
+        &self.skipped
+    }
+
+    /// The number of imported items, by kind.
+    pub fn counts(&self) -> &BTreeMap<String, u64> {
+        &self.counts
+    }
+
+    /// The number of imported items of `kind`.
+    pub fn count_of(&self, kind: &str) -> u64 {
+        self.counts.get(kind).copied().unwrap_or(0)
+    }
+
+    /// Whether nothing was imported, skipped or warned about.
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty() && self.skipped.is_empty() && self.counts.is_empty()
+    }
+
+    /// Store these diagnostics on `view`, replacing those of the last import of the same importer.
+    pub fn store(&self, view: &BinaryView) {
+        let mut all = view
+            .query_metadata(IMPORT_DIAGNOSTICS_METADATA_KEY)
+            .and_then(|metadata| HashMap::<String, Ref<Metadata>>::try_from(&*metadata).ok())
+            .unwrap_or_default();
+        all.insert(self.importer.clone(), self.to_metadata());
+        view.store_metadata(IMPORT_DIAGNOSTICS_METADATA_KEY, all, true);
+    }
+
+    /// The diagnostics of the last import of every importer into `view`, by importer name.
+    pub fn for_view(view: &BinaryView) -> Vec<ImportDiagnostics> {
+        let Some(metadata) = view.query_metadata(IMPORT_DIAGNOSTICS_METADATA_KEY) else {
+            return vec![];
+        };
+        let mut all: Vec<_> = HashMap::<String, Ref<Metadata>>::try_from(&*metadata)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(importer, metadata)| Self::from_metadata(importer, &metadata))
+            .collect();
+        all.sort_by(|a, b| a.importer.cmp(&b.importer));
+        all
+    }
+
+    /// The diagnostics of the last import of `importer` into `view`.
+    pub fn for_importer(view: &BinaryView, importer: &str) -> Option<ImportDiagnostics> {
+        let metadata = view.query_metadata(IMPORT_DIAGNOSTICS_METADATA_KEY)?;
+        let metadata = metadata.get(importer).ok()??;
+        Some(Self::from_metadata(importer.to_string(), &metadata))
+    }
+
+    fn to_metadata(&self) -> Ref<Metadata> {
+        let skipped: Vec<Ref<Metadata>> = self
+            .skipped
+            .iter()
+            .map(|item| {
+                let mut fields = HashMap::<&str, Ref<Metadata>>::new();
+                fields.insert("kind", item.kind.as_str().into());
+                fields.insert("reason", item.reason.as_str().into());
+                if let Some(address) = item.address {
+                    fields.insert("address", address.into());
+                }
+                fields.into()
+            })
+            .collect();
+        let counts: HashMap<&str, Ref<Metadata>> = self
+            .counts
+            .iter()
+            .map(|(kind, &count)| (kind.as_str(), count.into()))
+            .collect();
+
+        let mut fields = HashMap::<&str, Ref<Metadata>>::new();
+        fields.insert("warnings", self.warnings.clone().into());
+        fields.insert("skipped", (&skipped).into());
+        fields.insert("counts", counts.into());
+        fields.into()
+    }
+
+    fn from_metadata(importer: String, metadata: &Metadata) -> Self {
+        let fields = HashMap::<String, Ref<Metadata>>::try_from(metadata).unwrap_or_default();
+        let field = |name: &str| fields.get(name);
+
+        let warnings = field("warnings")
+            .and_then(|warnings| Vec::<String>::try_from(&**warnings).ok())
+            .unwrap_or_default();
+        let skipped = field("skipped")
+            .and_then(|skipped| Array::<Metadata>::try_from(&**skipped).ok())
+            .map(|skipped| {
+                skipped
+                    .iter()
+                    .filter_map(|item| {
+                        let item = HashMap::<String, Ref<Metadata>>::try_from(&*item).ok()?;
+                        let string = |name: &str| String::try_from(&**item.get(name)?).ok();
+                        Some(SkippedItem {
+                            kind: string("kind")?,
+                            address: item
+                                .get("address")
+                                .and_then(|address| address.get_unsigned_integer().ok()),
+                            reason: string("reason").unwrap_or_default(),
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let counts = field("counts")
+            .and_then(|counts| HashMap::<String, Ref<Metadata>>::try_from(&**counts).ok())
+            .map(|counts| {
+                counts
+                    .into_iter()
+                    .filter_map(|(kind, count)| Some((kind, count.get_unsigned_integer().ok()?)))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Self {
+            importer,
+            warnings,
+            skipped,
+            counts,
+        }
+    }
+}
+
+impl fmt::Display for ImportDiagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: ", self.importer)?;
+        if self.counts.is_empty() {
+            write!(f, "nothing imported")?;
+        } else {
+            let counts: Vec<_> = self
+                .counts
+                .iter()
+                .map(|(kind, count)| format!("{} {}", count, kind))
+                .collect();
+            write!(f, "{}", counts.join(", "))?;
+        }
+        write!(
+            f,
+            "; {} skipped, {} warnings",
+            self.skipped.len(),
+            self.warnings.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn summarizes_diagnostics() {
+        let mut diagnostics = ImportDiagnostics::new("DWARF");
+        assert!(diagnostics.is_empty());
+        assert_eq!(
+            diagnostics.to_string(),
+            "DWARF: nothing imported; 0 skipped, 0 warnings"
+        );
+
+        diagnostics.count("types");
+        diagnostics.add_count("functions", 2);
+        diagnostics.count("functions");
+        diagnostics.skip("local variable", Some(0x1000), "positive stack offset");
+        diagnostics.warn("duplicate type `foo`");
+        assert!(!diagnostics.is_empty());
+        assert_eq!(diagnostics.count_of("functions"), 3);
+        assert_eq!(diagnostics.count_of("data variables"), 0);
+        assert_eq!(
+            diagnostics.to_string(),
+            "DWARF: 3 functions, 1 types; 1 skipped, 1 warnings"
+        );
+        assert_eq!(
+            diagnostics.skipped()[0].to_string(),
+            "local variable at 0x1000: positive stack offset"
+        );
+
+        let mut later = ImportDiagnostics::new("DWARF");
+        later.count("functions");
+        later.warn("duplicate type `bar`");
+        diagnostics.merge(&later);
+        assert_eq!(
+            diagnostics.to_string(),
+            "DWARF: 4 functions, 1 types; 1 skipped, 2 warnings"
+        );
+    }
+}
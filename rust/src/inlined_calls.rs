@@ -0,0 +1,267 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The calls the compiler inlined, as recorded by debug information importers.
+
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::binary_view::{BinaryView, BinaryViewExt};
+use crate::metadata::Metadata;
+use crate::rc::{Array, Ref};
+
+/// View metadata key of the map from importer name to the inlined calls of its last import.
+pub const INLINED_CALLS_METADATA_KEY: &str = "inlined_calls";
+
+/// Name of the tag type [`InlinedCallTable::annotate`] tags inlined calls with.
+pub const INLINED_CALL_TAG_TYPE: &str = "Inlined Call";
+
+/// A call the compiler replaced by the body of the callee.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InlinedCall {
+    pub callee: String,
+    /// The address ranges holding the inlined body.
+    pub ranges: Vec<Range<u64>>,
+    /// Number of inlined calls this one is nested in, zero when inlined directly into a function.
+    pub depth: usize,
+    /// Line of the call in the source of the caller.
+    pub call_line: Option<u64>,
+}
+
+impl InlinedCall {
+    pub fn contains(&self, addr: u64) -> bool {
+        self.ranges.iter().any(|range| range.contains(&addr))
+    }
+
+    /// The lowest address of the inlined body.
+    pub fn start(&self) -> Option<u64> {
+        self.ranges.iter().map(|range| range.start).min()
+    }
+
+    fn to_metadata(&self) -> Ref<Metadata> {
+        let ranges: Vec<u64> = self
+            .ranges
+            .iter()
+            .flat_map(|range| [range.start, range.end])
+            .collect();
+        let mut fields = HashMap::<&str, Ref<Metadata>>::new();
+        fields.insert("callee", self.callee.as_str().into());
+        fields.insert("ranges", (&ranges).into());
+        fields.insert("depth", (self.depth as u64).into());
+        if let Some(line) = self.call_line {
+            fields.insert("call_line", line.into());
+        }
+        fields.into()
+    }
+
+    fn from_metadata(metadata: &Metadata) -> Option<Self> {
+        let fields = HashMap::<String, Ref<Metadata>>::try_from(metadata).ok()?;
+        let ranges = Vec::<u64>::try_from(&**fields.get("ranges")?).ok()?;
+        Some(Self {
+            callee: String::try_from(&**fields.get("callee")?).ok()?,
+            ranges: ranges
+                .chunks_exact(2)
+                .map(|bounds| bounds[0]..bounds[1])
+                .collect(),
+            depth: fields.get("depth")?.get_unsigned_integer().ok()? as usize,
+            call_line: fields
+                .get("call_line")
+                .and_then(|line| line.get_unsigned_integer().ok()),
+        })
+    }
+}
+
+/// The inlined calls found by one run of an importer.
+///
+/// [`DebugInfo`](crate::debuginfo::DebugInfo) has no place for inlined calls, so importers collect
+/// a table while they parse and [`store`](InlinedCallTable::store) it on the view they imported
+/// into. The inlined callees at an address can then be looked up:
+///
+/// ```no_run
+/// use binaryninja::binary_view::BinaryViewBase;
+/// use binaryninja::inlined_calls::InlinedCallTable;
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// for table in InlinedCallTable::for_view(&view) {
+///     for call in table.calls_at(view.entry_point()) {
+///         println!("{}: inlined `{}`", table.importer(), call.callee);
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct InlinedCallTable {
+    importer: String,
+    calls: Vec<InlinedCall>,
+}
+
+impl InlinedCallTable {
+    /// An empty table for `importer`, usually the name its parser is registered under.
+    pub fn new(importer: impl Into<String>) -> Self {
+        Self {
+            importer: importer.into(),
+            calls: vec![],
+        }
+    }
+
+    pub fn importer(&self) -> &str {
+        &self.importer
+    }
+
+    pub fn add(&mut self, call: InlinedCall) {
+        self.calls.push(call);
+    }
+
+    pub fn calls(&self) -> &[InlinedCall] {
+        &self.calls
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.calls.is_empty()
+    }
+
+    /// Move every call by `delta` bytes, such as when the view is loaded at a different base than
+    /// the debug information assumes.
+    pub fn rebase(&mut self, delta: u64) {
+        for call in &mut self.calls {
+            for range in &mut call.ranges {
+                *range = range.start.wrapping_add(delta)..range.end.wrapping_add(delta);
+            }
+        }
+    }
+
+    /// The calls inlined at `addr`, outermost first.
+    pub fn calls_at(&self, addr: u64) -> Vec<&InlinedCall> {
+        let mut calls: Vec<_> = self
+            .calls
+            .iter()
+            .filter(|call| call.contains(addr))
+            .collect();
+        calls.sort_by_key(|call| call.depth);
+        calls
+    }
+
+    /// Store the table on `view`, replacing the one of the last import of the same importer.
+    pub fn store(&self, view: &BinaryView) {
+        let mut all = view
+            .query_metadata(INLINED_CALLS_METADATA_KEY)
+            .and_then(|metadata| HashMap::<String, Ref<Metadata>>::try_from(&*metadata).ok())
+            .unwrap_or_default();
+        let calls: Vec<Ref<Metadata>> = self.calls.iter().map(InlinedCall::to_metadata).collect();
+        all.insert(self.importer.clone(), (&calls).into());
+        view.store_metadata(INLINED_CALLS_METADATA_KEY, all, true);
+    }
+
+    /// The inlined calls of the last import of every importer into `view`, by importer name.
+    pub fn for_view(view: &BinaryView) -> Vec<InlinedCallTable> {
+        let Some(metadata) = view.query_metadata(INLINED_CALLS_METADATA_KEY) else {
+            return vec![];
+        };
+        let mut all: Vec<_> = HashMap::<String, Ref<Metadata>>::try_from(&*metadata)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(importer, metadata)| Self::from_metadata(importer, &metadata))
+            .collect();
+        all.sort_by(|a, b| a.importer.cmp(&b.importer));
+        all
+    }
+
+    /// The inlined calls of the last import of `importer` into `view`.
+    pub fn for_importer(view: &BinaryView, importer: &str) -> Option<InlinedCallTable> {
+        let metadata = view.query_metadata(INLINED_CALLS_METADATA_KEY)?;
+        let metadata = metadata.get(importer).ok()??;
+        Some(Self::from_metadata(importer.to_string(), &metadata))
+    }
+
+    /// Tag the start of every inlined call in the functions of `view` containing it with the
+    /// callee, under the [`INLINED_CALL_TAG_TYPE`] tag type.
+    ///
+    /// Calls that are already tagged are skipped, so annotating again after another import only
+    /// tags the calls that are new. Returns the number of tags added.
+    pub fn annotate(&self, view: &BinaryView) -> usize {
+        let tag_type = view
+            .tag_type_by_name(INLINED_CALL_TAG_TYPE)
+            .unwrap_or_else(|| view.create_tag_type(INLINED_CALL_TAG_TYPE, "⤵"));
+        let mut added = 0;
+        for call in &self.calls {
+            let Some(start) = call.start() else {
+                continue;
+            };
+            let data = match call.call_line {
+                Some(line) => format!("inlined `{}`, called at line {}", call.callee, line),
+                None => format!("inlined `{}`", call.callee),
+            };
+            for func in view.functions_containing(start).iter() {
+                let tagged = func.tags_at(start, Some(true), None).iter().any(|tag| {
+                    tag.ty().id() == tag_type.id() && tag.data().as_str() == data.as_str()
+                });
+                if !tagged {
+                    func.add_tag(&tag_type, data.as_str(), Some(start), false, None);
+                    added += 1;
+                }
+            }
+        }
+        added
+    }
+
+    fn from_metadata(importer: String, metadata: &Metadata) -> Self {
+        let calls = Array::<Metadata>::try_from(metadata)
+            .map(|calls| {
+                calls
+                    .iter()
+                    .filter_map(|call| InlinedCall::from_metadata(&call))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { importer, calls }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn call(callee: &str, ranges: &[(u64, u64)], depth: usize) -> InlinedCall {
+        InlinedCall {
+            callee: callee.to_string(),
+            ranges: ranges.iter().map(|&(start, end)| start..end).collect(),
+            depth,
+            call_line: None,
+        }
+    }
+
+    fn callees(table: &InlinedCallTable, addr: u64) -> Vec<&str> {
+        table
+            .calls_at(addr)
+            .into_iter()
+            .map(|call| call.callee.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn finds_nested_calls() {
+        let mut table = InlinedCallTable::new("DWARF");
+        table.add(call("inner", &[(0x1010, 0x1020)], 1));
+        table.add(call("outer", &[(0x1000, 0x1030), (0x2000, 0x2010)], 0));
+        table.add(call("other", &[(0x3000, 0x3010)], 0));
+
+        assert_eq!(callees(&table, 0x1018), ["outer", "inner"]);
+        assert_eq!(callees(&table, 0x2000), ["outer"]);
+        assert!(callees(&table, 0x1030).is_empty());
+        assert_eq!(table.calls()[1].start(), Some(0x1000));
+
+        table.rebase(0x10000);
+        assert_eq!(callees(&table, 0x11018), ["outer", "inner"]);
+        assert!(callees(&table, 0x1018).is_empty());
+    }
+}
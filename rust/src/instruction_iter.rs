@@ -0,0 +1,156 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Linear decoding of the instructions in a range of a view.
+
+use std::ops::Range;
+
+use crate::architecture::{Architecture, CoreArchitecture};
+use crate::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
+use crate::disassembly::InstructionTextToken;
+use crate::rc::Ref;
+
+/// An instruction decoded by [`InstructionIter`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedInstruction {
+    pub address: u64,
+    /// Length of the instruction in bytes.
+    pub length: usize,
+    /// The architecture the instruction was decoded with.
+    pub arch: CoreArchitecture,
+    pub tokens: Vec<InstructionTextToken>,
+}
+
+impl DecodedInstruction {
+    pub fn address_range(&self) -> Range<u64> {
+        self.address..self.address + self.length as u64
+    }
+
+    /// The disassembly of the instruction.
+    pub fn text(&self) -> String {
+        self.tokens
+            .iter()
+            .map(|token| token.text.as_str())
+            .collect()
+    }
+}
+
+/// The instructions starting in a range of a view, see [`BinaryViewExt::instructions_in`].
+///
+/// The architecture of an address is the one of the function containing it. Addresses outside of
+/// functions keep the architecture of the instruction before them, or the default architecture
+/// of the view at the start of the range, so code mixing architectures (such as ARM and Thumb)
+/// decodes correctly. Data variables and non-executable regions are stepped over, and bytes that
+/// don't decode are skipped one instruction alignment at a time.
+///
+/// ```no_run
+/// use binaryninja::binary_view::BinaryViewExt;
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// for section in view.sections().iter() {
+///     for instr in view.instructions_in(section.address_range()) {
+///         println!("{:#x} [{}] {}", instr.address, instr.arch.name(), instr.text());
+///     }
+/// }
+/// ```
+///
+/// [`BinaryViewExt::instructions_in`]: crate::binary_view::BinaryViewExt::instructions_in
+pub struct InstructionIter {
+    view: Ref<BinaryView>,
+    address: u64,
+    end: u64,
+    arch: Option<CoreArchitecture>,
+}
+
+impl InstructionIter {
+    pub fn new(view: &BinaryView, range: Range<u64>) -> Self {
+        Self {
+            view: view.to_owned(),
+            address: range.start,
+            end: range.end,
+            arch: view.default_arch(),
+        }
+    }
+
+    /// The end of the data at `address`, `None` if it may hold code.
+    fn data_end(&self, address: u64) -> Option<u64> {
+        if !self.view.offset_executable(address) {
+            let end = match self.view.segment_at(address) {
+                Some(segment) => segment.address_range().end,
+                None => self.view.next_valid_offset_after(address),
+            };
+            return Some(end.max(address.saturating_add(1)));
+        }
+        let var = self.view.data_variable_at_address(address)?;
+        let end = var.address.saturating_add(var.ty.contents.width());
+        Some(end.max(address.saturating_add(1)))
+    }
+
+    fn arch_at(&self, address: u64) -> Option<CoreArchitecture> {
+        self.view
+            .functions_containing(address)
+            .iter()
+            .next()
+            .map(|func| func.arch())
+            .or(self.arch)
+    }
+
+    fn decode(&self, arch: CoreArchitecture, address: u64) -> Option<DecodedInstruction> {
+        let data = self.view.read_vec(address, arch.max_instr_len());
+        let info = arch.instruction_info(&data, address)?;
+        if info.length == 0 || info.length > data.len() {
+            return None;
+        }
+        let (_, tokens) = arch.instruction_text(&data[..info.length], address)?;
+        Some(DecodedInstruction {
+            address,
+            length: info.length,
+            arch,
+            tokens,
+        })
+    }
+}
+
+impl Iterator for InstructionIter {
+    type Item = DecodedInstruction;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.address < self.end {
+            let address = self.address;
+            if let Some(end) = self.data_end(address) {
+                self.address = end;
+                continue;
+            }
+            // Without a default architecture there is nothing to decode code outside of functions
+            let Some(arch) = self.arch_at(address) else {
+                self.address = address.saturating_add(1);
+                continue;
+            };
+            match self.decode(arch, address) {
+                Some(instr) => {
+                    self.address = address.saturating_add(instr.length as u64);
+                    self.arch = Some(arch);
+                    return Some(instr);
+                }
+                None => {
+                    let alignment = arch.instruction_alignment().max(1) as u64;
+                    self.address = address.saturating_add(alignment);
+                }
+            }
+        }
+        None
+    }
+}
+
+impl std::iter::FusedIterator for InstructionIter {}
@@ -14,6 +14,12 @@
 
 //! Interfaces for asking the user for information: forms, opening files, etc.
 
+mod handler;
+mod report;
+
+pub use handler::*;
+pub use report::*;
+
 use binaryninjacore_sys::*;
 
 use std::ffi::{c_char, c_void, CStr};
@@ -78,6 +84,59 @@ pub fn get_address_input(prompt: &str, title: &str) -> Option<u64> {
     Some(value)
 }
 
+/// Prompts the user to pick one of `choices`, returning its index.
+pub fn get_choice_input(prompt: &str, title: &str, choices: &[&str]) -> Option<usize> {
+    let choices: Vec<BnString> = choices.iter().map(|&s| BnString::new(s)).collect();
+    let mut raw_choices: Vec<*const c_char> = choices
+        .iter()
+        .map(|c| c.as_ref().as_ptr() as *const c_char)
+        .collect();
+    let mut value: usize = 0;
+
+    let result = unsafe {
+        BNGetChoiceInput(
+            &mut value,
+            prompt.into_bytes_with_nul().as_ptr() as *mut _,
+            title.into_bytes_with_nul().as_ptr() as *mut _,
+            raw_choices.as_mut_ptr(),
+            raw_choices.len(),
+        )
+    };
+
+    if !result {
+        return None;
+    }
+
+    Some(value)
+}
+
+/// Like [`get_choice_input`], for more choices than fit a drop down. The UI lets the user filter
+/// them.
+pub fn get_large_choice_input(prompt: &str, title: &str, choices: &[&str]) -> Option<usize> {
+    let choices: Vec<BnString> = choices.iter().map(|&s| BnString::new(s)).collect();
+    let mut raw_choices: Vec<*const c_char> = choices
+        .iter()
+        .map(|c| c.as_ref().as_ptr() as *const c_char)
+        .collect();
+    let mut value: usize = 0;
+
+    let result = unsafe {
+        BNGetLargeChoiceInput(
+            &mut value,
+            prompt.into_bytes_with_nul().as_ptr() as *mut _,
+            title.into_bytes_with_nul().as_ptr() as *mut _,
+            raw_choices.as_mut_ptr(),
+            raw_choices.len(),
+        )
+    };
+
+    if !result {
+        return None;
+    }
+
+    Some(value)
+}
+
 pub fn get_open_filename_input(prompt: &str, extension: &str) -> Option<PathBuf> {
     let mut value: *mut c_char = std::ptr::null_mut();
 
@@ -156,6 +215,55 @@ pub fn show_message_box(
     }
 }
 
+pub fn show_plain_text_report(view: Option<&BinaryView>, title: &str, contents: &str) {
+    unsafe {
+        BNShowPlainTextReport(
+            view.map_or(std::ptr::null_mut(), |view| view.handle),
+            title.into_bytes_with_nul().as_ptr() as *const _,
+            contents.into_bytes_with_nul().as_ptr() as *const _,
+        )
+    }
+}
+
+/// Shows a markdown report, or `plain_text` where markdown can't be rendered.
+pub fn show_markdown_report(
+    view: Option<&BinaryView>,
+    title: &str,
+    contents: &str,
+    plain_text: &str,
+) {
+    unsafe {
+        BNShowMarkdownReport(
+            view.map_or(std::ptr::null_mut(), |view| view.handle),
+            title.into_bytes_with_nul().as_ptr() as *const _,
+            contents.into_bytes_with_nul().as_ptr() as *const _,
+            plain_text.into_bytes_with_nul().as_ptr() as *const _,
+        )
+    }
+}
+
+/// Shows an HTML report, or `plain_text` where HTML can't be rendered.
+pub fn show_html_report(view: Option<&BinaryView>, title: &str, contents: &str, plain_text: &str) {
+    unsafe {
+        BNShowHTMLReport(
+            view.map_or(std::ptr::null_mut(), |view| view.handle),
+            title.into_bytes_with_nul().as_ptr() as *const _,
+            contents.into_bytes_with_nul().as_ptr() as *const _,
+            plain_text.into_bytes_with_nul().as_ptr() as *const _,
+        )
+    }
+}
+
+pub fn markdown_to_html(contents: &str) -> String {
+    let html = unsafe { BNMarkdownToHTML(contents.into_bytes_with_nul().as_ptr() as *const _) };
+    unsafe { BnString::from_raw(html) }.to_string()
+}
+
+/// Opens `url` in the browser of the user, returning whether it was opened.
+pub fn open_url(url: &str) -> bool {
+    unsafe { BNOpenUrl(url.into_bytes_with_nul().as_ptr() as *const _) }
+}
+
 pub enum FormResponses {
     None,
     String(String),
@@ -0,0 +1,741 @@
+use binaryninjacore_sys::*;
+
+use std::ffi::{c_char, c_void};
+
+use crate::binary_view::BinaryView;
+use crate::flowgraph::FlowGraph;
+use crate::interaction::report::{ReportCollection, ReportContents};
+use crate::interaction::{MessageBoxButtonResult, MessageBoxButtonSet, MessageBoxIcon};
+use crate::rc::Ref;
+use crate::string::{raw_to_string, BnString};
+
+/// Answers the prompts of the interaction API and shows its reports.
+///
+/// The UI registers its own handler, register one with [`register_interaction_handler`] to
+/// interact in headless environments, e.g. on a terminal or from a script. Single prompts default
+/// to a form with one field, reports other than plain text default to their plain text.
+pub trait InteractionHandler: 'static + Send + Sync {
+    fn show_message_box(
+        &self,
+        title: &str,
+        text: &str,
+        buttons: MessageBoxButtonSet,
+        icon: MessageBoxIcon,
+    ) -> MessageBoxButtonResult;
+
+    /// Fill in the `value` of the `fields`, returning `false` if the user cancelled.
+    fn get_form_input(&self, fields: &mut [FormInputField], title: &str) -> bool;
+
+    fn show_plain_text_report(&self, view: Option<&BinaryView>, title: &str, contents: &str);
+
+    fn show_markdown_report(
+        &self,
+        view: Option<&BinaryView>,
+        title: &str,
+        _contents: &str,
+        plain_text: &str,
+    ) {
+        self.show_plain_text_report(view, title, plain_text)
+    }
+
+    fn show_html_report(
+        &self,
+        view: Option<&BinaryView>,
+        title: &str,
+        _contents: &str,
+        plain_text: &str,
+    ) {
+        self.show_plain_text_report(view, title, plain_text)
+    }
+
+    /// Graphs have no plain text, they are not shown by default.
+    fn show_graph_report(&self, _view: Option<&BinaryView>, _title: &str, _graph: &FlowGraph) {}
+
+    /// Shows each report of the collection on its own by default.
+    fn show_report_collection(&self, _title: &str, reports: &ReportCollection) {
+        for report in reports.iter() {
+            let view = report.view.as_deref();
+            match &report.contents {
+                ReportContents::PlainText(contents) => {
+                    self.show_plain_text_report(view, &report.title, contents)
+                }
+                ReportContents::Markdown {
+                    contents,
+                    plain_text,
+                } => self.show_markdown_report(view, &report.title, contents, plain_text),
+                ReportContents::Html {
+                    contents,
+                    plain_text,
+                } => self.show_html_report(view, &report.title, contents, plain_text),
+                ReportContents::FlowGraph(graph) => {
+                    self.show_graph_report(view, &report.title, graph)
+                }
+            }
+        }
+    }
+
+    fn get_text_line_input(&self, prompt: &str, title: &str) -> Option<String> {
+        let field = FormInputField::TextLine {
+            prompt: prompt.to_string(),
+            default: None,
+            value: String::new(),
+        };
+        match get_single_input(self, field, title)? {
+            FormInputField::TextLine { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    fn get_integer_input(&self, prompt: &str, title: &str) -> Option<i64> {
+        let field = FormInputField::Integer {
+            prompt: prompt.to_string(),
+            default: None,
+            value: 0,
+        };
+        match get_single_input(self, field, title)? {
+            FormInputField::Integer { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    fn get_address_input(
+        &self,
+        prompt: &str,
+        title: &str,
+        view: Option<&BinaryView>,
+        current_address: u64,
+    ) -> Option<u64> {
+        let field = FormInputField::Address {
+            prompt: prompt.to_string(),
+            view: view.map(|view| view.to_owned()),
+            current_address,
+            default: None,
+            value: 0,
+        };
+        match get_single_input(self, field, title)? {
+            FormInputField::Address { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    fn get_choice_input(&self, prompt: &str, title: &str, choices: &[String]) -> Option<usize> {
+        let field = FormInputField::Choice {
+            prompt: prompt.to_string(),
+            choices: choices.to_vec(),
+            default: None,
+            value: 0,
+        };
+        match get_single_input(self, field, title)? {
+            FormInputField::Choice { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    /// A choice from more options than fit a drop down, the UI lets the user filter them.
+    fn get_large_choice_input(
+        &self,
+        prompt: &str,
+        title: &str,
+        choices: &[String],
+    ) -> Option<usize> {
+        self.get_choice_input(prompt, title, choices)
+    }
+
+    fn get_open_file_name_input(&self, prompt: &str, extension: &str) -> Option<String> {
+        let field = FormInputField::OpenFileName {
+            prompt: prompt.to_string(),
+            extension: extension.to_string(),
+            default: None,
+            value: String::new(),
+        };
+        match get_single_input(self, field, prompt)? {
+            FormInputField::OpenFileName { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    fn get_save_file_name_input(
+        &self,
+        prompt: &str,
+        extension: &str,
+        default_name: &str,
+    ) -> Option<String> {
+        let field = FormInputField::SaveFileName {
+            prompt: prompt.to_string(),
+            extension: extension.to_string(),
+            default_name: default_name.to_string(),
+            default: None,
+            value: String::new(),
+        };
+        match get_single_input(self, field, prompt)? {
+            FormInputField::SaveFileName { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    fn get_directory_name_input(&self, prompt: &str, default_name: &str) -> Option<String> {
+        let field = FormInputField::DirectoryName {
+            prompt: prompt.to_string(),
+            default_name: default_name.to_string(),
+            default: None,
+            value: String::new(),
+        };
+        match get_single_input(self, field, prompt)? {
+            FormInputField::DirectoryName { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Open `url` in a browser, returning whether it was opened.
+    fn open_url(&self, _url: &str) -> bool {
+        false
+    }
+
+    /// Run `task`, reporting its progress. Returns `false` if the user cancelled the task.
+    ///
+    /// Runs the task to completion without showing progress by default.
+    fn run_progress_dialog(
+        &self,
+        _title: &str,
+        _can_cancel: bool,
+        task: &InteractionHandlerTask,
+    ) -> bool {
+        task.run(|_, _| true);
+        true
+    }
+}
+
+fn get_single_input<H: InteractionHandler + ?Sized>(
+    handler: &H,
+    field: FormInputField,
+    title: &str,
+) -> Option<FormInputField> {
+    let mut fields = [field];
+    handler.get_form_input(&mut fields, title).then(|| {
+        let [field] = fields;
+        field
+    })
+}
+
+/// A field of a form passed to [`InteractionHandler::get_form_input`].
+///
+/// The `value` of a field starts out as its default, or empty if it has none.
+#[derive(Clone, Debug)]
+pub enum FormInputField {
+    Label {
+        prompt: String,
+    },
+    Separator,
+    TextLine {
+        prompt: String,
+        default: Option<String>,
+        value: String,
+    },
+    MultilineText {
+        prompt: String,
+        default: Option<String>,
+        value: String,
+    },
+    Integer {
+        prompt: String,
+        default: Option<i64>,
+        value: i64,
+    },
+    Address {
+        prompt: String,
+        view: Option<Ref<BinaryView>>,
+        current_address: u64,
+        default: Option<u64>,
+        value: u64,
+    },
+    Choice {
+        prompt: String,
+        choices: Vec<String>,
+        default: Option<usize>,
+        value: usize,
+    },
+    OpenFileName {
+        prompt: String,
+        extension: String,
+        default: Option<String>,
+        value: String,
+    },
+    SaveFileName {
+        prompt: String,
+        extension: String,
+        default_name: String,
+        default: Option<String>,
+        value: String,
+    },
+    DirectoryName {
+        prompt: String,
+        default_name: String,
+        default: Option<String>,
+        value: String,
+    },
+}
+
+impl FormInputField {
+    unsafe fn from_raw(raw: &BNFormInputField) -> Self {
+        let prompt = raw_to_string(raw.prompt).unwrap_or_default();
+        let string_default = match raw.hasDefault {
+            true => raw_to_string(raw.stringDefault),
+            false => None,
+        };
+        let string_value = string_default.clone().unwrap_or_default();
+        match raw.type_ {
+            BNFormInputFieldType::LabelFormField => FormInputField::Label { prompt },
+            BNFormInputFieldType::SeparatorFormField => FormInputField::Separator,
+            BNFormInputFieldType::TextLineFormField => FormInputField::TextLine {
+                prompt,
+                default: string_default,
+                value: string_value,
+            },
+            BNFormInputFieldType::MultilineTextFormField => FormInputField::MultilineText {
+                prompt,
+                default: string_default,
+                value: string_value,
+            },
+            BNFormInputFieldType::IntegerFormField => FormInputField::Integer {
+                prompt,
+                default: raw.hasDefault.then_some(raw.intDefault),
+                value: if raw.hasDefault { raw.intDefault } else { 0 },
+            },
+            BNFormInputFieldType::AddressFormField => FormInputField::Address {
+                prompt,
+                view: (!raw.view.is_null()).then(|| BinaryView::from_raw(raw.view).to_owned()),
+                current_address: raw.currentAddress,
+                default: raw.hasDefault.then_some(raw.addressDefault),
+                value: match raw.hasDefault {
+                    true => raw.addressDefault,
+                    false => raw.currentAddress,
+                },
+            },
+            BNFormInputFieldType::ChoiceFormField => FormInputField::Choice {
+                prompt,
+                choices: raw_strings(raw.choices, raw.count),
+                default: raw.hasDefault.then_some(raw.indexDefault),
+                value: if raw.hasDefault { raw.indexDefault } else { 0 },
+            },
+            BNFormInputFieldType::OpenFileNameFormField => FormInputField::OpenFileName {
+                prompt,
+                extension: raw_to_string(raw.ext).unwrap_or_default(),
+                default: string_default,
+                value: string_value,
+            },
+            BNFormInputFieldType::SaveFileNameFormField => FormInputField::SaveFileName {
+                prompt,
+                extension: raw_to_string(raw.ext).unwrap_or_default(),
+                default_name: raw_to_string(raw.defaultName).unwrap_or_default(),
+                default: string_default,
+                value: string_value,
+            },
+            BNFormInputFieldType::DirectoryNameFormField => FormInputField::DirectoryName {
+                prompt,
+                default_name: raw_to_string(raw.defaultName).unwrap_or_default(),
+                default: string_default,
+                value: string_value,
+            },
+        }
+    }
+
+    /// Store the value in the result of `raw`, where the core frees it.
+    fn write_result(&self, raw: &mut BNFormInputField) {
+        match self {
+            FormInputField::Label { .. } | FormInputField::Separator => {}
+            FormInputField::TextLine { value, .. }
+            | FormInputField::MultilineText { value, .. }
+            | FormInputField::OpenFileName { value, .. }
+            | FormInputField::SaveFileName { value, .. }
+            | FormInputField::DirectoryName { value, .. } => {
+                raw.stringResult = BnString::into_raw(BnString::new(value.as_str()));
+            }
+            FormInputField::Integer { value, .. } => raw.intResult = *value,
+            FormInputField::Address { value, .. } => raw.addressResult = *value,
+            FormInputField::Choice { value, .. } => raw.indexResult = *value,
+        }
+    }
+}
+
+unsafe fn raw_strings(raw: *const *const c_char, count: usize) -> Vec<String> {
+    if raw.is_null() {
+        return vec![];
+    }
+    std::slice::from_raw_parts(raw, count)
+        .iter()
+        .map(|&s| raw_to_string(s).unwrap_or_default())
+        .collect()
+}
+
+/// The task of a progress dialog, see [`InteractionHandler::run_progress_dialog`].
+pub struct InteractionHandlerTask {
+    ctxt: *mut c_void,
+    task: Option<
+        unsafe extern "C" fn(
+            *mut c_void,
+            Option<unsafe extern "C" fn(*mut c_void, usize, usize) -> bool>,
+            *mut c_void,
+        ),
+    >,
+}
+
+impl InteractionHandlerTask {
+    /// Run the task, which calls `progress` with its progress and the total. The task is
+    /// cancelled once `progress` returns `false`.
+    pub fn run<P: FnMut(usize, usize) -> bool>(&self, mut progress: P) {
+        unsafe extern "C" fn cb_progress<P: FnMut(usize, usize) -> bool>(
+            ctxt: *mut c_void,
+            cur: usize,
+            max: usize,
+        ) -> bool {
+            ffi_wrap!("InteractionHandlerTask::progress", {
+                let progress = &mut *(ctxt as *mut P);
+                progress(cur, max)
+            })
+        }
+
+        if let Some(task) = self.task {
+            unsafe {
+                task(
+                    self.ctxt,
+                    Some(cb_progress::<P>),
+                    &mut progress as *mut P as *mut c_void,
+                )
+            }
+        }
+    }
+}
+
+/// Register `handler` to answer all prompts and show all reports from now on.
+pub fn register_interaction_handler<H: InteractionHandler>(handler: H) {
+    let handler = Box::leak(Box::new(handler));
+    let mut callbacks = BNInteractionHandlerCallbacks {
+        context: handler as *mut H as *mut c_void,
+        showPlainTextReport: Some(cb_show_plain_text_report::<H>),
+        showMarkdownReport: Some(cb_show_markdown_report::<H>),
+        showHTMLReport: Some(cb_show_html_report::<H>),
+        showGraphReport: Some(cb_show_graph_report::<H>),
+        showReportCollection: Some(cb_show_report_collection::<H>),
+        getTextLineInput: Some(cb_get_text_line_input::<H>),
+        getIntegerInput: Some(cb_get_integer_input::<H>),
+        getAddressInput: Some(cb_get_address_input::<H>),
+        getChoiceInput: Some(cb_get_choice_input::<H>),
+        getLargeChoiceInput: Some(cb_get_large_choice_input::<H>),
+        getOpenFileNameInput: Some(cb_get_open_file_name_input::<H>),
+        getSaveFileNameInput: Some(cb_get_save_file_name_input::<H>),
+        getDirectoryNameInput: Some(cb_get_directory_name_input::<H>),
+        getFormInput: Some(cb_get_form_input::<H>),
+        showMessageBox: Some(cb_show_message_box::<H>),
+        openUrl: Some(cb_open_url::<H>),
+        runProgressDialog: Some(cb_run_progress_dialog::<H>),
+    };
+    unsafe { BNRegisterInteractionHandler(&mut callbacks) }
+}
+
+fn raw_str(raw: *const c_char) -> String {
+    raw_to_string(raw).unwrap_or_default()
+}
+
+unsafe fn raw_view(view: *mut BNBinaryView) -> Option<BinaryView> {
+    (!view.is_null()).then(|| BinaryView::from_raw(view))
+}
+
+unsafe fn write_string(result: *mut *mut c_char, value: Option<String>) -> bool {
+    match value {
+        Some(value) => {
+            *result = BnString::into_raw(BnString::new(value));
+            true
+        }
+        None => false,
+    }
+}
+
+unsafe extern "C" fn cb_show_plain_text_report<H: InteractionHandler>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    title: *const c_char,
+    contents: *const c_char,
+) {
+    ffi_wrap!("InteractionHandler::show_plain_text_report", {
+        let handler = &*(ctxt as *const H);
+        let view = raw_view(view);
+        handler.show_plain_text_report(view.as_ref(), &raw_str(title), &raw_str(contents))
+    })
+}
+
+unsafe extern "C" fn cb_show_markdown_report<H: InteractionHandler>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    title: *const c_char,
+    contents: *const c_char,
+    plain_text: *const c_char,
+) {
+    ffi_wrap!("InteractionHandler::show_markdown_report", {
+        let handler = &*(ctxt as *const H);
+        let view = raw_view(view);
+        handler.show_markdown_report(
+            view.as_ref(),
+            &raw_str(title),
+            &raw_str(contents),
+            &raw_str(plain_text),
+        )
+    })
+}
+
+unsafe extern "C" fn cb_show_html_report<H: InteractionHandler>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    title: *const c_char,
+    contents: *const c_char,
+    plain_text: *const c_char,
+) {
+    ffi_wrap!("InteractionHandler::show_html_report", {
+        let handler = &*(ctxt as *const H);
+        let view = raw_view(view);
+        handler.show_html_report(
+            view.as_ref(),
+            &raw_str(title),
+            &raw_str(contents),
+            &raw_str(plain_text),
+        )
+    })
+}
+
+unsafe extern "C" fn cb_show_graph_report<H: InteractionHandler>(
+    ctxt: *mut c_void,
+    view: *mut BNBinaryView,
+    title: *const c_char,
+    graph: *mut BNFlowGraph,
+) {
+    ffi_wrap!("InteractionHandler::show_graph_report", {
+        let handler = &*(ctxt as *const H);
+        let view = raw_view(view);
+        let graph = FlowGraph::from_raw(graph);
+        handler.show_graph_report(view.as_ref(), &raw_str(title), &graph)
+    })
+}
+
+unsafe extern "C" fn cb_show_report_collection<H: InteractionHandler>(
+    ctxt: *mut c_void,
+    title: *const c_char,
+    reports: *mut BNReportCollection,
+) {
+    ffi_wrap!("InteractionHandler::show_report_collection", {
+        let handler = &*(ctxt as *const H);
+        let reports = ReportCollection::from_raw(reports);
+        handler.show_report_collection(&raw_str(title), &reports)
+    })
+}
+
+unsafe extern "C" fn cb_get_text_line_input<H: InteractionHandler>(
+    ctxt: *mut c_void,
+    result: *mut *mut c_char,
+    prompt: *const c_char,
+    title: *const c_char,
+) -> bool {
+    ffi_wrap!("InteractionHandler::get_text_line_input", {
+        let handler = &*(ctxt as *const H);
+        let value = handler.get_text_line_input(&raw_str(prompt), &raw_str(title));
+        write_string(result, value)
+    })
+}
+
+unsafe extern "C" fn cb_get_integer_input<H: InteractionHandler>(
+    ctxt: *mut c_void,
+    result: *mut i64,
+    prompt: *const c_char,
+    title: *const c_char,
+) -> bool {
+    ffi_wrap!("InteractionHandler::get_integer_input", {
+        let handler = &*(ctxt as *const H);
+        match handler.get_integer_input(&raw_str(prompt), &raw_str(title)) {
+            Some(value) => {
+                *result = value;
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+unsafe extern "C" fn cb_get_address_input<H: InteractionHandler>(
+    ctxt: *mut c_void,
+    result: *mut u64,
+    prompt: *const c_char,
+    title: *const c_char,
+    view: *mut BNBinaryView,
+    current_address: u64,
+) -> bool {
+    ffi_wrap!("InteractionHandler::get_address_input", {
+        let handler = &*(ctxt as *const H);
+        let view = raw_view(view);
+        let value = handler.get_address_input(
+            &raw_str(prompt),
+            &raw_str(title),
+            view.as_ref(),
+            current_address,
+        );
+        match value {
+            Some(value) => {
+                *result = value;
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+unsafe extern "C" fn cb_get_choice_input<H: InteractionHandler>(
+    ctxt: *mut c_void,
+    result: *mut usize,
+    prompt: *const c_char,
+    title: *const c_char,
+    choices: *mut *const c_char,
+    count: usize,
+) -> bool {
+    ffi_wrap!("InteractionHandler::get_choice_input", {
+        let handler = &*(ctxt as *const H);
+        let choices = raw_strings(choices, count);
+        match handler.get_choice_input(&raw_str(prompt), &raw_str(title), &choices) {
+            Some(value) => {
+                *result = value;
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+unsafe extern "C" fn cb_get_large_choice_input<H: InteractionHandler>(
+    ctxt: *mut c_void,
+    result: *mut usize,
+    prompt: *const c_char,
+    title: *const c_char,
+    choices: *mut *const c_char,
+    count: usize,
+) -> bool {
+    ffi_wrap!("InteractionHandler::get_large_choice_input", {
+        let handler = &*(ctxt as *const H);
+        let choices = raw_strings(choices, count);
+        match handler.get_large_choice_input(&raw_str(prompt), &raw_str(title), &choices) {
+            Some(value) => {
+                *result = value;
+                true
+            }
+            None => false,
+        }
+    })
+}
+
+unsafe extern "C" fn cb_get_open_file_name_input<H: InteractionHandler>(
+    ctxt: *mut c_void,
+    result: *mut *mut c_char,
+    prompt: *const c_char,
+    ext: *const c_char,
+) -> bool {
+    ffi_wrap!("InteractionHandler::get_open_file_name_input", {
+        let handler = &*(ctxt as *const H);
+        let value = handler.get_open_file_name_input(&raw_str(prompt), &raw_str(ext));
+        write_string(result, value)
+    })
+}
+
+unsafe extern "C" fn cb_get_save_file_name_input<H: InteractionHandler>(
+    ctxt: *mut c_void,
+    result: *mut *mut c_char,
+    prompt: *const c_char,
+    ext: *const c_char,
+    default_name: *const c_char,
+) -> bool {
+    ffi_wrap!("InteractionHandler::get_save_file_name_input", {
+        let handler = &*(ctxt as *const H);
+        let value = handler.get_save_file_name_input(
+            &raw_str(prompt),
+            &raw_str(ext),
+            &raw_str(default_name),
+        );
+        write_string(result, value)
+    })
+}
+
+unsafe extern "C" fn cb_get_directory_name_input<H: InteractionHandler>(
+    ctxt: *mut c_void,
+    result: *mut *mut c_char,
+    prompt: *const c_char,
+    default_name: *const c_char,
+) -> bool {
+    ffi_wrap!("InteractionHandler::get_directory_name_input", {
+        let handler = &*(ctxt as *const H);
+        let value = handler.get_directory_name_input(&raw_str(prompt), &raw_str(default_name));
+        write_string(result, value)
+    })
+}
+
+unsafe extern "C" fn cb_get_form_input<H: InteractionHandler>(
+    ctxt: *mut c_void,
+    fields: *mut BNFormInputField,
+    count: usize,
+    title: *const c_char,
+) -> bool {
+    ffi_wrap!("InteractionHandler::get_form_input", {
+        let handler = &*(ctxt as *const H);
+        let raw_fields = std::slice::from_raw_parts_mut(fields, count);
+        let mut form: Vec<FormInputField> = raw_fields
+            .iter()
+            .map(|raw| FormInputField::from_raw(raw))
+            .collect();
+        if !handler.get_form_input(&mut form, &raw_str(title)) {
+            return false;
+        }
+        for (field, raw) in form.iter().zip(raw_fields.iter_mut()) {
+            field.write_result(raw);
+        }
+        true
+    })
+}
+
+unsafe extern "C" fn cb_show_message_box<H: InteractionHandler>(
+    ctxt: *mut c_void,
+    title: *const c_char,
+    text: *const c_char,
+    buttons: MessageBoxButtonSet,
+    icon: MessageBoxIcon,
+) -> MessageBoxButtonResult {
+    ffi_wrap!("InteractionHandler::show_message_box", {
+        let handler = &*(ctxt as *const H);
+        handler.show_message_box(&raw_str(title), &raw_str(text), buttons, icon)
+    })
+}
+
+unsafe extern "C" fn cb_open_url<H: InteractionHandler>(
+    ctxt: *mut c_void,
+    url: *const c_char,
+) -> bool {
+    ffi_wrap!("InteractionHandler::open_url", {
+        let handler = &*(ctxt as *const H);
+        handler.open_url(&raw_str(url))
+    })
+}
+
+unsafe extern "C" fn cb_run_progress_dialog<H: InteractionHandler>(
+    ctxt: *mut c_void,
+    title: *const c_char,
+    can_cancel: bool,
+    task: Option<
+        unsafe extern "C" fn(
+            *mut c_void,
+            Option<unsafe extern "C" fn(*mut c_void, usize, usize) -> bool>,
+            *mut c_void,
+        ),
+    >,
+    task_ctxt: *mut c_void,
+) -> bool {
+    ffi_wrap!("InteractionHandler::run_progress_dialog", {
+        let handler = &*(ctxt as *const H);
+        let task = InteractionHandlerTask {
+            ctxt: task_ctxt,
+            task,
+        };
+        handler.run_progress_dialog(&raw_str(title), can_cancel, &task)
+    })
+}
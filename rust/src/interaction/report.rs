@@ -0,0 +1,226 @@
+use binaryninjacore_sys::*;
+
+use std::ffi::c_char;
+
+use crate::binary_view::BinaryView;
+use crate::flowgraph::FlowGraph;
+use crate::rc::{Ref, RefCountable};
+use crate::string::{BnStrCompatible, BnString};
+
+pub type ReportType = BNReportType;
+
+/// A set of reports shown together, e.g. as tabs of a single window.
+#[derive(PartialEq, Eq, Hash)]
+pub struct ReportCollection {
+    pub(crate) handle: *mut BNReportCollection,
+}
+
+impl ReportCollection {
+    pub(crate) unsafe fn from_raw(handle: *mut BNReportCollection) -> Self {
+        Self { handle }
+    }
+
+    pub(crate) unsafe fn ref_from_raw(handle: *mut BNReportCollection) -> Ref<Self> {
+        Ref::new(Self { handle })
+    }
+
+    pub fn new() -> Ref<Self> {
+        unsafe { Self::ref_from_raw(BNCreateReportCollection()) }
+    }
+
+    pub fn len(&self) -> usize {
+        unsafe { BNGetReportCollectionCount(self.handle) }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The report at `index`, `None` if the collection holds fewer reports.
+    pub fn get(&self, index: usize) -> Option<Report> {
+        if index >= self.len() {
+            return None;
+        }
+        let view = unsafe { BNGetReportView(self.handle, index) };
+        let view = (!view.is_null()).then(|| unsafe { BinaryView::ref_from_raw(view) });
+        let title = unsafe { BnString::from_raw(BNGetReportTitle(self.handle, index)) };
+        let contents = match unsafe { BNGetReportType(self.handle, index) } {
+            ReportType::PlainTextReportType => ReportContents::PlainText(self.contents(index)),
+            ReportType::MarkdownReportType => ReportContents::Markdown {
+                contents: self.contents(index),
+                plain_text: self.plain_text(index),
+            },
+            ReportType::HTMLReportType => ReportContents::Html {
+                contents: self.contents(index),
+                plain_text: self.plain_text(index),
+            },
+            ReportType::FlowGraphReportType => {
+                let graph = unsafe { BNGetReportFlowGraph(self.handle, index) };
+                ReportContents::FlowGraph(unsafe { Ref::new(FlowGraph::from_raw(graph)) })
+            }
+        };
+        Some(Report {
+            view,
+            title: title.to_string(),
+            contents,
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Report> + '_ {
+        (0..self.len()).filter_map(|index| self.get(index))
+    }
+
+    fn contents(&self, index: usize) -> String {
+        unsafe { BnString::from_raw(BNGetReportContents(self.handle, index)) }.to_string()
+    }
+
+    fn plain_text(&self, index: usize) -> String {
+        unsafe { BnString::from_raw(BNGetReportPlainText(self.handle, index)) }.to_string()
+    }
+
+    pub fn add_plain_text<S: BnStrCompatible, C: BnStrCompatible>(
+        &self,
+        view: Option<&BinaryView>,
+        title: S,
+        contents: C,
+    ) {
+        let title = title.into_bytes_with_nul();
+        let contents = contents.into_bytes_with_nul();
+        unsafe {
+            BNAddPlainTextReportToCollection(
+                self.handle,
+                view_handle(view),
+                title.as_ref().as_ptr() as *const c_char,
+                contents.as_ref().as_ptr() as *const c_char,
+            )
+        }
+    }
+
+    /// Add a markdown report, shown as `plain_text` where markdown can't be rendered.
+    pub fn add_markdown<S: BnStrCompatible, C: BnStrCompatible, P: BnStrCompatible>(
+        &self,
+        view: Option<&BinaryView>,
+        title: S,
+        contents: C,
+        plain_text: P,
+    ) {
+        let title = title.into_bytes_with_nul();
+        let contents = contents.into_bytes_with_nul();
+        let plain_text = plain_text.into_bytes_with_nul();
+        unsafe {
+            BNAddMarkdownReportToCollection(
+                self.handle,
+                view_handle(view),
+                title.as_ref().as_ptr() as *const c_char,
+                contents.as_ref().as_ptr() as *const c_char,
+                plain_text.as_ref().as_ptr() as *const c_char,
+            )
+        }
+    }
+
+    /// Add an HTML report, shown as `plain_text` where HTML can't be rendered.
+    pub fn add_html<S: BnStrCompatible, C: BnStrCompatible, P: BnStrCompatible>(
+        &self,
+        view: Option<&BinaryView>,
+        title: S,
+        contents: C,
+        plain_text: P,
+    ) {
+        let title = title.into_bytes_with_nul();
+        let contents = contents.into_bytes_with_nul();
+        let plain_text = plain_text.into_bytes_with_nul();
+        unsafe {
+            BNAddHTMLReportToCollection(
+                self.handle,
+                view_handle(view),
+                title.as_ref().as_ptr() as *const c_char,
+                contents.as_ref().as_ptr() as *const c_char,
+                plain_text.as_ref().as_ptr() as *const c_char,
+            )
+        }
+    }
+
+    pub fn add_graph<S: BnStrCompatible>(
+        &self,
+        view: Option<&BinaryView>,
+        title: S,
+        graph: &FlowGraph,
+    ) {
+        let title = title.into_bytes_with_nul();
+        unsafe {
+            BNAddGraphReportToCollection(
+                self.handle,
+                view_handle(view),
+                title.as_ref().as_ptr() as *const c_char,
+                graph.handle,
+            )
+        }
+    }
+
+    /// Replace the graph of the flow graph report at `index`.
+    pub fn update_graph(&self, index: usize, graph: &FlowGraph) {
+        unsafe { BNUpdateReportFlowGraph(self.handle, index, graph.handle) }
+    }
+
+    /// Show the reports of the collection to the user.
+    pub fn show<S: BnStrCompatible>(&self, title: S) {
+        let title = title.into_bytes_with_nul();
+        unsafe { BNShowReportCollection(title.as_ref().as_ptr() as *const c_char, self.handle) }
+    }
+}
+
+fn view_handle(view: Option<&BinaryView>) -> *mut BNBinaryView {
+    view.map_or(std::ptr::null_mut(), |view| view.handle)
+}
+
+unsafe impl RefCountable for ReportCollection {
+    unsafe fn inc_ref(handle: &Self) -> Ref<Self> {
+        Self::ref_from_raw(BNNewReportCollectionReference(handle.handle))
+    }
+
+    unsafe fn dec_ref(handle: &Self) {
+        BNFreeReportCollection(handle.handle);
+    }
+}
+
+impl ToOwned for ReportCollection {
+    type Owned = Ref<Self>;
+
+    fn to_owned(&self) -> Self::Owned {
+        unsafe { RefCountable::inc_ref(self) }
+    }
+}
+
+unsafe impl Send for ReportCollection {}
+unsafe impl Sync for ReportCollection {}
+
+/// A report of a [`ReportCollection`].
+pub struct Report {
+    pub view: Option<Ref<BinaryView>>,
+    pub title: String,
+    pub contents: ReportContents,
+}
+
+pub enum ReportContents {
+    PlainText(String),
+    Markdown {
+        contents: String,
+        plain_text: String,
+    },
+    Html {
+        contents: String,
+        plain_text: String,
+    },
+    FlowGraph(Ref<FlowGraph>),
+}
+
+impl ReportContents {
+    pub fn report_type(&self) -> ReportType {
+        match self {
+            ReportContents::PlainText(_) => ReportType::PlainTextReportType,
+            ReportContents::Markdown { .. } => ReportType::MarkdownReportType,
+            ReportContents::Html { .. } => ReportType::HTMLReportType,
+            ReportContents::FlowGraph(_) => ReportType::FlowGraphReportType,
+        }
+    }
+}
@@ -28,7 +28,12 @@
 mod ffi;
 mod operand_iter;
 
+pub mod address;
+pub mod analysis_budget;
+pub mod analysis_completion;
+pub mod analysis_quality;
 pub mod architecture;
+pub mod asm_search;
 pub mod background_task;
 pub mod basic_block;
 pub mod binary_reader;
@@ -41,40 +46,65 @@ pub mod component;
 pub mod confidence;
 pub mod custom_binary_view;
 pub mod data_buffer;
+pub mod data_notification;
 pub mod database;
 pub mod debuginfo;
+pub mod decompilation_cache;
 pub mod demangle;
 pub mod disassembly;
 pub mod download_provider;
 pub mod enterprise;
+pub mod entropy;
+pub mod error;
 pub mod external_library;
 pub mod file_accessor;
 pub mod file_metadata;
 pub mod flowgraph;
+pub mod frame_info;
 pub mod function;
 pub mod function_recognizer;
+pub mod function_signatures;
+pub mod headers;
 pub mod headless;
+pub mod heat_map;
 pub mod high_level_il;
+pub mod import_diagnostics;
+pub mod inlined_calls;
+pub mod instruction_iter;
 pub mod interaction;
 pub mod linear_view;
 pub mod logger;
 pub mod low_level_il;
 pub mod main_thread;
 pub mod medium_level_il;
+pub mod memory_map;
 pub mod metadata;
+pub mod patch;
+pub mod pipeline;
 pub mod platform;
+#[cfg(feature = "platform_types")]
+pub mod platform_types;
+pub mod plugin_storage;
 pub mod progress;
 pub mod project;
 pub mod rc;
 pub mod references;
 pub mod relocation;
+pub mod retype;
+pub mod search;
 pub mod section;
 pub mod segment;
 pub mod settings;
+pub mod signatures;
+pub mod source_lines;
 pub mod string;
 pub mod symbol;
+pub mod symbol_index;
+pub mod symbol_name_transformer;
+pub mod symbol_server;
 pub mod tags;
 pub mod template_simplifier;
+pub mod thunk;
 pub mod type_archive;
 pub mod type_container;
 pub mod type_library;
@@ -83,6 +113,8 @@ pub mod type_printer;
 pub mod types;
 pub mod update;
 pub mod variable;
+pub mod watchpoint;
+pub mod work_queue;
 pub mod worker_thread;
 pub mod workflow;
 
@@ -105,6 +137,7 @@ pub use binaryninjacore_sys::BNBranchType as BranchType;
 pub use binaryninjacore_sys::BNDataFlowQueryOption as DataFlowQueryOption;
 pub use binaryninjacore_sys::BNEndianness as Endianness;
 pub use binaryninjacore_sys::BNILBranchDependence as ILBranchDependence;
+pub use error::Error;
 
 pub const BN_FULL_CONFIDENCE: u8 = u8::MAX;
 pub const BN_INVALID_EXPR: usize = usize::MAX;
@@ -21,12 +21,18 @@ use crate::disassembly::{DisassemblySettings, DisassemblyTextLine};
 use crate::function::Function;
 
 use crate::rc::*;
-use std::ops::Deref;
+use crate::string::{raw_to_string, BnString};
+use std::ffi::c_char;
+use std::ops::{Deref, Range};
 
 use std::mem;
 
 pub type LinearDisassemblyLineType = BNLinearDisassemblyLineType;
 
+/// A node of the linear view tree, from a root for the whole view or a single function down to the
+/// objects holding the lines of each function and data variable.
+///
+/// The objects are provided by the core, which has no interface for objects implemented by plugins.
 // TODO: Rename to LinearView?
 pub struct LinearViewObject {
     pub(crate) handle: *mut BNLinearViewObject,
@@ -59,6 +65,20 @@ impl LinearViewObject {
         }
     }
 
+    pub fn llil(view: &BinaryView, settings: &DisassemblySettings) -> Ref<Self> {
+        unsafe {
+            let handle = BNCreateLinearViewLowLevelIL(view.handle, settings.handle);
+            Self::ref_from_raw(handle)
+        }
+    }
+
+    pub fn llil_ssa(view: &BinaryView, settings: &DisassemblySettings) -> Ref<Self> {
+        unsafe {
+            let handle = BNCreateLinearViewLowLevelILSSAForm(view.handle, settings.handle);
+            Self::ref_from_raw(handle)
+        }
+    }
+
     pub fn mlil(view: &BinaryView, settings: &DisassemblySettings) -> Ref<Self> {
         unsafe {
             let handle = BNCreateLinearViewMediumLevelIL(view.handle, settings.handle);
@@ -73,6 +93,20 @@ impl LinearViewObject {
         }
     }
 
+    pub fn mapped_mlil(view: &BinaryView, settings: &DisassemblySettings) -> Ref<Self> {
+        unsafe {
+            let handle = BNCreateLinearViewMappedMediumLevelIL(view.handle, settings.handle);
+            Self::ref_from_raw(handle)
+        }
+    }
+
+    pub fn mapped_mlil_ssa(view: &BinaryView, settings: &DisassemblySettings) -> Ref<Self> {
+        unsafe {
+            let handle = BNCreateLinearViewMappedMediumLevelILSSAForm(view.handle, settings.handle);
+            Self::ref_from_raw(handle)
+        }
+    }
+
     pub fn hlil(view: &BinaryView, settings: &DisassemblySettings) -> Ref<Self> {
         unsafe {
             let handle = BNCreateLinearViewHighLevelIL(view.handle, settings.handle);
@@ -125,6 +159,25 @@ impl LinearViewObject {
         }
     }
 
+    pub fn single_function_llil(function: &Function, settings: &DisassemblySettings) -> Ref<Self> {
+        unsafe {
+            let handle =
+                BNCreateLinearViewSingleFunctionLowLevelIL(function.handle, settings.handle);
+            Self::ref_from_raw(handle)
+        }
+    }
+
+    pub fn single_function_llil_ssa(
+        function: &Function,
+        settings: &DisassemblySettings,
+    ) -> Ref<Self> {
+        unsafe {
+            let handle =
+                BNCreateLinearViewSingleFunctionLowLevelILSSAForm(function.handle, settings.handle);
+            Self::ref_from_raw(handle)
+        }
+    }
+
     pub fn single_function_mlil(function: &Function, settings: &DisassemblySettings) -> Ref<Self> {
         unsafe {
             let handle =
@@ -146,6 +199,32 @@ impl LinearViewObject {
         }
     }
 
+    pub fn single_function_mapped_mlil(
+        function: &Function,
+        settings: &DisassemblySettings,
+    ) -> Ref<Self> {
+        unsafe {
+            let handle = BNCreateLinearViewSingleFunctionMappedMediumLevelIL(
+                function.handle,
+                settings.handle,
+            );
+            Self::ref_from_raw(handle)
+        }
+    }
+
+    pub fn single_function_mapped_mlil_ssa(
+        function: &Function,
+        settings: &DisassemblySettings,
+    ) -> Ref<Self> {
+        unsafe {
+            let handle = BNCreateLinearViewSingleFunctionMappedMediumLevelILSSAForm(
+                function.handle,
+                settings.handle,
+            );
+            Self::ref_from_raw(handle)
+        }
+    }
+
     pub fn single_function_hlil(function: &Function, settings: &DisassemblySettings) -> Ref<Self> {
         unsafe {
             let handle =
@@ -189,6 +268,113 @@ impl LinearViewObject {
             LinearViewCursor::ref_from_raw(handle)
         }
     }
+
+    unsafe fn child_from_raw(handle: *mut BNLinearViewObject) -> Option<Ref<Self>> {
+        (!handle.is_null()).then(|| Self::ref_from_raw(handle))
+    }
+
+    pub fn first_child(&self) -> Option<Ref<Self>> {
+        unsafe { Self::child_from_raw(BNGetFirstLinearViewObjectChild(self.handle)) }
+    }
+
+    pub fn last_child(&self) -> Option<Ref<Self>> {
+        unsafe { Self::child_from_raw(BNGetLastLinearViewObjectChild(self.handle)) }
+    }
+
+    /// The child before `child`, which must be a child of this object.
+    pub fn previous_child(&self, child: &LinearViewObject) -> Option<Ref<Self>> {
+        unsafe {
+            Self::child_from_raw(BNGetPreviousLinearViewObjectChild(
+                self.handle,
+                child.handle,
+            ))
+        }
+    }
+
+    /// The child after `child`, which must be a child of this object.
+    pub fn next_child(&self, child: &LinearViewObject) -> Option<Ref<Self>> {
+        unsafe { Self::child_from_raw(BNGetNextLinearViewObjectChild(self.handle, child.handle)) }
+    }
+
+    /// The child containing `address`, or the closest one after it.
+    pub fn child_for_address(&self, address: u64) -> Option<Ref<Self>> {
+        unsafe { Self::child_from_raw(BNGetLinearViewObjectChildForAddress(self.handle, address)) }
+    }
+
+    pub fn child_for_identifier(
+        &self,
+        identifier: &LinearViewObjectIdentifier,
+    ) -> Option<Ref<Self>> {
+        let name = BnString::new(identifier.name.as_str());
+        let mut raw = identifier.as_raw(&name);
+        unsafe {
+            Self::child_from_raw(BNGetLinearViewObjectChildForIdentifier(
+                self.handle,
+                &mut raw,
+            ))
+        }
+    }
+
+    /// The child at the ordering index `index`, see [`LinearViewObject::ordering_index_total`].
+    pub fn child_for_ordering_index(&self, index: u64) -> Option<Ref<Self>> {
+        unsafe {
+            Self::child_from_raw(BNGetLinearViewObjectChildForOrderingIndex(
+                self.handle,
+                index,
+            ))
+        }
+    }
+
+    /// The ordering index of `child`, which must be a child of this object.
+    pub fn ordering_index_for_child(&self, child: &LinearViewObject) -> u64 {
+        unsafe { BNGetLinearViewObjectOrderingIndexForChild(self.handle, child.handle) }
+    }
+
+    /// The number of ordering indices of the object, which give the position of its children
+    /// independent of their addresses, e.g. for scroll bars.
+    pub fn ordering_index_total(&self) -> u64 {
+        unsafe { BNGetLinearViewObjectOrderingIndexTotal(self.handle) }
+    }
+
+    /// Compare the order of the children `a` and `b` of this object.
+    pub fn compare_children(
+        &self,
+        a: &LinearViewObject,
+        b: &LinearViewObject,
+    ) -> std::cmp::Ordering {
+        unsafe { BNCompareLinearViewObjectChildren(self.handle, a.handle, b.handle) }.cmp(&0)
+    }
+
+    pub fn start(&self) -> u64 {
+        unsafe { BNGetLinearViewObjectStart(self.handle) }
+    }
+
+    pub fn end(&self) -> u64 {
+        unsafe { BNGetLinearViewObjectEnd(self.handle) }
+    }
+
+    pub fn identifier(&self) -> LinearViewObjectIdentifier {
+        let mut raw = unsafe { BNGetLinearViewObjectIdentifier(self.handle) };
+        let identifier = LinearViewObjectIdentifier::from_raw(&raw);
+        unsafe { BNFreeLinearViewObjectIdentifier(&mut raw) };
+        identifier
+    }
+
+    /// The lines of the object, where `previous` and `next` are its neighbouring siblings, used
+    /// for the lines separating them.
+    pub fn lines(
+        &self,
+        previous: Option<&LinearViewObject>,
+        next: Option<&LinearViewObject>,
+    ) -> Array<LinearDisassemblyLine> {
+        let previous = previous.map_or(std::ptr::null_mut(), |object| object.handle);
+        let next = next.map_or(std::ptr::null_mut(), |object| object.handle);
+        let mut count = 0;
+        unsafe {
+            let handles = BNGetLinearViewObjectLines(self.handle, previous, next, &mut count);
+            Array::new(handles, count, ())
+        }
+    }
 }
 
 unsafe impl RefCountable for LinearViewObject {
@@ -214,6 +400,126 @@ impl ToOwned for LinearViewObject {
 unsafe impl Send for LinearViewObject {}
 unsafe impl Sync for LinearViewObject {}
 
+impl CoreArrayProvider for LinearViewObject {
+    type Raw = *mut BNLinearViewObject;
+    type Context = ();
+    type Wrapped<'a> = Guard<'a, Self>;
+}
+
+unsafe impl CoreArrayProviderInner for LinearViewObject {
+    unsafe fn free(raw: *mut Self::Raw, count: usize, _context: &Self::Context) {
+        BNFreeLinearViewCursorPathObjects(raw, count)
+    }
+
+    unsafe fn wrap_raw<'a>(raw: &'a Self::Raw, context: &'a Self::Context) -> Self::Wrapped<'a> {
+        Guard::new(Self { handle: *raw }, context)
+    }
+}
+
+/// Identifies a [`LinearViewObject`] among the children of its parent, so that a position in the
+/// linear view can be found again after the objects are recreated.
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct LinearViewObjectIdentifier {
+    pub name: String,
+    pub location: LinearViewObjectLocation,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum LinearViewObjectLocation {
+    /// The object is the only one with its name.
+    Single,
+    Address(u64),
+    AddressRange(Range<u64>),
+}
+
+impl LinearViewObjectIdentifier {
+    pub fn new(name: impl Into<String>, location: LinearViewObjectLocation) -> Self {
+        Self {
+            name: name.into(),
+            location,
+        }
+    }
+
+    pub(crate) fn from_raw(value: &BNLinearViewObjectIdentifier) -> Self {
+        let location = match value.type_ {
+            BNLinearViewObjectIdentifierType::SingleLinearViewObject => {
+                LinearViewObjectLocation::Single
+            }
+            BNLinearViewObjectIdentifierType::AddressLinearViewObject => {
+                LinearViewObjectLocation::Address(value.start)
+            }
+            BNLinearViewObjectIdentifierType::AddressRangeLinearViewObject => {
+                LinearViewObjectLocation::AddressRange(value.start..value.end)
+            }
+        };
+        Self {
+            name: raw_to_string(value.name).unwrap_or_default(),
+            location,
+        }
+    }
+
+    /// The raw identifier, borrowing `name` which must hold the name of the identifier.
+    pub(crate) fn as_raw(&self, name: &BnString) -> BNLinearViewObjectIdentifier {
+        let (type_, start, end) = match &self.location {
+            LinearViewObjectLocation::Single => (
+                BNLinearViewObjectIdentifierType::SingleLinearViewObject,
+                0,
+                0,
+            ),
+            LinearViewObjectLocation::Address(address) => (
+                BNLinearViewObjectIdentifierType::AddressLinearViewObject,
+                *address,
+                *address,
+            ),
+            LinearViewObjectLocation::AddressRange(range) => (
+                BNLinearViewObjectIdentifierType::AddressRangeLinearViewObject,
+                range.start,
+                range.end,
+            ),
+        };
+        BNLinearViewObjectIdentifier {
+            name: name.as_ptr() as *mut c_char,
+            type_,
+            start,
+            end,
+        }
+    }
+}
+
+fn path_names(path: &[LinearViewObjectIdentifier]) -> Vec<BnString> {
+    path.iter()
+        .map(|identifier| BnString::new(identifier.name.as_str()))
+        .collect()
+}
+
+/// The raw identifiers of `path`, borrowing the names from [`path_names`].
+fn raw_path(
+    path: &[LinearViewObjectIdentifier],
+    names: &[BnString],
+) -> Vec<BNLinearViewObjectIdentifier> {
+    path.iter()
+        .zip(names)
+        .map(|(identifier, name)| identifier.as_raw(name))
+        .collect()
+}
+
+impl CoreArrayProvider for LinearViewObjectIdentifier {
+    type Raw = BNLinearViewObjectIdentifier;
+    type Context = ();
+    type Wrapped<'a> = Self;
+}
+
+unsafe impl CoreArrayProviderInner for LinearViewObjectIdentifier {
+    unsafe fn free(raw: *mut Self::Raw, count: usize, _context: &Self::Context) {
+        BNFreeLinearViewCursorPath(raw, count)
+    }
+
+    unsafe fn wrap_raw<'a>(raw: &'a Self::Raw, _context: &'a Self::Context) -> Self::Wrapped<'a> {
+        Self::from_raw(raw)
+    }
+}
+
+/// Walks the lines of the leaves of a [`LinearViewObject`] tree in order.
 #[derive(Eq)]
 pub struct LinearViewCursor {
     pub(crate) handle: *mut BNLinearViewCursor,
@@ -276,7 +582,61 @@ impl LinearViewCursor {
     }
 
     pub fn seek_to_ordering_index(&self, idx: u64) {
-        unsafe { BNSeekLinearViewCursorToAddress(self.handle, idx) }
+        unsafe { BNSeekLinearViewCursorToOrderingIndex(self.handle, idx) }
+    }
+
+    /// The identifiers of the objects from the root to the current object.
+    pub fn path(&self) -> Array<LinearViewObjectIdentifier> {
+        let mut count = 0;
+        unsafe {
+            let result = BNGetLinearViewCursorPath(self.handle, &mut count);
+            Array::new(result, count, ())
+        }
+    }
+
+    /// The objects from the root to the current object.
+    pub fn path_objects(&self) -> Array<LinearViewObject> {
+        let mut count = 0;
+        unsafe {
+            let result = BNGetLinearViewCursorPathObjects(self.handle, &mut count);
+            Array::new(result, count, ())
+        }
+    }
+
+    /// Seek to the object at `path`, as returned by [`LinearViewCursor::path`], or to the closest
+    /// object if it no longer exists. Returns whether the object was found.
+    pub fn seek_to_path(&self, path: &[LinearViewObjectIdentifier]) -> bool {
+        let names = path_names(path);
+        let mut raw_path = raw_path(path, &names);
+        unsafe { BNSeekLinearViewCursorToPath(self.handle, raw_path.as_mut_ptr(), raw_path.len()) }
+    }
+
+    /// Seek to `address` within the object at `path`, see [`LinearViewCursor::seek_to_path`].
+    pub fn seek_to_path_and_address(
+        &self,
+        path: &[LinearViewObjectIdentifier],
+        address: u64,
+    ) -> bool {
+        let names = path_names(path);
+        let mut raw_path = raw_path(path, &names);
+        unsafe {
+            BNSeekLinearViewCursorToPathAndAddress(
+                self.handle,
+                raw_path.as_mut_ptr(),
+                raw_path.len(),
+                address,
+            )
+        }
+    }
+
+    /// Seek to the current object of `cursor`, which may be a cursor of another root object.
+    pub fn seek_to_cursor_path(&self, cursor: &LinearViewCursor) -> bool {
+        unsafe { BNSeekLinearViewCursorToCursorPath(self.handle, cursor.handle) }
+    }
+
+    /// Seek to `address` within the current object of `cursor`.
+    pub fn seek_to_cursor_path_and_address(&self, cursor: &LinearViewCursor, address: u64) -> bool {
+        unsafe { BNSeekLinearViewCursorToCursorPathAndAddress(self.handle, cursor.handle, address) }
     }
 
     pub fn previous(&self) -> bool {
@@ -30,16 +30,19 @@
 
 pub use binaryninjacore_sys::BNLogLevel as Level;
 use binaryninjacore_sys::{
-    BNFreeLogger, BNLogCreateLogger, BNLogListener, BNLogger, BNLoggerGetName,
-    BNLoggerGetSessionId, BNNewLoggerReference, BNUpdateLogListeners,
+    BNFreeLogger, BNLogCreateLogger, BNLogGetLogger, BNLogGetLoggerNames, BNLogListener,
+    BNLogToFile, BNLogToStderr, BNLogToStdout, BNLogger, BNLoggerDedent, BNLoggerGetName,
+    BNLoggerGetSessionId, BNLoggerIndent, BNLoggerLogString, BNLoggerResetIndent,
+    BNNewLoggerReference, BNUpdateLogListeners,
 };
 
-use crate::rc::{Ref, RefCountable};
-use crate::string::BnString;
+use crate::rc::{Array, Ref, RefCountable};
+use crate::string::{BnStrCompatible, BnString};
 use log;
 use log::LevelFilter;
 use std::ffi::{CStr, CString};
 use std::os::raw::{c_char, c_void};
+use std::path::Path;
 use std::ptr::NonNull;
 
 const LOGGER_DEFAULT_SESSION_ID: usize = 0;
@@ -66,6 +69,25 @@ impl Logger {
         }
     }
 
+    /// The logger named `name` in the session, if it was created.
+    pub fn get(name: &str, session_id: usize) -> Option<Ref<Logger>> {
+        let name_raw = CString::new(name).ok()?;
+        let handle = unsafe { BNLogGetLogger(name_raw.as_ptr(), session_id) };
+        NonNull::new(handle).map(|handle| unsafe {
+            Ref::new(Logger {
+                handle,
+                level: LevelFilter::Debug,
+            })
+        })
+    }
+
+    /// The names of all loggers that were created.
+    pub fn names() -> Array<BnString> {
+        let mut count = 0;
+        let names = unsafe { BNLogGetLoggerNames(&mut count) };
+        unsafe { Array::new(names, count, ()) }
+    }
+
     pub fn name(&self) -> BnString {
         unsafe { BnString::from_raw(BNLoggerGetName(self.handle.as_ptr())) }
     }
@@ -73,6 +95,26 @@ impl Logger {
     pub fn session_id(&self) -> usize {
         unsafe { BNLoggerGetSessionId(self.handle.as_ptr()) }
     }
+
+    /// Log `msg` with this logger, regardless of the level of the `log` facade.
+    pub fn log_string(&self, level: Level, msg: &str) {
+        if let Ok(msg) = CString::new(msg) {
+            unsafe { BNLoggerLogString(self.handle.as_ptr(), level, msg.as_ptr()) }
+        }
+    }
+
+    /// Indent the messages logged after this one more level.
+    pub fn indent(&self) {
+        unsafe { BNLoggerIndent(self.handle.as_ptr()) }
+    }
+
+    pub fn dedent(&self) {
+        unsafe { BNLoggerDedent(self.handle.as_ptr()) }
+    }
+
+    pub fn reset_indent(&self) {
+        unsafe { BNLoggerResetIndent(self.handle.as_ptr()) }
+    }
 }
 
 // NOTE: Due to the ref counted core object, we must impl on the ref counted object.
@@ -121,36 +163,25 @@ unsafe impl RefCountable for Logger {
 }
 
 impl log::Log for Ref<Logger> {
-    fn enabled(&self, _metadata: &log::Metadata) -> bool {
-        true
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= self.level
     }
 
     fn log(&self, record: &log::Record) {
         use self::Level::*;
-        use binaryninjacore_sys::BNLog;
         use log::Level;
 
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
         let level = match record.level() {
             Level::Error => ErrorLog,
             Level::Warn => WarningLog,
             Level::Info => InfoLog,
             Level::Debug | Level::Trace => DebugLog,
         };
-
-        if let Ok(msg) = CString::new(format!("{}", record.args())) {
-            let percent_s = CString::new("%s").expect("'%s' has no null bytes");
-            let logger_name = self.name();
-            unsafe {
-                BNLog(
-                    self.session_id(),
-                    level,
-                    logger_name.as_ptr(),
-                    0,
-                    percent_s.as_ptr(),
-                    msg.as_ptr(),
-                );
-            }
-        };
+        self.log_string(level, &record.args().to_string());
     }
 
     fn flush(&self) {}
@@ -159,6 +190,9 @@ impl log::Log for Ref<Logger> {
 unsafe impl Send for Logger {}
 unsafe impl Sync for Logger {}
 
+/// Receives the messages logged in the core, e.g. to capture them in tests and headless runs.
+///
+/// Messages below [`LogListener::level`] aren't sent to the listener.
 pub trait LogListener: 'static + Sync {
     fn log(&self, session: usize, level: Level, msg: &CStr, logger_name: &CStr, tid: usize);
     fn level(&self) -> Level;
@@ -249,3 +283,20 @@ where
         listener.level()
     })
 }
+
+/// Print the messages at or above `level` to stdout.
+pub fn log_to_stdout(level: Level) {
+    unsafe { BNLogToStdout(level) }
+}
+
+/// Print the messages at or above `level` to stderr.
+pub fn log_to_stderr(level: Level) {
+    unsafe { BNLogToStderr(level) }
+}
+
+/// Write the messages at or above `level` to the file at `path`, returns `false` if the file
+/// can't be opened.
+pub fn log_to_file<P: AsRef<Path>>(level: Level, path: P, append: bool) -> bool {
+    let path = path.as_ref().into_bytes_with_nul();
+    unsafe { BNLogToFile(level, path.as_ptr() as *const c_char, append) }
+}
@@ -12,6 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use binaryninjacore_sys::BNCreateLowLevelILFunctionGraph;
 use binaryninjacore_sys::BNFreeLowLevelILFunction;
 use binaryninjacore_sys::BNGetLowLevelILOwnerFunction;
 use binaryninjacore_sys::BNLowLevelILFunction;
@@ -24,6 +25,8 @@ use std::marker::PhantomData;
 
 use crate::architecture::CoreArchitecture;
 use crate::basic_block::BasicBlock;
+use crate::disassembly::DisassemblySettings;
+use crate::flowgraph::FlowGraph;
 use crate::function::Function;
 use crate::low_level_il::block::LowLevelILBlock;
 use crate::rc::*;
@@ -176,6 +179,25 @@ where
             Array::new(blocks, count, context)
         }
     }
+
+    pub fn create_graph(&self, settings: Option<&DisassemblySettings>) -> Ref<FlowGraph> {
+        let settings = settings.map_or(std::ptr::null_mut(), |settings| settings.handle);
+        let graph = unsafe { BNCreateLowLevelILFunctionGraph(self.handle, settings) };
+        unsafe { Ref::new(FlowGraph::from_raw(graph)) }
+    }
+}
+
+impl<A, V> LowLevelILFunction<A, Mutable, NonSSA<V>>
+where
+    A: Architecture,
+    V: NonSSAVariant,
+{
+    /// Regenerate the SSA form, required after the IL has been modified in place, such as from a
+    /// workflow activity.
+    pub fn generate_ssa_form(&self) {
+        use binaryninjacore_sys::BNGenerateLowLevelILSSAForm;
+        unsafe { BNGenerateLowLevelILSSAForm(self.handle) }
+    }
 }
 
 // Allow instantiating Lifted IL functions for querying Lifted IL from Architectures
@@ -1069,6 +1069,60 @@ where
         self.const_ptr_sized(self.arch().address_size(), val)
     }
 
+    pub fn float_const_single(
+        &self,
+        val: f32,
+    ) -> LowLevelILExpression<A, Mutable, NonSSA<LiftedNonSSA>, ValueExpr> {
+        use binaryninjacore_sys::BNLowLevelILAddExpr;
+        use binaryninjacore_sys::BNLowLevelILOperation::LLIL_FLOAT_CONST;
+
+        let expr_idx = unsafe {
+            BNLowLevelILAddExpr(
+                self.handle,
+                LLIL_FLOAT_CONST,
+                4,
+                0,
+                val.to_bits() as u64,
+                0,
+                0,
+                0,
+            )
+        };
+
+        LowLevelILExpression::new(self, LowLevelExpressionIndex(expr_idx))
+    }
+
+    pub fn float_const_double(
+        &self,
+        val: f64,
+    ) -> LowLevelILExpression<A, Mutable, NonSSA<LiftedNonSSA>, ValueExpr> {
+        use binaryninjacore_sys::BNLowLevelILAddExpr;
+        use binaryninjacore_sys::BNLowLevelILOperation::LLIL_FLOAT_CONST;
+
+        let expr_idx = unsafe {
+            BNLowLevelILAddExpr(self.handle, LLIL_FLOAT_CONST, 8, 0, val.to_bits(), 0, 0, 0)
+        };
+
+        LowLevelILExpression::new(self, LowLevelExpressionIndex(expr_idx))
+    }
+
+    /// A pointer `offset` bytes into the external symbol at `val`.
+    pub fn extern_ptr(
+        &self,
+        size: usize,
+        val: u64,
+        offset: u64,
+    ) -> LowLevelILExpression<A, Mutable, NonSSA<LiftedNonSSA>, ValueExpr> {
+        use binaryninjacore_sys::BNLowLevelILAddExpr;
+        use binaryninjacore_sys::BNLowLevelILOperation::LLIL_EXTERN_PTR;
+
+        let expr_idx = unsafe {
+            BNLowLevelILAddExpr(self.handle, LLIL_EXTERN_PTR, size, 0, val, offset, 0, 0)
+        };
+
+        LowLevelILExpression::new(self, LowLevelExpressionIndex(expr_idx))
+    }
+
     pub fn trap(
         &self,
         val: u64,
@@ -1092,6 +1146,38 @@ where
     unsized_unary_op_lifter!(call, LLIL_CALL, VoidExpr);
     unsized_unary_op_lifter!(ret, LLIL_RET, VoidExpr);
     unsized_unary_op_lifter!(jump, LLIL_JUMP, VoidExpr);
+    unsized_unary_op_lifter!(tailcall, LLIL_TAILCALL, VoidExpr);
+
+    /// A call after which the callee has adjusted the stack pointer by `stack_adjust` bytes, for
+    /// calling conventions where the callee cleans up the stack.
+    pub fn call_stack_adjust<'a, E>(
+        &'a self,
+        expr: E,
+        stack_adjust: i64,
+    ) -> LowLevelILExpression<'a, A, Mutable, NonSSA<LiftedNonSSA>, VoidExpr>
+    where
+        E: LiftableLowLevelIL<'a, A, Result = ValueExpr>,
+    {
+        use binaryninjacore_sys::BNLowLevelILAddExpr;
+        use binaryninjacore_sys::BNLowLevelILOperation::LLIL_CALL_STACK_ADJUST;
+
+        let expr = E::lift(self, expr);
+
+        let expr_idx = unsafe {
+            BNLowLevelILAddExpr(
+                self.handle,
+                LLIL_CALL_STACK_ADJUST,
+                0,
+                0,
+                expr.index.0 as u64,
+                stack_adjust as u64,
+                0,
+                0,
+            )
+        };
+
+        LowLevelILExpression::new(self, LowLevelExpressionIndex(expr_idx))
+    }
     // TODO: LLIL_JUMP_TO
 
     pub fn if_expr<'a: 'b, 'b, C>(
@@ -1156,6 +1242,49 @@ where
         LowLevelILExpression::new(self, LowLevelExpressionIndex(expr_idx))
     }
 
+    /// Append a branch to `target`, a `goto` if the instruction at `target` was already lifted
+    /// into this function, a `jump` otherwise.
+    pub fn goto_address<L: Into<Location>>(&self, target: L) {
+        let target: Location = target.into();
+        match self.label_for_address(target) {
+            Some(mut label) => self.goto(&mut label).append(),
+            None => self.jump(self.const_ptr(target.addr)).append(),
+        }
+    }
+
+    /// Append a conditional branch to `true_target` if `cond` holds, and to `false_target`
+    /// otherwise, which is usually the next instruction.
+    ///
+    /// Labels of targets already lifted into this function are reused, for the others a label is
+    /// placed right after the `if`, followed by a `jump` for `true_target`.
+    pub fn branch_if<'a, C, T, F>(&'a self, cond: C, true_target: T, false_target: F)
+    where
+        C: LiftableLowLevelIL<'a, A, Result = ValueExpr>,
+        T: Into<Location>,
+        F: Into<Location>,
+    {
+        let true_target: Location = true_target.into();
+        let false_target: Location = false_target.into();
+        let existing_true = self.label_for_address(true_target);
+        let existing_false = self.label_for_address(false_target);
+        let new_true = existing_true.is_none();
+        let new_false = existing_false.is_none();
+        let mut true_label = existing_true.unwrap_or_default();
+        let mut false_label = existing_false.unwrap_or_default();
+
+        let cond = C::lift(self, cond);
+        self.if_expr(cond, &mut true_label, &mut false_label)
+            .append();
+
+        if new_true {
+            self.mark_label(&mut true_label);
+            self.jump(self.const_ptr(true_target.addr)).append();
+        }
+        if new_false {
+            self.mark_label(&mut false_label);
+        }
+    }
+
     pub fn reg<R: Into<LowLevelILRegister<A::Register>>>(
         &self,
         size: usize,
@@ -1299,6 +1428,33 @@ where
         LowLevelILExpression::new(self, LowLevelExpressionIndex(expr_idx))
     }
 
+    /// The value of `flag`, shifted to bit `bit_index` of a `size` byte integer.
+    pub fn flag_bit(
+        &self,
+        size: usize,
+        flag: A::Flag,
+        bit_index: u64,
+    ) -> LowLevelILExpression<A, Mutable, NonSSA<LiftedNonSSA>, ValueExpr> {
+        use binaryninjacore_sys::BNLowLevelILAddExpr;
+        use binaryninjacore_sys::BNLowLevelILOperation::LLIL_FLAG_BIT;
+
+        // TODO verify valid id
+        let expr_idx = unsafe {
+            BNLowLevelILAddExpr(
+                self.handle,
+                LLIL_FLAG_BIT,
+                size,
+                0,
+                flag.id().0 as u64,
+                bit_index,
+                0,
+                0,
+            )
+        };
+
+        LowLevelILExpression::new(self, LowLevelExpressionIndex(expr_idx))
+    }
+
     pub fn flag_group(
         &self,
         group: A::FlagGroup,
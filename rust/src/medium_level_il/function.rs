@@ -1,5 +1,4 @@
 use binaryninjacore_sys::*;
-use std::ffi::c_char;
 use std::fmt::{Debug, Formatter};
 use std::hash::{Hash, Hasher};
 
@@ -87,10 +86,36 @@ impl MediumLevelILFunction {
         unsafe { BNGetMediumLevelILInstructionCount(self.handle) }
     }
 
+    /// All instructions of the function in index order, each with the address it was lifted from.
+    pub fn instructions(&self) -> impl Iterator<Item = MediumLevelILInstruction> + '_ {
+        (0..self.instruction_count())
+            .filter_map(|index| self.instruction_from_index(MediumLevelInstructionIndex(index)))
+    }
+
     pub fn expression_count(&self) -> usize {
         unsafe { BNGetMediumLevelILExprCount(self.handle) }
     }
 
+    /// Replace the expression at `expr_index` with the one at `replacement`, for instance with one
+    /// of its own sub expressions to rewrite it.
+    ///
+    /// # Safety
+    ///
+    /// The replacement must have a compatible operation and size, the function must be regenerated
+    /// (see [`MediumLevelILFunction::finalize`] and [`MediumLevelILFunction::generate_ssa_form`])
+    /// before the change takes effect.
+    pub unsafe fn replace_expression(
+        &self,
+        expr_index: MediumLevelInstructionIndex,
+        replacement: MediumLevelInstructionIndex,
+    ) -> bool {
+        if expr_index.0 >= self.expression_count() || replacement.0 >= self.expression_count() {
+            return false;
+        }
+        BNReplaceMediumLevelILExpr(self.handle, expr_index.0, replacement.0);
+        true
+    }
+
     pub fn ssa_form(&self) -> MediumLevelILFunction {
         let ssa = unsafe { BNGetMediumLevelILSSAForm(self.handle) };
         assert!(!ssa.is_null());
@@ -128,20 +153,12 @@ impl MediumLevelILFunction {
         var_type: C,
         name: S,
     ) {
-        let mut owned_raw_var_ty = Conf::<&Type>::into_raw(var_type.into());
-        let name = name.into_bytes_with_nul();
-        unsafe {
-            BNCreateUserStackVariable(
-                self.function().handle,
-                offset,
-                &mut owned_raw_var_ty,
-                name.as_ref().as_ptr() as *const c_char,
-            )
-        }
+        self.function()
+            .create_user_stack_var(offset, var_type, name)
     }
 
     pub fn delete_user_stack_var(self, offset: i64) {
-        unsafe { BNDeleteUserStackVariable(self.function().handle, offset) }
+        self.function().delete_user_stack_var(offset)
     }
 
     pub fn create_user_var<'a, S: BnStrCompatible, C: Into<Conf<&'a Type>>>(
@@ -151,23 +168,12 @@ impl MediumLevelILFunction {
         name: S,
         ignore_disjoint_uses: bool,
     ) {
-        let raw_var = BNVariable::from(var);
-        let mut owned_raw_var_ty = Conf::<&Type>::into_raw(var_type.into());
-        let name = name.into_bytes_with_nul();
-        unsafe {
-            BNCreateUserVariable(
-                self.function().handle,
-                &raw_var,
-                &mut owned_raw_var_ty,
-                name.as_ref().as_ptr() as *const _,
-                ignore_disjoint_uses,
-            )
-        }
+        self.function()
+            .create_user_var(var, var_type, name, ignore_disjoint_uses)
     }
 
     pub fn delete_user_var(&self, var: &Variable) {
-        let raw_var = BNVariable::from(var);
-        unsafe { BNDeleteUserVariable(self.function().handle, &raw_var) }
+        self.function().delete_user_var(var)
     }
 
     pub fn is_var_user_defined(&self, var: &Variable) -> bool {
@@ -275,21 +281,12 @@ impl MediumLevelILFunction {
         var_type: T,
         name: S,
     ) {
-        let mut owned_raw_var_ty = Conf::<&Type>::into_raw(var_type.into());
-        let name = name.into_bytes_with_nul();
-        let name_c_str = name.as_ref();
-        unsafe {
-            BNCreateAutoStackVariable(
-                self.function().handle,
-                offset,
-                &mut owned_raw_var_ty,
-                name_c_str.as_ptr() as *const c_char,
-            )
-        }
+        self.function()
+            .create_auto_stack_var(offset, var_type, name)
     }
 
     pub fn delete_auto_stack_var(&self, offset: i64) {
-        unsafe { BNDeleteAutoStackVariable(self.function().handle, offset) }
+        self.function().delete_auto_stack_var(offset)
     }
 
     pub fn create_auto_var<'a, S: BnStrCompatible, C: Into<Conf<&'a Type>>>(
@@ -299,19 +296,8 @@ impl MediumLevelILFunction {
         name: S,
         ignore_disjoint_uses: bool,
     ) {
-        let raw_var = BNVariable::from(var);
-        let mut owned_raw_var_ty = Conf::<&Type>::into_raw(var_type.into());
-        let name = name.into_bytes_with_nul();
-        let name_c_str = name.as_ref();
-        unsafe {
-            BNCreateAutoVariable(
-                self.function().handle,
-                &raw_var,
-                &mut owned_raw_var_ty,
-                name_c_str.as_ptr() as *const c_char,
-                ignore_disjoint_uses,
-            )
-        }
+        self.function()
+            .create_auto_var(var, var_type, name, ignore_disjoint_uses)
     }
 
     /// Returns a list of ILReferenceSource objects (IL xrefs or cross-references)
@@ -548,10 +534,10 @@ impl MediumLevelILFunction {
         unsafe { BNGetMediumLevelILSSAVarValue(self.handle, &raw_var, ssa_variable.version) }.into()
     }
 
-    pub fn create_graph(&self, settings: Option<DisassemblySettings>) -> FlowGraph {
+    pub fn create_graph(&self, settings: Option<&DisassemblySettings>) -> Ref<FlowGraph> {
         let settings = settings.map(|x| x.handle).unwrap_or(std::ptr::null_mut());
         let graph = unsafe { BNCreateMediumLevelILFunctionGraph(self.handle, settings) };
-        unsafe { FlowGraph::from_raw(graph) }
+        unsafe { Ref::new(FlowGraph::from_raw(graph)) }
     }
 
     /// This gets just the MLIL variables - you may be interested in the union
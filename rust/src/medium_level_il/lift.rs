@@ -1,6 +1,7 @@
 use std::collections::BTreeMap;
 
 use super::operation::*;
+use super::VisitorAction;
 use super::{MediumLevelILFunction, MediumLevelInstructionIndex};
 use crate::architecture::CoreIntrinsic;
 use crate::rc::Ref;
@@ -303,6 +304,66 @@ impl MediumLevelILLiftedInstruction {
         }
     }
 
+    /// The expressions directly used by this one, in operand order.
+    pub fn sub_expressions(&self) -> Vec<MediumLevelILLiftedInstruction> {
+        self.operands()
+            .into_iter()
+            .flat_map(|(_, operand)| match operand {
+                MediumLevelILLiftedOperand::Expr(expr) => vec![expr],
+                MediumLevelILLiftedOperand::ExprList(exprs) => exprs,
+                _ => vec![],
+            })
+            .collect()
+    }
+
+    /// Visit this expression and then its sub expressions, depth first, for as long as `f`
+    /// returns [`VisitorAction::Descend`].
+    ///
+    /// [`VisitorAction::Sibling`] skips the sub expressions of the visited expression and
+    /// [`VisitorAction::Halt`] stops the traversal, which is then also returned.
+    pub fn visit_tree<T>(&self, f: &mut T) -> VisitorAction
+    where
+        T: FnMut(&MediumLevelILLiftedInstruction) -> VisitorAction,
+    {
+        match f(self) {
+            VisitorAction::Descend => {
+                for sub_expr in self.sub_expressions() {
+                    if sub_expr.visit_tree(f) == VisitorAction::Halt {
+                        return VisitorAction::Halt;
+                    }
+                }
+                VisitorAction::Sibling
+            }
+            action => action,
+        }
+    }
+
+    /// Visit the sub expressions of this expression, depth first, and then this expression.
+    pub fn visit_tree_post_order<T>(&self, f: &mut T)
+    where
+        T: FnMut(&MediumLevelILLiftedInstruction),
+    {
+        for sub_expr in self.sub_expressions() {
+            sub_expr.visit_tree_post_order(f);
+        }
+        f(self);
+    }
+
+    /// This expression and its sub expressions matching `predicate`, in pre-order.
+    pub fn find_all<P>(&self, mut predicate: P) -> Vec<MediumLevelILLiftedInstruction>
+    where
+        P: FnMut(&MediumLevelILLiftedInstruction) -> bool,
+    {
+        let mut found = Vec::new();
+        self.visit_tree(&mut |expr| {
+            if predicate(expr) {
+                found.push(expr.clone());
+            }
+            VisitorAction::Descend
+        });
+        found
+    }
+
     pub fn operands(&self) -> Vec<(&'static str, MediumLevelILLiftedOperand)> {
         use MediumLevelILLiftedInstructionKind::*;
         use MediumLevelILLiftedOperand as Operand;
@@ -0,0 +1,393 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The memory map of a view, describing which object provides the bytes at each address.
+
+use std::ffi::c_char;
+use std::ops::Range;
+
+use binaryninjacore_sys::*;
+
+use crate::binary_view::{BinaryView, BinaryViewExt};
+use crate::data_buffer::DataBuffer;
+use crate::rc::Ref;
+use crate::string::{BnStrCompatible, BnString};
+use crate::Error;
+
+/// Where the bytes of a [`MemoryRegionObject`] come from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MemoryRegionBacking {
+    /// Mapped from the file of the view at absolute offsets, such as the segments of the view.
+    File,
+    /// Mapped from a buffer or view of its own, such as a region added at runtime.
+    Data,
+    /// Not backed by any data, reads return the fill byte.
+    Unbacked { fill: u8 },
+}
+
+/// One of the objects making up a [`MemoryRegion`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MemoryRegionObject {
+    pub name: String,
+    pub backing: MemoryRegionBacking,
+    /// The `BNSegmentFlag` bits of the object.
+    pub flags: u32,
+    pub enabled: bool,
+}
+
+/// A range of addresses provided by the same stack of objects.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MemoryRegion {
+    pub address_range: Range<u64>,
+    /// The objects covering the range, with the one providing its bytes first.
+    pub objects: Vec<MemoryRegionObject>,
+}
+
+impl MemoryRegion {
+    /// The enabled object that provides the bytes of the range.
+    pub fn active_object(&self) -> Option<&MemoryRegionObject> {
+        self.objects.iter().find(|object| object.enabled)
+    }
+}
+
+/// The memory map of a view, describing which object provides the bytes at each address.
+///
+/// Besides the segments of the view, named regions can be added at runtime, for example to map
+/// a ROM dump next to the firmware image it belongs to. The most recently added region wins where
+/// regions overlap:
+///
+/// ```no_run
+/// use binaryninja::binary_view::BinaryViewExt;
+/// use binaryninja::data_buffer::DataBuffer;
+///
+/// let view = binaryninja::load("firmware.bin").unwrap();
+/// let memory_map = view.memory_map();
+/// let rom = std::fs::read("rom.bin").unwrap();
+/// memory_map
+///     .add_data_region("rom", 0xc000_0000, &DataBuffer::new(&rom).unwrap(), 0x4 | 0x1)
+///     .unwrap();
+/// println!("{}", memory_map.description());
+/// assert_eq!(memory_map.active_region_at(0xc000_0000).as_deref(), Some("rom"));
+/// ```
+pub struct MemoryMap {
+    view: Ref<BinaryView>,
+}
+
+impl MemoryMap {
+    pub fn new(view: &BinaryView) -> Self {
+        Self {
+            view: view.to_owned(),
+        }
+    }
+
+    /// The JSON description of the memory map, resolving overlaps of every region.
+    pub fn description(&self) -> String {
+        unsafe { BnString::from_raw(BNGetMemoryMapDescription(self.view.handle)) }.to_string()
+    }
+
+    /// The JSON description of the memory map made only from the auto and user segments.
+    pub fn base_description(&self) -> String {
+        unsafe { BnString::from_raw(BNGetBaseMemoryMapDescription(self.view.handle)) }.to_string()
+    }
+
+    /// The regions of the memory map in address order.
+    #[cfg(feature = "serde")]
+    pub fn regions(&self) -> Vec<MemoryRegion> {
+        parse_regions(&self.description()).unwrap_or_else(|err| {
+            log::error!("Failed to parse the memory map description: {}", err);
+            Vec::new()
+        })
+    }
+
+    /// The region containing `addr`, if it is mapped.
+    #[cfg(feature = "serde")]
+    pub fn region_at(&self, addr: u64) -> Option<MemoryRegion> {
+        self.regions()
+            .into_iter()
+            .find(|region| region.address_range.contains(&addr))
+    }
+
+    /// The name of the region that provides the byte at `addr`.
+    pub fn active_region_at(&self, addr: u64) -> Option<String> {
+        let name = unsafe { BnString::from_raw(BNGetActiveMemoryRegionAt(self.view.handle, addr)) };
+        (!name.is_empty()).then(|| name.to_string())
+    }
+
+    /// Show merged regions of matching flags instead of every mapped segment.
+    pub fn set_logical_enabled(&self, enabled: bool) {
+        unsafe { BNSetLogicalMemoryMapEnabled(self.view.handle, enabled) }
+    }
+
+    /// Map a copy of `data` at `start` as the region `name`.
+    ///
+    /// With `flags` of 0 the region takes on the flags of the regions it overlaps.
+    pub fn add_data_region<S: BnStrCompatible>(
+        &self,
+        name: S,
+        start: u64,
+        data: &DataBuffer,
+        flags: u32,
+    ) -> Result<(), Error> {
+        let name = name.into_bytes_with_nul();
+        let added = unsafe {
+            BNAddDataMemoryRegion(
+                self.view.handle,
+                name.as_ref().as_ptr() as *const c_char,
+                start,
+                data.as_raw(),
+                flags,
+            )
+        };
+        added
+            .then_some(())
+            .ok_or(Error::CoreCallFailed("BNAddDataMemoryRegion"))
+    }
+
+    /// Map the contents of `source` at `start` as the region `name`, see
+    /// [`MemoryMap::add_data_region`].
+    pub fn add_binary_region<S: BnStrCompatible>(
+        &self,
+        name: S,
+        start: u64,
+        source: &BinaryView,
+        flags: u32,
+    ) -> Result<(), Error> {
+        let name = name.into_bytes_with_nul();
+        let added = unsafe {
+            BNAddBinaryMemoryRegion(
+                self.view.handle,
+                name.as_ref().as_ptr() as *const c_char,
+                start,
+                source.handle,
+                flags,
+            )
+        };
+        added
+            .then_some(())
+            .ok_or(Error::CoreCallFailed("BNAddBinaryMemoryRegion"))
+    }
+
+    /// Map a copy of the bytes of the view in `source` at `start` as the region `name`, see
+    /// [`MemoryMap::add_data_region`].
+    ///
+    /// The bytes are copied when the region is added, later writes to `source` are not reflected.
+    pub fn add_remapped_region<S: BnStrCompatible>(
+        &self,
+        name: S,
+        start: u64,
+        source: Range<u64>,
+        flags: u32,
+    ) -> Result<(), Error> {
+        let len = source.end.saturating_sub(source.start) as usize;
+        let data = self.view.read_buffer(source.start, len)?;
+        if data.len() != len {
+            return Err(Error::InvalidArgument(format!(
+                "{:#x}..{:#x} is not readable",
+                source.start, source.end
+            )));
+        }
+        self.add_data_region(name, start, &data, flags)
+    }
+
+    /// Remove the region `name`, returning whether it existed.
+    pub fn remove_region<S: BnStrCompatible>(&self, name: S) -> bool {
+        let name = name.into_bytes_with_nul();
+        unsafe { BNRemoveMemoryRegion(self.view.handle, name.as_ref().as_ptr() as *const c_char) }
+    }
+
+    pub fn region_flags<S: BnStrCompatible>(&self, name: S) -> u32 {
+        let name = name.into_bytes_with_nul();
+        unsafe { BNGetMemoryRegionFlags(self.view.handle, name.as_ref().as_ptr() as *const c_char) }
+    }
+
+    pub fn set_region_flags<S: BnStrCompatible>(&self, name: S, flags: u32) -> bool {
+        let name = name.into_bytes_with_nul();
+        unsafe {
+            BNSetMemoryRegionFlags(
+                self.view.handle,
+                name.as_ref().as_ptr() as *const c_char,
+                flags,
+            )
+        }
+    }
+
+    pub fn is_region_enabled<S: BnStrCompatible>(&self, name: S) -> bool {
+        let name = name.into_bytes_with_nul();
+        unsafe {
+            BNIsMemoryRegionEnabled(self.view.handle, name.as_ref().as_ptr() as *const c_char)
+        }
+    }
+
+    /// Enable or disable the region `name`, a disabled region provides no bytes.
+    pub fn set_region_enabled<S: BnStrCompatible>(&self, name: S, enabled: bool) -> bool {
+        let name = name.into_bytes_with_nul();
+        unsafe {
+            BNSetMemoryRegionEnabled(
+                self.view.handle,
+                name.as_ref().as_ptr() as *const c_char,
+                enabled,
+            )
+        }
+    }
+
+    pub fn is_region_rebaseable<S: BnStrCompatible>(&self, name: S) -> bool {
+        let name = name.into_bytes_with_nul();
+        unsafe {
+            BNIsMemoryRegionRebaseable(self.view.handle, name.as_ref().as_ptr() as *const c_char)
+        }
+    }
+
+    /// Whether the region `name` moves along when the view is rebased.
+    pub fn set_region_rebaseable<S: BnStrCompatible>(&self, name: S, rebaseable: bool) -> bool {
+        let name = name.into_bytes_with_nul();
+        unsafe {
+            BNSetMemoryRegionRebaseable(
+                self.view.handle,
+                name.as_ref().as_ptr() as *const c_char,
+                rebaseable,
+            )
+        }
+    }
+
+    pub fn region_fill<S: BnStrCompatible>(&self, name: S) -> u8 {
+        let name = name.into_bytes_with_nul();
+        unsafe { BNGetMemoryRegionFill(self.view.handle, name.as_ref().as_ptr() as *const c_char) }
+    }
+
+    /// The byte read from the parts of the region `name` without backing data.
+    pub fn set_region_fill<S: BnStrCompatible>(&self, name: S, fill: u8) -> bool {
+        let name = name.into_bytes_with_nul();
+        unsafe {
+            BNSetMemoryRegionFill(
+                self.view.handle,
+                name.as_ref().as_ptr() as *const c_char,
+                fill,
+            )
+        }
+    }
+
+    /// Remove every region added at runtime, leaving only the segments of the view.
+    pub fn reset(&self) {
+        unsafe { BNResetMemoryMap(self.view.handle) }
+    }
+}
+
+/// Parse the regions of a memory map description.
+#[cfg(feature = "serde")]
+fn parse_regions(description: &str) -> Result<Vec<MemoryRegion>, Error> {
+    let json: serde_json::Value = serde_json::from_str(description)
+        .map_err(|err| Error::Parse(format!("memory map description: {}", err)))?;
+    let Some(entries) = json.get("MemoryMap").and_then(|entries| entries.as_array()) else {
+        return Ok(Vec::new());
+    };
+    entries.iter().map(parse_region).collect()
+}
+
+#[cfg(feature = "serde")]
+fn parse_region(entry: &serde_json::Value) -> Result<MemoryRegion, Error> {
+    let address = json_u64(entry, "address")?;
+    let length = json_u64(entry, "length")?;
+    let objects = match entry.get("objects").and_then(|objects| objects.as_array()) {
+        Some(objects) => objects.iter().map(parse_object).collect::<Result<_, _>>()?,
+        None => Vec::new(),
+    };
+    Ok(MemoryRegion {
+        address_range: address..address.wrapping_add(length),
+        objects,
+    })
+}
+
+#[cfg(feature = "serde")]
+fn parse_object(object: &serde_json::Value) -> Result<MemoryRegionObject, Error> {
+    use serde_json::Value;
+
+    // The target is the object the bytes are mapped from, if any
+    let mapped = match object.get("target") {
+        None | Some(Value::Null) => false,
+        Some(Value::Bool(mapped)) => *mapped,
+        Some(Value::String(target)) => !target.is_empty(),
+        Some(_) => true,
+    };
+    let absolute = object
+        .get("absolute_address_mode")
+        .and_then(|absolute| absolute.as_bool())
+        .unwrap_or(false);
+    let backing = match (mapped, absolute) {
+        (true, true) => MemoryRegionBacking::File,
+        (true, false) => MemoryRegionBacking::Data,
+        (false, _) => MemoryRegionBacking::Unbacked {
+            fill: json_u64(object, "fill").unwrap_or(0) as u8,
+        },
+    };
+    Ok(MemoryRegionObject {
+        name: object
+            .get("name")
+            .and_then(|name| name.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        backing,
+        flags: json_u64(object, "flags")? as u32,
+        enabled: object
+            .get("enabled")
+            .and_then(|enabled| enabled.as_bool())
+            .unwrap_or(true),
+    })
+}
+
+#[cfg(feature = "serde")]
+fn json_u64(value: &serde_json::Value, key: &str) -> Result<u64, Error> {
+    value
+        .get(key)
+        .and_then(|field| field.as_u64())
+        .ok_or_else(|| Error::Parse(format!("memory map description: entry without `{}`", key)))
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_description() {
+        let description = r#"{"MemoryMap": [
+            {"address": 65536, "length": 4, "objects": [
+                {"name": "origin<Mapped>@0x0", "target": true, "absolute_address_mode": true,
+                 "flags": 5, "fill": 0, "enabled": true}
+            ]},
+            {"address": 3221225472, "length": 8, "objects": [
+                {"name": "rom", "target": true, "absolute_address_mode": false, "flags": 5,
+                 "fill": 0, "enabled": false},
+                {"name": "bss", "target": null, "absolute_address_mode": false, "flags": 6,
+                 "fill": 165, "enabled": true}
+            ]}
+        ]}"#;
+        let regions = parse_regions(description).unwrap();
+        assert_eq!(regions.len(), 2);
+        assert_eq!(regions[0].address_range, 0x10000..0x10004);
+        assert_eq!(regions[0].objects[0].backing, MemoryRegionBacking::File);
+        assert_eq!(regions[0].objects[0].flags, 5);
+
+        let objects = &regions[1].objects;
+        assert_eq!(objects[0].backing, MemoryRegionBacking::Data);
+        assert!(!objects[0].enabled);
+        assert_eq!(
+            objects[1].backing,
+            MemoryRegionBacking::Unbacked { fill: 0xa5 }
+        );
+        assert_eq!(regions[1].active_object().unwrap().name, "bss");
+
+        assert!(parse_regions("{}").unwrap().is_empty());
+        assert!(parse_regions("not json").is_err());
+        assert!(parse_regions(r#"{"MemoryMap": [{"length": 1}]}"#).is_err());
+    }
+}
@@ -5,6 +5,12 @@ use std::collections::HashMap;
 use std::os::raw::c_char;
 use std::slice;
 
+#[cfg(feature = "serde")]
+mod serde;
+
+#[cfg(feature = "serde")]
+pub use self::serde::{from_metadata, to_metadata};
+
 pub type MetadataType = BNMetadataType;
 
 pub struct Metadata {
@@ -0,0 +1,682 @@
+//! Conversion of serializable values to and from [`Metadata`].
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use ::serde::de::{self, DeserializeOwned, IntoDeserializer, Visitor};
+use ::serde::forward_to_deserialize_any;
+use ::serde::ser::{self, Serialize};
+
+use crate::error::{Error, Result};
+use crate::metadata::{Metadata, MetadataType};
+use crate::rc::Ref;
+
+/// Serialize `value` into metadata.
+///
+/// Structs and maps become key-value stores, sequences and tuples arrays, and byte buffers
+/// serialized as bytes (e.g. with `serde_bytes`) raw data. Absent values, such as a `None` field,
+/// are left out of the store they would be a value of. Enum variants without data are stored as
+/// their name, other variants as a store with their name as the single key.
+///
+/// ```no_run
+/// use std::collections::HashMap;
+/// use binaryninja::metadata::{from_metadata, to_metadata};
+///
+/// let limits = HashMap::from([("main".to_string(), vec![1u64, 2])]);
+/// let metadata = to_metadata(&limits).unwrap();
+/// let read: HashMap<String, Vec<u64>> = from_metadata(&metadata).unwrap();
+/// assert_eq!(read, limits);
+/// ```
+pub fn to_metadata<T: Serialize + ?Sized>(value: &T) -> Result<Ref<Metadata>> {
+    value
+        .serialize(MetadataSerializer)?
+        .ok_or_else(|| Error::InvalidArgument("an absent value has no metadata".into()))
+}
+
+/// Deserialize a `T` from `metadata`, see [`to_metadata`].
+pub fn from_metadata<T: DeserializeOwned>(metadata: &Metadata) -> Result<T> {
+    T::deserialize(MetadataDeserializer(metadata.to_owned()))
+}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::InvalidArgument(msg.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error::Parse(msg.to_string())
+    }
+}
+
+/// Serializes a value into its metadata, `None` if the value is absent.
+struct MetadataSerializer;
+
+fn present<T: Into<Ref<Metadata>>>(value: T) -> Result<Option<Ref<Metadata>>> {
+    Ok(Some(value.into()))
+}
+
+fn store(entries: HashMap<String, Ref<Metadata>>) -> Ref<Metadata> {
+    entries.into()
+}
+
+impl ser::Serializer for MetadataSerializer {
+    type Ok = Option<Ref<Metadata>>;
+    type Error = Error;
+    type SerializeSeq = SeqSerializer;
+    type SerializeTuple = SeqSerializer;
+    type SerializeTupleStruct = SeqSerializer;
+    type SerializeTupleVariant = SeqSerializer;
+    type SerializeMap = MapSerializer;
+    type SerializeStruct = MapSerializer;
+    type SerializeStructVariant = MapSerializer;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok> {
+        present(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok> {
+        present(v as i64)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok> {
+        present(v as i64)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok> {
+        present(v as i64)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok> {
+        present(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok> {
+        present(v as u64)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok> {
+        present(v as u64)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok> {
+        present(v as u64)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok> {
+        present(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok> {
+        present(v as f64)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok> {
+        present(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok> {
+        present(v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok> {
+        present(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok> {
+        present(&v.to_vec())
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok> {
+        present(&Vec::<Ref<Metadata>>::new())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok> {
+        present(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok> {
+        let value = value.serialize(MetadataSerializer)?.ok_or_else(|| {
+            Error::InvalidArgument(format!("the data of variant `{}` is absent", variant))
+        })?;
+        Ok(Some(wrap_variant(Some(variant), value)))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Ok(SeqSerializer {
+            variant: None,
+            items: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Ok(SeqSerializer {
+            variant: Some(variant),
+            items: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Ok(MapSerializer {
+            variant: None,
+            entries: HashMap::new(),
+            next_key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Ok(MapSerializer {
+            variant: Some(variant),
+            entries: HashMap::new(),
+            next_key: None,
+        })
+    }
+}
+
+/// Wrap `value` in a store with `variant` as its only key, if it is the data of a variant.
+fn wrap_variant(variant: Option<&'static str>, value: Ref<Metadata>) -> Ref<Metadata> {
+    match variant {
+        Some(variant) => store(HashMap::from([(variant.to_string(), value)])),
+        None => value,
+    }
+}
+
+struct SeqSerializer {
+    variant: Option<&'static str>,
+    items: Vec<Ref<Metadata>>,
+}
+
+impl SeqSerializer {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let value = value.serialize(MetadataSerializer)?.ok_or_else(|| {
+            Error::InvalidArgument("an array of metadata can't hold absent values".into())
+        })?;
+        self.items.push(value);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Option<Ref<Metadata>>> {
+        let array: Ref<Metadata> = (&self.items).into();
+        Ok(Some(wrap_variant(self.variant, array)))
+    }
+}
+
+impl ser::SerializeSeq for SeqSerializer {
+    type Ok = Option<Ref<Metadata>>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SeqSerializer {
+    type Ok = Option<Ref<Metadata>>;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqSerializer {
+    type Ok = Option<Ref<Metadata>>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqSerializer {
+    type Ok = Option<Ref<Metadata>>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+struct MapSerializer {
+    variant: Option<&'static str>,
+    entries: HashMap<String, Ref<Metadata>>,
+    next_key: Option<String>,
+}
+
+impl MapSerializer {
+    fn insert<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> Result<()> {
+        if let Some(value) = value.serialize(MetadataSerializer)? {
+            self.entries.insert(key, value);
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Option<Ref<Metadata>>> {
+        Ok(Some(wrap_variant(self.variant, store(self.entries))))
+    }
+}
+
+/// The key of a store for a map key, which must be a string, an integer or a boolean.
+fn map_key<T: Serialize + ?Sized>(key: &T) -> Result<String> {
+    let key = key
+        .serialize(MetadataSerializer)?
+        .ok_or_else(|| Error::InvalidArgument("a map key can't be absent".into()))?;
+    let key = match key.get_type() {
+        MetadataType::StringDataType => key.get_string().map(|key| key.to_string()),
+        MetadataType::UnsignedIntegerDataType => key.get_unsigned_integer().map(|k| k.to_string()),
+        MetadataType::SignedIntegerDataType => key.get_signed_integer().map(|k| k.to_string()),
+        MetadataType::BooleanDataType => key.get_boolean().map(|k| k.to_string()),
+        _ => Err(()),
+    };
+    key.map_err(|_| Error::InvalidArgument("map keys must be strings, integers or booleans".into()))
+}
+
+impl ser::SerializeMap for MapSerializer {
+    type Ok = Option<Ref<Metadata>>;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        self.next_key = Some(map_key(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let key = self
+            .next_key
+            .take()
+            .ok_or_else(|| Error::InvalidArgument("map value without a key".into()))?;
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for MapSerializer {
+    type Ok = Option<Ref<Metadata>>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for MapSerializer {
+    type Ok = Option<Ref<Metadata>>;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Self::Ok> {
+        self.finish()
+    }
+}
+
+struct MetadataDeserializer(Ref<Metadata>);
+
+impl MetadataDeserializer {
+    fn items(&self) -> Result<Vec<Ref<Metadata>>> {
+        let items = self.0.get_array().map_err(|_| Error::TypeMismatch)?;
+        Ok(items.iter().map(|item| item.to_owned()).collect())
+    }
+
+    fn entries(&self) -> Result<Vec<(String, Ref<Metadata>)>> {
+        let entries = self.0.get_value_store().map_err(|_| Error::TypeMismatch)?;
+        Ok(entries
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect())
+    }
+}
+
+impl<'de> de::Deserializer<'de> for MetadataDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let value = &self.0;
+        match value.get_type() {
+            MetadataType::InvalidDataType => visitor.visit_unit(),
+            MetadataType::BooleanDataType => {
+                visitor.visit_bool(value.get_boolean().map_err(|_| Error::TypeMismatch)?)
+            }
+            MetadataType::StringDataType => {
+                let string = value.get_string().map_err(|_| Error::TypeMismatch)?;
+                visitor.visit_string(string.to_string())
+            }
+            MetadataType::UnsignedIntegerDataType => visitor.visit_u64(
+                value
+                    .get_unsigned_integer()
+                    .map_err(|_| Error::TypeMismatch)?,
+            ),
+            MetadataType::SignedIntegerDataType => visitor.visit_i64(
+                value
+                    .get_signed_integer()
+                    .map_err(|_| Error::TypeMismatch)?,
+            ),
+            MetadataType::DoubleDataType => {
+                visitor.visit_f64(value.get_double().map_err(|_| Error::TypeMismatch)?)
+            }
+            MetadataType::RawDataType => {
+                visitor.visit_byte_buf(value.get_raw().map_err(|_| Error::TypeMismatch)?)
+            }
+            MetadataType::KeyValueDataType => visitor.visit_map(MapDeserializer {
+                entries: self.entries()?.into_iter(),
+                value: None,
+            }),
+            MetadataType::ArrayDataType => visitor.visit_seq(SeqDeserializer {
+                items: self.items()?.into_iter(),
+            }),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// Raw data is read as a sequence of bytes too, e.g. into a `Vec<u8>`.
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0.get_type() {
+            MetadataType::RawDataType => {
+                let bytes = self.0.get_raw().map_err(|_| Error::TypeMismatch)?;
+                visitor.visit_seq(de::value::SeqDeserializer::new(bytes.into_iter()))
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self.0.get_type() {
+            MetadataType::StringDataType => {
+                let variant = self.0.get_string().map_err(|_| Error::TypeMismatch)?;
+                visitor.visit_enum(variant.to_string().into_deserializer())
+            }
+            MetadataType::KeyValueDataType => {
+                let mut entries = self.entries()?;
+                if entries.len() != 1 {
+                    return Err(Error::Parse(
+                        "an enum variant with data must be a store with a single key".into(),
+                    ));
+                }
+                let (variant, value) = entries.remove(0);
+                visitor.visit_enum(VariantDeserializer { variant, value })
+            }
+            _ => Err(Error::TypeMismatch),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf tuple tuple_struct map struct identifier
+    }
+}
+
+struct SeqDeserializer {
+    items: std::vec::IntoIter<Ref<Metadata>>,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqDeserializer {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>> {
+        self.items
+            .next()
+            .map(|item| seed.deserialize(MetadataDeserializer(item)))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.items.len())
+    }
+}
+
+struct MapDeserializer {
+    entries: std::vec::IntoIter<(String, Ref<Metadata>)>,
+    value: Option<Ref<Metadata>>,
+}
+
+impl<'de> de::MapAccess<'de> for MapDeserializer {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.value = Some(value);
+        seed.deserialize(MapKeyDeserializer(key)).map(Some)
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| Error::Parse("map value without a key".into()))?;
+        seed.deserialize(MetadataDeserializer(value))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/// Deserializes the key of a store, parsing it for maps with integer or boolean keys.
+struct MapKeyDeserializer(String);
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident),* $(,)?) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+                match self.0.parse() {
+                    Ok(value) => visitor.$visit(value),
+                    Err(_) => visitor.visit_string(self.0),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for MapKeyDeserializer {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_string(self.0)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_enum(self.0.into_deserializer())
+    }
+
+    deserialize_parsed_key! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 f32 f64 char str string bytes byte_buf option unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct VariantDeserializer {
+    variant: String,
+    value: Ref<Metadata>,
+}
+
+impl<'de> de::EnumAccess<'de> for VariantDeserializer {
+    type Error = Error;
+    type Variant = MetadataDeserializer;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant)> {
+        let variant = seed.deserialize(MapKeyDeserializer(self.variant))?;
+        Ok((variant, MetadataDeserializer(self.value)))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for MetadataDeserializer {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        de::Deserializer::deserialize_map(self, visitor)
+    }
+}
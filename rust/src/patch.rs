@@ -0,0 +1,429 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Patching that controls whether writes land in the analysis view or the raw file view.
+
+pub mod bps;
+pub mod compare;
+pub mod dif;
+pub mod ips;
+
+use std::fmt::{Display, Formatter};
+use std::fs::OpenOptions;
+use std::io::{Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::Path;
+
+use thiserror::Error;
+
+use crate::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt, ModificationStatus};
+use crate::rc::Ref;
+
+#[derive(Error, Debug)]
+pub enum PatchError {
+    #[error("the view has no raw file view")]
+    NoRawView,
+    #[error("address {0:#x} is not backed by the file")]
+    NotFileBacked(u64),
+    #[error("wrote {written} of {expected} bytes at {address:#x}")]
+    ShortWrite {
+        address: u64,
+        written: usize,
+        expected: usize,
+    },
+    #[error("{0:#x?} is patched in the analysis view, a write to the file would not be visible")]
+    Conflict(Range<u64>),
+    #[error("data was inserted at file offset {0:#x}, the patched file no longer lines up with the original")]
+    Inserted(u64),
+    #[error("malformed {0} patch: {1}")]
+    Malformed(PatchFormat, &'static str),
+    #[error("{0} patch was not made for this file: {1}")]
+    SourceMismatch(PatchFormat, &'static str),
+    #[error("{0} patches cannot describe this change: {1}")]
+    Unsupported(PatchFormat, &'static str),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PatchFormat {
+    /// See [`ips`], limited to the first 16 MiB of the file.
+    Ips,
+    /// See [`bps`].
+    Bps,
+    /// See [`dif`], as exported by IDA.
+    Dif,
+}
+
+impl PatchFormat {
+    /// The format for a patch file extension, such as `"ips"`.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "ips" => Some(PatchFormat::Ips),
+            "bps" => Some(PatchFormat::Bps),
+            "dif" => Some(PatchFormat::Dif),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            PatchFormat::Ips => "ips",
+            PatchFormat::Bps => "bps",
+            PatchFormat::Dif => "dif",
+        }
+    }
+}
+
+impl Display for PatchFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PatchFormat::Ips => write!(f, "IPS"),
+            PatchFormat::Bps => write!(f, "BPS"),
+            PatchFormat::Dif => write!(f, "DIF"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WriteTarget {
+    /// Write through the analysis view, the view decides whether the write reaches the file.
+    #[default]
+    Analysis,
+    /// Write the file offsets backing the addresses in the raw view, failing for addresses that
+    /// are not backed by the file.
+    Raw,
+}
+
+/// Bytes patched in the analysis view that differ from the file, so are not saved with it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub address: Range<u64>,
+    /// Where the bytes are in the file, `None` when they are not backed by the file at all.
+    pub file_offset: Option<u64>,
+}
+
+struct SegmentMapping {
+    address: Range<u64>,
+    file: Option<Range<u64>>,
+}
+
+/// Writes to a view and its raw file view, and saves only the patched bytes of the file.
+///
+/// Writes to an analysis view are not guaranteed to reach the file, a region that is not backed by
+/// the file only keeps the patch in memory. A patcher makes that choice explicit and reports the
+/// patches that would be lost on save as [`Conflict`]s. The patches can also be exported as IPS,
+/// BPS or DIF patch files and applied from them, see [`Patcher::export`] and [`Patcher::apply`].
+///
+/// ```no_run
+/// use binaryninja::binary_view::BinaryViewExt;
+/// use binaryninja::patch::{Patcher, WriteTarget};
+///
+/// # let view = binaryninja::load("/bin/cat").unwrap();
+/// std::fs::copy("/bin/cat", "/tmp/cat.patched").unwrap();
+/// let patcher = Patcher::new(&view).unwrap().with_target(WriteTarget::Raw);
+/// patcher.write(view.entry_point(), &[0xcc]).unwrap();
+/// for conflict in patcher.conflicts() {
+///     log::warn!("Patch at {:#x?} will not be saved", conflict.address);
+/// }
+/// let written = patcher.save_modifications_to("/tmp/cat.patched").unwrap();
+/// println!("Patched file ranges: {:#x?}", written);
+/// ```
+pub struct Patcher {
+    view: Ref<BinaryView>,
+    raw: Ref<BinaryView>,
+    target: WriteTarget,
+    // Empty when the view is the raw view, addresses are then file offsets
+    segments: Vec<SegmentMapping>,
+}
+
+impl Patcher {
+    pub fn new(view: &BinaryView) -> Result<Self, PatchError> {
+        let raw = view.raw_view().ok_or(PatchError::NoRawView)?;
+        let segments = match raw.handle == view.handle {
+            true => Vec::new(),
+            false => view
+                .segments()
+                .iter()
+                .map(|segment| SegmentMapping {
+                    address: segment.address_range(),
+                    file: segment.parent_backing(),
+                })
+                .collect(),
+        };
+        Ok(Self {
+            view: view.to_owned(),
+            raw,
+            target: WriteTarget::default(),
+            segments,
+        })
+    }
+
+    pub fn with_target(mut self, target: WriteTarget) -> Self {
+        self.target = target;
+        self
+    }
+
+    pub fn target(&self) -> WriteTarget {
+        self.target
+    }
+
+    pub fn view(&self) -> &BinaryView {
+        &self.view
+    }
+
+    pub fn raw_view(&self) -> &BinaryView {
+        &self.raw
+    }
+
+    /// The file offset backing `address`, if any.
+    pub fn file_offset(&self, address: u64) -> Option<u64> {
+        if self.segments.is_empty() {
+            return self.raw.offset_valid(address).then_some(address);
+        }
+        self.segments
+            .iter()
+            .filter(|segment| segment.address.contains(&address))
+            .find_map(|segment| {
+                let file = segment.file.as_ref()?;
+                let offset = file.start + (address - segment.address.start);
+                file.contains(&offset).then_some(offset)
+            })
+    }
+
+    /// Write `data` at `address` in the view selected by [`Patcher::with_target`].
+    ///
+    /// Writing to the raw view fails with [`PatchError::Conflict`] if the analysis view already
+    /// holds its own patch of those bytes, as the new bytes would be hidden behind it.
+    pub fn write(&self, address: u64, data: &[u8]) -> Result<(), PatchError> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let written = match self.target {
+            WriteTarget::Analysis => self.view.write(address, data),
+            WriteTarget::Raw => {
+                let offset = self.raw_range(address, data.len())?;
+                if let Some(conflict) = self.conflicts_in(address..address + data.len() as u64) {
+                    return Err(PatchError::Conflict(conflict.address));
+                }
+                self.raw.write(offset, data)
+            }
+        };
+        match written == data.len() {
+            true => Ok(()),
+            false => Err(PatchError::ShortWrite {
+                address,
+                written,
+                expected: data.len(),
+            }),
+        }
+    }
+
+    /// The patched ranges of the view selected by [`Patcher::with_target`], as addresses for the
+    /// analysis view and file offsets for the raw view.
+    pub fn modified_ranges(&self) -> Vec<Range<u64>> {
+        match self.target {
+            WriteTarget::Analysis => self.view.modified_ranges(),
+            WriteTarget::Raw => self.raw.modified_ranges(),
+        }
+    }
+
+    /// Patches in the analysis view that will not be saved with the file.
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        if self.segments.is_empty() {
+            return Vec::new();
+        }
+        let mut conflicts = Vec::new();
+        for range in self.view.modified_ranges() {
+            let len = (range.end - range.start) as usize;
+            let patched = self.view.read_vec(range.start, len);
+            // Most patches map to one run of the file, read it at once rather than per byte
+            let contiguous = self
+                .raw_range(range.start, len)
+                .ok()
+                .map(|offset| self.raw.read_vec(offset, len));
+            for (i, byte) in patched.iter().enumerate() {
+                let address = range.start + i as u64;
+                let file_offset = self.file_offset(address);
+                let saved = match (&contiguous, file_offset) {
+                    (Some(file), _) => file.get(i) == Some(byte),
+                    (None, Some(offset)) => self.raw.read_vec(offset, 1).first() == Some(byte),
+                    (None, None) => false,
+                };
+                if saved {
+                    continue;
+                }
+                match conflicts.last_mut() {
+                    Some(Conflict {
+                        address: last,
+                        file_offset: last_offset,
+                    }) if last.end == address
+                        && last_offset.map(|offset| offset + (last.end - last.start))
+                            == file_offset =>
+                    {
+                        last.end += 1
+                    }
+                    _ => conflicts.push(Conflict {
+                        address: address..address + 1,
+                        file_offset,
+                    }),
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Write the patched ranges of the raw view into the file at `path`, leaving every other byte
+    /// of it untouched. The file is expected to be a copy of the original, it is not created.
+    ///
+    /// Returns the file ranges that were written. Patches only held by the analysis view are not
+    /// saved, see [`Patcher::conflicts`].
+    pub fn save_modifications_to(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<Vec<Range<u64>>, PatchError> {
+        let ranges = self.raw.modified_ranges();
+        for range in &ranges {
+            let statuses = self
+                .raw
+                .modifications(range.start, (range.end - range.start) as usize);
+            if let Some(i) = statuses
+                .iter()
+                .position(|status| *status == ModificationStatus::Inserted)
+            {
+                return Err(PatchError::Inserted(range.start + i as u64));
+            }
+        }
+
+        let mut file = OpenOptions::new().write(true).open(path.as_ref())?;
+        for range in &ranges {
+            let data = self
+                .raw
+                .read_vec(range.start, (range.end - range.start) as usize);
+            file.seek(SeekFrom::Start(range.start))?;
+            file.write_all(&data)?;
+            log::debug!(
+                "Saved patch at file offset {:#x} ({} bytes) to {}",
+                range.start,
+                data.len(),
+                path.as_ref().display()
+            );
+        }
+        file.flush()?;
+
+        let conflicts = self.conflicts();
+        if !conflicts.is_empty() {
+            log::warn!(
+                "{} patched ranges of the analysis view were not saved to {}",
+                conflicts.len(),
+                path.as_ref().display()
+            );
+        }
+        Ok(ranges)
+    }
+
+    /// Export the patches of the raw view as a patch file in `format`.
+    ///
+    /// Patch formats describe changes relative to the original file, which the view does not keep,
+    /// so its contents have to be passed as `original`. Patches only held by the analysis view are
+    /// not exported, see [`Patcher::conflicts`].
+    pub fn export(&self, format: PatchFormat, original: &[u8]) -> Result<Vec<u8>, PatchError> {
+        let patched = self.raw.read_vec(self.raw.start(), self.raw.len() as usize);
+        match format {
+            PatchFormat::Ips => ips::encode(original, &patched),
+            PatchFormat::Bps => bps::encode(original, &patched),
+            PatchFormat::Dif => {
+                let file_name = self.view.file().filename();
+                let name = Path::new(file_name.as_str())
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                dif::encode(original, &patched, &name)
+            }
+        }
+    }
+
+    /// Apply a patch file in `format` to the raw view, returning the file ranges it changed. If the
+    /// patch truncates the file, that is reported as an empty range at the new end of the file.
+    ///
+    /// The patch is applied to the current contents of the raw view, so BPS and DIF patches, which
+    /// check the bytes they expect, fail if the view has already been patched differently.
+    pub fn apply(&self, format: PatchFormat, patch: &[u8]) -> Result<Vec<Range<u64>>, PatchError> {
+        let start = self.raw.start();
+        let current = self.raw.read_vec(start, self.raw.len() as usize);
+        let patched = match format {
+            PatchFormat::Ips => ips::apply(&current, patch)?,
+            PatchFormat::Bps => bps::apply(&current, patch)?,
+            PatchFormat::Dif => dif::apply(&current, patch)?,
+        };
+
+        let mut changed: Vec<Range<u64>> = Vec::new();
+        let common = current.len().min(patched.len());
+        for i in (0..common).filter(|&i| current[i] != patched[i]) {
+            let offset = start + i as u64;
+            match changed.last_mut() {
+                Some(last) if last.end == offset => last.end += 1,
+                _ => changed.push(offset..offset + 1),
+            }
+        }
+        for range in &changed {
+            let data = &patched[(range.start - start) as usize..(range.end - start) as usize];
+            let written = self.raw.write(range.start, data);
+            if written != data.len() {
+                return Err(PatchError::ShortWrite {
+                    address: range.start,
+                    written,
+                    expected: data.len(),
+                });
+            }
+        }
+
+        let end = start + common as u64;
+        if patched.len() > current.len() {
+            self.raw.insert(end, &patched[common..]);
+            changed.push(end..start + patched.len() as u64);
+        } else if patched.len() < current.len() {
+            self.raw.remove(end, current.len() - common);
+            changed.push(end..end);
+        }
+        Ok(changed)
+    }
+
+    fn raw_range(&self, address: u64, len: usize) -> Result<u64, PatchError> {
+        let start = self
+            .file_offset(address)
+            .ok_or(PatchError::NotFileBacked(address))?;
+        // The whole write must map to one contiguous run of the file
+        let last = address + len as u64 - 1;
+        match self.file_offset(last) {
+            Some(offset) if offset == start + len as u64 - 1 => Ok(start),
+            _ => Err(PatchError::NotFileBacked(last)),
+        }
+    }
+
+    fn conflicts_in(&self, range: Range<u64>) -> Option<Conflict> {
+        let statuses = self
+            .view
+            .modifications(range.start, (range.end - range.start) as usize);
+        if statuses
+            .iter()
+            .all(|status| *status == ModificationStatus::Original)
+        {
+            return None;
+        }
+        self.conflicts().into_iter().find(|conflict| {
+            conflict.address.start < range.end && range.start < conflict.address.end
+        })
+    }
+}
@@ -0,0 +1,300 @@
+//! BPS patches, copy actions between the source and target files with CRC32 checksums.
+
+use crate::patch::{PatchError, PatchFormat};
+
+const MAGIC: &[u8] = b"BPS1";
+// Three trailing CRC32 checksums
+const FOOTER_LEN: usize = 12;
+
+const SOURCE_READ: u64 = 0;
+const TARGET_READ: u64 = 1;
+const SOURCE_COPY: u64 = 2;
+const TARGET_COPY: u64 = 3;
+
+fn malformed(reason: &'static str) -> PatchError {
+    PatchError::Malformed(PatchFormat::Bps, reason)
+}
+
+/// The CRC32 (IEEE) checksum BPS uses.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xedb88320 & (crc & 1).wrapping_neg());
+        }
+    }
+    !crc
+}
+
+fn push_number(patch: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let bits = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            patch.push(0x80 | bits);
+            return;
+        }
+        patch.push(bits);
+        value -= 1;
+    }
+}
+
+fn push_action(patch: &mut Vec<u8>, action: u64, len: usize) {
+    push_number(patch, ((len as u64 - 1) << 2) | action);
+}
+
+/// Encode the changes from `source` to `target` as a BPS patch.
+///
+/// Unchanged bytes are read from the source at the same offset and changed bytes are stored, so
+/// patches for in place modifications stay small. Sizes and offsets are variable length, so unlike
+/// IPS there is no limit on the file size.
+pub fn encode(source: &[u8], target: &[u8]) -> Result<Vec<u8>, PatchError> {
+    let mut patch = MAGIC.to_vec();
+    push_number(&mut patch, source.len() as u64);
+    push_number(&mut patch, target.len() as u64);
+    // No metadata
+    push_number(&mut patch, 0);
+
+    let unchanged = |i: usize| source.get(i) == Some(&target[i]);
+    let mut start = 0;
+    while start < target.len() {
+        let is_unchanged = unchanged(start);
+        let end = (start..target.len())
+            .find(|i| unchanged(*i) != is_unchanged)
+            .unwrap_or(target.len());
+        if is_unchanged {
+            push_action(&mut patch, SOURCE_READ, end - start);
+        } else {
+            push_action(&mut patch, TARGET_READ, end - start);
+            patch.extend_from_slice(&target[start..end]);
+        }
+        start = end;
+    }
+
+    patch.extend_from_slice(&crc32(source).to_le_bytes());
+    patch.extend_from_slice(&crc32(target).to_le_bytes());
+    let patch_crc = crc32(&patch);
+    patch.extend_from_slice(&patch_crc.to_le_bytes());
+    Ok(patch)
+}
+
+struct Actions<'a>(&'a [u8]);
+
+impl<'a> Actions<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], PatchError> {
+        if self.0.len() < len {
+            return Err(malformed("truncated action"));
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn number(&mut self) -> Result<u64, PatchError> {
+        let mut value = 0u64;
+        let mut shift = 1u64;
+        loop {
+            let byte = self.take(1)?[0] as u64;
+            value = (byte & 0x7f)
+                .checked_mul(shift)
+                .and_then(|bits| value.checked_add(bits))
+                .ok_or_else(|| malformed("number out of range"))?;
+            if byte & 0x80 != 0 {
+                return Ok(value);
+            }
+            shift = shift
+                .checked_mul(0x80)
+                .ok_or_else(|| malformed("number out of range"))?;
+            value = value
+                .checked_add(shift)
+                .ok_or_else(|| malformed("number out of range"))?;
+        }
+    }
+
+    fn usize(&mut self) -> Result<usize, PatchError> {
+        usize::try_from(self.number()?).map_err(|_| malformed("number out of range"))
+    }
+
+    /// A copy offset, stored relative to the previous copy of the same kind.
+    fn relative_offset(&mut self, offset: usize) -> Result<usize, PatchError> {
+        let value = self.number()?;
+        let delta = (value >> 1) as usize;
+        let offset = match value & 1 {
+            0 => offset.checked_add(delta),
+            _ => offset.checked_sub(delta),
+        };
+        offset.ok_or_else(|| malformed("copy offset out of range"))
+    }
+}
+
+fn read_crc(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// Apply a BPS patch to `source`, returning the patched contents.
+///
+/// Fails with [`PatchError::SourceMismatch`] if the patch was made for a different file.
+pub fn apply(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    if patch.len() < MAGIC.len() + FOOTER_LEN || !patch.starts_with(MAGIC) {
+        return Err(malformed("missing `BPS1` header"));
+    }
+    let (body, footer) = patch.split_at(patch.len() - FOOTER_LEN);
+    if crc32(&patch[..patch.len() - 4]) != read_crc(&footer[8..]) {
+        return Err(malformed("patch checksum mismatch"));
+    }
+    if crc32(source) != read_crc(&footer[..4]) {
+        return Err(PatchError::SourceMismatch(
+            PatchFormat::Bps,
+            "source checksum mismatch",
+        ));
+    }
+
+    let mut actions = Actions(&body[MAGIC.len()..]);
+    if actions.usize()? != source.len() {
+        return Err(PatchError::SourceMismatch(
+            PatchFormat::Bps,
+            "source size mismatch",
+        ));
+    }
+    let target_len = actions.usize()?;
+    let metadata_len = actions.usize()?;
+    actions.take(metadata_len)?;
+
+    // The size comes from the patch, don't trust it with the allocation
+    let mut target = Vec::with_capacity(target_len.min(source.len() + patch.len()));
+    let mut source_offset = 0;
+    let mut target_offset = 0;
+    while !actions.0.is_empty() {
+        let action = actions.number()?;
+        let len = (action >> 2) as usize + 1;
+        if target
+            .len()
+            .checked_add(len)
+            .is_none_or(|end| end > target_len)
+        {
+            return Err(malformed("action writes past the end of the target"));
+        }
+        match action & 3 {
+            SOURCE_READ => {
+                let start = target.len();
+                let data = source
+                    .get(start..start + len)
+                    .ok_or_else(|| malformed("source read out of range"))?;
+                target.extend_from_slice(data);
+            }
+            TARGET_READ => target.extend_from_slice(actions.take(len)?),
+            SOURCE_COPY => {
+                source_offset = actions.relative_offset(source_offset)?;
+                let end = source_offset
+                    .checked_add(len)
+                    .ok_or_else(|| malformed("source copy out of range"))?;
+                let data = source
+                    .get(source_offset..end)
+                    .ok_or_else(|| malformed("source copy out of range"))?;
+                target.extend_from_slice(data);
+                source_offset = end;
+            }
+            TARGET_COPY => {
+                target_offset = actions.relative_offset(target_offset)?;
+                if target_offset >= target.len() {
+                    return Err(malformed("target copy out of range"));
+                }
+                // The copy may overlap the bytes it produces, so it has to go byte by byte
+                for _ in 0..len {
+                    target.push(target[target_offset]);
+                    target_offset += 1;
+                }
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    if target.len() != target_len {
+        return Err(malformed("target size mismatch"));
+    }
+    if crc32(&target) != read_crc(&footer[4..8]) {
+        return Err(malformed("target checksum mismatch"));
+    }
+    Ok(target)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bps_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xcbf43926);
+    }
+
+    #[test]
+    fn bps_round_trip() {
+        let source: Vec<u8> = (0..=255).cycle().take(0x1000).collect();
+        let mut target = source.clone();
+        target[0x10..0x14].copy_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+        target.truncate(0xf00);
+        target.extend_from_slice(&[0xcc; 0x300]);
+        let patch = encode(&source, &target).unwrap();
+        assert_eq!(apply(&source, &patch).unwrap(), target);
+    }
+
+    #[test]
+    fn bps_wrong_source() {
+        let source = vec![1u8; 0x40];
+        let mut target = source.clone();
+        target[0] = 2;
+        let patch = encode(&source, &target).unwrap();
+        assert!(matches!(
+            apply(&target, &patch),
+            Err(PatchError::SourceMismatch(..))
+        ));
+        let mut corrupt = patch.clone();
+        corrupt[MAGIC.len()] ^= 1;
+        assert!(matches!(
+            apply(&source, &corrupt),
+            Err(PatchError::Malformed(..))
+        ));
+    }
+
+    #[test]
+    fn bps_copy_actions() {
+        // Hand written: a target copy overlapping its own output and a backwards source copy
+        let source = b"abcd".to_vec();
+        let mut patch = MAGIC.to_vec();
+        push_number(&mut patch, 4);
+        push_number(&mut patch, 8);
+        push_number(&mut patch, 0);
+        push_action(&mut patch, SOURCE_READ, 2);
+        push_action(&mut patch, TARGET_COPY, 4);
+        push_number(&mut patch, 0);
+        push_action(&mut patch, SOURCE_COPY, 2);
+        push_number(&mut patch, 2 << 1);
+        let target = b"ababab".iter().chain(b"cd").copied().collect::<Vec<u8>>();
+        patch.extend_from_slice(&crc32(&source).to_le_bytes());
+        patch.extend_from_slice(&crc32(&target).to_le_bytes());
+        let patch_crc = crc32(&patch);
+        patch.extend_from_slice(&patch_crc.to_le_bytes());
+        assert_eq!(apply(&source, &patch).unwrap(), target);
+    }
+
+    #[test]
+    fn bps_source_copy_out_of_range() {
+        // A source copy as far past the source as the offset can go
+        let source = b"abcd".to_vec();
+        let mut patch = MAGIC.to_vec();
+        push_number(&mut patch, 4);
+        push_number(&mut patch, 4);
+        push_number(&mut patch, 0);
+        push_action(&mut patch, SOURCE_COPY, 4);
+        push_number(&mut patch, (usize::MAX as u64) & !1);
+        patch.extend_from_slice(&crc32(&source).to_le_bytes());
+        patch.extend_from_slice(&crc32(&source).to_le_bytes());
+        let patch_crc = crc32(&patch);
+        patch.extend_from_slice(&patch_crc.to_le_bytes());
+        assert!(matches!(
+            apply(&source, &patch),
+            Err(PatchError::Malformed(..))
+        ));
+    }
+}
@@ -0,0 +1,185 @@
+//! Byte comparisons that ignore the bytes written by relocations.
+
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::ops::Range;
+use std::path::Path;
+
+use crate::binary_view::{BinaryView, BinaryViewExt};
+use crate::patch::PatchError;
+
+/// The number of bytes read from a view at a time.
+const CHUNK_SIZE: u64 = 0x10000;
+
+/// The ranges of `range` whose bytes differ between `left` and `right`, ignoring the bytes
+/// written by the relocations of either view.
+///
+/// Bytes only one of the views can read count as different.
+pub fn differences(left: &BinaryView, right: &BinaryView, range: Range<u64>) -> Vec<Range<u64>> {
+    let mut masked = left.relocation_ranges_in(range.clone());
+    masked.extend(right.relocation_ranges_in(range.clone()));
+    let mut differences = Vec::new();
+    for chunk in chunks(range) {
+        let len = (chunk.end - chunk.start) as usize;
+        let left_bytes = left.read_vec(chunk.start, len);
+        let right_bytes = right.read_vec(chunk.start, len);
+        diff_bytes(
+            chunk.start,
+            &left_bytes,
+            &right_bytes,
+            &masked,
+            &mut differences,
+        );
+    }
+    differences
+}
+
+/// The ranges of `view` starting at `start` whose bytes differ from `baseline`, ignoring the
+/// bytes written by relocations.
+pub fn differences_from_bytes(view: &BinaryView, start: u64, baseline: &[u8]) -> Vec<Range<u64>> {
+    let range = start..start.saturating_add(baseline.len() as u64);
+    let masked = view.relocation_ranges_in(range.clone());
+    let mut differences = Vec::new();
+    for chunk in chunks(range) {
+        let offset = (chunk.start - start) as usize;
+        let len = (chunk.end - chunk.start) as usize;
+        let bytes = view.read_vec(chunk.start, len);
+        diff_bytes(
+            chunk.start,
+            &bytes,
+            &baseline[offset..offset + len],
+            &masked,
+            &mut differences,
+        );
+    }
+    differences
+}
+
+/// The ranges of `range` whose bytes differ from the file at `path` the view was loaded from,
+/// ignoring the bytes written by relocations.
+///
+/// Only the parts of segments backed by the file are compared, with the data offsets of the
+/// segments taken as offsets into the file. Since the loader applies relocations to the bytes of
+/// the view, this reports only real changes, such as patches:
+///
+/// ```no_run
+/// use binaryninja::binary_view::BinaryViewExt;
+/// use binaryninja::patch::compare;
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// for section in view.sections().iter() {
+///     let patched = compare::differences_from_file(&view, section.address_range(), "/bin/cat")
+///         .unwrap();
+///     for range in patched {
+///         println!("{} patched at {:#x?}", section.name(), range);
+///     }
+/// }
+/// ```
+pub fn differences_from_file(
+    view: &BinaryView,
+    range: Range<u64>,
+    path: impl AsRef<Path>,
+) -> Result<Vec<Range<u64>>, PatchError> {
+    let mut file = File::open(path)?;
+    let mut differences = Vec::new();
+    for segment in view.segments().iter() {
+        let Some(backing) = segment.parent_backing() else {
+            continue;
+        };
+        let segment_start = segment.address_range().start;
+        let backed = segment_start..segment_start + (backing.end - backing.start);
+        let start = backed.start.max(range.start);
+        let end = backed.end.min(range.end);
+        if start >= end {
+            continue;
+        }
+        let mut baseline = vec![0; (end - start) as usize];
+        file.seek(SeekFrom::Start(backing.start + (start - segment_start)))?;
+        file.read_exact(&mut baseline)?;
+        differences.extend(differences_from_bytes(view, start, &baseline));
+    }
+    differences.sort_by_key(|range| range.start);
+    Ok(differences)
+}
+
+/// `range` split into pieces of at most [`CHUNK_SIZE`] bytes.
+fn chunks(range: Range<u64>) -> impl Iterator<Item = Range<u64>> {
+    let end = range.end;
+    range
+        .step_by(CHUNK_SIZE as usize)
+        .map(move |start| start..start.saturating_add(CHUNK_SIZE).min(end))
+}
+
+/// Add the ranges where `left` and `right`, both starting at `start`, differ outside of `masked`
+/// to `differences`, extending its last range if it ends where the first new one starts.
+///
+/// The bytes past the end of the shorter slice count as different.
+fn diff_bytes(
+    start: u64,
+    left: &[u8],
+    right: &[u8],
+    masked: &[Range<u64>],
+    differences: &mut Vec<Range<u64>>,
+) {
+    let len = left.len().max(right.len());
+    let mut ignored = vec![false; len];
+    let end = start + len as u64;
+    for mask in masked {
+        let mask_start = mask.start.max(start);
+        let mask_end = mask.end.min(end);
+        if mask_start < mask_end {
+            ignored[(mask_start - start) as usize..(mask_end - start) as usize].fill(true);
+        }
+    }
+    for (i, ignored) in ignored.into_iter().enumerate() {
+        if ignored || left.get(i) == right.get(i) {
+            continue;
+        }
+        let address = start + i as u64;
+        match differences.last_mut() {
+            Some(last) if last.end == address => last.end += 1,
+            _ => differences.push(address..address + 1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_relocations() {
+        let mut differences = Vec::new();
+        let left = [0, 1, 2, 3, 4, 5, 6, 7];
+        let right = [0, 9, 9, 9, 9, 5, 6, 9];
+        // Adjacent relocations mask a single range
+        let relocations = [0x1002..0x1003, 0x1003..0x1004];
+        diff_bytes(0x1000, &left, &right, &relocations, &mut differences);
+        assert_eq!(
+            differences,
+            [0x1001..0x1002, 0x1004..0x1005, 0x1007..0x1008]
+        );
+
+        // Ranges continue across calls, and missing bytes differ
+        diff_bytes(0x1008, &[1, 2], &[9], &[], &mut differences);
+        assert_eq!(differences.last(), Some(&(0x1007..0x100a)));
+
+        let mut differences = Vec::new();
+        diff_bytes(0, &left, &left, &[], &mut differences);
+        assert!(differences.is_empty());
+    }
+
+    #[test]
+    fn splits_into_chunks() {
+        let pieces: Vec<_> = chunks(0x1000..0x1000 + CHUNK_SIZE * 2 + 1).collect();
+        assert_eq!(
+            pieces,
+            [
+                0x1000..0x1000 + CHUNK_SIZE,
+                0x1000 + CHUNK_SIZE..0x1000 + CHUNK_SIZE * 2,
+                0x1000 + CHUNK_SIZE * 2..0x1000 + CHUNK_SIZE * 2 + 1,
+            ]
+        );
+        assert_eq!(chunks(0x10..0x10).count(), 0);
+    }
+}
@@ -0,0 +1,84 @@
+//! DIF patches, the text format IDA exports patches in.
+
+use std::fmt::Write;
+
+use crate::patch::{PatchError, PatchFormat};
+
+const HEADER: &str = "This difference file was created by Binary Ninja";
+
+fn malformed(reason: &'static str) -> PatchError {
+    PatchError::Malformed(PatchFormat::Dif, reason)
+}
+
+/// Encode the changes from `source` to `target` as a DIF patch, `name` is the file name recorded
+/// in the patch.
+///
+/// One `offset: original patched` line is written per changed byte, all in hex. DIF cannot
+/// describe a change of the file size.
+pub fn encode(source: &[u8], target: &[u8], name: &str) -> Result<Vec<u8>, PatchError> {
+    if source.len() != target.len() {
+        return Err(PatchError::Unsupported(
+            PatchFormat::Dif,
+            "the file size changed",
+        ));
+    }
+    let mut patch = format!("{}\n\n{}\n", HEADER, name);
+    for (offset, (original, patched)) in source.iter().zip(target).enumerate() {
+        if original != patched {
+            // Writing to a `String` can not fail
+            let _ = writeln!(patch, "{:016X}: {:02X} {:02X}", offset, original, patched);
+        }
+    }
+    Ok(patch.into_bytes())
+}
+
+fn parse_line(line: &str) -> Result<Option<(usize, u8, u8)>, PatchError> {
+    // Lines other than changes are the header and the file name
+    let Some((offset, bytes)) = line.split_once(':') else {
+        return Ok(None);
+    };
+    // Offsets are at least 8 digits, which tells them apart from a drive letter in the file name
+    let offset = offset.trim();
+    if offset.len() < 8 {
+        return Ok(None);
+    }
+    let Ok(offset) = usize::from_str_radix(offset, 16) else {
+        return Ok(None);
+    };
+    let mut bytes = bytes
+        .split_whitespace()
+        .map(|byte| u8::from_str_radix(byte, 16));
+    match (bytes.next(), bytes.next(), bytes.next()) {
+        (Some(Ok(original)), Some(Ok(patched)), None) => Ok(Some((offset, original, patched))),
+        _ => Err(malformed("expected an original and a patched byte")),
+    }
+}
+
+/// Apply a DIF patch to `source`, returning the patched contents.
+///
+/// Fails with [`PatchError::SourceMismatch`] if an original byte in the patch does not match.
+pub fn apply(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    let patch = std::str::from_utf8(patch).map_err(|_| malformed("not a text file"))?;
+    let mut target = source.to_vec();
+    for line in patch.lines() {
+        let Some((offset, original, patched)) = parse_line(line)? else {
+            continue;
+        };
+        match target.get_mut(offset) {
+            Some(byte) if source[offset] == original => *byte = patched,
+            Some(_) => {
+                return Err(PatchError::SourceMismatch(
+                    PatchFormat::Dif,
+                    "original byte mismatch",
+                ))
+            }
+            None => {
+                return Err(PatchError::SourceMismatch(
+                    PatchFormat::Dif,
+                    "offset past the end of the file",
+                ))
+            }
+        }
+    }
+    Ok(target)
+}
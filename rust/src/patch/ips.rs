@@ -0,0 +1,187 @@
+//! IPS patches, a list of `(offset, bytes)` records with run length encoding for repeated bytes.
+
+use crate::patch::{PatchError, PatchFormat};
+
+const MAGIC: &[u8] = b"PATCH";
+const FOOTER: &[u8] = b"EOF";
+// A record at this offset would be read as the footer
+const FOOTER_OFFSET: usize = 0x454f46;
+const MAX_OFFSET: usize = 0xffffff;
+const MAX_RECORD_LEN: usize = 0xffff;
+// Unchanged bytes between two changes are cheaper to repeat than a new record header
+const MERGE_GAP: usize = 5;
+// Shorter runs of one byte are stored as is, a run length record is 8 bytes
+const MIN_RLE_LEN: usize = 8;
+
+fn malformed(reason: &'static str) -> PatchError {
+    PatchError::Malformed(PatchFormat::Ips, reason)
+}
+
+fn unsupported(reason: &'static str) -> PatchError {
+    PatchError::Unsupported(PatchFormat::Ips, reason)
+}
+
+/// The ranges of `target` that differ from `source`, with short gaps merged.
+fn changed_runs(source: &[u8], target: &[u8]) -> Vec<(usize, usize)> {
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for (i, byte) in target.iter().enumerate() {
+        if source.get(i) == Some(byte) {
+            continue;
+        }
+        match runs.last_mut() {
+            Some((_, end)) if i - *end <= MERGE_GAP => *end = i + 1,
+            _ => runs.push((i, i + 1)),
+        }
+    }
+    runs
+}
+
+fn push_offset(patch: &mut Vec<u8>, offset: usize) {
+    patch.extend_from_slice(&(offset as u32).to_be_bytes()[1..]);
+}
+
+/// Encode the changes from `source` to `target` as an IPS patch.
+///
+/// Offsets are 24 bits, so only changes within the first 16 MiB of a file can be encoded.
+pub fn encode(source: &[u8], target: &[u8]) -> Result<Vec<u8>, PatchError> {
+    let mut patch = MAGIC.to_vec();
+    for (mut start, end) in changed_runs(source, target) {
+        if start == FOOTER_OFFSET {
+            // Start a byte early instead, the extra byte is written unchanged
+            start -= 1;
+        }
+        while start < end {
+            let mut len = (end - start).min(MAX_RECORD_LEN);
+            if start + len == FOOTER_OFFSET && start + len < end {
+                len -= 1;
+            }
+            if start > MAX_OFFSET {
+                return Err(unsupported("changes beyond 16 MiB cannot be addressed"));
+            }
+            let data = &target[start..start + len];
+            push_offset(&mut patch, start);
+            if len >= MIN_RLE_LEN && data.iter().all(|byte| *byte == data[0]) {
+                patch.extend_from_slice(&[0, 0]);
+                patch.extend_from_slice(&(len as u16).to_be_bytes());
+                patch.push(data[0]);
+            } else {
+                patch.extend_from_slice(&(len as u16).to_be_bytes());
+                patch.extend_from_slice(data);
+            }
+            start += len;
+        }
+    }
+    patch.extend_from_slice(FOOTER);
+    if target.len() < source.len() {
+        if target.len() > MAX_OFFSET {
+            return Err(unsupported("cannot truncate to a size beyond 16 MiB"));
+        }
+        push_offset(&mut patch, target.len());
+    }
+    Ok(patch)
+}
+
+struct Records<'a>(&'a [u8]);
+
+impl<'a> Records<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], PatchError> {
+        if self.0.len() < len {
+            return Err(malformed("truncated record"));
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn u16(&mut self) -> Result<usize, PatchError> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]) as usize)
+    }
+}
+
+fn read_u24(bytes: &[u8]) -> usize {
+    u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]) as usize
+}
+
+/// Apply an IPS patch to `source`, returning the patched contents.
+pub fn apply(source: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    let mut records = Records(
+        patch
+            .strip_prefix(MAGIC)
+            .ok_or_else(|| malformed("missing `PATCH` header"))?,
+    );
+
+    let mut target = source.to_vec();
+    loop {
+        let offset = records.take(3)?;
+        if offset == FOOTER {
+            break;
+        }
+        let offset = read_u24(offset);
+        let (len, data) = match records.u16()? {
+            0 => (records.u16()?, None),
+            len => (len, Some(records.take(len)?)),
+        };
+        if target.len() < offset + len {
+            target.resize(offset + len, 0);
+        }
+        match data {
+            Some(data) => target[offset..offset + len].copy_from_slice(data),
+            None => target[offset..offset + len].fill(records.take(1)?[0]),
+        }
+    }
+    // Some patchers append the size to truncate the file to after the footer
+    if let Ok(size) = records.take(3) {
+        target.truncate(read_u24(size));
+    }
+    Ok(target)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ips_round_trip() {
+        let source: Vec<u8> = (0..=255).cycle().take(0x1000).collect();
+        let mut target = source.clone();
+        target[0x10] = 0xcc;
+        target[0x13] = 0xcc;
+        target[0x800..0x840].fill(0x90);
+        let patch = encode(&source, &target).unwrap();
+        assert!(patch.starts_with(MAGIC) && patch.ends_with(FOOTER));
+        assert_eq!(apply(&source, &patch).unwrap(), target);
+    }
+
+    #[test]
+    fn ips_resize() {
+        let source = vec![0u8; 0x100];
+        let mut grown = source.clone();
+        grown.extend_from_slice(&[1, 2, 3]);
+        assert_eq!(
+            apply(&source, &encode(&source, &grown).unwrap()).unwrap(),
+            grown
+        );
+        let truncated = &source[..0x80];
+        assert_eq!(
+            apply(&source, &encode(&source, truncated).unwrap()).unwrap(),
+            truncated
+        );
+    }
+
+    #[test]
+    fn ips_footer_offset() {
+        let source = vec![0u8; FOOTER_OFFSET + 0x10];
+        let mut target = source.clone();
+        target[FOOTER_OFFSET] = 1;
+        let patch = encode(&source, &target).unwrap();
+        assert_eq!(&patch[MAGIC.len()..MAGIC.len() + 3], &[0x45, 0x4f, 0x45]);
+        assert_eq!(apply(&source, &patch).unwrap(), target);
+    }
+
+    #[test]
+    fn ips_malformed() {
+        assert!(apply(&[], b"PATCH\x00\x00\x10\x00\x04\x01").is_err());
+        assert!(apply(&[], b"NOTAPATCH").is_err());
+    }
+}
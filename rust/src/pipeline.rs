@@ -0,0 +1,388 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The batch workflow of opening a file, applying importers, analyzing and saving a database.
+
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use thiserror::Error;
+
+use crate::binary_view::{AnalysisState, BinaryView, BinaryViewExt};
+use crate::debuginfo::DebugInfoParser;
+use crate::file_metadata::{FileMetadata, SaveSettings};
+use crate::rc::Ref;
+
+// How often analysis progress is checked while waiting with a timeout.
+const ANALYSIS_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Error, Debug)]
+pub enum PipelineError {
+    #[error("failed to open `{0}`")]
+    OpenFailed(PathBuf),
+    #[error("debug info parser `{0}` is not registered")]
+    UnknownDebugInfoParser(String),
+    #[error("failed to open debug file `{0}`")]
+    DebugFileOpenFailed(PathBuf),
+    #[error("debug info parser `{0}` did not produce any debug info")]
+    DebugInfoFailed(String),
+    #[error("stage `{name}` failed: {message}")]
+    StageFailed { name: String, message: String },
+    #[error("failed to save database to `{0}`")]
+    SaveFailed(PathBuf),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalysisOutcome {
+    /// Analysis ran until the view was idle.
+    Completed,
+    /// Analysis was aborted after exceeding [`Pipeline::analysis_timeout`].
+    TimedOut,
+    /// Analysis was not run, see [`Pipeline::skip_analysis`].
+    Skipped,
+}
+
+#[derive(Debug, Clone)]
+pub struct StageTiming {
+    pub name: String,
+    pub duration: Duration,
+}
+
+#[derive(Debug)]
+pub struct PipelineResult {
+    pub view: Ref<BinaryView>,
+    pub analysis: AnalysisOutcome,
+    /// The database that was written, if any.
+    pub database: Option<PathBuf>,
+    pub timings: Vec<StageTiming>,
+}
+
+impl PipelineResult {
+    pub fn total_duration(&self) -> Duration {
+        self.timings.iter().map(|timing| timing.duration).sum()
+    }
+}
+
+type StageFn = Box<dyn FnOnce(&BinaryView) -> Result<(), String>>;
+
+enum Step {
+    DebugInfo {
+        parser: String,
+        debug_file: Option<PathBuf>,
+    },
+    Custom {
+        name: String,
+        action: StageFn,
+    },
+}
+
+impl Step {
+    fn name(&self) -> String {
+        match self {
+            Step::DebugInfo { parser, .. } => format!("debug_info:{}", parser),
+            Step::Custom { name, .. } => name.clone(),
+        }
+    }
+}
+
+/// Builder for the open, import, analyze and save workflow, with each stage timed and logged.
+///
+/// ```no_run
+/// use binaryninja::headless::Session;
+/// use binaryninja::pipeline::Pipeline;
+/// use std::time::Duration;
+///
+/// let _session = Session::new().expect("Failed to initialize session");
+/// let result = Pipeline::new("/bin/cat")
+///     .debug_info("DWARF")
+///     .analysis_timeout(Duration::from_secs(120))
+///     .save_database("/tmp/cat.bndb")
+///     .run()
+///     .expect("Pipeline failed");
+/// println!("Analysis {:?} in {:?}", result.analysis, result.total_duration());
+/// ```
+pub struct Pipeline {
+    input: PathBuf,
+    options: Option<String>,
+    steps: Vec<Step>,
+    analyze: bool,
+    analysis_timeout: Option<Duration>,
+    output: Option<PathBuf>,
+    save_settings: Option<Ref<SaveSettings>>,
+}
+
+impl Pipeline {
+    /// Start a pipeline for a binary or an existing database.
+    pub fn new(input: impl AsRef<Path>) -> Self {
+        Self {
+            input: input.as_ref().to_path_buf(),
+            options: None,
+            steps: Vec::new(),
+            analyze: true,
+            analysis_timeout: None,
+            output: None,
+            save_settings: None,
+        }
+    }
+
+    /// JSON load options, see [`crate::load_with_options`].
+    pub fn load_options(mut self, options: impl Into<String>) -> Self {
+        self.options = Some(options.into());
+        self
+    }
+
+    /// Apply the debug info parser with the given name to the view itself.
+    pub fn debug_info(mut self, parser: impl Into<String>) -> Self {
+        self.steps.push(Step::DebugInfo {
+            parser: parser.into(),
+            debug_file: None,
+        });
+        self
+    }
+
+    /// Apply the debug info parser with the given name to a separate debug file, such as a PDB.
+    pub fn debug_info_from_file(
+        mut self,
+        parser: impl Into<String>,
+        debug_file: impl AsRef<Path>,
+    ) -> Self {
+        self.steps.push(Step::DebugInfo {
+            parser: parser.into(),
+            debug_file: Some(debug_file.as_ref().to_path_buf()),
+        });
+        self
+    }
+
+    /// Run a custom stage against the view, stages run in the order they were added and before
+    /// analysis.
+    pub fn stage<F>(mut self, name: impl Into<String>, action: F) -> Self
+    where
+        F: FnOnce(&BinaryView) -> Result<(), String> + 'static,
+    {
+        self.steps.push(Step::Custom {
+            name: name.into(),
+            action: Box::new(action),
+        });
+        self
+    }
+
+    /// Abort analysis once it has run for longer than `timeout`, the pipeline still continues.
+    pub fn analysis_timeout(mut self, timeout: Duration) -> Self {
+        self.analysis_timeout = Some(timeout);
+        self
+    }
+
+    pub fn skip_analysis(mut self) -> Self {
+        self.analyze = false;
+        self
+    }
+
+    /// Save the results to a database at `path`. Saving to the database the pipeline was opened
+    /// from adds a snapshot to it instead.
+    pub fn save_database(mut self, path: impl AsRef<Path>) -> Self {
+        self.output = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn save_settings(mut self, settings: &SaveSettings) -> Self {
+        self.save_settings = Some(settings.to_owned());
+        self
+    }
+
+    pub fn run(self) -> Result<PipelineResult, PipelineError> {
+        let mut timings = Vec::new();
+        let input = self.input.display().to_string();
+
+        let view = timed(&mut timings, &input, "open", || {
+            crate::load_with_options(&self.input, false, self.options.as_deref())
+                .map(OpenedView::new)
+                .ok_or_else(|| PipelineError::OpenFailed(self.input.clone()))
+        })?;
+
+        for step in self.steps {
+            let name = step.name();
+            timed(&mut timings, &input, &name, || run_step(&view, step))?;
+        }
+
+        let analysis = if self.analyze {
+            let timeout = self.analysis_timeout;
+            timed(&mut timings, &input, "analysis", || {
+                Ok(run_analysis(&view, timeout))
+            })?
+        } else {
+            AnalysisOutcome::Skipped
+        };
+        if analysis == AnalysisOutcome::TimedOut {
+            log::warn!(
+                "pipeline input={:?} stage=analysis result=timeout functions={}",
+                input,
+                view.functions().len()
+            );
+        }
+
+        let database = match self.output {
+            Some(output) => {
+                let settings = self.save_settings.unwrap_or_else(SaveSettings::new);
+                timed(&mut timings, &input, "save", || {
+                    save(&view, &self.input, &output, &settings)
+                })?;
+                Some(output)
+            }
+            None => None,
+        };
+
+        let result = PipelineResult {
+            view: view.keep(),
+            analysis,
+            database,
+            timings,
+        };
+        log::info!(
+            "pipeline input={:?} result=ok analysis={:?} functions={} duration_ms={}",
+            input,
+            result.analysis,
+            result.view.functions().len(),
+            result.total_duration().as_millis()
+        );
+        Ok(result)
+    }
+}
+
+fn timed<T>(
+    timings: &mut Vec<StageTiming>,
+    input: &str,
+    name: &str,
+    stage: impl FnOnce() -> Result<T, PipelineError>,
+) -> Result<T, PipelineError> {
+    let start = Instant::now();
+    let result = stage();
+    let duration = start.elapsed();
+    match &result {
+        Ok(_) => log::info!(
+            "pipeline input={:?} stage={} result=ok duration_ms={}",
+            input,
+            name,
+            duration.as_millis()
+        ),
+        Err(err) => log::error!(
+            "pipeline input={:?} stage={} result=error duration_ms={} error={:?}",
+            input,
+            name,
+            duration.as_millis(),
+            err.to_string()
+        ),
+    }
+    timings.push(StageTiming {
+        name: name.to_string(),
+        duration,
+    });
+    result
+}
+
+/// A view the pipeline opened, closed when dropped unless it is kept.
+struct OpenedView(Option<Ref<BinaryView>>);
+
+impl OpenedView {
+    fn new(view: Ref<BinaryView>) -> Self {
+        Self(Some(view))
+    }
+
+    /// The view, left open for the caller.
+    fn keep(mut self) -> Ref<BinaryView> {
+        self.0.take().unwrap()
+    }
+}
+
+impl Deref for OpenedView {
+    type Target = BinaryView;
+
+    fn deref(&self) -> &BinaryView {
+        self.0.as_deref().unwrap()
+    }
+}
+
+impl Drop for OpenedView {
+    fn drop(&mut self) {
+        if let Some(view) = self.0.take() {
+            view.file().close();
+        }
+    }
+}
+
+fn run_step(view: &BinaryView, step: Step) -> Result<(), PipelineError> {
+    match step {
+        Step::DebugInfo { parser, debug_file } => {
+            let debug_info_parser = DebugInfoParser::from_name(parser.as_str())
+                .map_err(|_| PipelineError::UnknownDebugInfoParser(parser.clone()))?;
+            let opened = match &debug_file {
+                Some(path) => {
+                    let mut file = FileMetadata::with_filename(path.as_path());
+                    let debug_view = BinaryView::from_path(&mut file, path)
+                        .map_err(|_| PipelineError::DebugFileOpenFailed(path.clone()))?;
+                    Some(OpenedView::new(debug_view))
+                }
+                None => None,
+            };
+            let debug_view = opened.as_deref().unwrap_or(view);
+            let debug_info = debug_info_parser
+                .parse_debug_info(view, debug_view, None)
+                .ok_or(PipelineError::DebugInfoFailed(parser))?;
+            view.apply_debug_info(&debug_info);
+            Ok(())
+        }
+        Step::Custom { name, action } => {
+            action(view).map_err(|message| PipelineError::StageFailed { name, message })
+        }
+    }
+}
+
+fn run_analysis(view: &BinaryView, timeout: Option<Duration>) -> AnalysisOutcome {
+    let Some(timeout) = timeout else {
+        view.update_analysis_and_wait();
+        return AnalysisOutcome::Completed;
+    };
+
+    let start = Instant::now();
+    view.update_analysis();
+    loop {
+        thread::sleep(ANALYSIS_POLL_INTERVAL);
+        if view.analysis_progress().state == AnalysisState::IdleState {
+            return AnalysisOutcome::Completed;
+        }
+        if start.elapsed() >= timeout {
+            view.abort_analysis();
+            return AnalysisOutcome::TimedOut;
+        }
+    }
+}
+
+fn save(
+    view: &BinaryView,
+    input: &Path,
+    output: &Path,
+    settings: &SaveSettings,
+) -> Result<(), PipelineError> {
+    let file = view.file();
+    let saved = if file.is_database_backed() && input == output {
+        file.save_auto_snapshot_with_settings(settings)
+    } else {
+        file.create_database_with_settings(output, settings)
+    };
+    match saved {
+        true => Ok(()),
+        false => Err(PipelineError::SaveFailed(output.to_path_buf())),
+    }
+}
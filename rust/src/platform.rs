@@ -15,14 +15,16 @@
 //! Contains all information related to the execution environment of the binary, mainly the calling conventions used
 
 use crate::type_container::TypeContainer;
-use crate::type_parser::{TypeParserError, TypeParserErrorSeverity, TypeParserResult};
+use crate::type_parser::{
+    TypeParserError, TypeParserErrorSeverity, TypeParserOptions, TypeParserResult,
+};
 use crate::{
     architecture::{Architecture, CoreArchitecture},
-    calling_convention::CoreCallingConvention,
+    calling_convention::{CallingConventionInfo, CoreCallingConvention},
     rc::*,
     string::*,
     type_library::TypeLibrary,
-    types::QualifiedNameAndType,
+    types::{QualifiedName, QualifiedNameAndType, Type},
 };
 use binaryninjacore_sys::*;
 use std::fmt::Debug;
@@ -244,6 +246,14 @@ impl Platform {
         }
     }
 
+    /// The ABI details of every calling convention of the platform.
+    pub fn calling_convention_infos(&self) -> Vec<CallingConventionInfo> {
+        self.calling_conventions()
+            .iter()
+            .map(|convention| convention.info())
+            .collect()
+    }
+
     pub fn types(&self) -> Array<QualifiedNameAndType> {
         unsafe {
             let mut count = 0;
@@ -268,7 +278,38 @@ impl Platform {
         }
     }
 
-    // TODO: system_calls
+    /// The system calls known to this platform, as defined by the platform type libraries.
+    pub fn system_calls(&self) -> Array<SystemCallInfo> {
+        unsafe {
+            let mut count = 0;
+            let handles = BNGetPlatformSystemCalls(self.handle, &mut count);
+            Array::new(handles, count, ())
+        }
+    }
+
+    /// The name of the system call `number`, if known to the platform.
+    pub fn system_call_name(&self, number: u32) -> Option<BnString> {
+        let raw_name = unsafe { BNGetPlatformSystemCallName(self.handle, number) };
+        if raw_name.is_null() {
+            return None;
+        }
+        let name = unsafe { BnString::from_raw(raw_name) };
+        // The core will return an empty string if the system call is unknown.
+        match name.is_empty() {
+            true => None,
+            false => Some(name),
+        }
+    }
+
+    /// The prototype of the system call `number`, if known to the platform.
+    pub fn system_call_type(&self, number: u32) -> Option<Ref<Type>> {
+        let handle = unsafe { BNGetPlatformSystemCallType(self.handle, number) };
+        match handle.is_null() {
+            false => Some(unsafe { Type::ref_from_raw(handle) }),
+            true => None,
+        }
+    }
+
     // TODO: add a helper function to define a system call (platform function with a specific type)
 
     // TODO: Documentation, specifically how this differs from the TypeParser impl
@@ -393,6 +434,40 @@ impl Platform {
             ))
         }
     }
+
+    /// Parse an entire block of source into types, variables, and functions, using the default
+    /// type parser and the types of the platform.
+    ///
+    /// Unlike [`Platform::parse_types_from_source`] all diagnostics are returned on failure,
+    /// each with its file, line and column.
+    pub fn parse_types_with_options(
+        &self,
+        source: &str,
+        file_name: &str,
+        options: &TypeParserOptions,
+    ) -> Result<TypeParserResult, Vec<TypeParserError>> {
+        self.type_container()
+            .parse_types_from_source(
+                source,
+                file_name,
+                options.arguments.iter().map(String::as_str),
+                options.include_dirs.iter().map(String::as_str),
+                options.auto_type_source.as_str(),
+                options.import_dependencies,
+            )
+            .map_err(|errors| errors.to_vec())
+    }
+
+    /// Parse a single type and name from a string containing their definition, such as
+    /// `struct foo*` or `int (*handler)(void*)`, with the types of the platform.
+    pub fn parse_type_string(
+        &self,
+        source: &str,
+    ) -> Result<QualifiedNameAndType, Vec<TypeParserError>> {
+        self.type_container()
+            .parse_type_string(source, false)
+            .map_err(|errors| errors.to_vec())
+    }
 }
 
 impl Debug for Platform {
@@ -440,3 +515,45 @@ unsafe impl CoreArrayProviderInner for Platform {
         Guard::new(Self::from_raw(*raw), context)
     }
 }
+
+/// A system call provided by a [`Platform`].
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct SystemCallInfo {
+    pub number: u32,
+    pub name: QualifiedName,
+    pub ty: Ref<Type>,
+}
+
+impl SystemCallInfo {
+    pub(crate) fn from_raw(value: &BNSystemCallInfo) -> Self {
+        Self {
+            number: value.number,
+            name: QualifiedName::from_raw(&value.name),
+            ty: unsafe { Type::from_raw(value.type_) }.to_owned(),
+        }
+    }
+
+    pub fn new(number: u32, name: impl Into<QualifiedName>, ty: Ref<Type>) -> Self {
+        Self {
+            number,
+            name: name.into(),
+            ty,
+        }
+    }
+}
+
+impl CoreArrayProvider for SystemCallInfo {
+    type Raw = BNSystemCallInfo;
+    type Context = ();
+    type Wrapped<'a> = Self;
+}
+
+unsafe impl CoreArrayProviderInner for SystemCallInfo {
+    unsafe fn free(raw: *mut Self::Raw, count: usize, _context: &Self::Context) {
+        BNFreeSystemCallList(raw, count);
+    }
+
+    unsafe fn wrap_raw<'a>(raw: &'a Self::Raw, _context: &'a Self::Context) -> Self::Wrapped<'a> {
+        SystemCallInfo::from_raw(raw)
+    }
+}
@@ -0,0 +1,281 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Ready-made definitions of commonly used operating system structures.
+
+use crate::binary_view::{BinaryView, BinaryViewExt};
+use crate::platform::Platform;
+use crate::types::{QualifiedName, QualifiedNameAndType};
+use crate::Error;
+
+const WINDOWS_SOURCE: &str = r#"
+typedef struct _LIST_ENTRY {
+    struct _LIST_ENTRY* Flink;
+    struct _LIST_ENTRY* Blink;
+} LIST_ENTRY;
+
+typedef struct _UNICODE_STRING {
+    unsigned short Length;
+    unsigned short MaximumLength;
+    wchar_t* Buffer;
+} UNICODE_STRING;
+
+typedef struct _CLIENT_ID {
+    void* UniqueProcess;
+    void* UniqueThread;
+} CLIENT_ID;
+
+typedef struct _NT_TIB {
+    void* ExceptionList;
+    void* StackBase;
+    void* StackLimit;
+    void* SubSystemTib;
+    void* FiberData;
+    void* ArbitraryUserPointer;
+    struct _NT_TIB* Self;
+} NT_TIB;
+
+typedef struct _PEB_LDR_DATA {
+    unsigned int Length;
+    unsigned char Initialized;
+    void* SsHandle;
+    LIST_ENTRY InLoadOrderModuleList;
+    LIST_ENTRY InMemoryOrderModuleList;
+    LIST_ENTRY InInitializationOrderModuleList;
+} PEB_LDR_DATA;
+
+typedef struct _LDR_DATA_TABLE_ENTRY {
+    LIST_ENTRY InLoadOrderLinks;
+    LIST_ENTRY InMemoryOrderLinks;
+    LIST_ENTRY InInitializationOrderLinks;
+    void* DllBase;
+    void* EntryPoint;
+    unsigned int SizeOfImage;
+    UNICODE_STRING FullDllName;
+    UNICODE_STRING BaseDllName;
+} LDR_DATA_TABLE_ENTRY;
+
+typedef struct _CURDIR {
+    UNICODE_STRING DosPath;
+    void* Handle;
+} CURDIR;
+
+typedef struct _RTL_USER_PROCESS_PARAMETERS {
+    unsigned int MaximumLength;
+    unsigned int Length;
+    unsigned int Flags;
+    unsigned int DebugFlags;
+    void* ConsoleHandle;
+    unsigned int ConsoleFlags;
+    void* StandardInput;
+    void* StandardOutput;
+    void* StandardError;
+    CURDIR CurrentDirectory;
+    UNICODE_STRING DllPath;
+    UNICODE_STRING ImagePathName;
+    UNICODE_STRING CommandLine;
+    void* Environment;
+} RTL_USER_PROCESS_PARAMETERS;
+
+typedef struct _PEB {
+    unsigned char InheritedAddressSpace;
+    unsigned char ReadImageFileExecOptions;
+    unsigned char BeingDebugged;
+    unsigned char BitField;
+    void* Mutant;
+    void* ImageBaseAddress;
+    PEB_LDR_DATA* Ldr;
+    RTL_USER_PROCESS_PARAMETERS* ProcessParameters;
+    void* SubSystemData;
+    void* ProcessHeap;
+} PEB;
+
+typedef struct _TEB {
+    NT_TIB NtTib;
+    void* EnvironmentPointer;
+    CLIENT_ID ClientId;
+    void* ActiveRpcHandle;
+    void* ThreadLocalStoragePointer;
+    PEB* ProcessEnvironmentBlock;
+    unsigned int LastErrorValue;
+} TEB;
+"#;
+
+const GLIBC_SOURCE: &str = r#"
+struct _IO_marker;
+struct _IO_codecvt;
+struct _IO_wide_data;
+
+struct _IO_FILE {
+    int _flags;
+    char* _IO_read_ptr;
+    char* _IO_read_end;
+    char* _IO_read_base;
+    char* _IO_write_base;
+    char* _IO_write_ptr;
+    char* _IO_write_end;
+    char* _IO_buf_base;
+    char* _IO_buf_end;
+    char* _IO_save_base;
+    char* _IO_backup_base;
+    char* _IO_save_end;
+    struct _IO_marker* _markers;
+    struct _IO_FILE* _chain;
+    int _fileno;
+    int _flags2;
+    long _old_offset;
+    unsigned short _cur_column;
+    signed char _vtable_offset;
+    char _shortbuf[1];
+    void* _lock;
+    long long _offset;
+    struct _IO_codecvt* _codecvt;
+    struct _IO_wide_data* _wide_data;
+    struct _IO_FILE* _freeres_list;
+    void* _freeres_buf;
+    unsigned long __pad5;
+    int _mode;
+    char _unused2[15 * sizeof(int) - 4 * sizeof(void*) - sizeof(unsigned long)];
+};
+
+typedef struct _IO_FILE FILE;
+"#;
+
+const LINUX_KERNEL_SOURCE: &str = r#"
+struct list_head {
+    struct list_head* next;
+    struct list_head* prev;
+};
+
+struct hlist_node {
+    struct hlist_node* next;
+    struct hlist_node** pprev;
+};
+
+struct hlist_head {
+    struct hlist_node* first;
+};
+
+struct rb_node {
+    unsigned long __rb_parent_color;
+    struct rb_node* rb_right;
+    struct rb_node* rb_left;
+};
+
+struct rb_root {
+    struct rb_node* rb_node;
+};
+"#;
+
+/// A set of related structure definitions that can be installed into a view.
+///
+/// Each set is a C source parsed with the type parser of a platform, so pointer sized fields and
+/// padding follow the bitness of the platform the types are installed for. The structures are the
+/// stable prefixes of their real definitions, fields past the prefix are left out rather than
+/// guessed, as their layout changes between releases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PlatformTypeSet {
+    /// Process and thread environment blocks (`PEB`, `TEB`) and the loader structures they
+    /// reference, such as `PEB_LDR_DATA` and `LDR_DATA_TABLE_ENTRY`.
+    Windows,
+    /// The glibc `FILE` structure.
+    Glibc,
+    /// The intrusive containers embedded in most Linux kernel structures: `list_head`,
+    /// `hlist_head`, `hlist_node`, `rb_node` and `rb_root`.
+    ///
+    /// Structures such as `task_struct` are not included, their layout depends on the version and
+    /// configuration of the kernel.
+    LinuxKernel,
+}
+
+impl PlatformTypeSet {
+    pub const ALL: [PlatformTypeSet; 3] = [Self::Windows, Self::Glibc, Self::LinuxKernel];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Windows => "windows",
+            Self::Glibc => "glibc",
+            Self::LinuxKernel => "linux-kernel",
+        }
+    }
+
+    /// The C source the types are parsed from.
+    pub fn source(&self) -> &'static str {
+        match self {
+            Self::Windows => WINDOWS_SOURCE,
+            Self::Glibc => GLIBC_SOURCE,
+            Self::LinuxKernel => LINUX_KERNEL_SOURCE,
+        }
+    }
+
+    /// The source of the types installed from this set, see [`BinaryViewExt::define_auto_type`].
+    pub fn type_source(&self) -> String {
+        format!("binaryninja.platform_types.{}", self.name())
+    }
+
+    /// The types of this set, laid out for `platform`.
+    pub fn types(&self, platform: &Platform) -> Result<Vec<QualifiedNameAndType>, Error> {
+        let file_name = format!("{}.h", self.name());
+        let result = platform
+            .parse_types_from_source(self.source(), &file_name, &[], &self.type_source())
+            .map_err(|err| Error::Parse(format!("{}: {}", file_name, err.message)))?;
+        Ok(result
+            .types
+            .into_iter()
+            .map(|parsed| QualifiedNameAndType {
+                name: parsed.name().clone(),
+                ty: parsed.ty().clone(),
+            })
+            .collect())
+    }
+}
+
+/// Install the types of `set` into `view` as auto types, laid out for its default platform.
+///
+/// Returns the names the types were defined under. Installing the same set again replaces the
+/// types instead of duplicating them.
+///
+/// ```no_run
+/// use binaryninja::binary_view::BinaryViewExt;
+/// use binaryninja::platform_types::{install_platform_types, PlatformTypeSet};
+///
+/// let view = binaryninja::load("C:\\Windows\\System32\\notepad.exe").unwrap();
+/// let names = install_platform_types(&view, PlatformTypeSet::Windows).unwrap();
+/// println!("Installed {} types", names.len());
+/// assert!(view.type_by_name("PEB").is_some());
+/// ```
+pub fn install_platform_types(
+    view: &BinaryView,
+    set: PlatformTypeSet,
+) -> Result<Vec<QualifiedName>, Error> {
+    let platform = view
+        .default_platform()
+        .ok_or_else(|| Error::NotFound("default platform".to_string()))?;
+    install_platform_types_for(view, &platform, set)
+}
+
+/// Install the types of `set` into `view` as auto types, laid out for `platform`.
+pub fn install_platform_types_for(
+    view: &BinaryView,
+    platform: &Platform,
+    set: PlatformTypeSet,
+) -> Result<Vec<QualifiedName>, Error> {
+    let type_source = set.type_source();
+    let names = set
+        .types(platform)?
+        .into_iter()
+        .map(|named| view.define_auto_type(named.name, type_source.as_str(), &named.ty))
+        .collect();
+    Ok(names)
+}
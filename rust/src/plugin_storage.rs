@@ -0,0 +1,187 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Namespaced storage for plugin state that is saved with the database of a view.
+
+use std::collections::HashMap;
+
+use crate::binary_view::{BinaryView, BinaryViewExt};
+use crate::metadata::Metadata;
+use crate::rc::Ref;
+use crate::Error;
+
+/// Prefix of the view metadata keys plugin storage is kept under.
+const KEY_PREFIX: &str = "plugin_storage.";
+
+/// The key-value storage of a single plugin in a view.
+///
+/// Every plugin gets its own storage, kept as a single metadata entry of the view, so keys of
+/// different plugins never collide. With the `serde` feature any serializable value can be stored
+/// with `set_serde` and read back with `get_serde`.
+///
+/// ```no_run
+/// use binaryninja::plugin_storage::PluginStorage;
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// let storage = PluginStorage::new(&view, "com.example.tracer")
+///     .unwrap()
+///     .with_limit(64 * 1024);
+/// let runs = storage.get::<u64>("runs").and_then(Result::ok).unwrap_or(0);
+/// storage.set("runs", runs + 1).unwrap();
+/// println!("{} bytes stored", storage.size());
+/// ```
+pub struct PluginStorage {
+    view: Ref<BinaryView>,
+    namespace: String,
+    limit: Option<usize>,
+}
+
+impl PluginStorage {
+    /// The storage of the plugin `namespace` in `view`, such as `"com.example.tracer"`.
+    pub fn new(view: &BinaryView, namespace: &str) -> Result<Self, Error> {
+        if namespace.is_empty() {
+            return Err(Error::InvalidArgument(
+                "plugin storage namespace is empty".to_string(),
+            ));
+        }
+        Ok(Self {
+            view: view.to_owned(),
+            namespace: namespace.to_string(),
+            limit: None,
+        })
+    }
+
+    /// Refuse to store values that would grow the storage past `max_bytes`, see
+    /// [`PluginStorage::size`].
+    pub fn with_limit(mut self, max_bytes: usize) -> Self {
+        self.limit = Some(max_bytes);
+        self
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    fn metadata_key(&self) -> String {
+        format!("{}{}", KEY_PREFIX, self.namespace)
+    }
+
+    fn entries(&self) -> HashMap<String, Ref<Metadata>> {
+        self.view
+            .get_metadata::<HashMap<String, Ref<Metadata>>, _>(self.metadata_key())
+            .and_then(Result::ok)
+            .unwrap_or_default()
+    }
+
+    fn store(&self, entries: HashMap<String, Ref<Metadata>>) {
+        match entries.is_empty() {
+            true => self.view.remove_metadata(self.metadata_key()),
+            false => self
+                .view
+                .store_metadata(self.metadata_key(), entries, false),
+        }
+    }
+
+    /// The value of `key`, `None` if there is none and an error if it is not a `T`.
+    pub fn get<T>(&self, key: &str) -> Option<Result<T, Error>>
+    where
+        T: for<'a> TryFrom<&'a Metadata>,
+    {
+        let value = self.entries().remove(key)?;
+        Some(T::try_from(&value).map_err(|_| Error::TypeMismatch))
+    }
+
+    /// Set the value of `key`, failing if it would grow the storage past its limit.
+    pub fn set<V: Into<Ref<Metadata>>>(&self, key: &str, value: V) -> Result<(), Error> {
+        let value = value.into();
+        let mut entries = self.entries();
+        if let Some(limit) = self.limit {
+            let old_size = entries.get(key).map_or(0, |old| entry_size(key, old));
+            let size = total_size(&entries) - old_size + entry_size(key, &value);
+            if size > limit {
+                return Err(Error::InvalidArgument(format!(
+                    "storing `{}` grows the storage of `{}` to {} bytes, over its limit of {}",
+                    key, self.namespace, size, limit
+                )));
+            }
+        }
+        entries.insert(key.to_string(), value);
+        self.store(entries);
+        Ok(())
+    }
+
+    /// Remove `key`, returning whether it had a value.
+    pub fn remove(&self, key: &str) -> bool {
+        let mut entries = self.entries();
+        let removed = entries.remove(key).is_some();
+        if removed {
+            self.store(entries);
+        }
+        removed
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.entries().contains_key(key)
+    }
+
+    /// The keys with a value, sorted.
+    pub fn keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.entries().into_keys().collect();
+        keys.sort();
+        keys
+    }
+
+    /// Remove every value of the plugin.
+    pub fn clear(&self) {
+        self.view.remove_metadata(self.metadata_key());
+    }
+
+    /// The size of the storage in bytes, counted as the length of every key and the JSON of its
+    /// value.
+    pub fn size(&self) -> usize {
+        total_size(&self.entries())
+    }
+
+    /// Set `key` to `value` serialized as JSON.
+    #[cfg(feature = "serde")]
+    pub fn set_serde<T: serde::Serialize>(&self, key: &str, value: &T) -> Result<(), Error> {
+        let json = serde_json::to_string(value)
+            .map_err(|err| Error::InvalidArgument(format!("`{}`: {}", key, err)))?;
+        self.set(key, json)
+    }
+
+    /// The value of `key` deserialized from JSON, see [`PluginStorage::set_serde`].
+    #[cfg(feature = "serde")]
+    pub fn get_serde<T: serde::de::DeserializeOwned>(&self, key: &str) -> Option<Result<T, Error>> {
+        let json = match self.get::<String>(key)? {
+            Ok(json) => json,
+            Err(err) => return Some(Err(err)),
+        };
+        Some(
+            serde_json::from_str(&json)
+                .map_err(|err| Error::Parse(format!("`{}` of `{}`: {}", key, self.namespace, err))),
+        )
+    }
+}
+
+fn entry_size(key: &str, value: &Metadata) -> usize {
+    key.len() + value.get_json_string().map_or(0, |json| json.len())
+}
+
+fn total_size(entries: &HashMap<String, Ref<Metadata>>) -> usize {
+    entries
+        .iter()
+        .map(|(key, value)| entry_size(key, value))
+        .sum()
+}
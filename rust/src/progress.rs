@@ -1,4 +1,6 @@
 use std::ffi::c_void;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 
 pub trait ProgressCallback: Sized {
     type SplitProgressType: SplitProgressBuilder;
@@ -154,6 +156,218 @@ impl<P: ProgressCallback> ProgressCallback for SplitProgressInstance<'_, P> {
     }
 }
 
+/// The resolution the root callback of a [`ProgressScope`] is given progress in.
+const PROGRESS_STEPS: u64 = 1_000_000;
+
+struct ProgressRoot {
+    callback: Mutex<Box<dyn FnMut(usize, usize) -> bool + Send>>,
+    cancelled: AtomicBool,
+}
+
+struct ProgressNode {
+    root: Arc<ProgressRoot>,
+    parent: Option<Arc<ProgressNode>>,
+    /// Share of [`PROGRESS_STEPS`] this scope covers.
+    len: u64,
+    /// Progress made within `len`, including progress reported by child scopes.
+    done: AtomicU64,
+}
+
+impl ProgressNode {
+    fn advance(&self, steps: u64) {
+        self.done.fetch_add(steps, Ordering::Relaxed);
+        if let Some(parent) = &self.parent {
+            parent.advance(steps);
+        }
+    }
+
+    fn root_done(&self) -> u64 {
+        match &self.parent {
+            Some(parent) => parent.root_done(),
+            None => self.done.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Progress reporting for an operation made of nested, possibly concurrent, sub-operations.
+///
+/// Each scope covers a share of the whole operation. [`ProgressScope::subscopes`] divides that share
+/// between sub-operations by weight, so each one reports progress over its own work and the single
+/// root callback sees the combined progress, e.g. section decompression, unit parsing and type
+/// application of a debug info parser.
+///
+/// Scopes are cheap to clone and can be sent to other threads. Once the callback returns `false`
+/// the operation is cancelled and every scope of it returns `Err` from [`ProgressScope::report`].
+/// A scope is itself a [`ProgressCallback`], so it can be passed to any API taking one and split
+/// with [`ProgressCallback::split`] for sequential sub-operations.
+///
+/// ```no_run
+/// use binaryninja::progress::ProgressScope;
+///
+/// let progress = ProgressScope::new(|cur, total| {
+///     println!("{cur}/{total}");
+///     true
+/// });
+/// let parts = progress.subscopes(&[1, 3]);
+/// parts[0].finish().unwrap();
+/// for unit in parts[1].subscopes(&[1, 1]) {
+///     unit.report(1, 2).unwrap();
+///     unit.finish().unwrap();
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ProgressScope {
+    node: Arc<ProgressNode>,
+}
+
+impl ProgressScope {
+    /// Create a root scope, `callback` is given the progress of the whole operation and returns
+    /// `false` to cancel it.
+    ///
+    /// Reports from different threads are serialized before reaching `callback`.
+    pub fn new<P>(mut callback: P) -> Self
+    where
+        P: ProgressCallback + Send + 'static,
+    {
+        let root = Arc::new(ProgressRoot {
+            callback: Mutex::new(Box::new(move |cur, total| callback.progress(cur, total))),
+            cancelled: AtomicBool::new(false),
+        });
+        Self {
+            node: Arc::new(ProgressNode {
+                root,
+                parent: None,
+                len: PROGRESS_STEPS,
+                done: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// A scope that ignores progress and is only cancelled through [`ProgressScope::cancel`].
+    pub fn none() -> Self {
+        Self::new(|_, _| true)
+    }
+
+    /// Wrap a progress callback given by the core.
+    pub(crate) unsafe fn from_raw(
+        callback: Option<unsafe extern "C" fn(*mut c_void, usize, usize) -> bool>,
+        ctxt: *mut c_void,
+    ) -> Self {
+        struct RawCallback {
+            callback: unsafe extern "C" fn(*mut c_void, usize, usize) -> bool,
+            ctxt: *mut c_void,
+        }
+        // The core progress callbacks may be called from any thread
+        unsafe impl Send for RawCallback {}
+
+        impl ProgressCallback for RawCallback {
+            type SplitProgressType = SplitProgress<Self>;
+
+            fn progress(&mut self, progress: usize, total: usize) -> bool {
+                unsafe { (self.callback)(self.ctxt, progress, total) }
+            }
+
+            fn split(self, subpart_weights: &'static [usize]) -> Self::SplitProgressType {
+                SplitProgress::new(self, subpart_weights)
+            }
+        }
+
+        match callback {
+            Some(callback) => Self::new(RawCallback { callback, ctxt }),
+            None => Self::none(),
+        }
+    }
+
+    /// Divide this scope between sub-operations, each getting a share proportional to its weight.
+    ///
+    /// Progress of the returned scopes adds up, so they can be used one after the other or all at
+    /// once from different threads.
+    pub fn subscopes(&self, weights: &[usize]) -> Vec<ProgressScope> {
+        let total_weight: u128 = weights.iter().map(|w| *w as u128).sum();
+        let mut remaining = self.node.len;
+        weights
+            .iter()
+            .enumerate()
+            .map(|(i, weight)| {
+                let len = if i + 1 == weights.len() {
+                    remaining
+                } else {
+                    (self.node.len as u128 * *weight as u128)
+                        .checked_div(total_weight)
+                        .unwrap_or(0) as u64
+                };
+                remaining -= len;
+                ProgressScope {
+                    node: Arc::new(ProgressNode {
+                        root: self.node.root.clone(),
+                        parent: Some(self.node.clone()),
+                        len,
+                        done: AtomicU64::new(0),
+                    }),
+                }
+            })
+            .collect()
+    }
+
+    /// Report `progress` out of `total` for the work of this scope.
+    ///
+    /// Progress never moves backwards, reporting less than before only checks for cancellation.
+    /// Returns `Err` if the operation was cancelled.
+    pub fn report(&self, progress: usize, total: usize) -> Result<(), ()> {
+        if self.is_cancelled() {
+            return Err(());
+        }
+        let steps = (self.node.len as u128 * progress.min(total) as u128)
+            .checked_div(total as u128)
+            .unwrap_or(0) as u64;
+        let previous = self.node.done.load(Ordering::Relaxed);
+        if steps > previous {
+            // Concurrent reports to the same scope may race, only the increase is added
+            let previous = self.node.done.fetch_max(steps, Ordering::Relaxed);
+            if steps > previous {
+                if let Some(parent) = &self.node.parent {
+                    parent.advance(steps - previous);
+                }
+            }
+        }
+
+        let done = self.node.root_done().min(PROGRESS_STEPS);
+        let mut callback = self.node.root.callback.lock().unwrap();
+        if callback(done as usize, PROGRESS_STEPS as usize) {
+            Ok(())
+        } else {
+            self.cancel();
+            Err(())
+        }
+    }
+
+    /// Report all work of this scope as done.
+    pub fn finish(&self) -> Result<(), ()> {
+        self.report(1, 1)
+    }
+
+    /// Cancel the operation this scope is part of.
+    pub fn cancel(&self) {
+        self.node.root.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.node.root.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl ProgressCallback for ProgressScope {
+    type SplitProgressType = SplitProgress<Self>;
+
+    fn progress(&mut self, progress: usize, total: usize) -> bool {
+        self.report(progress, total).is_ok()
+    }
+
+    fn split(self, subpart_weights: &'static [usize]) -> Self::SplitProgressType {
+        SplitProgress::new(self, subpart_weights)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use std::cell::Cell;
@@ -268,4 +482,85 @@ mod test {
 
         assert!(split.next_subpart().is_none());
     }
+
+    #[test]
+    fn progress_scope_nested() {
+        let progress = Arc::new(AtomicU64::new(0));
+        let reported = progress.clone();
+        let scope = ProgressScope::new(move |p, total| {
+            assert_eq!(total, PROGRESS_STEPS as usize);
+            reported.store(p as u64, Ordering::Relaxed);
+            true
+        });
+        let percent = || progress.load(Ordering::Relaxed) * 100 / PROGRESS_STEPS;
+
+        let parts = scope.subscopes(&[25, 50, 25]);
+        parts[0].report(1, 2).unwrap();
+        assert_eq!(percent(), 12);
+        parts[0].finish().unwrap();
+        assert_eq!(percent(), 25);
+
+        // Progress of concurrent sub-scopes adds up, regardless of the order it is reported in
+        let units = parts[1].subscopes(&[1, 1]);
+        units[1].finish().unwrap();
+        assert_eq!(percent(), 50);
+        units[0].report(1, 2).unwrap();
+        assert_eq!(percent(), 62);
+        // Moving backwards is ignored
+        units[0].report(0, 2).unwrap();
+        assert_eq!(percent(), 62);
+        units[0].finish().unwrap();
+        assert_eq!(percent(), 75);
+
+        parts[2].finish().unwrap();
+        assert_eq!(progress.load(Ordering::Relaxed), PROGRESS_STEPS);
+    }
+
+    #[test]
+    fn progress_scope_split() {
+        let progress = Arc::new(AtomicU64::new(0));
+        let reported = progress.clone();
+        let scope = ProgressScope::new(move |p, _| {
+            reported.store(p as u64, Ordering::Relaxed);
+            true
+        });
+        let percent = || progress.load(Ordering::Relaxed) * 100 / PROGRESS_STEPS;
+
+        let mut split = scope.split(&[25, 75]);
+        let mut first = split.next_subpart().unwrap();
+        first.progress(100, 100);
+        assert_eq!(percent(), 25);
+        drop(first);
+        let mut second = split.next_subpart().unwrap();
+        second.progress(50, 100);
+        assert_eq!(percent(), 62);
+        second.progress(100, 100);
+        assert_eq!(percent(), 100);
+    }
+
+    #[test]
+    fn progress_scope_threads() {
+        let scope = ProgressScope::none();
+        let parts = scope.subscopes(&[1; 8]);
+        std::thread::scope(|s| {
+            for part in &parts {
+                s.spawn(move || {
+                    for i in 0..=100 {
+                        part.report(i, 100).unwrap();
+                    }
+                });
+            }
+        });
+        assert_eq!(scope.node.done.load(Ordering::Relaxed), PROGRESS_STEPS);
+    }
+
+    #[test]
+    fn progress_scope_cancel() {
+        let scope = ProgressScope::new(|p, total| p < total / 2);
+        let parts = scope.subscopes(&[1, 1]);
+        assert!(parts[0].report(1, 2).is_ok());
+        assert!(parts[1].finish().is_err());
+        assert!(parts[0].is_cancelled());
+        assert!(scope.report(0, 1).is_err());
+    }
 }
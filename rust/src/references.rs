@@ -1,8 +1,16 @@
 #![allow(dead_code)]
 use crate::architecture::CoreArchitecture;
+use crate::binary_view::{BinaryView, BinaryViewExt};
 use crate::function::Function;
+use crate::medium_level_il::{
+    MediumLevelILInstruction, MediumLevelILLiftedInstruction, MediumLevelILLiftedInstructionKind,
+    MediumLevelILLiftedOperand,
+};
 use crate::rc::{CoreArrayProvider, CoreArrayProviderInner, Ref};
+use crate::workflow::{Activity, AnalysisContext, Workflow};
 use binaryninjacore_sys::{BNFreeCodeReferences, BNFreeDataReferences, BNReferenceSource};
+use std::ops::Range;
+use std::sync::Mutex;
 
 /// A struct representing a single code cross-reference.
 #[derive(Debug)]
@@ -110,3 +118,207 @@ unsafe impl CoreArrayProviderInner for DataReference {
         DataReference { address: *raw }
     }
 }
+
+/// The way an instruction uses a data variable, see [`DataVariableAccess`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum DataVariableAccessKind {
+    /// The instruction loads from the data variable.
+    Read,
+    /// The instruction stores to the data variable.
+    Write,
+    /// The address of the data variable is used without being dereferenced, e.g. passed to a call.
+    AddressTaken,
+}
+
+/// A single instruction accessing a data variable, classified using the functions MLIL.
+///
+/// Retrieved with [`crate::binary_view::BinaryViewExt::data_variable_accesses`].
+#[derive(Debug, Clone)]
+pub struct DataVariableAccess {
+    pub kind: DataVariableAccessKind,
+    /// The MLIL instruction containing the access.
+    pub instruction: MediumLevelILInstruction,
+}
+
+impl DataVariableAccess {
+    pub fn address(&self) -> u64 {
+        self.instruction.address
+    }
+
+    pub fn function(&self) -> Ref<Function> {
+        self.instruction.function.function()
+    }
+}
+
+/// Collects the kinds of accesses `instr` makes to the data in `range`.
+///
+/// A single instruction may produce multiple accesses, e.g. `data_1000 = data_1000 + 1` is both
+/// a read and a write.
+pub(crate) fn classify_data_accesses(
+    instr: &MediumLevelILLiftedInstruction,
+    range: &Range<u64>,
+    accesses: &mut Vec<DataVariableAccessKind>,
+) {
+    use MediumLevelILLiftedInstructionKind::*;
+    let points_into_range = |expr: &MediumLevelILLiftedInstruction| match expr.kind {
+        ConstPtr(op) | Const(op) => range.contains(&op.constant),
+        _ => false,
+    };
+
+    match &instr.kind {
+        Store(op) if points_into_range(&op.dest) => {
+            accesses.push(DataVariableAccessKind::Write);
+            classify_data_accesses(&op.src, range, accesses);
+        }
+        StoreStruct(op) if points_into_range(&op.dest) => {
+            accesses.push(DataVariableAccessKind::Write);
+            classify_data_accesses(&op.src, range, accesses);
+        }
+        Load(op) if points_into_range(&op.src) => {
+            accesses.push(DataVariableAccessKind::Read);
+        }
+        LoadStruct(op) if points_into_range(&op.src) => {
+            accesses.push(DataVariableAccessKind::Read);
+        }
+        ConstPtr(op) if range.contains(&op.constant) => {
+            accesses.push(DataVariableAccessKind::AddressTaken);
+        }
+        _ => {
+            for (_, operand) in instr.operands() {
+                match operand {
+                    MediumLevelILLiftedOperand::Expr(expr) => {
+                        classify_data_accesses(&expr, range, accesses)
+                    }
+                    MediumLevelILLiftedOperand::ExprList(exprs) => {
+                        for expr in &exprs {
+                            classify_data_accesses(expr, range, accesses)
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// A cross-reference contributed by a [`ReferenceProvider`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ProvidedReference {
+    /// A code reference from the instruction at `from` to `to`.
+    ///
+    /// The reference is added to every function containing `from`.
+    Code { from: u64, to: u64 },
+    /// A data reference from the data at `from` to `to`.
+    Data { from: u64, to: u64 },
+}
+
+impl ProvidedReference {
+    /// Whether `view` already has the reference, in every function containing `from` for code
+    /// references.
+    pub fn exists(&self, view: &BinaryView) -> bool {
+        match *self {
+            ProvidedReference::Code { from, to } => view
+                .functions_containing(from)
+                .iter()
+                .all(|func| view.code_refs_from_addr(from, Some(&func)).contains(&to)),
+            ProvidedReference::Data { from, to } => view
+                .data_refs_from_addr(from)
+                .iter()
+                .any(|data_ref| data_ref.address == to),
+        }
+    }
+
+    /// Adds the reference to the `view` as a user reference, making it visible to the normal
+    /// cross-reference queries such as [`BinaryViewExt::code_refs_to_addr`].
+    pub fn apply(&self, view: &BinaryView) {
+        match *self {
+            ProvidedReference::Code { from, to } => view.add_user_code_ref(from, to),
+            ProvidedReference::Data { from, to } => view.add_user_data_ref(from, to),
+        }
+    }
+}
+
+/// Contributes synthetic cross-references which the analysis cannot discover by itself,
+/// such as uses of string table indices or entries of RPC handler tables.
+///
+/// Register a provider with [`register_reference_provider`].
+pub trait ReferenceProvider: 'static + Sync {
+    /// Returns the references to add to `view`, called once initial analysis has completed.
+    fn provide_references(&self, view: &BinaryView) -> Vec<ProvidedReference>;
+}
+
+const ACTIVITY_NAME: &str = "analysis.references.provideReferences";
+const ACTIVITY_CONFIG: &str = r#"{
+    "name": "analysis.references.provideReferences",
+    "title": "Provide References",
+    "description": "This analysis step adds the cross-references of the registered reference providers.",
+    "eligibility": {
+        "auto": {},
+        "runOnce": true
+    }
+}"#;
+
+static PROVIDERS: Mutex<Vec<&'static dyn ReferenceProvider>> = Mutex::new(Vec::new());
+
+/// Add the references of every provider that `view` doesn't have yet.
+fn provide_references(view: &BinaryView) {
+    let providers = PROVIDERS.lock().unwrap().clone();
+    for provider in providers {
+        for reference in provider.provide_references(view) {
+            if !reference.exists(view) {
+                reference.apply(view);
+            }
+        }
+    }
+}
+
+fn register_activity() {
+    let workflow = Workflow::instance("core.module.metaAnalysis").clone("core.module.metaAnalysis");
+    let activity = Activity::new_with_action(ACTIVITY_CONFIG, |ctx: &AnalysisContext| {
+        provide_references(&ctx.view())
+    });
+    if workflow.register_activity(&activity).is_err() {
+        log::error!("Failed to register the reference provider activity");
+        return;
+    }
+    workflow.insert("core.module.notifyCompletion", [ACTIVITY_NAME]);
+    if workflow.register().is_err() {
+        log::error!("Failed to register the reference provider activity");
+    }
+}
+
+/// Registers a [`ReferenceProvider`] which will be queried for every view once its initial
+/// analysis has found its functions.
+///
+/// The providers are queried by a module analysis activity which runs once per view. The core
+/// only exposes user references to plugins, so the provided references are added as user
+/// references, but only those the view doesn't have yet: reopening a database the references
+/// were saved with doesn't modify it again.
+///
+/// # Example
+///
+/// ```no_run
+/// use binaryninja::binary_view::BinaryView;
+/// use binaryninja::references::{register_reference_provider, ProvidedReference, ReferenceProvider};
+///
+/// struct HandlerTableProvider;
+///
+/// impl ReferenceProvider for HandlerTableProvider {
+///     fn provide_references(&self, _view: &BinaryView) -> Vec<ProvidedReference> {
+///         vec![ProvidedReference::Data {
+///             from: 0x401000,
+///             to: 0x402000,
+///         }]
+///     }
+/// }
+///
+/// register_reference_provider(HandlerTableProvider);
+/// ```
+pub fn register_reference_provider<P: ReferenceProvider>(provider: P) {
+    let mut providers = PROVIDERS.lock().unwrap();
+    if providers.is_empty() {
+        register_activity();
+    }
+    // Providers are registered for the lifetime of the process, like the activity calling them
+    providers.push(Box::leak(Box::new(provider)));
+}
@@ -0,0 +1,267 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Retype many functions and data variables at once, with a dry run to review the changes first.
+
+use crate::binary_view::{BinaryView, BinaryViewExt};
+use crate::confidence::{Conf, MAX_CONFIDENCE};
+use crate::rc::Ref;
+use crate::types::{Type, TypeClass};
+
+/// What to retype, an address or the name of a symbol.
+///
+/// Strings starting with `0x` are parsed as an address.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum RetypeTarget {
+    Address(u64),
+    Name(String),
+}
+
+impl From<u64> for RetypeTarget {
+    fn from(address: u64) -> Self {
+        Self::Address(address)
+    }
+}
+
+impl From<&str> for RetypeTarget {
+    fn from(value: &str) -> Self {
+        match value
+            .strip_prefix("0x")
+            .and_then(|hex| u64::from_str_radix(hex, 16).ok())
+        {
+            Some(address) => Self::Address(address),
+            None => Self::Name(value.to_string()),
+        }
+    }
+}
+
+impl From<String> for RetypeTarget {
+    fn from(value: String) -> Self {
+        Self::from(value.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetypeMode {
+    /// Only report what would change, nothing is modified.
+    DryRun,
+    /// Apply every type except the conflicting ones.
+    SkipConflicts,
+    /// Apply every type, replacing the conflicting user types.
+    Overwrite,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetypeKind {
+    Function,
+    DataVariable,
+}
+
+/// A single change of type, planned or made.
+#[derive(Debug, Clone)]
+pub struct Retype {
+    pub target: RetypeTarget,
+    pub address: u64,
+    pub kind: RetypeKind,
+    /// The type before the change, `None` for a new data variable.
+    pub old: Option<Conf<Ref<Type>>>,
+    pub new: Conf<Ref<Type>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictReason {
+    /// The existing user type is at least as confident as the new type.
+    ConfidentUserType,
+    /// A function can only be given a function type.
+    NotAFunctionType,
+}
+
+#[derive(Debug, Clone)]
+pub struct RetypeConflict {
+    pub target: RetypeTarget,
+    pub address: u64,
+    pub kind: RetypeKind,
+    pub reason: ConflictReason,
+    pub existing: Conf<Ref<Type>>,
+    pub new: Conf<Ref<Type>>,
+}
+
+/// The outcome of [`apply_types`].
+#[derive(Debug, Clone, Default)]
+pub struct RetypeReport {
+    /// Changes made, or for a dry run the changes that would be made.
+    pub applied: Vec<Retype>,
+    /// Targets that already have the new type.
+    pub unchanged: Vec<RetypeTarget>,
+    /// Conflicting targets, these were also applied in [`RetypeMode::Overwrite`].
+    pub conflicts: Vec<RetypeConflict>,
+    /// Names without a symbol.
+    pub unresolved: Vec<RetypeTarget>,
+    /// The undo action of the changes, `None` for a dry run or if nothing changed.
+    pub undo_id: Option<String>,
+}
+
+fn resolve(view: &BinaryView, target: &RetypeTarget) -> Option<u64> {
+    match target {
+        RetypeTarget::Address(address) => Some(*address),
+        RetypeTarget::Name(name) => view
+            .symbols_by_name(name.as_str())
+            .iter()
+            .map(|symbol| symbol.address())
+            .next()
+            .or_else(|| {
+                view.symbol_by_raw_name(name.as_str())
+                    .map(|symbol| symbol.address())
+            }),
+    }
+}
+
+enum Plan {
+    Apply(Retype),
+    Conflict(RetypeConflict, Retype),
+    Unchanged,
+}
+
+fn plan(view: &BinaryView, target: RetypeTarget, address: u64, new: Conf<Ref<Type>>) -> Plan {
+    let (kind, existing) = match view.functions_at(address).iter().next() {
+        Some(function) => {
+            // User function types have no confidence of their own
+            let confidence = match function.has_user_type() {
+                true => MAX_CONFIDENCE,
+                false => 0,
+            };
+            let existing = Conf::new(function.function_type(), confidence);
+            (
+                RetypeKind::Function,
+                Some((existing, function.has_user_type())),
+            )
+        }
+        None => {
+            let existing = view
+                .data_variable_at_address(address)
+                .map(|var| (var.ty, !var.auto_discovered));
+            (RetypeKind::DataVariable, existing)
+        }
+    };
+
+    let retype = Retype {
+        target: target.clone(),
+        address,
+        kind,
+        old: existing.as_ref().map(|(ty, _)| ty.clone()),
+        new: new.clone(),
+    };
+    let conflict = |reason, existing: &Conf<Ref<Type>>| RetypeConflict {
+        target: target.clone(),
+        address,
+        kind,
+        reason,
+        existing: existing.clone(),
+        new: new.clone(),
+    };
+
+    if kind == RetypeKind::Function && new.contents.type_class() != TypeClass::FunctionTypeClass {
+        let existing = retype.old.clone().unwrap();
+        return Plan::Conflict(
+            conflict(ConflictReason::NotAFunctionType, &existing),
+            retype,
+        );
+    }
+    match &existing {
+        Some((ty, _)) if ty.contents == new.contents => Plan::Unchanged,
+        Some((ty, true)) if ty.confidence >= new.confidence => {
+            Plan::Conflict(conflict(ConflictReason::ConfidentUserType, ty), retype)
+        }
+        _ => Plan::Apply(retype),
+    }
+}
+
+fn apply(view: &BinaryView, retype: &Retype) {
+    match retype.kind {
+        RetypeKind::Function => {
+            if let Some(function) = view.functions_at(retype.address).iter().next() {
+                function.set_user_type(&retype.new.contents);
+            }
+        }
+        RetypeKind::DataVariable => view.define_user_data_var(retype.address, &retype.new),
+    }
+}
+
+/// Set the type of the function starting at, or the data variable at, each target.
+///
+/// A target already given a user type at least as confident as the new type is a conflict, which
+/// is reported instead of overwritten unless [`RetypeMode::Overwrite`] is used. Conflicts are
+/// found against the types before any of the changes are made. Functions can only be given
+/// function types, in any mode. All changes are made as a single undo action:
+///
+/// ```no_run
+/// use binaryninja::binary_view::BinaryViewExt;
+/// use binaryninja::retype::{apply_types, RetypeMode};
+/// use binaryninja::types::Type;
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// let types = vec![
+///     ("main", Type::function(&Type::int(4, true), vec![], false)),
+///     ("0x4010", Type::int(8, false)),
+/// ];
+/// let report = apply_types(&view, types.clone(), RetypeMode::DryRun);
+/// for conflict in &report.conflicts {
+///     println!("{:#x}: keeping {}", conflict.address, conflict.existing.contents);
+/// }
+/// if report.conflicts.is_empty() {
+///     apply_types(&view, types, RetypeMode::SkipConflicts);
+/// }
+/// ```
+pub fn apply_types<I, T, C>(view: &BinaryView, types: I, mode: RetypeMode) -> RetypeReport
+where
+    I: IntoIterator<Item = (T, C)>,
+    T: Into<RetypeTarget>,
+    C: Into<Conf<Ref<Type>>>,
+{
+    let mut report = RetypeReport::default();
+    let mut conflicting = Vec::new();
+    for (target, new) in types {
+        let target = target.into();
+        let Some(address) = resolve(view, &target) else {
+            report.unresolved.push(target);
+            continue;
+        };
+        match plan(view, target.clone(), address, new.into()) {
+            Plan::Apply(retype) => report.applied.push(retype),
+            Plan::Conflict(conflict, retype) => {
+                if conflict.reason == ConflictReason::ConfidentUserType {
+                    conflicting.push(retype);
+                }
+                report.conflicts.push(conflict);
+            }
+            Plan::Unchanged => report.unchanged.push(target),
+        }
+    }
+
+    if mode == RetypeMode::Overwrite {
+        report.applied.append(&mut conflicting);
+    }
+    if mode == RetypeMode::DryRun || report.applied.is_empty() {
+        return report;
+    }
+
+    let file = view.file();
+    let undo_id = file.begin_undo_actions(false);
+    for retype in &report.applied {
+        apply(view, retype);
+    }
+    file.commit_undo_actions(undo_id.as_str());
+    report.undo_id = Some(undo_id.to_string());
+    report
+}
@@ -0,0 +1,415 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Search the contents of a view for byte patterns, text and regular expressions.
+
+use std::fmt;
+use std::ops::Range;
+use std::thread;
+
+use crate::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
+use crate::progress::ProgressCallback;
+use crate::string::json_string;
+
+pub type FindFlag = binaryninjacore_sys::BNFindFlag;
+
+/// Bytes read and matched by a single thread at a time.
+const CHUNK_SIZE: u64 = 0x10_0000;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum BytePatternError {
+    #[error("the pattern is empty")]
+    Empty,
+    #[error("`{0}` is not a hex byte or wildcard")]
+    InvalidByte(String),
+}
+
+/// A sequence of bytes where any bits may be left unspecified.
+///
+/// Patterns are matched in parallel over the whole view with [`BinaryViewExt::find_all`], or
+/// collected with [`BinaryViewExt::find_all_pattern`]:
+///
+/// ```no_run
+/// use binaryninja::binary_view::BinaryViewExt;
+/// use binaryninja::search::BytePattern;
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// let pattern = BytePattern::parse("48 8B ?? ?? C3").unwrap();
+/// for address in view.find_all_pattern(&pattern) {
+///     println!("{:#x}", address);
+/// }
+/// ```
+///
+/// [`BinaryViewExt::find_all`]: crate::binary_view::BinaryViewExt::find_all
+/// [`BinaryViewExt::find_all_pattern`]: crate::binary_view::BinaryViewExt::find_all_pattern
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BytePattern {
+    values: Vec<u8>,
+    masks: Vec<u8>,
+}
+
+fn parse_nibble(c: char) -> Option<(u8, u8)> {
+    match c {
+        '?' => Some((0, 0)),
+        c => c.to_digit(16).map(|value| (value as u8, 0xf)),
+    }
+}
+
+impl BytePattern {
+    /// Parse a pattern such as `48 8B ?? ?? C3`. Bytes are two hex digits, either of which may be
+    /// `?` to match any nibble, and a lone `?` matches any byte. Whitespace between bytes is
+    /// optional.
+    pub fn parse(pattern: &str) -> Result<Self, BytePatternError> {
+        let mut result = Self {
+            values: Vec::new(),
+            masks: Vec::new(),
+        };
+        for token in pattern.split_whitespace() {
+            if token == "?" {
+                result.push(0, 0);
+                continue;
+            }
+            let chars: Vec<char> = token.chars().collect();
+            for pair in chars.chunks(2) {
+                let byte = match pair {
+                    [high, low] => parse_nibble(*high).zip(parse_nibble(*low)),
+                    _ => None,
+                };
+                let Some(((high, high_mask), (low, low_mask))) = byte else {
+                    return Err(BytePatternError::InvalidByte(pair.iter().collect()));
+                };
+                result.push((high << 4) | low, (high_mask << 4) | low_mask);
+            }
+        }
+        match result.is_empty() {
+            true => Err(BytePatternError::Empty),
+            false => Ok(result),
+        }
+    }
+
+    /// A pattern matching exactly `bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Self {
+        Self {
+            values: bytes.to_vec(),
+            masks: vec![0xff; bytes.len()],
+        }
+    }
+
+    pub(crate) fn push(&mut self, value: u8, mask: u8) {
+        self.values.push(value);
+        self.masks.push(mask);
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Whether `data` starts with bytes matching the pattern.
+    pub fn matches_at(&self, data: &[u8]) -> bool {
+        data.len() >= self.len()
+            && data
+                .iter()
+                .zip(self.values.iter().zip(&self.masks))
+                .all(|(byte, (value, mask))| byte & mask == *value)
+    }
+
+    /// Offsets of every match in `data`, including overlapping matches.
+    pub fn find_in<'a>(&'a self, data: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+        let last = (data.len() + 1).saturating_sub(self.len());
+        (0..last).filter(move |&offset| self.matches_at(&data[offset..]))
+    }
+}
+
+impl fmt::Display for BytePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, (value, mask)) in self.values.iter().zip(&self.masks).enumerate() {
+            if index > 0 {
+                f.write_str(" ")?;
+            }
+            for shift in [4, 0] {
+                match (mask >> shift) & 0xf {
+                    0 => f.write_str("?")?,
+                    _ => write!(f, "{:X}", (value >> shift) & 0xf)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A search run by the core with [`BinaryViewExt::search`].
+///
+/// The pattern is a string of hex digits where `?` is a wildcard, or otherwise a regular
+/// expression matched against the bytes of the view. With [`SearchQuery::raw`] the pattern is
+/// matched as plain text instead.
+///
+/// ```no_run
+/// use binaryninja::binary_view::{BinaryViewBase, BinaryViewExt};
+/// use binaryninja::search::SearchQuery;
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// let query = SearchQuery::new(r"GCC: \([^)]*\)").range(view.start()..view.end());
+/// view.search(&query, |address, matched| {
+///     println!("{:#x}: {}", address, String::from_utf8_lossy(matched));
+///     true
+/// });
+/// ```
+///
+/// [`BinaryViewExt::search`]: crate::binary_view::BinaryViewExt::search
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchQuery {
+    pattern: String,
+    range: Option<Range<u64>>,
+    raw: bool,
+    ignore_case: bool,
+    overlap: bool,
+    align: u64,
+}
+
+impl SearchQuery {
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self {
+            pattern: pattern.into(),
+            range: None,
+            raw: false,
+            ignore_case: false,
+            overlap: false,
+            align: 1,
+        }
+    }
+
+    /// Only search `range`, by default the whole view is searched.
+    pub fn range(mut self, range: Range<u64>) -> Self {
+        self.range = Some(range);
+        self
+    }
+
+    /// Match the pattern as plain text rather than hex bytes or a regular expression.
+    pub fn raw(mut self, raw: bool) -> Self {
+        self.raw = raw;
+        self
+    }
+
+    pub fn ignore_case(mut self, ignore_case: bool) -> Self {
+        self.ignore_case = ignore_case;
+        self
+    }
+
+    /// Also report matches that start inside a previous match.
+    pub fn overlap(mut self, overlap: bool) -> Self {
+        self.overlap = overlap;
+        self
+    }
+
+    /// Only report matches starting at a multiple of `align`.
+    pub fn align(mut self, align: u64) -> Self {
+        self.align = align.max(1);
+        self
+    }
+
+    pub(crate) fn to_json(&self) -> String {
+        let mut json = format!("{{\"pattern\":{}", json_string(&self.pattern));
+        if let Some(range) = &self.range {
+            json += &format!(",\"start\":{},\"end\":{}", range.start, range.end);
+        }
+        json += &format!(
+            ",\"raw\":{},\"ignoreCase\":{},\"overlap\":{},\"align\":{}}}",
+            self.raw, self.ignore_case, self.overlap, self.align
+        );
+        json
+    }
+}
+
+/// Split the mapped ranges of the view into chunks of at most [`CHUNK_SIZE`], each paired with
+/// the end of the range it is part of.
+pub(crate) fn scan_chunks(view: &BinaryView) -> Vec<(Range<u64>, u64)> {
+    let segments = view.segments();
+    let mut ranges: Vec<Range<u64>> = segments
+        .iter()
+        .map(|segment| segment.address_range())
+        .collect();
+    if ranges.is_empty() {
+        ranges.push(view.start()..view.end());
+    }
+
+    let mut chunks = Vec::new();
+    for range in ranges {
+        let mut start = range.start;
+        while start < range.end {
+            let end = start.saturating_add(CHUNK_SIZE).min(range.end);
+            chunks.push((start..end, range.end));
+            start = end;
+        }
+    }
+    chunks
+}
+
+/// Call `f` with each of `items` and collect the results in order, on the rayon thread pool with
+/// the `rayon` feature and on all available threads otherwise. A panic of `f` is resumed on the
+/// calling thread.
+pub(crate) fn map_parallel<I, T, F>(items: Vec<I>, f: F) -> Vec<T>
+where
+    I: Send,
+    T: Send,
+    F: Fn(I) -> T + Send + Sync,
+{
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        items.into_par_iter().map(f).collect()
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+        let f = &f;
+        let mut results = Vec::with_capacity(items.len());
+        let mut items = items.into_iter().peekable();
+        while items.peek().is_some() {
+            let batch: Vec<I> = items.by_ref().take(threads).collect();
+            thread::scope(|scope| {
+                let handles: Vec<_> = batch
+                    .into_iter()
+                    .map(|item| scope.spawn(move || f(item)))
+                    .collect();
+                for handle in handles {
+                    match handle.join() {
+                        Ok(result) => results.push(result),
+                        Err(panic) => std::panic::resume_unwind(panic),
+                    }
+                }
+            });
+        }
+        results
+    }
+}
+
+/// Matches starting in `chunk`, reading past its end up to `limit` for matches crossing into the
+/// next chunk.
+fn scan_chunk(view: &BinaryView, pattern: &BytePattern, chunk: Range<u64>, limit: u64) -> Vec<u64> {
+    let read_end = chunk
+        .end
+        .saturating_add(pattern.len().saturating_sub(1) as u64)
+        .min(limit);
+    let data = view.read_vec(chunk.start, (read_end - chunk.start) as usize);
+    pattern
+        .find_in(&data)
+        .map(|offset| chunk.start + offset as u64)
+        .take_while(|address| *address < chunk.end)
+        .collect()
+}
+
+/// Match `pattern` over every segment of `view`, scanning chunks on all available threads.
+///
+/// Matches are passed to `on_match` in address order within each segment, and the search stops
+/// once it returns `false`. Progress is reported after every batch of chunks and the search is
+/// cancelled when it returns `false`. Returns whether the search ran to completion.
+pub(crate) fn find_all<P, F>(
+    view: &BinaryView,
+    pattern: &BytePattern,
+    mut progress: P,
+    mut on_match: F,
+) -> bool
+where
+    P: ProgressCallback,
+    F: FnMut(u64) -> bool,
+{
+    if pattern.is_empty() {
+        return true;
+    }
+    let chunks = scan_chunks(view);
+    let threads = thread::available_parallelism().map_or(1, |threads| threads.get());
+    let mut done = 0;
+    for batch in chunks.chunks(threads) {
+        let results = map_parallel(batch.to_vec(), |(chunk, limit)| {
+            scan_chunk(view, pattern, chunk, limit)
+        });
+        for address in results.into_iter().flatten() {
+            if !on_match(address) {
+                return false;
+            }
+        }
+
+        done += batch.len();
+        // SAFETY: The context is the progress callback the function is called for
+        let keep_going =
+            unsafe { P::cb_progress_callback(progress.into_raw(), done, chunks.len()) };
+        if !keep_going {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_pattern() {
+        let pattern = BytePattern::parse("48 8B ?? ?? C3").unwrap();
+        assert_eq!(pattern.len(), 5);
+        assert_eq!(pattern.to_string(), "48 8B ?? ?? C3");
+        assert_eq!(BytePattern::parse("488b????c3").unwrap(), pattern);
+        assert_eq!(BytePattern::parse("48 8b ? ? c3").unwrap(), pattern);
+        assert_eq!(BytePattern::parse("4? ?B").unwrap().to_string(), "4? ?B");
+    }
+
+    #[test]
+    fn parse_invalid_pattern() {
+        assert_eq!(BytePattern::parse("  "), Err(BytePatternError::Empty));
+        assert_eq!(
+            BytePattern::parse("48 8G"),
+            Err(BytePatternError::InvalidByte("8G".to_string()))
+        );
+        assert_eq!(
+            BytePattern::parse("48 8"),
+            Err(BytePatternError::InvalidByte("8".to_string()))
+        );
+
+        let parse =
+            |pattern| -> crate::error::Result<BytePattern> { Ok(BytePattern::parse(pattern)?) };
+        assert!(matches!(
+            parse("48 8G"),
+            Err(crate::Error::BytePattern(BytePatternError::InvalidByte(_)))
+        ));
+    }
+
+    #[test]
+    fn find_pattern() {
+        let data = [
+            0x48, 0x8b, 0x45, 0x10, 0xc3, 0x48, 0x8b, 0x00, 0x00, 0xc3, 0x48,
+        ];
+        let pattern = BytePattern::parse("48 8B ?? ?? C3").unwrap();
+        assert_eq!(pattern.find_in(&data).collect::<Vec<_>>(), vec![0, 5]);
+        let nibbles = BytePattern::parse("4? ?b").unwrap();
+        assert_eq!(nibbles.find_in(&data).collect::<Vec<_>>(), vec![0, 5]);
+        let overlapping = BytePattern::parse("?? ??").unwrap();
+        assert_eq!(overlapping.find_in(&data[..3]).count(), 2);
+        assert!(!pattern.matches_at(&data[5..8]));
+        assert_eq!(BytePattern::from_bytes(&[0x48]).find_in(&data).count(), 3);
+    }
+
+    #[test]
+    fn query_json() {
+        let query = SearchQuery::new("a\"b").range(0x10..0x20).ignore_case(true);
+        assert_eq!(
+            query.to_json(),
+            r#"{"pattern":"a\"b","start":16,"end":32,"raw":false,"ignoreCase":true,"overlap":false,"align":1}"#
+        );
+    }
+}
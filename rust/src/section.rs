@@ -19,9 +19,11 @@ use std::ops::Range;
 
 use binaryninjacore_sys::*;
 
-use crate::binary_view::BinaryView;
+use crate::binary_view::{BinaryView, BinaryViewExt};
+use crate::headers::{ElfHeader, ElfSectionHeader};
 use crate::rc::*;
 use crate::string::*;
+use crate::Endianness;
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum Semantics {
@@ -60,13 +62,64 @@ impl From<Semantics> for BNSectionSemantics {
     }
 }
 
+/// Flags of an ELF section, its `sh_flags`.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash, Debug)]
+pub struct ElfSectionFlags(pub u64);
+
+impl ElfSectionFlags {
+    pub const WRITE: Self = Self(0x1);
+    pub const ALLOC: Self = Self(0x2);
+    pub const EXECINSTR: Self = Self(0x4);
+    pub const MERGE: Self = Self(0x10);
+    pub const STRINGS: Self = Self(0x20);
+    pub const INFO_LINK: Self = Self(0x40);
+    pub const LINK_ORDER: Self = Self(0x80);
+    pub const OS_NONCONFORMING: Self = Self(0x100);
+    pub const GROUP: Self = Self(0x200);
+    pub const TLS: Self = Self(0x400);
+    /// `SHF_COMPRESSED`, the section starts with a compression header.
+    pub const COMPRESSED: Self = Self(0x800);
+
+    pub fn bits(&self) -> u64 {
+        self.0
+    }
+
+    pub fn contains(&self, flags: Self) -> bool {
+        self.0 & flags.0 == flags.0
+    }
+}
+
+/// How the contents of a section are compressed.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum SectionCompression {
+    /// `ELFCOMPRESS_ZLIB`
+    Zlib,
+    /// `ELFCOMPRESS_ZSTD`
+    Zstd,
+    /// The legacy GNU format of `.zdebug_*` sections, zlib with a `ZLIB` magic header.
+    ZlibGnu,
+    /// A compression type this API doesn't know about.
+    Unknown(u32),
+}
+
+/// The header at the start of a compressed section.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct CompressionHeader {
+    pub compression: SectionCompression,
+    pub uncompressed_size: u64,
+    /// Alignment of the uncompressed data, 1 for `.zdebug_*` sections.
+    pub uncompressed_align: u64,
+    /// Size of this header, the compressed data follows it.
+    pub header_size: usize,
+}
+
 #[derive(PartialEq, Eq, Hash)]
 pub struct Section {
     handle: *mut BNSection,
 }
 
 impl Section {
-    unsafe fn from_raw(handle: *mut BNSection) -> Self {
+    pub(crate) unsafe fn from_raw(handle: *mut BNSection) -> Self {
         debug_assert!(!handle.is_null());
         Self { handle }
     }
@@ -143,6 +196,63 @@ impl Section {
     pub fn auto_defined(&self) -> bool {
         unsafe { BNSectionIsAutoDefined(self.handle) }
     }
+
+    /// A builder for a section identical to this one, to re-add it with some of its properties
+    /// changed using [`BinaryViewExt::replace_section`].
+    ///
+    /// ```no_run
+    /// # use binaryninja::binary_view::BinaryViewExt;
+    /// # use binaryninja::section::Semantics;
+    /// let bv = binaryninja::load("example").unwrap();
+    /// let section = bv.section_by_name(".data").unwrap();
+    /// bv.replace_section(&section, section.to_builder().semantics(Semantics::ReadOnlyData));
+    /// ```
+    pub fn to_builder(&self) -> SectionBuilder<BnString> {
+        SectionBuilder {
+            is_auto: self.auto_defined(),
+            name: self.name(),
+            range: self.address_range(),
+            semantics: self.semantics(),
+            _ty: Some(self.section_type()),
+            align: self.align(),
+            entry_size: self.entry_size() as u64,
+            linked_section: Some(self.linked_section()),
+            info_section: Some(self.info_section()),
+            info_data: self.info_data(),
+        }
+    }
+
+    /// The ELF section flags of this section of `view`.
+    ///
+    /// The core does not keep section flags, so they are read from the section header table of
+    /// the file backing `view`. Returns `None` if that is not an ELF file or this section is not
+    /// in its section header table.
+    pub fn flags(&self, view: &BinaryView) -> Option<ElfSectionFlags> {
+        let raw_view = view.raw_view()?;
+        let elf = ElfHeader::parse(|offset, len| raw_view.read_vec(offset, len))?;
+        let header = find_section(&elf.section_headers, self.name().as_str(), self.start())?;
+        Some(header.flags)
+    }
+
+    /// The compression header of this section of `view`, `None` if it is not compressed.
+    ///
+    /// Both `SHF_COMPRESSED` sections and the legacy `.zdebug_*` sections are recognized.
+    pub fn compression(&self, view: &BinaryView) -> Option<CompressionHeader> {
+        if self.name().as_str().starts_with(".zdebug") {
+            let header = view.read_vec(self.start(), GNU_COMPRESSION_HEADER_SIZE);
+            return parse_gnu_compression_header(&header);
+        }
+
+        let raw_view = view.raw_view()?;
+        let elf = ElfHeader::parse(|offset, len| raw_view.read_vec(offset, len))?;
+        let header = find_section(&elf.section_headers, self.name().as_str(), self.start())?;
+        if !header.flags.contains(ElfSectionFlags::COMPRESSED) {
+            return None;
+        }
+        let elf = ElfIdent::from(&elf);
+        let data = view.read_vec(self.start(), elf.compression_header_size());
+        elf.parse_compression_header(&data)
+    }
 }
 
 impl fmt::Debug for Section {
@@ -266,6 +376,12 @@ impl<S: BnStrCompatible> SectionBuilder<S> {
         self
     }
 
+    /// Move the section to `range`.
+    pub fn range(mut self, range: Range<u64>) -> Self {
+        self.range = range;
+        self
+    }
+
     pub(crate) fn create(self, view: &BinaryView) {
         let name = self.name.into_bytes_with_nul();
         let ty = self._ty.map(|s| s.into_bytes_with_nul());
@@ -315,3 +431,177 @@ impl<S: BnStrCompatible> SectionBuilder<S> {
         }
     }
 }
+
+const GNU_COMPRESSION_HEADER_SIZE: usize = 12;
+
+/// Parse the `ZLIB` magic and big endian uncompressed size at the start of a `.zdebug_*` section.
+fn parse_gnu_compression_header(data: &[u8]) -> Option<CompressionHeader> {
+    let size = data.get(4..GNU_COMPRESSION_HEADER_SIZE)?;
+    if &data[..4] != b"ZLIB" {
+        return None;
+    }
+    Some(CompressionHeader {
+        compression: SectionCompression::ZlibGnu,
+        uncompressed_size: u64::from_be_bytes(size.try_into().unwrap()),
+        uncompressed_align: 1,
+        header_size: GNU_COMPRESSION_HEADER_SIZE,
+    })
+}
+
+/// Class and data encoding of an ELF file, which decide the layout of its compression headers.
+#[derive(Copy, Clone, Debug)]
+struct ElfIdent {
+    is_64: bool,
+    little_endian: bool,
+}
+
+impl From<&ElfHeader> for ElfIdent {
+    fn from(elf: &ElfHeader) -> Self {
+        Self {
+            is_64: elf.is_64,
+            little_endian: elf.endianness == Endianness::LittleEndian,
+        }
+    }
+}
+
+impl ElfIdent {
+    fn read_uint(&self, data: &[u8], offset: usize, size: usize) -> Option<u64> {
+        let bytes = data.get(offset..offset + size)?;
+        let value = bytes.iter().enumerate().fold(0u64, |value, (i, b)| {
+            let shift = match self.little_endian {
+                true => i * 8,
+                false => (size - 1 - i) * 8,
+            };
+            value | (*b as u64) << shift
+        });
+        Some(value)
+    }
+
+    /// Read an address sized field.
+    fn read_word(&self, data: &[u8], offset: usize) -> Option<u64> {
+        self.read_uint(data, offset, if self.is_64 { 8 } else { 4 })
+    }
+
+    fn compression_header_size(&self) -> usize {
+        if self.is_64 {
+            24
+        } else {
+            12
+        }
+    }
+
+    fn parse_compression_header(&self, data: &[u8]) -> Option<CompressionHeader> {
+        // Elf64_Chdr has a reserved word after ch_type, Elf32_Chdr does not
+        let ch_type = self.read_uint(data, 0, 4)? as u32;
+        let (size_offset, align_offset) = if self.is_64 { (8, 16) } else { (4, 8) };
+        let compression = match ch_type {
+            1 => SectionCompression::Zlib,
+            2 => SectionCompression::Zstd,
+            other => SectionCompression::Unknown(other),
+        };
+        Some(CompressionHeader {
+            compression,
+            uncompressed_size: self.read_word(data, size_offset)?,
+            uncompressed_align: self.read_word(data, align_offset)?,
+            header_size: self.compression_header_size(),
+        })
+    }
+}
+
+/// The header named `name`, preferring the one at `start` (an address or file offset) when
+/// several sections share the name.
+fn find_section<'a>(
+    headers: &'a [ElfSectionHeader],
+    name: &str,
+    start: u64,
+) -> Option<&'a ElfSectionHeader> {
+    let mut named = headers.iter().filter(|header| header.name == name);
+    let first = named.clone().next()?;
+    Some(
+        named
+            .find(|header| header.addr == start || header.offset == start)
+            .unwrap_or(first),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::headers::reader;
+
+    /// A little endian ELF64 file with a null section, `.debug_info` and `.shstrtab`.
+    fn elf64(debug_info_flags: u64) -> Vec<u8> {
+        let names = b"\0.debug_info\0.shstrtab\0";
+        let strtab_offset = 0x40u64;
+        let shoff = 0x80u64;
+        let mut data = vec![0u8; shoff as usize];
+        data[..6].copy_from_slice(b"\x7fELF\x02\x01");
+        data[0x28..0x30].copy_from_slice(&shoff.to_le_bytes());
+        data[0x3a..0x3c].copy_from_slice(&64u16.to_le_bytes());
+        data[0x3c..0x3e].copy_from_slice(&3u16.to_le_bytes());
+        data[0x3e..0x40].copy_from_slice(&2u16.to_le_bytes());
+        data[strtab_offset as usize..][..names.len()].copy_from_slice(names);
+
+        let section = |name: u32, flags: u64, offset: u64, size: u64| {
+            let mut header = vec![0u8; 64];
+            header[0..4].copy_from_slice(&name.to_le_bytes());
+            header[8..16].copy_from_slice(&flags.to_le_bytes());
+            header[24..32].copy_from_slice(&offset.to_le_bytes());
+            header[32..40].copy_from_slice(&size.to_le_bytes());
+            header
+        };
+        data.extend(section(0, 0, 0, 0));
+        data.extend(section(1, debug_info_flags, 0x1000, 0x20));
+        data.extend(section(13, 0, strtab_offset, names.len() as u64));
+        data
+    }
+
+    #[test]
+    fn finds_elf_section_flags() {
+        let data = elf64(ElfSectionFlags::COMPRESSED.bits());
+        let headers = ElfHeader::parse(reader(&data)).unwrap().section_headers;
+        assert_eq!(headers.len(), 3);
+        let debug_info = find_section(&headers, ".debug_info", 0x1000).unwrap();
+        assert_eq!(debug_info.offset, 0x1000);
+        assert!(debug_info.flags.contains(ElfSectionFlags::COMPRESSED));
+        assert!(find_section(&headers, ".debug_line", 0).is_none());
+
+        let data = elf64(0);
+        let headers = ElfHeader::parse(reader(&data)).unwrap().section_headers;
+        let debug_info = find_section(&headers, ".debug_info", 0x1000).unwrap();
+        assert!(!debug_info.flags.contains(ElfSectionFlags::COMPRESSED));
+    }
+
+    #[test]
+    fn parses_compression_headers() {
+        let elf = ElfIdent {
+            is_64: true,
+            little_endian: true,
+        };
+        let mut chdr = vec![];
+        chdr.extend(2u32.to_le_bytes());
+        chdr.extend(0u32.to_le_bytes());
+        chdr.extend(0x1234u64.to_le_bytes());
+        chdr.extend(8u64.to_le_bytes());
+        let header = elf.parse_compression_header(&chdr).unwrap();
+        assert_eq!(header.compression, SectionCompression::Zstd);
+        assert_eq!(header.uncompressed_size, 0x1234);
+        assert_eq!(header.uncompressed_align, 8);
+        assert_eq!(header.header_size, 24);
+
+        let elf = ElfIdent {
+            is_64: false,
+            little_endian: false,
+        };
+        let chdr = [0, 0, 0, 1, 0, 0, 0, 0x40, 0, 0, 0, 4];
+        let header = elf.parse_compression_header(&chdr).unwrap();
+        assert_eq!(header.compression, SectionCompression::Zlib);
+        assert_eq!(header.uncompressed_size, 0x40);
+        assert_eq!(header.header_size, 12);
+
+        let gnu = parse_gnu_compression_header(b"ZLIB\0\0\0\0\0\0\x01\0").unwrap();
+        assert_eq!(gnu.compression, SectionCompression::ZlibGnu);
+        assert_eq!(gnu.uncompressed_size, 0x100);
+        assert!(parse_gnu_compression_header(b"ZLIX\0\0\0\0\0\0\x01\0").is_none());
+    }
+}
@@ -19,7 +19,7 @@ use std::fmt::{Debug, Formatter};
 
 use std::ops::Range;
 
-use crate::binary_view::BinaryView;
+use crate::binary_view::{BinaryView, BinaryViewExt};
 use crate::rc::*;
 
 fn set_bit(val: u32, bit_mask: u32, new_val: bool) -> u32 {
@@ -49,6 +49,12 @@ impl SegmentBuilder {
         self
     }
 
+    /// Set every flag at once from the raw `BNSegmentFlag` bits, see [`Segment::flags`].
+    pub fn flags(mut self, flags: u32) -> Self {
+        self.flags = flags;
+        self
+    }
+
     pub fn executable(mut self, executable: bool) -> Self {
         self.flags = set_bit(self.flags, 0x01, executable);
         self
@@ -151,7 +157,8 @@ impl Segment {
         }
     }
 
-    fn flags(&self) -> u32 {
+    /// The raw `BNSegmentFlag` bits of the segment.
+    pub fn flags(&self) -> u32 {
         unsafe { BNSegmentGetFlags(self.handle) }
     }
 
@@ -186,6 +193,47 @@ impl Segment {
     pub fn auto_defined(&self) -> bool {
         unsafe { BNSegmentIsAutoDefined(self.handle) }
     }
+
+    /// A builder for a segment identical to this one, to re-add it with some of its properties
+    /// changed using [`BinaryViewExt::replace_segment`].
+    pub fn to_builder(&self) -> SegmentBuilder {
+        let builder = SegmentBuilder::new(self.address_range())
+            .flags(self.flags())
+            .is_auto(self.auto_defined());
+        match self.parent_backing() {
+            Some(parent_backing) => builder.parent_backing(parent_backing),
+            None => builder,
+        }
+    }
+
+    /// Replace the flags of this segment of `view`.
+    ///
+    /// The core can't modify segments in place, so the segment is removed and re-added with the
+    /// same range, after which this handle still describes the old segment.
+    ///
+    /// ```no_run
+    /// # use binaryninja::binary_view::BinaryViewExt;
+    /// let bv = binaryninja::load("example").unwrap();
+    /// let segment = bv.segment_at(0x1000).unwrap();
+    /// // Make the segment read-only
+    /// segment.set_flags(&bv, segment.flags() & !0x02);
+    /// ```
+    pub fn set_flags(&self, view: &BinaryView, flags: u32) {
+        view.replace_segment(self, self.to_builder().flags(flags));
+    }
+
+    /// Grow or shrink this segment of `view` to `length` bytes, see [`Segment::set_flags`].
+    pub fn set_length(&self, view: &BinaryView, length: u64) {
+        let start = self.address_range().start;
+        let builder = self.to_builder();
+        view.replace_segment(
+            self,
+            SegmentBuilder {
+                ea: start..start.wrapping_add(length),
+                ..builder
+            },
+        );
+    }
 }
 
 impl Debug for Segment {
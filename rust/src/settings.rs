@@ -15,12 +15,15 @@
 //! An interface for reading, writing, and creating new settings
 
 use binaryninjacore_sys::*;
-use std::ffi::c_char;
+use std::ffi::{c_char, CStr};
 use std::fmt::Debug;
 
 use crate::binary_view::BinaryView;
 use crate::rc::*;
+#[cfg(not(feature = "serde"))]
+use crate::string::json_string;
 use crate::string::{BnStrCompatible, BnString};
+use crate::Error;
 
 use crate::function::Function;
 
@@ -107,17 +110,10 @@ impl Settings {
         options: &mut QueryOptions,
     ) -> bool {
         let key = key.into_bytes_with_nul();
-        let view_ptr = match options.view.as_ref() {
-            Some(view) => view.handle,
-            _ => std::ptr::null_mut(),
-        };
-        let func_ptr = match options.function.as_ref() {
-            Some(func) => func.handle,
-            _ => std::ptr::null_mut(),
-        };
+        let (settings, view_ptr, func_ptr) = self.query_target(options);
         unsafe {
             BNSettingsGetBool(
-                self.handle,
+                settings.handle,
                 key.as_ref().as_ptr() as *mut _,
                 view_ptr,
                 func_ptr,
@@ -136,17 +132,10 @@ impl Settings {
         options: &mut QueryOptions,
     ) -> f64 {
         let key = key.into_bytes_with_nul();
-        let view_ptr = match options.view.as_ref() {
-            Some(view) => view.handle,
-            _ => std::ptr::null_mut(),
-        };
-        let func_ptr = match options.function.as_ref() {
-            Some(func) => func.handle,
-            _ => std::ptr::null_mut(),
-        };
+        let (settings, view_ptr, func_ptr) = self.query_target(options);
         unsafe {
             BNSettingsGetDouble(
-                self.handle,
+                settings.handle,
                 key.as_ref().as_ptr() as *mut _,
                 view_ptr,
                 func_ptr,
@@ -165,17 +154,10 @@ impl Settings {
         options: &mut QueryOptions,
     ) -> u64 {
         let key = key.into_bytes_with_nul();
-        let view_ptr = match options.view.as_ref() {
-            Some(view) => view.handle,
-            _ => std::ptr::null_mut(),
-        };
-        let func_ptr = match options.function.as_ref() {
-            Some(func) => func.handle,
-            _ => std::ptr::null_mut(),
-        };
+        let (settings, view_ptr, func_ptr) = self.query_target(options);
         unsafe {
             BNSettingsGetUInt64(
-                self.handle,
+                settings.handle,
                 key.as_ref().as_ptr() as *mut _,
                 view_ptr,
                 func_ptr,
@@ -194,17 +176,10 @@ impl Settings {
         options: &mut QueryOptions,
     ) -> BnString {
         let key = key.into_bytes_with_nul();
-        let view_ptr = match options.view.as_ref() {
-            Some(view) => view.handle,
-            _ => std::ptr::null_mut(),
-        };
-        let func_ptr = match options.function.as_ref() {
-            Some(func) => func.handle,
-            _ => std::ptr::null_mut(),
-        };
+        let (settings, view_ptr, func_ptr) = self.query_target(options);
         unsafe {
             BnString::from_raw(BNSettingsGetString(
-                self.handle,
+                settings.handle,
                 key.as_ref().as_ptr() as *mut _,
                 view_ptr,
                 func_ptr,
@@ -223,19 +198,12 @@ impl Settings {
         options: &mut QueryOptions,
     ) -> Array<BnString> {
         let key = key.into_bytes_with_nul();
-        let view_ptr = match options.view.as_ref() {
-            Some(view) => view.handle,
-            _ => std::ptr::null_mut(),
-        };
-        let func_ptr = match options.function.as_ref() {
-            Some(func) => func.handle,
-            _ => std::ptr::null_mut(),
-        };
+        let (settings, view_ptr, func_ptr) = self.query_target(options);
         let mut size: usize = 0;
         unsafe {
             Array::new(
                 BNSettingsGetStringList(
-                    self.handle,
+                    settings.handle,
                     key.as_ref().as_ptr() as *mut _,
                     view_ptr,
                     func_ptr,
@@ -258,17 +226,10 @@ impl Settings {
         options: &mut QueryOptions,
     ) -> BnString {
         let key = key.into_bytes_with_nul();
-        let view_ptr = match options.view.as_ref() {
-            Some(view) => view.handle,
-            _ => std::ptr::null_mut(),
-        };
-        let func_ptr = match options.function.as_ref() {
-            Some(func) => func.handle,
-            _ => std::ptr::null_mut(),
-        };
+        let (settings, view_ptr, func_ptr) = self.query_target(options);
         unsafe {
             BnString::from_raw(BNSettingsGetJson(
-                self.handle,
+                settings.handle,
                 key.as_ref().as_ptr() as *mut _,
                 view_ptr,
                 func_ptr,
@@ -288,17 +249,10 @@ impl Settings {
         options: &QueryOptions,
     ) {
         let key = key.into_bytes_with_nul();
-        let view_ptr = match options.view.as_ref() {
-            Some(view) => view.handle,
-            _ => std::ptr::null_mut(),
-        };
-        let func_ptr = match options.function.as_ref() {
-            Some(func) => func.handle,
-            _ => std::ptr::null_mut(),
-        };
+        let (settings, view_ptr, func_ptr) = self.query_target(options);
         unsafe {
             BNSettingsSetBool(
-                self.handle,
+                settings.handle,
                 view_ptr,
                 func_ptr,
                 options.scope,
@@ -318,17 +272,10 @@ impl Settings {
         options: &QueryOptions,
     ) {
         let key = key.into_bytes_with_nul();
-        let view_ptr = match options.view.as_ref() {
-            Some(view) => view.handle,
-            _ => std::ptr::null_mut(),
-        };
-        let func_ptr = match options.function.as_ref() {
-            Some(func) => func.handle,
-            _ => std::ptr::null_mut(),
-        };
+        let (settings, view_ptr, func_ptr) = self.query_target(options);
         unsafe {
             BNSettingsSetDouble(
-                self.handle,
+                settings.handle,
                 view_ptr,
                 func_ptr,
                 options.scope,
@@ -349,17 +296,10 @@ impl Settings {
         options: &QueryOptions,
     ) {
         let key = key.into_bytes_with_nul();
-        let view_ptr = match options.view.as_ref() {
-            Some(view) => view.handle,
-            _ => std::ptr::null_mut(),
-        };
-        let func_ptr = match options.function.as_ref() {
-            Some(func) => func.handle,
-            _ => std::ptr::null_mut(),
-        };
+        let (settings, view_ptr, func_ptr) = self.query_target(options);
         unsafe {
             BNSettingsSetUInt64(
-                self.handle,
+                settings.handle,
                 view_ptr,
                 func_ptr,
                 options.scope,
@@ -381,17 +321,10 @@ impl Settings {
     ) {
         let key = key.into_bytes_with_nul();
         let value = value.into_bytes_with_nul();
-        let view_ptr = match options.view.as_ref() {
-            Some(view) => view.handle,
-            _ => std::ptr::null_mut(),
-        };
-        let func_ptr = match options.function.as_ref() {
-            Some(func) => func.handle,
-            _ => std::ptr::null_mut(),
-        };
+        let (settings, view_ptr, func_ptr) = self.query_target(options);
         unsafe {
             BNSettingsSetString(
-                self.handle,
+                settings.handle,
                 view_ptr,
                 func_ptr,
                 options.scope,
@@ -426,17 +359,10 @@ impl Settings {
             .map(|s| s.as_ref().as_ptr() as *const c_char)
             .collect();
 
-        let view_ptr = match options.view.as_ref() {
-            Some(view) => view.handle,
-            _ => std::ptr::null_mut(),
-        };
-        let func_ptr = match options.function.as_ref() {
-            Some(func) => func.handle,
-            _ => std::ptr::null_mut(),
-        };
+        let (settings, view_ptr, func_ptr) = self.query_target(options);
         unsafe {
             BNSettingsSetStringList(
-                self.handle,
+                settings.handle,
                 view_ptr,
                 func_ptr,
                 options.scope,
@@ -459,17 +385,10 @@ impl Settings {
     ) -> bool {
         let key = key.into_bytes_with_nul();
         let value = value.into_bytes_with_nul();
-        let view_ptr = match options.view.as_ref() {
-            Some(view) => view.handle,
-            _ => std::ptr::null_mut(),
-        };
-        let func_ptr = match options.function.as_ref() {
-            Some(func) => func.handle,
-            _ => std::ptr::null_mut(),
-        };
+        let (settings, view_ptr, func_ptr) = self.query_target(options);
         unsafe {
             BNSettingsSetJson(
-                self.handle,
+                settings.handle,
                 view_ptr,
                 func_ptr,
                 options.scope,
@@ -479,6 +398,165 @@ impl Settings {
         }
     }
 
+    pub fn get_signed_integer<S: BnStrCompatible>(&self, key: S) -> i64 {
+        self.get_signed_integer_with_opts(key, &mut QueryOptions::default())
+    }
+
+    pub fn get_signed_integer_with_opts<S: BnStrCompatible>(
+        &self,
+        key: S,
+        options: &mut QueryOptions,
+    ) -> i64 {
+        let key = key.into_bytes_with_nul();
+        let (settings, view_ptr, func_ptr) = self.query_target(options);
+        unsafe {
+            BNSettingsGetInt64(
+                settings.handle,
+                key.as_ref().as_ptr() as *mut _,
+                view_ptr,
+                func_ptr,
+                &mut options.scope,
+            )
+        }
+    }
+
+    pub fn set_signed_integer<S: BnStrCompatible>(&self, key: S, value: i64) {
+        self.set_signed_integer_with_opts(key, value, &QueryOptions::default())
+    }
+
+    pub fn set_signed_integer_with_opts<S: BnStrCompatible>(
+        &self,
+        key: S,
+        value: i64,
+        options: &QueryOptions,
+    ) {
+        let key = key.into_bytes_with_nul();
+        let (settings, view_ptr, func_ptr) = self.query_target(options);
+        unsafe {
+            BNSettingsSetInt64(
+                settings.handle,
+                view_ptr,
+                func_ptr,
+                options.scope,
+                key.as_ref().as_ptr() as *mut _,
+                value,
+            );
+        }
+    }
+
+    /// Read `key` as a `T`, `None` if no such setting is registered.
+    ///
+    /// After the call `options.scope` is the scope the value was read from.
+    ///
+    /// ```no_run
+    /// use binaryninja::settings::{QueryOptions, Settings};
+    ///
+    /// let view = binaryninja::load("/bin/cat").unwrap();
+    /// let mut options = QueryOptions::new().with_view(&view);
+    /// let max_size: u64 = Settings::new()
+    ///     .get_or("analysis.limits.maxFunctionSize", 65536, &mut options);
+    /// ```
+    pub fn get<T: SettingValue, S: BnStrCompatible>(
+        &self,
+        key: S,
+        options: &mut QueryOptions,
+    ) -> Option<T> {
+        let key = key.into_bytes_with_nul();
+        let key = CStr::from_bytes_with_nul(key.as_ref()).ok()?;
+        if !self.contains(key) {
+            return None;
+        }
+        Some(T::get(self, key, options))
+    }
+
+    /// Read `key` as a `T`, `default` if no such setting is registered.
+    pub fn get_or<T: SettingValue, S: BnStrCompatible>(
+        &self,
+        key: S,
+        default: T,
+        options: &mut QueryOptions,
+    ) -> T {
+        self.get(key, options).unwrap_or(default)
+    }
+
+    /// Write `value` to `key` in the scope and for the view or function of `options`.
+    pub fn set<T: SettingValue, S: BnStrCompatible>(
+        &self,
+        key: S,
+        value: T,
+        options: &QueryOptions,
+    ) {
+        let key = key.into_bytes_with_nul();
+        if let Ok(key) = CStr::from_bytes_with_nul(key.as_ref()) {
+            value.set(self, key, options);
+        }
+    }
+
+    /// Reset `key` to its default value.
+    pub fn reset<S: BnStrCompatible>(&self, key: S) -> bool {
+        self.reset_with_opts(key, &QueryOptions::default())
+    }
+
+    pub fn reset_with_opts<S: BnStrCompatible>(&self, key: S, options: &QueryOptions) -> bool {
+        let key = key.into_bytes_with_nul();
+        let (settings, view_ptr, func_ptr) = self.query_target(options);
+        unsafe {
+            BNSettingsReset(
+                settings.handle,
+                key.as_ref().as_ptr() as *mut _,
+                view_ptr,
+                func_ptr,
+                options.scope,
+            )
+        }
+    }
+
+    /// Reset all settings in the scope of `options`, or only their schema if `schema_only` is set.
+    pub fn reset_all_with_opts(&self, options: &QueryOptions, schema_only: bool) -> bool {
+        let (settings, view_ptr, func_ptr) = self.query_target(options);
+        unsafe {
+            BNSettingsResetAll(
+                settings.handle,
+                view_ptr,
+                func_ptr,
+                options.scope,
+                schema_only,
+            )
+        }
+    }
+
+    /// The settings set in the scope of `options`, as JSON.
+    pub fn serialize_settings_with_opts(&self, options: &QueryOptions) -> BnString {
+        let (settings, view_ptr, func_ptr) = self.query_target(options);
+        unsafe {
+            BnString::from_raw(BNSerializeSettings(
+                settings.handle,
+                view_ptr,
+                func_ptr,
+                options.scope,
+            ))
+        }
+    }
+
+    /// Apply settings serialized with [`Settings::serialize_settings_with_opts`].
+    pub fn deserialize_settings_with_opts<S: BnStrCompatible>(
+        &self,
+        contents: S,
+        options: &QueryOptions,
+    ) -> bool {
+        let contents = contents.into_bytes_with_nul();
+        let (settings, view_ptr, func_ptr) = self.query_target(options);
+        unsafe {
+            BNDeserializeSettings(
+                settings.handle,
+                contents.as_ref().as_ptr() as *mut _,
+                view_ptr,
+                func_ptr,
+                options.scope,
+            )
+        }
+    }
+
     pub fn get_property_string<S: BnStrCompatible>(&self, key: S, property: S) -> BnString {
         let key = key.into_bytes_with_nul();
         let property = property.into_bytes_with_nul();
@@ -625,7 +703,69 @@ impl Settings {
         }
     }
 
-    // TODO: register_setting but type-safely turn it into json
+    /// Register `key` with the properties of `schema`, the group of the key must be registered.
+    ///
+    /// Returns `false` if the core rejects the setting or `schema` has a number that isn't finite.
+    ///
+    /// ```no_run
+    /// use binaryninja::settings::{SettingDefault, SettingSchema, Settings, SettingsScope};
+    ///
+    /// let settings = Settings::new();
+    /// settings.register_group("myPlugin", "My Plugin");
+    /// settings.register_setting(
+    ///     "myPlugin.mode",
+    ///     &SettingSchema::new("Mode", SettingDefault::String("fast".into()))
+    ///         .description("How thorough the analysis of the plugin is.")
+    ///         .enum_value("fast", "Only analyze functions with symbols.")
+    ///         .enum_value("full", "Analyze every function.")
+    ///         .ignore_scope(SettingsScope::SettingsProjectScope),
+    /// );
+    /// ```
+    pub fn register_setting<S: BnStrCompatible>(&self, key: S, schema: &SettingSchema) -> bool {
+        match schema.to_json() {
+            Ok(json) => self.register_setting_json(key, json),
+            Err(err) => {
+                log::error!("Failed to register setting `{}`: {}", schema.title, err);
+                false
+            }
+        }
+    }
+
+    /// A separate instance for the settings of the resource `resource_id`, such as a project
+    /// file, with the schema of this instance.
+    ///
+    /// Every user of an instance sees the resource it is switched to, so queries for a resource
+    /// go through an instance of their own instead. Instances are shared by id, the schema is
+    /// copied when the instance of a resource is first created.
+    pub fn for_resource<S: AsRef<str>>(&self, resource_id: S) -> Ref<Settings> {
+        let resource_id = resource_id.as_ref();
+        let settings = Settings::new_with_id(format!("resource:{}", resource_id));
+        if settings.keys().is_empty() {
+            settings.deserialize_schema(self.serialize_schema());
+        }
+        settings.set_resource_id(resource_id);
+        settings
+    }
+
+    /// The instance and the view and function handles to pass to the core for `options`.
+    fn query_target(
+        &self,
+        options: &QueryOptions,
+    ) -> (Ref<Settings>, *mut BNBinaryView, *mut BNFunction) {
+        let settings = match &options.resource_id {
+            Some(resource_id) => self.for_resource(resource_id),
+            None => self.to_owned(),
+        };
+        let view_ptr = match options.view.as_ref() {
+            Some(view) => view.handle,
+            _ => std::ptr::null_mut(),
+        };
+        let func_ptr = match options.function.as_ref() {
+            Some(func) => func.handle,
+            _ => std::ptr::null_mut(),
+        };
+        (settings, view_ptr, func_ptr)
+    }
 }
 
 impl Default for Ref<Settings> {
@@ -657,11 +797,27 @@ unsafe impl RefCountable for Settings {
     }
 }
 
+/// Where a setting is read from or written to.
+///
+/// By default settings are read from the most specific scope that has a value and written to the
+/// user scope. Narrow that down by targeting a view, function or scope:
+///
+/// ```no_run
+/// use binaryninja::settings::{QueryOptions, Settings, SettingsScope};
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// let options = QueryOptions::new()
+///     .with_view(&view)
+///     .with_scope(SettingsScope::SettingsResourceScope);
+/// Settings::new().set("analysis.limits.maxFunctionSize", 131072u64, &options);
+/// ```
 #[derive(Debug, Clone)]
 pub struct QueryOptions<'a> {
     pub scope: SettingsScope,
     pub view: Option<&'a BinaryView>,
     pub function: Option<Ref<Function>>,
+    /// Resource the query targets, through the instance [`Settings::for_resource`] returns.
+    pub resource_id: Option<String>,
 }
 
 impl<'a> QueryOptions<'a> {
@@ -699,6 +855,12 @@ impl<'a> QueryOptions<'a> {
         self.function = Some(function);
         self
     }
+
+    /// Set the query to target the settings of a resource, such as a project file.
+    pub fn with_resource_id<S: Into<String>>(mut self, resource_id: S) -> Self {
+        self.resource_id = Some(resource_id.into());
+        self
+    }
 }
 
 impl Default for QueryOptions<'_> {
@@ -707,6 +869,311 @@ impl Default for QueryOptions<'_> {
             view: None,
             scope: SettingsScope::SettingsDefaultScope,
             function: None,
+            resource_id: None,
         }
     }
 }
+
+/// The type of a setting, with its default value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SettingDefault {
+    Boolean(bool),
+    Number(f64),
+    String(String),
+    StringList(Vec<String>),
+}
+
+/// The properties of a setting registered with [`Settings::register_setting`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SettingSchema {
+    pub title: String,
+    pub description: String,
+    pub default: SettingDefault,
+    /// The values a string setting is limited to, with their descriptions.
+    pub enum_values: Vec<(String, String)>,
+    pub min_value: Option<f64>,
+    pub max_value: Option<f64>,
+    /// The scopes the setting can't be set in.
+    pub ignored_scopes: Vec<SettingsScope>,
+    pub read_only: bool,
+    pub requires_restart: bool,
+    pub hidden: bool,
+}
+
+impl SettingSchema {
+    pub fn new<S: Into<String>>(title: S, default: SettingDefault) -> Self {
+        Self {
+            title: title.into(),
+            description: String::new(),
+            default,
+            enum_values: vec![],
+            min_value: None,
+            max_value: None,
+            ignored_scopes: vec![],
+            read_only: false,
+            requires_restart: false,
+            hidden: false,
+        }
+    }
+
+    pub fn description<S: Into<String>>(mut self, description: S) -> Self {
+        self.description = description.into();
+        self
+    }
+
+    /// Limit a string setting to the values added with this.
+    pub fn enum_value<S: Into<String>, D: Into<String>>(
+        mut self,
+        value: S,
+        description: D,
+    ) -> Self {
+        self.enum_values.push((value.into(), description.into()));
+        self
+    }
+
+    pub fn range(mut self, min_value: f64, max_value: f64) -> Self {
+        self.min_value = Some(min_value);
+        self.max_value = Some(max_value);
+        self
+    }
+
+    pub fn ignore_scope(mut self, scope: SettingsScope) -> Self {
+        self.ignored_scopes.push(scope);
+        self
+    }
+
+    pub fn read_only(mut self) -> Self {
+        self.read_only = true;
+        self
+    }
+
+    pub fn requires_restart(mut self) -> Self {
+        self.requires_restart = true;
+        self
+    }
+
+    pub fn hidden(mut self) -> Self {
+        self.hidden = true;
+        self
+    }
+
+    /// The properties as the JSON object [`Settings::register_setting_json`] takes.
+    ///
+    /// JSON has no infinity or NaN, so the default, minimum and maximum must be finite.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, Error> {
+        use serde_json::{json, Map, Value};
+
+        self.check_finite()?;
+        let mut json = Map::new();
+        json.insert("title".into(), json!(self.title));
+        let (ty, default) = match &self.default {
+            SettingDefault::Boolean(value) => ("boolean", json!(value)),
+            SettingDefault::Number(value) => ("number", json!(value)),
+            SettingDefault::String(value) => ("string", json!(value)),
+            SettingDefault::StringList(values) => {
+                json.insert("elementType".into(), json!("string"));
+                ("array", json!(values))
+            }
+        };
+        json.insert("type".into(), json!(ty));
+        json.insert("default".into(), default);
+        json.insert("description".into(), json!(self.description));
+        if !self.enum_values.is_empty() {
+            let (values, descriptions): (Vec<_>, Vec<_>) = self.enum_values.iter().cloned().unzip();
+            json.insert("enum".into(), json!(values));
+            json.insert("enumDescriptions".into(), json!(descriptions));
+        }
+        if let Some(min_value) = self.min_value {
+            json.insert("minValue".into(), json!(min_value));
+        }
+        if let Some(max_value) = self.max_value {
+            json.insert("maxValue".into(), json!(max_value));
+        }
+        json.insert("ignore".into(), json!(self.ignored_scope_names()));
+        for (name, set) in [
+            ("readOnly", self.read_only),
+            ("requiresRestart", self.requires_restart),
+            ("hidden", self.hidden),
+        ] {
+            if set {
+                json.insert(name.into(), json!(true));
+            }
+        }
+        Ok(Value::Object(json).to_string())
+    }
+
+    /// The properties as the JSON object [`Settings::register_setting_json`] takes.
+    ///
+    /// JSON has no infinity or NaN, so the default, minimum and maximum must be finite.
+    #[cfg(not(feature = "serde"))]
+    pub fn to_json(&self) -> Result<String, Error> {
+        self.check_finite()?;
+        let string_list = |strings: &mut dyn Iterator<Item = &str>| {
+            let strings: Vec<String> = strings.map(json_string).collect();
+            format!("[{}]", strings.join(","))
+        };
+        let (ty, default) = match &self.default {
+            SettingDefault::Boolean(value) => ("\"boolean\"", value.to_string()),
+            SettingDefault::Number(value) => ("\"number\"", value.to_string()),
+            SettingDefault::String(value) => ("\"string\"", json_string(value)),
+            SettingDefault::StringList(values) => (
+                "\"array\",\"elementType\":\"string\"",
+                string_list(&mut values.iter().map(String::as_str)),
+            ),
+        };
+        let mut json = format!(
+            "{{\"title\":{},\"type\":{},\"default\":{},\"description\":{}",
+            json_string(&self.title),
+            ty,
+            default,
+            json_string(&self.description)
+        );
+        if !self.enum_values.is_empty() {
+            json.push_str(&format!(
+                ",\"enum\":{},\"enumDescriptions\":{}",
+                string_list(&mut self.enum_values.iter().map(|(value, _)| value.as_str())),
+                string_list(&mut self.enum_values.iter().map(|(_, desc)| desc.as_str()))
+            ));
+        }
+        if let Some(min_value) = self.min_value {
+            json.push_str(&format!(",\"minValue\":{}", min_value));
+        }
+        if let Some(max_value) = self.max_value {
+            json.push_str(&format!(",\"maxValue\":{}", max_value));
+        }
+        json.push_str(&format!(
+            ",\"ignore\":{}",
+            string_list(&mut self.ignored_scope_names().into_iter())
+        ));
+        for (name, set) in [
+            ("readOnly", self.read_only),
+            ("requiresRestart", self.requires_restart),
+            ("hidden", self.hidden),
+        ] {
+            if set {
+                json.push_str(&format!(",\"{}\":true", name));
+            }
+        }
+        json.push('}');
+        Ok(json)
+    }
+
+    fn check_finite(&self) -> Result<(), Error> {
+        let default = match self.default {
+            SettingDefault::Number(value) => Some(value),
+            _ => None,
+        };
+        for (name, value) in [
+            ("default", default),
+            ("minimum", self.min_value),
+            ("maximum", self.max_value),
+        ] {
+            if value.is_some_and(|value| !value.is_finite()) {
+                return Err(Error::InvalidArgument(format!(
+                    "{} of setting `{}` is not finite",
+                    name, self.title
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn ignored_scope_names(&self) -> Vec<&'static str> {
+        self.ignored_scopes
+            .iter()
+            .filter_map(|scope| match scope {
+                SettingsScope::SettingsAutoScope => Some("SettingsAutoScope"),
+                SettingsScope::SettingsDefaultScope => Some("SettingsDefaultScope"),
+                SettingsScope::SettingsUserScope => Some("SettingsUserScope"),
+                SettingsScope::SettingsProjectScope => Some("SettingsProjectScope"),
+                SettingsScope::SettingsResourceScope => Some("SettingsResourceScope"),
+                SettingsScope::SettingsInvalidScope => None,
+            })
+            .collect()
+    }
+}
+
+/// Types settings can be read as and written from with [`Settings::get`] and [`Settings::set`].
+pub trait SettingValue: Sized {
+    fn get(settings: &Settings, key: &CStr, options: &mut QueryOptions) -> Self;
+    fn set(self, settings: &Settings, key: &CStr, options: &QueryOptions);
+}
+
+impl SettingValue for bool {
+    fn get(settings: &Settings, key: &CStr, options: &mut QueryOptions) -> Self {
+        settings.get_bool_with_opts(key, options)
+    }
+
+    fn set(self, settings: &Settings, key: &CStr, options: &QueryOptions) {
+        settings.set_bool_with_opts(key, self, options);
+    }
+}
+
+impl SettingValue for f64 {
+    fn get(settings: &Settings, key: &CStr, options: &mut QueryOptions) -> Self {
+        settings.get_double_with_opts(key, options)
+    }
+
+    fn set(self, settings: &Settings, key: &CStr, options: &QueryOptions) {
+        settings.set_double_with_opts(key, self, options);
+    }
+}
+
+impl SettingValue for u64 {
+    fn get(settings: &Settings, key: &CStr, options: &mut QueryOptions) -> Self {
+        settings.get_integer_with_opts(key, options)
+    }
+
+    fn set(self, settings: &Settings, key: &CStr, options: &QueryOptions) {
+        settings.set_integer_with_opts(key, self, options);
+    }
+}
+
+impl SettingValue for i64 {
+    fn get(settings: &Settings, key: &CStr, options: &mut QueryOptions) -> Self {
+        settings.get_signed_integer_with_opts(key, options)
+    }
+
+    fn set(self, settings: &Settings, key: &CStr, options: &QueryOptions) {
+        settings.set_signed_integer_with_opts(key, self, options);
+    }
+}
+
+impl SettingValue for String {
+    fn get(settings: &Settings, key: &CStr, options: &mut QueryOptions) -> Self {
+        settings.get_string_with_opts(key, options).to_string()
+    }
+
+    fn set(self, settings: &Settings, key: &CStr, options: &QueryOptions) {
+        settings.set_string_with_opts(key, self, options);
+    }
+}
+
+impl SettingValue for Vec<String> {
+    fn get(settings: &Settings, key: &CStr, options: &mut QueryOptions) -> Self {
+        settings
+            .get_string_list_with_opts(key, options)
+            .iter()
+            .map(|s| s.to_string())
+            .collect()
+    }
+
+    fn set(self, settings: &Settings, key: &CStr, options: &QueryOptions) {
+        settings.set_string_list_with_opts(key, self.into_iter(), options);
+    }
+}
+
+/// JSON settings, e.g. objects, which have no typed accessor of their own. Settings that aren't
+/// valid JSON read as `Null`.
+#[cfg(feature = "serde")]
+impl SettingValue for serde_json::Value {
+    fn get(settings: &Settings, key: &CStr, options: &mut QueryOptions) -> Self {
+        let json = settings.get_json_with_opts(key, options);
+        serde_json::from_str(json.as_str()).unwrap_or(serde_json::Value::Null)
+    }
+
+    fn set(self, settings: &Settings, key: &CStr, options: &QueryOptions) {
+        settings.set_json_with_opts(key, self.to_string(), options);
+    }
+}
@@ -0,0 +1,893 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Match YARA style signature rules against the contents of a view.
+
+use std::collections::HashMap;
+use std::iter::Peekable;
+use std::ops::Range;
+use std::str::CharIndices;
+
+use crate::binary_view::{BinaryView, BinaryViewExt};
+use crate::search::{map_parallel, scan_chunks, BytePattern, BytePatternError};
+use crate::tags::TagType;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SignatureError {
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: String },
+    #[error("line {line}: {source}")]
+    Pattern {
+        line: usize,
+        source: BytePatternError,
+    },
+    #[error("rule `{rule}` has no string `{name}`")]
+    UndefinedString { rule: String, name: String },
+}
+
+/// A string of a rule, which matches where any of its patterns does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleString {
+    /// The name of the string including its `$`.
+    pub name: String,
+    pub patterns: Vec<BytePattern>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+    GreaterOrEqual,
+    Greater,
+}
+
+/// How many of the strings of a rule must match, in `N of them`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Quantifier {
+    Any,
+    All,
+    AtLeast(usize),
+}
+
+/// The condition of a rule, over the matches of its strings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Condition {
+    /// `$name`, the string matched at least once.
+    Matched(String),
+    /// `#name >= 2`, the number of matches of the string compared to a count.
+    Count(String, Comparison, usize),
+    /// `any of them`, `all of them` or `N of them`.
+    Of(Quantifier),
+    And(Box<Condition>, Box<Condition>),
+    Or(Box<Condition>, Box<Condition>),
+    Not(Box<Condition>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub name: String,
+    pub tags: Vec<String>,
+    /// The entries of the `meta:` section, in order.
+    pub meta: Vec<(String, String)>,
+    pub strings: Vec<RuleString>,
+    pub condition: Condition,
+}
+
+/// A match of a string of a rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringMatch {
+    pub name: String,
+    pub address: u64,
+    pub len: usize,
+}
+
+/// A rule whose condition held, with every match of its strings in address order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureMatch {
+    pub rule: String,
+    pub tags: Vec<String>,
+    pub strings: Vec<StringMatch>,
+}
+
+/// A match of the pattern of a rule string, before conditions are evaluated.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct Hit {
+    rule: usize,
+    string: usize,
+    address: u64,
+    len: usize,
+}
+
+impl Comparison {
+    fn compare(&self, left: usize, right: usize) -> bool {
+        match self {
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Equal => left == right,
+            Comparison::NotEqual => left != right,
+            Comparison::GreaterOrEqual => left >= right,
+            Comparison::Greater => left > right,
+        }
+    }
+}
+
+impl Condition {
+    /// Whether the condition holds when the strings of `rule` matched `counts` times each.
+    fn evaluate(&self, rule: &Rule, counts: &[usize]) -> bool {
+        let count = |name: &str| {
+            rule.strings
+                .iter()
+                .position(|string| string.name == name)
+                .map_or(0, |index| counts[index])
+        };
+        match self {
+            Condition::Matched(name) => count(name) > 0,
+            Condition::Count(name, comparison, value) => comparison.compare(count(name), *value),
+            Condition::Of(quantifier) => {
+                let matched = counts.iter().filter(|count| **count > 0).count();
+                match quantifier {
+                    Quantifier::Any => matched > 0,
+                    Quantifier::All => matched == counts.len(),
+                    Quantifier::AtLeast(n) => matched >= *n,
+                }
+            }
+            Condition::And(left, right) => {
+                left.evaluate(rule, counts) && right.evaluate(rule, counts)
+            }
+            Condition::Or(left, right) => {
+                left.evaluate(rule, counts) || right.evaluate(rule, counts)
+            }
+            Condition::Not(condition) => !condition.evaluate(rule, counts),
+        }
+    }
+
+    /// The names of the strings the condition refers to.
+    fn names(&self) -> Vec<&str> {
+        match self {
+            Condition::Matched(name) | Condition::Count(name, _, _) => vec![name],
+            Condition::Of(_) => vec![],
+            Condition::And(left, right) | Condition::Or(left, right) => {
+                let mut names = left.names();
+                names.extend(right.names());
+                names
+            }
+            Condition::Not(condition) => condition.names(),
+        }
+    }
+}
+
+impl SignatureMatch {
+    /// Add a user data tag of `tag_type` to `view` at every string match, with the rule and string
+    /// name as its data.
+    pub fn add_tags(&self, view: &BinaryView, tag_type: &TagType) {
+        for string in &self.strings {
+            let data = format!("{} {}", self.rule, string.name);
+            view.add_tag(string.address, tag_type, data, true);
+        }
+    }
+}
+
+/// A compiled set of rules, written in a subset of the YARA language.
+///
+/// The strings of a rule are hex strings, written like a [`BytePattern`] with `??` wildcards, or
+/// text strings with the `ascii`, `wide` (UTF-16LE) and `nocase` modifiers. Conditions combine
+/// `$name` (the string matched), `#name` compared to a number (the number of matches of the
+/// string), `any of them`, `all of them` and `N of them` with `and`, `or`, `not` and parentheses.
+/// A `meta:` section is kept but not interpreted. Other YARA features, such as modules, jumps in
+/// hex strings, regular expressions and match offsets, are not supported.
+///
+/// ```no_run
+/// use binaryninja::signatures::RuleSet;
+///
+/// let rules = RuleSet::compile(
+///     r#"
+///     rule upx : packer {
+///         strings:
+///             $magic = "UPX!"
+///             $stub = { 60 BE ?? ?? ?? ?? 8D BE }
+///         condition:
+///             $magic and #stub >= 1
+///     }
+///     "#,
+/// )
+/// .unwrap();
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// for found in rules.scan(&view) {
+///     println!("{} {:?}: {:x?}", found.rule, found.tags, found.strings);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    /// A set of rules built in code rather than compiled.
+    ///
+    /// Fails if a condition refers to a string its rule doesn't have.
+    pub fn new(rules: Vec<Rule>) -> Result<Self, SignatureError> {
+        for rule in &rules {
+            for name in rule.condition.names() {
+                if !rule.strings.iter().any(|string| string.name == name) {
+                    return Err(SignatureError::UndefinedString {
+                        rule: rule.name.clone(),
+                        name: name.to_string(),
+                    });
+                }
+            }
+        }
+        Ok(Self { rules })
+    }
+
+    /// Compile the rules of `source`.
+    pub fn compile(source: &str) -> Result<Self, SignatureError> {
+        let mut parser = Parser {
+            tokens: Lexer::new(source).tokenize()?,
+            pos: 0,
+        };
+        let mut rules = Vec::new();
+        while parser.peek().is_some() {
+            rules.push(parser.rule()?);
+        }
+        Self::new(rules)
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    /// Match the rules against the mapped contents of `view`, scanning chunks on all available
+    /// threads. Matches don't cross from one segment into the next.
+    pub fn scan(&self, view: &BinaryView) -> Vec<SignatureMatch> {
+        let hits = map_parallel(scan_chunks(view), |(chunk, limit)| {
+            self.scan_chunk(view, chunk, limit)
+        });
+        self.collect_matches(hits.into_iter().flatten().collect())
+    }
+
+    /// Match the rules against `data`, as if it were at address `base`.
+    pub fn scan_bytes(&self, data: &[u8], base: u64) -> Vec<SignatureMatch> {
+        let end = base + data.len() as u64;
+        self.collect_matches(self.find_hits(data, base, end))
+    }
+
+    /// The longest pattern of any rule, matches starting near the end of a chunk read this much
+    /// past it.
+    fn max_pattern_len(&self) -> usize {
+        self.rules
+            .iter()
+            .flat_map(|rule| &rule.strings)
+            .flat_map(|string| &string.patterns)
+            .map(|pattern| pattern.len())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn scan_chunk(&self, view: &BinaryView, chunk: Range<u64>, limit: u64) -> Vec<Hit> {
+        let overlap = self.max_pattern_len().saturating_sub(1) as u64;
+        let read_end = chunk.end.saturating_add(overlap).min(limit);
+        let data = view.read_vec(chunk.start, (read_end - chunk.start) as usize);
+        self.find_hits(&data, chunk.start, chunk.end)
+    }
+
+    /// The hits in `data` at `base` that start before `end`.
+    fn find_hits(&self, data: &[u8], base: u64, end: u64) -> Vec<Hit> {
+        let mut hits = Vec::new();
+        for (rule_index, rule) in self.rules.iter().enumerate() {
+            for (string_index, string) in rule.strings.iter().enumerate() {
+                for pattern in &string.patterns {
+                    let found = pattern
+                        .find_in(data)
+                        .map(|offset| base + offset as u64)
+                        .take_while(|address| *address < end)
+                        .map(|address| Hit {
+                            rule: rule_index,
+                            string: string_index,
+                            address,
+                            len: pattern.len(),
+                        });
+                    hits.extend(found);
+                }
+            }
+        }
+        hits
+    }
+
+    fn collect_matches(&self, mut hits: Vec<Hit>) -> Vec<SignatureMatch> {
+        // Alternative patterns of a string, like its ascii and wide forms, count once per address
+        hits.sort();
+        hits.dedup_by_key(|hit| (hit.rule, hit.string, hit.address));
+
+        let mut by_rule: HashMap<usize, Vec<Hit>> = HashMap::new();
+        for hit in hits {
+            by_rule.entry(hit.rule).or_default().push(hit);
+        }
+        let mut matches = Vec::new();
+        for (index, rule) in self.rules.iter().enumerate() {
+            let hits = by_rule.remove(&index).unwrap_or_default();
+            let mut counts = vec![0; rule.strings.len()];
+            for hit in &hits {
+                counts[hit.string] += 1;
+            }
+            if !rule.condition.evaluate(rule, &counts) {
+                continue;
+            }
+            let mut strings: Vec<StringMatch> = hits
+                .iter()
+                .map(|hit| StringMatch {
+                    name: rule.strings[hit.string].name.clone(),
+                    address: hit.address,
+                    len: hit.len,
+                })
+                .collect();
+            strings.sort_by_key(|string| string.address);
+            matches.push(SignatureMatch {
+                rule: rule.name.clone(),
+                tags: rule.tags.clone(),
+                strings,
+            });
+        }
+        matches
+    }
+}
+
+/// The pattern of a text string, where `nocase` letters match either case.
+fn text_pattern(text: &[u8], wide: bool, nocase: bool) -> BytePattern {
+    let mut pattern = BytePattern::from_bytes(&[]);
+    for byte in text {
+        // ASCII letters only differ from the other case in bit 5
+        match nocase && byte.is_ascii_alphabetic() {
+            true => pattern.push(byte & !0x20, !0x20),
+            false => pattern.push(*byte, 0xff),
+        }
+        if wide {
+            pattern.push(0, 0xff);
+        }
+    }
+    pattern
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    /// `$name`, kept with its `$`.
+    StringId(String),
+    /// `#name`, kept as the `$name` it counts.
+    CountId(String),
+    Text(Vec<u8>),
+    /// The contents of `{ ... }` after `=`.
+    Hex(String),
+    Number(usize),
+    Punct(&'static str),
+}
+
+struct Lexer<'a> {
+    source: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    line: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            source,
+            chars: source.char_indices().peekable(),
+            line: 1,
+        }
+    }
+
+    fn error(&self, message: impl Into<String>) -> SignatureError {
+        SignatureError::Syntax {
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let (_, c) = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn eat(&mut self, expected: char) -> bool {
+        let found = self.chars.peek().is_some_and(|(_, c)| *c == expected);
+        if found {
+            self.bump();
+        }
+        found
+    }
+
+    /// Consume characters while `f` holds and return them.
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &'a str {
+        let start = self.chars.peek().map_or(self.source.len(), |(i, _)| *i);
+        while self.chars.peek().is_some_and(|(_, c)| f(*c)) {
+            self.bump();
+        }
+        let end = self.chars.peek().map_or(self.source.len(), |(i, _)| *i);
+        &self.source[start..end]
+    }
+
+    fn tokenize(mut self) -> Result<Vec<(Token, usize)>, SignatureError> {
+        let is_ident = |c: char| c.is_ascii_alphanumeric() || c == '_';
+        let mut tokens: Vec<(Token, usize)> = Vec::new();
+        while let Some(&(_, c)) = self.chars.peek() {
+            let line = self.line;
+            let token = match c {
+                c if c.is_whitespace() => {
+                    self.bump();
+                    continue;
+                }
+                '/' => {
+                    self.bump();
+                    if self.eat('/') {
+                        self.take_while(|c| c != '\n');
+                    } else if self.eat('*') {
+                        while !(self
+                            .bump()
+                            .ok_or_else(|| self.error("unterminated comment"))?
+                            == '*'
+                            && self.eat('/'))
+                        {}
+                    } else {
+                        return Err(self.error("unexpected `/`"));
+                    }
+                    continue;
+                }
+                '{' if tokens
+                    .last()
+                    .is_some_and(|(token, _)| *token == Token::Punct("=")) =>
+                {
+                    self.bump();
+                    let hex = self.take_while(|c| c != '}').to_string();
+                    if !self.eat('}') {
+                        return Err(self.error("unterminated hex string"));
+                    }
+                    Token::Hex(hex)
+                }
+                '"' => {
+                    self.bump();
+                    Token::Text(self.text()?)
+                }
+                '$' | '#' => {
+                    self.bump();
+                    let name = format!("${}", self.take_while(is_ident));
+                    if name.len() == 1 {
+                        return Err(self.error(format!("`{}` without a name", c)));
+                    }
+                    match c {
+                        '$' => Token::StringId(name),
+                        _ => Token::CountId(name),
+                    }
+                }
+                c if c.is_ascii_digit() => {
+                    let digits = self.take_while(is_ident);
+                    let number = match digits.strip_prefix("0x") {
+                        Some(hex) => usize::from_str_radix(hex, 16),
+                        None => digits.parse(),
+                    };
+                    Token::Number(
+                        number.map_err(|_| self.error(format!("invalid number `{}`", digits)))?,
+                    )
+                }
+                c if is_ident(c) => Token::Ident(self.take_while(is_ident).to_string()),
+                _ => {
+                    self.bump();
+                    let punct = match (c, self.chars.peek().map(|(_, c)| *c)) {
+                        ('=', Some('=')) => "==",
+                        ('!', Some('=')) => "!=",
+                        ('>', Some('=')) => ">=",
+                        ('<', Some('=')) => "<=",
+                        ('{', _) => "{",
+                        ('}', _) => "}",
+                        ('(', _) => "(",
+                        (')', _) => ")",
+                        (':', _) => ":",
+                        ('=', _) => "=",
+                        ('>', _) => ">",
+                        ('<', _) => "<",
+                        _ => return Err(self.error(format!("unexpected `{}`", c))),
+                    };
+                    if punct.len() == 2 {
+                        self.bump();
+                    }
+                    Token::Punct(punct)
+                }
+            };
+            tokens.push((token, line));
+        }
+        Ok(tokens)
+    }
+
+    /// The bytes of a text string after its opening quote, with escapes decoded.
+    fn text(&mut self) -> Result<Vec<u8>, SignatureError> {
+        let mut bytes = Vec::new();
+        loop {
+            let c = self
+                .bump()
+                .ok_or_else(|| self.error("unterminated string"))?;
+            match c {
+                '"' => return Ok(bytes),
+                '\n' => return Err(self.error("unterminated string")),
+                '\\' => {
+                    let escaped = self
+                        .bump()
+                        .ok_or_else(|| self.error("unterminated string"))?;
+                    match escaped {
+                        'n' => bytes.push(b'\n'),
+                        'r' => bytes.push(b'\r'),
+                        't' => bytes.push(b'\t'),
+                        '\\' | '"' => bytes.push(escaped as u8),
+                        'x' => {
+                            let hex: String =
+                                [self.bump(), self.bump()].into_iter().flatten().collect();
+                            let byte = u8::from_str_radix(&hex, 16)
+                                .map_err(|_| self.error(format!("invalid escape `\\x{}`", hex)))?;
+                            bytes.push(byte);
+                        }
+                        _ => return Err(self.error(format!("invalid escape `\\{}`", escaped))),
+                    }
+                }
+                c => {
+                    let mut buffer = [0; 4];
+                    bytes.extend(c.encode_utf8(&mut buffer).as_bytes());
+                }
+            }
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or(self.tokens.last())
+            .map_or(1, |(_, line)| *line)
+    }
+
+    fn error(&self, message: impl Into<String>) -> SignatureError {
+        SignatureError::Syntax {
+            line: self.line(),
+            message: message.into(),
+        }
+    }
+
+    fn next(&mut self) -> Result<Token, SignatureError> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .map(|(token, _)| token.clone())
+            .ok_or_else(|| self.error("unexpected end of rules"))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(ident)) if ident == keyword)
+    }
+
+    fn is_punct(&self, punct: &str) -> bool {
+        matches!(self.peek(), Some(Token::Punct(p)) if *p == punct)
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), SignatureError> {
+        match self.is_keyword(keyword) {
+            true => self.next().map(|_| ()),
+            false => Err(self.error(format!("expected `{}`", keyword))),
+        }
+    }
+
+    fn expect_punct(&mut self, punct: &str) -> Result<(), SignatureError> {
+        match self.is_punct(punct) {
+            true => self.next().map(|_| ()),
+            false => Err(self.error(format!("expected `{}`", punct))),
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, SignatureError> {
+        match self.next()? {
+            Token::Ident(ident) => Ok(ident),
+            _ => Err(SignatureError::Syntax {
+                line: self.tokens[self.pos - 1].1,
+                message: "expected a name".to_string(),
+            }),
+        }
+    }
+
+    fn rule(&mut self) -> Result<Rule, SignatureError> {
+        self.expect_keyword("rule")?;
+        let name = self.ident()?;
+        let mut tags = Vec::new();
+        if self.is_punct(":") {
+            self.next()?;
+            while let Some(Token::Ident(_)) = self.peek() {
+                tags.push(self.ident()?);
+            }
+        }
+        self.expect_punct("{")?;
+
+        let mut meta = Vec::new();
+        let mut strings = Vec::new();
+        loop {
+            let section = self.ident()?;
+            self.expect_punct(":")?;
+            match section.as_str() {
+                "meta" => {
+                    while !self.is_keyword("strings") && !self.is_keyword("condition") {
+                        let key = self.ident()?;
+                        self.expect_punct("=")?;
+                        let value = match self.next()? {
+                            Token::Text(text) => String::from_utf8_lossy(&text).into_owned(),
+                            Token::Number(number) => number.to_string(),
+                            Token::Ident(ident) if ident == "true" || ident == "false" => ident,
+                            _ => return Err(self.error("expected a meta value")),
+                        };
+                        meta.push((key, value));
+                    }
+                }
+                "strings" => {
+                    while let Some(Token::StringId(_)) = self.peek() {
+                        strings.push(self.string()?);
+                    }
+                }
+                "condition" => {
+                    let condition = self.expression()?;
+                    self.expect_punct("}")?;
+                    return Ok(Rule {
+                        name,
+                        tags,
+                        meta,
+                        strings,
+                        condition,
+                    });
+                }
+                _ => return Err(self.error(format!("unknown section `{}`", section))),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<RuleString, SignatureError> {
+        let Token::StringId(name) = self.next()? else {
+            unreachable!("strings start with their name");
+        };
+        self.expect_punct("=")?;
+        let line = self.line();
+        let patterns = match self.next()? {
+            Token::Hex(hex) => {
+                let pattern = BytePattern::parse(&hex)
+                    .map_err(|source| SignatureError::Pattern { line, source })?;
+                vec![pattern]
+            }
+            Token::Text(text) => {
+                let (mut ascii, mut wide, mut nocase) = (false, false, false);
+                while let Some(Token::Ident(modifier)) = self.peek() {
+                    match modifier.as_str() {
+                        "ascii" => ascii = true,
+                        "wide" => wide = true,
+                        "nocase" => nocase = true,
+                        _ => break,
+                    }
+                    self.next()?;
+                }
+                if text.is_empty() {
+                    return Err(SignatureError::Pattern {
+                        line,
+                        source: BytePatternError::Empty,
+                    });
+                }
+                let mut patterns = Vec::new();
+                if ascii || !wide {
+                    patterns.push(text_pattern(&text, false, nocase));
+                }
+                if wide {
+                    patterns.push(text_pattern(&text, true, nocase));
+                }
+                patterns
+            }
+            _ => return Err(self.error("expected a text or hex string")),
+        };
+        Ok(RuleString { name, patterns })
+    }
+
+    fn expression(&mut self) -> Result<Condition, SignatureError> {
+        let mut condition = self.conjunction()?;
+        while self.is_keyword("or") {
+            self.next()?;
+            condition = Condition::Or(Box::new(condition), Box::new(self.conjunction()?));
+        }
+        Ok(condition)
+    }
+
+    fn conjunction(&mut self) -> Result<Condition, SignatureError> {
+        let mut condition = self.unary()?;
+        while self.is_keyword("and") {
+            self.next()?;
+            condition = Condition::And(Box::new(condition), Box::new(self.unary()?));
+        }
+        Ok(condition)
+    }
+
+    fn unary(&mut self) -> Result<Condition, SignatureError> {
+        if self.is_keyword("not") {
+            self.next()?;
+            return Ok(Condition::Not(Box::new(self.unary()?)));
+        }
+        match self.next()? {
+            Token::Punct("(") => {
+                let condition = self.expression()?;
+                self.expect_punct(")")?;
+                Ok(condition)
+            }
+            Token::StringId(name) => Ok(Condition::Matched(name)),
+            Token::CountId(name) => {
+                let comparison = match self.next()? {
+                    Token::Punct("<") => Comparison::Less,
+                    Token::Punct("<=") => Comparison::LessOrEqual,
+                    Token::Punct("==") => Comparison::Equal,
+                    Token::Punct("!=") => Comparison::NotEqual,
+                    Token::Punct(">=") => Comparison::GreaterOrEqual,
+                    Token::Punct(">") => Comparison::Greater,
+                    _ => return Err(self.error("expected a comparison")),
+                };
+                match self.next()? {
+                    Token::Number(count) => Ok(Condition::Count(name, comparison, count)),
+                    _ => Err(self.error("expected a number")),
+                }
+            }
+            Token::Ident(quantifier) if quantifier == "any" || quantifier == "all" => {
+                self.expect_keyword("of")?;
+                self.expect_keyword("them")?;
+                Ok(Condition::Of(match quantifier.as_str() {
+                    "any" => Quantifier::Any,
+                    _ => Quantifier::All,
+                }))
+            }
+            Token::Number(count) => {
+                self.expect_keyword("of")?;
+                self.expect_keyword("them")?;
+                Ok(Condition::Of(Quantifier::AtLeast(count)))
+            }
+            _ => Err(self.error("expected a condition")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const RULES: &str = r#"
+        // Packers
+        rule upx : packer triage {
+            meta:
+                author = "test"
+                version = 2
+            strings:
+                $magic = "UPX!"
+                $stub = { 60 BE ?? ?? ?? ?? 8D BE }
+            condition:
+                $magic and #stub >= 1
+        }
+
+        rule greeting {
+            strings:
+                $hello = "hello" ascii wide nocase
+                $bye = "bye\x21"
+            condition:
+                (#hello == 2 or $bye) and not 2 of them
+        }
+    "#;
+
+    #[test]
+    fn compiles_rules() {
+        let rules = RuleSet::compile(RULES).unwrap();
+        let upx = &rules.rules()[0];
+        assert_eq!(upx.name, "upx");
+        assert_eq!(upx.tags, ["packer", "triage"]);
+        assert_eq!(upx.meta[1], ("version".to_string(), "2".to_string()));
+        assert_eq!(upx.strings[1].name, "$stub");
+        assert_eq!(
+            upx.strings[1].patterns[0].to_string(),
+            "60 BE ?? ?? ?? ?? 8D BE"
+        );
+        let greeting = &rules.rules()[1];
+        assert_eq!(greeting.strings[0].patterns.len(), 2);
+        assert_eq!(
+            greeting.strings[1].patterns[0],
+            BytePattern::from_bytes(b"bye!")
+        );
+    }
+
+    #[test]
+    fn scans_bytes() {
+        let rules = RuleSet::compile(RULES).unwrap();
+        let mut data = b"..UPX!..\x60\xbe\x01\x02\x03\x04\x8d\xbe..".to_vec();
+        data.extend(b"HeLLo h\0E\0l\0L\0o\0");
+        let matches = rules.scan_bytes(&data, 0x1000);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].rule, "upx");
+        assert_eq!(matches[0].tags, ["packer", "triage"]);
+        assert_eq!(
+            matches[0].strings,
+            [
+                StringMatch {
+                    name: "$magic".into(),
+                    address: 0x1002,
+                    len: 4
+                },
+                StringMatch {
+                    name: "$stub".into(),
+                    address: 0x1008,
+                    len: 8
+                },
+            ]
+        );
+        let hello: Vec<_> = matches[1]
+            .strings
+            .iter()
+            .map(|s| (s.address, s.len))
+            .collect();
+        assert_eq!(hello, [(0x1012, 5), (0x1018, 10)]);
+
+        // A third greeting fails `#hello == 2`, matching `$bye` too fails `not 2 of them`
+        data.extend(b"hello bye!");
+        assert_eq!(rules.scan_bytes(&data, 0).len(), 1);
+        assert!(rules.scan_bytes(b"nothing", 0).is_empty());
+    }
+
+    #[test]
+    fn reports_errors() {
+        let error = |source: &str| RuleSet::compile(source).unwrap_err();
+        assert_eq!(
+            error("rule a {\n strings:\n $a = { 4G }\n condition: $a }"),
+            SignatureError::Pattern {
+                line: 3,
+                source: BytePatternError::InvalidByte("4G".into())
+            }
+        );
+        assert_eq!(
+            error("rule a { strings: $a = \"x\" condition: $b }"),
+            SignatureError::UndefinedString {
+                rule: "a".into(),
+                name: "$b".into()
+            }
+        );
+        assert!(matches!(
+            error("rule a { strings: $a = \"x\"\n condition: #a }"),
+            SignatureError::Syntax { line: 2, .. }
+        ));
+        assert!(matches!(
+            error("rule a { strings: $a = \"x"),
+            SignatureError::Syntax { .. }
+        ));
+
+        let compile = |source| -> crate::error::Result<RuleSet> { Ok(RuleSet::compile(source)?) };
+        assert!(matches!(
+            compile("rule a { strings: $a = \"x"),
+            Err(crate::Error::Signature(SignatureError::Syntax { .. }))
+        ));
+    }
+}
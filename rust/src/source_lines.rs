@@ -0,0 +1,312 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The source lines instructions were compiled from, as recorded by debug information importers.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+
+use crate::binary_view::{BinaryView, BinaryViewExt};
+use crate::metadata::Metadata;
+use crate::rc::Ref;
+
+/// View metadata key of the map from importer name to the line table of its last import.
+pub const SOURCE_LINES_METADATA_KEY: &str = "source_lines";
+
+/// A line in a source file.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SourceLocation {
+    pub file: String,
+    pub line: u64,
+    pub column: Option<u64>,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.column {
+            Some(column) => write!(f, "{}:{}:{}", self.file, self.line, column),
+            None => write!(f, "{}:{}", self.file, self.line),
+        }
+    }
+}
+
+/// The addresses of a [`SourceLineTable`] compiled from one location.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct LineRow {
+    range: Range<u64>,
+    file: usize,
+    line: u64,
+    column: Option<u64>,
+}
+
+/// The source locations of the addresses of a view, found by one run of an importer.
+///
+/// The core has no place for line information, so importers collect a table while they parse and
+/// [`store`](SourceLineTable::store) it on the view they imported into. The source location of an
+/// address can then be looked up:
+///
+/// ```no_run
+/// use binaryninja::binary_view::BinaryViewBase;
+/// use binaryninja::source_lines::SourceLineTable;
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// for table in SourceLineTable::for_view(&view) {
+///     if let Some(location) = table.location_at(view.entry_point()) {
+///         println!("{}: {}", table.importer(), location);
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SourceLineTable {
+    importer: String,
+    files: Vec<String>,
+    file_indices: HashMap<String, usize>,
+    /// Sorted by start address and not overlapping.
+    rows: Vec<LineRow>,
+}
+
+impl SourceLineTable {
+    /// An empty table for `importer`, usually the name its parser is registered under.
+    pub fn new(importer: impl Into<String>) -> Self {
+        Self {
+            importer: importer.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn importer(&self) -> &str {
+        &self.importer
+    }
+
+    /// Record that the addresses of `range` were compiled from `line` of `file`.
+    ///
+    /// Ranges overlapping ones already in the table are ignored, and a range continuing the one
+    /// before it from the same location extends it.
+    pub fn add(&mut self, range: Range<u64>, file: &str, line: u64, column: Option<u64>) {
+        if range.is_empty() {
+            return;
+        }
+        let file = match self.file_indices.get(file) {
+            Some(&index) => index,
+            None => {
+                self.files.push(file.to_string());
+                self.file_indices
+                    .insert(file.to_string(), self.files.len() - 1);
+                self.files.len() - 1
+            }
+        };
+        // Line programs mostly go up in address, which makes this an append
+        let index = self
+            .rows
+            .partition_point(|row| row.range.start < range.start);
+        let overlaps_before = index > 0 && self.rows[index - 1].range.end > range.start;
+        let overlaps_after = self
+            .rows
+            .get(index)
+            .is_some_and(|row| row.range.start < range.end);
+        if overlaps_before || overlaps_after {
+            return;
+        }
+        if index > 0 {
+            let before = &mut self.rows[index - 1];
+            let same_location =
+                before.file == file && before.line == line && before.column == column;
+            if same_location && before.range.end == range.start {
+                before.range.end = range.end;
+                return;
+            }
+        }
+        self.rows.insert(
+            index,
+            LineRow {
+                range,
+                file,
+                line,
+                column,
+            },
+        );
+    }
+
+    /// The number of address ranges in the table.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// The source files the table refers to.
+    pub fn files(&self) -> &[String] {
+        &self.files
+    }
+
+    /// The location the instruction at `addr` was compiled from.
+    pub fn location_at(&self, addr: u64) -> Option<SourceLocation> {
+        let index = self.rows.partition_point(|row| row.range.start <= addr);
+        let row = self.rows.get(index.checked_sub(1)?)?;
+        row.range.contains(&addr).then(|| self.location(row))
+    }
+
+    /// The address ranges compiled from `line` of `file`.
+    pub fn ranges_of(&self, file: &str, line: u64) -> Vec<Range<u64>> {
+        let Some(&file) = self.file_indices.get(file) else {
+            return vec![];
+        };
+        self.rows
+            .iter()
+            .filter(|row| row.file == file && row.line == line)
+            .map(|row| row.range.clone())
+            .collect()
+    }
+
+    /// Every address range of the table with its location, in address order.
+    pub fn iter(&self) -> impl Iterator<Item = (Range<u64>, SourceLocation)> + '_ {
+        self.rows
+            .iter()
+            .map(|row| (row.range.clone(), self.location(row)))
+    }
+
+    /// Move every range by `delta` bytes, such as when the view is loaded at a different base
+    /// than the debug information assumes.
+    pub fn rebase(&mut self, delta: u64) {
+        for row in &mut self.rows {
+            row.range = row.range.start.wrapping_add(delta)..row.range.end.wrapping_add(delta);
+        }
+        self.rows.sort_by_key(|row| row.range.start);
+    }
+
+    /// Store the table on `view`, replacing the one of the last import of the same importer.
+    pub fn store(&self, view: &BinaryView) {
+        let mut all = view
+            .query_metadata(SOURCE_LINES_METADATA_KEY)
+            .and_then(|metadata| HashMap::<String, Ref<Metadata>>::try_from(&*metadata).ok())
+            .unwrap_or_default();
+        all.insert(self.importer.clone(), self.to_metadata());
+        view.store_metadata(SOURCE_LINES_METADATA_KEY, all, true);
+    }
+
+    /// The line tables of the last import of every importer into `view`, by importer name.
+    pub fn for_view(view: &BinaryView) -> Vec<SourceLineTable> {
+        let Some(metadata) = view.query_metadata(SOURCE_LINES_METADATA_KEY) else {
+            return vec![];
+        };
+        let mut all: Vec<_> = HashMap::<String, Ref<Metadata>>::try_from(&*metadata)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(importer, metadata)| Self::from_metadata(importer, &metadata))
+            .collect();
+        all.sort_by(|a, b| a.importer.cmp(&b.importer));
+        all
+    }
+
+    /// The line table of the last import of `importer` into `view`.
+    pub fn for_importer(view: &BinaryView, importer: &str) -> Option<SourceLineTable> {
+        let metadata = view.query_metadata(SOURCE_LINES_METADATA_KEY)?;
+        let metadata = metadata.get(importer).ok()??;
+        Some(Self::from_metadata(importer.to_string(), &metadata))
+    }
+
+    fn location(&self, row: &LineRow) -> SourceLocation {
+        SourceLocation {
+            file: self.files[row.file].clone(),
+            line: row.line,
+            column: row.column,
+        }
+    }
+
+    // Each row is spread over lists of numbers, which is much smaller than a map per row
+    fn to_metadata(&self) -> Ref<Metadata> {
+        let column = |row: &LineRow| row.column.unwrap_or(0);
+        let starts: Vec<u64> = self.rows.iter().map(|row| row.range.start).collect();
+        let ends: Vec<u64> = self.rows.iter().map(|row| row.range.end).collect();
+        let files: Vec<u64> = self.rows.iter().map(|row| row.file as u64).collect();
+        let lines: Vec<u64> = self.rows.iter().map(|row| row.line).collect();
+        let columns: Vec<u64> = self.rows.iter().map(column).collect();
+
+        let mut fields = HashMap::<&str, Ref<Metadata>>::new();
+        fields.insert("file_names", self.files.clone().into());
+        fields.insert("starts", (&starts).into());
+        fields.insert("ends", (&ends).into());
+        fields.insert("files", (&files).into());
+        fields.insert("lines", (&lines).into());
+        fields.insert("columns", (&columns).into());
+        fields.into()
+    }
+
+    fn from_metadata(importer: String, metadata: &Metadata) -> Self {
+        let mut table = Self::new(importer);
+        let fields = HashMap::<String, Ref<Metadata>>::try_from(metadata).unwrap_or_default();
+        let numbers = |name: &str| {
+            fields
+                .get(name)
+                .and_then(|field| Vec::<u64>::try_from(&**field).ok())
+                .unwrap_or_default()
+        };
+        let file_names = fields
+            .get("file_names")
+            .and_then(|field| Vec::<String>::try_from(&**field).ok())
+            .unwrap_or_default();
+        let (starts, ends, files) = (numbers("starts"), numbers("ends"), numbers("files"));
+        let (lines, columns) = (numbers("lines"), numbers("columns"));
+        for (i, &start) in starts.iter().enumerate() {
+            let (Some(&end), Some(file), Some(&line)) = (
+                ends.get(i),
+                files.get(i).and_then(|&file| file_names.get(file as usize)),
+                lines.get(i),
+            ) else {
+                continue;
+            };
+            let column = columns.get(i).copied().filter(|&column| column != 0);
+            table.add(start..end, file, line, column);
+        }
+        table
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn looks_up_locations() {
+        let mut table = SourceLineTable::new("DWARF");
+        table.add(0x1000..0x1004, "a.c", 10, Some(5));
+        // Continues the row before it
+        table.add(0x1004..0x1008, "a.c", 10, Some(5));
+        table.add(0x1010..0x1018, "b.c", 3, None);
+        table.add(0x1008..0x1010, "a.c", 11, None);
+        // Overlaps rows already in the table
+        table.add(0x1002..0x1012, "c.c", 1, None);
+        table.add(0x1020..0x1020, "c.c", 1, None);
+
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.files(), ["a.c", "b.c"]);
+        let location = table.location_at(0x1006).unwrap();
+        assert_eq!(location.to_string(), "a.c:10:5");
+        assert_eq!(table.location_at(0x1008).unwrap().line, 11);
+        assert_eq!(table.location_at(0x1017).unwrap().file, "b.c");
+        assert!(table.location_at(0x1018).is_none());
+        assert!(table.location_at(0xfff).is_none());
+        let ranges = table.ranges_of("a.c", 10);
+        assert_eq!(ranges.len(), 1);
+        assert_eq!(ranges[0], 0x1000..0x1008);
+
+        table.rebase(0x100);
+        assert_eq!(table.location_at(0x1106).unwrap().line, 10);
+        let ranges: Vec<_> = table.iter().map(|(range, _)| range).collect();
+        assert_eq!(ranges, [0x1100..0x1108, 0x1108..0x1110, 0x1110..0x1118]);
+    }
+}
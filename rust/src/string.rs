@@ -33,6 +33,25 @@ pub(crate) fn raw_to_string(ptr: *const c_char) -> Option<String> {
     }
 }
 
+/// Quote and escape `value` as a JSON string.
+pub(crate) fn json_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
 // TODO: Make this pass in an iterator over something more generic...
 pub(crate) fn strings_to_string_list(strings: &[String]) -> *mut *mut c_char {
     use binaryninjacore_sys::BNAllocStringList;
@@ -267,6 +267,10 @@ impl Symbol {
         unsafe { BNGetSymbolAddress(self.handle) }
     }
 
+    pub fn ordinal(&self) -> u64 {
+        unsafe { BNGetSymbolOrdinal(self.handle) }
+    }
+
     pub fn auto_defined(&self) -> bool {
         unsafe { BNIsSymbolAutoDefined(self.handle) }
     }
@@ -276,6 +280,30 @@ impl Symbol {
         self.binding() == Binding::Weak || self.binding() == Binding::Global
     }
 
+    /// A copy of this symbol with new short and full names, keeping its raw name, namespace and
+    /// everything else.
+    pub(crate) fn renamed(&self, short_name: &str, full_name: &str) -> Ref<Symbol> {
+        let short_name = short_name.into_bytes_with_nul();
+        let full_name = full_name.into_bytes_with_nul();
+        unsafe {
+            let mut name_space = BNGetSymbolNameSpace(self.handle);
+            let raw_name = BNGetSymbolRawName(self.handle);
+            let res = BNCreateSymbol(
+                BNGetSymbolType(self.handle),
+                short_name.as_ptr() as _,
+                full_name.as_ptr() as _,
+                raw_name,
+                self.address(),
+                BNGetSymbolBinding(self.handle),
+                &name_space,
+                self.ordinal(),
+            );
+            BNFreeString(raw_name);
+            BNFreeNameSpace(&mut name_space);
+            Symbol::ref_from_raw(res)
+        }
+    }
+
     pub fn imported_function_from_import_address_symbol(sym: &Symbol, addr: u64) -> Ref<Symbol> {
         unsafe {
             let res = BNImportedFunctionFromImportAddressSymbol(sym.handle, addr);
@@ -0,0 +1,207 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! An index over the symbols of a view for prefix and fuzzy name queries.
+
+use crate::binary_view::{BinaryView, BinaryViewExt};
+use crate::rc::Ref;
+use crate::symbol::Symbol;
+
+/// A snapshot of the symbols of a view for prefix and fuzzy name queries.
+///
+/// Building the index pulls every symbol of the view once, after which queries don't touch the
+/// core, so it can be queried on every keystroke of a command palette. Matching ignores case, and
+/// a query such as `mlc` matches `malloc` as a subsequence.
+///
+/// The index does not follow later changes to the symbols, call [`SymbolIndex::refresh`] to pick
+/// them up.
+///
+/// ```no_run
+/// use binaryninja::binary_view::BinaryViewExt;
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// let index = view.symbol_index();
+/// for symbol in index.symbols_matching("mlc", 10) {
+///     println!("{:#x} {}", symbol.address(), symbol.full_name());
+/// }
+/// ```
+pub struct SymbolIndex {
+    view: Ref<BinaryView>,
+    symbols: Vec<Ref<Symbol>>,
+    /// Lowercase full names of `symbols`, sorted, with the index of their symbol.
+    names: Vec<(String, usize)>,
+}
+
+impl SymbolIndex {
+    pub fn new(view: &BinaryView) -> Self {
+        let mut index = Self {
+            view: view.to_owned(),
+            symbols: Vec::new(),
+            names: Vec::new(),
+        };
+        index.refresh();
+        index
+    }
+
+    /// Rebuild the index from the current symbols of the view.
+    pub fn refresh(&mut self) {
+        self.symbols = self.view.symbols().iter().map(|s| s.to_owned()).collect();
+        self.names = self
+            .symbols
+            .iter()
+            .enumerate()
+            .map(|(idx, symbol)| (symbol.full_name().to_string_lossy().to_lowercase(), idx))
+            .collect();
+        self.names.sort_unstable();
+    }
+
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// At most `limit` symbols whose name starts with `query`, shortest name first.
+    pub fn symbols_with_prefix(&self, query: &str, limit: usize) -> Vec<Ref<Symbol>> {
+        let query = query.to_lowercase();
+        let mut matches = prefix_matches(&self.names, &query);
+        matches.sort_by_key(|&(name, _)| name.len());
+        self.resolve(matches.into_iter().map(|(_, idx)| idx), limit)
+    }
+
+    /// At most `limit` symbols whose name matches `query`, best match first.
+    ///
+    /// Names starting with `query` rank above names only containing it as a subsequence, which
+    /// rank higher the more of `query` matches in runs and at the start of words.
+    pub fn symbols_matching(&self, query: &str, limit: usize) -> Vec<Ref<Symbol>> {
+        let query = query.to_lowercase();
+        let ranked = rank_matches(&self.names, &query);
+        self.resolve(ranked, limit)
+    }
+
+    fn resolve(&self, indices: impl IntoIterator<Item = usize>, limit: usize) -> Vec<Ref<Symbol>> {
+        indices
+            .into_iter()
+            .take(limit)
+            .map(|idx| self.symbols[idx].clone())
+            .collect()
+    }
+}
+
+/// The names of the sorted `names` starting with `query`.
+fn prefix_matches<'a>(names: &'a [(String, usize)], query: &str) -> Vec<(&'a str, usize)> {
+    let start = names.partition_point(|(name, _)| name.as_str() < query);
+    names[start..]
+        .iter()
+        .take_while(|(name, _)| name.starts_with(query))
+        .map(|(name, idx)| (name.as_str(), *idx))
+        .collect()
+}
+
+/// The score of `name` containing `query` as a subsequence, `None` if it doesn't.
+///
+/// Each matched character scores a point, with bonus points for continuing a run of matches or
+/// starting a word.
+fn fuzzy_score(name: &str, query: &str) -> Option<u32> {
+    let mut query_chars = query.chars().peekable();
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut previous_matched = false;
+    for c in name.chars() {
+        let Some(&wanted) = query_chars.peek() else {
+            break;
+        };
+        let matched = c == wanted;
+        if matched {
+            query_chars.next();
+            score += 1;
+            if previous_matched {
+                score += 2;
+            }
+            if previous.is_none_or(|p| !p.is_alphanumeric()) {
+                score += 3;
+            }
+        }
+        previous_matched = matched;
+        previous = Some(c);
+    }
+    query_chars.peek().is_none().then_some(score)
+}
+
+/// The symbol indices of every name matching `query`: prefix matches by length, then subsequence
+/// matches by score and length.
+fn rank_matches(names: &[(String, usize)], query: &str) -> Vec<usize> {
+    let mut prefixed = prefix_matches(names, query);
+    prefixed.sort_by_key(|&(name, _)| name.len());
+
+    let mut fuzzy: Vec<(u32, &str, usize)> = names
+        .iter()
+        .filter(|(name, _)| !name.starts_with(query))
+        .filter_map(|(name, idx)| Some((fuzzy_score(name, query)?, name.as_str(), *idx)))
+        .collect();
+    fuzzy.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.len().cmp(&b.1.len())));
+
+    prefixed
+        .into_iter()
+        .map(|(_, idx)| idx)
+        .chain(fuzzy.into_iter().map(|(_, _, idx)| idx))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(names: &[&str]) -> Vec<(String, usize)> {
+        let mut names: Vec<(String, usize)> = names
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| (name.to_lowercase(), idx))
+            .collect();
+        names.sort_unstable();
+        names
+    }
+
+    #[test]
+    fn matches_prefixes() {
+        let names = index(&["memcpy", "malloc", "memset", "main", "_memcpy"]);
+        let matches: Vec<usize> = prefix_matches(&names, "mem")
+            .into_iter()
+            .map(|(_, idx)| idx)
+            .collect();
+        assert_eq!(matches, [0, 2]);
+        assert!(prefix_matches(&names, "z").is_empty());
+        assert_eq!(prefix_matches(&names, "").len(), names.len());
+    }
+
+    #[test]
+    fn scores_subsequences() {
+        assert_eq!(fuzzy_score("malloc", "xyz"), None);
+        assert_eq!(fuzzy_score("malloc", "mlcx"), None);
+        assert!(fuzzy_score("malloc", "mlc").is_some());
+        // Runs and word starts score higher
+        assert!(fuzzy_score("get_value", "gv") > fuzzy_score("gravity", "gv"));
+        assert!(fuzzy_score("loader", "load") > fuzzy_score("lxoxaxd", "load"));
+    }
+
+    #[test]
+    fn ranks_matches() {
+        let names = index(&["memcpy_s", "memcpy", "__imp_memcpy", "emcp", "strlen"]);
+        assert_eq!(rank_matches(&names, "memcpy"), [1, 0, 2]);
+        assert_eq!(rank_matches(&names, "emc")[0], 3);
+        assert!(rank_matches(&names, "qq").is_empty());
+    }
+}
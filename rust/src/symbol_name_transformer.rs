@@ -0,0 +1,143 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Rename auto symbols after they are defined, e.g. to strip prefixes or apply naming conventions.
+
+use std::sync::{Arc, Mutex};
+
+use crate::binary_view::{BinaryView, BinaryViewExt};
+use crate::rc::Ref;
+use crate::symbol::{Symbol, SymbolType};
+
+type Transformer = dyn Fn(&BinaryView, SymbolType, &str) -> Option<String> + Send + Sync;
+
+static TRANSFORMERS: Mutex<Vec<(String, Arc<Transformer>)>> = Mutex::new(Vec::new());
+
+/// Register `transformer` under `name`, replacing the transformer already registered under it.
+///
+/// The transformer is given the view, the type of the symbol and its short or full name, and returns
+/// the new name, or `None` to keep it. Transformers should be idempotent, as a name may be passed
+/// through them more than once, for instance when [`apply_symbol_name_transformers`] runs on
+/// symbols that were already transformed.
+///
+/// Registered transformers run, in registration order, on the short and full names of the auto
+/// symbols of a view when [`apply_symbol_name_transformers`] renames them. Raw names are never
+/// transformed.
+pub fn register_symbol_name_transformer<F>(name: impl Into<String>, transformer: F)
+where
+    F: Fn(&BinaryView, SymbolType, &str) -> Option<String> + 'static + Send + Sync,
+{
+    let name = name.into();
+    let transformer: Arc<Transformer> = Arc::new(transformer);
+    let mut transformers = TRANSFORMERS.lock().unwrap();
+    match transformers.iter_mut().find(|(n, _)| *n == name) {
+        Some((_, existing)) => *existing = transformer,
+        None => transformers.push((name, transformer)),
+    }
+}
+
+/// Unregister the transformer registered under `name`, returns whether there was one.
+pub fn unregister_symbol_name_transformer(name: &str) -> bool {
+    let mut transformers = TRANSFORMERS.lock().unwrap();
+    let count = transformers.len();
+    transformers.retain(|(n, _)| n != name);
+    transformers.len() != count
+}
+
+/// The names of the registered transformers, in the order they run.
+pub fn symbol_name_transformers() -> Vec<String> {
+    let transformers = TRANSFORMERS.lock().unwrap();
+    transformers.iter().map(|(name, _)| name.clone()).collect()
+}
+
+/// Run `name` through all registered transformers, returns `None` if none of them changed it.
+pub fn transform_symbol_name(
+    view: &BinaryView,
+    sym_type: SymbolType,
+    name: &str,
+) -> Option<String> {
+    // Don't hold the lock while transforming, a transformer may well define symbols itself
+    let transformers: Vec<_> = TRANSFORMERS
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|(_, transformer)| transformer.clone())
+        .collect();
+    let mut transformed = None;
+    for transformer in transformers {
+        let current = transformed.as_deref().unwrap_or(name);
+        if let Some(new_name) = transformer(view, sym_type, current) {
+            transformed = Some(new_name);
+        }
+    }
+    transformed.filter(|transformed| transformed != name)
+}
+
+/// `sym` with its short and full names transformed, or `None` if neither changed.
+pub fn transform_symbol(view: &BinaryView, sym: &Symbol) -> Option<Ref<Symbol>> {
+    let sym_type = sym.sym_type();
+    let short_name = sym.short_name();
+    let full_name = sym.full_name();
+    let new_short_name = transform_symbol_name(view, sym_type, short_name.as_str());
+    let new_full_name = transform_symbol_name(view, sym_type, full_name.as_str());
+    if new_short_name.is_none() && new_full_name.is_none() {
+        return None;
+    }
+    Some(sym.renamed(
+        new_short_name.as_deref().unwrap_or(short_name.as_str()),
+        new_full_name.as_deref().unwrap_or(full_name.as_str()),
+    ))
+}
+
+/// Transform the names of the auto symbols defined in `view`, returns the number of renamed
+/// symbols.
+///
+/// This is a rename pass over the symbols the view has, whichever loader or importer defined
+/// them. Defining symbols does not run the transformers, so symbols defined afterwards, for
+/// instance by further analysis, need another pass:
+///
+/// ```no_run
+/// use binaryninja::symbol::SymbolType;
+/// use binaryninja::symbol_name_transformer::*;
+///
+/// register_symbol_name_transformer("strip-fw-prefix", |_view, sym_type, name| {
+///     match sym_type {
+///         SymbolType::Function => name.strip_prefix("fw_").map(str::to_string),
+///         _ => None,
+///     }
+/// });
+///
+/// let view = binaryninja::load("/tmp/firmware.elf").unwrap();
+/// let renamed = apply_symbol_name_transformers(&view);
+/// println!("Renamed {} symbols", renamed);
+/// ```
+pub fn apply_symbol_name_transformers(view: &BinaryView) -> usize {
+    if TRANSFORMERS.lock().unwrap().is_empty() {
+        return 0;
+    }
+
+    let mut renamed = 0;
+    let _bulk = view.bulk_modify_symbols();
+    for sym in view.symbols().iter() {
+        if !sym.auto_defined() {
+            continue;
+        }
+        if let Some(new_sym) = transform_symbol(view, &sym) {
+            view.undefine_auto_symbol(&sym);
+            view.define_auto_symbol(&new_sym);
+            renamed += 1;
+        }
+    }
+    renamed
+}
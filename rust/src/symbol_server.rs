@@ -0,0 +1,826 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Fetching symbol files, such as PDBs, from Microsoft symbol servers (SymSrv).
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use miniz_oxide::inflate::core::{decompress, inflate_flags, DecompressorOxide};
+use miniz_oxide::inflate::TINFLStatus;
+use thiserror::Error;
+
+use crate::download_provider::{DownloadInstanceInputOutputCallbacks, DownloadProvider};
+use crate::progress::ProgressCallback;
+
+/// The symbol server of Microsoft, holding the symbols of Windows.
+pub const MICROSOFT_SYMBOL_SERVER: &str = "https://msdl.microsoft.com/download/symbols";
+
+#[derive(Error, Debug)]
+pub enum SymbolServerError {
+    #[error("i/o error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid symbol file id: {0}")]
+    InvalidId(String),
+    #[error("{0} not found in any symbol store")]
+    NotFound(String),
+    #[error("download failed: {0}")]
+    Download(String),
+    #[error("download cancelled")]
+    Cancelled,
+    #[error("corrupt cabinet: {0}")]
+    CorruptCabinet(&'static str),
+    #[error("unsupported cabinet compression {0}")]
+    UnsupportedCompression(u16),
+}
+
+/// A symbol file as it is stored in a symbol store, by name and the key derived from its contents.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SymbolFileId {
+    file_name: String,
+    key: String,
+}
+
+impl SymbolFileId {
+    /// The file named `file_name`, stored under `key`.
+    ///
+    /// Both end up in paths and URLs, so the name may not have directories in it and the key
+    /// has to be alphanumeric.
+    pub fn new(
+        file_name: impl Into<String>,
+        key: impl Into<String>,
+    ) -> Result<Self, SymbolServerError> {
+        let (file_name, key) = (file_name.into(), key.into());
+        let plain_name = !file_name.contains(['/', '\\', ':']) && file_name != "..";
+        if file_name.is_empty() || file_name == "." || !plain_name {
+            return Err(SymbolServerError::InvalidId(format!(
+                "not a file name: {:?}",
+                file_name
+            )));
+        }
+        if key.is_empty() || !key.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            return Err(SymbolServerError::InvalidId(format!(
+                "not an alphanumeric key: {:?}",
+                key
+            )));
+        }
+        Ok(Self { file_name, key })
+    }
+
+    /// The PDB with `guid` and `age`, named as the last component of `pdb_path`.
+    ///
+    /// The GUID bytes are in the order the GUID is written in (as `uuid::Uuid::as_bytes` returns
+    /// them). The CodeView record of a PE stores the first three fields little endian instead.
+    pub fn pdb(pdb_path: &str, guid: &[u8; 16], age: u32) -> Result<Self, SymbolServerError> {
+        let key: String = guid.iter().map(|b| format!("{:02X}", b)).collect();
+        Self::new(file_name_of(pdb_path), format!("{}{:X}", key, age))
+    }
+
+    /// The executable with the `TimeDateStamp` and `SizeOfImage` of its PE header, named as the
+    /// last component of `pe_path`.
+    pub fn pe(pe_path: &str, timestamp: u32, image_size: u32) -> Result<Self, SymbolServerError> {
+        Self::new(
+            file_name_of(pe_path),
+            format!("{:08X}{:x}", timestamp, image_size),
+        )
+    }
+
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Name of the file compressed in a cabinet, with its last character replaced by `_`.
+    pub fn compressed_file_name(&self) -> String {
+        let mut name = self.file_name.clone();
+        name.pop();
+        name.push('_');
+        name
+    }
+
+    /// Where the file is in a local store.
+    pub fn store_path(&self, store: &Path) -> PathBuf {
+        store
+            .join(&self.file_name)
+            .join(&self.key)
+            .join(&self.file_name)
+    }
+}
+
+impl fmt::Display for SymbolFileId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.file_name, self.key)
+    }
+}
+
+// Paths recorded by Windows tools use `\`, whatever platform reads them
+fn file_name_of(path: &str) -> &str {
+    path.rsplit(['/', '\\']).next().unwrap_or(path)
+}
+
+/// A symbol store, local or on a server.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SymbolStore {
+    /// A directory, which may be a network share.
+    Local(PathBuf),
+    /// The base URL of a symbol server.
+    Http(String),
+}
+
+impl SymbolStore {
+    /// The store at `location`, a URL if it has a scheme and a directory otherwise.
+    pub fn parse(location: &str) -> Self {
+        match location.contains("://") {
+            true => SymbolStore::Http(location.trim_end_matches('/').to_string()),
+            false => SymbolStore::Local(PathBuf::from(location)),
+        }
+    }
+
+    pub fn is_remote(&self) -> bool {
+        matches!(self, SymbolStore::Http(_))
+    }
+
+    /// Read the file `id` from the store, `None` if the store doesn't have it.
+    ///
+    /// The file is looked for uncompressed, then compressed in a cabinet, then through a
+    /// `file.ptr`. `progress` is called with the number of bytes of the current download and its
+    /// total size (0 if the server does not say), returning `false` cancels the download.
+    pub fn fetch<P: ProgressCallback + 'static>(
+        &self,
+        id: &SymbolFileId,
+        progress: P,
+    ) -> Result<Option<Vec<u8>>, SymbolServerError> {
+        // Shared between the requests for each form of the file
+        let progress = Rc::new(RefCell::new(progress));
+        let directory = format!("{}/{}/{}", self, id.file_name, id.key);
+
+        if let Some(data) = read_location(&format!("{}/{}", directory, id.file_name), &progress)? {
+            return Ok(Some(data));
+        }
+        let compressed = format!("{}/{}", directory, id.compressed_file_name());
+        if let Some(cabinet) = read_location(&compressed, &progress)? {
+            return extract_cabinet_file(&cabinet, &id.file_name).map(Some);
+        }
+        // The store only points at where the file is: `PATH:<location>` or `MSG:<reason>`
+        if let Some(pointer) = read_location(&format!("{}/file.ptr", directory), &progress)? {
+            let pointer = String::from_utf8_lossy(&pointer);
+            if let Some(location) = pointer.trim().strip_prefix("PATH:") {
+                return read_location(location, &progress);
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl fmt::Display for SymbolStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SymbolStore::Local(path) => write!(f, "{}", path.display()),
+            SymbolStore::Http(url) => write!(f, "{}", url),
+        }
+    }
+}
+
+/// Parse a symbol path into chains of stores, each searched in order.
+///
+/// A `srv*` element of the path is a chain of stores separated by `*`, where an empty store is
+/// `default_store`. A `cache*<directory>` element adds a store in front of the chains after it,
+/// which files found in those chains are copied into. Other elements are directories, which are
+/// only stores if they have a `pingme.txt` in them.
+pub fn parse_symbol_path(symbol_path: &str, default_store: &Path) -> Vec<Vec<SymbolStore>> {
+    let store = |location: &str| match location.is_empty() {
+        true => SymbolStore::Local(default_store.to_path_buf()),
+        false => SymbolStore::parse(location),
+    };
+
+    let mut chains = vec![];
+    let mut caches = vec![];
+    for element in symbol_path.split(';').map(str::trim) {
+        let lowercase = element.to_ascii_lowercase();
+        if lowercase.starts_with("srv*") {
+            let mut chain = caches.clone();
+            chain.extend(element[4..].split('*').map(store));
+            chains.push(chain);
+        } else if lowercase.starts_with("cache*") {
+            caches.push(store(&element[6..]));
+        } else if !element.is_empty() && Path::new(element).join("pingme.txt").exists() {
+            let mut chain = caches.clone();
+            chain.push(store(element));
+            chains.push(chain);
+        }
+    }
+    chains
+}
+
+/// Chains of symbol stores, with a local cache in front of them.
+///
+/// A symbol store is a directory or URL holding each file at `<name>/<key>/<name>`, where the key
+/// of a PDB is its GUID and age. A store may instead hold the file compressed in a cabinet, with
+/// the last character of its name replaced by `_` (`<name>/<key>/ntdll.pd_`), or a `file.ptr`
+/// pointing at where the file is. Stores are listed in a symbol path, the syntax of the
+/// `_NT_SYMBOL_PATH` environment variable, see
+/// <https://learn.microsoft.com/en-us/windows/win32/debug/using-symsrv>.
+///
+/// ```no_run
+/// use binaryninja::symbol_server::{SymbolFileId, SymbolServer};
+///
+/// let mut server = SymbolServer::new("/tmp/symbols");
+/// server.add_symbol_path("srv**https://msdl.microsoft.com/download/symbols");
+/// let guid = [
+///     0x1d, 0x22, 0x5f, 0x32, 0xb8, 0x6d, 0x4e, 0x27, 0x8c, 0x4b, 0xf4, 0x9c, 0xb5, 0xd1, 0xa7, 0x34,
+/// ];
+/// let id = SymbolFileId::pdb("ntdll.pdb", &guid, 1).unwrap();
+/// let path = server
+///     .fetch_path(&id, |done, total| {
+///         println!("{}/{}", done, total);
+///         true
+///     })
+///     .unwrap();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SymbolServer {
+    /// Files found in a store of a chain are copied into the local stores before it.
+    chains: Vec<Vec<SymbolStore>>,
+    cache_directory: PathBuf,
+    offline: bool,
+}
+
+impl SymbolServer {
+    /// A server without stores, caching the files it fetches in `cache_directory`, which is laid
+    /// out as a symbol store.
+    pub fn new(cache_directory: impl Into<PathBuf>) -> Self {
+        Self {
+            chains: vec![],
+            cache_directory: cache_directory.into(),
+            offline: false,
+        }
+    }
+
+    /// Search the stores of `symbol_path` after those already added, see [`parse_symbol_path`].
+    ///
+    /// Empty stores in the path are the cache directory.
+    pub fn add_symbol_path(&mut self, symbol_path: &str) {
+        let chains = parse_symbol_path(symbol_path, &self.cache_directory);
+        self.chains.extend(chains);
+    }
+
+    /// Search `store` after the stores already added.
+    pub fn add_store(&mut self, store: SymbolStore) {
+        self.chains.push(vec![store]);
+    }
+
+    /// Only search the cache and local stores, never the network.
+    pub fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline
+    }
+
+    pub fn cache_directory(&self) -> &Path {
+        &self.cache_directory
+    }
+
+    /// Every store searched, in order, including stores searched more than once.
+    pub fn stores(&self) -> impl Iterator<Item = &SymbolStore> {
+        self.chains.iter().flatten()
+    }
+
+    /// Where the file `id` is cached, whether or not it has been fetched.
+    pub fn cache_path(&self, id: &SymbolFileId) -> PathBuf {
+        id.store_path(&self.cache_directory)
+    }
+
+    /// Read the file `id` from the cache, or search the stores for it and cache it.
+    ///
+    /// `progress` is called for every download, see [`SymbolStore::fetch`].
+    pub fn fetch<P: ProgressCallback + 'static>(
+        &self,
+        id: &SymbolFileId,
+        progress: P,
+    ) -> Result<Vec<u8>, SymbolServerError> {
+        let cache_path = self.cache_path(id);
+        if cache_path.is_file() {
+            return Ok(fs::read(&cache_path)?);
+        }
+        let data = self.search(id, progress)?;
+        if let Err(e) = write_file(&cache_path, &data) {
+            log::warn!("Could not cache {}: {}", cache_path.display(), e);
+        }
+        Ok(data)
+    }
+
+    /// Path of the file `id` in the cache, searching the stores for it if it is not there yet.
+    pub fn fetch_path<P: ProgressCallback + 'static>(
+        &self,
+        id: &SymbolFileId,
+        progress: P,
+    ) -> Result<PathBuf, SymbolServerError> {
+        let cache_path = self.cache_path(id);
+        if !cache_path.is_file() {
+            let data = self.search(id, progress)?;
+            write_file(&cache_path, &data)?;
+        }
+        Ok(cache_path)
+    }
+
+    fn search<P: ProgressCallback + 'static>(
+        &self,
+        id: &SymbolFileId,
+        progress: P,
+    ) -> Result<Vec<u8>, SymbolServerError> {
+        // Shared between the stores, through a callback each can own
+        let progress = Rc::new(RefCell::new(progress));
+        let mut last_error = None;
+        for chain in &self.chains {
+            for (i, store) in chain.iter().enumerate() {
+                if self.offline && store.is_remote() {
+                    continue;
+                }
+                let store_progress = progress.clone();
+                let on_progress = move |done: usize, total: usize| unsafe {
+                    let mut progress = store_progress.borrow_mut();
+                    P::cb_progress_callback(progress.into_raw(), done, total)
+                };
+                let data = match store.fetch(id, on_progress) {
+                    Ok(Some(data)) => data,
+                    Ok(None) => continue,
+                    Err(SymbolServerError::Cancelled) => return Err(SymbolServerError::Cancelled),
+                    // Try the next store if this one can't be read
+                    Err(e) => {
+                        last_error = Some(e);
+                        continue;
+                    }
+                };
+                for downstream in &chain[..i] {
+                    let SymbolStore::Local(directory) = downstream else {
+                        continue;
+                    };
+                    let path = id.store_path(directory);
+                    if let Err(e) = write_file(&path, &data) {
+                        log::warn!("Could not copy {} to {}: {}", id, path.display(), e);
+                    }
+                }
+                return Ok(data);
+            }
+        }
+        Err(last_error.unwrap_or_else(|| SymbolServerError::NotFound(id.to_string())))
+    }
+}
+
+/// Read a local file or download a URL, `None` if it doesn't exist.
+fn read_location<P: ProgressCallback + 'static>(
+    location: &str,
+    progress: &Rc<RefCell<P>>,
+) -> Result<Option<Vec<u8>>, SymbolServerError> {
+    if !location.contains("://") {
+        return match fs::read(location) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        };
+    }
+
+    let provider = DownloadProvider::try_default()
+        .map_err(|_| SymbolServerError::Download("no default download provider".to_string()))?;
+    let data = Rc::new(RefCell::new(Vec::new()));
+    let cancelled = Rc::new(RefCell::new(false));
+
+    let write_data = data.clone();
+    let write = move |bytes: &[u8]| -> usize {
+        write_data.borrow_mut().extend_from_slice(bytes);
+        bytes.len()
+    };
+    let request_progress = progress.clone();
+    let request_cancelled = cancelled.clone();
+    let on_progress = move |done: u64, total: u64| -> bool {
+        let mut progress = request_progress.borrow_mut();
+        // Through the raw callback so that `NoProgressCallback` is not called directly
+        let keep_going =
+            unsafe { P::cb_progress_callback(progress.into_raw(), done as usize, total as usize) };
+        *request_cancelled.borrow_mut() = !keep_going;
+        keep_going
+    };
+
+    let mut instance = provider.create_instance().map_err(|_| {
+        SymbolServerError::Download("couldn't create download instance".to_string())
+    })?;
+    let response = instance.perform_custom_request(
+        "GET",
+        location,
+        HashMap::<String, String>::new(),
+        DownloadInstanceInputOutputCallbacks {
+            read: None,
+            write: Some(Box::new(write)),
+            progress: Some(Box::new(on_progress)),
+        },
+    );
+    if *cancelled.borrow() {
+        return Err(SymbolServerError::Cancelled);
+    }
+    let response =
+        response.map_err(|e| SymbolServerError::Download(format!("{}: {}", location, e)))?;
+    match response.status_code {
+        200 => {}
+        404 => return Ok(None),
+        status => {
+            return Err(SymbolServerError::Download(format!(
+                "{}: status {}",
+                location, status
+            )))
+        }
+    }
+
+    let data = data.take();
+    let expected_length = response
+        .headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.trim().parse::<usize>().ok());
+    if let Some(expected) = expected_length {
+        if data.len() != expected {
+            return Err(SymbolServerError::Download(format!(
+                "bad length from {}: expected {} got {}",
+                location,
+                expected,
+                data.len()
+            )));
+        }
+    }
+    Ok(Some(data))
+}
+
+/// Write `data` to `path`, through a temporary file so that a partial file is never seen there.
+fn write_file(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    static NEXT_TEMP_FILE: AtomicUsize = AtomicUsize::new(0);
+    let directory = path.parent().unwrap_or(Path::new("."));
+    fs::create_dir_all(directory)?;
+    let temp_file = NEXT_TEMP_FILE.fetch_add(1, Ordering::Relaxed);
+    let temp_path = directory.join(format!(".symbol.{}.{}.tmp", std::process::id(), temp_file));
+    fs::write(&temp_path, data)?;
+    fs::rename(&temp_path, path)
+}
+
+const CAB_SIGNATURE: &[u8; 4] = b"MSCF";
+const CAB_PREV_CABINET: u16 = 0x1;
+const CAB_NEXT_CABINET: u16 = 0x2;
+const CAB_RESERVE_PRESENT: u16 = 0x4;
+const CAB_COMPRESSION_NONE: u16 = 0;
+const CAB_COMPRESSION_MSZIP: u16 = 1;
+/// Uncompressed size of every data block but the last of a folder.
+const CAB_BLOCK_SIZE: usize = 0x8000;
+
+/// Little endian reads from a cabinet, failing past its end.
+struct CabinetReader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> CabinetReader<'a> {
+    fn at(data: &'a [u8], offset: usize) -> Self {
+        Self { data, offset }
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], SymbolServerError> {
+        let bytes = self
+            .offset
+            .checked_add(len)
+            .and_then(|end| self.data.get(self.offset..end))
+            .ok_or(SymbolServerError::CorruptCabinet("truncated"))?;
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, SymbolServerError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, SymbolServerError> {
+        Ok(u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, SymbolServerError> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn c_string(&mut self) -> Result<&'a [u8], SymbolServerError> {
+        let rest = self.data.get(self.offset..).unwrap_or_default();
+        let len = rest
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or(SymbolServerError::CorruptCabinet("unterminated string"))?;
+        self.offset += len + 1;
+        Ok(&rest[..len])
+    }
+}
+
+/// Extract the file named `file_name` from a cabinet, or its only file if it has one.
+///
+/// Only uncompressed and MSZIP compressed folders are supported, which is what symbol stores use.
+/// Cabinets spanning several files are not.
+pub fn extract_cabinet_file(cabinet: &[u8], file_name: &str) -> Result<Vec<u8>, SymbolServerError> {
+    // CFHEADER
+    let mut header = CabinetReader::at(cabinet, 0);
+    if header.bytes(4)? != CAB_SIGNATURE {
+        return Err(SymbolServerError::CorruptCabinet("bad signature"));
+    }
+    header.bytes(12)?;
+    let files_offset = header.u32()? as usize;
+    header.bytes(6)?;
+    let folder_count = header.u16()?;
+    let file_count = header.u16()?;
+    let flags = header.u16()?;
+    header.bytes(4)?;
+    if flags & (CAB_PREV_CABINET | CAB_NEXT_CABINET) != 0 {
+        return Err(SymbolServerError::CorruptCabinet("spans several cabinets"));
+    }
+    let (mut folder_reserve, mut data_reserve) = (0, 0);
+    if flags & CAB_RESERVE_PRESENT != 0 {
+        let header_reserve = header.u16()? as usize;
+        folder_reserve = header.u8()? as usize;
+        data_reserve = header.u8()? as usize;
+        header.bytes(header_reserve)?;
+    }
+
+    // CFFOLDER entries follow the header
+    let mut folders = vec![];
+    for _ in 0..folder_count {
+        let data_offset = header.u32()? as usize;
+        let block_count = header.u16()?;
+        let compression = header.u16()?;
+        header.bytes(folder_reserve)?;
+        folders.push((data_offset, block_count, compression));
+    }
+
+    // CFFILE entries
+    let mut files = CabinetReader::at(cabinet, files_offset);
+    let mut found = None;
+    for _ in 0..file_count {
+        let size = files.u32()? as usize;
+        let folder_offset = files.u32()? as usize;
+        let folder = files.u16()? as usize;
+        files.bytes(6)?;
+        let name = files.c_string()?;
+        let name = String::from_utf8_lossy(name);
+        if file_count == 1 || name.eq_ignore_ascii_case(file_name) {
+            found = Some((size, folder_offset, folder));
+            break;
+        }
+    }
+    let Some((size, folder_offset, folder)) = found else {
+        return Err(SymbolServerError::CorruptCabinet("file not in cabinet"));
+    };
+    let &(data_offset, block_count, compression) = folders
+        .get(folder)
+        .ok_or(SymbolServerError::CorruptCabinet("file in missing folder"))?;
+
+    let contents =
+        extract_cabinet_folder(cabinet, data_offset, block_count, compression, data_reserve)?;
+    folder_offset
+        .checked_add(size)
+        .and_then(|end| contents.get(folder_offset..end))
+        .map(<[u8]>::to_vec)
+        .ok_or(SymbolServerError::CorruptCabinet("file past end of folder"))
+}
+
+/// The uncompressed contents of a folder, from its `CFDATA` blocks.
+fn extract_cabinet_folder(
+    cabinet: &[u8],
+    data_offset: usize,
+    block_count: u16,
+    compression: u16,
+    data_reserve: usize,
+) -> Result<Vec<u8>, SymbolServerError> {
+    // The low bits are the compression type, the others its parameters
+    let compression = compression & 0xf;
+    if compression != CAB_COMPRESSION_NONE && compression != CAB_COMPRESSION_MSZIP {
+        return Err(SymbolServerError::UnsupportedCompression(compression));
+    }
+
+    let mut blocks = CabinetReader::at(cabinet, data_offset);
+    let mut contents: Vec<u8> = vec![];
+    for _ in 0..block_count {
+        // The checksum is optional and not checked
+        blocks.u32()?;
+        let compressed_size = blocks.u16()? as usize;
+        let size = blocks.u16()? as usize;
+        blocks.bytes(data_reserve)?;
+        let block = blocks.bytes(compressed_size)?;
+        if size > CAB_BLOCK_SIZE {
+            return Err(SymbolServerError::CorruptCabinet("oversized block"));
+        }
+        if compression == CAB_COMPRESSION_NONE {
+            if block.len() != size {
+                return Err(SymbolServerError::CorruptCabinet("bad block size"));
+            }
+            contents.extend_from_slice(block);
+            continue;
+        }
+
+        // An MSZIP block is a deflate stream after a `CK` signature. Matches may refer back into
+        // the blocks before it, which are kept in the output buffer for that.
+        let stream = block
+            .strip_prefix(b"CK")
+            .ok_or(SymbolServerError::CorruptCabinet("bad MSZIP signature"))?;
+        let start = contents.len();
+        contents.resize(start + size, 0);
+        let mut decompressor = DecompressorOxide::new();
+        let (status, _, written) = decompress(
+            &mut decompressor,
+            stream,
+            &mut contents,
+            start,
+            inflate_flags::TINFL_FLAG_USING_NON_WRAPPING_OUTPUT_BUF,
+        );
+        if status != TINFLStatus::Done || written != size {
+            return Err(SymbolServerError::CorruptCabinet("bad MSZIP block"));
+        }
+    }
+    Ok(contents)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use miniz_oxide::deflate::compress_to_vec;
+
+    /// A cabinet of one folder, holding `files` in order, stored in blocks of `block_size`.
+    fn cabinet(files: &[(&str, &[u8])], compression: u16, block_size: usize) -> Vec<u8> {
+        let contents: Vec<u8> = files.iter().flat_map(|(_, data)| data.to_vec()).collect();
+        let mut file_entries = vec![];
+        let mut folder_offset = 0u32;
+        for (name, data) in files {
+            file_entries.extend((data.len() as u32).to_le_bytes());
+            file_entries.extend(folder_offset.to_le_bytes());
+            file_entries.extend([0; 8]);
+            file_entries.extend(name.as_bytes());
+            file_entries.push(0);
+            folder_offset += data.len() as u32;
+        }
+        let mut blocks = vec![];
+        let mut block_count = 0u16;
+        for chunk in contents.chunks(block_size) {
+            let data = match compression {
+                CAB_COMPRESSION_MSZIP => [b"CK".to_vec(), compress_to_vec(chunk, 6)].concat(),
+                _ => chunk.to_vec(),
+            };
+            blocks.extend([0; 4]);
+            blocks.extend((data.len() as u16).to_le_bytes());
+            blocks.extend((chunk.len() as u16).to_le_bytes());
+            blocks.extend(data);
+            block_count += 1;
+        }
+
+        let files_offset = 36 + 8;
+        let data_offset = files_offset + file_entries.len();
+        let mut cabinet = CAB_SIGNATURE.to_vec();
+        cabinet.extend([0; 4]);
+        cabinet.extend(((data_offset + blocks.len()) as u32).to_le_bytes());
+        cabinet.extend([0; 4]);
+        cabinet.extend((files_offset as u32).to_le_bytes());
+        cabinet.extend([0; 4]);
+        cabinet.extend([3, 1]);
+        cabinet.extend(1u16.to_le_bytes());
+        cabinet.extend((files.len() as u16).to_le_bytes());
+        cabinet.extend([0; 6]);
+        cabinet.extend((data_offset as u32).to_le_bytes());
+        cabinet.extend(block_count.to_le_bytes());
+        cabinet.extend(compression.to_le_bytes());
+        cabinet.extend(file_entries);
+        cabinet.extend(blocks);
+        cabinet
+    }
+
+    #[test]
+    fn formats_file_ids() {
+        let guid = [
+            0x1d, 0x22, 0x5f, 0x32, 0xb8, 0x6d, 0x4e, 0x27, 0x8c, 0x4b, 0xf4, 0x9c, 0xb5, 0xd1,
+            0xa7, 0x34,
+        ];
+        let id = SymbolFileId::pdb(r"C:\build\ntdll.pdb", &guid, 0x1a).unwrap();
+        assert_eq!(id.file_name(), "ntdll.pdb");
+        assert_eq!(id.key(), "1D225F32B86D4E278C4BF49CB5D1A7341A");
+        assert_eq!(id.compressed_file_name(), "ntdll.pd_");
+        assert_eq!(
+            id.store_path(Path::new("/symbols")),
+            Path::new("/symbols/ntdll.pdb/1D225F32B86D4E278C4BF49CB5D1A7341A/ntdll.pdb")
+        );
+        let id = SymbolFileId::pe("acpi.sys", 0x37cdb039, 0x62040).unwrap();
+        assert_eq!(id.key(), "37CDB03962040");
+
+        assert!(SymbolFileId::new("..", "AB").is_err());
+        assert!(SymbolFileId::new("a.pdb", "../AB").is_err());
+        assert!(SymbolFileId::pdb("dir/", &guid, 1).is_err());
+    }
+
+    #[test]
+    fn parses_symbol_paths() {
+        let default = Path::new("DEFAULT_STORE");
+        let local = |path: &str| SymbolStore::Local(PathBuf::from(path));
+        let http = |url: &str| SymbolStore::Http(url.to_string());
+        assert_eq!(
+            parse_symbol_path(r"srv*c:\localsymbols*\\mybuilds\mysymbols", default),
+            [[local(r"c:\localsymbols"), local(r"\\mybuilds\mysymbols")]]
+        );
+        assert_eq!(
+            parse_symbol_path("SRV**https://msdl.microsoft.com/download/symbols/", default),
+            [[
+                local("DEFAULT_STORE"),
+                http("https://msdl.microsoft.com/download/symbols")
+            ]]
+        );
+        assert_eq!(
+            parse_symbol_path(
+                r"cache*c:\cache;srv*https://a;no\such\directory;srv*\\share\store",
+                default
+            ),
+            [
+                [local(r"c:\cache"), http("https://a")],
+                [local(r"c:\cache"), local(r"\\share\store")]
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_cabinets() {
+        let pdb: Vec<u8> = (0..100_000u32)
+            .map(|i| (i % 251) as u8 ^ (i / 977) as u8)
+            .collect();
+        let mszip = cabinet(&[("a.pdb", &pdb)], CAB_COMPRESSION_MSZIP, CAB_BLOCK_SIZE);
+        assert_eq!(extract_cabinet_file(&mszip, "A.PDB").unwrap(), pdb);
+
+        let stored = cabinet(
+            &[("a.pdb", b"first"), ("b.pdb", b"second")],
+            CAB_COMPRESSION_NONE,
+            4,
+        );
+        assert_eq!(extract_cabinet_file(&stored, "b.pdb").unwrap(), b"second");
+        assert!(extract_cabinet_file(&stored, "c.pdb").is_err());
+        assert!(extract_cabinet_file(&stored[..stored.len() - 1], "b.pdb").is_err());
+
+        let lzx = cabinet(&[("a.pdb", b"data")], 3, CAB_BLOCK_SIZE);
+        assert!(matches!(
+            extract_cabinet_file(&lzx, "a.pdb"),
+            Err(SymbolServerError::UnsupportedCompression(3))
+        ));
+
+        let extract =
+            |name| -> crate::error::Result<Vec<u8>> { Ok(extract_cabinet_file(&lzx, name)?) };
+        assert!(matches!(
+            extract("a.pdb"),
+            Err(crate::Error::SymbolServer(
+                SymbolServerError::UnsupportedCompression(3)
+            ))
+        ));
+    }
+
+    #[test]
+    fn fetches_from_local_stores() {
+        let root = tempfile::tempdir().unwrap();
+        let (downstream, upstream) = (root.path().join("down"), root.path().join("up"));
+        let id = SymbolFileId::new("a.pdb", "ABC1").unwrap();
+        let compressed = upstream.join("a.pdb/ABC1/a.pd_");
+        fs::create_dir_all(compressed.parent().unwrap()).unwrap();
+        let cab = cabinet(&[("a.pdb", b"pdb")], CAB_COMPRESSION_MSZIP, CAB_BLOCK_SIZE);
+        fs::write(&compressed, cab).unwrap();
+
+        let mut server = SymbolServer::new(root.path().join("cache"));
+        server.add_symbol_path(&format!(
+            "srv*{}*{}",
+            downstream.display(),
+            upstream.display()
+        ));
+        server.set_offline(true);
+        let path = server
+            .fetch_path(&id, crate::progress::NoProgressCallback)
+            .unwrap();
+        assert_eq!(fs::read(path).unwrap(), b"pdb");
+        assert_eq!(fs::read(id.store_path(&downstream)).unwrap(), b"pdb");
+
+        let missing = SymbolFileId::new("b.pdb", "ABC1").unwrap();
+        assert!(matches!(
+            server.fetch(&missing, crate::progress::NoProgressCallback),
+            Err(SymbolServerError::NotFound(_))
+        ));
+    }
+}
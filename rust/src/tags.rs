@@ -129,6 +129,11 @@ pub struct TagType {
 }
 
 impl TagType {
+    pub(crate) unsafe fn from_raw(handle: *mut BNTagType) -> Self {
+        debug_assert!(!handle.is_null());
+        Self { handle }
+    }
+
     pub(crate) unsafe fn ref_from_raw(handle: *mut BNTagType) -> Ref<Self> {
         debug_assert!(!handle.is_null());
         Ref::new(Self { handle })
@@ -227,13 +232,30 @@ impl ToOwned for TagType {
     }
 }
 
+impl CoreArrayProvider for TagType {
+    type Raw = *mut BNTagType;
+    type Context = ();
+    type Wrapped<'a> = Guard<'a, TagType>;
+}
+
+unsafe impl CoreArrayProviderInner for TagType {
+    unsafe fn free(raw: *mut Self::Raw, count: usize, _context: &Self::Context) {
+        BNFreeTagTypeList(raw, count)
+    }
+
+    unsafe fn wrap_raw<'a>(raw: &'a Self::Raw, context: &'a Self::Context) -> Self::Wrapped<'a> {
+        Guard::new(Self::from_raw(*raw), context)
+    }
+}
+
 unsafe impl Send for TagType {}
 unsafe impl Sync for TagType {}
 
+/// Where a tag is used. Data tags have no function or architecture.
 #[derive(Clone, PartialEq)]
 pub struct TagReference {
-    pub arch: CoreArchitecture,
-    pub func: Ref<Function>,
+    pub arch: Option<CoreArchitecture>,
+    pub func: Option<Ref<Function>>,
     pub addr: u64,
     pub auto_defined: bool,
     pub reference_type: TagReferenceType,
@@ -245,9 +267,11 @@ impl From<&BNTagReference> for TagReference {
         Self {
             reference_type: value.refType,
             auto_defined: value.autoDefined,
-            tag: unsafe { Tag::ref_from_raw(value.tag).to_owned() },
-            arch: unsafe { CoreArchitecture::from_raw(value.arch) },
-            func: unsafe { Function::from_raw(value.func).to_owned() },
+            tag: unsafe { Tag::from_raw(value.tag).to_owned() },
+            arch: (!value.arch.is_null())
+                .then(|| unsafe { CoreArchitecture::from_raw(value.arch) }),
+            func: (!value.func.is_null())
+                .then(|| unsafe { Function::from_raw(value.func).to_owned() }),
             addr: value.addr,
         }
     }
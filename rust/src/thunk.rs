@@ -0,0 +1,326 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Classification of functions that only jump somewhere else.
+
+use std::collections::HashMap;
+
+use crate::binary_view::{BinaryView, BinaryViewExt};
+use crate::function::Function;
+use crate::low_level_il::expression::{ExpressionHandler, LowLevelILExpressionKind};
+use crate::low_level_il::instruction::{InstructionHandler, LowLevelILInstructionKind};
+use crate::metadata::Metadata;
+use crate::rc::Ref;
+use crate::symbol::{Symbol, SymbolBuilder, SymbolType};
+use crate::types::{Type, TypeClass};
+
+/// View metadata key of the map from function address to the thunk kind set by the user.
+pub const THUNK_KINDS_METADATA_KEY: &str = "thunk_kinds";
+
+/// The most low level IL instructions a function can have and still be a thunk.
+const MAX_THUNK_INSTRUCTIONS: usize = 8;
+
+/// The most thunks [`resolve_thunks`] follows, which stops it on cycles.
+const MAX_THUNK_CHAIN: usize = 16;
+
+/// Sections holding the stubs the dynamic linker resolves imports through.
+const STUB_SECTIONS: &[&str] = &[
+    ".plt",
+    ".plt.got",
+    ".plt.sec",
+    ".iplt",
+    "__stubs",
+    "__auth_stubs",
+    "__symbol_stub",
+    "__symbol_stub1",
+    "__picsymbolstub4",
+];
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ThunkKind {
+    /// A jump to another function of the view.
+    Thunk,
+    /// A stub in a section of stubs the dynamic linker resolves, such as `.plt` or `__stubs`.
+    PltStub,
+    /// A jump to an import outside of a stub section, such as through the import address table
+    /// of a PE.
+    ImportTrampoline,
+}
+
+impl ThunkKind {
+    fn name(self) -> &'static str {
+        match self {
+            ThunkKind::Thunk => "thunk",
+            ThunkKind::PltStub => "plt_stub",
+            ThunkKind::ImportTrampoline => "import_trampoline",
+        }
+    }
+
+    fn from_name(kind: &str) -> Option<Self> {
+        match kind {
+            "thunk" => Some(ThunkKind::Thunk),
+            "plt_stub" => Some(ThunkKind::PltStub),
+            "import_trampoline" => Some(ThunkKind::ImportTrampoline),
+            _ => None,
+        }
+    }
+}
+
+/// A function that only jumps somewhere else, see [`Function::thunk`].
+///
+/// PLT stubs, import trampolines and compiler generated thunks show up as functions of their own,
+/// so call graphs built from the functions of a view are full of them. The classification can be
+/// corrected with [`Function::set_user_thunk_kind`], which is stored in the metadata of the view.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Thunk {
+    pub kind: ThunkKind,
+    /// The start of the thunk function.
+    pub address: u64,
+    /// The pointer the thunk jumps through, such as an entry of the GOT or import address table.
+    pub slot: Option<u64>,
+    /// The address the thunk jumps to, if known. For thunks jumping through a slot this is the
+    /// value of the slot, which may only be filled in at run time.
+    pub target: Option<u64>,
+    /// The symbol of the slot or of the target, naming what the thunk stands in for.
+    pub target_symbol: Option<Ref<Symbol>>,
+    /// Whether the kind was set with [`Function::set_user_thunk_kind`].
+    pub user: bool,
+}
+
+impl Thunk {
+    /// The function the thunk jumps to, if it is one of the view.
+    pub fn target_function(&self, view: &BinaryView) -> Option<Ref<Function>> {
+        let target = self.target?;
+        view.functions_at(target)
+            .iter()
+            .next()
+            .map(|func| func.to_owned())
+    }
+
+    /// The type of what the thunk stands in for: the type of the target function, or the type
+    /// pointed to by the slot.
+    pub fn target_type(&self, view: &BinaryView) -> Option<Ref<Type>> {
+        if let Some(func) = self.target_function(view) {
+            return Some(func.function_type());
+        }
+        let var = view.data_variable_at_address(self.slot?)?;
+        let target = var.ty.contents.target()?.contents;
+        (target.type_class() == TypeClass::FunctionTypeClass).then_some(target)
+    }
+}
+
+/// How a thunk function leaves, found by [`jump_of`].
+struct Jump {
+    slot: Option<u64>,
+    target: Option<u64>,
+}
+
+/// The jump ending `func` if all it does before is set registers.
+fn jump_of(func: &Function) -> Option<Jump> {
+    let llil = func.low_level_il().ok()?;
+    let blocks = llil.basic_blocks();
+    if blocks.len() != 1 {
+        return None;
+    }
+    let block = blocks.iter().next()?;
+    let instrs: Vec<_> = block.iter().collect();
+    if instrs.len() > MAX_THUNK_INSTRUCTIONS {
+        return None;
+    }
+    let (last, setup) = instrs.split_last()?;
+    let only_sets_registers = setup.iter().all(|instr| {
+        matches!(
+            instr.kind(),
+            LowLevelILInstructionKind::Nop(_)
+                | LowLevelILInstructionKind::SetReg(_)
+                | LowLevelILInstructionKind::SetRegSplit(_)
+        )
+    });
+    if !only_sets_registers {
+        return None;
+    }
+    let dest = match last.kind() {
+        LowLevelILInstructionKind::Jump(op) => op.target(),
+        LowLevelILInstructionKind::TailCall(op) => op.target(),
+        _ => return None,
+    };
+
+    let mut jump = Jump {
+        slot: None,
+        target: None,
+    };
+    match dest.kind() {
+        LowLevelILExpressionKind::Const(op) | LowLevelILExpressionKind::ConstPtr(op) => {
+            jump.target = Some(op.value())
+        }
+        LowLevelILExpressionKind::Load(op) => {
+            if let LowLevelILExpressionKind::ConstPtr(op) = op.source_mem_expr().kind() {
+                jump.slot = Some(op.value());
+            }
+        }
+        _ => {}
+    }
+    if jump.slot.is_none() && jump.target.is_none() {
+        // Jumps through a register, such as on aarch64, load the slot while setting it up
+        let view = func.view();
+        jump.slot = setup.iter().find_map(|instr| {
+            let data_refs = view.data_refs_from_addr(instr.address());
+            let slot = data_refs.iter().next().map(|data_ref| data_ref.address);
+            slot
+        });
+    }
+    if let Some(slot) = jump.slot {
+        jump.target = func.view().read_pointer(slot).ok().filter(|&ptr| ptr != 0);
+    }
+    Some(jump)
+}
+
+fn in_stub_section(view: &BinaryView, addr: u64) -> bool {
+    view.sections_at(addr)
+        .iter()
+        .any(|section| STUB_SECTIONS.contains(&section.name().as_str()))
+}
+
+fn is_import(symbol: &Symbol) -> bool {
+    matches!(
+        symbol.sym_type(),
+        SymbolType::ImportAddress | SymbolType::ImportedFunction | SymbolType::External
+    )
+}
+
+/// Recognize `func` as a thunk, see [`Function::thunk`].
+pub(crate) fn classify(func: &Function) -> Option<Thunk> {
+    let view = func.view();
+    let user_kind = user_kind(&view, func.start());
+    if let Some(None) = user_kind {
+        return None;
+    }
+    let jump = jump_of(func);
+    if jump.is_none() && user_kind.is_none() {
+        return None;
+    }
+    let (slot, target) = jump.map_or((None, None), |jump| (jump.slot, jump.target));
+    let target_symbol = slot
+        .and_then(|slot| view.symbol_by_address(slot))
+        .or_else(|| target.and_then(|target| view.symbol_by_address(target)));
+
+    let kind = match user_kind {
+        Some(Some(kind)) => kind,
+        _ if in_stub_section(&view, func.start()) => ThunkKind::PltStub,
+        _ if target_symbol.as_deref().is_some_and(is_import) => ThunkKind::ImportTrampoline,
+        _ => ThunkKind::Thunk,
+    };
+    Some(Thunk {
+        kind,
+        address: func.start(),
+        slot,
+        target,
+        target_symbol,
+        user: user_kind.is_some(),
+    })
+}
+
+fn user_kinds(view: &BinaryView) -> HashMap<String, Ref<Metadata>> {
+    view.query_metadata(THUNK_KINDS_METADATA_KEY)
+        .and_then(|metadata| HashMap::<String, Ref<Metadata>>::try_from(&*metadata).ok())
+        .unwrap_or_default()
+}
+
+/// The kind the user set for the function at `addr`, `Some(None)` if they set it to not be a
+/// thunk.
+fn user_kind(view: &BinaryView, addr: u64) -> Option<Option<ThunkKind>> {
+    let kinds = user_kinds(view);
+    let kind = String::try_from(&**kinds.get(&format!("{addr:#x}"))?).ok()?;
+    Some(ThunkKind::from_name(&kind))
+}
+
+/// Set the kind of the function at `addr`, or forget it with `None`.
+pub(crate) fn set_user_kind(view: &BinaryView, addr: u64, kind: Option<Option<ThunkKind>>) {
+    let mut kinds = user_kinds(view);
+    let key = format!("{addr:#x}");
+    match kind {
+        Some(kind) => {
+            kinds.insert(key, kind.map_or("none", ThunkKind::name).into());
+        }
+        None => {
+            kinds.remove(&key);
+        }
+    }
+    view.store_metadata(THUNK_KINDS_METADATA_KEY, kinds, false);
+}
+
+/// The function the function at `addr` stands in for, following chains of thunks.
+///
+/// Returns `addr` if it is not the start of a thunk, and the last address reached if a thunk
+/// jumps to an unknown place.
+///
+/// ```no_run
+/// use binaryninja::binary_view::BinaryViewExt;
+/// use binaryninja::thunk::resolve_thunks;
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// for func in view.functions().iter() {
+///     if let Some(thunk) = func.thunk() {
+///         let target = resolve_thunks(&view, func.start());
+///         println!("{:#x} is a {:?} for {:#x}", func.start(), thunk.kind, target);
+///     }
+/// }
+/// ```
+pub fn resolve_thunks(view: &BinaryView, addr: u64) -> u64 {
+    let mut addr = addr;
+    for _ in 0..MAX_THUNK_CHAIN {
+        let Some(func) = view.functions_at(addr).iter().next().map(|f| f.to_owned()) else {
+            break;
+        };
+        match func.thunk().and_then(|thunk| thunk.target) {
+            Some(target) if target != addr => addr = target,
+            _ => break,
+        }
+    }
+    addr
+}
+
+/// Name the thunks of `view` that still have a generated name after what they jump to, with a
+/// `j_` prefix, and give them its type unless the user already set one.
+///
+/// Returns the number of thunks that were renamed or retyped.
+pub fn forward_through_thunks(view: &BinaryView) -> usize {
+    let mut forwarded = 0;
+    for func in view.functions().iter() {
+        let Some(thunk) = func.thunk() else {
+            continue;
+        };
+        let mut changed = false;
+        let symbol = func.symbol();
+        let generated_name =
+            symbol.auto_defined() && symbol.raw_name().as_str().starts_with("sub_");
+        if let (true, Some(target_symbol)) = (generated_name, &thunk.target_symbol) {
+            let name = format!("j_{}", target_symbol.short_name());
+            view.define_auto_symbol(
+                &SymbolBuilder::new(SymbolType::Function, name, func.start()).create(),
+            );
+            changed = true;
+        }
+        if !func.has_user_type() {
+            if let Some(ty) = thunk.target_type(view) {
+                func.set_auto_type(&ty);
+                changed = true;
+            }
+        }
+        if changed {
+            forwarded += 1;
+        }
+    }
+    forwarded
+}
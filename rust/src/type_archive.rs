@@ -1,5 +1,6 @@
 use crate::progress::{NoProgressCallback, ProgressCallback};
 use binaryninjacore_sys::*;
+use std::collections::{HashMap, HashSet};
 use std::ffi::{c_char, c_void, CStr};
 use std::fmt::{Debug, Display, Formatter};
 use std::hash::Hash;
@@ -14,6 +15,8 @@ use crate::string::{raw_to_string, BnStrCompatible, BnString};
 use crate::type_container::TypeContainer;
 use crate::types::{QualifiedName, QualifiedNameAndType, QualifiedNameTypeAndId, Type};
 
+pub type TypeArchiveSyncStatus = BNSyncStatus;
+
 #[repr(transparent)]
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct TypeArchiveSnapshotId(pub String);
@@ -30,6 +33,14 @@ impl Display for TypeArchiveSnapshotId {
     }
 }
 
+unsafe impl BnStrCompatible for &TypeArchiveSnapshotId {
+    type Result = Vec<u8>;
+
+    fn into_bytes_with_nul(self) -> Self::Result {
+        self.0.as_str().into_bytes_with_nul()
+    }
+}
+
 impl CoreArrayProvider for TypeArchiveSnapshotId {
     type Raw = *mut c_char;
     type Context = ();
@@ -142,7 +153,10 @@ impl TypeArchive {
     /// Revert the type archive's current snapshot to the given snapshot
     pub fn set_current_snapshot_id(&self, id: &TypeArchiveSnapshotId) {
         unsafe {
-            BNSetTypeArchiveCurrentSnapshot(self.handle.as_ptr(), id.0.as_ptr() as *const c_char)
+            BNSetTypeArchiveCurrentSnapshot(
+                self.handle.as_ptr(),
+                id.into_bytes_with_nul().as_ptr() as *const c_char,
+            )
         }
     }
 
@@ -163,7 +177,7 @@ impl TypeArchive {
         let result = unsafe {
             BNGetTypeArchiveSnapshotParentIds(
                 self.handle.as_ptr(),
-                snapshot.0.as_ptr() as *const c_char,
+                snapshot.into_bytes_with_nul().as_ptr() as *const c_char,
                 &mut count,
             )
         };
@@ -179,7 +193,7 @@ impl TypeArchive {
         let result = unsafe {
             BNGetTypeArchiveSnapshotChildIds(
                 self.handle.as_ptr(),
-                snapshot.0.as_ptr() as *const c_char,
+                snapshot.into_bytes_with_nul().as_ptr() as *const c_char,
                 &mut count,
             )
         };
@@ -287,7 +301,7 @@ impl TypeArchive {
             BNGetTypeArchiveTypeByName(
                 self.handle.as_ptr(),
                 &raw_name,
-                snapshot.0.as_ptr() as *const c_char,
+                snapshot.into_bytes_with_nul().as_ptr() as *const c_char,
             )
         };
         QualifiedName::free_raw(raw_name);
@@ -315,7 +329,7 @@ impl TypeArchive {
             BNGetTypeArchiveTypeById(
                 self.handle.as_ptr(),
                 id.as_ref().as_ptr() as *const c_char,
-                snapshot.0.as_ptr() as *const c_char,
+                snapshot.into_bytes_with_nul().as_ptr() as *const c_char,
             )
         };
         (!result.is_null()).then(|| unsafe { Type::ref_from_raw(result) })
@@ -342,7 +356,7 @@ impl TypeArchive {
             BNGetTypeArchiveTypeName(
                 self.handle.as_ptr(),
                 id.as_ref().as_ptr() as *const c_char,
-                snapshot.0.as_ptr() as *const c_char,
+                snapshot.into_bytes_with_nul().as_ptr() as *const c_char,
             )
         };
         QualifiedName::from_owned_raw(result)
@@ -369,7 +383,7 @@ impl TypeArchive {
             BNGetTypeArchiveTypeId(
                 self.handle.as_ptr(),
                 &raw_name,
-                snapshot.0.as_ptr() as *const c_char,
+                snapshot.into_bytes_with_nul().as_ptr() as *const c_char,
             )
         };
         QualifiedName::free_raw(raw_name);
@@ -392,7 +406,7 @@ impl TypeArchive {
         let result = unsafe {
             BNGetTypeArchiveTypes(
                 self.handle.as_ptr(),
-                snapshot.0.as_ptr() as *const c_char,
+                snapshot.into_bytes_with_nul().as_ptr() as *const c_char,
                 &mut count,
             )
         };
@@ -400,6 +414,39 @@ impl TypeArchive {
         unsafe { Array::new(result, count, ()) }
     }
 
+    /// The ids of the types that differ between the snapshots `old` and `new`.
+    pub fn diff_snapshots(
+        &self,
+        old: &TypeArchiveSnapshotId,
+        new: &TypeArchiveSnapshotId,
+    ) -> TypeArchiveDiff {
+        let old_types: HashMap<String, QualifiedNameTypeAndId> = self
+            .get_types_and_ids_from_snapshot(old)
+            .iter()
+            .map(|t| (t.id.clone(), t))
+            .collect();
+        let mut diff = TypeArchiveDiff::default();
+        let mut new_ids = HashSet::new();
+        for new_type in &self.get_types_and_ids_from_snapshot(new) {
+            match old_types.get(&new_type.id) {
+                None => diff.added.push(new_type.id.clone()),
+                Some(old_type) if old_type.name != new_type.name || old_type.ty != new_type.ty => {
+                    diff.modified.push(new_type.id.clone())
+                }
+                Some(_) => {}
+            }
+            new_ids.insert(new_type.id);
+        }
+        diff.removed = old_types
+            .into_keys()
+            .filter(|id| !new_ids.contains(id))
+            .collect();
+        diff.added.sort();
+        diff.removed.sort();
+        diff.modified.sort();
+        diff
+    }
+
     /// Get a list of all types' ids in the archive at a snapshot
     pub fn get_type_ids(&self) -> Array<BnString> {
         self.get_type_ids_from_snapshot(&TypeArchiveSnapshotId::unset())
@@ -413,7 +460,7 @@ impl TypeArchive {
         let result = unsafe {
             BNGetTypeArchiveTypeIds(
                 self.handle.as_ptr(),
-                snapshot.0.as_ptr() as *const c_char,
+                snapshot.into_bytes_with_nul().as_ptr() as *const c_char,
                 &mut count,
             )
         };
@@ -437,7 +484,7 @@ impl TypeArchive {
         let result = unsafe {
             BNGetTypeArchiveTypeNames(
                 self.handle.as_ptr(),
-                snapshot.0.as_ptr() as *const c_char,
+                snapshot.into_bytes_with_nul().as_ptr() as *const c_char,
                 &mut count,
             )
         };
@@ -463,7 +510,7 @@ impl TypeArchive {
         let result = unsafe {
             BNGetTypeArchiveTypeNamesAndIds(
                 self.handle.as_ptr(),
-                snapshot.0.as_ptr() as *const c_char,
+                snapshot.into_bytes_with_nul().as_ptr() as *const c_char,
                 &mut names,
                 &mut ids,
                 &mut count,
@@ -497,7 +544,7 @@ impl TypeArchive {
             BNGetTypeArchiveOutgoingDirectTypeReferences(
                 self.handle.as_ptr(),
                 id.as_ref().as_ptr() as *const c_char,
-                snapshot.0.as_ptr() as *const c_char,
+                snapshot.into_bytes_with_nul().as_ptr() as *const c_char,
                 &mut count,
             )
         };
@@ -527,7 +574,7 @@ impl TypeArchive {
             BNGetTypeArchiveOutgoingRecursiveTypeReferences(
                 self.handle.as_ptr(),
                 id.as_ref().as_ptr() as *const c_char,
-                snapshot.0.as_ptr() as *const c_char,
+                snapshot.into_bytes_with_nul().as_ptr() as *const c_char,
                 &mut count,
             )
         };
@@ -557,7 +604,7 @@ impl TypeArchive {
             BNGetTypeArchiveIncomingDirectTypeReferences(
                 self.handle.as_ptr(),
                 id.as_ref().as_ptr() as *const c_char,
-                snapshot.0.as_ptr() as *const c_char,
+                snapshot.into_bytes_with_nul().as_ptr() as *const c_char,
                 &mut count,
             )
         };
@@ -587,7 +634,7 @@ impl TypeArchive {
             BNGetTypeArchiveIncomingRecursiveTypeReferences(
                 self.handle.as_ptr(),
                 id.as_ref().as_ptr() as *const c_char,
-                snapshot.0.as_ptr() as *const c_char,
+                snapshot.into_bytes_with_nul().as_ptr() as *const c_char,
                 &mut count,
             )
         };
@@ -639,7 +686,7 @@ impl TypeArchive {
         let result = unsafe {
             BNTypeArchiveSerializeSnapshot(
                 self.handle.as_ptr(),
-                snapshot.0.as_ptr() as *const c_char,
+                snapshot.into_bytes_with_nul().as_ptr() as *const c_char,
             )
         };
         assert!(!result.is_null());
@@ -744,7 +791,8 @@ impl TypeArchive {
             fun(&TypeArchiveSnapshotId(id_str))
         }
 
-        // SAFETY TypeArchiveSnapshotId and `*const c_char` are transparent
+        let parents: Vec<BnString> = parents.iter().map(BnString::new).collect();
+        // SAFETY BnString and `*const c_char` are transparent
         let parents_raw = parents.as_ptr() as *const *const c_char;
 
         let result = unsafe {
@@ -865,6 +913,17 @@ impl TypeArchive {
     }
 }
 
+/// The types that differ between two snapshots of a [`TypeArchive`], by type id.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TypeArchiveDiff {
+    /// Types only in the newer snapshot.
+    pub added: Vec<String>,
+    /// Types only in the older snapshot.
+    pub removed: Vec<String>,
+    /// Types in both snapshots that were renamed or changed.
+    pub modified: Vec<String>,
+}
+
 impl ToOwned for TypeArchive {
     type Owned = Ref<Self>;
 
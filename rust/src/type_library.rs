@@ -8,6 +8,7 @@ use crate::{
     platform::Platform,
     rc::{Array, CoreArrayProvider, CoreArrayProviderInner, Ref},
     string::{BnStrCompatible, BnString},
+    symbol::Symbol,
     types::{QualifiedName, QualifiedNameAndType, Type},
 };
 
@@ -370,3 +371,26 @@ unsafe impl CoreArrayProviderInner for TypeLibrary {
         Self::ref_from_raw(raw)
     }
 }
+
+/// An import given a type by [`BinaryViewExt::apply_type_library_to_imports`].
+///
+/// [`BinaryViewExt::apply_type_library_to_imports`]: crate::binary_view::BinaryViewExt::apply_type_library_to_imports
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypedImport {
+    pub symbol: Ref<Symbol>,
+    /// The name of the type library the type was found in.
+    pub library: String,
+    /// The name of the object in the type library.
+    pub name: String,
+    pub ty: Ref<Type>,
+}
+
+/// What [`BinaryViewExt::apply_type_library_to_imports`] did with the imports of a view.
+///
+/// [`BinaryViewExt::apply_type_library_to_imports`]: crate::binary_view::BinaryViewExt::apply_type_library_to_imports
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ImportTypingReport {
+    pub applied: Vec<TypedImport>,
+    /// The imports none of the type libraries of the view has an object for.
+    pub unmatched: Vec<Ref<Symbol>>,
+}
@@ -1,7 +1,7 @@
 #![allow(unused)]
 use binaryninjacore_sys::*;
 use std::ffi::{c_char, c_void};
-use std::fmt::Debug;
+use std::fmt::{Debug, Display, Formatter};
 use std::ptr::NonNull;
 
 use crate::platform::Platform;
@@ -14,6 +14,8 @@ pub type TypeParserErrorSeverity = BNTypeParserErrorSeverity;
 pub type TypeParserOption = BNTypeParserOption;
 
 /// Register a custom parser with the API
+///
+/// The parser can then be selected with the `analysis.types.parserName` setting.
 pub fn register_type_parser<S: BnStrCompatible, T: TypeParser>(
     name: S,
     parser: T,
@@ -82,7 +84,7 @@ impl TypeParser for CoreTypeParser {
         };
         result.then(|| {
             assert!(!output.is_null());
-            value_cstr.to_string()
+            unsafe { BnString::from_raw(output) }.to_string()
         })
     }
 
@@ -97,6 +99,10 @@ impl TypeParser for CoreTypeParser {
     ) -> Result<String, Vec<TypeParserError>> {
         let source_cstr = BnString::new(source);
         let file_name_cstr = BnString::new(file_name);
+        let options: Vec<_> = options.iter().map(BnString::new).collect();
+        let options_raw: Vec<_> = options.iter().map(|o| o.as_ptr()).collect();
+        let include_dirs: Vec<_> = include_dirs.iter().map(BnString::new).collect();
+        let include_dirs_raw: Vec<_> = include_dirs.iter().map(|d| d.as_ptr()).collect();
         let mut result = std::ptr::null_mut();
         let mut errors = std::ptr::null_mut();
         let mut error_count = 0;
@@ -107,10 +113,10 @@ impl TypeParser for CoreTypeParser {
                 file_name_cstr.as_ptr(),
                 platform.handle,
                 existing_types.handle.as_ptr(),
-                options.as_ptr() as *const *const c_char,
-                options.len(),
-                include_dirs.as_ptr() as *const *const c_char,
-                include_dirs.len(),
+                options_raw.as_ptr(),
+                options_raw.len(),
+                include_dirs_raw.as_ptr(),
+                include_dirs_raw.len(),
                 &mut result,
                 &mut errors,
                 &mut error_count,
@@ -138,6 +144,10 @@ impl TypeParser for CoreTypeParser {
     ) -> Result<TypeParserResult, Vec<TypeParserError>> {
         let source_cstr = BnString::new(source);
         let file_name_cstr = BnString::new(file_name);
+        let options: Vec<_> = options.iter().map(BnString::new).collect();
+        let options_raw: Vec<_> = options.iter().map(|o| o.as_ptr()).collect();
+        let include_dirs: Vec<_> = include_dirs.iter().map(BnString::new).collect();
+        let include_dirs_raw: Vec<_> = include_dirs.iter().map(|d| d.as_ptr()).collect();
         let auto_type_source = BnString::new(auto_type_source);
         let mut raw_result = BNTypeParserResult::default();
         let mut errors = std::ptr::null_mut();
@@ -149,10 +159,10 @@ impl TypeParser for CoreTypeParser {
                 file_name_cstr.as_ptr(),
                 platform.handle,
                 existing_types.handle.as_ptr(),
-                options.as_ptr() as *const *const c_char,
-                options.len(),
-                include_dirs.as_ptr() as *const *const c_char,
-                include_dirs.len(),
+                options_raw.as_ptr(),
+                options_raw.len(),
+                include_dirs_raw.as_ptr(),
+                include_dirs_raw.len(),
                 auto_type_source.as_ptr(),
                 &mut raw_result,
                 &mut errors,
@@ -215,10 +225,16 @@ pub trait TypeParser {
     ///
     /// * `option` - Option type
     /// * `value` - Option value
-    fn get_option_text(&self, option: TypeParserOption, value: &str) -> Option<String>;
+    ///
+    /// Defaults to `None`, for parsers without options.
+    fn get_option_text(&self, option: TypeParserOption, value: &str) -> Option<String> {
+        None
+    }
 
     /// Preprocess a block of source, returning the source that would be parsed
     ///
+    /// Defaults to the source unchanged, for languages without a preprocessor.
+    ///
     /// * `source` - Source code to process
     /// * `file_name` - Name of the file containing the source (does not need to exist on disk)
     /// * `platform` - Platform to assume the source is relevant to
@@ -233,7 +249,9 @@ pub trait TypeParser {
         existing_types: &TypeContainer,
         options: &[String],
         include_dirs: &[String],
-    ) -> Result<String, Vec<TypeParserError>>;
+    ) -> Result<String, Vec<TypeParserError>> {
+        Ok(source.to_string())
+    }
 
     /// Parse an entire block of source into types, variables, and functions
     ///
@@ -344,6 +362,24 @@ impl TypeParserError {
     }
 }
 
+impl Display for TypeParserError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let severity = match self.severity {
+            TypeParserErrorSeverity::IgnoredSeverity => "ignored",
+            TypeParserErrorSeverity::NoteSeverity => "note",
+            TypeParserErrorSeverity::RemarkSeverity => "remark",
+            TypeParserErrorSeverity::WarningSeverity => "warning",
+            TypeParserErrorSeverity::ErrorSeverity => "error",
+            TypeParserErrorSeverity::FatalSeverity => "fatal error",
+        };
+        write!(
+            f,
+            "{}:{}:{}: {}: {}",
+            self.file_name, self.line, self.column, severity, self.message
+        )
+    }
+}
+
 impl CoreArrayProvider for TypeParserError {
     type Raw = BNTypeParserError;
     type Context = ();
@@ -360,6 +396,35 @@ unsafe impl CoreArrayProviderInner for TypeParserError {
     }
 }
 
+/// Options for parsing source with [`Platform::parse_types_with_options`].
+///
+/// ```no_run
+/// # use binaryninja::platform::Platform;
+/// use binaryninja::type_parser::TypeParserOptions;
+///
+/// let platform = Platform::by_name("windows-x86_64").unwrap();
+/// let options = TypeParserOptions {
+///     arguments: vec!["-DWIN32_LEAN_AND_MEAN".to_string()],
+///     include_dirs: vec!["/opt/sdk/include".to_string()],
+///     ..Default::default()
+/// };
+/// match platform.parse_types_with_options("#include <sdk.h>", "sdk.c", &options) {
+///     Ok(result) => println!("Parsed {} types", result.types.len()),
+///     Err(errors) => errors.iter().for_each(|e| eprintln!("{}", e)),
+/// }
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TypeParserOptions {
+    /// Arguments to pass to the parser, e.g. `-D` and `-x c++` command line arguments.
+    pub arguments: Vec<String>,
+    /// Directories to search for included headers.
+    pub include_dirs: Vec<String>,
+    /// Source of the types if they are automatically generated, empty for user types.
+    pub auto_type_source: String,
+    /// Whether to import the types of type libraries and type archives the source uses.
+    pub import_dependencies: bool,
+}
+
 #[derive(Debug, Eq, PartialEq, Default)]
 pub struct TypeParserResult {
     pub types: Vec<ParsedType>,
@@ -487,6 +552,18 @@ impl ParsedType {
     pub fn new(name: QualifiedName, ty: Ref<Type>, user: bool) -> Self {
         Self { name, ty, user }
     }
+
+    pub fn name(&self) -> &QualifiedName {
+        &self.name
+    }
+
+    pub fn ty(&self) -> &Ref<Type> {
+        &self.ty
+    }
+
+    pub fn is_user(&self) -> bool {
+        self.user
+    }
 }
 
 impl CoreArrayProvider for ParsedType {
@@ -1,6 +1,7 @@
 #![allow(unused)]
 
 use crate::binary_view::BinaryView;
+use crate::confidence::MAX_CONFIDENCE;
 use crate::disassembly::InstructionTextToken;
 use crate::platform::Platform;
 use crate::rc::{Array, CoreArrayProvider, CoreArrayProviderInner, Ref};
@@ -14,14 +15,16 @@ use std::ptr::NonNull;
 pub type TokenEscapingType = BNTokenEscapingType;
 pub type TypeDefinitionLineType = BNTypeDefinitionLineType;
 
-/// Register a custom parser with the API
+/// Register a custom printer with the API
+///
+/// The printer can then be selected with the `analysis.types.printerName` setting.
 pub fn register_type_printer<S: BnStrCompatible, T: TypePrinter>(
     name: S,
-    parser: T,
+    printer: T,
 ) -> (&'static mut T, CoreTypePrinter) {
-    let parser = Box::leak(Box::new(parser));
+    let printer = Box::leak(Box::new(printer));
     let mut callback = BNTypePrinterCallbacks {
-        context: parser as *mut _ as *mut c_void,
+        context: printer as *mut _ as *mut c_void,
         getTypeTokens: Some(cb_get_type_tokens::<T>),
         getTypeTokensBeforeName: Some(cb_get_type_tokens_before_name::<T>),
         getTypeTokensAfterName: Some(cb_get_type_tokens_after_name::<T>),
@@ -41,7 +44,7 @@ pub fn register_type_printer<S: BnStrCompatible, T: TypePrinter>(
         )
     };
     let core = unsafe { CoreTypePrinter::from_raw(NonNull::new(result).unwrap()) };
-    (parser, core)
+    (printer, core)
 }
 
 #[repr(transparent)]
@@ -189,11 +192,11 @@ impl CoreTypePrinter {
         &self,
         type_: &Type,
         platform: &Platform,
-        escaping: BNTokenEscapingType,
+        escaping: TokenEscapingType,
     ) -> Option<BnString> {
         let mut result = std::ptr::null_mut();
         let success = unsafe {
-            BNGetTypePrinterTypeStringAfterName(
+            BNGetTypePrinterTypeStringBeforeName(
                 self.handle.as_ptr(),
                 type_.handle,
                 platform.handle,
@@ -215,7 +218,7 @@ impl CoreTypePrinter {
     ) -> Option<BnString> {
         let mut result = std::ptr::null_mut();
         let success = unsafe {
-            BNGetTypePrinterTypeStringBeforeName(
+            BNGetTypePrinterTypeStringAfterName(
                 self.handle.as_ptr(),
                 type_.handle,
                 platform.handle,
@@ -446,6 +449,8 @@ pub trait TypePrinter {
     /// Generate a single-line text representation of a type. Returns a string
     /// representing the type
     ///
+    /// Defaults to the text of the tokens from [`TypePrinter::get_type_tokens`].
+    ///
     /// * `type_` - Type to print
     /// * `platform` - Platform responsible for this type
     /// * `name` - Name of the type
@@ -456,12 +461,17 @@ pub trait TypePrinter {
         platform: Option<Ref<Platform>>,
         name: T,
         escaping: TokenEscapingType,
-    ) -> Option<String>;
+    ) -> Option<String> {
+        self.get_type_tokens(type_, platform, name, MAX_CONFIDENCE, escaping)
+            .map(|tokens| tokens_to_string(&tokens))
+    }
 
     /// In a single-line text representation of a type, generate the string that
     /// should be printed before the type's name. Returns a string representing
     /// the type
     ///
+    /// Defaults to the text of the tokens from [`TypePrinter::get_type_tokens_before_name`].
+    ///
     /// * `type_` - Type to print
     /// * `platform` - Platform responsible for this type
     /// * `escaping` - Style of escaping literals which may not be parsable
@@ -470,12 +480,17 @@ pub trait TypePrinter {
         type_: Ref<Type>,
         platform: Option<Ref<Platform>>,
         escaping: TokenEscapingType,
-    ) -> Option<String>;
+    ) -> Option<String> {
+        self.get_type_tokens_before_name(type_, platform, MAX_CONFIDENCE, None, escaping)
+            .map(|tokens| tokens_to_string(&tokens))
+    }
 
     /// In a single-line text representation of a type, generate the string that
     /// should be printed after the type's name. Returns a string representing
     /// the type
     ///
+    /// Defaults to the text of the tokens from [`TypePrinter::get_type_tokens_after_name`].
+    ///
     /// * `type_` - Type to print
     /// * `platform` - Platform responsible for this type
     /// * `escaping` - Style of escaping literals which may not be parsable
@@ -484,7 +499,10 @@ pub trait TypePrinter {
         type_: Ref<Type>,
         platform: Option<Ref<Platform>>,
         escaping: TokenEscapingType,
-    ) -> Option<String>;
+    ) -> Option<String> {
+        self.get_type_tokens_after_name(type_, platform, MAX_CONFIDENCE, None, escaping)
+            .map(|tokens| tokens_to_string(&tokens))
+    }
 
     /// Generate a multi-line representation of a type. Returns a list of type
     /// definition lines
@@ -522,6 +540,10 @@ pub trait TypePrinter {
     ) -> Option<String>;
 }
 
+fn tokens_to_string(tokens: &[InstructionTextToken]) -> String {
+    tokens.iter().map(|token| token.text.as_str()).collect()
+}
+
 // TODO: This needs an extreme amount of documentation...
 #[derive(Clone)]
 pub struct TypeDefinitionLine {
@@ -684,9 +706,9 @@ unsafe extern "C" fn cb_get_type_tokens<T: TypePrinter>(
     // NOTE: The caller is responsible for freeing name.
     let qualified_name = QualifiedName::from_raw(&*name);
     let inner_result = ctxt.get_type_tokens(
-        unsafe { Type::ref_from_raw(type_) },
+        Type::from_raw(type_).to_owned(),
         match platform.is_null() {
-            false => Some(Platform::ref_from_raw(platform)),
+            false => Some(Platform::from_raw(platform).to_owned()),
             true => None,
         },
         qualified_name,
@@ -721,14 +743,14 @@ unsafe extern "C" fn cb_get_type_tokens_before_name<T: TypePrinter>(
 ) -> bool {
     let ctxt: &mut T = &mut *(ctxt as *mut T);
     let inner_result = ctxt.get_type_tokens_before_name(
-        Type::ref_from_raw(type_),
+        Type::from_raw(type_).to_owned(),
         match platform.is_null() {
-            false => Some(Platform::ref_from_raw(platform)),
+            false => Some(Platform::from_raw(platform).to_owned()),
             true => None,
         },
         base_confidence,
         match parent_type.is_null() {
-            false => Some(Type::ref_from_raw(parent_type)),
+            false => Some(Type::from_raw(parent_type).to_owned()),
             true => None,
         },
         escaping,
@@ -761,14 +783,14 @@ unsafe extern "C" fn cb_get_type_tokens_after_name<T: TypePrinter>(
 ) -> bool {
     let ctxt: &mut T = &mut *(ctxt as *mut T);
     let inner_result = ctxt.get_type_tokens_after_name(
-        Type::ref_from_raw(type_),
+        Type::from_raw(type_).to_owned(),
         match platform.is_null() {
-            false => Some(Platform::ref_from_raw(platform)),
+            false => Some(Platform::from_raw(platform).to_owned()),
             true => None,
         },
         base_confidence,
         match parent_type.is_null() {
-            false => Some(Type::ref_from_raw(parent_type)),
+            false => Some(Type::from_raw(parent_type).to_owned()),
             true => None,
         },
         escaping,
@@ -801,9 +823,9 @@ unsafe extern "C" fn cb_get_type_string<T: TypePrinter>(
     // NOTE: The caller is responsible for freeing name.
     let qualified_name = QualifiedName::from_raw(&*name);
     let inner_result = ctxt.get_type_string(
-        Type::ref_from_raw(type_),
+        Type::from_raw(type_).to_owned(),
         match platform.is_null() {
-            false => Some(Platform::ref_from_raw(platform)),
+            false => Some(Platform::from_raw(platform).to_owned()),
             true => None,
         },
         qualified_name,
@@ -829,9 +851,9 @@ unsafe extern "C" fn cb_get_type_string_before_name<T: TypePrinter>(
 ) -> bool {
     let ctxt: &mut T = &mut *(ctxt as *mut T);
     let inner_result = ctxt.get_type_string_before_name(
-        Type::ref_from_raw(type_),
+        Type::from_raw(type_).to_owned(),
         match platform.is_null() {
-            false => Some(Platform::ref_from_raw(platform)),
+            false => Some(Platform::from_raw(platform).to_owned()),
             true => None,
         },
         escaping,
@@ -856,9 +878,9 @@ unsafe extern "C" fn cb_get_type_string_after_name<T: TypePrinter>(
 ) -> bool {
     let ctxt: &mut T = &mut *(ctxt as *mut T);
     let inner_result = ctxt.get_type_string_after_name(
-        Type::ref_from_raw(type_),
+        Type::from_raw(type_).to_owned(),
         match platform.is_null() {
-            false => Some(Platform::ref_from_raw(platform)),
+            false => Some(Platform::from_raw(platform).to_owned()),
             true => None,
         },
         escaping,
@@ -891,7 +913,7 @@ unsafe extern "C" fn cb_get_type_lines<T: TypePrinter>(
     let types_ptr = NonNull::new(types).unwrap();
     let types = TypeContainer::from_raw(types_ptr);
     let inner_result = ctxt.get_type_lines(
-        Type::ref_from_raw(type_),
+        Type::from_raw(type_).to_owned(),
         &types,
         qualified_name,
         padding_cols as isize,
@@ -930,11 +952,14 @@ unsafe extern "C" fn cb_print_all_types<T: TypePrinter>(
     let names: Vec<_> = raw_names.iter().map(QualifiedName::from_raw).collect();
     let raw_types = std::slice::from_raw_parts(types, type_count);
     // NOTE: The caller is responsible for freeing raw_types.
-    let types: Vec<_> = raw_types.iter().map(|&t| Type::ref_from_raw(t)).collect();
+    let types: Vec<_> = raw_types
+        .iter()
+        .map(|&t| Type::from_raw(t).to_owned())
+        .collect();
     let inner_result = ctxt.print_all_types(
         names,
         types,
-        BinaryView::ref_from_raw(data),
+        BinaryView::from_raw(data).to_owned(),
         padding_cols as isize,
         escaping,
     );
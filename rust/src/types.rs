@@ -739,63 +739,20 @@ impl Type {
         result
     }
 
-    // TODO: FunctionBuilder
+    /// Create a function type, see [`FunctionBuilder`] for the other properties of one.
     pub fn function<'a, T: Into<Conf<&'a Type>>>(
         return_type: T,
         parameters: Vec<FunctionParameter>,
         variable_arguments: bool,
     ) -> Ref<Self> {
-        let mut owned_raw_return_type = Conf::<&Type>::into_raw(return_type.into());
-        let mut variable_arguments = Conf::new(variable_arguments, MAX_CONFIDENCE).into();
-        let mut can_return = Conf::new(true, MIN_CONFIDENCE).into();
-        let mut pure = Conf::new(false, MIN_CONFIDENCE).into();
-
-        let mut raw_calling_convention: BNCallingConventionWithConfidence =
-            BNCallingConventionWithConfidence {
-                convention: std::ptr::null_mut(),
-                confidence: MIN_CONFIDENCE,
-            };
-
-        let mut stack_adjust = Conf::new(0, MIN_CONFIDENCE).into();
-        let mut raw_parameters = parameters
-            .into_iter()
-            .map(FunctionParameter::into_raw)
-            .collect::<Vec<_>>();
-        let reg_stack_adjust_regs = std::ptr::null_mut();
-        let reg_stack_adjust_values = std::ptr::null_mut();
-
-        let mut return_regs: BNRegisterSetWithConfidence = BNRegisterSetWithConfidence {
-            regs: std::ptr::null_mut(),
-            count: 0,
-            confidence: 0,
-        };
-
-        let result = unsafe {
-            Self::ref_from_raw(BNNewTypeReference(BNCreateFunctionType(
-                &mut owned_raw_return_type,
-                &mut raw_calling_convention,
-                raw_parameters.as_mut_ptr(),
-                raw_parameters.len(),
-                &mut variable_arguments,
-                &mut can_return,
-                &mut stack_adjust,
-                reg_stack_adjust_regs,
-                reg_stack_adjust_values,
-                0,
-                &mut return_regs,
-                BNNameType::NoNameType,
-                &mut pure,
-            )))
-        };
-
-        for raw_param in raw_parameters {
-            FunctionParameter::free_raw(raw_param);
-        }
-
-        result
+        FunctionBuilder::new(return_type)
+            .parameters(parameters)
+            .variadic(variable_arguments)
+            .finalize()
     }
 
-    // TODO: FunctionBuilder
+    /// Create a function type with a calling convention, see [`FunctionBuilder`] for the other
+    /// properties of one.
     pub fn function_with_opts<
         'a,
         T: Into<Conf<&'a Type>>,
@@ -807,54 +764,12 @@ impl Type {
         calling_convention: C,
         stack_adjust: Conf<i64>,
     ) -> Ref<Self> {
-        let mut owned_raw_return_type = Conf::<&Type>::into_raw(return_type.into());
-        let mut variable_arguments = Conf::new(variable_arguments, MAX_CONFIDENCE).into();
-        let mut can_return = Conf::new(true, MIN_CONFIDENCE).into();
-        let mut pure = Conf::new(false, MIN_CONFIDENCE).into();
-
-        let mut owned_raw_calling_convention =
-            Conf::<Ref<CoreCallingConvention>>::into_owned_raw(&calling_convention.into());
-
-        let mut stack_adjust = stack_adjust.into();
-        let mut raw_parameters = parameters
-            .iter()
-            .cloned()
-            .map(FunctionParameter::into_raw)
-            .collect::<Vec<_>>();
-
-        // TODO: Update type signature and include these (will be a breaking change)
-        let reg_stack_adjust_regs = std::ptr::null_mut();
-        let reg_stack_adjust_values = std::ptr::null_mut();
-
-        let mut return_regs: BNRegisterSetWithConfidence = BNRegisterSetWithConfidence {
-            regs: std::ptr::null_mut(),
-            count: 0,
-            confidence: 0,
-        };
-
-        let result = unsafe {
-            Self::ref_from_raw(BNCreateFunctionType(
-                &mut owned_raw_return_type,
-                &mut owned_raw_calling_convention,
-                raw_parameters.as_mut_ptr(),
-                raw_parameters.len(),
-                &mut variable_arguments,
-                &mut can_return,
-                &mut stack_adjust,
-                reg_stack_adjust_regs,
-                reg_stack_adjust_values,
-                0,
-                &mut return_regs,
-                BNNameType::NoNameType,
-                &mut pure,
-            ))
-        };
-
-        for raw_param in raw_parameters {
-            FunctionParameter::free_raw(raw_param);
-        }
-
-        result
+        FunctionBuilder::new(return_type)
+            .parameters(parameters.iter().cloned())
+            .variadic(variable_arguments)
+            .calling_convention(calling_convention)
+            .stack_adjust(stack_adjust)
+            .finalize()
     }
 
     pub fn pointer<'a, A: Architecture, T: Into<Conf<&'a Type>>>(arch: &A, ty: T) -> Ref<Self> {
@@ -1123,6 +1038,168 @@ impl FunctionParameter {
     }
 }
 
+/// Builds function types.
+///
+/// Properties that aren't set are left for analysis to decide: the calling convention is the
+/// default one of the platform, the function is assumed to return and not to be pure, and there
+/// is no stack adjustment.
+///
+/// ```no_run
+/// # use binaryninja::platform::Platform;
+/// use binaryninja::types::{FunctionBuilder, Type};
+///
+/// let platform = Platform::by_name("windows-x86").unwrap();
+/// let cdecl = platform.get_cdecl_calling_convention().unwrap();
+/// let char_ptr = Type::pointer(&platform.arch(), &Type::char());
+///
+/// // int printf(const char* format, ...)
+/// let printf = FunctionBuilder::new(&Type::int(4, true))
+///     .param(&char_ptr, "format")
+///     .variadic(true)
+///     .calling_convention(cdecl)
+///     .finalize();
+///
+/// // void exit(int status), which never returns
+/// let exit = FunctionBuilder::new(&Type::void())
+///     .param(&Type::int(4, true), "status")
+///     .can_return(false)
+///     .finalize();
+/// ```
+#[derive(Clone, Debug)]
+pub struct FunctionBuilder {
+    return_type: Conf<Ref<Type>>,
+    parameters: Vec<FunctionParameter>,
+    variable_arguments: Conf<bool>,
+    calling_convention: Option<Conf<Ref<CoreCallingConvention>>>,
+    can_return: Conf<bool>,
+    pure: Conf<bool>,
+    stack_adjust: Conf<i64>,
+}
+
+impl FunctionBuilder {
+    pub fn new<'a, T: Into<Conf<&'a Type>>>(return_type: T) -> Self {
+        Self {
+            return_type: return_type.into().map(ToOwned::to_owned),
+            parameters: vec![],
+            variable_arguments: Conf::new(false, MAX_CONFIDENCE),
+            calling_convention: None,
+            can_return: Conf::new(true, MIN_CONFIDENCE),
+            pure: Conf::new(false, MIN_CONFIDENCE),
+            stack_adjust: Conf::new(0, MIN_CONFIDENCE),
+        }
+    }
+
+    /// Append a parameter with the default location for the calling convention.
+    pub fn param<'a, T: Into<Conf<&'a Type>>, S: Into<String>>(
+        &mut self,
+        ty: T,
+        name: S,
+    ) -> &mut Self {
+        let ty = ty.into().map(ToOwned::to_owned);
+        self.parameters
+            .push(FunctionParameter::new(ty, name.into(), None));
+        self
+    }
+
+    /// Append a parameter, which may have a location that isn't the default one.
+    pub fn parameter(&mut self, parameter: FunctionParameter) -> &mut Self {
+        self.parameters.push(parameter);
+        self
+    }
+
+    pub fn parameters<I: IntoIterator<Item = FunctionParameter>>(
+        &mut self,
+        parameters: I,
+    ) -> &mut Self {
+        self.parameters.extend(parameters);
+        self
+    }
+
+    /// Sets whether the function takes variable arguments after its parameters.
+    pub fn variadic<T: Into<Conf<bool>>>(&mut self, variable_arguments: T) -> &mut Self {
+        self.variable_arguments = variable_arguments.into();
+        self
+    }
+
+    pub fn calling_convention<C: Into<Conf<Ref<CoreCallingConvention>>>>(
+        &mut self,
+        calling_convention: C,
+    ) -> &mut Self {
+        self.calling_convention = Some(calling_convention.into());
+        self
+    }
+
+    pub fn can_return<T: Into<Conf<bool>>>(&mut self, can_return: T) -> &mut Self {
+        self.can_return = can_return.into();
+        self
+    }
+
+    pub fn pure<T: Into<Conf<bool>>>(&mut self, pure: T) -> &mut Self {
+        self.pure = pure.into();
+        self
+    }
+
+    /// Sets the number of bytes the function removes from the stack of its caller when it returns.
+    pub fn stack_adjust<T: Into<Conf<i64>>>(&mut self, stack_adjust: T) -> &mut Self {
+        self.stack_adjust = stack_adjust.into();
+        self
+    }
+
+    pub fn finalize(&self) -> Ref<Type> {
+        let mut raw_return_type = Conf::<&Type>::into_raw((&self.return_type).into());
+        let mut raw_calling_convention = match &self.calling_convention {
+            Some(calling_convention) => {
+                Conf::<Ref<CoreCallingConvention>>::into_owned_raw(calling_convention)
+            }
+            None => BNCallingConventionWithConfidence {
+                convention: std::ptr::null_mut(),
+                confidence: MIN_CONFIDENCE,
+            },
+        };
+        let mut raw_parameters = self
+            .parameters
+            .iter()
+            .cloned()
+            .map(FunctionParameter::into_raw)
+            .collect::<Vec<_>>();
+        let mut variable_arguments = self.variable_arguments.into();
+        let mut can_return = self.can_return.into();
+        let mut stack_adjust = self.stack_adjust.into();
+        let mut pure = self.pure.into();
+
+        // TODO: Support register stack adjustments and return registers
+        let mut return_regs: BNRegisterSetWithConfidence = BNRegisterSetWithConfidence {
+            regs: std::ptr::null_mut(),
+            count: 0,
+            confidence: 0,
+        };
+
+        let result = unsafe {
+            Type::ref_from_raw(BNCreateFunctionType(
+                &mut raw_return_type,
+                &mut raw_calling_convention,
+                raw_parameters.as_mut_ptr(),
+                raw_parameters.len(),
+                &mut variable_arguments,
+                &mut can_return,
+                &mut stack_adjust,
+                std::ptr::null_mut(),
+                std::ptr::null_mut(),
+                0,
+                &mut return_regs,
+                BNNameType::NoNameType,
+                &mut pure,
+            ))
+        };
+
+        for raw_param in raw_parameters {
+            FunctionParameter::free_raw(raw_param);
+        }
+
+        result
+    }
+}
+
 // TODO: We need to delete this...
 // Name, Variable and Type
 impl CoreArrayProvider for (&str, Variable, &Type) {
@@ -1265,6 +1342,34 @@ impl EnumerationBuilder {
     }
 }
 
+/// Build an enumeration with the given values, which needn't be consecutive.
+///
+/// ```no_run
+/// # use std::num::NonZeroUsize;
+/// use binaryninja::types::{EnumerationBuilder, Type};
+///
+/// let enumeration: EnumerationBuilder =
+///     [("READ", 1), ("WRITE", 2), ("EXECUTE", 4), ("ALL", 0xff)]
+///         .into_iter()
+///         .collect();
+/// let ty = Type::enumeration(&enumeration.finalize(), NonZeroUsize::new(4).unwrap(), false);
+/// ```
+impl<S: BnStrCompatible> FromIterator<(S, u64)> for EnumerationBuilder {
+    fn from_iter<I: IntoIterator<Item = (S, u64)>>(members: I) -> Self {
+        let mut builder = Self::new();
+        builder.extend(members);
+        builder
+    }
+}
+
+impl<S: BnStrCompatible> Extend<(S, u64)> for EnumerationBuilder {
+    fn extend<I: IntoIterator<Item = (S, u64)>>(&mut self, members: I) {
+        for (name, value) in members {
+            self.insert(name, value);
+        }
+    }
+}
+
 impl Default for EnumerationBuilder {
     fn default() -> Self {
         Self::new()
@@ -1571,6 +1676,150 @@ impl StructureBuilder {
     pub fn current_width(&self) -> u64 {
         unsafe { BNGetStructureBuilderWidth(self.handle) }
     }
+
+    /// Append a public member, with the alignment of its type unless the structure is packed.
+    pub fn member<'a, S: BnStrCompatible, T: Into<Conf<&'a Type>>>(
+        &mut self,
+        ty: T,
+        name: S,
+    ) -> &mut Self {
+        self.append(ty, name, MemberAccess::PublicAccess, MemberScope::NoScope)
+    }
+
+    /// Add a public member at `offset`, which doesn't replace the members it overlaps.
+    pub fn member_at<'a, S: BnStrCompatible, T: Into<Conf<&'a Type>>>(
+        &mut self,
+        ty: T,
+        name: S,
+        offset: u64,
+    ) -> &mut Self {
+        self.insert(
+            ty,
+            name,
+            offset,
+            false,
+            MemberAccess::PublicAccess,
+            MemberScope::NoScope,
+        )
+    }
+
+    /// Make this a union, all of its appended members are at offset `0`.
+    pub fn union(&mut self) -> &mut Self {
+        self.structure_type(StructureType::UnionStructureType)
+    }
+
+    pub fn class(&mut self) -> &mut Self {
+        self.structure_type(StructureType::ClassStructureType)
+    }
+
+    /// Add a structure this one inherits from, the members of which are at `offset`.
+    ///
+    /// Members aren't added for the base, see [`StructureBuilder::base_structures`].
+    pub fn base(&mut self, base: BaseStructure) -> &mut Self {
+        let mut bases = self.current_base_structures();
+        bases.push(base);
+        self.base_structures(&bases)
+    }
+
+    /// Append a storage unit of bitfields, each of `fields` being a name and a width in bits.
+    ///
+    /// The core has no bitfield members, so the bitfields become members of a union of the width
+    /// of `storage`, named `__bitfield` followed by the offset of the union in hex. Each of them is
+    /// a member of the whole `storage` type, their widths and bit positions are only used to pack
+    /// them: they are packed into the union in order until the next one doesn't fit, which starts
+    /// another union right after it.
+    ///
+    /// # Panics
+    ///
+    /// If a width is zero or wider than `storage`.
+    ///
+    /// ```no_run
+    /// use binaryninja::types::{StructureBuilder, Type};
+    ///
+    /// // struct { uint32_t flags : 3; uint32_t mode : 5; uint32_t length : 30; uint8_t tag; }
+    /// let u32_ty = Type::int(4, false);
+    /// let structure = StructureBuilder::new()
+    ///     .bitfield(&u32_ty, &[("flags", 3), ("mode", 5), ("length", 30)])
+    ///     .member(&Type::int(1, false), "tag")
+    ///     .finalize();
+    /// // `flags` and `mode` are in `__bitfield0`, `length` is in `__bitfield4`.
+    /// assert_eq!(structure.members().len(), 3);
+    /// ```
+    pub fn bitfield<S: AsRef<str>>(&mut self, storage: &Type, fields: &[(S, u64)]) -> &mut Self {
+        let storage_bits = storage.width() * 8;
+        let mut units: Vec<Vec<&str>> = vec![];
+        let mut used_bits = 0;
+        for (name, bits) in fields {
+            assert!(
+                (1..=storage_bits).contains(bits),
+                "bitfield `{}` of {} bits doesn't fit its {} bit storage",
+                name.as_ref(),
+                bits,
+                storage_bits
+            );
+            match units.last_mut() {
+                Some(unit) if used_bits + bits <= storage_bits => unit.push(name.as_ref()),
+                _ => {
+                    units.push(vec![name.as_ref()]);
+                    used_bits = 0;
+                }
+            }
+            used_bits += bits;
+        }
+
+        for unit in units {
+            let mut union = StructureBuilder::new();
+            union.union().width(storage.width());
+            for name in unit {
+                union.member(storage, name);
+            }
+            let offset = self.next_member_offset(storage);
+            let mut name = format!("__bitfield{:x}", offset);
+            let members = self.current_members();
+            let mut index = 0;
+            while members.iter().any(|m| m.name == name) {
+                index += 1;
+                name = format!("__bitfield{:x}_{}", offset, index);
+            }
+            self.member_at(&Type::structure(&union.finalize()), name, offset);
+        }
+        self
+    }
+
+    /// The offset [`StructureBuilder::append`] would place a member of type `ty` at.
+    fn next_member_offset(&self, ty: &Type) -> u64 {
+        let structure_type = unsafe { BNGetStructureBuilderType(self.handle) };
+        if structure_type == StructureType::UnionStructureType {
+            return 0;
+        }
+        let width = self.current_width();
+        let alignment = ty.alignment() as u64;
+        if unsafe { BNIsStructureBuilderPacked(self.handle) } || alignment <= 1 {
+            return width;
+        }
+        width.div_ceil(alignment) * alignment
+    }
+
+    fn current_members(&self) -> Vec<StructureMember> {
+        unsafe {
+            let mut count = 0;
+            let members_raw_ptr = BNGetStructureBuilderMembers(self.handle, &mut count);
+            let members_raw = std::slice::from_raw_parts(members_raw_ptr, count);
+            let members = members_raw.iter().map(StructureMember::from_raw).collect();
+            BNFreeStructureMemberList(members_raw_ptr, count);
+            members
+        }
+    }
+
+    fn current_base_structures(&self) -> Vec<BaseStructure> {
+        let mut count = 0;
+        let bases_raw_ptr =
+            unsafe { BNGetBaseStructuresForStructureBuilder(self.handle, &mut count) };
+        let bases_raw = unsafe { std::slice::from_raw_parts(bases_raw_ptr, count) };
+        let bases = bases_raw.iter().map(BaseStructure::from_raw).collect();
+        unsafe { BNFreeBaseStructureList(bases_raw_ptr, count) };
+        bases
+    }
 }
 
 impl From<&Structure> for StructureBuilder {
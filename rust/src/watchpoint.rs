@@ -0,0 +1,278 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Invariants over a view that are re-checked every time analysis completes.
+
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+
+use crate::analysis_completion::AnalysisCompletionEvent;
+use crate::binary_view::{BinaryView, BinaryViewExt};
+use crate::rc::Ref;
+use crate::types::Type;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WatchpointId(pub usize);
+
+/// A property of the view that is expected to keep holding across analysis updates.
+pub enum Invariant {
+    /// A data variable of exactly this type must be defined at the address.
+    DataVariableType { address: u64, ty: Ref<Type> },
+    /// A function must start at the address.
+    Function { address: u64 },
+    /// The symbol at the address must have this raw name.
+    Symbol { address: u64, name: String },
+    /// Any other check, returning a description of the problem when it does not hold.
+    Custom(Box<dyn Fn(&BinaryView) -> Result<(), String> + Send + Sync>),
+}
+
+impl Invariant {
+    pub fn address(&self) -> Option<u64> {
+        match self {
+            Invariant::DataVariableType { address, .. }
+            | Invariant::Function { address }
+            | Invariant::Symbol { address, .. } => Some(*address),
+            Invariant::Custom(_) => None,
+        }
+    }
+
+    /// Check the invariant now, returning a description of the problem if it does not hold.
+    pub fn check(&self, view: &BinaryView) -> Result<(), String> {
+        match self {
+            Invariant::DataVariableType { address, ty } => {
+                match view.data_variable_at_address(*address) {
+                    Some(var) if var.ty.contents == *ty => Ok(()),
+                    Some(var) => Err(format!(
+                        "data variable at {:#x} is `{}`, expected `{}`",
+                        address, var.ty.contents, ty
+                    )),
+                    None => Err(format!("no data variable at {:#x}", address)),
+                }
+            }
+            Invariant::Function { address } => match view.functions_at(*address).is_empty() {
+                false => Ok(()),
+                true => Err(format!("no function at {:#x}", address)),
+            },
+            Invariant::Symbol { address, name } => match view.symbol_by_address(*address) {
+                Some(symbol) if symbol.raw_name().as_str() == name => Ok(()),
+                Some(symbol) => Err(format!(
+                    "symbol at {:#x} is `{}`, expected `{}`",
+                    address,
+                    symbol.raw_name(),
+                    name
+                )),
+                None => Err(format!("no symbol at {:#x}, expected `{}`", address, name)),
+            },
+            Invariant::Custom(check) => check(view),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    pub id: WatchpointId,
+    pub name: String,
+    pub address: Option<u64>,
+    pub description: String,
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "watchpoint `{}` violated: {}",
+            self.name, self.description
+        )
+    }
+}
+
+pub trait ViolationHandler: 'static + Send + Sync {
+    fn on_violation(&self, view: &BinaryView, violation: &Violation);
+}
+
+impl<F> ViolationHandler for F
+where
+    F: Fn(&BinaryView, &Violation) + 'static + Send + Sync,
+{
+    fn on_violation(&self, view: &BinaryView, violation: &Violation) {
+        self(view, violation)
+    }
+}
+
+struct Watchpoint {
+    id: WatchpointId,
+    name: String,
+    invariant: Invariant,
+}
+
+struct WatchpointsInner {
+    view: Ref<BinaryView>,
+    handler: Box<dyn ViolationHandler>,
+    watchpoints: Mutex<Vec<Watchpoint>>,
+    // Watchpoints currently violated, only the transition into violation is reported
+    violated: Mutex<HashSet<WatchpointId>>,
+    next_id: Mutex<usize>,
+    event: Mutex<Option<AnalysisCompletionEvent>>,
+}
+
+impl WatchpointsInner {
+    fn check(&self) -> Vec<Violation> {
+        let watchpoints = self.watchpoints.lock().unwrap();
+        let mut violated = self.violated.lock().unwrap();
+        let mut new_violations = Vec::new();
+        for watchpoint in watchpoints.iter() {
+            match watchpoint.invariant.check(&self.view) {
+                Ok(()) => {
+                    violated.remove(&watchpoint.id);
+                }
+                Err(description) => {
+                    if violated.insert(watchpoint.id) {
+                        new_violations.push(Violation {
+                            id: watchpoint.id,
+                            name: watchpoint.name.clone(),
+                            address: watchpoint.invariant.address(),
+                            description,
+                        });
+                    }
+                }
+            }
+        }
+        new_violations
+    }
+
+    fn report(&self, violations: &[Violation]) {
+        for violation in violations {
+            self.handler.on_violation(&self.view, violation);
+        }
+    }
+
+    fn arm(self: &Arc<Self>) {
+        let inner = Arc::downgrade(self);
+        let event = AnalysisCompletionEvent::new(&self.view, move |_| {
+            if let Some(inner) = inner.upgrade() {
+                let violations = inner.check();
+                inner.report(&violations);
+                inner.arm();
+            }
+        });
+        *self.event.lock().unwrap() = Some(event);
+    }
+}
+
+/// A set of [`Invariant`]s checked against a view whenever its analysis completes, with new
+/// violations passed to a [`ViolationHandler`].
+///
+/// A violation is only reported when the invariant stops holding, not on every check while it
+/// remains violated. The view is kept alive until the watchpoints are dropped.
+///
+/// Importers can watch the types and symbols they apply, to be told when re-analysis clobbers
+/// them:
+///
+/// ```no_run
+/// use binaryninja::binary_view::BinaryView;
+/// use binaryninja::types::Type;
+/// use binaryninja::watchpoint::{Invariant, Violation, Watchpoints};
+///
+/// fn watch_header(view: &BinaryView, address: u64, header_type: &Type) -> Watchpoints {
+///     let watchpoints = Watchpoints::new(view, |_view: &BinaryView, violation: &Violation| {
+///         log::warn!("{}", violation);
+///     });
+///     watchpoints.add(
+///         "image header",
+///         Invariant::DataVariableType {
+///             address,
+///             ty: header_type.to_owned(),
+///         },
+///     );
+///     watchpoints
+/// }
+/// ```
+pub struct Watchpoints {
+    inner: Arc<WatchpointsInner>,
+}
+
+impl Watchpoints {
+    pub fn new(view: &BinaryView, handler: impl ViolationHandler) -> Self {
+        let inner = Arc::new(WatchpointsInner {
+            view: view.to_owned(),
+            handler: Box::new(handler),
+            watchpoints: Mutex::new(Vec::new()),
+            violated: Mutex::new(HashSet::new()),
+            next_id: Mutex::new(0),
+            event: Mutex::new(None),
+        });
+        inner.arm();
+        Self { inner }
+    }
+
+    /// Start watching the invariant. It is expected to hold when added, if it does not the
+    /// violation is reported by the next check.
+    pub fn add(&self, name: impl Into<String>, invariant: Invariant) -> WatchpointId {
+        let id = {
+            let mut next_id = self.inner.next_id.lock().unwrap();
+            *next_id += 1;
+            WatchpointId(*next_id)
+        };
+        self.inner.watchpoints.lock().unwrap().push(Watchpoint {
+            id,
+            name: name.into(),
+            invariant,
+        });
+        id
+    }
+
+    pub fn remove(&self, id: WatchpointId) -> bool {
+        self.inner.violated.lock().unwrap().remove(&id);
+        let mut watchpoints = self.inner.watchpoints.lock().unwrap();
+        let count = watchpoints.len();
+        watchpoints.retain(|watchpoint| watchpoint.id != id);
+        watchpoints.len() != count
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.watchpoints.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Check every invariant now, reporting and returning the new violations.
+    pub fn check(&self) -> Vec<Violation> {
+        let violations = self.inner.check();
+        self.inner.report(&violations);
+        violations
+    }
+
+    /// The watchpoints that are currently violated.
+    pub fn violated(&self) -> Vec<WatchpointId> {
+        let mut violated: Vec<_> = self
+            .inner
+            .violated
+            .lock()
+            .unwrap()
+            .iter()
+            .copied()
+            .collect();
+        violated.sort();
+        violated
+    }
+}
+
+impl Drop for Watchpoints {
+    fn drop(&mut self) {
+        self.inner.event.lock().unwrap().take();
+    }
+}
@@ -0,0 +1,312 @@
+// Copyright 2021-2024 Vector 35 Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+// http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! A queue of triage work items kept in the database of a view.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+use binaryninjacore_sys::*;
+
+use crate::binary_view::{BinaryView, BinaryViewExt};
+use crate::metadata::Metadata;
+use crate::rc::{Array, Ref};
+use crate::tags::{Tag, TagType};
+use crate::Error;
+
+/// The view metadata key the items are kept under.
+const METADATA_KEY: &str = "work_queue.items";
+/// The name of the tag type marking work items.
+pub const TAG_TYPE_NAME: &str = "Work Item";
+const TAG_TYPE_ICON: &str = "📋";
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum WorkItemState {
+    /// Waiting for someone to claim it.
+    Open,
+    /// Being worked on by its assignee.
+    Claimed,
+    Resolved,
+}
+
+impl WorkItemState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WorkItemState::Open => "open",
+            WorkItemState::Claimed => "claimed",
+            WorkItemState::Resolved => "resolved",
+        }
+    }
+}
+
+impl fmt::Display for WorkItemState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for WorkItemState {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "open" => Ok(WorkItemState::Open),
+            "claimed" => Ok(WorkItemState::Claimed),
+            "resolved" => Ok(WorkItemState::Resolved),
+            _ => Err(Error::Parse(format!("work item state `{}`", s))),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WorkItem {
+    /// The id of the tag marking the item.
+    pub id: String,
+    pub address: u64,
+    pub state: WorkItemState,
+    /// Who claimed the item, kept once it is resolved.
+    pub assignee: Option<String>,
+    pub note: String,
+}
+
+impl WorkItem {
+    /// The text of the tag marking the item.
+    fn summary(&self) -> String {
+        match (&self.state, &self.assignee) {
+            (WorkItemState::Claimed, Some(assignee)) => {
+                format!("[claimed by {}] {}", assignee, self.note)
+            }
+            (state, _) => format!("[{}] {}", state, self.note),
+        }
+    }
+
+    fn to_metadata(&self) -> Ref<Metadata> {
+        let mut fields: HashMap<&str, Ref<Metadata>> = HashMap::new();
+        fields.insert("address", self.address.into());
+        fields.insert("state", self.state.as_str().into());
+        fields.insert("note", self.note.as_str().into());
+        if let Some(assignee) = &self.assignee {
+            fields.insert("assignee", assignee.as_str().into());
+        }
+        fields.into()
+    }
+
+    fn from_metadata(id: String, metadata: &Metadata) -> Option<Self> {
+        let fields = HashMap::<String, Ref<Metadata>>::try_from(metadata).ok()?;
+        let field = |name: &str| String::try_from(fields.get(name)?.as_ref()).ok();
+        Some(Self {
+            id,
+            address: u64::try_from(fields.get("address")?.as_ref()).ok()?,
+            state: field("state")?.parse().ok()?,
+            assignee: field("assignee"),
+            note: field("note").unwrap_or_default(),
+        })
+    }
+}
+
+/// The triage work items of a view.
+///
+/// Every [`WorkItem`] is a user tag at its address, so it shows up in the tag list of the UI, with
+/// its state, assignee and note saved as view metadata. Both are stored in the database, so a
+/// team sharing the database (for example through collaboration) shares the queue:
+///
+/// ```no_run
+/// use binaryninja::work_queue::{WorkItemState, WorkQueue};
+///
+/// let view = binaryninja::load("/bin/cat").unwrap();
+/// let queue = WorkQueue::new(&view);
+/// let item = queue.create(0x1000, "check the bounds of this copy");
+/// queue.claim(&item.id, "alice").unwrap();
+/// for item in queue.items_in_state(WorkItemState::Claimed) {
+///     println!("{:#x} {:?}: {}", item.address, item.assignee, item.note);
+/// }
+/// queue.resolve(&item.id).unwrap();
+/// ```
+pub struct WorkQueue {
+    view: Ref<BinaryView>,
+    tag_type: Ref<TagType>,
+}
+
+impl WorkQueue {
+    /// The queue of `view`, creating the [`TAG_TYPE_NAME`] tag type if needed.
+    pub fn new(view: &BinaryView) -> Self {
+        let tag_type = view
+            .tag_type_by_name(TAG_TYPE_NAME)
+            .unwrap_or_else(|| view.create_tag_type(TAG_TYPE_NAME, TAG_TYPE_ICON));
+        Self {
+            view: view.to_owned(),
+            tag_type,
+        }
+    }
+
+    fn entries(&self) -> HashMap<String, Ref<Metadata>> {
+        self.view
+            .get_metadata::<HashMap<String, Ref<Metadata>>, _>(METADATA_KEY)
+            .and_then(Result::ok)
+            .unwrap_or_default()
+    }
+
+    fn store(&self, item: &WorkItem) {
+        let mut entries = self.entries();
+        entries.insert(item.id.clone(), item.to_metadata());
+        self.view.store_metadata(METADATA_KEY, entries, false);
+        if let Some(tag) = self.tag(item) {
+            tag.set_data(item.summary());
+        }
+    }
+
+    /// The tag marking `item`, `None` if it was removed from its address.
+    fn tag(&self, item: &WorkItem) -> Option<Ref<Tag>> {
+        let tags: Array<Tag> = unsafe {
+            let mut count = 0;
+            let tags = BNGetUserDataTagsOfType(
+                self.view.handle,
+                item.address,
+                self.tag_type.handle,
+                &mut count,
+            );
+            Array::new(tags, count, ())
+        };
+        tags.iter()
+            .find(|tag| tag.id().as_str() == item.id)
+            .map(|tag| tag.to_owned())
+    }
+
+    /// Queue a new open item at `address`.
+    pub fn create(&self, address: u64, note: &str) -> WorkItem {
+        let tag = Tag::new(&self.tag_type, "");
+        unsafe {
+            BNAddTag(self.view.handle, tag.handle, true);
+            BNAddUserDataTag(self.view.handle, address, tag.handle);
+        }
+        let item = WorkItem {
+            id: tag.id().to_string(),
+            address,
+            state: WorkItemState::Open,
+            assignee: None,
+            note: note.to_string(),
+        };
+        self.store(&item);
+        item
+    }
+
+    /// The item `id`, `None` if it doesn't exist or its tag was removed.
+    pub fn item(&self, id: &str) -> Option<WorkItem> {
+        let item = WorkItem::from_metadata(id.to_string(), self.entries().get(id)?)?;
+        self.tag(&item).map(|_| item)
+    }
+
+    /// Every item, ordered by address.
+    pub fn items(&self) -> Vec<WorkItem> {
+        let mut items: Vec<WorkItem> = self
+            .entries()
+            .into_iter()
+            .filter_map(|(id, metadata)| WorkItem::from_metadata(id, &metadata))
+            .filter(|item| self.tag(item).is_some())
+            .collect();
+        items.sort_by(|a, b| a.address.cmp(&b.address).then_with(|| a.id.cmp(&b.id)));
+        items
+    }
+
+    /// The items in `state`, ordered by address.
+    pub fn items_in_state(&self, state: WorkItemState) -> Vec<WorkItem> {
+        self.items()
+            .into_iter()
+            .filter(|item| item.state == state)
+            .collect()
+    }
+
+    fn update(
+        &self,
+        id: &str,
+        f: impl FnOnce(&mut WorkItem) -> Result<(), Error>,
+    ) -> Result<WorkItem, Error> {
+        let mut item = self
+            .item(id)
+            .ok_or_else(|| Error::NotFound(format!("work item `{}`", id)))?;
+        f(&mut item)?;
+        self.store(&item);
+        Ok(item)
+    }
+
+    /// Assign the item `id` to `assignee`, failing if someone else claimed it or it is resolved.
+    pub fn claim(&self, id: &str, assignee: &str) -> Result<WorkItem, Error> {
+        self.update(id, |item| match (&item.state, &item.assignee) {
+            (WorkItemState::Resolved, _) => Err(Error::InvalidArgument(format!(
+                "work item `{}` is resolved",
+                item.id
+            ))),
+            (WorkItemState::Claimed, Some(other)) if other != assignee => Err(
+                Error::InvalidArgument(format!("work item `{}` is claimed by {}", item.id, other)),
+            ),
+            _ => {
+                item.state = WorkItemState::Claimed;
+                item.assignee = Some(assignee.to_string());
+                Ok(())
+            }
+        })
+    }
+
+    /// Return the claimed item `id` to the open items.
+    pub fn release(&self, id: &str) -> Result<WorkItem, Error> {
+        self.update(id, |item| {
+            if item.state == WorkItemState::Claimed {
+                item.state = WorkItemState::Open;
+                item.assignee = None;
+            }
+            Ok(())
+        })
+    }
+
+    pub fn resolve(&self, id: &str) -> Result<WorkItem, Error> {
+        self.update(id, |item| {
+            item.state = WorkItemState::Resolved;
+            Ok(())
+        })
+    }
+
+    /// Open the item `id` again, unassigned.
+    pub fn reopen(&self, id: &str) -> Result<WorkItem, Error> {
+        self.update(id, |item| {
+            item.state = WorkItemState::Open;
+            item.assignee = None;
+            Ok(())
+        })
+    }
+
+    pub fn set_note(&self, id: &str, note: &str) -> Result<WorkItem, Error> {
+        self.update(id, |item| {
+            item.note = note.to_string();
+            Ok(())
+        })
+    }
+
+    /// Remove the item `id` and its tag, returning whether it existed.
+    pub fn remove(&self, id: &str) -> bool {
+        let mut entries = self.entries();
+        let Some(metadata) = entries.remove(id) else {
+            return false;
+        };
+        let item = WorkItem::from_metadata(id.to_string(), &metadata);
+        if let Some(item) = item {
+            if let Some(tag) = self.tag(&item) {
+                self.view.remove_user_data_tag(item.address, &tag);
+                unsafe { BNRemoveTag(self.view.handle, tag.handle, true) };
+            }
+        }
+        self.view.store_metadata(METADATA_KEY, entries, false);
+        true
+    }
+}
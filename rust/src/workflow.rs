@@ -1,7 +1,10 @@
 use binaryninjacore_sys::*;
-use std::ffi::{c_char, c_void};
+use std::collections::BTreeMap;
+use std::ffi::{c_char, c_void, CStr, CString};
 use std::ptr::NonNull;
+use std::sync::{Arc, Mutex, OnceLock};
 
+use crate::analysis_budget::{AnalysisBudget, BudgetToken};
 use crate::architecture::CoreArchitecture;
 use crate::basic_block::BasicBlock;
 use crate::binary_view::BinaryView;
@@ -12,7 +15,7 @@ use crate::low_level_il::function::{LowLevelILFunction, Mutable, NonSSA, NonSSAV
 use crate::low_level_il::MutableLiftedILFunction;
 use crate::medium_level_il::MediumLevelILFunction;
 use crate::rc::{Array, CoreArrayProvider, CoreArrayProviderInner, Guard, Ref, RefCountable};
-use crate::string::{BnStrCompatible, BnString};
+use crate::string::{json_string, BnStrCompatible, BnString};
 
 #[repr(transparent)]
 /// The AnalysisContext struct is used to represent the current state of
@@ -157,6 +160,180 @@ unsafe impl RefCountable for AnalysisContext {
     }
 }
 
+/// The role of an [Activity] within a [Workflow].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ActivityRole {
+    /// Performs a specific task.
+    #[default]
+    Action,
+    /// Runs the subactivities its eligibility handler selects.
+    Selector,
+    /// Processes its subactivities on a new thread, in a new task context.
+    Subflow,
+    /// Processes the rest of the workflow on a new thread.
+    Task,
+}
+
+impl ActivityRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivityRole::Action => "action",
+            ActivityRole::Selector => "selector",
+            ActivityRole::Subflow => "subflow",
+            ActivityRole::Task => "task",
+        }
+    }
+}
+
+/// Typed configuration of an [Activity], passed anywhere the JSON configuration is accepted.
+///
+/// ```
+/// # use binaryninja::workflow::ActivityConfig;
+/// let config = ActivityConfig::new("extension.myPass")
+///     .title("My Pass")
+///     .description("Does things to functions")
+///     .auto(true);
+/// assert_eq!(
+///     config.to_json(),
+///     r#"{"name":"extension.myPass","role":"action","title":"My Pass","description":"Does things to functions","eligibility":{"auto":{"default":true}}}"#
+/// );
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActivityConfig {
+    pub name: String,
+    pub role: ActivityRole,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub aliases: Vec<String>,
+    /// Generate a setting controlling the activity, enabled by default when `Some(true)`.
+    pub auto: Option<bool>,
+    /// Run once across all sessions of a file.
+    pub run_once: bool,
+    /// Run once per session.
+    pub run_once_per_session: bool,
+    /// Whether a [`ActivityRole::Subflow`] is re-evaluated after its tasks finish.
+    pub continuation: bool,
+}
+
+impl ActivityConfig {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn role(mut self, role: ActivityRole) -> Self {
+        self.role = role;
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    pub fn alias(mut self, alias: impl Into<String>) -> Self {
+        self.aliases.push(alias.into());
+        self
+    }
+
+    pub fn auto(mut self, default: bool) -> Self {
+        self.auto = Some(default);
+        self
+    }
+
+    pub fn run_once(mut self, run_once: bool) -> Self {
+        self.run_once = run_once;
+        self
+    }
+
+    pub fn run_once_per_session(mut self, run_once_per_session: bool) -> Self {
+        self.run_once_per_session = run_once_per_session;
+        self
+    }
+
+    pub fn continuation(mut self, continuation: bool) -> Self {
+        self.continuation = continuation;
+        self
+    }
+
+    /// The JSON configuration understood by the core.
+    pub fn to_json(&self) -> String {
+        let mut json = format!(
+            "{{\"name\":{},\"role\":{}",
+            json_string(&self.name),
+            json_string(self.role.as_str())
+        );
+        if let Some(title) = &self.title {
+            json += &format!(",\"title\":{}", json_string(title));
+        }
+        if let Some(description) = &self.description {
+            json += &format!(",\"description\":{}", json_string(description));
+        }
+        if !self.aliases.is_empty() {
+            let aliases: Vec<_> = self.aliases.iter().map(|a| json_string(a)).collect();
+            json += &format!(",\"aliases\":[{}]", aliases.join(","));
+        }
+
+        let mut eligibility = Vec::new();
+        if let Some(default) = self.auto {
+            eligibility.push(format!("\"auto\":{{\"default\":{}}}", default));
+        }
+        if self.run_once {
+            eligibility.push("\"runOnce\":true".to_string());
+        }
+        if self.run_once_per_session {
+            eligibility.push("\"runOncePerSession\":true".to_string());
+        }
+        if self.continuation {
+            eligibility.push("\"continuation\":true".to_string());
+        }
+        if !eligibility.is_empty() {
+            json += &format!(",\"eligibility\":{{{}}}", eligibility.join(","));
+        }
+        json.push('}');
+        json
+    }
+}
+
+unsafe impl BnStrCompatible for ActivityConfig {
+    type Result = Vec<u8>;
+
+    fn into_bytes_with_nul(self) -> Self::Result {
+        self.to_json().into_bytes_with_nul()
+    }
+}
+
+unsafe impl BnStrCompatible for &ActivityConfig {
+    type Result = Vec<u8>;
+
+    fn into_bytes_with_nul(self) -> Self::Result {
+        self.to_json().into_bytes_with_nul()
+    }
+}
+
+/// The callbacks of an activity created with an action, until the activity is released.
+struct ActivityCallbacks {
+    context: *mut c_void,
+    free: unsafe fn(*mut c_void),
+    /// References to the activity held through [`Ref`]s.
+    refs: usize,
+    /// Registered with a workflow, which keeps calling the callbacks for as long as it exists.
+    registered: bool,
+}
+
+// SAFETY: The callbacks are `Send + Sync`, see `Activity::with_callbacks`
+unsafe impl Send for ActivityCallbacks {}
+
+// By activity handle
+static ACTIVITY_CALLBACKS: Mutex<BTreeMap<usize, ActivityCallbacks>> = Mutex::new(BTreeMap::new());
+
 // TODO: This needs to be made into a trait similar to that of `Command`.
 #[repr(transparent)]
 pub struct Activity {
@@ -186,29 +363,150 @@ impl Activity {
         unsafe { Activity::from_raw(NonNull::new(result).unwrap()) }
     }
 
-    pub fn new_with_action<S, F>(config: S, mut action: F) -> Self
+    /// Create an activity with `callbacks` as the context of its callbacks, freed once the last
+    /// reference to the activity is dropped if it was never registered with a workflow.
+    fn with_callbacks<T: 'static + Send + Sync>(
+        callbacks: T,
+        create: impl FnOnce(*mut c_void) -> *mut BNActivity,
+    ) -> Ref<Self> {
+        unsafe fn free<T>(context: *mut c_void) {
+            drop(Box::from_raw(context as *mut T));
+        }
+        let context = Box::into_raw(Box::new(callbacks)) as *mut c_void;
+        let handle = NonNull::new(create(context)).unwrap();
+        ACTIVITY_CALLBACKS.lock().unwrap().insert(
+            handle.as_ptr() as usize,
+            ActivityCallbacks {
+                context,
+                free: free::<T>,
+                refs: 1,
+                registered: false,
+            },
+        );
+        unsafe { Activity::ref_from_raw(handle) }
+    }
+
+    /// Create an activity running `action`, which may be called for several analysis contexts at
+    /// once.
+    ///
+    /// The action is freed with the activity, unless the activity was registered with a workflow,
+    /// which keeps calling it for as long as the workflow exists.
+    pub fn new_with_action<S, F>(config: S, action: F) -> Ref<Self>
     where
         S: BnStrCompatible,
-        F: FnMut(&AnalysisContext),
+        F: 'static + Fn(&AnalysisContext) + Send + Sync,
     {
-        unsafe extern "C" fn cb_action<F: FnMut(&AnalysisContext)>(
+        unsafe extern "C" fn cb_action<F: Fn(&AnalysisContext)>(
             ctxt: *mut c_void,
             analysis: *mut BNAnalysisContext,
         ) {
-            let ctxt = &mut *(ctxt as *mut F);
+            let ctxt = &*(ctxt as *const F);
             if let Some(analysis) = NonNull::new(analysis) {
                 ctxt(&AnalysisContext::from_raw(analysis))
             }
         }
         let config = config.into_bytes_with_nul();
-        let result = unsafe {
+        Self::with_callbacks(action, |context| unsafe {
             BNCreateActivity(
                 config.as_ref().as_ptr() as *const c_char,
-                &mut action as *mut F as *mut c_void,
+                context,
                 Some(cb_action::<F>),
             )
-        };
-        unsafe { Activity::from_raw(NonNull::new(result).unwrap()) }
+        })
+    }
+
+    /// Like [`Activity::new_with_action`], with `eligible` deciding whether the activity runs
+    /// for a given analysis context, on top of the eligibility in `config`.
+    pub fn new_with_eligibility<S, F, E>(config: S, action: F, eligible: E) -> Ref<Self>
+    where
+        S: BnStrCompatible,
+        F: 'static + Fn(&AnalysisContext) + Send + Sync,
+        E: 'static + Fn(&Activity, &AnalysisContext) -> bool + Send + Sync,
+    {
+        unsafe extern "C" fn cb_action<F, E>(ctxt: *mut c_void, analysis: *mut BNAnalysisContext)
+        where
+            F: Fn(&AnalysisContext),
+        {
+            let (action, _) = &*(ctxt as *const (F, E));
+            if let Some(analysis) = NonNull::new(analysis) {
+                action(&AnalysisContext::from_raw(analysis))
+            }
+        }
+        unsafe extern "C" fn cb_eligible<F, E>(
+            ctxt: *mut c_void,
+            activity: *mut BNActivity,
+            analysis: *mut BNAnalysisContext,
+        ) -> bool
+        where
+            E: Fn(&Activity, &AnalysisContext) -> bool,
+        {
+            let (_, eligible) = &*(ctxt as *const (F, E));
+            match (NonNull::new(activity), NonNull::new(analysis)) {
+                (Some(activity), Some(analysis)) => eligible(
+                    &Activity::from_raw(activity),
+                    &AnalysisContext::from_raw(analysis),
+                ),
+                _ => false,
+            }
+        }
+        let config = config.into_bytes_with_nul();
+        Self::with_callbacks((action, eligible), |context| unsafe {
+            BNCreateActivityWithEligibility(
+                config.as_ref().as_ptr() as *const c_char,
+                context,
+                Some(cb_action::<F, E>),
+                Some(cb_eligible::<F, E>),
+            )
+        })
+    }
+
+    /// Like [`Activity::new_with_action`], with each run of `action` watched under `budget`.
+    ///
+    /// Runs are named after the activity and the function they are for, e.g.
+    /// `extension.myPass @ 0x401000`, in the warnings logged when they go over budget.
+    ///
+    /// ```no_run
+    /// # use binaryninja::analysis_budget::AnalysisBudget;
+    /// # use binaryninja::workflow::{Activity, Workflow};
+    /// # use std::time::Duration;
+    /// let budget = AnalysisBudget::new()
+    ///     .with_time_limit(Duration::from_millis(500))
+    ///     .with_memory_limit(256 * 1024 * 1024);
+    /// let activity = Activity::new_with_budget(
+    ///     r#"{"name": "extension.myPass", "title": "My Pass", "description": "", "eligibility": {}}"#,
+    ///     budget,
+    ///     |context, token| {
+    ///         for block in context.function().basic_blocks().iter() {
+    ///             if token.check().is_err() {
+    ///                 return;
+    ///             }
+    ///             // ... expensive work on `block`
+    ///         }
+    ///     },
+    /// );
+    /// let workflow = Workflow::instance("core.function.baseAnalysis").clone("MyWorkflow");
+    /// workflow.register_activity(&activity).unwrap();
+    /// workflow.insert("core.function.analyzeTailCalls", ["extension.myPass"]);
+    /// workflow.register().unwrap();
+    /// ```
+    pub fn new_with_budget<S, F>(config: S, budget: AnalysisBudget, action: F) -> Ref<Self>
+    where
+        S: BnStrCompatible,
+        F: 'static + Fn(&AnalysisContext, &BudgetToken) + Send + Sync,
+    {
+        let name = Arc::new(OnceLock::<String>::new());
+        let action_name = name.clone();
+        let activity = Self::new_with_action(config, move |context: &AnalysisContext| {
+            let run_name = format!(
+                "{} @ {:#x}",
+                action_name.get().map_or("activity", String::as_str),
+                context.function().start()
+            );
+            let guard = budget.start(run_name);
+            action(context, guard.token());
+        });
+        let _ = name.set(activity.name().to_string());
+        activity
     }
 
     pub fn name(&self) -> BnString {
@@ -228,6 +526,10 @@ impl ToOwned for Activity {
 
 unsafe impl RefCountable for Activity {
     unsafe fn inc_ref(handle: &Self) -> Ref<Self> {
+        let mut callbacks = ACTIVITY_CALLBACKS.lock().unwrap();
+        if let Some(callbacks) = callbacks.get_mut(&(handle.handle.as_ptr() as usize)) {
+            callbacks.refs += 1;
+        }
         Ref::new(Self {
             handle: NonNull::new(BNNewActivityReference(handle.handle.as_ptr()))
                 .expect("valid handle"),
@@ -235,7 +537,19 @@ unsafe impl RefCountable for Activity {
     }
 
     unsafe fn dec_ref(handle: &Self) {
+        let key = handle.handle.as_ptr() as usize;
+        let mut callbacks = ACTIVITY_CALLBACKS.lock().unwrap();
         BNFreeActivity(handle.handle.as_ptr());
+        let Some(entry) = callbacks.get_mut(&key) else {
+            return;
+        };
+        entry.refs -= 1;
+        if entry.refs == 0 {
+            let entry = callbacks.remove(&key).unwrap();
+            if !entry.registered {
+                (entry.free)(entry.context);
+            }
+        }
     }
 }
 
@@ -375,6 +689,10 @@ impl Workflow {
             )
         };
         let activity_ptr = NonNull::new(result).ok_or(())?;
+        let key = activity.handle.as_ptr() as usize;
+        if let Some(callbacks) = ACTIVITY_CALLBACKS.lock().unwrap().get_mut(&key) {
+            callbacks.registered = true;
+        }
         unsafe { Ok(Activity::from_raw(activity_ptr)) }
     }
 
@@ -529,6 +847,49 @@ impl Workflow {
         }
     }
 
+    /// Insert the list of `activities` after the specified `activity` and at the same level.
+    ///
+    /// Returns `false` if `activity` is not part of this [Workflow].
+    ///
+    /// * `activity` - the Activity node for which to insert `activities` after
+    /// * `activities` - the list of Activities to insert
+    pub fn insert_after<A, I>(&self, activity: A, activities: I) -> bool
+    where
+        A: BnStrCompatible,
+        I: IntoIterator,
+        I::Item: BnStrCompatible,
+    {
+        let activity = activity.into_bytes_with_nul();
+        let activity = CStr::from_bytes_with_nul(activity.as_ref())
+            .expect("activity name is nul terminated")
+            .to_string_lossy();
+        let activities: Vec<_> = activities
+            .into_iter()
+            .map(|a| {
+                CStr::from_bytes_with_nul(a.into_bytes_with_nul().as_ref())
+                    .expect("activity name is nul terminated")
+                    .to_owned()
+            })
+            .collect();
+
+        for parent in &self.subactivities("", false) {
+            // The listing of an activity starts with the activity itself
+            let children = self.subactivities(parent, true);
+            let siblings: Vec<&str> = children.iter().filter(|c| *c != parent).collect();
+            let Some(position) = siblings.iter().position(|s| *s == activity) else {
+                continue;
+            };
+            return match siblings.get(position + 1) {
+                Some(next) => self.insert(*next, activities),
+                None => {
+                    let siblings = siblings.iter().map(|s| CString::new(*s).unwrap());
+                    self.assign_subactivities(parent, siblings.chain(activities))
+                }
+            };
+        }
+        false
+    }
+
     /// Remove the specified `activity`
     pub fn remove<A: BnStrCompatible>(&self, activity: A) -> bool {
         unsafe {
@@ -557,6 +918,14 @@ impl Workflow {
         }
     }
 
+    /// Retrieve the names of the settings controlling the eligibility of the activities.
+    pub fn eligibility_settings(&self) -> Array<BnString> {
+        let mut count = 0;
+        let result = unsafe { BNWorkflowGetEligibilitySettings(self.handle.as_ptr(), &mut count) };
+        assert!(!result.is_null());
+        unsafe { Array::new(result as *mut *mut c_char, count, ()) }
+    }
+
     /// Generate a FlowGraph object for the current [Workflow] and optionally show it in the UI.
     ///
     /// * `activity` - if specified, generate the Flowgraph using `activity` as the root
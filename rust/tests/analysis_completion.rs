@@ -0,0 +1,75 @@
+use binaryninja::binary_view::{BinaryView, BinaryViewExt};
+use binaryninja::headless::Session;
+use rstest::*;
+use std::future::Future;
+use std::path::PathBuf;
+use std::pin::pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::task::{Context, Poll, Wake};
+use std::thread::{self, Thread};
+use std::time::Duration;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Poll `future` to completion on the current thread, the simplest possible executor.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Arc::new(ThreadWaker(thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[rstest]
+fn test_update_analysis_async(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    block_on(view.update_analysis_async());
+    assert!(!view.functions().is_empty());
+}
+
+#[rstest]
+fn test_on_analysis_complete(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let (sender, receiver) = mpsc::channel();
+    let event = view.on_analysis_complete(move |view: &BinaryView| {
+        sender.send(view.functions().len()).unwrap();
+    });
+    view.update_analysis();
+    let count = receiver
+        .recv_timeout(Duration::from_secs(60))
+        .expect("Analysis did not complete");
+    assert!(count > 0);
+    assert!(event.is_finished());
+
+    // A cancelled callback never runs
+    let calls = Arc::new(AtomicUsize::new(0));
+    let event = view.on_analysis_complete({
+        let calls = calls.clone();
+        move |_: &BinaryView| {
+            calls.fetch_add(1, Ordering::SeqCst);
+        }
+    });
+    event.cancel();
+    assert!(event.is_finished());
+    view.update_analysis_and_wait();
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+}
@@ -0,0 +1,78 @@
+use binaryninja::architecture::{
+    ArchitectureExt, CoreArchitecture, FlagRole, ImplicitRegisterExtend, Intrinsic,
+};
+use binaryninja::headless::Session;
+use binaryninja::types::Type;
+use rstest::*;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_register_descriptions(_session: &Session) {
+    let arch = CoreArchitecture::by_name("x86_64").expect("x86_64 exists");
+    let registers = arch.register_descriptions();
+    let by_name = |name: &str| {
+        registers
+            .iter()
+            .find(|reg| reg.name == name)
+            .unwrap_or_else(|| panic!("No register {name}"))
+    };
+
+    let rax = by_name("rax");
+    assert_eq!(rax.size, 8);
+    assert!(rax.is_full_width());
+    let eax = by_name("eax");
+    assert_eq!(eax.size, 4);
+    assert_eq!(eax.full_width, rax.id);
+    assert_eq!(
+        eax.implicit_extend,
+        ImplicitRegisterExtend::ZeroExtendToFullWidth
+    );
+    let ah = by_name("ah");
+    assert_eq!((ah.size, ah.offset), (1, 1));
+    assert!(!ah.is_full_width());
+
+    let flags = arch.flag_descriptions();
+    let zf = flags
+        .iter()
+        .find(|flag| flag.name == "z")
+        .expect("No zero flag");
+    assert_eq!(zf.role, FlagRole::ZeroFlagRole);
+
+    let stacks = arch.register_stack_descriptions();
+    let x87 = stacks.first().expect("No register stacks");
+    assert_eq!(x87.storage_registers.len(), 8);
+    assert_eq!(x87.top_relative_registers.len(), 8);
+    assert!(registers.iter().any(|reg| reg.id == x87.stack_top));
+}
+
+#[rstest]
+fn test_intrinsic_descriptions(_session: &Session) {
+    let arch = CoreArchitecture::by_name("aarch64").expect("aarch64 exists");
+    let system_registers = arch.system_register_descriptions();
+    assert!(!system_registers.is_empty());
+    assert!(system_registers.iter().all(|reg| reg.system));
+
+    let intrinsics = arch.intrinsic_descriptions();
+    let intrinsic = intrinsics.first().expect("No intrinsics");
+    let found = arch
+        .intrinsic_by_name(&intrinsic.name)
+        .expect("Intrinsic not found by name");
+    assert_eq!(found.id(), intrinsic.id);
+
+    let derived = arch
+        .intrinsic_prototype(&intrinsic.name)
+        .expect("No prototype");
+    assert_eq!(derived, intrinsic.prototype());
+    let parameters = derived.parameters().expect("Prototype is not a function");
+    assert_eq!(parameters.len(), intrinsic.inputs.len());
+
+    let prototype = Type::function(&Type::int(8, false), vec![], false);
+    arch.register_intrinsic_prototype(&intrinsic.name, &prototype);
+    assert_eq!(arch.intrinsic_prototype(&intrinsic.name), Some(prototype));
+    assert_eq!(arch.intrinsic_prototype("not_an_intrinsic"), None);
+}
@@ -0,0 +1,30 @@
+use binaryninja::asm_search::{DisassembledInstruction, InstructionPattern};
+use binaryninja::headless::Session;
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_find_calls(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let pattern = InstructionPattern::parse("call ?").unwrap();
+    let sites = pattern.find_in_view(&view);
+    assert!(!sites.is_empty(), "No calls found");
+    for site in &sites {
+        assert_eq!(site.captures.len(), 1);
+        let instr = DisassembledInstruction::disassemble(&site.arch, &view, site.address)
+            .expect("Failed to disassemble match");
+        assert_eq!(instr.mnemonic, "call");
+        assert_eq!(instr.length, site.length);
+        assert_eq!(instr.operands[0].text, site.captures[0].text);
+    }
+
+    let pattern = InstructionPattern::parse("notaninstruction ?").unwrap();
+    assert!(pattern.find_in_view(&view).is_empty());
+}
@@ -1,6 +1,8 @@
 use binaryninja::background_task::*;
 use binaryninja::headless::Session;
 use rstest::*;
+use std::sync::mpsc;
+use std::time::Duration;
 
 #[fixture]
 #[once]
@@ -47,3 +49,41 @@ fn test_background_task_progress(_session: &Session) {
     assert_eq!(second_progress, "new progress");
     task.finish();
 }
+
+#[rstest]
+fn test_spawn_analysis_job(_session: &Session) {
+    let (sender, receiver) = mpsc::channel();
+    let task = spawn_analysis_job(
+        "test job",
+        false,
+        |task| {
+            task.set_progress_text("test job working");
+            42
+        },
+        move |result| sender.send(result).unwrap(),
+    );
+    let result = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(result, 42);
+    assert_eq!(task.progress_text().as_str(), "test job working");
+    assert!(task.is_finished());
+}
+
+#[rstest]
+fn test_spawn_analysis_job_cancelled(_session: &Session) {
+    let (sender, receiver) = mpsc::channel();
+    let task = spawn_analysis_job(
+        "test cancelled job",
+        true,
+        |task| {
+            while !task.is_cancelled() {
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            "cancelled"
+        },
+        move |result| sender.send(result).unwrap(),
+    );
+    task.cancel();
+    let result = receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(result, "cancelled");
+    assert!(task.is_finished());
+}
@@ -1,9 +1,18 @@
+use binaryninja::address::Addr;
 use binaryninja::binary_view::{AnalysisState, BinaryViewBase, BinaryViewExt};
+use binaryninja::disassembly::StringType;
+use binaryninja::function::{FunctionAnalysisSkipOverride, FunctionUpdateType};
 use binaryninja::headless::Session;
 use binaryninja::main_thread::execute_on_main_thread_and_wait;
+use binaryninja::section::{Section, Semantics};
+use binaryninja::segment::Segment;
 use binaryninja::symbol::{SymbolBuilder, SymbolType};
+use binaryninja::type_library::TypeLibrary;
+use binaryninja::types::{Type, TypeClass};
+use binaryninja::Endianness;
 use rstest::*;
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[fixture]
 #[once]
@@ -67,3 +76,550 @@ fn test_binary_saving_database(_session: &Session) {
         .expect("Failed to get entry point function");
     assert_eq!(new_entry_function.symbol().raw_name().as_str(), "test");
 }
+
+#[rstest]
+fn test_iter_blocks(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let mut blocks = view.iter_blocks(0x100);
+    let mut next_address = 0;
+    let mut total = 0;
+    let mut read = vec![];
+    while let Some((address, data)) = blocks.next_block() {
+        assert!(address >= next_address, "Blocks out of order");
+        assert!(!data.is_empty() && data.len() <= 0x100);
+        assert_eq!(data, view.read_vec(address, data.len()).as_slice());
+        next_address = address + data.len() as u64;
+        total += data.len();
+        read.push(address..next_address);
+    }
+    assert!(total > 0, "No blocks read");
+    // Unreadable parts of a segment don't hide the readable segments after them
+    for segment in view.segments().iter() {
+        let start = segment.address_range().start;
+        if !view.read_vec(start, 1).is_empty() {
+            assert!(read.iter().any(|range| range.contains(&start)));
+        }
+    }
+}
+
+#[rstest]
+fn test_duplicate_in_memory(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let entry_function = view
+        .entry_point_function()
+        .expect("Failed to get entry point function");
+    let original_name = entry_function.symbol().raw_name().to_string();
+
+    let scratch = view
+        .duplicate_in_memory()
+        .expect("Failed to duplicate view");
+    assert_eq!(scratch.view_type(), view.view_type());
+    assert_eq!(scratch.functions().len(), view.functions().len());
+    let database_path = scratch.database_path().to_owned();
+    assert!(database_path.exists());
+
+    // Changes to the copy must not show up in the original
+    let scratch_symbol =
+        SymbolBuilder::new(SymbolType::Function, "scratch", entry_function.start()).create();
+    scratch.define_user_symbol(&scratch_symbol);
+    assert_eq!(scratch.write(0x1560, &[0xff, 0xff, 0xff, 0xff]), 4);
+    assert_eq!(view.read_vec(0x1560, 4), [0x00, 0xf1, 0x00, 0x00]);
+    assert_eq!(entry_function.symbol().raw_name().as_str(), original_name);
+    assert!(!view.file().is_database_backed());
+
+    drop(scratch);
+    assert!(!database_path.exists());
+}
+
+#[rstest]
+fn test_typed_reads(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    assert_eq!(view.read_u32(0x1560).unwrap(), 0x0000f100);
+    assert_eq!(
+        view.read_value::<[u8; 4]>(0x1560).unwrap(),
+        [0x00, 0xf1, 0x00, 0x00]
+    );
+    assert_eq!(
+        view.read_value_with_endianness::<u32>(0x1560, binaryninja::Endianness::BigEndian)
+            .unwrap(),
+        0x00f10000
+    );
+    assert!(view.read_u64(view.end() - 4).is_err());
+
+    assert_eq!(view.write(0x1560, b"atox\0"), 5);
+    assert_eq!(
+        view.read_cstring(0x1560, 16, StringType::Utf8String)
+            .unwrap(),
+        "atox"
+    );
+    assert!(view
+        .read_cstring(0x1560, 2, StringType::Utf8String)
+        .is_err());
+    assert_eq!(view.write(0x1560, &[0xe5, 0x8e, 0x26]), 3);
+    assert_eq!(view.read_uleb128(0x1560).unwrap(), (624485, 3));
+}
+
+#[rstest]
+fn test_string_reads(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let endianness = view.default_endianness();
+
+    assert_eq!(view.write(0x1560, b"at\xe9x\0"), 5);
+    assert!(view
+        .read_cstring(0x1560, 16, StringType::AsciiString)
+        .is_err());
+    assert!(view
+        .read_cstring(0x1560, 16, StringType::Utf8String)
+        .is_err());
+    assert_eq!(view.write(0x1560, "at\u{e9}\0".as_bytes()), 5);
+    assert_eq!(
+        view.read_cstring(0x1560, 16, StringType::Utf8String)
+            .unwrap(),
+        "at\u{e9}"
+    );
+    assert!(view
+        .read_cstring(0x1560, 3, StringType::Utf8String)
+        .is_err());
+    assert!(view
+        .read_cstring(view.end() - 1, 16, StringType::Utf8String)
+        .is_err());
+
+    let utf16: Vec<u8> = "at\u{1f600}\0"
+        .encode_utf16()
+        .flat_map(|unit| match endianness {
+            Endianness::LittleEndian => unit.to_le_bytes(),
+            Endianness::BigEndian => unit.to_be_bytes(),
+        })
+        .collect();
+    assert_eq!(view.write(0x1560, &utf16), utf16.len());
+    assert_eq!(
+        view.read_utf16_string(0x1560, 4, endianness).unwrap(),
+        "at\u{1f600}"
+    );
+    assert!(view.read_utf16_string(0x1560, 3, endianness).is_err());
+
+    assert_eq!(view.write(0x1560, b"\x04atox"), 5);
+    assert_eq!(
+        view.read_length_prefixed_string(0x1560, 1, 16, StringType::AsciiString)
+            .unwrap(),
+        ("atox".to_string(), 5)
+    );
+    assert!(view
+        .read_length_prefixed_string(0x1560, 1, 3, StringType::AsciiString)
+        .is_err());
+    assert!(view
+        .read_length_prefixed_string(0x1560, 3, 16, StringType::AsciiString)
+        .is_err());
+}
+
+#[rstest]
+fn test_define_symbols(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let start = view.start();
+    let symbols: Vec<_> = (0..100u64)
+        .map(|i| {
+            SymbolBuilder::new(SymbolType::Data, format!("bulk_{}", i), start + i * 4).create()
+        })
+        .collect();
+
+    let mut reported = Vec::new();
+    let defined = view.define_user_symbols_with_progress(&symbols, |done, total| {
+        reported.push((done, total));
+        true
+    });
+    assert_eq!(defined, 100);
+    assert_eq!(reported.last(), Some(&(100, 100)));
+    for (i, symbol) in symbols.iter().enumerate() {
+        let found = view
+            .symbol_by_address(symbol.address())
+            .expect("Failed to find symbol");
+        assert_eq!(found.raw_name().as_str(), format!("bulk_{}", i));
+    }
+
+    // Cancelling stops after the current symbol
+    let renamed: Vec<_> = (0..10u64)
+        .map(|i| {
+            SymbolBuilder::new(
+                SymbolType::Data,
+                format!("auto_{}", i),
+                start + 0x400 + i * 4,
+            )
+            .create()
+        })
+        .collect();
+    let defined = view.define_auto_symbols_with_progress(renamed.iter(), |done, _| done < 3);
+    assert_eq!(defined, 3);
+    assert!(view.symbol_by_address(start + 0x400 + 2 * 4).is_some());
+    assert!(view.symbol_by_address(start + 0x400 + 3 * 4).is_none());
+}
+
+#[rstest]
+fn test_strings(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let strings = view.strings();
+    assert!(!strings.is_empty(), "No strings found");
+    for string in &strings {
+        let text = string.text(&view).expect("Failed to decode string");
+        assert!(!text.is_empty());
+        assert_eq!(view.string_at(string.start), Some(string));
+    }
+
+    let first = strings.get(0);
+    let in_range = view.strings_in_range(first.range());
+    assert!(in_range.iter().any(|string| string == first));
+    assert!(view.strings_in_range(0..0).is_empty());
+}
+
+#[rstest]
+fn test_symbol_index(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let mut index = view.symbol_index();
+    assert_eq!(index.len(), view.symbols().len());
+
+    let symbol = view.symbols().get(0).to_owned();
+    let name = symbol.full_name().to_string_lossy().to_uppercase();
+    let matches = index.symbols_with_prefix(&name, usize::MAX);
+    assert!(matches.contains(&symbol));
+    assert!(matches.iter().all(|s| s
+        .full_name()
+        .to_string_lossy()
+        .to_uppercase()
+        .starts_with(&name)));
+    assert_eq!(index.symbols_matching(&name, 1).len(), 1);
+    assert!(index.symbols_matching(&name, 0).is_empty());
+
+    view.define_user_symbol(
+        &SymbolBuilder::new(SymbolType::Data, "zz_index_test_symbol", view.start()).create(),
+    );
+    assert!(index.symbols_matching("zzindex", 10).is_empty());
+    index.refresh();
+    let found = index.symbols_matching("zzindex", 10);
+    assert_eq!(found[0].raw_name().as_str(), "zz_index_test_symbol");
+}
+
+#[rstest]
+fn test_checked_addresses(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let start = 0x2000_0000;
+    view.add_segment(Segment::builder(start..start + 0x100).readable(true));
+    let addr = Addr(start + 0x10);
+    assert_eq!(addr.segment_range(&view), Some(start..start + 0x100));
+    assert_eq!(
+        addr.checked_add_within_segment(&view, 0xef),
+        Some(Addr(start + 0xff))
+    );
+    assert_eq!(addr.checked_add_within_segment(&view, 0xf0), None);
+    assert_eq!(
+        addr.checked_offset_within_segment(&view, -0x10),
+        Some(Addr(start))
+    );
+    assert_eq!(addr.checked_offset_within_segment(&view, -0x11), None);
+    assert_eq!(
+        addr.checked_range_within_segment(&view, 0xf0),
+        Some(start + 0x10..start + 0x100)
+    );
+    assert_eq!(addr.checked_range_within_segment(&view, 0xf1), None);
+    assert_eq!(Addr(start + 0x100).segment_range(&view), None);
+}
+
+#[rstest]
+fn test_instructions_in(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let func = view.entry_point_function().expect("No entry function");
+    let block = func.basic_blocks().iter().next().unwrap().to_owned();
+    let range = block.start_index()..block.end_index();
+    let instructions: Vec<_> = view.instructions_in(range.clone()).collect();
+    assert!(!instructions.is_empty());
+    let mut next_address = range.start;
+    for instr in &instructions {
+        assert_eq!(instr.address, next_address, "Instructions not contiguous");
+        assert_eq!(instr.arch, func.arch());
+        assert!(!instr.text().is_empty());
+        next_address = instr.address_range().end;
+    }
+    assert_eq!(next_address, range.end);
+
+    // Data isn't decoded
+    let start = 0x2000_0000;
+    view.add_segment(Segment::builder(start..start + 0x100).readable(true));
+    assert_eq!(view.instructions_in(start..start + 0x100).count(), 0);
+}
+
+#[rstest]
+fn test_segment_mutation(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let start = 0x1000_0000;
+    view.add_segment(Segment::builder(start..start + 0x1000).readable(true));
+    let segment = view.segment_at(start).unwrap();
+    assert!(segment.readable() && !segment.writable());
+
+    segment.set_flags(&view, segment.flags() | 0x02);
+    let segment = view.segment_at(start).unwrap();
+    assert!(segment.readable() && segment.writable());
+    assert_eq!(segment.address_range(), start..start + 0x1000);
+
+    segment.set_length(&view, 0x2000);
+    let segment = view.segment_at(start + 0x1800).unwrap();
+    assert_eq!(segment.address_range(), start..start + 0x2000);
+    assert!(segment.writable());
+
+    view.remove_user_segment(segment.address_range());
+    assert!(view.segment_at(start).is_none());
+}
+
+#[rstest]
+fn test_section_mutation(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let start = view.start();
+    view.add_section(Section::builder(".example", start..start + 0x10).align(4));
+    let section = view.section_by_name(".example").unwrap();
+    assert_eq!(section.semantics(), Semantics::DefaultSection);
+
+    view.replace_section(
+        &section,
+        section
+            .to_builder()
+            .semantics(Semantics::ReadOnlyData)
+            .range(start..start + 0x20),
+    );
+    let section = view.section_by_name(".example").unwrap();
+    assert_eq!(section.semantics(), Semantics::ReadOnlyData);
+    assert_eq!(section.address_range(), start..start + 0x20);
+    assert_eq!(section.align(), 4);
+
+    view.remove_user_section(".example");
+    assert!(view.section_by_name(".example").is_none());
+}
+
+#[rstest]
+fn test_type_libraries(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let library = TypeLibrary::new(view.default_arch().unwrap(), "atox-types");
+    view.add_type_library(&library);
+    assert!(view.type_libraries().iter().any(|library| library
+        .name()
+        .is_some_and(|name| name.as_str() == "atox-types")));
+}
+
+#[rstest]
+fn test_apply_type_library_to_imports(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let library = TypeLibrary::new(view.default_arch().unwrap(), "atox-imports");
+    let import_type = Type::function(&Type::int(4, true), vec![], false);
+    library.add_named_object("atox_import".into(), &import_type);
+    view.add_type_library(&library);
+
+    let slot = view.end() - 0x10;
+    let unknown_slot = view.end() - 0x8;
+    view.define_auto_symbol(
+        &SymbolBuilder::new(SymbolType::ImportAddress, "__imp_atox_import", slot).create(),
+    );
+    view.define_auto_symbol(
+        &SymbolBuilder::new(
+            SymbolType::ImportAddress,
+            "__imp_atox_unknown",
+            unknown_slot,
+        )
+        .create(),
+    );
+
+    let report = view
+        .apply_type_library_to_imports()
+        .expect("Failed to type imports");
+    let applied = report
+        .applied
+        .iter()
+        .find(|import| import.symbol.address() == slot)
+        .expect("Import was not typed");
+    assert_eq!(applied.library, "atox-imports");
+    assert_eq!(applied.name, "atox_import");
+    assert!(report
+        .unmatched
+        .iter()
+        .any(|symbol| symbol.address() == unknown_slot));
+
+    let var = view
+        .data_variable_at_address(slot)
+        .expect("Import address has no data variable");
+    assert_eq!(var.ty.contents.type_class(), TypeClass::PointerTypeClass);
+    let (found_library, name) = view
+        .lookup_imported_object_library(slot, &view.default_platform().unwrap())
+        .expect("Import library was not recorded");
+    assert_eq!(found_library.name().unwrap().as_str(), "atox-imports");
+    assert_eq!(name.to_string(), "atox_import");
+}
+
+#[rstest]
+fn test_analysis_queue(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let info = view.analysis_info().expect("Failed to get analysis info");
+    assert_eq!(info.state, AnalysisState::IdleState);
+    assert!(info.running_longer_than(Duration::ZERO).is_empty());
+    assert!(view.functions_pending_analysis().is_empty());
+
+    let func = view
+        .entry_point_function()
+        .expect("Failed to get entry point function");
+    func.mark_updates_required(FunctionUpdateType::FullAutoFunctionUpdate);
+    let pending = view.functions_pending_analysis();
+    assert!(pending
+        .iter()
+        .any(|pending| pending.start() == func.start()));
+
+    func.cancel_analysis();
+    view.update_analysis_and_wait();
+    assert!(func.analysis_skipped());
+    assert_eq!(
+        func.analysis_skip_override(),
+        FunctionAnalysisSkipOverride::AlwaysSkipFunctionAnalysis
+    );
+
+    func.set_analysis_skip_override(FunctionAnalysisSkipOverride::DefaultFunctionAnalysisSkip);
+    view.update_analysis_and_wait();
+    assert!(!func.analysis_skipped());
+}
+
+#[rstest]
+fn test_metadata_namespaces(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    view.store_metadata("rustTest.v1.name", "old", false);
+    view.store_metadata("rustTest.v2.name", "new", false);
+    view.store_metadata("rustTest.v2.count", 2u64, true);
+    view.store_metadata("other", true, false);
+
+    let keys = view.metadata_keys();
+    assert!(keys.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert!(keys.contains(&"other".to_string()));
+    let v2 = view.metadata_with_prefix("rustTest.v2.");
+    assert_eq!(
+        v2.keys().collect::<Vec<_>>(),
+        vec!["rustTest.v2.count", "rustTest.v2.name"]
+    );
+    assert!(view
+        .auto_metadata()
+        .get("rustTest.v2.count")
+        .unwrap()
+        .is_some());
+
+    assert_eq!(view.remove_metadata_with_prefix("rustTest."), 3);
+    assert!(view.metadata_with_prefix("rustTest.").is_empty());
+    assert!(view.query_metadata("other").is_some());
+}
+
+#[rstest]
+fn test_tag_queries(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let review = view.create_tag_type("Review", "R");
+    let data_addr = view.start();
+    view.add_tag(data_addr, &review, "data", true);
+    view.add_tag(data_addr, &review, "auto data", false);
+    let func = view.functions().iter().next().unwrap().to_owned();
+    func.add_tag(&review, "function", None, true, None);
+    func.add_tag(&review, "address", Some(func.start()), true, None);
+
+    assert!(view
+        .tag_types()
+        .iter()
+        .any(|ty| ty.name().as_str() == "Review"));
+    assert_eq!(view.all_tags_of_type(&review).len(), 4);
+    assert!(view.all_tags().len() >= 4);
+
+    let data_tags = view.data_tags(None);
+    let data_tag = data_tags
+        .iter()
+        .find(|tag_ref| tag_ref.tag.data().as_str() == "data")
+        .expect("Data tag");
+    assert_eq!(data_tag.addr, data_addr);
+    assert!(data_tag.func.is_none());
+    assert_eq!(view.data_tags(Some(true)).len(), 1);
+    assert_eq!(view.data_tags_at(data_addr, Some(false)).len(), 1);
+    assert_eq!(view.data_tags_of_type_at(data_addr, &review, None).len(), 2);
+    assert_eq!(
+        view.data_tags_in_range(data_addr..data_addr + 1, None)
+            .len(),
+        2
+    );
+
+    assert_eq!(func.all_tags_of_type(&review).len(), 2);
+    assert_eq!(func.function_tag_references(Some(false)).len(), 1);
+    let address_tags = func.tags_of_type_at(func.start(), &review, None, None);
+    assert_eq!(address_tags.get(0).data().as_str(), "address");
+    let function_tag = func
+        .all_tags()
+        .iter()
+        .find(|tag_ref| tag_ref.tag.data().as_str() == "function");
+    assert!(function_tag.expect("Function tag").func.is_some());
+}
+
+#[rstest]
+fn test_file_headers(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    // A COFF object has none of the executable file headers
+    assert!(view.elf_header().is_none());
+    assert!(view.pe_header().is_none());
+    assert!(view.macho_header().is_none());
+}
+
+#[rstest]
+fn test_entropy(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let range = view.start()..view.start() + 0x1100;
+    let entropy = view.entropy(range.clone(), 0x400);
+    // The last block holds the remaining 0x100 bytes
+    assert_eq!(entropy.len(), 5);
+    assert!(entropy.iter().all(|entropy| (0.0..=1.0).contains(entropy)));
+
+    let histogram = view.byte_histogram(range.clone());
+    assert_eq!(histogram.total(), 0x1100);
+    let data = view.read_vec(range.start, 0x400);
+    let first_block = binaryninja::entropy::shannon_entropy(&data);
+    assert!((entropy[0] - first_block).abs() < 1e-9);
+}
+
+#[rstest]
+fn test_user_system_calls(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let platform = view.default_platform().expect("Default platform");
+    let prototype = Type::function(&Type::int(4, true), vec![], false);
+    view.set_user_system_call(0x1337, "custom_syscall", &prototype);
+
+    let info = view
+        .system_call_info(&platform, 0x1337)
+        .expect("Overridden system call");
+    assert_eq!(info.number, 0x1337);
+    assert_eq!(info.name.to_string(), "custom_syscall");
+    assert_eq!(info.ty, prototype);
+    assert_eq!(view.type_by_name("custom_syscall"), Some(prototype.clone()));
+
+    // Without its prototype the override falls back to the platform
+    let platform_info = platform
+        .system_calls()
+        .iter()
+        .find(|info| info.number == 0x1337)
+        .map(|info| info.to_owned());
+    view.undefine_user_type("custom_syscall");
+    assert_eq!(view.system_call_info(&platform, 0x1337), platform_info);
+
+    view.set_user_system_call(0x1337, "custom_syscall", &prototype);
+    view.remove_user_system_call(0x1337);
+    assert_eq!(view.system_call_info(&platform, 0x1337), platform_info);
+}
@@ -24,3 +24,46 @@ fn test_component_creation(_session: &Session) {
         "Component not found in root component"
     );
 }
+
+#[rstest]
+fn test_component_path(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let component = view.create_component_path(&["src", "net"]).unwrap();
+    assert_eq!(component.name().as_str(), "net");
+    let parent = component.parent().unwrap();
+    assert_eq!(parent.name().as_str(), "src");
+    // Existing components are reused
+    let again = view.create_component_path(&["src", "net"]).unwrap();
+    assert_eq!(again.guid(), component.guid());
+    let sibling = view.create_component_path(&["src", "io"]).unwrap();
+    assert_eq!(sibling.parent().unwrap().guid(), parent.guid());
+    assert_eq!(parent.components().len(), 2);
+    assert_eq!(view.root_component().unwrap().components().len(), 1);
+}
+
+#[rstest]
+fn test_component_membership(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let lib = view.create_component("lib", None);
+    let net = view.create_component("net", Some(&lib));
+    let io = view.create_component("io", None);
+    assert_eq!(net.parent().unwrap().guid(), lib.guid());
+
+    let func = view.functions().iter().next().unwrap().to_owned();
+    assert!(net.add_function(&func));
+    assert!(io.add_function(&func));
+    assert_eq!(func.parent_components().len(), 2);
+    // Moving leaves the function only in the destination
+    assert!(io.move_function(&func));
+    assert!(!net.contains_function(&func));
+    assert_eq!(io.functions().len(), 1);
+
+    io.set_name("input");
+    assert_eq!(io.name().as_str(), "input");
+    assert!(lib.add_component(&io));
+    assert_eq!(lib.components().len(), 2);
+    assert!(lib.remove_component(&io));
+    assert!(view.root_component().unwrap().contains_component(&io));
+}
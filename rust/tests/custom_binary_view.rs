@@ -0,0 +1,130 @@
+use binaryninja::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
+use binaryninja::custom_binary_view::*;
+use binaryninja::file_metadata::FileMetadata;
+use binaryninja::headless::Session;
+use binaryninja::rc::Ref;
+use binaryninja::segment::SegmentBuilder;
+use binaryninja::{Endianness, Error};
+use rstest::*;
+
+const HEADER_SIZE: u64 = 8;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+struct FirmwareViewType {
+    core: BinaryViewType,
+}
+
+impl AsRef<BinaryViewType> for FirmwareViewType {
+    fn as_ref(&self) -> &BinaryViewType {
+        &self.core
+    }
+}
+
+impl BinaryViewTypeBase for FirmwareViewType {
+    fn is_valid_for(&self, data: &BinaryView) -> bool {
+        data.read_vec(0, 4) == b"FW01"
+    }
+}
+
+impl CustomBinaryViewType for FirmwareViewType {
+    fn create_custom_view<'builder>(
+        &self,
+        data: &BinaryView,
+        builder: CustomViewBuilder<'builder, Self>,
+    ) -> Result<CustomView<'builder>, Error> {
+        let load_address = data.read_u32(4)? as u64;
+        builder.create::<FirmwareView>(data, load_address)
+    }
+}
+
+struct FirmwareView {
+    core: Ref<BinaryView>,
+    load_address: u64,
+    len: u64,
+}
+
+impl AsRef<BinaryView> for FirmwareView {
+    fn as_ref(&self) -> &BinaryView {
+        &self.core
+    }
+}
+
+unsafe impl CustomBinaryView for FirmwareView {
+    type Args = u64;
+
+    fn new(handle: &BinaryView, _load_address: &u64) -> Result<Self, Error> {
+        Ok(Self {
+            core: handle.to_owned(),
+            load_address: 0,
+            len: 0,
+        })
+    }
+
+    fn init(&mut self, load_address: u64) -> Result<(), Error> {
+        let parent = self
+            .core
+            .parent_view()
+            .ok_or(Error::NullHandle("parent view"))?;
+        self.load_address = load_address;
+        self.len = parent.len().saturating_sub(HEADER_SIZE);
+        self.add_segment(
+            SegmentBuilder::new(load_address..load_address + self.len)
+                .parent_backing(HEADER_SIZE..HEADER_SIZE + self.len)
+                .readable(true)
+                .contains_data(true),
+        );
+        Ok(())
+    }
+}
+
+impl BinaryViewBase for FirmwareView {
+    fn start(&self) -> u64 {
+        self.load_address
+    }
+
+    fn len(&self) -> u64 {
+        self.len
+    }
+
+    fn entry_point(&self) -> u64 {
+        self.load_address
+    }
+
+    fn default_endianness(&self) -> Endianness {
+        Endianness::LittleEndian
+    }
+
+    fn address_size(&self) -> usize {
+        4
+    }
+}
+
+#[rstest]
+fn test_custom_view_type(_session: &Session) {
+    let view_type = register_view_type(c"RustTestFW01", c"Rust Test Firmware", |core| {
+        FirmwareViewType { core }
+    });
+    assert_eq!(view_type.name().as_str(), "RustTestFW01");
+    assert!(BinaryViewType::by_name("RustTestFW01").unwrap() == *view_type.as_ref());
+
+    let mut image = b"FW01".to_vec();
+    image.extend_from_slice(&0x8000u32.to_le_bytes());
+    image.extend_from_slice(&[0xde, 0xad, 0xbe, 0xef]);
+    let file = FileMetadata::new();
+    let raw = BinaryView::from_data(&file, &image).expect("Failed to create raw view");
+    assert!(view_type.is_valid_for(&raw));
+    let not_firmware = BinaryView::from_data(&FileMetadata::new(), b"ELF").unwrap();
+    assert!(!view_type.is_valid_for(&not_firmware));
+
+    let view = view_type.open(&raw).expect("Failed to open firmware");
+    assert_eq!(view.view_type().as_str(), "RustTestFW01");
+    assert_eq!(view.start(), 0x8000);
+    assert_eq!(view.len(), 4);
+    assert_eq!(view.read_u32(0x8000).unwrap(), 0xefbeadde);
+    assert!(view.segment_at(0x8000).is_some());
+}
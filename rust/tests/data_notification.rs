@@ -0,0 +1,63 @@
+use binaryninja::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
+use binaryninja::data_notification::BinaryDataNotification;
+use binaryninja::headless::Session;
+use binaryninja::symbol::{Symbol, SymbolBuilder, SymbolType};
+use rstest::*;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[derive(Default)]
+struct Recorder {
+    writes: Mutex<Vec<(u64, usize)>>,
+    symbols: Mutex<Vec<String>>,
+}
+
+impl BinaryDataNotification for Recorder {
+    fn on_data_written(&self, _view: &BinaryView, offset: u64, len: usize) {
+        self.writes.lock().unwrap().push((offset, len));
+    }
+
+    fn on_symbol_added(&self, _view: &BinaryView, symbol: &Symbol) {
+        self.symbols
+            .lock()
+            .unwrap()
+            .push(symbol.raw_name().to_string());
+    }
+}
+
+#[rstest]
+fn test_data_notification(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let entry = view.entry_point();
+
+    let handle = view.register_data_notification(Recorder::default());
+    assert_eq!(view.write(entry, &[0x90, 0x90]), 2);
+    let symbol = SymbolBuilder::new(SymbolType::Data, "notified", entry + 0x10).create();
+    view.define_user_symbol(&symbol);
+    view.update_analysis_and_wait();
+    assert!(handle
+        .notification()
+        .writes
+        .lock()
+        .unwrap()
+        .contains(&(entry, 2)));
+
+    let recorder = handle.unregister();
+    assert!(recorder
+        .symbols
+        .lock()
+        .unwrap()
+        .contains(&"notified".to_string()));
+
+    // Nothing is recorded once the notification is unregistered
+    let writes = recorder.writes.lock().unwrap().len();
+    view.write(entry, &[0xcc]);
+    assert_eq!(recorder.writes.lock().unwrap().len(), writes);
+}
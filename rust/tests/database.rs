@@ -0,0 +1,113 @@
+use binaryninja::binary_view::{BinaryViewBase, BinaryViewExt};
+use binaryninja::data_buffer::DataBuffer;
+use binaryninja::database::diff::{Change, CommentLocation};
+use binaryninja::database::kvs::KeyValueStore;
+use binaryninja::headless::Session;
+use binaryninja::symbol::{SymbolBuilder, SymbolType};
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_diff_snapshots(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let temp_dir = tempfile::tempdir().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    assert!(view
+        .file()
+        .create_database(temp_dir.path().join("atox.obj.bndb")));
+    let database = view.file().database().expect("Failed to get database");
+    let first = database.current_snapshot().expect("No first snapshot");
+
+    let entry_function = view
+        .entry_point_function()
+        .expect("Failed to get entry point function");
+    let symbol =
+        SymbolBuilder::new(SymbolType::Function, "renamed", entry_function.start()).create();
+    view.define_user_symbol(&symbol);
+    view.set_comment_at(entry_function.start(), "entry point");
+    assert!(view.file().save_auto_snapshot());
+    let second = database.current_snapshot().expect("No second snapshot");
+    assert_ne!(first.id(), second.id());
+
+    let changes = database
+        .diff_snapshots(&first, &second, view.view_type().as_str())
+        .expect("Failed to diff snapshots");
+    assert!(changes.types.is_empty());
+    assert!(changes.functions.iter().any(|change| matches!(
+        change,
+        Change::Modified { key, new, .. } if *key == entry_function.start() && new.name == "renamed"
+    )));
+    let comment_location = CommentLocation {
+        function: None,
+        address: entry_function.start(),
+    };
+    assert!(changes.comments.contains(&Change::Added {
+        key: comment_location,
+        new: "entry point".to_string(),
+    }));
+
+    // Nothing changed between a snapshot and itself
+    let unchanged = database
+        .diff_snapshots(&second, &second, view.view_type().as_str())
+        .expect("Failed to diff snapshots");
+    assert!(unchanged.is_empty());
+    // The database still points at the latest snapshot
+    assert_eq!(database.current_snapshot().unwrap().id(), second.id());
+}
+
+#[rstest]
+fn test_create_and_trim_snapshots(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let temp_dir = tempfile::tempdir().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    assert!(view
+        .file()
+        .create_database(temp_dir.path().join("atox.obj.bndb")));
+    let database = view.file().database().expect("Failed to get database");
+    let first = database.current_snapshot().expect("No first snapshot");
+
+    view.set_comment_at(view.entry_point(), "first");
+    let second = database
+        .create_snapshot(&view, "second")
+        .expect("Failed to create snapshot");
+    view.set_comment_at(view.entry_point(), "second");
+    let third = database
+        .create_snapshot(&view, "third")
+        .expect("Failed to create snapshot");
+    assert_eq!(database.current_snapshot().unwrap().id(), third);
+    let third_snapshot = database.snapshot_by_id(third).unwrap();
+    assert_eq!(third_snapshot.name().as_str(), "third");
+    assert!(!third_snapshot.is_auto_save());
+    assert_eq!(third_snapshot.first_parent().unwrap().id(), second);
+    assert!(database.snapshots().len() >= 3);
+
+    // Only the contents of the first snapshot are trimmed, the history is kept
+    let trimmed = database.trim_old_snapshots(2).unwrap();
+    assert_eq!(trimmed, vec![first.id()]);
+    assert!(!database.snapshot_by_id(first.id()).unwrap().has_contents());
+    assert!(database.snapshot_by_id(second).unwrap().has_contents());
+    assert!(database.trim_old_snapshots(2).unwrap().is_empty());
+}
+
+#[rstest]
+fn test_key_value_store(_session: &Session) {
+    let store = KeyValueStore::new();
+    assert!(store.is_empty());
+    assert!(store.set_string_value("name", "value"));
+    assert!(store.set_value("data", &DataBuffer::new(&[1, 2, 3]).unwrap()));
+    assert!(store.has_value("name"));
+    assert!(!store.has_value("missing"));
+    assert_eq!(store.string_value("name").unwrap().as_str(), "value");
+    assert!(store.value_hash("data").is_some());
+
+    let copy = KeyValueStore::from_serialized_data(&store.serialized_data())
+        .expect("Failed to deserialize store");
+    assert_eq!(copy.string_value("name").unwrap().as_str(), "value");
+    assert_eq!(copy.value("data").unwrap().get_data(), &[1, 2, 3]);
+}
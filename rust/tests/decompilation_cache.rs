@@ -0,0 +1,59 @@
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::decompilation_cache::{DecompilationCache, DecompilationCacheError};
+use binaryninja::headless::Session;
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_decompilation_cache_round_trip(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let cache = DecompilationCache::from_view(&view);
+    assert_eq!(cache.len(), view.functions().len());
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let cache_path = temp_dir.path().join("atox.obj.hlilcache");
+    cache.save(&cache_path).expect("Failed to save cache");
+    let loaded = DecompilationCache::load(&cache_path).expect("Failed to load cache");
+    assert_eq!(loaded.len(), cache.len());
+    for function in cache.functions() {
+        let loaded_function = loaded
+            .function(function.start)
+            .expect("Missing cached function");
+        assert_eq!(loaded_function.name, function.name);
+        assert_eq!(loaded_function.lines.len(), function.lines.len());
+        for (loaded_line, line) in loaded_function.lines.iter().zip(&function.lines) {
+            assert_eq!(loaded_line.address, line.address);
+            assert_eq!(loaded_line.tokens, line.tokens);
+        }
+    }
+
+    // The cache can be checked against the file without running analysis.
+    let unanalyzed = binaryninja::load_with_options(
+        out_dir.join("atox.obj"),
+        false,
+        Some("{\"analysis.mode\": \"basic\"}"),
+    )
+    .expect("Failed to create view");
+    assert!(loaded.is_valid_for(&unanalyzed));
+}
+
+#[rstest]
+fn test_decompilation_cache_invalid(_session: &Session) {
+    let result = DecompilationCache::read_from(&mut &b"not a cache"[..]);
+    assert!(matches!(result, Err(DecompilationCacheError::InvalidMagic)));
+
+    let mut truncated = Vec::new();
+    DecompilationCache::default()
+        .write_to(&mut truncated)
+        .expect("Failed to write cache");
+    truncated.pop();
+    let result = DecompilationCache::read_from(&mut truncated.as_slice());
+    assert!(matches!(result, Err(DecompilationCacheError::Corrupt(_))));
+}
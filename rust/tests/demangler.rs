@@ -1,9 +1,12 @@
 use binaryninja::architecture::CoreArchitecture;
 use binaryninja::binary_view::BinaryView;
 use binaryninja::demangle::{
-    demangle_generic, demangle_gnu3, demangle_llvm, demangle_ms, CustomDemangler, Demangler,
+    demangle_generic, demangle_gnu3, demangle_gnu3_with_options, demangle_llvm,
+    demangle_llvm_with_options, demangle_ms, demangle_ms_platform, demangle_ms_with_options,
+    is_gnu3_mangled_string, CustomDemangler, Demangler,
 };
 use binaryninja::headless::Session;
+use binaryninja::platform::Platform;
 use binaryninja::rc::Ref;
 use binaryninja::types::{QualifiedName, Type};
 use rstest::*;
@@ -45,6 +48,26 @@ fn test_demangler_simple(_session: &Session) {
     );
 }
 
+#[rstest]
+fn test_demangler_options(_session: &Session) {
+    let arch = CoreArchitecture::by_name("x86").expect("x86 exists");
+    assert!(is_gnu3_mangled_string("_Z3bari"));
+    assert!(!is_gnu3_mangled_string("?baz@@YAHH@Z"));
+
+    let (name, _) = demangle_gnu3_with_options(&arch, "_Z3bari", None).unwrap();
+    assert_eq!(name, "bar".into());
+    let (name, _) = demangle_ms_with_options(&arch, "?baz@@YAHH@Z", None).unwrap();
+    assert_eq!(name, "baz".into());
+    let name = demangle_llvm_with_options("_Z3fooi", None).unwrap();
+    assert_eq!(name, "foo(int)".into());
+
+    let platform = Platform::by_name("windows-x86").expect("windows-x86 exists");
+    let (name, ty) = demangle_ms_platform(&platform, "?baz@@YAHH@Z", true).unwrap();
+    assert_eq!(name, "baz".into());
+    assert!(ty.is_some());
+    assert!(demangle_ms_platform(&platform, "not mangled", true).is_none());
+}
+
 #[rstest]
 fn test_custom_demangler(_session: &Session) {
     struct TestDemangler;
@@ -0,0 +1,52 @@
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::headless::{InitializationOptions, Session};
+use binaryninja::settings::{QueryOptions, Settings};
+use binaryninja::worker_thread::worker_thread_count;
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    let options = InitializationOptions::default().with_deterministic_analysis(true);
+    Session::new_with_opts(options).expect("Failed to initialize session")
+}
+
+const TIME_LIMIT_SETTING: &str = "analysis.limits.maxFunctionAnalysisTime";
+
+/// Loads `path` and exports the functions and data variables found by analysis.
+fn export(path: PathBuf) -> String {
+    let view = binaryninja::load(path).expect("Failed to create view");
+    let settings = Settings::new();
+    let mut options = QueryOptions::new_with_view(&view);
+    assert_eq!(
+        settings.get_integer_with_opts(TIME_LIMIT_SETTING, &mut options),
+        0
+    );
+
+    let mut export = String::new();
+    for func in &view.functions() {
+        export += &format!(
+            "{:#x} {} {}\n",
+            func.start(),
+            func.symbol().full_name(),
+            func.function_type()
+        );
+    }
+    for var in &view.data_variables() {
+        export += &format!("{:#x} {}\n", var.address, var.ty.contents);
+    }
+    export
+}
+
+#[rstest]
+fn test_deterministic_analysis(_session: &Session) {
+    assert_eq!(worker_thread_count(), 1);
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let first = export(out_dir.join("atox.obj"));
+    let second = export(out_dir.join("atox.obj"));
+    assert!(!first.is_empty());
+    assert_eq!(first, second);
+    // Only the views are affected, not the settings of the user
+    assert_ne!(Settings::new().get_integer(TIME_LIMIT_SETTING), 0);
+}
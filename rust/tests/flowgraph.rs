@@ -0,0 +1,107 @@
+use binaryninja::flowgraph::{
+    register_flow_graph_layout, BranchType, CoreFlowGraphLayout, EdgePenStyle, EdgeStyle,
+    FlowGraph, FlowGraphLayoutHandler, FlowGraphNode, ThemeColor,
+};
+use binaryninja::function::{HighlightColor, HighlightStandardColor};
+use binaryninja::headless::Session;
+use binaryninja::rc::Ref;
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+fn two_node_graph() -> Ref<FlowGraph> {
+    let graph = FlowGraph::new();
+    let node_a = FlowGraphNode::new(&graph);
+    node_a.set_lines(["Line 1".into()]);
+    let node_b = FlowGraphNode::new(&graph);
+    node_b.set_lines(["Line 2".into(), "Line 3".into()]);
+    assert_eq!(graph.append(&node_a), 0);
+    assert_eq!(graph.append(&node_b), 1);
+    let style = EdgeStyle::new(EdgePenStyle::DashLine, 2, ThemeColor::TrueBranchColor);
+    node_a.add_outgoing_edge(BranchType::TrueBranch, &node_b, style);
+    graph
+}
+
+#[rstest]
+fn test_build_graph(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let graph = two_node_graph();
+    graph.set_view(Some(&view));
+    assert_eq!(graph.view().as_deref(), Some(&*view));
+    assert!(graph.has_nodes());
+
+    let nodes = graph.nodes();
+    assert_eq!(nodes.len(), 2);
+    let node_a = graph.node(0).unwrap();
+    let node_b = graph.node(1).unwrap();
+    assert!(graph.contains(&node_a));
+    assert_eq!(node_b.lines().len(), 2);
+    assert!(node_b.graph() == graph);
+
+    let outgoing = node_a.outgoing_edges();
+    assert_eq!(outgoing.len(), 1);
+    let edge = outgoing.get(0);
+    assert_eq!(edge.branch_type, BranchType::TrueBranch);
+    assert_eq!(edge.target, node_b);
+    assert_eq!(
+        edge.style,
+        EdgeStyle::new(EdgePenStyle::DashLine, 2, ThemeColor::TrueBranchColor)
+    );
+    assert_eq!(node_b.incoming_edges().get(0).target, node_a);
+
+    let highlight = HighlightColor::StandardHighlightColor {
+        color: HighlightStandardColor::RedHighlightColor,
+        alpha: 255,
+    };
+    node_a.set_highlight(highlight);
+    assert_eq!(node_a.highlight(), highlight);
+}
+
+#[rstest]
+fn test_layout_graph(_session: &Session) {
+    let graph = two_node_graph();
+    let laid_out = graph.layout_and_wait();
+    let nodes = laid_out.nodes();
+    assert_eq!(nodes.len(), 2);
+    for node in &nodes {
+        let (x, y) = node.position();
+        assert!(x + node.width() <= laid_out.width());
+        assert!(y + node.height() <= laid_out.height());
+    }
+    // The edge goes down from the first node to the second
+    assert!(nodes.get(0).position().1 < nodes.get(1).position().1);
+}
+
+struct ColumnLayout;
+
+impl FlowGraphLayoutHandler for ColumnLayout {
+    fn layout(&self, graph: &FlowGraph, nodes: &[Ref<FlowGraphNode>]) -> bool {
+        for (i, node) in nodes.iter().enumerate() {
+            node.set_position(0, i as i32 * 100);
+        }
+        graph.set_width(100);
+        graph.set_height(nodes.len() as i32 * 100);
+        true
+    }
+}
+
+#[rstest]
+fn test_custom_layout(_session: &Session) {
+    let layout = register_flow_graph_layout("column", ColumnLayout);
+    assert_eq!(layout.name().as_str(), "column");
+    assert_eq!(CoreFlowGraphLayout::by_name("column"), Some(layout));
+    assert!(CoreFlowGraphLayout::all().iter().any(|l| l == layout));
+
+    let graph = two_node_graph();
+    let nodes: Vec<Ref<FlowGraphNode>> = graph.nodes().iter().map(|node| node.to_owned()).collect();
+    let nodes: Vec<&FlowGraphNode> = nodes.iter().map(|node| node.as_ref()).collect();
+    assert!(layout.layout(&graph, &nodes));
+    assert_eq!(graph.node(1).unwrap().position(), (0, 100));
+    assert_eq!(graph.height(), 200);
+}
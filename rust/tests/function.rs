@@ -0,0 +1,151 @@
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::confidence::Conf;
+use binaryninja::disassembly::{
+    DisassemblyCallParameterHints, DisassemblyOption, DisassemblySettings,
+};
+use binaryninja::headless::Session;
+use binaryninja::types::Type;
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_call_type_adjustment(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let (func, call_site) = view
+        .functions()
+        .iter()
+        .find_map(|func| {
+            let call_site = func.call_sites().iter().next()?.address;
+            Some((func.to_owned(), call_site))
+        })
+        .expect("Failed to find a call site");
+    assert!(func.call_type_adjustment(call_site, None).is_none());
+
+    let adjusted_type = Type::function(&Type::int(4, true), vec![], true);
+    func.set_user_call_type_adjustment(call_site, Some(&adjusted_type), None);
+    let adjustment = func
+        .call_type_adjustment(call_site, None)
+        .expect("Failed to get call type adjustment");
+    assert_eq!(adjustment.contents, adjusted_type);
+
+    // Passing `None` removes the adjustment.
+    func.set_user_call_type_adjustment::<&Type>(call_site, None, None);
+    assert!(func.call_type_adjustment(call_site, None).is_none());
+}
+
+#[rstest]
+fn test_function_attributes(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let func = view.entry_point_function().expect("No entry function");
+    let return_type = Type::int(4, true);
+    let calling_convention = func
+        .platform()
+        .calling_conventions()
+        .iter()
+        .next()
+        .expect("No calling conventions")
+        .to_owned();
+
+    func.set_user_return_type(&return_type);
+    func.set_user_calling_convention(Some(&calling_convention));
+    func.set_user_parameter_variables(Conf::new(vec![], 200));
+    func.set_user_can_return(Conf::new(false, 200));
+    func.set_user_pure(true);
+    func.set_user_stack_adjustment(8);
+    func.set_user_inline_during_analysis(true);
+    view.update_analysis_and_wait();
+
+    assert_eq!(func.return_type().contents, return_type);
+    let applied_convention = func.calling_convention().expect("No calling convention");
+    assert_eq!(
+        applied_convention.contents.name(),
+        calling_convention.name()
+    );
+    assert!(func.parameter_variables().contents.is_empty());
+    assert!(!func.can_return().contents);
+    assert!(func.is_pure().contents);
+    assert_eq!(func.stack_adjustment().contents, 8);
+    assert!(func.inline_during_analysis().contents);
+}
+
+#[rstest]
+fn test_stack_variables(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let func = view.entry_point_function().expect("No entry function");
+    let offset = -0x40;
+
+    func.create_user_stack_var(offset, &Type::int(4, true), "counter");
+    view.update_analysis_and_wait();
+    let var = func.stack_var_at_offset(offset).expect("No stack variable");
+    assert_eq!(var.name, "counter");
+    assert!(!var.auto_defined);
+    // Offsets inside the variable find it too
+    let inner = func
+        .stack_var_at_offset(offset + 3)
+        .expect("No stack variable");
+    assert_eq!(inner.variable, var.variable);
+
+    func.rename_var(&var.variable, "total");
+    func.set_user_var_type(&var.variable, Conf::new(&*Type::int(8, false), 200));
+    view.update_analysis_and_wait();
+    assert_eq!(func.variable_name(&var.variable).as_str(), "total");
+    let var_type = func.variable_type(&var.variable).expect("No variable type");
+    assert_eq!(var_type.contents, Type::int(8, false));
+    assert_eq!(var_type.confidence, 200);
+    assert!(func
+        .stack_layout()
+        .iter()
+        .any(|layout_var| layout_var.name == "total"));
+
+    func.delete_user_stack_var(offset);
+    view.update_analysis_and_wait();
+    assert!(func
+        .stack_layout()
+        .iter()
+        .all(|layout_var| layout_var.name != "total"));
+}
+
+#[rstest]
+fn test_disassembly_settings(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let functions = view.functions();
+    let func = functions.get(0);
+
+    let settings = DisassemblySettings::default_settings().duplicate();
+    settings.set_option(DisassemblyOption::ShowOpcode, false);
+    settings.set_width(120);
+    settings.set_maximum_symbol_width(16);
+    settings.set_call_parameter_hints(DisassemblyCallParameterHints::NeverShowParameterHints);
+    assert_eq!(settings.width(), 120);
+    assert_eq!(settings.maximum_symbol_width(), 16);
+    assert_eq!(
+        settings.call_parameter_hints(),
+        DisassemblyCallParameterHints::NeverShowParameterHints
+    );
+    // Duplicates are independent of the settings they were made from
+    let with_opcodes = settings.duplicate();
+    with_opcodes.set_option(DisassemblyOption::ShowOpcode, true);
+    assert!(!settings.is_option_set(DisassemblyOption::ShowOpcode));
+
+    let blocks = func.basic_blocks();
+    let block = blocks.get(0);
+    let plain = block.disassembly_text(Some(&settings));
+    let opcodes = block.disassembly_text(Some(&with_opcodes));
+    assert_eq!(plain.len(), opcodes.len());
+    assert!(opcodes.get(0).tokens.len() > plain.get(0).tokens.len());
+
+    let hlil = func.high_level_il(false).unwrap();
+    assert!(hlil.create_graph(Some(&settings)).has_nodes());
+    let llil = func.low_level_il().unwrap();
+    assert!(llil.create_graph(None).has_nodes());
+}
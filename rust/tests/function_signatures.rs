@@ -0,0 +1,53 @@
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::function_signatures::{
+    apply_function_signatures, export_function_signatures, function_guid, function_guids,
+};
+use binaryninja::headless::Session;
+use binaryninja::symbol::{SymbolBuilder, SymbolType};
+use binaryninja::type_library::TypeLibrary;
+use binaryninja::types::Type;
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_signature_round_trip(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let old_view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let entry_function = old_view
+        .entry_point_function()
+        .expect("Failed to get entry point function");
+    let symbol =
+        SymbolBuilder::new(SymbolType::Function, "exported", entry_function.start()).create();
+    old_view.define_user_symbol(&symbol);
+    let prototype = Type::function(&Type::int(4, true), vec![], false);
+    entry_function.set_user_type(&prototype);
+    old_view.update_analysis_and_wait();
+
+    let library = TypeLibrary::new(old_view.default_arch().unwrap(), "atox-signatures");
+    assert!(export_function_signatures(&old_view, &library) > 0);
+    let guid = function_guid(&entry_function.platform(), "exported");
+    assert_eq!(
+        function_guids(&library).get(&guid).map(String::as_str),
+        Some("exported")
+    );
+    assert!(library.get_named_object("exported".into()).is_some());
+
+    // A new build of the same program, where the function has the same name
+    let new_view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let new_entry_function = new_view
+        .entry_point_function()
+        .expect("Failed to get entry point function");
+    new_view.define_user_symbol(&symbol);
+    assert!(apply_function_signatures(&new_view, &library) > 0);
+    new_view.update_analysis_and_wait();
+    assert_eq!(
+        new_entry_function.function_type().to_string(),
+        entry_function.function_type().to_string()
+    );
+}
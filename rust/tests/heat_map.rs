@@ -0,0 +1,36 @@
+use binaryninja::binary_view::{BinaryViewBase, BinaryViewExt};
+use binaryninja::headless::Session;
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_heat_map(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let entry_function = view.entry_point_function().unwrap();
+    view.set_comment_at(entry_function.start(), "entry");
+
+    let heat_map = view.heat_map(16);
+    assert!(heat_map.bins().len() <= 16);
+    assert_eq!(heat_map.range(), view.start()..view.start() + view.len());
+    let function_starts: usize = heat_map.bins().iter().map(|b| b.function_starts).sum();
+    assert_eq!(function_starts, view.functions().len());
+    let code_bytes: u64 = heat_map.bins().iter().map(|b| b.code_bytes).sum();
+    assert!(code_bytes > 0);
+    assert!(code_bytes <= view.len());
+
+    let entry_bin = heat_map.bin_for(entry_function.start()).unwrap();
+    assert!(entry_bin.function_starts > 0);
+    assert!(entry_bin.comments > 0);
+
+    let mut csv = Vec::new();
+    heat_map.write_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    assert_eq!(csv.lines().count(), heat_map.bins().len() + 1);
+}
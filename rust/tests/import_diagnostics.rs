@@ -0,0 +1,37 @@
+use binaryninja::headless::Session;
+use binaryninja::import_diagnostics::ImportDiagnostics;
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_store_import_diagnostics(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    assert!(ImportDiagnostics::for_importer(&view, "Test Importer").is_none());
+
+    let mut diagnostics = ImportDiagnostics::new("Test Importer");
+    diagnostics.add_count("functions", 3);
+    diagnostics.skip("function", Some(0x1000), "outside of the view");
+    diagnostics.skip("type", None, "unsupported");
+    diagnostics.warn("partial import");
+    diagnostics.store(&view);
+    ImportDiagnostics::new("Other Importer").store(&view);
+
+    let stored = ImportDiagnostics::for_importer(&view, "Test Importer")
+        .expect("Diagnostics were not stored");
+    assert_eq!(stored, diagnostics);
+    let all = ImportDiagnostics::for_view(&view);
+    let importers: Vec<_> = all.iter().map(|d| d.importer()).collect();
+    assert_eq!(importers, ["Other Importer", "Test Importer"]);
+
+    // A new import replaces the diagnostics of the last one
+    ImportDiagnostics::new("Test Importer").store(&view);
+    let stored = ImportDiagnostics::for_importer(&view, "Test Importer").unwrap();
+    assert!(stored.is_empty());
+}
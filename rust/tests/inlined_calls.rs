@@ -0,0 +1,54 @@
+use binaryninja::binary_view::{BinaryViewBase, BinaryViewExt};
+use binaryninja::headless::Session;
+use binaryninja::inlined_calls::{InlinedCall, InlinedCallTable, INLINED_CALL_TAG_TYPE};
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_store_inlined_calls(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let entry = view.entry_point();
+    assert!(InlinedCallTable::for_importer(&view, "Test Importer").is_none());
+
+    let mut table = InlinedCallTable::new("Test Importer");
+    table.add(InlinedCall {
+        callee: "outer".to_string(),
+        ranges: vec![entry..entry + 0x10, entry + 0x20..entry + 0x28],
+        depth: 0,
+        call_line: Some(12),
+    });
+    table.add(InlinedCall {
+        callee: "inner".to_string(),
+        ranges: std::iter::once(entry + 4..entry + 8).collect(),
+        depth: 1,
+        call_line: None,
+    });
+    table.store(&view);
+
+    let stored = InlinedCallTable::for_importer(&view, "Test Importer")
+        .expect("Inlined calls were not stored");
+    assert_eq!(stored, table);
+    let callees: Vec<_> = stored
+        .calls_at(entry + 5)
+        .iter()
+        .map(|call| call.callee.as_str())
+        .collect();
+    assert_eq!(callees, ["outer", "inner"]);
+    assert_eq!(InlinedCallTable::for_view(&view).len(), 1);
+
+    assert_eq!(stored.annotate(&view), 2);
+    assert!(view.tag_type_by_name(INLINED_CALL_TAG_TYPE).is_some());
+    let functions = view.functions_containing(entry);
+    let func = functions.get(0);
+    assert_eq!(func.tags_at(entry, Some(true), None).len(), 1);
+    // Tags already added aren't added again
+    assert_eq!(stored.annotate(&view), 0);
+    assert_eq!(func.tags_at(entry, Some(true), None).len(), 1);
+}
@@ -0,0 +1,104 @@
+use binaryninja::binary_view::BinaryView;
+use binaryninja::headless::Session;
+use binaryninja::interaction::{
+    get_choice_input, get_integer_input, get_text_line_input, register_interaction_handler,
+    run_progress_dialog, show_markdown_report, FormInputBuilder, FormInputField, FormResponses,
+    InteractionHandler, MessageBoxButtonResult, MessageBoxButtonSet, MessageBoxIcon,
+    ReportCollection,
+};
+use rstest::*;
+use std::sync::Mutex;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+/// The title and plain text of the reports shown to the handler.
+static REPORTS: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+/// Answers every prompt with scripted values and records the reports it is shown.
+struct ScriptedHandler;
+
+impl InteractionHandler for ScriptedHandler {
+    fn show_message_box(
+        &self,
+        _title: &str,
+        _text: &str,
+        _buttons: MessageBoxButtonSet,
+        _icon: MessageBoxIcon,
+    ) -> MessageBoxButtonResult {
+        MessageBoxButtonResult::OKButton
+    }
+
+    fn get_form_input(&self, fields: &mut [FormInputField], title: &str) -> bool {
+        if title == "Cancelled" {
+            return false;
+        }
+        for field in fields {
+            match field {
+                FormInputField::TextLine { prompt, value, .. } => *value = format!("{prompt}!"),
+                FormInputField::Integer { default, value, .. } => *value = default.unwrap_or(0) + 1,
+                FormInputField::Choice { choices, value, .. } => *value = choices.len() - 1,
+                _ => {}
+            }
+        }
+        true
+    }
+
+    fn show_plain_text_report(&self, _view: Option<&BinaryView>, title: &str, contents: &str) {
+        let report = (title.to_string(), contents.to_string());
+        REPORTS.lock().unwrap().push(report);
+    }
+}
+
+#[rstest]
+fn test_interaction_handler(_session: &Session) {
+    register_interaction_handler(ScriptedHandler);
+
+    assert_eq!(get_text_line_input("Name", "Title"), Some("Name!".into()));
+    assert_eq!(get_integer_input("Count", "Title"), Some(1));
+    assert_eq!(get_text_line_input("Name", "Cancelled"), None);
+    assert_eq!(get_choice_input("Pick", "Title", &["a", "b", "c"]), Some(2));
+
+    let responses = FormInputBuilder::new()
+        .label_field("Details")
+        .text_field("Name", None)
+        .integer_field("Count", Some(41))
+        .choice_field("Pick", &["a", "b"], Some(0))
+        .get_form_input("Form");
+    assert!(matches!(responses[0], FormResponses::None));
+    assert!(matches!(&responses[1], FormResponses::String(name) if name == "Name!"));
+    assert!(matches!(responses[2], FormResponses::Integer(42)));
+    assert!(matches!(responses[3], FormResponses::Index(1)));
+
+    let progress = Mutex::new(vec![]);
+    run_progress_dialog("Working", true, |report| {
+        for cur in 0..3 {
+            progress.lock().unwrap().push(cur);
+            report(cur, 3).unwrap();
+        }
+    })
+    .unwrap();
+    assert_eq!(*progress.lock().unwrap(), vec![0, 1, 2]);
+
+    show_markdown_report(None, "Markdown", "# Heading", "Heading");
+    let reports = ReportCollection::new();
+    reports.add_plain_text(None, "First", "one");
+    reports.add_html(None, "Second", "<b>two</b>", "two");
+    assert_eq!(reports.len(), 2);
+    let second = reports.get(1).expect("Second report");
+    assert_eq!(second.title, "Second");
+    assert!(reports.get(2).is_none());
+    reports.show("Collection");
+
+    assert_eq!(
+        *REPORTS.lock().unwrap(),
+        vec![
+            ("Markdown".to_string(), "Heading".to_string()),
+            ("First".to_string(), "one".to_string()),
+            ("Second".to_string(), "two".to_string()),
+        ]
+    );
+}
@@ -0,0 +1,89 @@
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::disassembly::DisassemblySettings;
+use binaryninja::headless::Session;
+use binaryninja::linear_view::{LinearViewObject, LinearViewObjectLocation};
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_object_hierarchy(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let settings = DisassemblySettings::new();
+    let root = LinearViewObject::disassembly(&view, &settings);
+
+    let first = root.first_child().expect("Root has no children");
+    let last = root.last_child().unwrap();
+    assert!(root.previous_child(&first).is_none());
+    assert!(root.next_child(&last).is_none());
+    assert!(root.compare_children(&first, &last).is_le());
+    assert_eq!(root.ordering_index_for_child(&first), 0);
+    assert!(root.ordering_index_total() > 0);
+
+    let identifier = first.identifier();
+    let found = root.child_for_identifier(&identifier).unwrap();
+    assert_eq!(found.identifier(), identifier);
+
+    let functions = view.functions();
+    let func = functions.get(0);
+    let child = root.child_for_address(func.start()).unwrap();
+    assert!(child.start() <= func.start() && func.start() < child.end());
+}
+
+#[rstest]
+fn test_single_function_objects(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let settings = DisassemblySettings::new();
+    let functions = view.functions();
+    let func = functions.get(0);
+    for object in [
+        LinearViewObject::single_function_llil(&func, &settings),
+        LinearViewObject::single_function_mapped_mlil(&func, &settings),
+        LinearViewObject::single_function_hlil(&func, &settings),
+    ] {
+        let cursor = object.create_cursor();
+        assert!(cursor.valid());
+        assert!(!cursor.lines().is_empty());
+    }
+}
+
+#[rstest]
+fn test_cursor_seek(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let settings = DisassemblySettings::new();
+    let root = LinearViewObject::hlil(&view, &settings);
+    let functions = view.functions();
+    let func = functions.get(0);
+
+    let cursor = root.create_cursor();
+    cursor.seek_to_address(func.start());
+    let path = cursor.path().to_vec();
+    assert_eq!(path.len(), cursor.path_objects().len());
+    assert!(path
+        .iter()
+        .any(|identifier| identifier.location != LinearViewObjectLocation::Single));
+
+    // Find the same position from another cursor
+    let other = root.create_cursor();
+    assert!(other.seek_to_path_and_address(&path, func.start()));
+    assert_eq!(other.path().to_vec(), path);
+    let other = root.create_cursor();
+    assert!(other.seek_to_cursor_path(&cursor));
+    assert_eq!(other.path().to_vec(), path);
+
+    // Ordering indices count from the start of the view
+    cursor.seek_to_start();
+    assert_eq!(cursor.ordering_index().start, 0);
+    let total = cursor.ordering_index_total();
+    cursor.seek_to_ordering_index(total / 2);
+    assert!(cursor.ordering_index().start <= total / 2);
+    assert!(cursor.ordering_index().end > 0);
+}
@@ -0,0 +1,59 @@
+use binaryninja::headless::Session;
+use binaryninja::logger::{register_listener, Level, LogListener, Logger};
+use log::LevelFilter;
+use rstest::*;
+use std::ffi::CStr;
+use std::sync::Mutex;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+/// The logger name and message of the messages logged by the test loggers.
+static MESSAGES: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
+
+struct CapturingListener;
+
+impl LogListener for CapturingListener {
+    fn log(&self, _session: usize, _level: Level, msg: &CStr, logger_name: &CStr, _tid: usize) {
+        let logger_name = logger_name.to_string_lossy();
+        if logger_name.starts_with("RustTest") {
+            let msg = msg.to_string_lossy().trim().to_string();
+            MESSAGES
+                .lock()
+                .unwrap()
+                .push((logger_name.to_string(), msg));
+        }
+    }
+
+    fn level(&self) -> Level {
+        Level::DebugLog
+    }
+}
+
+#[rstest]
+fn test_logger_and_listener(_session: &Session) {
+    let _guard = register_listener(CapturingListener);
+
+    let logger = Logger::new_with_session("RustTestDirect", 0);
+    logger.log_string(Level::WarningLog, "direct message");
+    assert!(Logger::names().iter().any(|name| name == "RustTestDirect"));
+    let found = Logger::get("RustTestDirect", 0).expect("Logger was created");
+    assert_eq!(found.session_id(), 0);
+
+    Logger::new("RustTestFacade")
+        .with_level(LevelFilter::Info)
+        .init();
+    log::info!("facade message");
+    log::debug!("filtered message");
+
+    assert_eq!(
+        *MESSAGES.lock().unwrap(),
+        vec![
+            ("RustTestDirect".to_string(), "direct message".to_string()),
+            ("RustTestFacade".to_string(), "facade message".to_string()),
+        ]
+    );
+}
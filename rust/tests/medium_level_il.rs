@@ -1,6 +1,9 @@
 use binaryninja::binary_view::BinaryViewExt;
 use binaryninja::headless::Session;
-use binaryninja::medium_level_il::{MediumLevelILInstructionKind, MediumLevelInstructionIndex};
+use binaryninja::medium_level_il::{
+    MediumLevelILInstructionKind, MediumLevelILLiftedInstructionKind, MediumLevelInstructionIndex,
+    VisitorAction,
+};
 use rstest::*;
 use std::path::PathBuf;
 
@@ -86,3 +89,49 @@ fn test_mlil_info(_session: &Session) {
         _ => panic!("Expected Ret"),
     }
 }
+
+#[rstest]
+fn test_mlil_visitor(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let entry_function = view.entry_point_function().unwrap();
+    let mlil_function = entry_function.medium_level_il().unwrap();
+    assert_eq!(
+        mlil_function.instructions().count(),
+        mlil_function.instruction_count()
+    );
+    assert!(mlil_function.instructions().all(|instr| instr.address != 0));
+
+    for instr in mlil_function.instructions() {
+        let lifted = instr.lift();
+        let mut pre_order = Vec::new();
+        lifted.visit_tree(&mut |expr| {
+            pre_order.push(expr.clone());
+            VisitorAction::Descend
+        });
+        let mut post_order = Vec::new();
+        lifted.visit_tree_post_order(&mut |expr| post_order.push(expr.clone()));
+        assert_eq!(pre_order.len(), post_order.len());
+        assert_eq!(pre_order.first(), Some(&lifted));
+        assert_eq!(post_order.last(), Some(&lifted));
+        assert_eq!(lifted.find_all(|_| true), pre_order);
+
+        // Skipping the children of the root only visits the root
+        let mut visited = 0;
+        lifted.visit_tree(&mut |_| {
+            visited += 1;
+            VisitorAction::Sibling
+        });
+        assert_eq!(visited, 1);
+    }
+
+    let calls = mlil_function
+        .instructions()
+        .flat_map(|instr| {
+            instr
+                .lift()
+                .find_all(|expr| matches!(expr.kind, MediumLevelILLiftedInstructionKind::Call(_)))
+        })
+        .count();
+    assert!(calls > 0);
+}
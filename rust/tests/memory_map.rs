@@ -0,0 +1,77 @@
+use binaryninja::binary_view::{BinaryViewBase, BinaryViewExt};
+use binaryninja::data_buffer::DataBuffer;
+use binaryninja::headless::Session;
+#[cfg(feature = "serde")]
+use binaryninja::memory_map::MemoryRegionBacking;
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_data_region(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let memory_map = view.memory_map();
+
+    let start = 0xc000_0000;
+    let data = DataBuffer::new(&[0x90; 0x100]).unwrap();
+    memory_map
+        .add_data_region("rom", start, &data, 0x4 | 0x1)
+        .unwrap();
+    assert_eq!(view.read_vec(start, 4), [0x90; 4]);
+    assert_eq!(memory_map.active_region_at(start).as_deref(), Some("rom"));
+    assert_eq!(memory_map.region_flags("rom"), 0x4 | 0x1);
+
+    assert!(memory_map.set_region_enabled("rom", false));
+    assert!(!memory_map.is_region_enabled("rom"));
+    assert!(memory_map.set_region_enabled("rom", true));
+    assert!(memory_map.remove_region("rom"));
+    assert!(!memory_map.remove_region("rom"));
+    assert_ne!(memory_map.active_region_at(start).as_deref(), Some("rom"));
+}
+
+#[cfg(feature = "serde")]
+#[rstest]
+fn test_regions(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let memory_map = view.memory_map();
+    assert!(!memory_map.regions().is_empty());
+
+    let start = 0xc000_0000;
+    let data = DataBuffer::new(&[0x90; 0x100]).unwrap();
+    memory_map
+        .add_data_region("rom", start, &data, 0x4 | 0x1)
+        .unwrap();
+    let region = memory_map.region_at(start).unwrap();
+    assert!(region.address_range.contains(&start));
+    let object = region.active_object().unwrap();
+    assert_eq!(object.name, "rom");
+    assert_eq!(object.backing, MemoryRegionBacking::Data);
+}
+
+#[rstest]
+fn test_remapped_region(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let memory_map = view.memory_map();
+    let source = view.start()..view.start() + 0x10;
+    let bytes = view.read_vec(source.start, 0x10);
+
+    let start = 0xd000_0000;
+    memory_map
+        .add_remapped_region("mirror", start, source, 0x4)
+        .unwrap();
+    assert_eq!(view.read_vec(start, 0x10), bytes);
+    assert!(memory_map
+        .add_remapped_region("unreadable", start, 0xffff_0000..0xffff_0010, 0x4)
+        .is_err());
+
+    memory_map.reset();
+    assert!(memory_map.active_region_at(start).is_none());
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "serde")]
+
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::headless::Session;
+use binaryninja::metadata::{from_metadata, to_metadata, Metadata, MetadataType};
+use binaryninja::rc::Ref;
+use rstest::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+enum Mode {
+    Fast,
+    Depth(u32),
+    Range { start: u64, end: u64 },
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct PluginState {
+    name: String,
+    offset: i32,
+    ratio: f64,
+    enabled: bool,
+    comment: Option<String>,
+    addresses: Vec<u64>,
+    names: HashMap<u64, String>,
+    modes: Vec<Mode>,
+}
+
+#[rstest]
+fn test_metadata_serde_round_trip(_session: &Session) {
+    let state = PluginState {
+        name: "state".into(),
+        offset: -4,
+        ratio: 0.5,
+        enabled: true,
+        comment: None,
+        addresses: vec![0x1000, 0x2000],
+        names: HashMap::from([(0x1000, "main".into())]),
+        modes: vec![Mode::Fast, Mode::Depth(3), Mode::Range { start: 1, end: 2 }],
+    };
+    let metadata = to_metadata(&state).unwrap();
+    assert_eq!(metadata.get_type(), MetadataType::KeyValueDataType);
+    // Absent values are left out of the store
+    assert!(metadata.get("comment").unwrap().is_none());
+    assert_eq!(from_metadata::<PluginState>(&metadata).unwrap(), state);
+
+    // Raw data reads as bytes
+    let raw: Ref<Metadata> = (&vec![1u8, 2, 3]).into();
+    assert_eq!(from_metadata::<Vec<u8>>(&raw).unwrap(), vec![1, 2, 3]);
+    assert!(from_metadata::<PluginState>(&raw).is_err());
+}
+
+#[rstest]
+fn test_view_serde_metadata(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let modes = vec![Mode::Depth(1), Mode::Fast];
+    view.store_serde_metadata("rust.modes", &modes, false)
+        .unwrap();
+    let read: Vec<Mode> = view.get_serde_metadata("rust.modes").unwrap().unwrap();
+    assert_eq!(read, modes);
+    assert!(view
+        .get_serde_metadata::<Vec<Mode>, _>("rust.missing")
+        .is_none());
+}
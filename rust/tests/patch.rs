@@ -0,0 +1,160 @@
+use binaryninja::binary_view::{BinaryViewBase, BinaryViewExt};
+use binaryninja::headless::Session;
+use binaryninja::patch::{compare, PatchError, PatchFormat, Patcher, WriteTarget};
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_raw_write_and_save(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let original_path = out_dir.join("atox.obj");
+    let view = binaryninja::load(&original_path).expect("Failed to create view");
+    let patcher = Patcher::new(&view)
+        .expect("Failed to create patcher")
+        .with_target(WriteTarget::Raw);
+    assert!(patcher.modified_ranges().is_empty());
+
+    let functions = view.functions();
+    let address = functions.iter().next().expect("No functions").start();
+    let offset = patcher
+        .file_offset(address)
+        .expect("Function is not backed by the file");
+    patcher
+        .write(address, &[0xcc, 0xcc])
+        .expect("Failed to write raw view");
+    assert_eq!(patcher.modified_ranges(), vec![offset..offset + 2]);
+    assert_eq!(view.read_vec(address, 2), vec![0xcc, 0xcc]);
+    assert!(patcher.conflicts().is_empty());
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let patched_path = temp_dir.path().join("atox.patched.obj");
+    std::fs::copy(&original_path, &patched_path).unwrap();
+    let written = patcher
+        .save_modifications_to(&patched_path)
+        .expect("Failed to save modifications");
+    assert_eq!(written, vec![offset..offset + 2]);
+
+    let original = std::fs::read(&original_path).unwrap();
+    let patched = std::fs::read(&patched_path).unwrap();
+    assert_eq!(original.len(), patched.len());
+    let differing: Vec<usize> = (0..original.len())
+        .filter(|&i| original[i] != patched[i])
+        .collect();
+    assert!(differing
+        .iter()
+        .all(|&i| (offset..offset + 2).contains(&(i as u64))));
+    assert_eq!(
+        &patched[offset as usize..offset as usize + 2],
+        &[0xcc, 0xcc]
+    );
+}
+
+#[rstest]
+fn test_unbacked_raw_write(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let patcher = Patcher::new(&view)
+        .expect("Failed to create patcher")
+        .with_target(WriteTarget::Raw);
+    let unmapped = view.start() + view.len() + 0x1000;
+    assert!(patcher.file_offset(unmapped).is_none());
+    assert!(matches!(
+        patcher.write(unmapped, &[0x90]),
+        Err(PatchError::NotFileBacked(address)) if address == unmapped
+    ));
+    assert!(!view.offset_valid(unmapped));
+}
+
+#[rstest]
+fn test_save_to_missing_file(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let patcher = Patcher::new(&view).expect("Failed to create patcher");
+    let temp_dir = tempfile::tempdir().unwrap();
+    assert!(matches!(
+        patcher.save_modifications_to(temp_dir.path().join("missing.obj")),
+        Err(PatchError::Io(_))
+    ));
+}
+
+#[rstest]
+fn test_export_and_apply(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let original_path = out_dir.join("atox.obj");
+    let original = std::fs::read(&original_path).unwrap();
+
+    let view = binaryninja::load(&original_path).expect("Failed to create view");
+    let patcher = Patcher::new(&view)
+        .expect("Failed to create patcher")
+        .with_target(WriteTarget::Raw);
+    let functions = view.functions();
+    let address = functions.iter().next().expect("No functions").start();
+    let offset = patcher.file_offset(address).unwrap();
+    patcher.write(address, &[0x90, 0x90, 0xc3]).unwrap();
+
+    for format in [PatchFormat::Ips, PatchFormat::Bps, PatchFormat::Dif] {
+        let patch = patcher.export(format, &original).expect("Failed to export");
+        assert_eq!(
+            PatchFormat::from_extension(format.extension()),
+            Some(format)
+        );
+
+        // Applying to a freshly opened copy of the file reproduces the patch
+        let fresh = binaryninja::load(&original_path).expect("Failed to create view");
+        let fresh_patcher = Patcher::new(&fresh).expect("Failed to create patcher");
+        let changed = fresh_patcher
+            .apply(format, &patch)
+            .expect("Failed to apply patch");
+        assert!(!changed.is_empty(), "{}", format);
+        assert!(changed
+            .iter()
+            .all(|range| range.start >= offset && range.end <= offset + 3));
+        assert_eq!(fresh.read_vec(address, 3), vec![0x90, 0x90, 0xc3]);
+
+        // Patches that check the original bytes refuse to apply twice
+        if format != PatchFormat::Ips {
+            assert!(matches!(
+                fresh_patcher.apply(format, &patch),
+                Err(PatchError::SourceMismatch(..))
+            ));
+        }
+    }
+}
+
+#[rstest]
+fn test_compare_masks_relocations(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let path = out_dir.join("atox.obj");
+    let view = binaryninja::load(&path).expect("Failed to create view");
+    let range = view.start()..view.end();
+    assert!(compare::differences(&view, &view, range.clone()).is_empty());
+
+    // Relocated bytes are not reported
+    let unpatched = compare::differences_from_file(&view, range.clone(), &path).unwrap();
+    for reloc in view.relocation_ranges_in(range.clone()) {
+        assert!(!unpatched
+            .iter()
+            .any(|diff| diff.start < reloc.end && reloc.start < diff.end));
+    }
+
+    let address = view
+        .functions()
+        .iter()
+        .next()
+        .expect("No functions")
+        .start();
+    let original = view.read_vec(address, 1)[0];
+    assert_eq!(view.write(address, &[!original]), 1);
+    assert!(!unpatched.iter().any(|diff| diff.contains(&address)));
+    let patched = compare::differences_from_file(&view, range, &path).unwrap();
+    assert!(patched.iter().any(|diff| diff.contains(&address)));
+    let differences = compare::differences_from_bytes(&view, address, &[original]);
+    assert_eq!(differences.len(), 1);
+    assert_eq!(differences[0], address..address + 1);
+}
@@ -0,0 +1,70 @@
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::file_metadata::{SaveOption, SaveSettings};
+use binaryninja::headless::Session;
+use binaryninja::pipeline::{AnalysisOutcome, Pipeline, PipelineError};
+use binaryninja::symbol::{SymbolBuilder, SymbolType};
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_pipeline_save_database(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let temp_dir = tempfile::tempdir().unwrap();
+    let database_path = temp_dir.path().join("atox.obj.bndb");
+    let settings = SaveSettings::new();
+    settings
+        .set_option(SaveOption::RemoveUndoData, true)
+        .set_name("pipeline");
+    assert!(settings.is_option_set(SaveOption::RemoveUndoData));
+    assert_eq!(settings.name().as_str(), "pipeline");
+
+    let result = Pipeline::new(out_dir.join("atox.obj"))
+        .stage("rename_entry", |view| {
+            let entry = view
+                .entry_point_function()
+                .ok_or_else(|| "no entry point".to_string())?;
+            let symbol = SymbolBuilder::new(SymbolType::Function, "test", entry.start()).create();
+            view.define_user_symbol(&symbol);
+            Ok(())
+        })
+        .save_settings(&settings)
+        .save_database(&database_path)
+        .run()
+        .expect("Pipeline failed");
+    assert_eq!(result.analysis, AnalysisOutcome::Completed);
+    assert_eq!(result.database.as_deref(), Some(database_path.as_path()));
+    let stages: Vec<_> = result.timings.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(stages, ["open", "rename_entry", "analysis", "save"]);
+
+    // Reopening the database goes through the same pipeline.
+    let reopened = Pipeline::new(&database_path)
+        .skip_analysis()
+        .run()
+        .expect("Failed to reopen database");
+    assert_eq!(reopened.analysis, AnalysisOutcome::Skipped);
+    assert!(reopened.view.file().is_database_backed());
+    assert!(reopened.view.symbol_by_raw_name("test").is_some());
+}
+
+#[rstest]
+fn test_pipeline_errors(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let result = Pipeline::new(out_dir.join("atox.obj"))
+        .debug_info("Not A Real Parser")
+        .run();
+    assert!(matches!(
+        result,
+        Err(PipelineError::UnknownDebugInfoParser(_))
+    ));
+
+    let result = Pipeline::new(out_dir.join("atox.obj"))
+        .stage("fail", |_| Err("expected failure".to_string()))
+        .run();
+    assert!(matches!(result, Err(PipelineError::StageFailed { .. })));
+}
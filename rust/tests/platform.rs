@@ -1,3 +1,4 @@
+use binaryninja::architecture::{CoreRegister, Register};
 use binaryninja::headless::Session;
 use binaryninja::platform::Platform;
 use rstest::*;
@@ -31,3 +32,35 @@ fn test_platform_calling_conventions(_session: &Session) {
     let platform = Platform::by_name("windows-x86_64").expect("windows-x86_64 exists");
     assert_eq!(platform.calling_conventions().len(), 1);
 }
+
+#[rstest]
+fn test_platform_calling_convention_infos(_session: &Session) {
+    let platform = Platform::by_name("windows-x86_64").expect("windows-x86_64 exists");
+    let infos = platform.calling_convention_infos();
+    let info = infos.first().expect("No calling conventions");
+    let names = |regs: &[CoreRegister]| {
+        regs.iter()
+            .map(|reg| reg.name().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(names(&info.int_arg_registers), ["rcx", "rdx", "r8", "r9"]);
+    assert_eq!(
+        info.return_int_reg
+            .map(|reg| reg.name().to_string())
+            .as_deref(),
+        Some("rax")
+    );
+    assert!(info.arg_registers_shared_index);
+    assert!(info.reserved_stack_space_for_arg_registers);
+    assert!(names(&info.callee_saved_registers).contains(&"rbx".to_string()));
+
+    // A float argument takes the second slot, so the next integer argument goes in r8
+    let assignment = info.arg_register_assignment(&[false, true, false]);
+    assert_eq!(
+        assignment
+            .iter()
+            .map(|reg| reg.map(|reg| reg.name().to_string()))
+            .collect::<Vec<_>>(),
+        [Some("rcx".into()), Some("xmm1".into()), Some("r8".into())]
+    );
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "platform_types")]
+
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::headless::Session;
+use binaryninja::platform::Platform;
+use binaryninja::platform_types::{install_platform_types, PlatformTypeSet};
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+fn member_offset(platform: &Platform, set: PlatformTypeSet, ty: &str, member: &str) -> u64 {
+    let types = set.types(platform).expect("Failed to parse types");
+    let named = types
+        .iter()
+        .find(|named| named.name.to_string() == ty)
+        .expect("Type exists");
+    let structure = named.ty.get_structure().expect("Type is a structure");
+    structure
+        .members()
+        .into_iter()
+        .find(|m| m.name == member)
+        .expect("Member exists")
+        .offset
+}
+
+#[rstest]
+fn test_layout_follows_platform(_session: &Session) {
+    let x86 = Platform::by_name("windows-x86").expect("windows-x86 exists");
+    let x86_64 = Platform::by_name("windows-x86_64").expect("windows-x86_64 exists");
+    let set = PlatformTypeSet::Windows;
+    assert_eq!(
+        member_offset(&x86, set, "_TEB", "ProcessEnvironmentBlock"),
+        0x30
+    );
+    assert_eq!(
+        member_offset(&x86_64, set, "_TEB", "ProcessEnvironmentBlock"),
+        0x60
+    );
+    assert_eq!(member_offset(&x86_64, set, "_PEB", "Ldr"), 0x18);
+    assert_eq!(
+        member_offset(&x86_64, set, "_RTL_USER_PROCESS_PARAMETERS", "CommandLine"),
+        0x70
+    );
+
+    let linux = Platform::by_name("linux-x86_64").expect("linux-x86_64 exists");
+    assert_eq!(
+        member_offset(&linux, PlatformTypeSet::Glibc, "_IO_FILE", "_fileno"),
+        0x70
+    );
+}
+
+#[rstest]
+fn test_install_platform_types(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let names = install_platform_types(&view, PlatformTypeSet::Windows).unwrap();
+    assert!(names.iter().any(|name| name.to_string() == "PEB"));
+    assert!(view.type_by_name("PEB").is_some());
+    assert!(view.type_by_name("LDR_DATA_TABLE_ENTRY").is_some());
+
+    // Installing again replaces the types
+    let type_count = view.types().len();
+    install_platform_types(&view, PlatformTypeSet::Windows).unwrap();
+    assert_eq!(view.types().len(), type_count);
+}
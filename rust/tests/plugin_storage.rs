@@ -0,0 +1,70 @@
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::headless::Session;
+use binaryninja::plugin_storage::PluginStorage;
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_plugin_storage(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    assert!(PluginStorage::new(&view, "").is_err());
+
+    let storage = PluginStorage::new(&view, "com.example.first").unwrap();
+    let other = PluginStorage::new(&view, "com.example.second").unwrap();
+    assert!(storage.get::<u64>("runs").is_none());
+    storage.set("runs", 3u64).unwrap();
+    storage.set("name", "atox").unwrap();
+    other.set("runs", "not a number").unwrap();
+    assert_eq!(storage.get::<u64>("runs").unwrap().unwrap(), 3);
+    assert_eq!(storage.get::<String>("name").unwrap().unwrap(), "atox");
+    assert!(storage.get::<u64>("name").unwrap().is_err());
+    assert!(other.get::<u64>("runs").unwrap().is_err());
+    assert_eq!(storage.keys(), ["name", "runs"]);
+
+    assert!(storage.remove("name"));
+    assert!(!storage.remove("name"));
+    assert!(!storage.contains("name"));
+    other.clear();
+    assert!(other.keys().is_empty());
+    assert!(storage.contains("runs"));
+}
+
+#[rstest]
+fn test_plugin_storage_limit(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let storage = PluginStorage::new(&view, "com.example.limited")
+        .unwrap()
+        .with_limit(64);
+    storage.set("small", "value").unwrap();
+    let size = storage.size();
+    assert!(size > 0 && size <= 64);
+    assert!(storage.set("large", "x".repeat(64)).is_err());
+    assert!(!storage.contains("large"));
+    assert_eq!(storage.size(), size);
+    // Replacing a value only counts the new one
+    storage.set("small", "other").unwrap();
+}
+
+#[rstest]
+fn test_plugin_storage_persists(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let temp_dir = tempfile::tempdir().unwrap();
+    let database_path = temp_dir.path().join("atox.obj.bndb");
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let storage = PluginStorage::new(&view, "com.example.persisted").unwrap();
+    storage.set("runs", 7u64).unwrap();
+    assert!(view.file().create_database(&database_path));
+    view.file().close();
+
+    let view = binaryninja::load(&database_path).expect("Failed to load database");
+    let storage = PluginStorage::new(&view, "com.example.persisted").unwrap();
+    assert_eq!(storage.get::<u64>("runs").unwrap().unwrap(), 7);
+}
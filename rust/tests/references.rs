@@ -0,0 +1,114 @@
+use binaryninja::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
+use binaryninja::headless::Session;
+use binaryninja::references::{
+    register_reference_provider, DataVariableAccessKind, ProvidedReference, ReferenceProvider,
+};
+use binaryninja::types::Type;
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_user_refs(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let entry = view.entry_point();
+    let target = view.end() - 1;
+
+    view.add_user_code_ref(entry, target);
+    view.add_user_data_ref(target - 8, target);
+    view.update_analysis_and_wait();
+    assert!(view
+        .code_refs_to_addr(target)
+        .iter()
+        .any(|code_ref| code_ref.address == entry));
+    assert!(view
+        .data_refs_to_addr(target)
+        .iter()
+        .any(|data_ref| data_ref.address == target - 8));
+
+    view.remove_user_code_ref(entry, target);
+    view.remove_user_data_ref(target - 8, target);
+    view.update_analysis_and_wait();
+    assert!(view
+        .code_refs_to_addr(target)
+        .iter()
+        .all(|code_ref| code_ref.address != entry));
+    assert!(view
+        .data_refs_to_addr(target)
+        .iter()
+        .all(|data_ref| data_ref.address != target - 8));
+}
+
+struct EntryPointProvider;
+
+impl ReferenceProvider for EntryPointProvider {
+    fn provide_references(&self, view: &BinaryView) -> Vec<ProvidedReference> {
+        vec![ProvidedReference::Code {
+            from: view.entry_point(),
+            to: view.end() - 2,
+        }]
+    }
+}
+
+#[rstest]
+fn test_reference_provider(_session: &Session) {
+    register_reference_provider(EntryPointProvider);
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let entry = view.entry_point();
+    let target = view.end() - 2;
+
+    view.update_analysis_and_wait();
+    assert!(view
+        .code_refs_to_addr(target)
+        .iter()
+        .any(|code_ref| code_ref.address == entry));
+    assert!(ProvidedReference::Code {
+        from: entry,
+        to: target
+    }
+    .exists(&view));
+}
+
+#[rstest]
+fn test_data_variable_accesses(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let global = view.start() + view.len() - 8;
+    view.define_user_data_var(global, &Type::int(4, true));
+
+    // Make the entry function increment the global:
+    // mov eax, [global]; inc eax; mov [global], eax; ret
+    let func = view
+        .entry_point_function()
+        .expect("Failed to get entry point function");
+    let address = (global as u32).to_le_bytes();
+    let mut code = vec![0xa1];
+    code.extend(address);
+    code.push(0x40);
+    code.push(0xa3);
+    code.extend(address);
+    code.push(0xc3);
+    assert_eq!(view.write(func.start(), &code), code.len());
+    view.update_analysis_and_wait();
+
+    let accesses = view.data_variable_accesses(global);
+    let kinds: Vec<_> = accesses.iter().map(|access| access.kind).collect();
+    assert!(kinds.contains(&DataVariableAccessKind::Read));
+    assert!(kinds.contains(&DataVariableAccessKind::Write));
+
+    let readers = view.data_variable_readers(global);
+    assert!(readers
+        .iter()
+        .any(|access| access.address() == func.start()));
+    let writers = view.data_variable_writers(global);
+    assert!(writers
+        .iter()
+        .any(|access| access.address() == func.start() + 6));
+}
@@ -0,0 +1,42 @@
+use binaryninja::binary_view::{BinaryViewBase, BinaryViewExt};
+use binaryninja::headless::Session;
+use binaryninja::relocation::{RelocationInfo, RelocationType};
+use binaryninja::symbol::{Symbol, SymbolType};
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_define_relocation(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let arch = view.default_arch().expect("View has an architecture");
+    let addr = view.start();
+    let target = view.end() - 8;
+
+    let mut info = RelocationInfo::new();
+    info.type_ = RelocationType::StandardRelocationType;
+    info.size = 8;
+    view.define_relocation(&arch, &info, target, addr);
+    let relocations = view.relocations_at(addr);
+    let relocation = relocations.iter().next().expect("Defined relocation");
+    assert_eq!(relocation.target(), target);
+    assert_eq!(relocation.address(), addr);
+    assert!(relocation.symbol().is_none());
+
+    let symbol = Symbol::builder(SymbolType::ImportedFunction, "imported", target).create();
+    view.define_auto_symbol(&symbol);
+    view.define_symbol_relocation(&arch, &info, &symbol, addr + 8);
+    let relocations = view.relocations_at(addr + 8);
+    let relocation = relocations
+        .iter()
+        .next()
+        .expect("Defined symbol relocation");
+    let relocation_symbol = relocation.symbol().expect("Relocation has a symbol");
+    assert_eq!(relocation_symbol.raw_name().as_str(), "imported");
+}
@@ -0,0 +1,115 @@
+use binaryninja::binary_view::{BinaryViewBase, BinaryViewExt};
+use binaryninja::confidence::Conf;
+use binaryninja::headless::Session;
+use binaryninja::retype::{apply_types, ConflictReason, RetypeMode, RetypeTarget};
+use binaryninja::types::Type;
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_retype_target() {
+    assert_eq!(RetypeTarget::from("0x4010"), RetypeTarget::Address(0x4010));
+    assert_eq!(RetypeTarget::from(0x10u64), RetypeTarget::Address(0x10));
+    assert_eq!(
+        RetypeTarget::from("main"),
+        RetypeTarget::Name("main".to_string())
+    );
+}
+
+#[rstest]
+fn test_apply_types(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let entry_function = view
+        .entry_point_function()
+        .expect("Failed to get entry point function");
+    let data_address = view.start() + view.len() - 8;
+    let user_address = view.start() + view.len() - 16;
+    view.define_user_data_var(user_address, &Type::int(4, true));
+
+    let prototype = Type::function(&Type::int(4, true), vec![], false);
+    let types = vec![
+        (
+            RetypeTarget::Address(entry_function.start()),
+            Conf::new(prototype.clone(), 255),
+        ),
+        (
+            RetypeTarget::Address(data_address),
+            Conf::new(Type::int(8, false), 255),
+        ),
+        (
+            RetypeTarget::Address(user_address),
+            Conf::new(Type::int(2, false), 128),
+        ),
+        (
+            RetypeTarget::Name("does_not_exist".to_string()),
+            Conf::new(Type::int(1, false), 255),
+        ),
+    ];
+
+    // A dry run reports the changes without making them
+    let report = apply_types(&view, types.clone(), RetypeMode::DryRun);
+    assert_eq!(report.applied.len(), 2);
+    assert_eq!(report.conflicts.len(), 1);
+    assert_eq!(report.conflicts[0].address, user_address);
+    assert_eq!(
+        report.conflicts[0].reason,
+        ConflictReason::ConfidentUserType
+    );
+    assert_eq!(report.unresolved.len(), 1);
+    assert!(report.undo_id.is_none());
+    assert!(view.data_variable_at_address(data_address).is_none());
+    assert!(!entry_function.has_user_type());
+
+    let report = apply_types(&view, types.clone(), RetypeMode::SkipConflicts);
+    assert!(report.undo_id.is_some());
+    assert!(entry_function.has_user_type());
+    assert_eq!(
+        view.data_variable_at_address(data_address)
+            .unwrap()
+            .ty
+            .contents
+            .width(),
+        8
+    );
+    assert_eq!(
+        view.data_variable_at_address(user_address)
+            .unwrap()
+            .ty
+            .contents
+            .width(),
+        4
+    );
+
+    // Only the conflicting user type is left to change
+    let report = apply_types(
+        &view,
+        [(user_address, Conf::new(Type::int(2, false), 128))],
+        RetypeMode::Overwrite,
+    );
+    assert_eq!(report.conflicts.len(), 1);
+    assert_eq!(report.applied.len(), 1);
+    assert_eq!(
+        view.data_variable_at_address(user_address)
+            .unwrap()
+            .ty
+            .contents
+            .width(),
+        2
+    );
+
+    // A function only takes a function type
+    let report = apply_types(
+        &view,
+        [(entry_function.start(), Type::int(4, true))],
+        RetypeMode::Overwrite,
+    );
+    assert_eq!(report.conflicts[0].reason, ConflictReason::NotAFunctionType);
+    assert!(report.applied.is_empty());
+}
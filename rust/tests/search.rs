@@ -0,0 +1,133 @@
+use binaryninja::binary_view::{BinaryViewBase, BinaryViewExt};
+use binaryninja::headless::Session;
+use binaryninja::progress::NoProgressCallback;
+use binaryninja::search::{BytePattern, FindFlag, SearchQuery};
+use binaryninja::signatures::RuleSet;
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_find_data(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let entry = view.entry_point();
+    let bytes = view.read_vec(entry, 8);
+    assert_eq!(bytes.len(), 8);
+
+    let found = view.find_next_data(view.start(), &bytes, FindFlag::FindCaseSensitive);
+    assert!(found.is_some_and(|address| address <= entry));
+    assert_eq!(
+        view.find_next_data(entry, &bytes, FindFlag::FindCaseSensitive),
+        Some(entry)
+    );
+
+    let mut matches = Vec::new();
+    let completed = view.find_all_data(
+        view.start()..view.end(),
+        &bytes,
+        FindFlag::FindCaseSensitive,
+        NoProgressCallback,
+        |address, matched| {
+            assert_eq!(matched, bytes.as_slice());
+            matches.push(address);
+            true
+        },
+    );
+    assert!(completed);
+    assert!(matches.contains(&entry));
+}
+
+#[rstest]
+fn test_find_pattern(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let entry = view.entry_point();
+    let bytes = view.read_vec(entry, 6);
+
+    // Leave the middle bytes unspecified
+    let pattern = format!(
+        "{:02x} {:02x} ?? ?? {:02x} {:02x}",
+        bytes[0], bytes[1], bytes[4], bytes[5]
+    );
+    let pattern = BytePattern::parse(&pattern).unwrap();
+    let matches = view.find_all_pattern(&pattern);
+    assert!(matches.contains(&entry));
+    assert!(matches.windows(2).all(|pair| pair[0] < pair[1]));
+    for address in &matches {
+        assert!(pattern.matches_at(&view.read_vec(*address, pattern.len())));
+    }
+
+    // Stop after the first match
+    let mut first = Vec::new();
+    let completed = view.find_all(&pattern, NoProgressCallback, |address| {
+        first.push(address);
+        false
+    });
+    assert!(!completed);
+    assert_eq!(first, matches[..1]);
+
+    // Cancel from the progress callback
+    let mut calls = 0;
+    let completed = view.find_all(
+        &pattern,
+        |_, _| {
+            calls += 1;
+            false
+        },
+        |_| true,
+    );
+    assert!(!completed);
+    assert_eq!(calls, 1);
+}
+
+#[rstest]
+fn test_search(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let entry = view.entry_point();
+    let bytes = view.read_vec(entry, 4);
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+
+    let query = SearchQuery::new(hex).range(entry..view.end());
+    let mut first = None;
+    view.search(&query, |address, matched| {
+        assert_eq!(matched, bytes.as_slice());
+        first = Some(address);
+        false
+    });
+    assert_eq!(first, Some(entry));
+}
+
+#[rstest]
+fn test_scan_signatures(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let entry = view.entry_point();
+    let bytes = view.read_vec(entry, 4);
+
+    let source = format!(
+        "rule entry : code {{ strings: $entry = {{ {:02x} {:02x} ?? {:02x} }} condition: $entry }}
+        rule missing {{ strings: $a = \"no such string in atox\" condition: any of them }}",
+        bytes[0], bytes[1], bytes[3]
+    );
+    let rules = RuleSet::compile(&source).unwrap();
+    let matches = rules.scan(&view);
+    assert_eq!(matches.len(), 1);
+    assert_eq!(matches[0].rule, "entry");
+    assert_eq!(matches[0].tags, ["code"]);
+    assert!(matches[0]
+        .strings
+        .iter()
+        .any(|string| string.address == entry && string.len == 4));
+
+    let tag_type = view.create_tag_type("Signature", "S");
+    matches[0].add_tags(&view, &tag_type);
+    let tags = view.data_tags_at(entry, Some(false));
+    assert!(tags.iter().any(|tag| tag.data().as_str() == "entry $entry"));
+}
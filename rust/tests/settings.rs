@@ -0,0 +1,125 @@
+use binaryninja::headless::Session;
+use binaryninja::settings::{QueryOptions, SettingDefault, SettingSchema, Settings, SettingsScope};
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_typed_settings(_session: &Session) {
+    let settings = Settings::new();
+    settings.register_group("rustTest", "Rust Test");
+    assert!(settings.register_setting_json(
+        "rustTest.limit",
+        r#"{
+            "title" : "Limit",
+            "type" : "number",
+            "default" : 10,
+            "description" : "A limit.",
+            "ignore" : []
+        }"#,
+    ));
+
+    let mut options = QueryOptions::new();
+    assert_eq!(
+        settings.get::<u64, _>("rustTest.limit", &mut options),
+        Some(10)
+    );
+    assert_eq!(settings.get_or("rustTest.missing", 5u64, &mut options), 5);
+
+    // A value set for a view only applies to that view
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let view_options = QueryOptions::new()
+        .with_view(&view)
+        .with_scope(SettingsScope::SettingsResourceScope);
+    settings.set("rustTest.limit", 20u64, &view_options);
+    let mut query = QueryOptions::new_with_view(&view);
+    assert_eq!(
+        settings.get::<u64, _>("rustTest.limit", &mut query),
+        Some(20)
+    );
+    assert_eq!(query.scope, SettingsScope::SettingsResourceScope);
+    assert_eq!(
+        settings.get::<u64, _>("rustTest.limit", &mut QueryOptions::new()),
+        Some(10)
+    );
+
+    assert!(settings.reset_with_opts("rustTest.limit", &view_options));
+    let mut query = QueryOptions::new_with_view(&view);
+    assert_eq!(
+        settings.get::<u64, _>("rustTest.limit", &mut query),
+        Some(10)
+    );
+}
+
+#[rstest]
+fn test_register_setting_schema(_session: &Session) {
+    let settings = Settings::new();
+    settings.register_group("rustSchema", "Rust Schema");
+    assert!(settings.register_setting(
+        "rustSchema.mode",
+        &SettingSchema::new("Mode", SettingDefault::String("fast".into()))
+            .description("How thorough the \"analysis\" is.")
+            .enum_value("fast", "Fast")
+            .enum_value("full", "Full")
+            .ignore_scope(SettingsScope::SettingsProjectScope),
+    ));
+    assert!(settings.register_setting(
+        "rustSchema.names",
+        &SettingSchema::new(
+            "Names",
+            SettingDefault::StringList(vec!["main".into(), "start".into()])
+        ),
+    ));
+    assert!(settings.register_setting(
+        "rustSchema.depth",
+        &SettingSchema::new("Depth", SettingDefault::Number(3.0)).range(1.0, 8.0),
+    ));
+    assert!(!settings.register_setting(
+        "rustSchema.ratio",
+        &SettingSchema::new("Ratio", SettingDefault::Number(f64::NAN)),
+    ));
+    assert!(!settings.register_setting(
+        "rustSchema.limit",
+        &SettingSchema::new("Limit", SettingDefault::Number(1.0)).range(0.0, f64::INFINITY),
+    ));
+
+    let mut options = QueryOptions::new();
+    assert_eq!(
+        settings.get::<String, _>("rustSchema.mode", &mut options),
+        Some("fast".to_string())
+    );
+    assert_eq!(
+        settings
+            .get_property_string("rustSchema.mode", "description")
+            .as_str(),
+        "How thorough the \"analysis\" is."
+    );
+    let enum_values: Vec<String> = settings
+        .get_property_string_list("rustSchema.mode", "enum")
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    assert_eq!(enum_values, vec!["fast", "full"]);
+    assert_eq!(
+        settings.get::<Vec<String>, _>("rustSchema.names", &mut options),
+        Some(vec!["main".to_string(), "start".to_string()])
+    );
+    assert_eq!(
+        settings.get::<u64, _>("rustSchema.depth", &mut options),
+        Some(3)
+    );
+
+    let user_options = QueryOptions::new().with_scope(SettingsScope::SettingsUserScope);
+    settings.set("rustSchema.names", vec!["entry".to_string()], &user_options);
+    assert_eq!(
+        settings.get::<Vec<String>, _>("rustSchema.names", &mut options),
+        Some(vec!["entry".to_string()])
+    );
+    assert!(settings.reset_with_opts("rustSchema.names", &user_options));
+}
@@ -0,0 +1,36 @@
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::headless::Session;
+use binaryninja::source_lines::SourceLineTable;
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_store_source_lines(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let func = view
+        .entry_point_function()
+        .expect("Failed to get entry point function");
+    let start = func.start();
+    assert!(func.source_location_at(start).is_none());
+
+    let mut table = SourceLineTable::new("Test Importer");
+    table.add(start..start + 2, "atox.c", 7, Some(3));
+    table.add(start + 2..start + 8, "atox.c", 8, None);
+    table.store(&view);
+
+    let stored = SourceLineTable::for_importer(&view, "Test Importer")
+        .expect("Source lines were not stored");
+    assert_eq!(stored, table);
+    let location = func
+        .source_location_at(start + 4)
+        .expect("Source location was not found");
+    assert_eq!(location.to_string(), "atox.c:8");
+    assert!(func.source_location_at(start + 8).is_none());
+}
@@ -0,0 +1,53 @@
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::headless::Session;
+use binaryninja::symbol::{Symbol, SymbolType};
+use binaryninja::symbol_name_transformer::*;
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_symbol_name_transformers(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let entry_function = view
+        .entry_point_function()
+        .expect("Failed to get entry point function");
+
+    register_symbol_name_transformer("strip-prefix", |_, sym_type, name| match sym_type {
+        SymbolType::Function => name.strip_prefix("fw_").map(str::to_string),
+        _ => None,
+    });
+    register_symbol_name_transformer("uppercase", |_, _, name| {
+        name.starts_with("fw_upper_").then(|| name.to_uppercase())
+    });
+    assert_eq!(symbol_name_transformers(), ["strip-prefix", "uppercase"]);
+    assert_eq!(
+        transform_symbol_name(&view, SymbolType::Function, "fw_reset"),
+        Some("reset".to_string())
+    );
+    assert_eq!(
+        transform_symbol_name(&view, SymbolType::Data, "fw_reset"),
+        None
+    );
+
+    let symbol = Symbol::builder(SymbolType::Function, "fw_reset", entry_function.start()).create();
+    view.define_auto_symbol(&symbol);
+    assert_eq!(apply_symbol_name_transformers(&view), 1);
+    let defined = view
+        .symbol_by_address(entry_function.start())
+        .expect("Symbol was not defined");
+    assert_eq!(defined.full_name().as_str(), "reset");
+    assert_eq!(defined.short_name().as_str(), "reset");
+    assert_eq!(defined.raw_name().as_str(), "fw_reset");
+
+    assert!(unregister_symbol_name_transformer("uppercase"));
+    assert!(!unregister_symbol_name_transformer("uppercase"));
+    assert!(unregister_symbol_name_transformer("strip-prefix"));
+    assert_eq!(apply_symbol_name_transformers(&view), 0);
+}
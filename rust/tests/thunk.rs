@@ -0,0 +1,34 @@
+use binaryninja::binary_view::BinaryViewExt;
+use binaryninja::headless::Session;
+use binaryninja::thunk::{resolve_thunks, ThunkKind};
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_user_thunk_kind(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let entry = view
+        .entry_point_function()
+        .expect("Failed to get entry point function");
+    // The entry point does more than jump somewhere else
+    assert!(entry.thunk().is_none());
+    assert_eq!(resolve_thunks(&view, entry.start()), entry.start());
+
+    entry.set_user_thunk_kind(Some(ThunkKind::ImportTrampoline));
+    let thunk = entry.thunk().expect("User thunk kind was not applied");
+    assert_eq!(thunk.kind, ThunkKind::ImportTrampoline);
+    assert_eq!(thunk.address, entry.start());
+    assert!(thunk.user);
+
+    entry.set_user_thunk_kind(None);
+    assert!(entry.thunk().is_none());
+    entry.clear_user_thunk_kind();
+    assert!(entry.thunk().is_none());
+}
@@ -1,9 +1,10 @@
-use binaryninja::binary_view::BinaryView;
+use binaryninja::binary_view::{BinaryView, BinaryViewExt};
 use binaryninja::file_metadata::FileMetadata;
 use binaryninja::headless::Session;
 use binaryninja::platform::Platform;
 use binaryninja::rc::Ref;
-use binaryninja::type_archive::TypeArchive;
+use binaryninja::type_archive::{TypeArchive, TypeArchiveSyncStatus};
+use binaryninja::types::{QualifiedNameAndType, Type};
 use rstest::*;
 
 #[fixture]
@@ -29,3 +30,48 @@ fn test_create_archive(_session: &Session) {
     // TODO: It seems that type archives have to be closed.
     type_archive.close();
 }
+
+#[rstest]
+fn test_archive_diff_and_sync(_session: &Session, empty_view: &BinaryView) {
+    let placeholder_platform = Platform::by_name("x86_64").expect("Failed to get platform");
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let type_archive_path = temp_dir.path().join("type_archive_1");
+    let type_archive = TypeArchive::create(type_archive_path, &placeholder_platform).unwrap();
+    let first_snapshot = type_archive.current_snapshot_id();
+    assert!(type_archive.add_type(QualifiedNameAndType::new(
+        "shared_int".into(),
+        Type::int(4, true)
+    )));
+    let second_snapshot = type_archive.current_snapshot_id();
+    let type_id = type_archive
+        .get_type_id("shared_int".into())
+        .expect("Type was added")
+        .to_string();
+
+    let diff = type_archive.diff_snapshots(&first_snapshot, &second_snapshot);
+    assert_eq!(diff.added, vec![type_id.clone()]);
+    assert!(diff.removed.is_empty());
+    assert!(diff.modified.is_empty());
+
+    let archive_id = type_archive.id().unwrap().to_string();
+    empty_view
+        .attach_type_archive(&type_archive)
+        .expect("Failed to attach archive");
+    assert!(empty_view
+        .attached_type_archives()
+        .contains_key(&archive_id));
+
+    let pulled = empty_view
+        .pull_types_from_archive(&type_archive, [type_id.as_str()])
+        .expect("Failed to pull types");
+    let view_type_id = pulled.get(&type_id).expect("Type was pulled");
+    assert!(empty_view.type_by_name("shared_int").is_some());
+    assert_eq!(
+        empty_view.type_archive_sync_status(view_type_id.as_str()),
+        TypeArchiveSyncStatus::NoChangesSyncStatus
+    );
+
+    assert!(empty_view.detach_type_archive(archive_id.as_str()));
+    type_archive.close();
+}
@@ -1,7 +1,12 @@
 use binaryninja::headless::Session;
 use binaryninja::platform::Platform;
-use binaryninja::type_parser::{CoreTypeParser, TypeParser, TypeParserError};
-use binaryninja::types::Type;
+use binaryninja::rc::Ref;
+use binaryninja::type_container::TypeContainer;
+use binaryninja::type_parser::{
+    register_type_parser, CoreTypeParser, ParsedType, TypeParser, TypeParserError,
+    TypeParserOptions, TypeParserResult,
+};
+use binaryninja::types::{QualifiedNameAndType, Type};
 use binaryninjacore_sys::BNTypeParserErrorSeverity::ErrorSeverity;
 use rstest::*;
 
@@ -83,3 +88,144 @@ fn test_parse_error(_session: &Session) {
         }]
     );
 }
+
+#[rstest]
+fn test_platform_parse_types(_session: &Session) {
+    let platform = Platform::by_name("windows-x86_64").expect("windows-x86_64 exists");
+    let options = TypeParserOptions {
+        arguments: vec!["-DFIELD_TYPE=float".to_string()],
+        ..Default::default()
+    };
+    let result = platform
+        .parse_types_with_options(
+            "struct with_macro { FIELD_TYPE field; };",
+            "test_file.h",
+            &options,
+        )
+        .expect("Parsed types");
+    assert_eq!(1, result.types.len());
+
+    let errors = platform
+        .parse_types_with_options("struct broken {", "broken.h", &options)
+        .expect_err("Parsing should fail!");
+    assert!(errors.iter().any(|e| e.file_name == "broken.h"));
+
+    let parsed_type = platform
+        .parse_type_string("int32_t")
+        .expect("Parsed int32_t");
+    assert_eq!(Type::int(4, true), parsed_type.ty);
+}
+
+/// Parses Go style `type Name int32` declarations of integer types.
+struct GoTypeParser;
+
+fn parse_go_int(source: &str, file_name: &str, line: u64) -> Result<Ref<Type>, TypeParserError> {
+    let (signed, bits) = match source.strip_prefix("uint") {
+        Some(bits) => (false, bits),
+        None => (true, source.strip_prefix("int").unwrap_or_default()),
+    };
+    match bits.parse::<usize>() {
+        Ok(bits @ (8 | 16 | 32 | 64)) => Ok(Type::int(bits / 8, signed)),
+        _ => Err(TypeParserError::new(
+            ErrorSeverity,
+            format!("unknown type `{}`", source),
+            file_name.to_string(),
+            line,
+            1,
+        )),
+    }
+}
+
+impl TypeParser for GoTypeParser {
+    fn parse_types_from_source(
+        &self,
+        source: &str,
+        file_name: &str,
+        _platform: &Platform,
+        _existing_types: &TypeContainer,
+        _options: &[String],
+        _include_dirs: &[String],
+        _auto_type_source: &str,
+    ) -> Result<TypeParserResult, Vec<TypeParserError>> {
+        let mut result = TypeParserResult::default();
+        let mut errors = vec![];
+        for (line, text) in (1..).zip(source.lines()) {
+            let Some(("type", declaration)) = text.trim().split_once(' ') else {
+                continue;
+            };
+            let Some((name, ty)) = declaration.split_once(' ') else {
+                continue;
+            };
+            match parse_go_int(ty.trim(), file_name, line) {
+                Ok(ty) => result.types.push(ParsedType::new(name.into(), ty, true)),
+                Err(error) => errors.push(error),
+            }
+        }
+        match errors.is_empty() {
+            true => Ok(result),
+            false => Err(errors),
+        }
+    }
+
+    fn parse_type_string(
+        &self,
+        source: &str,
+        _platform: &Platform,
+        _existing_types: &TypeContainer,
+    ) -> Result<QualifiedNameAndType, Vec<TypeParserError>> {
+        let ty = parse_go_int(source.trim(), "string.go", 1).map_err(|error| vec![error])?;
+        Ok(QualifiedNameAndType::new(source.trim().into(), ty))
+    }
+}
+
+#[rstest]
+fn test_custom_type_parser(_session: &Session) {
+    let platform = Platform::by_name("windows-x86_64").expect("windows-x86_64 exists");
+    let plat_type_container = platform.type_container();
+    let (_, parser) = register_type_parser("GoTypes", GoTypeParser);
+    assert_eq!(parser.name().as_str(), "GoTypes");
+
+    let result = parser
+        .parse_types_from_source(
+            "type Handle uint64\ntype Count int32\n",
+            "types.go",
+            &platform,
+            &plat_type_container,
+            &[],
+            &[],
+            "",
+        )
+        .expect("Parsed types");
+    assert_eq!(2, result.types.len());
+    assert_eq!(Type::int(8, false), *result.types[0].ty());
+
+    let errors = parser
+        .parse_types_from_source(
+            "type Handle uint64\ntype Ratio float32\n",
+            "types.go",
+            &platform,
+            &plat_type_container,
+            &[],
+            &[],
+            "",
+        )
+        .expect_err("Parsing should fail!");
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        (errors[0].file_name.as_str(), errors[0].line),
+        ("types.go", 2)
+    );
+
+    // Preprocessing defaults to the source unchanged
+    let source = parser
+        .preprocess_source(
+            "type Count int32",
+            "types.go",
+            &platform,
+            &plat_type_container,
+            &[],
+            &[],
+        )
+        .expect("Preprocessed source");
+    assert_eq!(source, "type Count int32");
+}
@@ -0,0 +1,139 @@
+use binaryninja::binary_view::BinaryView;
+use binaryninja::disassembly::{InstructionTextToken, InstructionTextTokenKind};
+use binaryninja::headless::Session;
+use binaryninja::platform::Platform;
+use binaryninja::rc::Ref;
+use binaryninja::type_container::TypeContainer;
+use binaryninja::type_printer::{
+    register_type_printer, TokenEscapingType, TypeDefinitionLine, TypePrinter,
+};
+use binaryninja::types::{QualifiedName, Type, TypeClass};
+use binaryninjacore_sys::BNTokenEscapingType::NoTokenEscapingType;
+use rstest::*;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+/// Prints integers in Go notation, with the name before the type.
+struct GoTypePrinter;
+
+impl GoTypePrinter {
+    fn type_tokens(&self, ty: &Type) -> Option<Vec<InstructionTextToken>> {
+        if ty.type_class() != TypeClass::IntegerTypeClass {
+            return None;
+        }
+        let prefix = if ty.is_signed().contents {
+            "int"
+        } else {
+            "uint"
+        };
+        Some(vec![InstructionTextToken::new(
+            format!("{}{}", prefix, ty.width() * 8),
+            InstructionTextTokenKind::TypeName,
+        )])
+    }
+}
+
+impl TypePrinter for GoTypePrinter {
+    fn get_type_tokens<T: Into<QualifiedName>>(
+        &self,
+        type_: Ref<Type>,
+        _platform: Option<Ref<Platform>>,
+        name: T,
+        _base_confidence: u8,
+        _escaping: TokenEscapingType,
+    ) -> Option<Vec<InstructionTextToken>> {
+        let mut tokens = vec![
+            InstructionTextToken::new(name.into().to_string(), InstructionTextTokenKind::Text),
+            InstructionTextToken::new(" ", InstructionTextTokenKind::Text),
+        ];
+        tokens.extend(self.type_tokens(&type_)?);
+        Some(tokens)
+    }
+
+    fn get_type_tokens_before_name(
+        &self,
+        _type_: Ref<Type>,
+        _platform: Option<Ref<Platform>>,
+        _base_confidence: u8,
+        _parent_type: Option<Ref<Type>>,
+        _escaping: TokenEscapingType,
+    ) -> Option<Vec<InstructionTextToken>> {
+        Some(vec![])
+    }
+
+    fn get_type_tokens_after_name(
+        &self,
+        type_: Ref<Type>,
+        _platform: Option<Ref<Platform>>,
+        _base_confidence: u8,
+        _parent_type: Option<Ref<Type>>,
+        _escaping: TokenEscapingType,
+    ) -> Option<Vec<InstructionTextToken>> {
+        let mut tokens = vec![InstructionTextToken::new(
+            " ",
+            InstructionTextTokenKind::Text,
+        )];
+        tokens.extend(self.type_tokens(&type_)?);
+        Some(tokens)
+    }
+
+    fn get_type_lines<T: Into<QualifiedName>>(
+        &self,
+        _type_: Ref<Type>,
+        _types: &TypeContainer,
+        _name: T,
+        _padding_cols: isize,
+        _collapsed: bool,
+        _escaping: TokenEscapingType,
+    ) -> Option<Vec<TypeDefinitionLine>> {
+        None
+    }
+
+    fn print_all_types(
+        &self,
+        _names: Vec<QualifiedName>,
+        _types: Vec<Ref<Type>>,
+        _data: Ref<BinaryView>,
+        _padding_cols: isize,
+        _escaping: TokenEscapingType,
+    ) -> Option<String> {
+        None
+    }
+}
+
+#[rstest]
+fn test_custom_type_printer(_session: &Session) {
+    let platform = Platform::by_name("windows-x86_64").expect("windows-x86_64 exists");
+    let (_, printer) = register_type_printer("GoTypes", GoTypePrinter);
+    assert_eq!(printer.name().as_str(), "GoTypes");
+
+    let tokens = printer
+        .get_type_tokens(
+            &Type::int(4, true),
+            &platform,
+            "count",
+            255,
+            NoTokenEscapingType,
+        )
+        .expect("Printed tokens");
+    let text: String = tokens.iter().map(|t| t.text).collect();
+    assert_eq!(text, "count int32");
+
+    // The strings default to the text of the tokens
+    let string = printer
+        .get_type_string(&Type::int(8, false), &platform, "size", NoTokenEscapingType)
+        .expect("Printed string");
+    assert_eq!(string.as_str(), "size uint64");
+    let after_name = printer
+        .get_type_string_after_name(&Type::int(2, true), &platform, NoTokenEscapingType)
+        .expect("Printed string");
+    assert_eq!(after_name.as_str(), " int16");
+
+    assert!(printer
+        .get_type_string(&Type::float(4), &platform, "f", NoTokenEscapingType)
+        .is_none());
+}
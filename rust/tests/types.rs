@@ -1,5 +1,9 @@
 use binaryninja::headless::Session;
-use binaryninja::types::{MemberAccess, MemberScope, StructureBuilder, StructureMember, Type};
+use binaryninja::types::{
+    BaseStructure, EnumerationBuilder, FunctionBuilder, MemberAccess, MemberScope,
+    NamedTypeReference, NamedTypeReferenceClass, StructureBuilder, StructureMember, StructureType,
+    Type,
+};
 use rstest::*;
 
 #[fixture]
@@ -48,3 +52,59 @@ fn test_structure_builder(_session: &Session) {
         }
     );
 }
+
+#[rstest]
+fn test_structure_builder_dsl(_session: &Session) {
+    let base = NamedTypeReference::new(NamedTypeReferenceClass::StructNamedTypeClass, "base");
+    let structure = StructureBuilder::new()
+        .class()
+        .base(BaseStructure::new(base, 0, 8))
+        .member_at(&Type::int(8, false), "vtable", 0)
+        .member(&Type::int(4, true), "len")
+        .bitfield(&Type::int(4, false), &[("a", 3), ("b", 5), ("c", 30)])
+        .finalize();
+    assert_eq!(
+        structure.structure_type(),
+        StructureType::ClassStructureType
+    );
+    assert_eq!(structure.base_structures().len(), 1);
+
+    let members = structure.members();
+    let names: Vec<_> = members.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, ["vtable", "len", "__bitfieldc", "__bitfield10"]);
+    let unit = members[2].ty.contents.get_structure().unwrap();
+    assert_eq!(unit.structure_type(), StructureType::UnionStructureType);
+    assert_eq!(unit.members().len(), 2);
+
+    let union = StructureBuilder::new()
+        .union()
+        .member(&Type::int(4, true), "i")
+        .member(&Type::float(4), "f")
+        .finalize();
+    assert!(union.members().iter().all(|m| m.offset == 0));
+}
+
+#[rstest]
+#[should_panic(expected = "doesn't fit")]
+fn test_structure_builder_wide_bitfield(_session: &Session) {
+    StructureBuilder::new().bitfield(&Type::int(1, false), &[("wide", 9)]);
+}
+
+#[rstest]
+fn test_enumeration_builder_sparse(_session: &Session) {
+    let enumeration: EnumerationBuilder = [("A", 1), ("B", 8), ("C", 0x100)].into_iter().collect();
+    let values: Vec<_> = enumeration.members().iter().map(|m| m.value).collect();
+    assert_eq!(values, [1, 8, 0x100]);
+}
+
+#[rstest]
+fn test_function_builder(_session: &Session) {
+    let function = FunctionBuilder::new(&Type::int(4, true))
+        .param(&Type::int(8, false), "count")
+        .variadic(true)
+        .can_return(false)
+        .finalize();
+    assert_eq!(function.parameters().unwrap().len(), 1);
+    assert!(function.has_variable_arguments().contents);
+    assert!(!function.can_return().contents);
+}
@@ -0,0 +1,71 @@
+use binaryninja::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
+use binaryninja::headless::Session;
+use binaryninja::symbol::{SymbolBuilder, SymbolType};
+use binaryninja::types::Type;
+use binaryninja::watchpoint::{Invariant, Violation, Watchpoints};
+use rstest::*;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_watchpoints(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let entry_function = view
+        .entry_point_function()
+        .expect("Failed to get entry point function");
+    let entry = entry_function.start();
+    let entry_name = entry_function.symbol().raw_name().to_string();
+
+    let data_address = view.start();
+    let data_type = Type::int(4, false);
+    view.define_user_data_var(data_address, &data_type);
+
+    let reported = Arc::new(Mutex::new(Vec::new()));
+    let handler_reported = reported.clone();
+    let watchpoints = Watchpoints::new(&view, move |_: &BinaryView, violation: &Violation| {
+        handler_reported.lock().unwrap().push(violation.clone());
+    });
+    watchpoints.add("entry function", Invariant::Function { address: entry });
+    let symbol_id = watchpoints.add(
+        "entry symbol",
+        Invariant::Symbol {
+            address: entry,
+            name: entry_name,
+        },
+    );
+    let data_id = watchpoints.add(
+        "header",
+        Invariant::DataVariableType {
+            address: data_address,
+            ty: data_type,
+        },
+    );
+    assert_eq!(watchpoints.len(), 3);
+    assert!(watchpoints.check().is_empty());
+
+    // Clobber the symbol and the data variable type.
+    let symbol = SymbolBuilder::new(SymbolType::Function, "clobbered", entry).create();
+    view.define_user_symbol(&symbol);
+    view.define_user_data_var(data_address, &Type::int(8, true));
+    // Analysis completing may report these before we check explicitly, either way once only.
+    watchpoints.check();
+    assert_eq!(watchpoints.violated(), [symbol_id, data_id]);
+    let mut reported_violations = reported.lock().unwrap().clone();
+    reported_violations.sort_by_key(|violation| violation.id);
+    let reported_ids: Vec<_> = reported_violations.iter().map(|v| v.id).collect();
+    assert_eq!(reported_ids, [symbol_id, data_id]);
+    assert_eq!(reported_violations[0].address, Some(entry));
+
+    // Still violated, but already reported.
+    assert!(watchpoints.check().is_empty());
+    assert!(watchpoints.remove(data_id));
+    assert!(!watchpoints.remove(data_id));
+    assert_eq!(watchpoints.violated(), [symbol_id]);
+}
@@ -0,0 +1,59 @@
+use binaryninja::binary_view::{BinaryViewBase, BinaryViewExt};
+use binaryninja::headless::Session;
+use binaryninja::work_queue::{WorkItemState, WorkQueue};
+use rstest::*;
+use std::path::PathBuf;
+
+#[fixture]
+#[once]
+fn session() -> Session {
+    Session::new().expect("Failed to initialize session")
+}
+
+#[rstest]
+fn test_work_queue(_session: &Session) {
+    let out_dir = env!("OUT_DIR").parse::<PathBuf>().unwrap();
+    let view = binaryninja::load(out_dir.join("atox.obj")).expect("Failed to create view");
+    let entry = view.entry_point();
+    let queue = WorkQueue::new(&view);
+    assert!(queue.items().is_empty());
+
+    let first = queue.create(entry + 4, "second by address");
+    let second = queue.create(entry, "first by address");
+    assert_eq!(first.state, WorkItemState::Open);
+    let items = queue.items();
+    assert_eq!(items, [second.clone(), first.clone()]);
+    let tag = view.tag_by_id(first.id.as_str()).unwrap();
+    assert_eq!(tag.data().as_str(), "[open] second by address");
+
+    let claimed = queue.claim(&first.id, "alice").unwrap();
+    assert_eq!(claimed.assignee.as_deref(), Some("alice"));
+    assert!(queue.claim(&first.id, "bob").is_err());
+    assert!(queue.claim(&first.id, "alice").is_ok());
+    assert_eq!(queue.items_in_state(WorkItemState::Claimed), [claimed]);
+    assert_eq!(tag.data().as_str(), "[claimed by alice] second by address");
+
+    let released = queue.release(&first.id).unwrap();
+    assert_eq!(released.state, WorkItemState::Open);
+    assert_eq!(released.assignee, None);
+
+    queue.claim(&second.id, "bob").unwrap();
+    let resolved = queue.resolve(&second.id).unwrap();
+    assert_eq!(resolved.state, WorkItemState::Resolved);
+    assert_eq!(resolved.assignee.as_deref(), Some("bob"));
+    assert!(queue.claim(&second.id, "alice").is_err());
+    assert_eq!(queue.reopen(&second.id).unwrap().state, WorkItemState::Open);
+    assert_eq!(
+        queue.set_note(&second.id, "updated").unwrap().note,
+        "updated"
+    );
+
+    assert!(queue.remove(&first.id));
+    assert!(!queue.remove(&first.id));
+    assert!(queue.item(&first.id).is_none());
+    assert!(queue.claim(&first.id, "alice").is_err());
+    assert_eq!(queue.items().len(), 1);
+
+    // A second queue of the same view sees the same items
+    assert_eq!(WorkQueue::new(&view).items().len(), 1);
+}
@@ -1,6 +1,6 @@
 use binaryninja::headless::Session;
 use binaryninja::settings::Settings;
-use binaryninja::workflow::Workflow;
+use binaryninja::workflow::{Activity, ActivityConfig, Workflow};
 use rstest::*;
 
 #[fixture]
@@ -10,7 +10,6 @@ fn session() -> Session {
 }
 
 // TODO: Test running a workflow activity
-// TODO: Test activity insertion and removal
 
 #[rstest]
 fn test_workflow_clone(_session: &Session) {
@@ -91,3 +90,58 @@ fn test_workflow_registration(_session: &Session) {
     );
     assert!(!base_workflow_clone.registered());
 }
+
+fn children(workflow: &Workflow, parent: &str) -> Vec<String> {
+    workflow
+        .subactivities(parent, true)
+        .iter()
+        .filter(|c| *c != parent)
+        .map(str::to_string)
+        .collect()
+}
+
+#[rstest]
+fn test_activity_insertion(_session: &Session) {
+    let workflow = Workflow::instance("core.function.baseAnalysis").clone("insertion_workflow");
+    let parent = workflow
+        .subactivities("", false)
+        .iter()
+        .map(str::to_string)
+        .find(|parent| children(&workflow, parent).len() >= 2)
+        .expect("Workflow has an activity with subactivities");
+    let original_children = children(&workflow, &parent);
+    let anchor = original_children[0].clone();
+    let last = original_children.last().unwrap().clone();
+
+    let before = Activity::new(ActivityConfig::new("test.insertion.before"));
+    let after = Activity::new_with_action(
+        ActivityConfig::new("test.insertion.after").auto(false),
+        |_| {},
+    );
+    let end = Activity::new_with_eligibility(
+        ActivityConfig::new("test.insertion.end").title("End"),
+        |_| {},
+        |_, _| false,
+    );
+    workflow.register_activity(&before).unwrap();
+    workflow.register_activity(&after).unwrap();
+    workflow.register_activity(&end).unwrap();
+
+    assert!(workflow.insert(anchor.as_str(), ["test.insertion.before"]));
+    assert!(workflow.insert_after(anchor.as_str(), ["test.insertion.after"]));
+    assert!(workflow.insert_after(last.as_str(), ["test.insertion.end"]));
+    assert!(!workflow.insert_after("test.insertion.missing", ["test.insertion.end"]));
+
+    let new_children = children(&workflow, &parent);
+    assert_eq!(new_children.len(), original_children.len() + 3);
+    let position = |name: &str| new_children.iter().position(|c| c == name).unwrap();
+    assert_eq!(position("test.insertion.before") + 1, position(&anchor));
+    assert_eq!(position(&anchor) + 1, position("test.insertion.after"));
+    assert_eq!(new_children.last().unwrap(), "test.insertion.end");
+
+    assert!(workflow.remove("test.insertion.after"));
+    assert_eq!(
+        children(&workflow, &parent).len(),
+        original_children.len() + 2
+    );
+}
@@ -9,9 +9,10 @@ use binaryninja::platform::Platform;
 use binaryninja::rc::Ref;
 use binaryninja::segment::SegmentBuilder;
 use binaryninja::settings::{QueryOptions, Settings};
+use binaryninja::Error;
 use ihex::Record;
 
-fn parse_ihex(string: &str) -> Result<(Vec<u8>, IHexViewData)> {
+fn parse_ihex(string: &str) -> std::result::Result<(Vec<u8>, IHexViewData), ()> {
     let mut reader = ihex::Reader::new(&string);
     let mut unmerged_data: Vec<UnmergedSegment> = vec![];
     let mut start = None;
@@ -106,13 +107,15 @@ impl CustomBinaryViewType for IHexViewConstructor {
         let bytes_read = parent.read(&mut buf, 0);
         if bytes_read != bytes {
             log::error!("IHex file is too small");
-            return Err(());
+            return Err(Error::Io(std::io::ErrorKind::UnexpectedEof.into()));
         }
-        let string = String::from_utf8(buf).map_err(|_| {
+        let string = String::from_utf8(buf).map_err(|e| {
             log::error!("File contains invalid UTF8 characters");
+            e
         })?;
 
-        let (data, segments) = parse_ihex(&string)?;
+        let (data, segments) =
+            parse_ihex(&string).map_err(|_| Error::Parse("Intel HEX file".to_string()))?;
 
         let parent_bin = BinaryView::from_data(&parent.file(), &data)?;
         builder.create::<IHexView>(&parent_bin, segments)
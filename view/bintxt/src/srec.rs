@@ -5,6 +5,7 @@ use binaryninja::custom_binary_view::{
 };
 use binaryninja::rc::Ref;
 use binaryninja::segment::SegmentBuilder;
+use binaryninja::Error;
 use srec::Record;
 
 use crate::{
@@ -211,18 +212,20 @@ impl CustomBinaryViewType for SRecViewConstructor {
         &self,
         parent: &BinaryView,
         builder: CustomViewBuilder<'builder, Self>,
-    ) -> Result<CustomView<'builder>, ()> {
+    ) -> Result<CustomView<'builder>, Error> {
         let bytes = parent.len() as usize;
         let mut buf = vec![0; bytes];
         let bytes_read = parent.read(&mut buf, 0);
         if bytes_read != bytes {
             log::error!("IHex file is too small");
-            return Err(());
+            return Err(Error::Io(std::io::ErrorKind::UnexpectedEof.into()));
         }
-        let string = String::from_utf8(buf).map_err(|_| {
+        let string = String::from_utf8(buf).map_err(|e| {
             log::error!("File contains invalid UTF8 characters");
+            e
         })?;
-        let (data, regs) = parse_srec(&string)?;
+        let (data, regs) =
+            parse_srec(&string).map_err(|_| Error::Parse("S-record file".to_string()))?;
 
         let parent_bin = BinaryView::from_data(&parent.file(), &data)?;
         builder.create::<SRecView>(&parent_bin, regs)
@@ -280,7 +283,7 @@ impl AsRef<BinaryView> for SRecView {
 unsafe impl CustomBinaryView for SRecView {
     type Args = SRecViewData;
 
-    fn new(handle: &BinaryView, _args: &Self::Args) -> Result<Self, ()> {
+    fn new(handle: &BinaryView, _args: &Self::Args) -> Result<Self, Error> {
         Ok(Self {
             core: handle.to_owned(),
             // NOTE dummy values, final values are added on init
@@ -289,7 +292,7 @@ unsafe impl CustomBinaryView for SRecView {
         })
     }
 
-    fn init(&mut self, SRecViewData { start, segments }: Self::Args) -> Result<(), ()> {
+    fn init(&mut self, SRecViewData { start, segments }: Self::Args) -> Result<(), Error> {
         self.start = start;
         self.segments = segments;
 
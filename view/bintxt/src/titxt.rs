@@ -5,6 +5,7 @@ use binaryninja::custom_binary_view::{
 };
 use binaryninja::rc::Ref;
 use binaryninja::segment::SegmentBuilder;
+use binaryninja::Error;
 
 use crate::{
     segment_after_address, segment_from_address, sort_and_merge_segments, MergedSegment,
@@ -171,15 +172,15 @@ impl CustomBinaryViewType for TiTxtViewConstructor {
         &self,
         parent: &BinaryView,
         builder: CustomViewBuilder<'builder, Self>,
-    ) -> Result<CustomView<'builder>, ()> {
+    ) -> Result<CustomView<'builder>, Error> {
         let bytes = parent.len() as usize;
         let mut buf = vec![0; bytes];
         let bytes_read = parent.read(&mut buf, 0);
         if bytes_read != bytes {
             log::error!("IHex file is too small");
-            return Err(());
+            return Err(Error::Io(std::io::ErrorKind::UnexpectedEof.into()));
         }
-        let sectors = parse_ti_txt(&buf)?;
+        let sectors = parse_ti_txt(&buf).map_err(|_| Error::Parse("TI-TXT file".to_string()))?;
 
         let parent_bin = BinaryView::from_data(&parent.file(), &sectors.data)?;
         builder.create::<TiTxtView>(&parent_bin, sectors.segments)
@@ -219,7 +220,7 @@ impl AsRef<BinaryView> for TiTxtView {
 unsafe impl CustomBinaryView for TiTxtView {
     type Args = Vec<MergedSegment>;
 
-    fn new(handle: &BinaryView, _args: &Self::Args) -> Result<Self, ()> {
+    fn new(handle: &BinaryView, _args: &Self::Args) -> Result<Self, Error> {
         Ok(Self {
             core: handle.to_owned(),
             // NOTE dummy value, final values are added on init
@@ -227,7 +228,7 @@ unsafe impl CustomBinaryView for TiTxtView {
         })
     }
 
-    fn init(&mut self, segments: Self::Args) -> Result<(), ()> {
+    fn init(&mut self, segments: Self::Args) -> Result<(), Error> {
         self.segments = segments;
 
         for segment in self.segments.iter() {
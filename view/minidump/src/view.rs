@@ -16,7 +16,7 @@ use binaryninja::custom_binary_view::{
     CustomViewBuilder,
 };
 use binaryninja::platform::Platform;
-use binaryninja::Endianness;
+use binaryninja::{Endianness, Error};
 
 type BinaryViewResult<R> = binaryninja::binary_view::Result<R>;
 
@@ -116,7 +116,9 @@ impl MinidumpBinaryView {
     }
 
     fn init(&self) -> BinaryViewResult<()> {
-        let parent_view = self.parent_view().ok_or(())?;
+        let parent_view = self
+            .parent_view()
+            .ok_or_else(|| Error::NotFound("parent view".to_string()))?;
         let read_buffer = parent_view.read_buffer(0, parent_view.len() as usize)?;
 
         if let Ok(minidump_obj) = Minidump::read(read_buffer.get_data()) {
@@ -135,11 +137,11 @@ impl MinidumpBinaryView {
                         minidump_obj.endian,
                         minidump_system_info.os,
                     );
-                    return Err(());
+                    return Err(Error::Parse("minidump system information".to_string()));
                 }
             } else {
                 error!("Could not parse system information from minidump: could not find a valid MinidumpSystemInfo stream");
-                return Err(());
+                return Err(Error::Parse("minidump system information".to_string()));
             }
 
             // Memory segments
@@ -286,7 +288,7 @@ impl MinidumpBinaryView {
             }
         } else {
             error!("Could not parse data as minidump");
-            return Err(());
+            return Err(Error::Parse("minidump".to_string()));
         }
         Ok(())
     }
@@ -0,0 +1,328 @@
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use binaryninja::Error;
+use serde::{Deserialize, Deserializer};
+
+/// Suffixes appended to the path of a dump when looking for its layout, in order of preference.
+pub const LAYOUT_SUFFIXES: [&str; 3] = [".layout.json", ".layout.yaml", ".layout.yml"];
+
+/// Describes how the contents of a raw dump are mapped into memory.
+///
+/// Keeping the layout in a file next to the dump makes the same mapping of a dump repeatable and
+/// shareable.
+///
+/// ```yaml
+/// platform: linux-x86_64
+/// entry_point: 0x401000
+/// regions:
+///   - name: .text
+///     base: 0x401000
+///     size: 0x1000
+///     file_offset: 0
+///     perms: r-x
+///   - name: .bss
+///     base: 0x402000
+///     size: 0x2000
+///     file_offset: 0x1000
+///     file_size: 0x800
+///     perms: rw-
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Layout {
+    /// Name of the platform of the dump, such as `linux-x86_64`. Takes precedence over `arch`.
+    #[serde(default)]
+    pub platform: Option<String>,
+    /// Name of the architecture of the dump, its standalone platform is used.
+    #[serde(default)]
+    pub arch: Option<String>,
+    #[serde(default, deserialize_with = "optional_number")]
+    pub entry_point: Option<u64>,
+    pub regions: Vec<Region>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Region {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(deserialize_with = "number")]
+    pub base: u64,
+    #[serde(deserialize_with = "number")]
+    pub size: u64,
+    #[serde(deserialize_with = "number")]
+    pub file_offset: u64,
+    /// Number of bytes of the region backed by the dump, the rest is zero filled.
+    ///
+    /// Defaults to the size of the region.
+    #[serde(default, deserialize_with = "optional_number")]
+    pub file_size: Option<u64>,
+    #[serde(default = "Permissions::all")]
+    pub perms: Permissions,
+}
+
+impl Region {
+    pub fn address_range(&self) -> Range<u64> {
+        self.base..self.base + self.size
+    }
+
+    pub fn file_range(&self) -> Range<u64> {
+        self.file_offset..self.file_offset + self.file_size.unwrap_or(self.size)
+    }
+}
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "region `{}`", name),
+            None => write!(f, "region at {:#x}", self.base),
+        }
+    }
+}
+
+/// Access permissions of a region, written as `rwx` with `-` for the missing ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Permissions {
+    pub readable: bool,
+    pub writable: bool,
+    pub executable: bool,
+}
+
+impl Permissions {
+    pub fn all() -> Self {
+        Self {
+            readable: true,
+            writable: true,
+            executable: true,
+        }
+    }
+}
+
+impl TryFrom<String> for Permissions {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let mut perms = Self {
+            readable: false,
+            writable: false,
+            executable: false,
+        };
+        for c in value.chars() {
+            match c {
+                'r' => perms.readable = true,
+                'w' => perms.writable = true,
+                'x' => perms.executable = true,
+                '-' => {}
+                _ => return Err(format!("invalid permissions `{}`", value)),
+            }
+        }
+        Ok(perms)
+    }
+}
+
+/// Numbers are either integers or strings, which allows hexadecimal values in JSON.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Number {
+    Integer(u64),
+    String(String),
+}
+
+impl TryFrom<Number> for u64 {
+    type Error = String;
+
+    fn try_from(value: Number) -> Result<Self, Self::Error> {
+        match value {
+            Number::Integer(value) => Ok(value),
+            Number::String(value) => {
+                let trimmed = value.trim().replace('_', "");
+                let parsed = match trimmed
+                    .strip_prefix("0x")
+                    .or_else(|| trimmed.strip_prefix("0X"))
+                {
+                    Some(hex) => u64::from_str_radix(hex, 16),
+                    None => trimmed.parse(),
+                };
+                parsed.map_err(|_| format!("invalid number `{}`", value))
+            }
+        }
+    }
+}
+
+fn number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    Number::deserialize(deserializer)?
+        .try_into()
+        .map_err(serde::de::Error::custom)
+}
+
+fn optional_number<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u64>, D::Error> {
+    Option::<Number>::deserialize(deserializer)?
+        .map(u64::try_from)
+        .transpose()
+        .map_err(serde::de::Error::custom)
+}
+
+impl Layout {
+    /// The layout file of the dump at `dump_path`, if there is one.
+    pub fn find_for(dump_path: &Path) -> Option<PathBuf> {
+        LAYOUT_SUFFIXES
+            .iter()
+            .map(|suffix| {
+                let mut path = dump_path.as_os_str().to_owned();
+                path.push(suffix);
+                PathBuf::from(path)
+            })
+            .find(|path| path.is_file())
+    }
+
+    /// Read the layout at `path`, YAML unless the extension is `.json`.
+    pub fn from_path(path: &Path) -> Result<Self, Error> {
+        let text = std::fs::read_to_string(path)?;
+        let is_json = path.extension().is_some_and(|ext| ext == "json");
+        let layout = if is_json {
+            Self::from_json(&text)
+        } else {
+            Self::from_yaml(&text)
+        };
+        layout.map_err(|err| Error::Parse(format!("{}: {}", path.display(), err)))
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, Error> {
+        serde_json::from_str(text).map_err(|err| Error::Parse(format!("layout: {}", err)))
+    }
+
+    pub fn from_yaml(text: &str) -> Result<Self, Error> {
+        serde_yaml::from_str(text).map_err(|err| Error::Parse(format!("layout: {}", err)))
+    }
+
+    /// Check that the regions fit a dump of `dump_len` bytes and do not overlap.
+    pub fn validate(&self, dump_len: u64) -> Result<(), Error> {
+        let invalid = |message: String| Err(Error::InvalidArgument(message));
+        if self.regions.is_empty() {
+            return invalid("layout has no regions".to_string());
+        }
+
+        for region in &self.regions {
+            if region.size == 0 {
+                return invalid(format!("{} is empty", region));
+            }
+            if region.base.checked_add(region.size).is_none() {
+                return invalid(format!("{} wraps around the address space", region));
+            }
+            let file_size = region.file_size.unwrap_or(region.size);
+            if file_size > region.size {
+                return invalid(format!("{} is backed by more bytes than its size", region));
+            }
+            match region.file_offset.checked_add(file_size) {
+                Some(end) if end <= dump_len => {}
+                _ => return invalid(format!("{} extends past the end of the dump", region)),
+            }
+        }
+
+        let mut regions: Vec<&Region> = self.regions.iter().collect();
+        regions.sort_unstable_by_key(|region| region.base);
+        for pair in regions.windows(2) {
+            if pair[1].base < pair[0].address_range().end {
+                return invalid(format!("{} overlaps {}", pair[1], pair[0]));
+            }
+        }
+
+        if let Some(entry_point) = self.entry_point {
+            if !regions
+                .iter()
+                .any(|region| region.address_range().contains(&entry_point))
+            {
+                return invalid(format!(
+                    "entry point {:#x} is not in any region",
+                    entry_point
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const YAML: &str = r#"
+platform: linux-x86_64
+entry_point: 0x401000
+regions:
+  - name: .text
+    base: 0x401000
+    size: 0x1000
+    file_offset: 0
+    perms: r-x
+  - name: .bss
+    base: 0x402000
+    size: 0x2000
+    file_offset: 0x1000
+    file_size: 0x800
+    perms: rw-
+"#;
+
+    const JSON: &str = r#"{
+        "arch": "armv7",
+        "regions": [
+            { "base": "0x8000_0000", "size": 256, "file_offset": "0x10" }
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse_yaml() {
+        let layout = Layout::from_yaml(YAML).unwrap();
+        assert_eq!(layout.platform.as_deref(), Some("linux-x86_64"));
+        assert_eq!(layout.entry_point, Some(0x401000));
+        assert_eq!(layout.regions.len(), 2);
+        assert_eq!(layout.regions[0].address_range(), 0x401000..0x402000);
+        assert_eq!(layout.regions[0].file_range(), 0..0x1000);
+        assert!(!layout.regions[0].perms.writable);
+        assert!(layout.regions[0].perms.executable);
+        assert_eq!(layout.regions[1].file_range(), 0x1000..0x1800);
+        layout.validate(0x1800).unwrap();
+        assert!(layout.validate(0x17ff).is_err());
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let layout = Layout::from_json(JSON).unwrap();
+        assert_eq!(layout.arch.as_deref(), Some("armv7"));
+        assert_eq!(layout.entry_point, None);
+        let region = &layout.regions[0];
+        assert_eq!(region.name, None);
+        assert_eq!(region.address_range(), 0x8000_0000..0x8000_0100);
+        assert_eq!(region.file_range(), 0x10..0x110);
+        assert_eq!(region.perms, Permissions::all());
+    }
+
+    #[test]
+    fn test_invalid_layouts() {
+        assert!(Layout::from_json(r#"{ "regions": [], "base": 0 }"#).is_err());
+        assert!(Layout::from_yaml(
+            "regions:\n  - { base: 0, size: 1, file_offset: 0, perms: rwz }"
+        )
+        .is_err());
+        assert!(
+            Layout::from_yaml("regions:\n  - { base: 0x1g, size: 1, file_offset: 0 }").is_err()
+        );
+
+        let overlapping = Layout::from_yaml(
+            "regions:\n  - { base: 0x1000, size: 0x100, file_offset: 0 }\n  - { base: 0x10ff, size: 1, file_offset: 0 }",
+        )
+        .unwrap();
+        assert!(overlapping.validate(0x100).is_err());
+
+        let mut entry_outside = Layout::from_json(JSON).unwrap();
+        entry_outside.entry_point = Some(0x8000_0100);
+        assert!(entry_outside.validate(0x110).is_err());
+        assert!(Layout::from_yaml("regions: []")
+            .unwrap()
+            .validate(0)
+            .is_err());
+    }
+}
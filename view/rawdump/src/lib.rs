@@ -0,0 +1,18 @@
+//! Loads raw memory dumps described by a layout file placed next to them.
+
+use binaryninja::custom_binary_view::register_view_type;
+use binaryninja::logger::Logger;
+use log::LevelFilter;
+
+pub mod layout;
+mod view;
+
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn CorePluginInit() -> bool {
+    Logger::new("RawDump").with_level(LevelFilter::Info).init();
+
+    register_view_type(c"RawDump", c"Raw Memory Dump", view::RawDumpViewType::new);
+
+    true
+}
@@ -0,0 +1,183 @@
+use std::path::{Path, PathBuf};
+
+use binaryninja::architecture::{Architecture, ArchitectureExt, CoreArchitecture};
+use binaryninja::binary_view::{BinaryView, BinaryViewBase, BinaryViewExt};
+use binaryninja::custom_binary_view::{
+    BinaryViewType, BinaryViewTypeBase, CustomBinaryView, CustomBinaryViewType, CustomView,
+    CustomViewBuilder,
+};
+use binaryninja::platform::Platform;
+use binaryninja::rc::Ref;
+use binaryninja::section::{Section, Semantics};
+use binaryninja::segment::Segment;
+use binaryninja::{Endianness, Error};
+use log::{debug, error, info};
+
+use crate::layout::{Layout, Region};
+
+/// The path of the layout of the file `data` was opened from, if it has one.
+fn layout_path(data: &BinaryView) -> Option<PathBuf> {
+    let filename = data.file().filename();
+    Layout::find_for(Path::new(&*filename.to_string_lossy()))
+}
+
+/// Read the layout of `data` and check it against the size of the dump.
+fn load_layout(data: &BinaryView) -> Result<Layout, Error> {
+    let path = layout_path(data).ok_or_else(|| Error::NotFound("dump layout".to_string()))?;
+    let layout = Layout::from_path(&path)?;
+    layout.validate(data.len())?;
+    Ok(layout)
+}
+
+fn resolve_platform(layout: &Layout) -> Result<Ref<Platform>, Error> {
+    if let Some(name) = &layout.platform {
+        return Platform::by_name(name.as_str())
+            .ok_or_else(|| Error::NotFound(format!("platform `{}`", name)));
+    }
+    let name = layout
+        .arch
+        .as_ref()
+        .ok_or_else(|| Error::InvalidArgument("layout names no platform or arch".to_string()))?;
+    CoreArchitecture::by_name(name)
+        .and_then(|arch| arch.standalone_platform())
+        .ok_or_else(|| Error::NotFound(format!("architecture `{}`", name)))
+}
+
+/// The _Raw Dump_ view type, valid for any file with a layout next to it, such as
+/// `memory.bin.layout.json` for `memory.bin`.
+pub struct RawDumpViewType {
+    view_type: BinaryViewType,
+}
+
+impl RawDumpViewType {
+    pub fn new(view_type: BinaryViewType) -> Self {
+        Self { view_type }
+    }
+}
+
+impl AsRef<BinaryViewType> for RawDumpViewType {
+    fn as_ref(&self) -> &BinaryViewType {
+        &self.view_type
+    }
+}
+
+impl BinaryViewTypeBase for RawDumpViewType {
+    fn is_valid_for(&self, data: &BinaryView) -> bool {
+        if layout_path(data).is_none() {
+            return false;
+        }
+        match load_layout(data) {
+            Ok(_) => true,
+            Err(err) => {
+                error!("Ignoring the dump layout: {}", err);
+                false
+            }
+        }
+    }
+
+    fn is_deprecated(&self) -> bool {
+        false
+    }
+}
+
+impl CustomBinaryViewType for RawDumpViewType {
+    fn create_custom_view<'builder>(
+        &self,
+        data: &BinaryView,
+        builder: CustomViewBuilder<'builder, Self>,
+    ) -> Result<CustomView<'builder>, Error> {
+        let layout = load_layout(data)?;
+        debug!(
+            "Creating raw dump view with {} regions",
+            layout.regions.len()
+        );
+        builder.create::<RawDumpView>(data, layout)
+    }
+}
+
+pub struct RawDumpView {
+    core: Ref<BinaryView>,
+    platform: Ref<Platform>,
+    entry_point: u64,
+}
+
+impl RawDumpView {
+    fn add_region(&self, region: &Region) {
+        info!(
+            "Adding {} from {:#x} to {:#x}, backed by {:#x} to {:#x}",
+            region,
+            region.address_range().start,
+            region.address_range().end,
+            region.file_range().start,
+            region.file_range().end,
+        );
+        self.add_segment(
+            Segment::builder(region.address_range())
+                .parent_backing(region.file_range())
+                .is_auto(true)
+                .readable(region.perms.readable)
+                .writable(region.perms.writable)
+                .executable(region.perms.executable)
+                .contains_code(region.perms.executable)
+                .contains_data(!region.perms.executable),
+        );
+
+        if let Some(name) = &region.name {
+            let semantics = match (region.perms.executable, region.perms.writable) {
+                (true, _) => Semantics::ReadOnlyCode,
+                (false, true) => Semantics::ReadWriteData,
+                (false, false) => Semantics::ReadOnlyData,
+            };
+            self.add_section(
+                Section::builder(name.as_str(), region.address_range())
+                    .semantics(semantics)
+                    .is_auto(true),
+            );
+        }
+    }
+}
+
+impl AsRef<BinaryView> for RawDumpView {
+    fn as_ref(&self) -> &BinaryView {
+        &self.core
+    }
+}
+
+impl BinaryViewBase for RawDumpView {
+    fn address_size(&self) -> usize {
+        self.platform.arch().address_size()
+    }
+
+    fn default_endianness(&self) -> Endianness {
+        self.platform.arch().endianness()
+    }
+
+    fn entry_point(&self) -> u64 {
+        self.entry_point
+    }
+}
+
+unsafe impl CustomBinaryView for RawDumpView {
+    type Args = Layout;
+
+    fn new(handle: &BinaryView, layout: &Self::Args) -> Result<Self, Error> {
+        Ok(Self {
+            core: handle.to_owned(),
+            platform: resolve_platform(layout)?,
+            entry_point: layout
+                .entry_point
+                .unwrap_or_else(|| layout.regions.iter().map(|r| r.base).min().unwrap_or(0)),
+        })
+    }
+
+    fn init(&mut self, layout: Self::Args) -> Result<(), Error> {
+        self.set_default_platform(&self.platform);
+        for region in &layout.regions {
+            self.add_region(region);
+        }
+        if layout.entry_point.is_some() {
+            self.add_entry_point(&self.platform, self.entry_point);
+        }
+        Ok(())
+    }
+}